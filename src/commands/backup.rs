@@ -0,0 +1,244 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+const JOURNAL_FILE: &str = "writes.json";
+
+/// Writes remembered for `ftool undo`; the backups of older ones are deleted
+const MAX_WRITES: usize = 20;
+
+/// Held while the journal is read and rewritten, so writes finishing on
+/// different threads don't drop each other's entries
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// A file or directory ftool wrote, and where the one it replaced was moved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteRecord {
+    pub path: PathBuf,
+    /// What was at `path` before; `None` when the write created it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<PathBuf>,
+    /// Seconds since the epoch
+    pub written: i64,
+}
+
+impl WriteRecord {
+    /// `YYYY-MM-DD HH:MM:SS  created|overwrote <path>`, in local time
+    pub fn describe(&self) -> String {
+        let when = DateTime::<Utc>::from_timestamp(self.written, 0)
+            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let what = if self.backup.is_some() { "overwrote" } else { "created" };
+        format!("{}  {} {}", when, what, self.path.display())
+    }
+}
+
+/// A write about to happen. The data goes to a hidden temp file next to the
+/// target (`temp`), which `finish` renames into place, so a killed ftool never
+/// leaves a half-written file behind; dropping it unfinished deletes the temp file.
+#[derive(Debug)]
+pub struct PendingWrite {
+    path: PathBuf,
+    temp: PathBuf,
+    /// Back up the previous file and record the write
+    tracked: bool,
+    finished: bool,
+}
+
+/// Starts a write of `path`: write to `temp()`, then call `finish`
+pub fn prepare(path: &Path) -> PendingWrite {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let temp = temp_path(&path);
+    PendingWrite { path, temp, tracked: true, finished: false }
+}
+
+/// Like `prepare`, without a backup or a journal entry: for outputs written by
+/// the hundred (split parts), which would push every other write out of the journal
+pub fn prepare_untracked(path: &Path) -> PendingWrite {
+    let mut pending = prepare(path);
+    pending.tracked = false;
+    pending
+}
+
+/// `.<stem>.<pid>-<n>.ftool-tmp.<ext>` next to `path`: hidden, on the same disk
+/// so the final rename is atomic, unique per write, and with the extension kept
+/// for writers that go by it
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}-{}.ftool-tmp{}", stem, std::process::id(), n, ext))
+}
+
+impl PendingWrite {
+    /// Where the data is written before `finish`
+    pub fn temp(&self) -> &Path {
+        &self.temp
+    }
+
+    /// Moves the temp file into place and records the write for `undo_last`.
+    /// An existing file is kept as a hidden `.<name>.<millis>.ftool-bak` next to
+    /// it: a hard link, so the rename replaces the file in one step, or for a
+    /// directory (or a filesystem without hard links) a rename just before.
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        // Untracked, an existing directory (e.g. partitions) is still moved
+        // aside, as a rename can't replace it, and deleted once replaced
+        let untracked_dir = !self.tracked && self.path.is_dir();
+        let backup = if self.tracked || untracked_dir { self.back_up()? } else { None };
+        if let Err(e) = std::fs::rename(&self.temp, &self.path) {
+            if let Some(backup) = &backup {
+                let restored = if self.path.symlink_metadata().is_ok() {
+                    remove_path(backup)
+                } else {
+                    std::fs::rename(backup, &self.path)
+                };
+                if let Err(e) = restored {
+                    tracing::warn!(file = %self.path.display(), backup = %backup.display(), error = %e, "could not restore backup");
+                }
+            }
+            return Err(anyhow::anyhow!("Failed to write {}: {}", self.path.display(), e));
+        }
+        self.finished = true;
+        if !self.tracked {
+            if let Some(old) = backup
+                && let Err(e) = remove_path(&old)
+            {
+                tracing::warn!(file = %old.display(), error = %e, "could not delete replaced directory");
+            }
+            return Ok(());
+        }
+        record(WriteRecord { path: self.path.clone(), backup, written: Utc::now().timestamp() });
+        Ok(())
+    }
+
+    fn back_up(&self) -> anyhow::Result<Option<PathBuf>> {
+        let Ok(metadata) = self.path.symlink_metadata() else { return Ok(None) };
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let backup = self.path.with_file_name(format!(".{}.{}.ftool-bak", name, Utc::now().timestamp_millis()));
+        if metadata.is_file() && std::fs::hard_link(&self.path, &backup).is_ok() {
+            return Ok(Some(backup));
+        }
+        std::fs::rename(&self.path, &backup)
+            .with_context(|| format!("Failed to back up {} before overwriting it", self.path.display()))?;
+        Ok(Some(backup))
+    }
+}
+
+impl Drop for PendingWrite {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Err(e) = remove_path(&self.temp) {
+            tracing::warn!(file = %self.temp.display(), error = %e, "could not delete temp file");
+        }
+    }
+}
+
+/// Writes a data file through a temp file, recording it for `undo_last`
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let pending = prepare(path);
+    let mut file = std::fs::File::create(pending.temp())
+        .with_context(|| format!("Failed to create {}", pending.temp().display()))?;
+    file.write_all(contents.as_ref())?;
+    file.sync_all()?;
+    drop(file);
+    pending.finish()
+}
+
+/// Writes one of ftool's own files (config, catalog, notes...) through a temp
+/// file and a rename, without a backup
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let temp = temp_path(path);
+    let result = std::fs::write(&temp, contents).and_then(|_| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Adds a write to the journal, deleting the backups that fall off its end.
+/// A journal that can't be saved only costs the undo, so it's logged.
+fn record(record: WriteRecord) {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut journal = load();
+    let path = record.path.clone();
+    journal.push(record);
+    let excess = journal.len().saturating_sub(MAX_WRITES);
+    for old in journal.drain(..excess) {
+        if let Some(backup) = old.backup
+            && let Err(e) = remove_path(&backup)
+        {
+            tracing::warn!(backup = %backup.display(), error = %e, "could not delete old backup");
+        }
+    }
+    if let Err(e) = save(&journal) {
+        tracing::warn!(file = %path.display(), error = %e, "could not record write");
+    }
+}
+
+fn journal_path() -> Option<PathBuf> {
+    config::data_dir().map(|d| d.join(JOURNAL_FILE))
+}
+
+fn load() -> Vec<WriteRecord> {
+    journal_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(journal: &[WriteRecord]) -> anyhow::Result<()> {
+    let path = journal_path().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_atomic(&path, serde_json::to_string_pretty(journal)?)?;
+    Ok(())
+}
+
+/// Deletes a file or a whole directory; a missing path is fine
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    match path.symlink_metadata() {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+        Ok(metadata) if metadata.is_dir() => std::fs::remove_dir_all(path),
+        Ok(_) => std::fs::remove_file(path),
+    }
+}
+
+/// Recorded writes, oldest first
+pub fn history() -> Vec<WriteRecord> {
+    load()
+}
+
+/// Reverts the most recent write: the file it replaced is moved back, or the
+/// file it created is deleted. `None` when there is nothing to undo.
+pub fn undo_last() -> anyhow::Result<Option<WriteRecord>> {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut journal = load();
+    let Some(record) = journal.pop() else { return Ok(None) };
+    if let Some(backup) = &record.backup
+        && backup.symlink_metadata().is_err()
+    {
+        // Nothing left to restore: dropped, so the writes before it can be undone
+        save(&journal)?;
+        anyhow::bail!("The backup of {} is gone ({}); nothing was restored", record.path.display(), backup.display());
+    }
+    // The journal keeps the write until it's undone, so a failed restore can be retried
+    remove_path(&record.path).with_context(|| format!("Failed to remove {}", record.path.display()))?;
+    if let Some(backup) = &record.backup {
+        std::fs::rename(backup, &record.path)
+            .with_context(|| format!("Failed to restore {} from {}", record.path.display(), backup.display()))?;
+    }
+    save(&journal)?;
+    Ok(Some(record))
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::backup::write_atomic;
+use super::duckdb_inspector::DuckDbInspector;
+use super::find_column::data_files;
+use super::table_format::to_markdown;
+use super::units::format_size;
+use crate::config;
+
+const CATALOG_FILE: &str = "catalog.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+}
+
+/// One indexed data file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// Absolute path
+    pub path: PathBuf,
+    pub size: u64,
+    /// Last modification, in seconds since the epoch; with `size`, tells
+    /// whether the file changed since it was indexed
+    pub modified: i64,
+    pub rows: Option<usize>,
+    pub columns: Vec<CatalogColumn>,
+    /// Why the schema or row count couldn't be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CatalogEntry {
+    fn scan(path: &Path, size: u64, modified: i64) -> Self {
+        let mut entry = Self { path: path.to_path_buf(), size, modified, rows: None, columns: Vec::new(), error: None };
+        let read = DuckDbInspector::new(path.to_string_lossy().to_string()).and_then(|inspector| {
+            let schema = inspector.schema()?;
+            Ok((schema, inspector.row_count()?))
+        });
+        match read {
+            Ok((schema, rows)) => {
+                entry.columns = schema.into_iter().map(|(name, data_type)| CatalogColumn { name, data_type }).collect();
+                entry.rows = Some(rows);
+            }
+            Err(e) => {
+                tracing::warn!(file = %path.display(), error = %e, "could not index file");
+                entry.error = Some(e.to_string());
+            }
+        }
+        entry
+    }
+
+    /// Columns whose name contains `term` (lowercased)
+    pub fn matching_columns(&self, term: &str) -> Vec<&CatalogColumn> {
+        self.columns.iter().filter(|c| c.name.to_lowercase().contains(term)).collect()
+    }
+
+    /// Whether the path or a column name contains `term`, ignoring case
+    pub fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        self.path.to_string_lossy().to_lowercase().contains(&term) || !self.matching_columns(&term).is_empty()
+    }
+
+    /// Local modification time, `YYYY-MM-DD HH:MM`
+    pub fn modified_label(&self) -> String {
+        DateTime::<Utc>::from_timestamp(self.modified, 0)
+            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// What a refresh changed
+#[derive(Debug, Clone, Default)]
+pub struct RefreshStats {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    /// Files indexed without a schema because they couldn't be read
+    pub failed: usize,
+}
+
+impl RefreshStats {
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "{} added, {} updated, {} unchanged, {} removed",
+            self.added, self.updated, self.unchanged, self.removed
+        );
+        if self.failed > 0 {
+            text.push_str(&format!(" ({} could not be read)", self.failed));
+        }
+        text
+    }
+}
+
+/// Data files indexed by `ftool index`, kept in `<data dir>/ftool/catalog.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Catalog {
+    /// Directories indexed, refreshed together from the TUI
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+    #[serde(default)]
+    pub entries: Vec<CatalogEntry>,
+}
+
+impl Catalog {
+    pub fn path() -> Option<PathBuf> {
+        config::data_dir().map(|d| d.join(CATALOG_FILE))
+    }
+
+    /// The saved catalog, or an empty one before the first `ftool index`
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content).map_err(|e| anyhow::anyhow!("Invalid catalog {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_atomic(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Indexes the data files under `dir`. Files whose size and modification
+    /// time match their entry are kept as they are unless `full` is set;
+    /// entries of files no longer there are dropped.
+    pub fn refresh(&mut self, dir: &Path, full: bool) -> anyhow::Result<RefreshStats> {
+        let dir = std::fs::canonicalize(dir).map_err(|e| anyhow::anyhow!("{}: {}", dir.display(), e))?;
+        let paths = data_files(&dir)?;
+        let mut stats = RefreshStats::default();
+
+        let (under_dir, others): (Vec<CatalogEntry>, Vec<CatalogEntry>) =
+            std::mem::take(&mut self.entries).into_iter().partition(|e| e.path.starts_with(&dir));
+        let mut previous: HashMap<PathBuf, CatalogEntry> = under_dir.into_iter().map(|e| (e.path.clone(), e)).collect();
+        let mut seen = HashSet::new();
+        self.entries = others;
+
+        for path in paths.iter().map(PathBuf::from) {
+            let Ok(metadata) = std::fs::metadata(&path) else { continue };
+            let modified = metadata.modified().map(|t| DateTime::<Utc>::from(t).timestamp()).unwrap_or(0);
+            seen.insert(path.clone());
+            let entry = match previous.remove(&path) {
+                Some(old) if !full && old.size == metadata.len() && old.modified == modified => {
+                    stats.unchanged += 1;
+                    old
+                }
+                old => {
+                    if old.is_some() {
+                        stats.updated += 1;
+                    } else {
+                        stats.added += 1;
+                    }
+                    CatalogEntry::scan(&path, metadata.len(), modified)
+                }
+            };
+            if entry.error.is_some() {
+                stats.failed += 1;
+            }
+            self.entries.push(entry);
+        }
+        stats.removed = previous.len();
+
+        if !self.roots.iter().any(|root| dir.starts_with(root)) {
+            self.roots.retain(|root| !root.starts_with(&dir));
+            self.roots.push(dir);
+        }
+        self.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(stats)
+    }
+
+    /// Refreshes every indexed directory, dropping the ones that are gone
+    pub fn refresh_all(&mut self) -> anyhow::Result<RefreshStats> {
+        let mut total = RefreshStats::default();
+        for root in self.roots.clone() {
+            if !root.is_dir() {
+                self.entries.retain(|e| !e.path.starts_with(&root));
+                self.roots.retain(|r| r != &root);
+                continue;
+            }
+            let stats = self.refresh(&root, false)?;
+            total.added += stats.added;
+            total.updated += stats.updated;
+            total.unchanged += stats.unchanged;
+            total.removed += stats.removed;
+            total.failed += stats.failed;
+        }
+        Ok(total)
+    }
+
+    /// Entries whose path or a column name contains `term`, ignoring case;
+    /// every entry for an empty term
+    pub fn search(&self, term: &str) -> Vec<&CatalogEntry> {
+        self.entries.iter().filter(|e| e.matches(term)).collect()
+    }
+}
+
+/// Search results as a table, with the matching columns and their types
+pub fn render_search(entries: &[&CatalogEntry], term: &str) -> String {
+    let term = term.to_lowercase();
+    let headers: Vec<String> =
+        ["Path", "Rows", "Size", "Modified", "Matching columns"].iter().map(|h| h.to_string()).collect();
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|e| {
+            let columns = match &e.error {
+                Some(error) => format!("error: {}", error),
+                None => e
+                    .matching_columns(&term)
+                    .iter()
+                    .map(|c| format!("{} ({})", c.name, c.data_type))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            vec![
+                e.path.display().to_string(),
+                e.rows.map_or("-".to_string(), |n| n.to_string()),
+                format_size(e.size),
+                e.modified_label(),
+                columns,
+            ]
+        })
+        .collect();
+    let mut out = if rows.is_empty() { String::new() } else { to_markdown(&headers, &rows) };
+    out.push_str(&format!("\n{} matching files\n", entries.len()));
+    out
+}
@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use ignore::WalkBuilder;
+
+use super::duckdb_inspector::{DuckDbError, DuckDbInspector};
+use super::table_format::to_markdown;
+use super::transform::Transform;
+
+/// Formats `ftool convert-dir` reads and writes
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DataFormat {
+    Csv,
+    Parquet,
+}
+
+impl DataFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// What `convert_dir` converts and how
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub from: DataFormat,
+    pub to: DataFormat,
+    /// Also convert files in subdirectories
+    pub recursive: bool,
+    /// Files converted at the same time
+    pub jobs: usize,
+    /// Leave files whose output already exists alone
+    pub skip_existing: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// Written to this path
+    Converted(String),
+    /// Written to this path, but the `after_convert` hook failed with this error
+    HookFailed(String, String),
+    /// The output already existed
+    Skipped(PathBuf),
+    Failed(String),
+}
+
+/// How one file of the batch went
+#[derive(Debug, Clone)]
+pub struct FileResult {
+    pub path: PathBuf,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+impl FileResult {
+    /// `ok`, `skipped` or `FAILED`, with the output or the error
+    pub fn describe(&self) -> String {
+        match &self.outcome {
+            Outcome::Converted(target) => {
+                format!("ok {} -> {} ({} ms)", self.path.display(), target, self.elapsed.as_millis())
+            }
+            Outcome::HookFailed(target, error) => {
+                format!("ok {} -> {} ({} ms), hook failed: {}", self.path.display(), target, self.elapsed.as_millis(), error)
+            }
+            Outcome::Skipped(target) => format!("skipped {} ({} exists)", self.path.display(), target.display()),
+            Outcome::Failed(error) => format!("FAILED {}: {}", self.path.display(), error),
+        }
+    }
+}
+
+/// Every file of a batch, in path order
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    pub results: Vec<FileResult>,
+}
+
+impl BatchReport {
+    fn count(&self, f: impl Fn(&Outcome) -> bool) -> usize {
+        self.results.iter().filter(|r| f(&r.outcome)).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Failed(_)))
+    }
+
+    /// Files converted whose hook then failed
+    pub fn hook_failed(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::HookFailed(..)))
+    }
+
+    /// A table of every file and its status, then the totals
+    pub fn render(&self) -> String {
+        let headers: Vec<String> = ["File", "Status", "Output / error", "Time"].iter().map(|h| h.to_string()).collect();
+        let rows: Vec<Vec<String>> = self
+            .results
+            .iter()
+            .map(|r| {
+                let (status, detail) = match &r.outcome {
+                    Outcome::Converted(target) => ("converted", target.clone()),
+                    Outcome::HookFailed(target, error) => ("converted, hook failed", format!("{}: {}", target, error)),
+                    Outcome::Skipped(target) => ("skipped", format!("{} exists", target.display())),
+                    Outcome::Failed(error) => ("failed", error.clone()),
+                };
+                vec![
+                    r.path.display().to_string(),
+                    status.to_string(),
+                    detail,
+                    format!("{} ms", r.elapsed.as_millis()),
+                ]
+            })
+            .collect();
+        let mut out = if rows.is_empty() { String::new() } else { to_markdown(&headers, &rows) };
+        out.push_str(&format!(
+            "\n{} converted, {} converted with a failed hook, {} skipped, {} failed\n",
+            self.count(|o| matches!(o, Outcome::Converted(_))),
+            self.hook_failed(),
+            self.count(|o| matches!(o, Outcome::Skipped(_))),
+            self.failed()
+        ));
+        out
+    }
+}
+
+/// The `from` files in `dir` (and below it when `recursive`), sorted; hidden
+/// files and directories are skipped and `.gitignore` is not applied
+pub fn matching_files(dir: &Path, from: DataFormat, recursive: bool) -> anyhow::Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        anyhow::bail!("{} is not a directory", dir.display());
+    }
+    let mut paths: Vec<PathBuf> = WalkBuilder::new(dir)
+        .standard_filters(false)
+        .hidden(true)
+        .max_depth(if recursive { None } else { Some(1) })
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == from.extension()))
+        .map(|entry| entry.into_path())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Converts every matching file under `dir` to `<stem>.<to>` next to it, with
+/// up to `options.jobs` files converting at once. A failed file doesn't stop
+/// the others; `on_done(done, total, result)` is called as each one finishes.
+pub fn convert_dir(
+    dir: &Path,
+    options: &BatchOptions,
+    on_done: impl Fn(usize, usize, &FileResult) + Sync,
+) -> anyhow::Result<BatchReport> {
+    if options.from == options.to {
+        anyhow::bail!("--from and --to are both {}", options.to.extension());
+    }
+    let paths = matching_files(dir, options.from, options.recursive)?;
+    let total = paths.len();
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<FileResult>>> = Mutex::new(vec![None; total]);
+
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.clamp(1, total.max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(i) else { break };
+                    let result = convert_one(path, options);
+                    on_done(done.fetch_add(1, Ordering::Relaxed) + 1, total, &result);
+                    if let Ok(mut results) = results.lock() {
+                        results[i] = Some(result);
+                    }
+                }
+            });
+        }
+    });
+
+    let results = results.into_inner().map_err(|_| anyhow::anyhow!("A conversion worker panicked"))?;
+    Ok(BatchReport { results: results.into_iter().flatten().collect() })
+}
+
+fn convert_one(path: &Path, options: &BatchOptions) -> FileResult {
+    let started = Instant::now();
+    let target = path.with_extension(options.to.extension());
+    let outcome = if options.skip_existing && target.exists() {
+        Outcome::Skipped(target)
+    } else {
+        // Untracked: a batch would push every other write out of the undo journal
+        let converted = DuckDbInspector::new(path.to_string_lossy().to_string()).and_then(|i| {
+            match i.convert_query(options.to.extension(), &Transform::default(), &[])? {
+                Some(mut plan) => {
+                    plan.tracked = false;
+                    i.run_copy(&plan)
+                }
+                None => Ok(path.to_string_lossy().to_string()),
+            }
+        });
+        match converted {
+            Ok(written) => Outcome::Converted(written),
+            // The file is in place: a re-run with --skip-existing leaves it be
+            Err(DuckDbError::HookFailed(error)) => {
+                tracing::warn!(file = %path.display(), error = %error, "after_convert hook failed");
+                Outcome::HookFailed(target.to_string_lossy().to_string(), error)
+            }
+            Err(e) => {
+                tracing::warn!(file = %path.display(), error = %e, "batch conversion failed");
+                Outcome::Failed(e.to_string())
+            }
+        }
+    };
+    FileResult { path: path.to_path_buf(), outcome, elapsed: started.elapsed() }
+}
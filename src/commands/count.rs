@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use clap::ValueEnum;
+use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
+
+use super::table_format::to_markdown;
+use super::units::format_size;
+
+/// Order of the `ftool count` table (largest first, except by name)
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CountSort {
+    Lines,
+    Files,
+    Size,
+    Name,
+}
+
+/// Files, lines and bytes of one extension
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionCount {
+    /// Lowercased extension, or `(none)`
+    pub extension: String,
+    pub files: u64,
+    pub lines: u64,
+    /// Lines holding only whitespace
+    pub blank: u64,
+    pub bytes: u64,
+}
+
+/// Counts of the text files under a directory, by extension
+#[derive(Debug, Clone, Default)]
+pub struct CountReport {
+    pub extensions: Vec<ExtensionCount>,
+    /// Files skipped because they contain NUL bytes
+    pub binary: u64,
+    /// Files or directories that couldn't be read
+    pub unreadable: u64,
+}
+
+/// Walks `dir` and counts lines per extension. `.gitignore`, `.ignore` and
+/// hidden files are skipped unless `no_ignore` / `hidden` say otherwise;
+/// `include` globs keep only the files they match, `exclude` globs drop files.
+pub fn count(
+    dir: &Path,
+    include: &[String],
+    exclude: &[String],
+    no_ignore: bool,
+    hidden: bool,
+) -> anyhow::Result<CountReport> {
+    if !dir.is_dir() {
+        anyhow::bail!("{} is not a directory", dir.display());
+    }
+    let mut overrides = OverrideBuilder::new(dir);
+    for glob in include {
+        overrides.add(glob)?;
+    }
+    for glob in exclude {
+        overrides.add(&format!("!{}", glob))?;
+    }
+    let walker = WalkBuilder::new(dir)
+        .hidden(!hidden)
+        .ignore(!no_ignore)
+        .parents(!no_ignore)
+        .git_ignore(!no_ignore)
+        .git_global(!no_ignore)
+        .git_exclude(!no_ignore)
+        // Honour .gitignore files in plain directories too, not only in git checkouts
+        .require_git(false)
+        .overrides(overrides.build()?)
+        .build();
+
+    let mut report = CountReport::default();
+    let mut by_extension: HashMap<String, ExtensionCount> = HashMap::new();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!(error = %e, "skipping unreadable entry");
+                report.unreadable += 1;
+                continue;
+            }
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let (lines, blank) = match count_lines(path) {
+            Ok(Some(counts)) => counts,
+            Ok(None) => {
+                report.binary += 1;
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!(file = %path.display(), error = %e, "skipping unreadable file");
+                report.unreadable += 1;
+                continue;
+            }
+        };
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "(none)".to_string());
+        let counts = by_extension
+            .entry(extension.clone())
+            .or_insert_with(|| ExtensionCount { extension, ..Default::default() });
+        counts.files += 1;
+        counts.lines += lines;
+        counts.blank += blank;
+        counts.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+    report.extensions = by_extension.into_values().collect();
+    report.sort(CountSort::Lines);
+    Ok(report)
+}
+
+/// Line and blank-line counts, read a line at a time; `None` for a binary
+/// file (a NUL byte in its first block)
+fn count_lines(path: &Path) -> std::io::Result<Option<(u64, u64)>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if reader.fill_buf()?.contains(&0) {
+        return Ok(None);
+    }
+    let (mut lines, mut blank) = (0, 0);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        lines += 1;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            blank += 1;
+        }
+    }
+    Ok(Some((lines, blank)))
+}
+
+impl CountReport {
+    pub fn sort(&mut self, by: CountSort) {
+        match by {
+            CountSort::Lines => self.extensions.sort_by(|a, b| b.lines.cmp(&a.lines).then(a.extension.cmp(&b.extension))),
+            CountSort::Files => self.extensions.sort_by(|a, b| b.files.cmp(&a.files).then(a.extension.cmp(&b.extension))),
+            CountSort::Size => self.extensions.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.extension.cmp(&b.extension))),
+            CountSort::Name => self.extensions.sort_by(|a, b| a.extension.cmp(&b.extension)),
+        }
+    }
+
+    /// Markdown table with a Total row, and a note about skipped files
+    pub fn render(&self) -> String {
+        let headers: Vec<String> =
+            ["Extension", "Files", "Lines", "Blank", "Size"].iter().map(|h| h.to_string()).collect();
+        let row = |name: &str, files: u64, lines: u64, blank: u64, bytes: u64| {
+            vec![name.to_string(), files.to_string(), lines.to_string(), blank.to_string(), format_size(bytes)]
+        };
+        let mut rows: Vec<Vec<String>> =
+            self.extensions.iter().map(|e| row(&e.extension, e.files, e.lines, e.blank, e.bytes)).collect();
+        let total = |f: fn(&ExtensionCount) -> u64| self.extensions.iter().map(f).sum::<u64>();
+        rows.push(row("Total", total(|e| e.files), total(|e| e.lines), total(|e| e.blank), total(|e| e.bytes)));
+
+        let mut out = to_markdown(&headers, &rows);
+        if self.binary > 0 || self.unreadable > 0 {
+            out.push_str(&format!("\n{} binary and {} unreadable files skipped\n", self.binary, self.unreadable));
+        }
+        out
+    }
+}
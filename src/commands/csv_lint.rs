@@ -0,0 +1,391 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Issues kept with their line numbers; the rest are only counted
+pub const MAX_REPORTED_ISSUES: usize = 200;
+
+/// Delimiters tried when guessing from the header line
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+/// A structural problem found in one record of a CSV file
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintKind {
+    /// A row with a different number of fields than the header
+    RaggedRow { expected: usize, found: usize },
+    /// A quote inside an unquoted field, or text after a closing quote
+    StrayQuote,
+    /// A quoted field still open at the end of the file, or after `MAX_RECORD_BYTES`
+    UnterminatedQuote,
+    /// The line ends in a delimiter, adding an empty field
+    TrailingDelimiter,
+    /// Bytes that aren't valid UTF-8
+    InvalidUtf8,
+}
+
+impl LintKind {
+    pub fn describe(&self) -> String {
+        match self {
+            LintKind::RaggedRow { expected, found } => {
+                format!("ragged row: {} fields, header has {}", found, expected)
+            }
+            LintKind::StrayQuote => "stray quote in an unquoted field".to_string(),
+            LintKind::UnterminatedQuote => "unterminated quote: the quoted field is never closed".to_string(),
+            LintKind::TrailingDelimiter => "trailing delimiter".to_string(),
+            LintKind::InvalidUtf8 => "not valid UTF-8".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LintIssue {
+    /// 1-based line the record starts on
+    pub line: usize,
+    pub kind: LintKind,
+}
+
+/// Number of issues of each kind across the whole file
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LintCounts {
+    pub ragged: usize,
+    pub stray_quotes: usize,
+    pub unterminated: usize,
+    pub trailing_delimiters: usize,
+    pub invalid_utf8: usize,
+}
+
+impl LintCounts {
+    pub fn total(&self) -> usize {
+        self.ragged + self.stray_quotes + self.unterminated + self.trailing_delimiters + self.invalid_utf8
+    }
+}
+
+/// What `lint` found in a CSV file
+#[derive(Debug, Clone)]
+pub struct LintReport {
+    pub delimiter: u8,
+    /// Fields in the header, not counting a trailing delimiter
+    pub columns: usize,
+    pub lines: usize,
+    pub records: usize,
+    /// The first `MAX_REPORTED_ISSUES` issues, in file order
+    pub issues: Vec<LintIssue>,
+    pub counts: LintCounts,
+    /// Header columns whose values are quoted on some rows and bare on others,
+    /// with the first line where the style changed
+    pub mixed_quoting: Vec<(String, usize)>,
+    /// Lines with non-ASCII characters that are valid UTF-8; together with
+    /// invalid lines this suggests more than one encoding
+    pub utf8_lines: usize,
+}
+
+impl LintReport {
+    pub fn is_clean(&self) -> bool {
+        self.counts.total() == 0 && self.mixed_quoting.is_empty()
+    }
+
+    /// A summary followed by one line per issue; problem lines start with `!`
+    pub fn report(&self) -> Vec<String> {
+        let delimiter = match self.delimiter {
+            b'\t' => "tab".to_string(),
+            d => format!("'{}'", d as char),
+        };
+        let mut lines = vec![format!(
+            "{} lines, {} records, {} columns, delimiter {}",
+            self.lines, self.records, self.columns, delimiter
+        )];
+        if self.is_clean() {
+            lines.push("No problems found".to_string());
+            return lines;
+        }
+
+        let counts = [
+            (self.counts.ragged, "ragged rows"),
+            (self.counts.stray_quotes, "stray quotes"),
+            (self.counts.unterminated, "unterminated quotes"),
+            (self.counts.trailing_delimiters, "trailing delimiters"),
+            (self.counts.invalid_utf8, "lines that aren't UTF-8"),
+        ];
+        for (count, label) in counts.into_iter().filter(|(n, _)| *n > 0) {
+            lines.push(format!("! {} {}", count, label));
+        }
+        if self.counts.invalid_utf8 > 0 && self.utf8_lines > 0 {
+            lines.push(format!(
+                "! mixed encodings: {} lines are UTF-8 and {} are not",
+                self.utf8_lines, self.counts.invalid_utf8
+            ));
+        }
+        for (column, line) in &self.mixed_quoting {
+            lines.push(format!("! column {} is quoted inconsistently (from line {})", column, line));
+        }
+
+        lines.push(String::new());
+        for issue in &self.issues {
+            lines.push(format!("! line {}: {}", issue.line, issue.kind.describe()));
+        }
+        if self.counts.total() > self.issues.len() {
+            lines.push(format!("… and {} more", self.counts.total() - self.issues.len()));
+        }
+        lines
+    }
+}
+
+/// Most bytes a record with an open quote may run to before it is reported as
+/// unterminated and the next line is read as a new record
+pub const MAX_RECORD_BYTES: usize = 1 << 20;
+
+/// One record as the linter sees it, parsed a line at a time so a quoted
+/// field spanning lines isn't scanned again with each line added
+struct Record {
+    delimiter: u8,
+    /// 1-based line the record starts on
+    line: usize,
+    fields: usize,
+    /// Whether each field started with a quote; `None` for empty fields
+    quoted: Vec<Option<bool>>,
+    /// Bytes read, without line endings
+    bytes: usize,
+    last: Option<u8>,
+    stray_quote: bool,
+    in_quotes: bool,
+    after_quote: bool,
+    field_start: bool,
+}
+
+impl Record {
+    fn new(delimiter: u8, line: usize) -> Self {
+        Self {
+            delimiter,
+            line,
+            fields: 1,
+            quoted: Vec::new(),
+            bytes: 0,
+            last: None,
+            stray_quote: false,
+            in_quotes: false,
+            after_quote: false,
+            field_start: true,
+        }
+    }
+
+    /// Parses the next line of the record. A line ending inside quotes is part
+    /// of the field, so it is skipped like the one ending the record.
+    fn feed(&mut self, line: &[u8]) {
+        let body = trim_line_end(line);
+        self.bytes += body.len();
+        if let Some(&b) = body.last() {
+            self.last = Some(b);
+        }
+        let mut i = 0;
+        while i < body.len() {
+            let b = body[i];
+            if self.in_quotes {
+                if b == b'"' {
+                    if body.get(i + 1) == Some(&b'"') {
+                        i += 1; // escaped quote
+                    } else {
+                        self.in_quotes = false;
+                        self.after_quote = true;
+                    }
+                }
+            } else if b == self.delimiter {
+                if self.field_start {
+                    self.quoted.push(None);
+                }
+                self.fields += 1;
+                self.field_start = true;
+                self.after_quote = false;
+            } else if b == b'"' && self.field_start {
+                self.quoted.push(Some(true));
+                self.in_quotes = true;
+                self.field_start = false;
+            } else {
+                if self.field_start {
+                    self.quoted.push(Some(false));
+                    self.field_start = false;
+                }
+                if b == b'"' || (self.after_quote && b != b' ') {
+                    self.stray_quote = true;
+                }
+            }
+            i += 1;
+        }
+    }
+
+    /// Whether the record needs more lines: a quoted field is still open and
+    /// the record isn't over `MAX_RECORD_BYTES`
+    fn is_open(&self) -> bool {
+        self.in_quotes && self.bytes < MAX_RECORD_BYTES
+    }
+
+    fn finish(mut self) -> Self {
+        if self.field_start {
+            self.quoted.push(None);
+        }
+        self
+    }
+
+    fn ends_with_delimiter(&self) -> bool {
+        self.last == Some(self.delimiter) && !self.in_quotes
+    }
+}
+
+/// Streams `path` line by line, checking every record against the header.
+/// Quoted fields may span lines; a record's line number is where it starts.
+pub fn lint(path: &Path) -> std::io::Result<LintReport> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut report = LintReport {
+        delimiter: b',',
+        columns: 0,
+        lines: 0,
+        records: 0,
+        issues: Vec::new(),
+        counts: LintCounts::default(),
+        mixed_quoting: Vec::new(),
+        utf8_lines: 0,
+    };
+    let mut headers: Vec<String> = Vec::new();
+    // The header record's text, kept until it is complete to name the columns
+    let mut header = Vec::new();
+    // Whether each column's first non-empty value was quoted, and whether
+    // mixed quoting was already reported for it
+    let mut quoting: Vec<(Option<bool>, bool)> = Vec::new();
+
+    let mut pending: Option<Record> = None;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            // A quoted field still open at the end of the file
+            if let Some(record) = pending.take() {
+                check(&mut report, &mut headers, &mut quoting, &header, record.finish());
+            }
+            break;
+        }
+        report.lines += 1;
+        let text = if report.lines == 1 {
+            line.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&line[..])
+        } else {
+            &line[..]
+        };
+        match std::str::from_utf8(text) {
+            Ok(s) if !s.is_ascii() => report.utf8_lines += 1,
+            Ok(_) => {}
+            Err(_) => {
+                let number = report.lines;
+                add_issue(&mut report, number, LintKind::InvalidUtf8);
+            }
+        }
+        if report.lines == 1 {
+            report.delimiter = guess_delimiter(text);
+        }
+
+        let record = pending.get_or_insert_with(|| Record::new(report.delimiter, report.lines));
+        record.feed(text);
+        if headers.is_empty() {
+            header.extend_from_slice(text);
+        }
+        // Open quoted fields span lines: read the next one into the record
+        let Some(record) = pending.take_if(|r| !r.is_open()) else { continue };
+        check(&mut report, &mut headers, &mut quoting, &header, record.finish());
+        header.clear();
+    }
+    Ok(report)
+}
+
+/// Checks a complete record; the first one is the header, which only sets the
+/// columns the others are checked against
+fn check(
+    report: &mut LintReport,
+    headers: &mut Vec<String>,
+    quoting: &mut Vec<(Option<bool>, bool)>,
+    header: &[u8],
+    record: Record,
+) {
+    // A delimiter at the end only counts as trailing when it adds a field
+    // the header doesn't have (a header ending in one always does)
+    let mut trailing = false;
+    if headers.is_empty() {
+        *headers = split_header(trim_line_end(header), report.delimiter);
+        trailing = record.ends_with_delimiter();
+        report.columns = record.fields - usize::from(trailing);
+        *quoting = vec![(None, false); report.columns];
+    } else if record.bytes > 0 {
+        report.records += 1;
+        let expected = report.columns;
+        trailing = record.ends_with_delimiter() && record.fields == expected + 1;
+        let found = record.fields - usize::from(trailing);
+        // An unterminated record was cut short, so its field count means nothing
+        if found != expected && !record.in_quotes {
+            add_issue(report, record.line, LintKind::RaggedRow { expected, found });
+        }
+        for (i, quoted) in record.quoted.iter().enumerate().take(expected) {
+            let (Some(quoted), Some((first, reported))) = (quoted, quoting.get_mut(i)) else {
+                continue;
+            };
+            match *first {
+                None => *first = Some(*quoted),
+                Some(q) if q != *quoted && !*reported => {
+                    *reported = true;
+                    report.mixed_quoting.push((headers.get(i).cloned().unwrap_or_default(), record.line));
+                }
+                _ => {}
+            }
+        }
+    }
+    if trailing {
+        add_issue(report, record.line, LintKind::TrailingDelimiter);
+    }
+    if record.stray_quote {
+        add_issue(report, record.line, LintKind::StrayQuote);
+    }
+    if record.in_quotes {
+        add_issue(report, record.line, LintKind::UnterminatedQuote);
+    }
+}
+
+fn add_issue(report: &mut LintReport, line: usize, kind: LintKind) {
+    let count = match kind {
+        LintKind::RaggedRow { .. } => &mut report.counts.ragged,
+        LintKind::StrayQuote => &mut report.counts.stray_quotes,
+        LintKind::UnterminatedQuote => &mut report.counts.unterminated,
+        LintKind::TrailingDelimiter => &mut report.counts.trailing_delimiters,
+        LintKind::InvalidUtf8 => &mut report.counts.invalid_utf8,
+    };
+    *count += 1;
+    if report.issues.len() < MAX_REPORTED_ISSUES {
+        report.issues.push(LintIssue { line, kind });
+    }
+}
+
+/// The candidate delimiter appearing most often outside quotes in the header
+fn guess_delimiter(header: &[u8]) -> u8 {
+    DELIMITERS
+        .into_iter()
+        .max_by_key(|&d| {
+            let mut in_quotes = false;
+            header
+                .iter()
+                .filter(|&&b| {
+                    if b == b'"' {
+                        in_quotes = !in_quotes;
+                    }
+                    b == d && !in_quotes
+                })
+                .count()
+        })
+        .unwrap_or(b',')
+}
+
+fn trim_line_end(record: &[u8]) -> &[u8] {
+    let record = record.strip_suffix(b"\n").unwrap_or(record);
+    record.strip_suffix(b"\r").unwrap_or(record)
+}
+
+/// Header names, unquoted, for naming columns in the report
+fn split_header(header: &[u8], delimiter: u8) -> Vec<String> {
+    String::from_utf8_lossy(header)
+        .split(delimiter as char)
+        .map(|name| name.trim().trim_matches('"').to_string())
+        .collect()
+}
@@ -0,0 +1,272 @@
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+/// Encodings `ftool decode` and the TUI's decode scratchpad understand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Base64,
+    Url,
+    Hex,
+    Jwt,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 4] = [Encoding::Base64, Encoding::Url, Encoding::Hex, Encoding::Jwt];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Encoding::Base64 => "Base64",
+            Encoding::Url => "URL",
+            Encoding::Hex => "Hex",
+            Encoding::Jwt => "JWT",
+        }
+    }
+
+    /// The encoding `text` is most likely in: a JWT, then hex, then
+    /// percent-encoding, then base64
+    pub fn detect(text: &str) -> Option<Encoding> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        if decode_jwt(text).is_ok() {
+            Some(Encoding::Jwt)
+        } else if hex_decode(text).is_ok() {
+            Some(Encoding::Hex)
+        } else if has_percent_escape(text) {
+            Some(Encoding::Url)
+        } else if base64_decode(text).is_ok() {
+            Some(Encoding::Base64)
+        } else {
+            None
+        }
+    }
+}
+
+/// The result of decoding some text
+#[derive(Debug, Clone)]
+pub enum Decoded {
+    Text(String),
+    /// Bytes that aren't UTF-8
+    Bytes(Vec<u8>),
+    Jwt(Jwt),
+}
+
+impl Decoded {
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        match String::from_utf8(bytes) {
+            Ok(text) => Decoded::Text(text),
+            Err(e) => Decoded::Bytes(e.into_bytes()),
+        }
+    }
+
+    /// Text to show for the result: binary data as a hex dump, a JWT as JSON
+    pub fn display(&self) -> String {
+        match self {
+            Decoded::Text(text) => text.clone(),
+            Decoded::Bytes(bytes) => hex_dump(bytes),
+            Decoded::Jwt(jwt) => serde_json::to_string_pretty(&jwt.to_json()).unwrap_or_default(),
+        }
+    }
+
+    /// The result as JSON text, if it is JSON (a JWT always is)
+    pub fn json(&self) -> Option<String> {
+        match self {
+            Decoded::Text(text) => serde_json::from_str::<Value>(text).is_ok().then(|| text.clone()),
+            Decoded::Bytes(_) => None,
+            Decoded::Jwt(jwt) => serde_json::to_string_pretty(&jwt.to_json()).ok(),
+        }
+    }
+}
+
+/// A JWT's decoded header and payload. The signature is kept as it is and not verified.
+#[derive(Debug, Clone)]
+pub struct Jwt {
+    pub header: Value,
+    pub payload: Value,
+    pub signature: String,
+}
+
+impl Jwt {
+    /// `{"header": ..., "payload": ...}`
+    pub fn to_json(&self) -> Value {
+        json!({ "header": self.header, "payload": self.payload })
+    }
+
+    /// The `exp`, `iat` and `nbf` claims that are present, as times
+    pub fn times(&self) -> Vec<(&'static str, DateTime<Utc>)> {
+        [("Expires", "exp"), ("Issued", "iat"), ("Not before", "nbf")]
+            .into_iter()
+            .filter_map(|(label, claim)| {
+                let seconds = self.payload.get(claim)?.as_i64()?;
+                Some((label, DateTime::from_timestamp(seconds, 0)?))
+            })
+            .collect()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.times().iter().any(|(label, time)| *label == "Expires" && *time < Utc::now())
+    }
+
+    /// One line per time claim, e.g. `Expires    2024-05-01 12:00:00 UTC (expired)`
+    pub fn describe_times(&self) -> Vec<String> {
+        let now = Utc::now();
+        self.times()
+            .into_iter()
+            .map(|(label, time)| {
+                let note = match label {
+                    "Expires" if time < now => " (expired)",
+                    "Not before" if time > now => " (not valid yet)",
+                    _ => "",
+                };
+                format!("{:<11}{}{}", label, time.format("%Y-%m-%d %H:%M:%S UTC"), note)
+            })
+            .collect()
+    }
+}
+
+pub fn decode(encoding: Encoding, text: &str) -> anyhow::Result<Decoded> {
+    let text = text.trim();
+    Ok(match encoding {
+        Encoding::Base64 => Decoded::from_bytes(base64_decode(text)?),
+        Encoding::Url => Decoded::from_bytes(url_decode(text)),
+        Encoding::Hex => Decoded::from_bytes(hex_decode(text)?),
+        Encoding::Jwt => Decoded::Jwt(decode_jwt(text)?),
+    })
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes standard or URL-safe base64, with or without padding; whitespace
+/// (e.g. line breaks in a PEM body) is ignored
+pub fn base64_decode(text: &str) -> anyhow::Result<Vec<u8>> {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let end = chars.iter().rposition(|c| *c != '=').map_or(0, |i| i + 1);
+    let chars = &chars[..end];
+    if chars.is_empty() {
+        bail!("Nothing to decode");
+    }
+    if chars.len() % 4 == 1 {
+        bail!("Truncated base64: {} characters", chars.len());
+    }
+    let mut out = Vec::with_capacity(chars.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0u32);
+    for (i, c) in chars.iter().enumerate() {
+        let value = match c {
+            'A'..='Z' => *c as u32 - 'A' as u32,
+            'a'..='z' => *c as u32 - 'a' as u32 + 26,
+            '0'..='9' => *c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => bail!("Invalid base64 character '{}' at position {}", c, i + 1),
+        };
+        bits = (bits << 6) | value;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Decodes `%XX` escapes and `+` (as a space). A `%` not followed by two hex
+/// digits is kept as it is, like browsers do.
+pub fn url_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() && is_hex_pair(&bytes[i + 1..i + 3]) => {
+                out.push(u8::from_str_radix(&text[i + 1..i + 3], 16).unwrap_or_default());
+                i += 3;
+                continue;
+            }
+            b'+' => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Decodes hex bytes separated by whitespace or not, each group optionally
+/// `0x`-prefixed (`0x48 0x69` as well as `0x4869`)
+pub fn hex_decode(text: &str) -> anyhow::Result<Vec<u8>> {
+    let digits: Vec<u8> = text
+        .split_ascii_whitespace()
+        .flat_map(|token| token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token).bytes())
+        .collect();
+    if digits.is_empty() {
+        bail!("Nothing to decode");
+    }
+    if let Some(i) = digits.iter().position(|b| !b.is_ascii_hexdigit()) {
+        bail!("Invalid hex character '{}' at position {}", digits[i] as char, i + 1);
+    }
+    if digits.len() % 2 == 1 {
+        bail!("Odd number of hex digits ({})", digits.len());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap_or_default(), 16).unwrap_or_default())
+        .collect())
+}
+
+/// Splits a JWT and decodes its header and payload
+pub fn decode_jwt(token: &str) -> anyhow::Result<Jwt> {
+    let token = token.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token);
+    let parts: Vec<&str> = token.split('.').collect();
+    let [header, payload, signature] = parts[..] else {
+        bail!("A JWT has three dot-separated parts, found {}", parts.len());
+    };
+    let part = |name: &str, text: &str| -> anyhow::Result<Value> {
+        let bytes = base64_decode(text).with_context(|| format!("Invalid JWT {}", name))?;
+        let value: Value = serde_json::from_slice(&bytes).with_context(|| format!("The JWT {} isn't JSON", name))?;
+        if !value.is_object() {
+            bail!("The JWT {} isn't a JSON object", name);
+        }
+        Ok(value)
+    };
+    Ok(Jwt { header: part("header", header)?, payload: part("payload", payload)?, signature: signature.to_string() })
+}
+
+/// `xxd`-style dump: offset, 16 bytes in hex, and their printable characters
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String =
+                chunk.iter().map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' }).collect();
+            format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn is_hex_pair(bytes: &[u8]) -> bool {
+    bytes.len() == 2 && bytes.iter().all(u8::is_ascii_hexdigit)
+}
+
+fn has_percent_escape(text: &str) -> bool {
+    text.as_bytes().windows(3).any(|w| w[0] == b'%' && is_hex_pair(&w[1..]))
+}
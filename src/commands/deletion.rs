@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, bail};
+
+/// Moves `paths` to the system trash, or with `permanent` deletes them for
+/// good (directories with everything in them)
+pub fn remove(paths: &[PathBuf], permanent: bool) -> anyhow::Result<()> {
+    if !permanent {
+        trash::delete_all(paths).context("Failed to move to the trash")?;
+        tracing::info!(count = paths.len(), "moved to the trash");
+        return Ok(());
+    }
+    for path in paths {
+        let removed = if path.symlink_metadata().is_ok_and(|m| m.is_dir()) {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        removed.with_context(|| format!("Failed to delete {}", path.display()))?;
+        tracing::info!(file = %path.display(), "deleted permanently");
+    }
+    Ok(())
+}
+
+/// Puts `paths`, moved to the trash by `remove`, back where they were. When a
+/// path was trashed more than once, the latest copy comes back. Fails before
+/// restoring anything if one of them isn't in the trash or its place is taken.
+#[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+pub fn restore(paths: &[PathBuf]) -> anyhow::Result<()> {
+    let mut trashed = trash::os_limited::list().context("Failed to read the trash")?;
+    let mut items = Vec::new();
+    for path in paths {
+        if path.exists() {
+            bail!("{} exists again; move it away to restore the deleted one", path.display());
+        }
+        let latest = trashed
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.original_path() == *path)
+            .max_by_key(|(_, item)| item.time_deleted)
+            .map(|(i, _)| i);
+        let Some(i) = latest else {
+            bail!("{} is no longer in the trash", path.display());
+        };
+        items.push(trashed.swap_remove(i));
+    }
+    trash::os_limited::restore_all(items).context("Failed to restore from the trash")?;
+    tracing::info!(count = paths.len(), "restored from the trash");
+    Ok(())
+}
+
+/// The trash can't be read back on this platform
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))))]
+pub fn restore(_paths: &[PathBuf]) -> anyhow::Result<()> {
+    bail!("Restoring from the trash isn't supported on this platform; use the Finder's Put Back")
+}
+
+/// `name` for one path, `N items` for several
+pub fn describe(paths: &[PathBuf]) -> String {
+    match paths {
+        [path] => path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().to_string()),
+        _ => format!("{} items", paths.len()),
+    }
+}
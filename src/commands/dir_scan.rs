@@ -0,0 +1,88 @@
+use std::fs::ReadDir;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+/// What `scan` found under a directory
+#[derive(Debug, Clone, Default)]
+pub struct DirStats {
+    pub files: u64,
+    /// Subdirectories, at any depth
+    pub dirs: u64,
+    pub bytes: u64,
+    /// Directories or entries that couldn't be read, left out of the totals
+    pub unreadable: u64,
+    /// The biggest file and its size
+    pub largest: Option<(PathBuf, u64)>,
+}
+
+impl DirStats {
+    fn add_file(&mut self, path: PathBuf, size: u64) {
+        self.files += 1;
+        self.bytes += size;
+        if self.largest.as_ref().is_none_or(|(_, largest)| size > *largest) {
+            self.largest = Some((path, size));
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.files += other.files;
+        self.dirs += other.dirs;
+        self.bytes += other.bytes;
+        self.unreadable += other.unreadable;
+        if let Some((path, size)) = other.largest
+            && self.largest.as_ref().is_none_or(|(_, largest)| size > *largest)
+        {
+            self.largest = Some((path, size));
+        }
+        self
+    }
+}
+
+/// Counts the files and bytes under `dir`, reading subdirectories in parallel
+/// on rayon's thread pool. Symbolic links are not followed, so a link to a
+/// parent can't loop. `cancelled` is checked before each directory is read;
+/// once it returns true the walk winds down and `None` is returned.
+pub fn scan(dir: &Path, cancelled: &(dyn Fn() -> bool + Sync)) -> std::io::Result<Option<DirStats>> {
+    let stats = scan_entries(std::fs::read_dir(dir)?, cancelled);
+    Ok((!cancelled()).then_some(stats))
+}
+
+fn scan_dir(dir: &Path, cancelled: &(dyn Fn() -> bool + Sync)) -> DirStats {
+    if cancelled() {
+        return DirStats::default();
+    }
+    match std::fs::read_dir(dir) {
+        Ok(entries) => scan_entries(entries, cancelled),
+        Err(e) => {
+            tracing::debug!(dir = %dir.display(), error = %e, "skipping unreadable directory");
+            DirStats { unreadable: 1, ..Default::default() }
+        }
+    }
+}
+
+fn scan_entries(entries: ReadDir, cancelled: &(dyn Fn() -> bool + Sync)) -> DirStats {
+    let mut stats = DirStats::default();
+    let mut subdirs = Vec::new();
+    for entry in entries {
+        let Ok(entry) = entry else {
+            stats.unreadable += 1;
+            continue;
+        };
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => subdirs.push(entry.path()),
+            Ok(t) if t.is_file() => match entry.metadata() {
+                Ok(metadata) => stats.add_file(entry.path(), metadata.len()),
+                Err(_) => stats.unreadable += 1,
+            },
+            Ok(_) => {}
+            Err(_) => stats.unreadable += 1,
+        }
+    }
+    stats.dirs += subdirs.len() as u64;
+    subdirs
+        .par_iter()
+        .map(|dir| scan_dir(dir, cancelled))
+        .reduce(DirStats::default, DirStats::merge)
+        .merge(stats)
+}
@@ -0,0 +1,26 @@
+use std::io;
+use std::path::Path;
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::cli::Cli;
+
+/// Writes a completion script for `shell` to stdout. The script is generated
+/// from the clap definitions, so new subcommands and flags are picked up automatically.
+pub fn completions(shell: Shell) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+}
+
+/// Renders the ftool(1) man page to stdout
+pub fn man() -> io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())
+}
+
+/// Writes ftool.1 plus one page per subcommand (ftool-inspect.1, ...) into `dir`
+pub fn man_to_dir(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    clap_mangen::generate_to(Cli::command(), dir)
+}
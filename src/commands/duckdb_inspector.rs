@@ -1,47 +1,481 @@
 use duckdb::Connection;
-use std::path::Path;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use thiserror::Error;
 
-#[derive(Debug)]
+use super::backup::{self, PendingWrite};
+use super::profile::{
+    ColumnProfile, HISTOGRAM_BUCKETS, HistogramBucket, TOP_VALUES, ValueCount, is_numeric_type,
+};
+use super::geo::{self, GeoColumn, GeoEncoding};
+use super::hooks::{self, HookEvent};
+use super::merge::read_function;
+use super::postgres::{DatabaseTable, LoadMode, TableName, load_ddl, load_finish_ddl, load_target};
+use super::split::{SplitMode, file_name_part};
+use super::transform::{ComputedColumn, Transform};
+
+#[derive(Debug, Error)]
 pub enum DuckDbError {
+    #[error("File not found: {0}")]
     FileNotFound(String),
+    #[error("Invalid file format: {0}")]
     InvalidFileFormat(String),
+    #[error("Database connection error: {0}")]
     ConnectionError(String),
+    #[error("Query execution error: {0}")]
     QueryError(String),
+    #[error("Invalid column name: {0}")]
     InvalidColumn(String),
+    #[error("Database error: {0}")]
     DatabaseError(String),
+    /// The file was written, but the `[hooks]` command run after it failed
+    #[error("Wrote the file, but {0}")]
+    HookFailed(String),
+}
+
+/// Runs the `[hooks]` command for `event` on a file just written
+fn run_hook(event: HookEvent, output: &str) -> Result<(), DuckDbError> {
+    hooks::run(event, Path::new(output)).map_err(|e| DuckDbError::HookFailed(format!("{:#}", e)))
+}
+
+impl From<duckdb::Error> for DuckDbError {
+    fn from(error: duckdb::Error) -> Self {
+        DuckDbError::DatabaseError(error.to_string())
+    }
+}
+
+/// Null count and min/max/mean of one column, as shown in the TUI Schema tab
+#[derive(Debug, Clone)]
+pub struct ColumnStats {
+    pub null_count: usize,
+    pub min: String,
+    pub max: String,
+    pub mean: String,
+    /// Row counts in equal-width buckets from min to max; empty for
+    /// non-numeric columns
+    pub histogram: Vec<usize>,
+}
+
+/// Column-level Parquet metadata from `parquet_schema()` and `parquet_metadata()`
+#[derive(Debug, Clone, Default)]
+pub struct ParquetColumnInfo {
+    pub physical_type: Option<String>,
+    pub logical_type: Option<String>,
+    pub converted_type: Option<String>,
+    pub field_id: Option<String>,
+    pub repetition: Option<String>,
+    /// Codecs used across row groups, e.g. "SNAPPY"
+    pub compression: Option<String>,
+    /// Encodings used across row groups, e.g. "PLAIN, RLE_DICTIONARY"
+    pub encodings: Option<String>,
+    pub compressed_bytes: Option<i64>,
+    pub uncompressed_bytes: Option<i64>,
+}
+
+impl ParquetColumnInfo {
+    pub fn dictionary_encoded(&self) -> bool {
+        self.encodings.as_deref().is_some_and(|e| e.contains("DICTIONARY"))
+    }
+
+    /// "label: value" lines for display, with "-" for missing values
+    pub fn report(&self) -> Vec<String> {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        let bytes = |v: Option<i64>| v.map(|b| b.to_string()).unwrap_or_else(|| "-".to_string());
+        vec![
+            format!("Physical type:      {}", show(&self.physical_type)),
+            format!("Logical type:       {}", show(&self.logical_type)),
+            format!("Converted type:     {}", show(&self.converted_type)),
+            format!("Field id:           {}", show(&self.field_id)),
+            format!("Repetition:         {}", show(&self.repetition)),
+            format!("Compression:        {}", show(&self.compression)),
+            format!("Encodings:          {}", show(&self.encodings)),
+            format!("Dictionary encoded: {}", if self.dictionary_encoded() { "yes" } else { "no" }),
+            format!("Compressed bytes:   {}", bytes(self.compressed_bytes)),
+            format!("Uncompressed bytes: {}", bytes(self.uncompressed_bytes)),
+        ]
+    }
+}
+
+/// One row group of a Parquet file, from `parquet_metadata()`
+#[derive(Debug, Clone)]
+pub struct RowGroupInfo {
+    /// Row group id within its file
+    pub id: usize,
+    /// The file it belongs to, for globs over several files
+    pub file: String,
+    pub rows: usize,
+    /// Index of its first row in the whole dataset, in read order
+    pub first_row: usize,
+    pub compressed_bytes: u64,
+    pub uncompressed_bytes: u64,
+}
+
+/// Rows read to recognise WKT, GeoJSON and WKB columns
+const GEO_SAMPLE_ROWS: usize = 200;
+
+/// Data rows per worksheet: Excel's limit of 1,048,576 rows minus the header
+pub const MAX_SHEET_ROWS: usize = 1_048_575;
+
+/// Options for `export_xlsx`
+#[derive(Debug, Clone)]
+pub struct ExcelOptions {
+    /// Rows per data sheet before continuing on a new one (at most `MAX_SHEET_ROWS`)
+    pub sheet_rows: usize,
+    /// Add a "Summary" sheet with each source column's type and stats
+    pub summary: bool,
+}
+
+impl Default for ExcelOptions {
+    fn default() -> Self {
+        Self {
+            sheet_rows: MAX_SHEET_ROWS,
+            summary: false,
+        }
+    }
+}
+
+/// One value as written to a worksheet cell
+enum XlsxCell {
+    Empty,
+    Number(f64),
+    Bool(bool),
+    Text(String),
+}
+
+type XlsxReader = fn(&duckdb::Row, usize) -> duckdb::Result<XlsxCell>;
+
+fn read_number(row: &duckdb::Row, i: usize) -> duckdb::Result<XlsxCell> {
+    Ok(row.get::<_, Option<f64>>(i)?.map_or(XlsxCell::Empty, XlsxCell::Number))
+}
+
+fn read_bool(row: &duckdb::Row, i: usize) -> duckdb::Result<XlsxCell> {
+    Ok(row.get::<_, Option<bool>>(i)?.map_or(XlsxCell::Empty, XlsxCell::Bool))
+}
+
+fn read_text(row: &duckdb::Row, i: usize) -> duckdb::Result<XlsxCell> {
+    Ok(row.get::<_, Option<String>>(i)?.map_or(XlsxCell::Empty, XlsxCell::Text))
+}
+
+/// How to select and read a column of `ty` so Excel gets numbers and booleans, not text
+fn xlsx_cast(name: &str, ty: &str) -> (String, XlsxReader) {
+    let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+    if is_numeric_type(ty) {
+        (format!("CAST({} AS DOUBLE)", quoted), read_number)
+    } else if ty == "BOOLEAN" {
+        (quoted, read_bool)
+    } else {
+        (format!("CAST({} AS VARCHAR)", quoted), read_text)
+    }
+}
+
+/// How `export_into` combines new rows with an existing target file
+#[derive(Debug, Clone)]
+pub enum ExportMode {
+    /// Add the rows after the existing ones
+    Append,
+    /// Replace existing rows whose key matches a new row, add the rest
+    Upsert { key: String },
+}
+
+/// Rows written by `export_into`
+#[derive(Debug, Clone, Default)]
+pub struct ExportSummary {
+    /// New rows whose key wasn't in the target (every new row when appending)
+    pub inserted: usize,
+    /// Existing rows replaced by a new row with the same key (upsert only)
+    pub replaced: usize,
+    /// Columns whose type differs between the target and the new rows
+    pub type_conflicts: Vec<String>,
+    /// The COPY statement run (or the statements, for a DuckDB table), or on a
+    /// dry run the ones that would be
+    pub query: String,
+}
+
+/// Whether an export target names a DuckDB database (`warehouse.duckdb` or
+/// `warehouse.duckdb:table`) rather than a file
+pub fn is_duckdb_target(target: &str) -> bool {
+    target.ends_with(".duckdb") || target.contains(".duckdb:")
+}
+
+/// A COPY statement to be run, with the path it is meant for. The rows are
+/// written to a temp file first, so the destination is given separately.
+#[derive(Debug, Clone)]
+pub struct CopyPlan {
+    pub target: String,
+    /// Back up what `target` replaces and record the write for `ftool undo`;
+    /// batch conversions turn this off so they don't flush the journal
+    pub tracked: bool,
+    select: String,
+    options: String,
+}
+
+impl CopyPlan {
+    /// The statement writing to `dest`
+    pub fn statement(&self, dest: &str) -> String {
+        format!("COPY ({}) TO '{}' ({})", self.select, dest.replace('\'', "''"), self.options)
+    }
+}
+
+/// Rows sampled from the source whose hashes must appear in a converted file
+pub const VERIFY_SAMPLE_ROWS: usize = 1000;
+
+/// Result of checking a converted file against its source
+#[derive(Debug, Clone)]
+pub struct Verification {
+    pub source_rows: usize,
+    pub output_rows: usize,
+    /// Columns missing from or added to the output; any of these fails the check
+    pub column_changes: Vec<String>,
+    /// (column, source type, output type); informational, since CSV types are re-inferred
+    pub type_changes: Vec<(String, String, String)>,
+    pub sampled: usize,
+    /// Sampled source rows whose hash wasn't found in the output
+    pub unmatched: usize,
+}
+
+impl Verification {
+    pub fn passed(&self) -> bool {
+        self.source_rows == self.output_rows && self.column_changes.is_empty() && self.unmatched == 0
+    }
+
+    /// One line per check; failed checks start with `!`
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let rows = format!("Row count: {} -> {}", self.source_rows, self.output_rows);
+        lines.push(if self.source_rows == self.output_rows { rows } else { format!("! {}", rows) });
+
+        if self.column_changes.is_empty() {
+            lines.push("Columns: match".to_string());
+        }
+        lines.extend(self.column_changes.iter().map(|c| format!("! {}", c)));
+        for (name, from, to) in &self.type_changes {
+            lines.push(format!("  Type of {}: {} -> {}", name, from, to));
+        }
+
+        if self.column_changes.is_empty() {
+            let sampled = format!("{} of {} sampled rows found in output", self.sampled - self.unmatched, self.sampled);
+            lines.push(if self.unmatched == 0 { sampled } else { format!("! {}", sampled) });
+        }
+        lines.push(if self.passed() { "Verification passed" } else { "Verification failed" }.to_string());
+        lines
+    }
 }
 
-impl std::fmt::Display for DuckDbError {
+/// CSVs at least this large get an estimated row count instead of a full scan
+pub const CSV_ESTIMATE_MIN_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Lines read from the start of a CSV to estimate its average row size
+const CSV_ESTIMATE_SAMPLE_LINES: usize = 10_000;
+
+/// A row count that is either exact or estimated from a sample of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowCount {
+    Exact(usize),
+    Estimated(usize),
+}
+
+impl RowCount {
+    pub fn value(self) -> usize {
+        match self {
+            RowCount::Exact(n) | RowCount::Estimated(n) => n,
+        }
+    }
+
+    pub fn is_estimated(self) -> bool {
+        matches!(self, RowCount::Estimated(_))
+    }
+}
+
+impl std::fmt::Display for RowCount {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            DuckDbError::FileNotFound(path) => write!(f, "File not found: {}", path),
-            DuckDbError::InvalidFileFormat(path) => {
-                write!(f, "Invalid file format: {}", path)
-            }
-            DuckDbError::ConnectionError(msg) => write!(f, "Database connection error: {}", msg),
-            DuckDbError::QueryError(msg) => write!(f, "Query execution error: {}", msg),
-            DuckDbError::InvalidColumn(col) => write!(f, "Invalid column name: {}", col),
-            DuckDbError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            RowCount::Exact(n) => write!(f, "{}", n),
+            RowCount::Estimated(n) => write!(f, "\u{2248}{}", n),
         }
     }
 }
 
-impl std::error::Error for DuckDbError {}
+/// Rows of a CSV extrapolated from the average size of its first
+/// `CSV_ESTIMATE_SAMPLE_LINES` lines; exact when the file is no longer than that
+pub fn estimate_csv_rows(path: &Path) -> std::io::Result<RowCount> {
+    let file_size = std::fs::metadata(path)?.len();
+    let mut lines = BufReader::new(std::fs::File::open(path)?).split(b'\n');
+    // The header isn't a row
+    let header_bytes = match lines.next() {
+        Some(line) => line?.len() as u64 + 1,
+        None => return Ok(RowCount::Exact(0)),
+    };
+    let (mut sampled, mut sampled_bytes) = (0u64, 0u64);
+    for line in lines.take(CSV_ESTIMATE_SAMPLE_LINES) {
+        sampled += 1;
+        sampled_bytes += line?.len() as u64 + 1;
+    }
+    if header_bytes + sampled_bytes >= file_size {
+        return Ok(RowCount::Exact(sampled as usize));
+    }
+    let bytes_per_row = (sampled_bytes / sampled).max(1);
+    Ok(RowCount::Estimated((file_size.saturating_sub(header_bytes) / bytes_per_row) as usize))
+}
 
-impl From<duckdb::Error> for DuckDbError {
-    fn from(error: duckdb::Error) -> Self {
-        DuckDbError::DatabaseError(error.to_string())
+/// How the numbers of a CSV are written
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum DecimalFormat {
+    /// `1234.56`
+    #[default]
+    Point,
+    /// `1.234,56`, as in most European CSVs. DuckDB reads the decimal comma
+    /// itself; `grouped` are the columns that also use periods between
+    /// thousands, which it leaves as text and ftool converts.
+    Comma { grouped: Vec<String> },
+}
+
+/// A number written with a decimal comma and optional thousands periods
+const DECIMAL_COMMA_PATTERN: &str = r"-?([0-9]{1,3}(\.[0-9]{3})+|[0-9]+)(,[0-9]+)?";
+
+/// `value` as DuckDB reads a number when it is written with a decimal comma
+/// (`-1.234,5` becomes `-1234.5`); `None` for anything else
+pub fn normalize_decimal_comma(value: &str) -> Option<String> {
+    let value = value.trim();
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value),
+    };
+    let (int, fraction) = match digits.split_once(',') {
+        Some((int, fraction)) => (int, Some(fraction)),
+        None => (digits, None),
+    };
+    let groups: Vec<&str> = int.split('.').collect();
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let grouped = groups.len() == 1 || (groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3));
+    if !grouped || !groups.iter().all(|g| is_digits(g)) || !fraction.is_none_or(is_digits) {
+        return None;
+    }
+    Some(match fraction {
+        Some(fraction) => format!("{}{}.{}", sign, groups.concat(), fraction),
+        None => format!("{}{}", sign, groups.concat()),
+    })
+}
+
+/// Starts a write of `path`; a COPY writes to the temp file it names,
+/// returned quoted for a SQL string
+pub fn prepare_write(path: &str) -> (PendingWrite, String) {
+    let pending = backup::prepare(Path::new(path));
+    let temp = pending.temp().to_string_lossy().replace('\'', "''");
+    (pending, temp)
+}
+
+/// Renames a finished write into place
+pub fn finish_write(pending: PendingWrite) -> Result<(), DuckDbError> {
+    pending.finish().map_err(|e| DuckDbError::DatabaseError(format!("{:#}", e)))
+}
+
+/// Name the configured Parquet key is registered under in each connection
+const PARQUET_KEY_NAME: &str = "ftool";
+
+/// Whether a Parquet file's footer is encrypted: it ends in `PARE` instead of `PAR1`
+pub fn is_encrypted_parquet(path: &Path) -> bool {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut f| {
+            f.seek(SeekFrom::End(-4))?;
+            f.read_exact(&mut magic)
+        })
+        .is_ok()
+        && magic == *b"PARE"
+}
+
+/// Opens an in-memory DuckDB connection with the memory, thread, spill and S3
+/// endpoint settings from the config (or their command-line overrides)
+/// applied, and the Parquet key registered when one is set
+pub fn open_connection() -> Result<Connection, DuckDbError> {
+    let connection = Connection::open_in_memory().map_err(|e| {
+        DuckDbError::ConnectionError(format!("Failed to open in-memory database: {}", e))
+    })?;
+
+    let config = crate::config::get();
+    let mut settings = Vec::new();
+    if let Some(limit) = &config.memory_limit {
+        settings.push(format!("SET memory_limit = '{}'", limit.replace('\'', "''")));
+    }
+    if let Some(threads) = config.threads {
+        settings.push(format!("SET threads = {}", threads));
+    }
+    if let Some(dir) = &config.temp_directory {
+        let dir = dir.to_string_lossy().replace('\'', "''");
+        settings.push(format!("SET temp_directory = '{}'", dir));
+    }
+    for setting in settings {
+        connection.execute_batch(&setting).map_err(|e| {
+            DuckDbError::ConnectionError(format!("Failed to apply '{}': {}", setting, e))
+        })?;
+        tracing::debug!(setting, "applied duckdb setting");
+    }
+    // Belongs to the httpfs extension, which may not be installable offline:
+    // only `s3://` reads need it, so local files still open without it
+    if let Some(endpoint) = &config.s3_endpoint {
+        let setting = format!("SET s3_endpoint = '{}'", endpoint.replace('\'', "''"));
+        match connection.execute_batch(&setting) {
+            Ok(()) => tracing::debug!(setting, "applied duckdb setting"),
+            Err(e) => tracing::warn!(error = %e, "could not set s3_endpoint"),
+        }
     }
+
+    Ok(connection)
+}
+
+/// Registers the configured Parquet key with `connection`, for a file that is
+/// encrypted or about to be written encrypted. Only done then, so a key of the
+/// wrong length only fails the files that need it.
+fn add_parquet_key(connection: &Connection) -> Result<(), DuckDbError> {
+    let key = crate::config::get().parquet_key().ok_or_else(|| {
+        DuckDbError::InvalidFileFormat("No Parquet key: pass --parquet-key or set parquet_key in the config".to_string())
+    })?;
+    // Its own statement, so a failure never prints the key
+    connection
+        .execute_batch(&format!("PRAGMA add_parquet_key('{}', '{}')", PARQUET_KEY_NAME, key.replace('\'', "''")))
+        .map_err(|e| DuckDbError::ConnectionError(format!("Failed to add the Parquet key: {}", e)))?;
+    tracing::debug!("added parquet encryption key");
+    Ok(())
 }
 
 pub struct DuckDbInspector {
     file_path: String,
     connection: Connection,
+    /// Every column's name and type, read with `read_csv(columns = ...)` instead
+    /// of DuckDB's inference; empty unless types were overridden
+    column_types: Vec<(String, String)>,
+    /// Columns of `column_types` given their type by an override, read as is
+    /// rather than converted from thousands-grouped text
+    overridden: Vec<String>,
+    /// Set when `file_path` is a Postgres or MySQL table URL instead of a file
+    table: Option<DatabaseTable>,
+    /// Only the first rows of the file are read, to sample one too large to open whole
+    row_limit: Option<usize>,
+    decimal_format: DecimalFormat,
+    /// Zone the preview shows TIMESTAMP values in; `None` for UTC, as stored
+    display_timezone: Option<String>,
+    /// `naive_timestamp_columns`, found once per zone and set of columns
+    zoned: OnceLock<Vec<String>>,
+    /// The file is Parquet with an encrypted footer, read with the configured key
+    encrypted: bool,
+    /// Parquet conversions are written encrypted with the configured key
+    encrypt_output: bool,
+    /// Nested columns unnested so far, in order, each with the SELECT list
+    /// that unnests it from the columns before it
+    unnested: Vec<(String, String)>,
 }
 
 impl DuckDbInspector {
-    /// Constructor - validates the file path before creating the connection
+    /// Constructor - validates the file path before creating the connection.
+    /// A `postgres://` or `mysql://` table URL is attached instead.
     pub fn new(file_path: String) -> Result<Self, DuckDbError> {
+        if DatabaseTable::is_url(&file_path) {
+            return Self::open_table(file_path);
+        }
+
         // Validate file exists
         let path = Path::new(&file_path);
         if !path.exists() {
@@ -70,18 +504,289 @@ impl DuckDbInspector {
             ));
         }
 
-        // Create connection
-        let connection = Connection::open_in_memory().map_err(|e| {
-            DuckDbError::ConnectionError(format!("Failed to open in-memory database: {}", e))
-        })?;
+        let encrypted = path.extension().is_some_and(|e| e == "parquet") && is_encrypted_parquet(path);
+        if encrypted && crate::config::get().parquet_key().is_none() {
+            return Err(DuckDbError::InvalidFileFormat(format!(
+                "{} is an encrypted Parquet file; pass --parquet-key or set parquet_key in the config",
+                file_path
+            )));
+        }
+
+        let connection = open_connection()?;
+        if encrypted {
+            add_parquet_key(&connection)?;
+        }
+
+        tracing::debug!(file = %file_path, encrypted, "opened duckdb connection");
 
         Ok(Self {
             file_path,
             connection,
+            column_types: Vec::new(),
+            overridden: Vec::new(),
+            table: None,
+            row_limit: None,
+            decimal_format: DecimalFormat::Point,
+            display_timezone: None,
+            zoned: OnceLock::new(),
+            encrypted,
+            encrypt_output: false,
+            unnested: Vec::new(),
+        })
+    }
+
+    /// Attaches the database of a table URL read-only through DuckDB's
+    /// postgres or mysql extension, installed on first use
+    fn open_table(url: String) -> Result<Self, DuckDbError> {
+        let table = DatabaseTable::parse(&url)?;
+        let connection = open_connection()?;
+        connection.execute_batch(&table.attach_sql()).map_err(|e| {
+            DuckDbError::ConnectionError(format!("Failed to connect to {}: {}", table.display, e))
+        })?;
+        tracing::debug!(table = %table.display, "attached database");
+
+        Ok(Self {
+            file_path: url,
+            connection,
+            column_types: Vec::new(),
+            overridden: Vec::new(),
+            table: Some(table),
+            row_limit: None,
+            decimal_format: DecimalFormat::Point,
+            display_timezone: None,
+            zoned: OnceLock::new(),
+            encrypted: false,
+            encrypt_output: false,
+            unnested: Vec::new(),
         })
     }
 
+    /// The file path, or the table URL with its password hidden, for logs
+    pub fn label(&self) -> &str {
+        self.table.as_ref().map_or(&self.file_path, |t| &t.display)
+    }
+
+    /// Where outputs named after the input go: the file itself, or the bare
+    /// table name (in the working directory) for a database table
+    fn local_path(&self) -> PathBuf {
+        match &self.table {
+            Some(table) => PathBuf::from(&table.table.table),
+            None => PathBuf::from(&self.file_path),
+        }
+    }
+
+    /// Reads the CSV with the given columns' types replaced, e.g. `zip` as
+    /// VARCHAR to keep leading zeros; the other columns keep their inferred type
+    pub fn with_types(mut self, overrides: &[(String, String)]) -> Result<Self, DuckDbError> {
+        self.set_types(overrides)?;
+        Ok(self)
+    }
+
+    /// Replaces any earlier overrides; on error the previous ones stay in effect
+    pub fn set_types(&mut self, overrides: &[(String, String)]) -> Result<(), DuckDbError> {
+        if overrides.is_empty() {
+            self.column_types.clear();
+            self.overridden.clear();
+            self.zoned = OnceLock::new();
+            return Ok(());
+        }
+        if self.read_function() != "read_csv_auto" {
+            return Err(DuckDbError::InvalidFileFormat(
+                "Column types can only be overridden for CSV files".to_string(),
+            ));
+        }
+
+        let previous = std::mem::take(&mut self.column_types);
+        let mut columns = match self.schema() {
+            Ok(schema) => schema,
+            Err(e) => {
+                self.column_types = previous;
+                return Err(e);
+            }
+        };
+        // Thousands-grouped numbers are read as text to be converted
+        if let DecimalFormat::Comma { grouped } = &self.decimal_format {
+            for (_, ty) in columns.iter_mut().filter(|(name, _)| grouped.contains(name)) {
+                *ty = "VARCHAR".to_string();
+            }
+        }
+        for (column, ty) in overrides {
+            let Some(entry) = columns.iter_mut().find(|(name, _)| name == column) else {
+                self.column_types = previous;
+                return Err(DuckDbError::InvalidColumn(format!("Column '{}' not found", column)));
+            };
+            entry.1 = ty.trim().to_uppercase();
+        }
+        self.column_types = columns;
+        let previous_overridden =
+            std::mem::replace(&mut self.overridden, overrides.iter().map(|(name, _)| name.clone()).collect());
+
+        // DESCRIBE rejects unknown type names before any data is read
+        if let Err(e) = self.schema() {
+            self.column_types = previous;
+            self.overridden = previous_overridden;
+            return Err(e);
+        }
+        self.zoned = OnceLock::new();
+        Ok(())
+    }
+
+    pub fn decimal_format(&self) -> &DecimalFormat {
+        &self.decimal_format
+    }
+
+    pub fn is_decimal_comma(&self) -> bool {
+        self.decimal_format != DecimalFormat::Point
+    }
+
+    /// Reads numbers in `format`, already detected by `set_decimal_comma` on
+    /// another connection; set before `with_types`
+    pub fn with_decimal_format(mut self, format: DecimalFormat) -> Self {
+        self.set_decimal_format(format);
+        self
+    }
+
+    pub fn set_decimal_format(&mut self, format: DecimalFormat) {
+        self.decimal_format = format;
+    }
+
+    /// Reads the CSV's numbers with a decimal comma (and periods between
+    /// thousands), or back with a decimal point. Type overrides are computed
+    /// for one format, so set them again afterwards.
+    pub fn set_decimal_comma(&mut self, on: bool) -> Result<(), DuckDbError> {
+        if !on {
+            self.decimal_format = DecimalFormat::Point;
+            return Ok(());
+        }
+        if self.read_function() != "read_csv_auto" {
+            return Err(DuckDbError::InvalidFileFormat(
+                "Decimal commas can only be read from CSV files".to_string(),
+            ));
+        }
+        let previous = std::mem::replace(&mut self.decimal_format, DecimalFormat::Comma { grouped: Vec::new() });
+        match self.grouped_number_columns() {
+            Ok(grouped) => {
+                self.set_grouped(grouped);
+                Ok(())
+            }
+            Err(e) => {
+                self.decimal_format = previous;
+                Err(e)
+            }
+        }
+    }
+
+    fn set_grouped(&mut self, columns: Vec<String>) {
+        if let DecimalFormat::Comma { grouped } = &mut self.decimal_format {
+            *grouped = columns;
+        }
+    }
+
+    /// Text columns whose every value is a number with periods between
+    /// thousands, e.g. `1.234.567,8`
+    fn grouped_number_columns(&self) -> Result<Vec<String>, DuckDbError> {
+        let text: Vec<String> = self
+            .schema()?
+            .into_iter()
+            .filter(|(_, ty)| ty == "VARCHAR")
+            .map(|(name, _)| name)
+            .collect();
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+        let checks: Vec<String> = text
+            .iter()
+            .map(|name| {
+                let name = name.replace('"', "\"\"");
+                // At least one period, so codes that are only digits stay text
+                format!(
+                    "bool_and(regexp_full_match(trim(\"{0}\"), '{1}')) AND bool_or(contains(\"{0}\", '.'))",
+                    name, DECIMAL_COMMA_PATTERN
+                )
+            })
+            .collect();
+        let query = format!("SELECT {} FROM {}", checks.join(", "), self.source());
+        let started = Instant::now();
+        let matches = self
+            .connection
+            .query_row(&query, [], |row| {
+                (0..text.len()).map(|i| row.get::<_, Option<bool>>(i)).collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to detect grouped numbers: {}", e)))?;
+        Self::log_query("decimal_comma", &query, started);
+        Ok(text.into_iter().zip(matches).filter(|(_, m)| *m == Some(true)).map(|(name, _)| name).collect())
+    }
+
+    /// Shows timestamps in `zone`: `utc`, `local` or an IANA name such as
+    /// `Europe/Paris`. The connection's `TimeZone` is set to it, so TIMESTAMPTZ
+    /// values render there with their offset; TIMESTAMP values, taken as UTC,
+    /// are converted to it in the preview.
+    pub fn set_display_timezone(&mut self, zone: &str) -> Result<(), DuckDbError> {
+        let zone = match zone {
+            "utc" => "UTC".to_string(),
+            "local" => iana_time_zone::get_timezone()
+                .map_err(|e| DuckDbError::ConnectionError(format!("Could not determine the local time zone: {}", e)))?,
+            zone => zone.to_string(),
+        };
+        let setting = format!("SET TimeZone = '{}'", zone.replace('\'', "''"));
+        self.connection
+            .execute_batch(&setting)
+            .map_err(|e| DuckDbError::QueryError(format!("Unknown time zone '{}': {}", zone, e)))?;
+        tracing::debug!(zone, "set display time zone");
+        self.display_timezone = (zone != "UTC").then_some(zone);
+        self.zoned = OnceLock::new();
+        Ok(())
+    }
+
+    /// The zone timestamps are shown in, e.g. `UTC` or `Europe/Paris`
+    pub fn display_timezone(&self) -> &str {
+        self.display_timezone.as_deref().unwrap_or("UTC")
+    }
+
+    /// TIMESTAMP (without time zone) columns the preview converts to the
+    /// display time zone; none when it is UTC
+    fn naive_timestamp_columns(&self) -> Result<Vec<String>, DuckDbError> {
+        if self.display_timezone.is_none() {
+            return Ok(Vec::new());
+        }
+        if let Some(zoned) = self.zoned.get() {
+            return Ok(zoned.clone());
+        }
+        let zoned: Vec<String> = self
+            .schema()?
+            .into_iter()
+            .filter(|(_, ty)| ty.starts_with("TIMESTAMP") && !ty.ends_with("TIME ZONE") && ty != "TIMESTAMPTZ")
+            .map(|(name, _)| name)
+            .collect();
+        Ok(self.zoned.get_or_init(|| zoned).clone())
+    }
+
+    /// Whether the preview shows `column` converted from UTC to the display
+    /// time zone, while filters still compare its stored UTC values
+    pub fn is_zoned(&self, column: &str) -> bool {
+        self.naive_timestamp_columns().is_ok_and(|zoned| zoned.iter().any(|c| c == column))
+    }
+
+    /// Handle that aborts this connection's running query from another thread
+    pub fn interrupt_handle(&self) -> std::sync::Arc<duckdb::InterruptHandle> {
+        self.connection.interrupt_handle()
+    }
+
+    /// Logs a finished query with its elapsed time so slow queries can be reproduced
+    fn log_query(kind: &str, query: &str, started: Instant) {
+        tracing::debug!(
+            kind,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            query,
+            "duckdb query"
+        );
+    }
+
+    /// The DuckDB function reading the file; empty for database tables
     fn read_function(&self) -> &'static str {
+        if self.table.is_some() {
+            return "";
+        }
         let ext = Path::new(&self.file_path)
             .extension()
             .and_then(|e| e.to_str())
@@ -89,6 +794,155 @@ impl DuckDbInspector {
         if ext == "csv" { "read_csv_auto" } else { "read_parquet" }
     }
 
+    /// The table function call that reads the file, with overridden types if
+    /// any, limited to the first rows when sampling
+    fn source(&self) -> String {
+        let source = self.full_source();
+        match self.row_limit {
+            Some(limit) => format!("(SELECT * FROM {} LIMIT {})", source, limit),
+            None => source,
+        }
+    }
+
+    /// The read with every unnested column exploded or flattened on top
+    fn full_source(&self) -> String {
+        self.unnested.iter().fold(self.read_source(), |source, (_, select)| {
+            format!("(SELECT {} FROM {})", select, source)
+        })
+    }
+
+    fn read_source(&self) -> String {
+        if let Some(table) = &self.table {
+            return table.source();
+        }
+        let options = match self.decimal_format {
+            DecimalFormat::Comma { .. } => ", decimal_separator = ','".to_string(),
+            DecimalFormat::Point if self.encrypted => {
+                format!(", encryption_config = {{footer_key: '{}'}}", PARQUET_KEY_NAME)
+            }
+            DecimalFormat::Point => String::new(),
+        };
+        let read = if self.column_types.is_empty() {
+            format!("{}('{}'{})", self.read_function(), self.escape_path(), options)
+        } else {
+            let columns: Vec<String> = self
+                .column_types
+                .iter()
+                .map(|(name, ty)| format!("'{}': '{}'", name.replace('\'', "''"), ty.replace('\'', "''")))
+                .collect();
+            format!(
+                "read_csv('{}', header = true, columns = {{{}}}{})",
+                self.escape_path(),
+                columns.join(", "),
+                options
+            )
+        };
+        match &self.decimal_format {
+            // An overridden column is read as its own type instead of converted
+            DecimalFormat::Comma { grouped } if grouped.iter().any(|c| !self.overridden.contains(c)) => {
+                let replaced: Vec<String> = grouped
+                    .iter()
+                    .filter(|c| !self.overridden.contains(c))
+                    .map(|name| {
+                        let name = name.replace('"', "\"\"");
+                        format!("CAST(replace(replace(trim(\"{0}\"), '.', ''), ',', '.') AS DOUBLE) AS \"{0}\"", name)
+                    })
+                    .collect();
+                format!("(SELECT * REPLACE ({}) FROM {})", replaced.join(", "), read)
+            }
+            _ => read,
+        }
+    }
+
+    /// Reads only the first `limit` rows, if set: previews, filters, stats and
+    /// exports all see that sample
+    pub fn with_row_limit(mut self, limit: Option<usize>) -> Self {
+        self.row_limit = limit;
+        self
+    }
+
+    pub fn row_limit(&self) -> Option<usize> {
+        self.row_limit
+    }
+
+    /// Explodes LIST column `column` into one row per element, or flattens
+    /// STRUCT column `column` into one column per field, on top of the columns
+    /// unnested before. Everything read afterwards (counts, stats, filters,
+    /// exports) sees the unnested rows.
+    pub fn unnest_column(&mut self, column: &str) -> Result<(), DuckDbError> {
+        let schema = self.schema()?;
+        let Some((_, ty)) = schema.iter().find(|(name, _)| name == column) else {
+            return Err(DuckDbError::InvalidColumn(format!("Column '{}' not found", column)));
+        };
+        let is_list = ty.ends_with(']');
+        if !is_list && !ty.starts_with("STRUCT(") {
+            return Err(DuckDbError::InvalidColumn(format!(
+                "'{}' is {}, not a LIST or STRUCT column",
+                column, ty
+            )));
+        }
+        // Listed in schema order so the unnested column stays in place
+        let select: Vec<String> = schema
+            .iter()
+            .map(|(name, _)| {
+                let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+                match (name == column, is_list) {
+                    (true, true) => format!("unnest({0}) AS {0}", quoted),
+                    (true, false) => format!("unnest({})", quoted),
+                    (false, _) => quoted,
+                }
+            })
+            .collect();
+        self.unnested.push((column.to_string(), select.join(", ")));
+        // DESCRIBE rejects field names clashing with other columns
+        if let Err(e) = self.schema() {
+            self.unnested.pop();
+            return Err(e);
+        }
+        self.zoned = OnceLock::new();
+        tracing::debug!(file = %self.label(), column, "unnested column");
+        Ok(())
+    }
+
+    /// Unnests `columns` in order, as `unnested` listed them on another connection
+    pub fn with_unnested(mut self, columns: &[String]) -> Result<Self, DuckDbError> {
+        for column in columns {
+            self.unnest_column(column)?;
+        }
+        Ok(self)
+    }
+
+    /// Columns unnested so far, in order
+    pub fn unnested(&self) -> Vec<String> {
+        self.unnested.iter().map(|(column, _)| column.clone()).collect()
+    }
+
+    /// Undoes the last `unnest_column`
+    pub fn pop_unnest(&mut self) {
+        self.unnested.pop();
+        self.zoned = OnceLock::new();
+    }
+
+    /// Reads the columns nested again
+    pub fn clear_unnest(&mut self) {
+        self.unnested.clear();
+        self.zoned = OnceLock::new();
+    }
+
+    /// Writes Parquet conversions encrypted with the configured key
+    pub fn with_encrypted_output(mut self, encrypt: bool) -> Result<Self, DuckDbError> {
+        // An encrypted input already registered it
+        if encrypt && !self.encrypted {
+            add_parquet_key(&self.connection)?;
+        }
+        self.encrypt_output = encrypt;
+        Ok(self)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
     fn escape_path(&self) -> String {
         self.file_path.replace('\'', "''")
     }
@@ -106,14 +960,27 @@ impl DuckDbInspector {
         }
     }
 
+    /// Limits the connection to reading this file: no other files, URLs or
+    /// extension installs, and no changing that back. For queries built from
+    /// someone else's input, like `ftool serve` filters.
+    pub fn restrict_to_file(&self) -> Result<(), DuckDbError> {
+        let query = format!(
+            "SET allowed_paths = ['{}']; SET enable_external_access = false; SET lock_configuration = true;",
+            self.escape_path()
+        );
+        self.connection.execute_batch(&query).map_err(|e| {
+            DuckDbError::ConnectionError(format!("Failed to restrict file access: {}", e))
+        })
+    }
+
     /// Returns the file schema (column name + type) for CSV or Parquet files
     pub fn schema(&self) -> Result<Vec<(String, String)>, DuckDbError> {
         let query = format!(
-            "DESCRIBE SELECT * FROM {}('{}')",
-            self.read_function(),
-            self.escape_path()
+            "DESCRIBE SELECT * FROM {}",
+            self.source()
         );
 
+        let started = Instant::now();
         let mut stmt = self.connection.prepare(&query).map_err(|e| {
             DuckDbError::QueryError(format!("Failed to prepare schema query: {}", e))
         })?;
@@ -135,6 +1002,7 @@ impl DuckDbInspector {
             })?;
             schema.push(row);
         }
+        Self::log_query("schema", &query, started);
 
         if schema.is_empty() {
             return Err(DuckDbError::InvalidFileFormat(
@@ -145,54 +1013,495 @@ impl DuckDbInspector {
         Ok(schema)
     }
 
-    /// Returns the number of rows in the file (CSV or Parquet)
+    /// Returns the number of rows in the file (CSV or Parquet).
+    /// Parquet counts come from the footer metadata, so no data is scanned.
     pub fn row_count(&self) -> Result<usize, DuckDbError> {
-        self.row_count_filtered("")
+        // The footer counts rows before any list was exploded, and
+        // parquet_file_metadata can't read an encrypted one
+        if self.read_function() != "read_parquet" || !self.unnested.is_empty() || self.encrypted {
+            return self.row_count_filtered("");
+        }
+
+        let query = format!(
+            "SELECT CAST(COALESCE(SUM(num_rows), 0) AS BIGINT) FROM parquet_file_metadata('{}')",
+            self.escape_path()
+        );
+        let started = Instant::now();
+        let count: i64 = self
+            .connection
+            .query_row(&query, [], |row| row.get(0))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read row count: {}", e)))?;
+        Self::log_query("row_count", &query, started);
+        Ok(count as usize)
+    }
+
+    /// Exact row count, except for CSVs of at least `CSV_ESTIMATE_MIN_BYTES`, whose
+    /// count is extrapolated from the average size of their first lines
+    pub fn row_count_estimate(&self) -> Result<RowCount, DuckDbError> {
+        if self.table.is_some() || self.row_limit.is_some() {
+            return self.row_count().map(RowCount::Exact);
+        }
+        let file_size = std::fs::metadata(&self.file_path)
+            .map_err(|e| DuckDbError::DatabaseError(e.to_string()))?
+            .len();
+        if self.read_function() != "read_csv_auto" || file_size < CSV_ESTIMATE_MIN_BYTES {
+            return self.row_count().map(RowCount::Exact);
+        }
+
+        let estimate = estimate_csv_rows(Path::new(&self.file_path))
+            .map_err(|e| DuckDbError::DatabaseError(e.to_string()))?;
+        tracing::debug!(file = %self.file_path, %estimate, "estimated csv row count");
+        Ok(estimate)
+    }
+
+    /// A row count that doesn't scan the file: exact for Parquet (read from its
+    /// footer), samples and database tables, estimated from the first lines for
+    /// CSVs, which `row_count` can then count in the background
+    pub fn quick_row_count(&self) -> Result<RowCount, DuckDbError> {
+        if self.table.is_some() || self.row_limit.is_some() || self.read_function() != "read_csv_auto" {
+            return self.row_count().map(RowCount::Exact);
+        }
+        estimate_csv_rows(Path::new(&self.file_path)).map_err(|e| DuckDbError::DatabaseError(e.to_string()))
     }
 
     /// Returns the number of rows matching an optional WHERE clause
     pub fn row_count_filtered(&self, where_clause: &str) -> Result<usize, DuckDbError> {
         let query = format!(
-            "SELECT COUNT(*) FROM {}('{}') {}",
-            self.read_function(),
-            self.escape_path(),
+            "SELECT COUNT(*) FROM {} {}",
+            self.source(),
             where_clause,
         );
 
-        self.connection
+        let started = Instant::now();
+        let count = self
+            .connection
             .query_row(&query, [], |row| row.get(0))
-            .map_err(|e| DuckDbError::QueryError(format!("Failed to count rows: {}", e)))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to count rows: {}", e)))?;
+        Self::log_query("row_count", &query, started);
+        Ok(count)
+    }
+
+    /// Runs `where_clause` over the first `rows` rows only, to tell quickly
+    /// whether DuckDB accepts it
+    pub fn check_filter(&self, where_clause: &str, rows: usize) -> Result<(), DuckDbError> {
+        let query = format!("SELECT COUNT(*) FROM (SELECT * FROM {} LIMIT {}) {}", self.source(), rows, where_clause);
+        self.connection
+            .query_row(&query, [], |row| row.get::<_, i64>(0))
+            .map(|_| ())
+            .map_err(|e| DuckDbError::QueryError(e.to_string()))
+    }
+
+    /// Compiles `pattern` with DuckDB's regex engine, so filters can reject it before applying
+    pub fn validate_regex(&self, pattern: &str) -> Result<(), DuckDbError> {
+        let query = format!("SELECT regexp_matches('', '{}')", pattern.replace('\'', "''"));
+        self.connection
+            .query_row(&query, [], |row| row.get::<_, bool>(0))
+            .map(|_| ())
+            .map_err(|e| DuckDbError::QueryError(format!("Invalid regex '{}': {}", pattern, e)))
     }
 
     /// Returns the number of null values in a single column (used by CLI)
     pub fn null_count(&self, column_name: &str) -> Result<usize, DuckDbError> {
         let safe_column = Self::sanitize_identifier(column_name)?;
         let query = format!(
-            "SELECT COUNT(*) FROM {}('{}') WHERE {} IS NULL",
-            self.read_function(),
-            self.escape_path(),
+            "SELECT COUNT(*) FROM {} WHERE {} IS NULL",
+            self.source(),
             safe_column
         );
-        self.connection
+        let started = Instant::now();
+        let count = self
+            .connection
             .query_row(&query, [], |row| row.get(0))
             .map_err(|e| {
                 DuckDbError::QueryError(format!(
                     "Failed to count nulls in column '{}': {}",
                     column_name, e
                 ))
+            })?;
+        Self::log_query("null_count", &query, started);
+        Ok(count)
+    }
+
+    /// Averages of `ys` over `buckets` equal-width ranges of `x`, one series per y
+    /// column as (x, y) points ordered by x. Temporal x values are seconds since
+    /// the epoch; buckets where a y is all NULL are left out of its series.
+    pub fn chart_series(
+        &self,
+        x: &str,
+        x_is_temporal: bool,
+        ys: &[String],
+        where_clause: &str,
+        buckets: usize,
+    ) -> Result<Vec<Vec<(f64, f64)>>, DuckDbError> {
+        let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+        let x_expr = if x_is_temporal {
+            format!("CAST(epoch({}) AS DOUBLE)", quote(x))
+        } else {
+            format!("CAST({} AS DOUBLE)", quote(x))
+        };
+        let y_exprs: Vec<String> = ys
+            .iter()
+            .enumerate()
+            .map(|(i, y)| format!("TRY_CAST({} AS DOUBLE) AS y{}", quote(y), i))
+            .collect();
+        let y_avgs: Vec<String> = (0..ys.len()).map(|i| format!("avg(y{})", i)).collect();
+
+        let query = format!(
+            "WITH src AS (SELECT {} AS x, {} FROM {} {}), \
+             pts AS (SELECT * FROM src WHERE x IS NOT NULL), \
+             bounds AS (SELECT min(x) AS lo, max(x) AS hi FROM pts) \
+             SELECT avg(x), {} FROM pts, bounds \
+             GROUP BY LEAST(floor((x - lo) / NULLIF(hi - lo, 0) * {}), {}) ORDER BY 1",
+            x_expr,
+            y_exprs.join(", "),
+            self.source(),
+            where_clause,
+            y_avgs.join(", "),
+            buckets,
+            buckets.saturating_sub(1)
+        );
+
+        let started = Instant::now();
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare chart query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                let x: f64 = row.get(0)?;
+                let ys = (0..ys.len())
+                    .map(|i| row.get::<_, Option<f64>>(i + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((x, ys))
             })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to run chart query: {}", e)))?;
+
+        let mut series = vec![Vec::new(); ys.len()];
+        for row in rows {
+            let (x, values) =
+                row.map_err(|e| DuckDbError::QueryError(format!("Failed to read chart point: {}", e)))?;
+            for (points, y) in series.iter_mut().zip(values) {
+                if let Some(y) = y {
+                    points.push((x, y));
+                }
+            }
+        }
+        Self::log_query("chart", &query, started);
+        Ok(series)
     }
 
-    /// Returns (null_counts, min_values, max_values, mean_values) for all columns in a single query
-    pub fn column_stats(
+    /// Up to `limit` (x, y) pairs sampled from the rows where both columns are
+    /// numbers, and how many such rows there are. The sample is drawn by DuckDB
+    /// (reservoir sampling, fixed seed), so large files never reach the UI whole.
+    pub fn scatter_sample(
         &self,
-        schema: &[(String, String)],
-    ) -> Result<(Vec<usize>, Vec<String>, Vec<String>, Vec<String>), DuckDbError> {
-        // Build a single query that computes all stats for all columns
+        x: &str,
+        y: &str,
+        where_clause: &str,
+        limit: usize,
+    ) -> Result<(Vec<(f64, f64)>, usize), DuckDbError> {
+        let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+        let query = format!(
+            "WITH src AS (SELECT TRY_CAST({} AS DOUBLE) AS x, TRY_CAST({} AS DOUBLE) AS y FROM {} {}), \
+             pts AS (SELECT x, y FROM src WHERE x IS NOT NULL AND y IS NOT NULL), \
+             total AS (SELECT count(*) AS n FROM pts) \
+             SELECT s.x, s.y, total.n FROM (SELECT * FROM pts USING SAMPLE reservoir({} ROWS) REPEATABLE (42)) s, total",
+            quote(x),
+            quote(y),
+            self.source(),
+            where_clause,
+            limit
+        );
+
+        let started = Instant::now();
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare scatter query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?, row.get::<_, i64>(2)?)))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to run scatter query: {}", e)))?;
+
+        let mut points = Vec::new();
+        let mut total = 0;
+        for row in rows {
+            let (x, y, n) =
+                row.map_err(|e| DuckDbError::QueryError(format!("Failed to read scatter point: {}", e)))?;
+            points.push((x, y));
+            total = n as usize;
+        }
+        Self::log_query("scatter", &query, started);
+        Ok((points, total))
+    }
+
+    /// Reads the Parquet footer metadata of one column; fails for other formats
+    pub fn parquet_column_info(&self, column: &str) -> Result<ParquetColumnInfo, DuckDbError> {
+        if self.read_function() != "read_parquet" {
+            return Err(DuckDbError::InvalidFileFormat(
+                "Column metadata is only available for Parquet files".to_string(),
+            ));
+        }
+        if self.encrypted {
+            return Err(DuckDbError::InvalidFileFormat(
+                "Column metadata can't be read from an encrypted Parquet footer".to_string(),
+            ));
+        }
+        let path = self.escape_path();
+        let name = column.replace('\'', "''");
+
+        let schema_query = format!(
+            "SELECT CAST(type AS VARCHAR), CAST(logical_type AS VARCHAR), CAST(converted_type AS VARCHAR), \
+             CAST(field_id AS VARCHAR), CAST(repetition_type AS VARCHAR) \
+             FROM parquet_schema('{}') WHERE name = '{}' LIMIT 1",
+            path, name
+        );
+        let started = Instant::now();
+        let mut info = self
+            .connection
+            .query_row(&schema_query, [], |row| {
+                Ok(ParquetColumnInfo {
+                    physical_type: row.get(0)?,
+                    logical_type: row.get(1)?,
+                    converted_type: row.get(2)?,
+                    field_id: row.get(3)?,
+                    repetition: row.get(4)?,
+                    ..Default::default()
+                })
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read Parquet schema: {}", e)))?;
+        Self::log_query("parquet_schema", &schema_query, started);
+
+        // One metadata row per row group and column chunk
+        let metadata_query = format!(
+            "SELECT string_agg(DISTINCT CAST(compression AS VARCHAR), ', '), \
+             string_agg(DISTINCT CAST(encodings AS VARCHAR), ', '), \
+             CAST(SUM(total_compressed_size) AS BIGINT), CAST(SUM(total_uncompressed_size) AS BIGINT) \
+             FROM parquet_metadata('{}') WHERE path_in_schema = '{}'",
+            path, name
+        );
+        let started = Instant::now();
+        self.connection
+            .query_row(&metadata_query, [], |row| {
+                info.compression = row.get(0)?;
+                info.encodings = row.get(1)?;
+                info.compressed_bytes = row.get(2)?;
+                info.uncompressed_bytes = row.get(3)?;
+                Ok(())
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read Parquet metadata: {}", e)))?;
+        Self::log_query("parquet_metadata", &metadata_query, started);
+
+        Ok(info)
+    }
+
+    /// Lists the row groups of a Parquet file (or every file of a glob) in read
+    /// order, with their sizes summed over column chunks; fails for other formats
+    pub fn parquet_row_groups(&self) -> Result<Vec<RowGroupInfo>, DuckDbError> {
+        if self.read_function() != "read_parquet" {
+            return Err(DuckDbError::InvalidFileFormat(
+                "Row groups are only available for Parquet files".to_string(),
+            ));
+        }
+        if self.encrypted {
+            return Err(DuckDbError::InvalidFileFormat(
+                "Row groups can't be read from an encrypted Parquet footer".to_string(),
+            ));
+        }
+        let query = format!(
+            "SELECT file_name, CAST(row_group_id AS BIGINT), CAST(ANY_VALUE(row_group_num_rows) AS BIGINT), \
+             CAST(SUM(total_compressed_size) AS BIGINT), CAST(SUM(total_uncompressed_size) AS BIGINT) \
+             FROM parquet_metadata('{}') GROUP BY file_name, row_group_id ORDER BY file_name, row_group_id",
+            self.escape_path()
+        );
+        let started = Instant::now();
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare row group query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read Parquet metadata: {}", e)))?;
+
+        let mut groups = Vec::new();
+        let mut first_row = 0;
+        for row in rows {
+            let (file, id, rows, compressed, uncompressed) =
+                row.map_err(|e| DuckDbError::QueryError(format!("Failed to read row group: {}", e)))?;
+            groups.push(RowGroupInfo {
+                id: id as usize,
+                file,
+                rows: rows as usize,
+                first_row,
+                compressed_bytes: compressed as u64,
+                uncompressed_bytes: uncompressed as u64,
+            });
+            first_row += rows as usize;
+        }
+        Self::log_query("row_groups", &query, started);
+        Ok(groups)
+    }
+
+    /// Finds columns holding geometries: `GEOMETRY` columns, latitude/longitude
+    /// pairs by name, and text or BLOB columns whose first non-null values (in
+    /// a sample of `GEO_SAMPLE_ROWS` rows) are all WKT, GeoJSON or WKB
+    pub fn geo_columns(&self, schema: &[(String, String)]) -> Result<Vec<GeoColumn>, DuckDbError> {
+        let mut found: Vec<GeoColumn> = schema
+            .iter()
+            .filter(|(_, ty)| ty == "GEOMETRY")
+            .map(|(name, _)| GeoColumn { encoding: GeoEncoding::Geometry, columns: vec![name.clone()] })
+            .collect();
+
+        let numeric: Vec<String> = schema
+            .iter()
+            .filter(|(_, ty)| is_numeric_type(ty))
+            .map(|(name, _)| name.clone())
+            .collect();
+        found.extend(geo::lat_lon_pairs(&numeric).into_iter().map(|(lat, lon)| GeoColumn {
+            encoding: GeoEncoding::LatLon,
+            columns: vec![lat, lon],
+        }));
+
+        let candidates: Vec<&(String, String)> =
+            schema.iter().filter(|(_, ty)| ty == "VARCHAR" || ty == "BLOB").collect();
+        if candidates.is_empty() {
+            return Ok(found);
+        }
+        let exprs: Vec<String> = candidates
+            .iter()
+            .map(|(name, ty)| {
+                let col = format!("\"{}\"", name.replace('"', "\"\""));
+                if ty == "BLOB" { format!("hex({})", col) } else { col }
+            })
+            .collect();
+        let query = format!("SELECT {} FROM {} LIMIT {}", exprs.join(", "), self.source(), GEO_SAMPLE_ROWS);
+        let started = Instant::now();
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare geo sample: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                (0..candidates.len()).map(|i| row.get::<_, Option<String>>(i)).collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to sample columns: {}", e)))?;
+
+        let classify = |value: &str, blob: bool| {
+            if blob {
+                geo::is_wkb_hex(value).then_some(GeoEncoding::Wkb)
+            } else if geo::is_wkt(value) {
+                Some(GeoEncoding::Wkt)
+            } else if geo::is_geojson(value) {
+                Some(GeoEncoding::GeoJson)
+            } else {
+                None
+            }
+        };
+        // Per candidate: the encoding of its first value, kept while the others match
+        let mut encodings: Vec<Option<GeoEncoding>> = vec![None; candidates.len()];
+        let mut seen = vec![false; candidates.len()];
+        for row in rows {
+            let row = row.map_err(|e| DuckDbError::QueryError(format!("Failed to read sample row: {}", e)))?;
+            for (i, value) in row.iter().enumerate() {
+                let Some(value) = value.as_deref().filter(|v| !v.trim().is_empty()) else {
+                    continue;
+                };
+                let encoding = classify(value, candidates[i].1 == "BLOB");
+                encodings[i] = if seen[i] { encodings[i].take().filter(|e| Some(e) == encoding.as_ref()) } else { encoding };
+                seen[i] = true;
+            }
+        }
+        Self::log_query("geo_sample", &query, started);
+
+        for ((name, _), encoding) in candidates.iter().zip(encodings) {
+            if let Some(encoding) = encoding {
+                found.push(GeoColumn { encoding, columns: vec![name.clone()] });
+            }
+        }
+        Ok(found)
+    }
+
+    /// Loads DuckDB's spatial extension, installing it first if needed
+    fn load_spatial(&self) -> Result<(), DuckDbError> {
+        self.connection.execute_batch("INSTALL spatial; LOAD spatial;").map_err(|e| {
+            DuckDbError::DatabaseError(format!("The DuckDB spatial extension is needed for this: {}", e))
+        })
+    }
+
+    /// A sample of up to `limit` (x, y) points of `geo`, one per row (the
+    /// centroid for lines and polygons), with the number of rows that have one
+    pub fn geo_points(&self, geo: &GeoColumn, limit: usize) -> Result<(Vec<(f64, f64)>, usize), DuckDbError> {
+        if geo.needs_spatial() {
+            self.load_spatial()?;
+        }
+        let (x, y) = geo.point_exprs();
+        let query = format!(
+            "WITH src AS (SELECT {} AS x, {} AS y FROM {}), \
+             pts AS (SELECT x, y FROM src WHERE x IS NOT NULL AND y IS NOT NULL), \
+             total AS (SELECT count(*) AS n FROM pts) \
+             SELECT s.x, s.y, total.n FROM (SELECT * FROM pts USING SAMPLE reservoir({} ROWS) REPEATABLE (42)) s, total",
+            x,
+            y,
+            self.source(),
+            limit
+        );
+        let started = Instant::now();
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare map query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?, row.get::<_, i64>(2)?)))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read points: {}", e)))?;
+        let mut points = Vec::new();
+        let mut total = 0;
+        for row in rows {
+            let (x, y, n) = row.map_err(|e| DuckDbError::QueryError(format!("Failed to read point: {}", e)))?;
+            points.push((x, y));
+            total = n as usize;
+        }
+        Self::log_query("geo_points", &query, started);
+        Ok((points, total))
+    }
+
+    /// Writes the file as a GeoJSON FeatureCollection next to it, with `geo` as
+    /// each feature's geometry and the other columns as its properties.
+    /// Returns the output path.
+    pub fn export_geojson(&self, geo: &GeoColumn) -> Result<String, DuckDbError> {
+        self.load_spatial()?;
+        let output = self.local_path().with_extension("geojson").to_string_lossy().to_string();
+        let excluded: Vec<String> =
+            geo.columns.iter().map(|c| format!("\"{}\"", c.replace('"', "\"\""))).collect();
+        let (pending, temp) = prepare_write(&output);
+        let query = format!(
+            "COPY (SELECT * EXCLUDE ({}), {} AS geometry FROM {}) TO '{}' WITH (FORMAT GDAL, DRIVER 'GeoJSON')",
+            excluded.join(", "),
+            geo.geometry_expr(),
+            self.source(),
+            temp
+        );
+        let started = Instant::now();
+        self.connection
+            .execute(&query, [])
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to write GeoJSON: {}", e)))?;
+        finish_write(pending)?;
+        Self::log_query("export_geojson", &query, started);
+        Ok(output)
+    }
+
+    /// Computes null count, min, max and mean for `columns` in a single scan
+    pub fn column_stats(&self, columns: &[String]) -> Result<Vec<ColumnStats>, DuckDbError> {
         // For each column: null_count, min, max, mean
         let mut select_parts = vec!["COUNT(*) AS total_rows".to_string()];
 
-        for (name, _) in schema {
+        for name in columns {
             let safe = name.replace('"', "\"\"");
             select_parts.push(format!("COUNT(\"{}\")", safe));
             select_parts.push(format!("CAST(MIN(\"{}\") AS VARCHAR)", safe));
@@ -204,37 +1513,32 @@ impl DuckDbInspector {
         }
 
         let query = format!(
-            "SELECT {} FROM {}('{}')",
+            "SELECT {} FROM {}",
             select_parts.join(", "),
-            self.read_function(),
-            self.escape_path()
+            self.source()
         );
 
+        let started = Instant::now();
         let mut stmt = self.connection.prepare(&query).map_err(|e| {
             DuckDbError::QueryError(format!("Failed to prepare column stats query: {}", e))
         })?;
 
-        let mut null_counts = Vec::with_capacity(schema.len());
-        let mut min_values = Vec::with_capacity(schema.len());
-        let mut max_values = Vec::with_capacity(schema.len());
-        let mut mean_values = Vec::with_capacity(schema.len());
+        let mut stats = Vec::with_capacity(columns.len());
 
         stmt.query_row([], |row| {
             let total_rows: usize = row.get(0)?;
+            let text = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
 
-            for i in 0..schema.len() {
+            for i in 0..columns.len() {
                 let base = 1 + i * 4;
                 let nonnull: usize = row.get(base)?;
-                null_counts.push(total_rows - nonnull);
-
-                let min_val: Option<String> = row.get(base + 1)?;
-                min_values.push(min_val.unwrap_or_else(|| "-".to_string()));
-
-                let max_val: Option<String> = row.get(base + 2)?;
-                max_values.push(max_val.unwrap_or_else(|| "-".to_string()));
-
-                let mean_val: Option<String> = row.get(base + 3)?;
-                mean_values.push(mean_val.unwrap_or_else(|| "-".to_string()));
+                stats.push(ColumnStats {
+                    null_count: total_rows - nonnull,
+                    min: text(row.get(base + 1)?),
+                    max: text(row.get(base + 2)?),
+                    mean: text(row.get(base + 3)?),
+                    histogram: Vec::new(),
+                });
             }
 
             Ok(())
@@ -242,8 +1546,227 @@ impl DuckDbInspector {
         .map_err(|e| {
             DuckDbError::QueryError(format!("Failed to execute column stats query: {}", e))
         })?;
+        Self::log_query("column_stats", &query, started);
+
+        Ok(stats)
+    }
+
+    /// Row counts of each column over `buckets` equal-width ranges between its
+    /// min and max, in one scan. A column holding a single value has every row
+    /// in the first bucket; NULLs and non-numeric values are not counted.
+    pub fn histograms(&self, columns: &[String], buckets: usize) -> Result<Vec<Vec<usize>>, DuckDbError> {
+        if columns.is_empty() || buckets == 0 {
+            return Ok(vec![Vec::new(); columns.len()]);
+        }
+        let values: Vec<String> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, name)| format!("TRY_CAST(\"{}\" AS DOUBLE) AS c{}", name.replace('"', "\"\""), i))
+            .collect();
+        let bounds: Vec<String> = (0..columns.len())
+            .map(|i| format!("min(c{0}) AS lo{0}, max(c{0}) AS hi{0}", i))
+            .collect();
+        let counts: Vec<String> = (0..columns.len())
+            .flat_map(|i| {
+                (0..buckets).map(move |b| {
+                    format!(
+                        "count_if(c{0} IS NOT NULL AND COALESCE(LEAST(floor((c{0} - lo{0}) / NULLIF(hi{0} - lo{0}, 0) * {1}), {2}), 0) = {3})",
+                        i,
+                        buckets,
+                        buckets - 1,
+                        b
+                    )
+                })
+            })
+            .collect();
+
+        let query = format!(
+            "WITH src AS (SELECT {} FROM {}), bounds AS (SELECT {} FROM src) SELECT {} FROM src, bounds",
+            values.join(", "),
+            self.source(),
+            bounds.join(", "),
+            counts.join(", ")
+        );
 
-        Ok((null_counts, min_values, max_values, mean_values))
+        let started = Instant::now();
+        let histograms = self
+            .connection
+            .query_row(&query, [], |row| {
+                (0..columns.len())
+                    .map(|i| {
+                        (0..buckets)
+                            .map(|b| row.get::<_, i64>(i * buckets + b).map(|n| n as usize))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to compute histograms: {}", e)))?;
+        Self::log_query("histograms", &query, started);
+        Ok(histograms)
+    }
+
+    /// Returns the `limit` most frequent values of a column with their counts (NULLs excluded)
+    pub fn top_values(&self, column: &str, limit: usize) -> Result<Vec<(String, usize)>, DuckDbError> {
+        let safe = column.replace('"', "\"\"");
+        let query = format!(
+            "SELECT CAST(\"{0}\" AS VARCHAR), COUNT(*) AS n FROM {1} WHERE \"{0}\" IS NOT NULL GROUP BY 1 ORDER BY n DESC, 1 LIMIT {2}",
+            safe,
+            self.source(),
+            limit
+        );
+
+        let started = Instant::now();
+        let mut stmt = self.connection.prepare(&query).map_err(|e| {
+            DuckDbError::QueryError(format!("Failed to prepare top values query: {}", e))
+        })?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                let value: String = row.get(0)?;
+                let count: usize = row.get(1)?;
+                Ok((value, count))
+            })
+            .map_err(|e| {
+                DuckDbError::QueryError(format!("Failed to execute top values query: {}", e))
+            })?;
+
+        let mut values = Vec::new();
+        for row_result in rows {
+            values.push(row_result.map_err(|e| {
+                DuckDbError::QueryError(format!("Failed to read top values row: {}", e))
+            })?);
+        }
+        Self::log_query("top_values", &query, started);
+
+        Ok(values)
+    }
+
+    /// Profiles every column: counts, approximate distinct values, min/max/mean,
+    /// the most frequent values and, for numeric columns, a histogram
+    pub fn profile(&self) -> Result<Vec<ColumnProfile>, DuckDbError> {
+        let schema = self.schema()?;
+
+        let mut select_parts = vec!["COUNT(*)".to_string()];
+        for (name, _) in &schema {
+            let safe = name.replace('"', "\"\"");
+            select_parts.push(format!("COUNT(\"{}\")", safe));
+            select_parts.push(format!("approx_count_distinct(\"{}\")", safe));
+            select_parts.push(format!("CAST(MIN(\"{}\") AS VARCHAR)", safe));
+            select_parts.push(format!("CAST(MAX(\"{}\") AS VARCHAR)", safe));
+            select_parts.push(format!("AVG(TRY_CAST(\"{}\" AS DOUBLE))", safe));
+        }
+        let query = format!(
+            "SELECT {} FROM {}",
+            select_parts.join(", "),
+            self.source()
+        );
+
+        let started = Instant::now();
+        let mut profiles = Vec::with_capacity(schema.len());
+        self.connection
+            .query_row(&query, [], |row| {
+                let total_rows: usize = row.get(0)?;
+                for (i, (name, data_type)) in schema.iter().enumerate() {
+                    let base = 1 + i * 5;
+                    let count: usize = row.get(base)?;
+                    let distinct: usize = row.get(base + 1)?;
+                    let min: Option<String> = row.get(base + 2)?;
+                    let max: Option<String> = row.get(base + 3)?;
+                    let mean: Option<f64> = row.get(base + 4)?;
+                    profiles.push(ColumnProfile {
+                        name: name.clone(),
+                        data_type: data_type.clone(),
+                        count,
+                        nulls: total_rows - count,
+                        distinct,
+                        min,
+                        max,
+                        mean,
+                        top_values: Vec::new(),
+                        histogram: Vec::new(),
+                    });
+                }
+                Ok(())
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to profile columns: {}", e)))?;
+        Self::log_query("profile", &query, started);
+
+        for column in &mut profiles {
+            column.top_values = self
+                .top_values(&column.name, TOP_VALUES)?
+                .into_iter()
+                .map(|(value, count)| ValueCount { value, count })
+                .collect();
+            if is_numeric_type(&column.data_type) {
+                column.histogram = self.histogram(column)?;
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    /// Equal-width buckets between the column's min and max
+    fn histogram(&self, column: &ColumnProfile) -> Result<Vec<HistogramBucket>, DuckDbError> {
+        let bounds = column
+            .min
+            .as_deref()
+            .and_then(|v| v.parse::<f64>().ok())
+            .zip(column.max.as_deref().and_then(|v| v.parse::<f64>().ok()));
+        let Some((min, max)) = bounds.filter(|(min, max)| min.is_finite() && max.is_finite()) else {
+            return Ok(Vec::new());
+        };
+        if min == max {
+            return Ok(vec![HistogramBucket {
+                lower: min,
+                upper: max,
+                count: column.count,
+            }]);
+        }
+
+        let width = (max - min) / HISTOGRAM_BUCKETS as f64;
+        let safe = column.name.replace('"', "\"\"");
+        let query = format!(
+            "SELECT LEAST(CAST(floor((CAST(\"{0}\" AS DOUBLE) - {1}) / {2}) AS BIGINT), {3}) AS bucket, COUNT(*) \
+             FROM {4} WHERE \"{0}\" IS NOT NULL GROUP BY 1 ORDER BY 1",
+            safe,
+            min,
+            width,
+            HISTOGRAM_BUCKETS - 1,
+            self.source()
+        );
+
+        let started = Instant::now();
+        let mut stmt = self.connection.prepare(&query).map_err(|e| {
+            DuckDbError::QueryError(format!("Failed to prepare histogram query: {}", e))
+        })?;
+        let rows = stmt
+            .query_map([], |row| {
+                let bucket: i64 = row.get(0)?;
+                let count: usize = row.get(1)?;
+                Ok((bucket, count))
+            })
+            .map_err(|e| {
+                DuckDbError::QueryError(format!("Failed to execute histogram query: {}", e))
+            })?;
+
+        let mut buckets: Vec<HistogramBucket> = (0..HISTOGRAM_BUCKETS)
+            .map(|i| HistogramBucket {
+                lower: min + width * i as f64,
+                upper: min + width * (i + 1) as f64,
+                count: 0,
+            })
+            .collect();
+        for row_result in rows {
+            let (bucket, count) = row_result.map_err(|e| {
+                DuckDbError::QueryError(format!("Failed to read histogram row: {}", e))
+            })?;
+            if let Some(b) = buckets.get_mut(bucket.max(0) as usize) {
+                b.count += count;
+            }
+        }
+        Self::log_query("histogram", &query, started);
+
+        Ok(buckets)
     }
 
     /// Returns a preview of rows as (headers, rows_of_strings), with optional WHERE clause.
@@ -254,33 +1777,20 @@ impl DuckDbInspector {
         offset: usize,
         where_clause: &str,
         columns: Option<&[String]>,
-    ) -> Result<(Vec<String>, Vec<Vec<String>>), DuckDbError> {
-        let headers: Vec<String> = if let Some(cols) = columns {
+        computed: &[ComputedColumn],
+    ) -> Result<(Vec<String>, Vec<Vec<Option<String>>>), DuckDbError> {
+        let mut headers: Vec<String> = if let Some(cols) = columns {
             cols.to_vec()
         } else {
             let schema = self.schema()?;
             schema.into_iter().map(|(name, _)| name).collect()
         };
 
-        // Cast all columns to VARCHAR, replacing NULLs with the string "NULL"
-        let columns: Vec<String> = headers
-            .iter()
-            .map(|name| {
-                let escaped = name.replace('"', "\"\"");
-                format!("COALESCE(CAST(\"{}\" AS VARCHAR), 'NULL')", escaped)
-            })
-            .collect();
-
-        let query = format!(
-            "SELECT {} FROM {}('{}') {} LIMIT {} OFFSET {}",
-            columns.join(", "),
-            self.read_function(),
-            self.escape_path(),
-            where_clause,
-            limit,
-            offset
-        );
+        let zoned = self.naive_timestamp_columns()?;
+        let query = self.preview_query(&headers, computed, &zoned, limit, offset, where_clause);
+        headers.extend(computed.iter().map(|c| c.name.clone()));
 
+        let started = Instant::now();
         let mut stmt = self.connection.prepare(&query).map_err(|e| {
             DuckDbError::QueryError(format!("Failed to prepare preview query: {}", e))
         })?;
@@ -292,7 +1802,7 @@ impl DuckDbInspector {
             .query_map([], |row| {
                 let mut values = Vec::with_capacity(column_count);
                 for i in 0..column_count {
-                    let val: String = row.get(i)?;
+                    let val: Option<String> = row.get(i)?;
                     values.push(val);
                 }
                 Ok(values)
@@ -306,13 +1816,133 @@ impl DuckDbInspector {
                 DuckDbError::QueryError(format!("Failed to read preview row: {}", e))
             })?);
         }
+        Self::log_query("preview", &query, started);
 
         Ok((headers, result))
     }
 
-    /// Converts the parquet file to CSV or Parquet, depending on the target format
-    pub fn convert(&self, target_format: &str) -> Result<String, DuckDbError> {
-        let path = Path::new(&self.file_path);
+    /// The preview SELECT; `zoned` TIMESTAMP columns are read as UTC and shown
+    /// in the connection's time zone
+    fn preview_query(
+        &self,
+        headers: &[String],
+        computed: &[ComputedColumn],
+        zoned: &[String],
+        limit: usize,
+        offset: usize,
+        where_clause: &str,
+    ) -> String {
+        // Cast all columns to VARCHAR; NULLs stay NULL so they can be told apart from text
+        let columns: Vec<String> = headers
+            .iter()
+            .map(|name| {
+                let column = format!("\"{}\"", name.replace('"', "\"\""));
+                if zoned.contains(name) {
+                    format!("timezone('UTC', {}::TIMESTAMP)", column)
+                } else {
+                    column
+                }
+            })
+            .chain(computed.iter().map(|c| format!("({})", c.expr)))
+            .map(|expr| format!("CAST({} AS VARCHAR)", expr))
+            .collect();
+
+        format!(
+            "SELECT {} FROM {} {} LIMIT {} OFFSET {}",
+            columns.join(", "),
+            self.source(),
+            where_clause,
+            limit,
+            offset
+        )
+    }
+
+    /// Returns DuckDB's physical plan for the preview query `preview` would run
+    pub fn explain_preview(
+        &self,
+        limit: usize,
+        offset: usize,
+        where_clause: &str,
+        columns: &[String],
+        computed: &[ComputedColumn],
+    ) -> Result<String, DuckDbError> {
+        let zoned = self.naive_timestamp_columns()?;
+        let preview = self.preview_query(columns, computed, &zoned, limit, offset, where_clause);
+        let query = format!("EXPLAIN {}", preview);
+
+        let started = Instant::now();
+        let mut stmt = self.connection.prepare(&query).map_err(|e| {
+            DuckDbError::QueryError(format!("Failed to prepare explain query: {}", e))
+        })?;
+        // EXPLAIN returns (explain_key, explain_value) rows; the value holds the rendered plan
+        let plans = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to explain query: {}", e)))?;
+
+        let mut plan = String::new();
+        for row in plans {
+            let text = row.map_err(|e| {
+                DuckDbError::QueryError(format!("Failed to read query plan: {}", e))
+            })?;
+            plan.push_str(&text);
+        }
+        Self::log_query("explain", &query, started);
+
+        Ok(plan)
+    }
+
+    /// Converts the file, applying `transform` (select/rename/cast/drop) to its columns.
+    /// With `partition_by`, writes a Hive-partitioned directory (`<stem>_partitioned/col=value/...`)
+    /// instead of a single file; the columns are named as they appear in the output.
+    pub fn convert_with(
+        &self,
+        target_format: &str,
+        transform: &Transform,
+        partition_by: &[String],
+    ) -> Result<String, DuckDbError> {
+        let Some(plan) = self.convert_query(target_format, transform, partition_by)? else {
+            return Ok(self.file_path.clone());
+        };
+        self.run_copy(&plan)
+    }
+
+    /// Runs a plan from `convert_query`, whose target may have been changed,
+    /// through a temp file. Returns the path written.
+    pub fn run_copy(&self, plan: &CopyPlan) -> Result<String, DuckDbError> {
+        let target_path = plan.target.clone();
+
+        let pending = if plan.tracked {
+            backup::prepare(Path::new(&target_path))
+        } else {
+            backup::prepare_untracked(Path::new(&target_path))
+        };
+        let query = plan.statement(&pending.temp().to_string_lossy());
+        let started = Instant::now();
+        self.connection
+            .execute(&query, [])
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to convert file: {}", e)))?;
+        finish_write(pending)?;
+        Self::log_query("convert", &query, started);
+        tracing::info!(
+            from = %self.label(),
+            to = %target_path,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "converted file"
+        );
+        run_hook(HookEvent::Convert, &target_path)?;
+
+        Ok(target_path)
+    }
+
+    /// The COPY statement `convert_with` runs, without running it; `None` when
+    /// the file already is in `target_format` and nothing changes
+    pub fn convert_query(
+        &self,
+        target_format: &str,
+        transform: &Transform,
+        partition_by: &[String],
+    ) -> Result<Option<CopyPlan>, DuckDbError> {
+        let path = self.local_path();
         let ext = path.extension().unwrap_or_default();
 
         if !["csv", "parquet"].contains(&target_format) {
@@ -321,14 +1951,29 @@ impl DuckDbInspector {
             ));
         }
 
-        if ext == target_format {
-            return Ok(self.file_path.clone());
+        if ext == target_format && transform.is_empty() && partition_by.is_empty() {
+            return Ok(None);
         }
 
-        let target_path = path
-            .with_extension(target_format)
-            .to_string_lossy()
-            .to_string();
+        // Don't overwrite the source when only transforming
+        let target_path = if !partition_by.is_empty() {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            path.with_file_name(format!("{}_partitioned", stem))
+        } else if ext == target_format {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            path.with_file_name(format!("{}_transformed.{}", stem, target_format))
+        } else {
+            path.with_extension(target_format)
+        }
+        .to_string_lossy()
+        .to_string();
+
+        let columns: Vec<String> = if transform.is_empty() {
+            Vec::new()
+        } else {
+            self.schema()?.into_iter().map(|(name, _)| name).collect()
+        };
+        let select_list = transform.select_list(&columns)?;
 
         let format_str = if target_format == "csv" {
             "CSV"
@@ -336,17 +1981,853 @@ impl DuckDbInspector {
             "PARQUET"
         };
 
-        let query = format!(
-            "COPY (SELECT * FROM '{}') TO '{}' (FORMAT {})",
-            self.escape_path(),
-            target_path.replace('\'', "''"),
-            format_str
+        let encryption_clause = if self.encrypt_output && target_format == "parquet" {
+            if crate::config::get().parquet_key().is_none() {
+                return Err(DuckDbError::InvalidFileFormat(
+                    "Encrypting needs a key: pass --parquet-key or set parquet_key in the config".to_string(),
+                ));
+            }
+            format!(", ENCRYPTION_CONFIG {{footer_key: '{}'}}", PARQUET_KEY_NAME)
+        } else {
+            String::new()
+        };
+
+        let partition_clause = if partition_by.is_empty() {
+            String::new()
+        } else {
+            let cols: Vec<String> = partition_by
+                .iter()
+                .map(|c| format!("\"{}\"", c.replace('"', "\"\"")))
+                .collect();
+            // The temp directory `run_copy` writes to is new; this only keeps
+            // DuckDB from refusing a directory left by an interrupted run
+            format!(", PARTITION_BY ({}), OVERWRITE_OR_IGNORE", cols.join(", "))
+        };
+
+        Ok(Some(CopyPlan {
+            target: target_path,
+            tracked: true,
+            select: format!("SELECT {} FROM {}", select_list, self.source()),
+            options: format!("FORMAT {}{}{}", format_str, partition_clause, encryption_clause),
+        }))
+    }
+
+    /// Column names and types of an arbitrary SELECT
+    fn describe(&self, select: &str) -> Result<Vec<(String, String)>, DuckDbError> {
+        let query = format!("DESCRIBE {}", select);
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to describe query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to describe query: {}", e)))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read column: {}", e)))
+    }
+
+    /// Converts the file (with `transform` applied) to `<stem>.xlsx`, continuing on a
+    /// new "Data N" sheet every `options.sheet_rows` rows. Returns the output path.
+    pub fn export_xlsx(&self, transform: &Transform, options: &ExcelOptions) -> Result<String, DuckDbError> {
+        let target_path = self.local_path().with_extension("xlsx").to_string_lossy().to_string();
+        let xlsx_error = |e: rust_xlsxwriter::XlsxError| {
+            DuckDbError::DatabaseError(format!("Failed to write {}: {}", target_path, e))
+        };
+        let sheet_rows = options.sheet_rows.clamp(1, MAX_SHEET_ROWS);
+
+        let (query, headers, readers) = self.xlsx_query(transform)?;
+
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let bold = rust_xlsxwriter::Format::new().set_bold();
+        let add_sheet = |workbook: &mut rust_xlsxwriter::Workbook, n: usize| {
+            let sheet = workbook.add_worksheet_with_constant_memory();
+            sheet.set_name(if n == 1 { "Data".to_string() } else { format!("Data {}", n) })?;
+            for (col, header) in headers.iter().enumerate() {
+                sheet.write_string_with_format(0, col as u16, header, &bold)?;
+            }
+            sheet.set_freeze_panes(1, 0)?;
+            Ok::<_, rust_xlsxwriter::XlsxError>(())
+        };
+
+        let started = Instant::now();
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare export query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| readers.iter().enumerate().map(|(i, read)| read(row, i)).collect::<Result<Vec<_>, _>>())
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to export file: {}", e)))?;
+
+        let mut sheets = 1;
+        add_sheet(&mut workbook, sheets).map_err(xlsx_error)?;
+        for (n, row) in rows.enumerate() {
+            let row = row.map_err(|e| DuckDbError::QueryError(format!("Failed to read row: {}", e)))?;
+            if n > 0 && n % sheet_rows == 0 {
+                sheets += 1;
+                add_sheet(&mut workbook, sheets).map_err(xlsx_error)?;
+            }
+            let sheet = workbook.worksheet_from_index(sheets - 1).map_err(xlsx_error)?;
+            let line = (n % sheet_rows + 1) as u32;
+            for (col, cell) in row.into_iter().enumerate() {
+                let col = col as u16;
+                let written = match cell {
+                    XlsxCell::Empty => continue,
+                    XlsxCell::Number(v) => sheet.write_number(line, col, v),
+                    XlsxCell::Bool(v) => sheet.write_boolean(line, col, v),
+                    XlsxCell::Text(v) => sheet.write_string(line, col, v),
+                };
+                written.map_err(xlsx_error)?;
+            }
+        }
+        Self::log_query("export_xlsx", &query, started);
+
+        if options.summary {
+            let source_schema = self.schema()?;
+            let columns: Vec<String> = source_schema.iter().map(|(name, _)| name.clone()).collect();
+            let stats = self.column_stats(&columns)?;
+            let sheet = workbook.add_worksheet();
+            sheet.set_name("Summary").map_err(xlsx_error)?;
+            for (col, header) in ["Column", "Type", "Nulls", "Min", "Max", "Avg"].iter().enumerate() {
+                sheet.write_string_with_format(0, col as u16, *header, &bold).map_err(xlsx_error)?;
+            }
+            for (i, ((name, ty), stat)) in source_schema.iter().zip(&stats).enumerate() {
+                let line = i as u32 + 1;
+                sheet.write_string(line, 0, name).map_err(xlsx_error)?;
+                sheet.write_string(line, 1, ty).map_err(xlsx_error)?;
+                sheet.write_number(line, 2, stat.null_count as f64).map_err(xlsx_error)?;
+                sheet.write_string(line, 3, &stat.min).map_err(xlsx_error)?;
+                sheet.write_string(line, 4, &stat.max).map_err(xlsx_error)?;
+                sheet.write_string(line, 5, &stat.mean).map_err(xlsx_error)?;
+            }
+        }
+
+        let pending = backup::prepare(Path::new(&target_path));
+        workbook.save(pending.temp()).map_err(xlsx_error)?;
+        finish_write(pending)?;
+        tracing::info!(
+            from = %self.label(),
+            to = %target_path,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "converted file"
+        );
+        run_hook(HookEvent::Convert, &target_path)?;
+        Ok(target_path)
+    }
+
+    /// The output path and the query `export_xlsx` streams into the workbook,
+    /// without running it
+    pub fn xlsx_plan(&self, transform: &Transform) -> Result<(String, String), DuckDbError> {
+        let target_path = self.local_path().with_extension("xlsx").to_string_lossy().to_string();
+        let (query, _, _) = self.xlsx_query(transform)?;
+        Ok((target_path, query))
+    }
+
+    /// The query of `export_xlsx`, with each output column cast to what a cell
+    /// can hold, its headers and the readers of its cells
+    fn xlsx_query(&self, transform: &Transform) -> Result<(String, Vec<String>, Vec<XlsxReader>), DuckDbError> {
+        let columns: Vec<String> = self.schema()?.into_iter().map(|(name, _)| name).collect();
+        let new_rows = format!("SELECT {} FROM {}", transform.select_list(&columns)?, self.source());
+        let output_schema = self.describe(&new_rows)?;
+        let (casts, readers): (Vec<String>, Vec<XlsxReader>) =
+            output_schema.iter().map(|(name, ty)| xlsx_cast(name, ty)).unzip();
+        let headers = output_schema.into_iter().map(|(name, _)| name).collect();
+        Ok((format!("SELECT {} FROM ({})", casts.join(", "), new_rows), headers, readers))
+    }
+
+    /// Bulk-loads the file into a Postgres table through DuckDB's postgres
+    /// extension (installed on first use). The table is created from
+    /// `load_ddl`, so its column types are the ones `ftool load --dry-run`
+    /// prints; appending matches columns by name. Replacing loads a staging
+    /// table swapped in by one transaction, so a failed load keeps the old
+    /// table. Returns the rows inserted.
+    pub fn load_postgres(&self, url: &str, table: &TableName, mode: LoadMode) -> Result<usize, DuckDbError> {
+        let columns = self.schema()?;
+        self.connection.execute_batch("INSTALL postgres; LOAD postgres;").map_err(|e| {
+            DuckDbError::DatabaseError(format!("The DuckDB postgres extension is needed for this: {}", e))
+        })?;
+        // Not logged: the URL may hold a password
+        let attach = format!("ATTACH '{}' AS pg (TYPE postgres)", url.replace('\'', "''"));
+        self.connection
+            .execute_batch(&attach)
+            .map_err(|e| DuckDbError::ConnectionError(format!("Failed to connect to Postgres: {}", e)))?;
+        let execute = |statement: &str| {
+            let query = format!("CALL postgres_execute('pg', '{}')", statement.replace('\'', "''"));
+            self.connection
+                .execute_batch(&query)
+                .map_err(|e| DuckDbError::QueryError(format!("Failed to run {}: {}", statement, e)))
+        };
+
+        let started = Instant::now();
+        for statement in load_ddl(table, &columns, mode) {
+            execute(&statement)?;
+        }
+        // DuckDB caches the Postgres catalog, which doesn't have the new table yet
+        self.connection
+            .execute_batch("CALL pg_clear_cache()")
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to refresh the Postgres catalog: {}", e)))?;
+        let target = load_target(table, mode);
+        let query = format!("INSERT INTO {} BY NAME SELECT * FROM {}", target.in_catalog("pg"), self.source());
+        let inserted = self.connection.execute(&query, []).map_err(|e| {
+            if mode == LoadMode::Replace
+                && let Err(e) = execute(&format!("DROP TABLE IF EXISTS {};", target.quoted()))
+            {
+                tracing::warn!(table = %target, error = %e, "could not drop the staging table");
+            }
+            DuckDbError::QueryError(format!("Failed to load rows into {}: {}", table, e))
+        })?;
+        if let Some(statement) = load_finish_ddl(table, mode) {
+            execute(&statement)?;
+        }
+        Self::log_query("load_postgres", &query, started);
+        Ok(inserted)
+    }
+
+    /// Writes the file (with `transform` applied) into an existing `target` instead of
+    /// a new file: appended after its rows, or replacing the rows whose key matches.
+    /// A missing target is simply created. Column name mismatches and duplicate keys
+    /// are errors; type differences are reported and widened by DuckDB.
+    /// With `dry_run`, the checks and row counts still run but nothing is written.
+    ///
+    /// The target is a CSV or Parquet file, or a table of a DuckDB database as
+    /// `warehouse.duckdb:orders` (a bare `warehouse.duckdb` names the table after
+    /// the source file). A CSV target is read back as text and rewritten with its
+    /// own delimiter, quoting, header and date formats, so its existing values
+    /// come out exactly as they were.
+    pub fn export_into(
+        &self,
+        target: &str,
+        transform: &Transform,
+        mode: &ExportMode,
+        dry_run: bool,
+    ) -> Result<ExportSummary, DuckDbError> {
+        let columns: Vec<String> = self.schema()?.into_iter().map(|(name, _)| name).collect();
+        let new_rows = format!(
+            "SELECT {} FROM {}",
+            transform.select_list(&columns)?,
+            self.source()
         );
+        if let Some((database, table)) = self.duckdb_target(target) {
+            return self.export_into_table(&database, &table, &new_rows, mode, dry_run);
+        }
+        let format_str = match Path::new(target).extension().and_then(|e| e.to_str()) {
+            Some("csv") => "CSV",
+            Some("parquet") => "PARQUET",
+            _ => {
+                return Err(DuckDbError::InvalidFileFormat(format!(
+                    "Export target must be a .csv, .parquet or .duckdb file: {}",
+                    target
+                )));
+            }
+        };
+        let escaped_target = target.replace('\'', "''");
+        let is_csv = format_str == "CSV";
+        let exists = Path::new(target).exists();
+        let dialect = if is_csv && exists { self.csv_dialect(target)? } else { String::new() };
+
+        // The rows go to a temp file renamed over the target, which also lets an
+        // upsert read the target while writing its replacement
+        let copy_to = |select: &str, dest: &str| {
+            format!("COPY ({}) TO '{}' (FORMAT {}{})", select, dest.replace('\'', "''"), format_str, dialect)
+        };
+
+        let started = Instant::now();
+        if !exists {
+            if dry_run {
+                let inserted = self.count_rows(&new_rows)?;
+                return Ok(ExportSummary { inserted, query: copy_to(&new_rows, target), ..Default::default() });
+            }
+            let pending = backup::prepare(Path::new(target));
+            let query = copy_to(&new_rows, &pending.temp().to_string_lossy());
+            let inserted = self
+                .connection
+                .execute(&query, [])
+                .map_err(|e| DuckDbError::QueryError(format!("Failed to export file: {}", e)))?;
+            finish_write(pending)?;
+            Self::log_query("export", &query, started);
+            run_hook(HookEvent::Export, target)?;
+            return Ok(ExportSummary { inserted, query, ..Default::default() });
+        }
+
+        let typed = format!("SELECT * FROM {}('{}')", read_function(target), escaped_target);
+        let existing = if is_csv {
+            format!("SELECT * FROM read_csv_auto('{}', all_varchar = true)", escaped_target)
+        } else {
+            typed.clone()
+        };
+        let existing_schema = self.describe(&typed)?;
+        let type_conflicts = self.check_export_columns(target, &existing_schema, &new_rows, mode)?;
+
+        let (kept, inserted, replaced) = match mode {
+            ExportMode::Append => (existing, self.count_rows(&new_rows)?, 0),
+            ExportMode::Upsert { key } => {
+                let key = format!("\"{}\"", key.replace('"', "\"\""));
+                // The CSV's keys are text now, so compare both sides as text
+                let (e_key, n_key) = if is_csv {
+                    (format!("e.{}::VARCHAR", key), format!("n.{}::VARCHAR", key))
+                } else {
+                    (format!("e.{}", key), format!("n.{}", key))
+                };
+                let replaced = self.count_rows(&format!(
+                    "SELECT * FROM ({}) e SEMI JOIN ({}) n ON {} = {}",
+                    existing, new_rows, e_key, n_key
+                ))?;
+                let inserted = self.count_rows(&format!(
+                    "SELECT * FROM ({}) n ANTI JOIN ({}) e ON {} = {}",
+                    new_rows, existing, n_key, e_key
+                ))?;
+                let kept = format!("SELECT e.* FROM ({}) e ANTI JOIN ({}) n ON {} = {}", existing, new_rows, e_key, n_key);
+                (kept, inserted, replaced)
+            }
+        };
+
+        let names: Vec<String> = existing_schema
+            .iter()
+            .map(|(name, _)| format!("\"{}\"", name.replace('"', "\"\"")))
+            .collect();
+        let merged = format!("SELECT {} FROM ({} UNION ALL BY NAME {})", names.join(", "), kept, new_rows);
+        let query = if dry_run {
+            copy_to(&merged, target)
+        } else {
+            let pending = backup::prepare(Path::new(target));
+            let query = copy_to(&merged, &pending.temp().to_string_lossy());
+            self.connection
+                .execute(&query, [])
+                .map_err(|e| DuckDbError::QueryError(format!("Failed to export file: {}", e)))?;
+            finish_write(pending)?;
+            Self::log_query("export", &query, started);
+            run_hook(HookEvent::Export, target)?;
+            query
+        };
+
+        Ok(ExportSummary {
+            inserted,
+            replaced,
+            type_conflicts,
+            query,
+        })
+    }
+
+    /// `warehouse.duckdb:orders` as the database and table; a bare
+    /// `warehouse.duckdb` names the table after the source file. `None` for
+    /// any other target.
+    fn duckdb_target(&self, target: &str) -> Option<(PathBuf, String)> {
+        match target.rsplit_once(".duckdb:") {
+            Some((database, table)) if !table.is_empty() => Some((PathBuf::from(format!("{}.duckdb", database)), table.to_string())),
+            _ if target.ends_with(".duckdb") => {
+                let table = self.local_path().file_stem().unwrap_or_default().to_string_lossy().to_string();
+                Some((PathBuf::from(target), table))
+            }
+            _ => None,
+        }
+    }
 
+    /// Rows of an arbitrary SELECT
+    fn count_rows(&self, select: &str) -> Result<usize, DuckDbError> {
         self.connection
+            .query_row(&format!("SELECT count(*) FROM ({})", select), [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to count rows: {}", e)))
+    }
+
+    /// Fails unless the new rows have the target's columns (and, for an
+    /// upsert, a unique key in it); returns the columns whose types differ
+    fn check_export_columns(
+        &self,
+        target: &str,
+        existing_schema: &[(String, String)],
+        new_rows: &str,
+        mode: &ExportMode,
+    ) -> Result<Vec<String>, DuckDbError> {
+        let new_schema = self.describe(new_rows)?;
+        let missing: Vec<&str> = existing_schema
+            .iter()
+            .filter(|(name, _)| !new_schema.iter().any(|(n, _)| n == name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let extra: Vec<&str> = new_schema
+            .iter()
+            .filter(|(name, _)| !existing_schema.iter().any(|(n, _)| n == name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !missing.is_empty() || !extra.is_empty() {
+            return Err(DuckDbError::InvalidColumn(format!(
+                "Columns don't match {} (missing: {}; extra: {})",
+                target,
+                if missing.is_empty() { "-".to_string() } else { missing.join(", ") },
+                if extra.is_empty() { "-".to_string() } else { extra.join(", ") },
+            )));
+        }
+        if let ExportMode::Upsert { key } = mode {
+            if !existing_schema.iter().any(|(name, _)| name == key) {
+                return Err(DuckDbError::InvalidColumn(format!(
+                    "Key column '{}' not found in {}",
+                    key, target
+                )));
+            }
+            let quoted = format!("\"{}\"", key.replace('"', "\"\""));
+            let duplicates =
+                self.count_rows(&format!("SELECT {0} FROM ({1}) GROUP BY {0} HAVING count(*) > 1", quoted, new_rows))?;
+            if duplicates > 0 {
+                return Err(DuckDbError::QueryError(format!(
+                    "{} key values appear more than once in the new rows; upsert needs a unique key",
+                    duplicates
+                )));
+            }
+        }
+        Ok(existing_schema
+            .iter()
+            .filter_map(|(name, ty)| {
+                let (_, new_ty) = new_schema.iter().find(|(n, _)| n == name)?;
+                (new_ty != ty).then(|| format!("Column '{}' is {} in {} but {} in the new rows", name, ty, target, new_ty))
+            })
+            .collect())
+    }
+
+    /// COPY options that write a CSV the way `path` is written: its delimiter,
+    /// quote and escape characters, header, and date and timestamp formats
+    fn csv_dialect(&self, path: &str) -> Result<String, DuckDbError> {
+        let query = format!(
+            "SELECT Delimiter, Quote, Escape, HasHeader, DateFormat, TimestampFormat FROM sniff_csv('{}')",
+            path.replace('\'', "''")
+        );
+        let (delimiter, quote, escape, header, date, timestamp) = self
+            .connection
+            .query_row(&query, [], |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, bool>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to read the CSV dialect of {}: {}", path, e)))?;
+        // An unset character or format comes back empty (or as NUL)
+        let given = |value: Option<String>| value.filter(|v| !v.is_empty() && !v.contains('\0'));
+        let mut options = format!(", HEADER {}", header);
+        for (name, value) in [
+            ("DELIMITER", delimiter),
+            ("QUOTE", quote),
+            ("ESCAPE", escape),
+            ("DATEFORMAT", date),
+            ("TIMESTAMPFORMAT", timestamp),
+        ] {
+            if let Some(value) = given(value) {
+                options.push_str(&format!(", {} '{}'", name, value.replace('\'', "''")));
+            }
+        }
+        Ok(options)
+    }
+
+    /// `export_into` for a table of a DuckDB database. The changes are made to
+    /// a copy of the database renamed over it once they succeed, so a failed
+    /// export leaves it untouched and `ftool undo` can put the old one back.
+    fn export_into_table(
+        &self,
+        database: &Path,
+        table: &str,
+        new_rows: &str,
+        mode: &ExportMode,
+        dry_run: bool,
+    ) -> Result<ExportSummary, DuckDbError> {
+        let label = format!("{}:{}", database.display(), table);
+        let exists = database.exists();
+        let wal = PathBuf::from(format!("{}.wal", database.display()));
+        if wal.exists() {
+            return Err(DuckDbError::DatabaseError(format!(
+                "{} has unsaved changes in {}; open it in DuckDB once (or run CHECKPOINT) first",
+                database.display(),
+                wal.display()
+            )));
+        }
+        let qualified = format!("ftool_target.\"{}\"", table.replace('"', "\"\""));
+        let started = Instant::now();
+
+        let pending = backup::prepare(database);
+        let attach_path = if dry_run { database.to_path_buf() } else { pending.temp().to_path_buf() };
+        if !dry_run && exists {
+            std::fs::copy(database, &attach_path)
+                .map_err(|e| DuckDbError::DatabaseError(format!("Failed to copy {}: {}", database.display(), e)))?;
+        }
+        let table_exists = if exists {
+            self.connection
+                .execute_batch(&format!(
+                    "ATTACH '{}' AS ftool_target{}",
+                    attach_path.to_string_lossy().replace('\'', "''"),
+                    if dry_run { " (READ_ONLY)" } else { "" }
+                ))
+                .map_err(|e| DuckDbError::ConnectionError(format!("Failed to open {}: {}", database.display(), e)))?;
+            self.connection
+                .query_row(
+                    "SELECT count(*) FROM duckdb_tables() WHERE database_name = 'ftool_target' AND table_name = ?",
+                    [table],
+                    |row| row.get::<_, i64>(0),
+                )
+                .map_err(|e| DuckDbError::QueryError(format!("Failed to list the tables of {}: {}", database.display(), e)))?
+                > 0
+        } else if dry_run {
+            false
+        } else {
+            self.connection
+                .execute_batch(&format!("ATTACH '{}' AS ftool_target", attach_path.to_string_lossy().replace('\'', "''")))
+                .map_err(|e| DuckDbError::ConnectionError(format!("Failed to create {}: {}", database.display(), e)))?;
+            false
+        };
+        let attached = exists || !dry_run;
+
+        let result = self.table_export_plan(&label, &qualified, table_exists, new_rows, mode).and_then(|summary| {
+            if !dry_run {
+                self.connection
+                    .execute_batch(&format!("BEGIN; {}; COMMIT", summary.query))
+                    .map_err(|e| DuckDbError::QueryError(format!("Failed to export into {}: {}", label, e)))?;
+            }
+            Ok(summary)
+        });
+        if attached {
+            // A failed batch leaves its transaction open, which would block the DETACH
+            if result.is_err() {
+                let _ = self.connection.execute_batch("ROLLBACK");
+            }
+            if let Err(e) = self.connection.execute_batch("DETACH ftool_target") {
+                tracing::warn!(database = %database.display(), error = %e, "could not detach database");
+            }
+        }
+        let summary = result?;
+        if !dry_run {
+            finish_write(pending)?;
+            Self::log_query("export", &summary.query, started);
+            run_hook(HookEvent::Export, &database.to_string_lossy())?;
+        }
+        Ok(summary)
+    }
+
+    /// The statements exporting `new_rows` into the attached `qualified` table,
+    /// with the rows they add and replace
+    fn table_export_plan(
+        &self,
+        label: &str,
+        qualified: &str,
+        table_exists: bool,
+        new_rows: &str,
+        mode: &ExportMode,
+    ) -> Result<ExportSummary, DuckDbError> {
+        if !table_exists {
+            let query = format!("CREATE TABLE {} AS {}", qualified, new_rows);
+            return Ok(ExportSummary { inserted: self.count_rows(new_rows)?, query, ..Default::default() });
+        }
+        let existing_schema = self.describe(&format!("SELECT * FROM {}", qualified))?;
+        let type_conflicts = self.check_export_columns(label, &existing_schema, new_rows, mode)?;
+        let insert = format!("INSERT INTO {} BY NAME {}", qualified, new_rows);
+        let (query, inserted, replaced) = match mode {
+            ExportMode::Append => (insert, self.count_rows(new_rows)?, 0),
+            ExportMode::Upsert { key } => {
+                let key = format!("\"{}\"", key.replace('"', "\"\""));
+                let replaced = self.count_rows(&format!(
+                    "SELECT * FROM {0} e SEMI JOIN ({1}) n ON e.{2} = n.{2}",
+                    qualified, new_rows, key
+                ))?;
+                let inserted = self.count_rows(&format!(
+                    "SELECT * FROM ({1}) n ANTI JOIN {0} e ON n.{2} = e.{2}",
+                    qualified, new_rows, key
+                ))?;
+                let delete = format!("DELETE FROM {0} WHERE {2} IN (SELECT {2} FROM ({1}))", qualified, new_rows, key);
+                (format!("{};\n{}", delete, insert), inserted, replaced)
+            }
+        };
+        Ok(ExportSummary { inserted, replaced, type_conflicts, query })
+    }
+
+    /// Writes a random sample of about `rows` rows to `output` (same format as the input).
+    /// With `stratify_by`, every group of that column gets a share proportional to its
+    /// size (at least one row), so rare groups aren't lost in skewed files.
+    pub fn sample(
+        &self,
+        rows: usize,
+        stratify_by: Option<&str>,
+        output: &str,
+    ) -> Result<usize, DuckDbError> {
+        let source = self.source();
+        let select = match stratify_by {
+            None => format!("SELECT * FROM {} USING SAMPLE {} ROWS", source, rows),
+            Some(column) => {
+                let total = self.row_count()?.max(1);
+                let quoted = format!("\"{}\"", column.replace('"', "\"\""));
+                format!(
+                    "SELECT * EXCLUDE (__ftool_rn, __ftool_n) FROM (\
+                     SELECT *, row_number() OVER (PARTITION BY {0} ORDER BY random()) AS __ftool_rn, \
+                     count(*) OVER (PARTITION BY {0}) AS __ftool_n FROM {1}) \
+                     WHERE __ftool_rn <= GREATEST(1, ROUND(__ftool_n * {2} / {3}))",
+                    quoted, source, rows, total
+                )
+            }
+        };
+
+        let format_str = if self.read_function() == "read_csv_auto" { "CSV" } else { "PARQUET" };
+        let (pending, temp) = prepare_write(output);
+        let query = format!("COPY ({}) TO '{}' (FORMAT {})", select, temp, format_str);
+
+        let started = Instant::now();
+        let written = self
+            .connection
             .execute(&query, [])
-            .map_err(|e| DuckDbError::QueryError(format!("Failed to convert file: {}", e)))?;
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to sample file: {}", e)))?;
+        finish_write(pending)?;
+        Self::log_query("sample", &query, started);
+        Ok(written)
+    }
 
-        Ok(target_path)
+    /// SQL expression hashing one row: the MD5 of every column cast to text, in
+    /// schema order, with NULLs marked so they differ from the text "NULL".
+    /// Files holding the same values hash the same whatever their format.
+    fn row_hash_expr(&self) -> Result<String, DuckDbError> {
+        let columns: Vec<String> = self.schema()?.into_iter().map(|(name, _)| name).collect();
+        Ok(Self::hash_columns_expr(&columns))
+    }
+
+    fn hash_columns_expr(columns: &[String]) -> String {
+        let columns: Vec<String> = columns
+            .iter()
+            .map(|name| format!("COALESCE(CAST(\"{}\" AS VARCHAR), chr(0))", name.replace('"', "\"\"")))
+            .collect();
+        format!("md5(concat_ws(chr(31), {}))", columns.join(", "))
+    }
+
+    /// Checks that `output`, converted from this file, holds the same data: equal
+    /// row counts and column names, and a random sample of this file's row hashes
+    /// all present in the output
+    pub fn verify_conversion(&self, output: &str) -> Result<Verification, DuckDbError> {
+        let converted = DuckDbInspector::new(output.to_string())?;
+        let source_schema = self.schema()?;
+        let output_schema = converted.schema()?;
+
+        let mut column_changes = Vec::new();
+        let mut type_changes = Vec::new();
+        for (name, ty) in &source_schema {
+            match output_schema.iter().find(|(n, _)| n == name) {
+                Some((_, out_ty)) if out_ty != ty => {
+                    type_changes.push((name.clone(), ty.clone(), out_ty.clone()));
+                }
+                Some(_) => {}
+                None => column_changes.push(format!("Column missing from output: {}", name)),
+            }
+        }
+        for (name, _) in &output_schema {
+            if !source_schema.iter().any(|(n, _)| n == name) {
+                column_changes.push(format!("Extra column in output: {}", name));
+            }
+        }
+
+        let mut verification = Verification {
+            source_rows: self.row_count()?,
+            output_rows: converted.row_count()?,
+            column_changes,
+            type_changes,
+            sampled: 0,
+            unmatched: 0,
+        };
+        if !verification.column_changes.is_empty() {
+            return Ok(verification);
+        }
+
+        let columns: Vec<String> = source_schema.into_iter().map(|(name, _)| name).collect();
+        let hash = Self::hash_columns_expr(&columns);
+        let query = format!(
+            "SELECT count(*), count(*) FILTER (WHERE h NOT IN (SELECT {0} FROM {1})) \
+             FROM (SELECT {0} AS h FROM {2} USING SAMPLE {3} ROWS)",
+            hash,
+            converted.source(),
+            self.source(),
+            VERIFY_SAMPLE_ROWS
+        );
+        let started = Instant::now();
+        let (sampled, unmatched): (i64, i64) = self
+            .connection
+            .query_row(&query, [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to compare row hashes: {}", e)))?;
+        Self::log_query("verify", &query, started);
+        verification.sampled = sampled as usize;
+        verification.unmatched = unmatched as usize;
+        Ok(verification)
+    }
+
+    /// MD5 digest of the whole file: the hash of its sorted row hashes, so it
+    /// doesn't depend on row order
+    pub fn dataset_digest(&self) -> Result<String, DuckDbError> {
+        let query = format!(
+            "SELECT md5(COALESCE(string_agg(h, ',' ORDER BY h), '')) FROM (SELECT {} AS h FROM {})",
+            self.row_hash_expr()?,
+            self.source()
+        );
+        let started = Instant::now();
+        let digest: String = self
+            .connection
+            .query_row(&query, [], |row| row.get(0))
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to compute digest: {}", e)))?;
+        Self::log_query("digest", &query, started);
+        Ok(digest)
     }
+
+    /// Writes a `row,hash` CSV with the hash of every row, numbered from 1 in file order
+    pub fn write_row_hashes(&self, output: &str) -> Result<usize, DuckDbError> {
+        let (pending, temp) = prepare_write(output);
+        let query = format!(
+            "COPY (SELECT row_number() OVER () AS row, {} AS hash FROM {}) TO '{}' (FORMAT CSV, HEADER)",
+            self.row_hash_expr()?,
+            self.source(),
+            temp
+        );
+        let started = Instant::now();
+        let written = self
+            .connection
+            .execute(&query, [])
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to write row hashes: {}", e)))?;
+        finish_write(pending)?;
+        Self::log_query("row_hashes", &query, started);
+        Ok(written)
+    }
+
+    /// Splits the file into several files of the same format inside `out_dir`.
+    /// `progress` is called with (files written, total files, path) after each file.
+    pub fn split(
+        &self,
+        mode: &SplitMode,
+        out_dir: &Path,
+        mut progress: impl FnMut(usize, usize, &str),
+    ) -> Result<Vec<String>, DuckDbError> {
+        std::fs::create_dir_all(out_dir)
+            .map_err(|e| DuckDbError::DatabaseError(format!("Failed to create {}: {}", out_dir.display(), e)))?;
+
+        let path = self.local_path();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let ext = path.extension().unwrap_or_default().to_string_lossy().to_string();
+        let format_str = if ext == "csv" { "CSV" } else { "PARQUET" };
+
+        // (file name suffix, WHERE clause over the split source)
+        let (source, parts): (String, Vec<(String, String)>) = match mode {
+            SplitMode::Rows(_) | SplitMode::Size(_) => {
+                let rows_per_file = match mode {
+                    SplitMode::Rows(n) => *n,
+                    _ => self.rows_per_size(mode)?,
+                }
+                .max(1);
+                let total = self.row_count()?;
+                let count = total.div_ceil(rows_per_file).max(1);
+
+                // Number rows once with a window function so each part is a cheap filter
+                let query = format!(
+                    "CREATE OR REPLACE TEMP TABLE __ftool_split AS SELECT *, (row_number() OVER () - 1) // {} AS __ftool_part FROM {}",
+                    rows_per_file,
+                    self.source()
+                );
+                let started = Instant::now();
+                self.connection
+                    .execute(&query, [])
+                    .map_err(|e| DuckDbError::QueryError(format!("Failed to number rows: {}", e)))?;
+                Self::log_query("split", &query, started);
+
+                let width = count.to_string().len().max(3);
+                let parts = (0..count)
+                    .map(|i| {
+                        (
+                            format!("part{:0width$}", i + 1, width = width),
+                            format!("WHERE __ftool_part = {}", i),
+                        )
+                    })
+                    .collect();
+                (
+                    "(SELECT * EXCLUDE (__ftool_part) FROM __ftool_split".to_string(),
+                    parts,
+                )
+            }
+            SplitMode::Column(column) => {
+                let quoted = format!("\"{}\"", column.replace('"', "\"\""));
+                let query = format!(
+                    "SELECT DISTINCT CAST({} AS VARCHAR) FROM {} ORDER BY 1 NULLS LAST",
+                    quoted,
+                    self.source()
+                );
+                let started = Instant::now();
+                let mut stmt = self.connection.prepare(&query).map_err(|e| {
+                    DuckDbError::QueryError(format!("Failed to prepare distinct values query: {}", e))
+                })?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        let value: Option<String> = row.get(0)?;
+                        Ok(value)
+                    })
+                    .map_err(|e| {
+                        DuckDbError::QueryError(format!("Failed to read distinct values: {}", e))
+                    })?;
+                let mut parts = Vec::new();
+                for row in rows {
+                    let value = row.map_err(|e| {
+                        DuckDbError::QueryError(format!("Failed to read distinct value: {}", e))
+                    })?;
+                    parts.push(match value {
+                        Some(v) => (
+                            file_name_part(&v),
+                            format!("WHERE CAST({} AS VARCHAR) = '{}'", quoted, v.replace('\'', "''")),
+                        ),
+                        None => ("null".to_string(), format!("WHERE {} IS NULL", quoted)),
+                    });
+                }
+                Self::log_query("split", &query, started);
+                (
+                    format!("(SELECT * FROM {}", self.source()),
+                    parts,
+                )
+            }
+        };
+
+        let total = parts.len();
+        let mut written = Vec::with_capacity(total);
+        for (i, (suffix, where_clause)) in parts.into_iter().enumerate() {
+            let target = out_dir
+                .join(format!("{}_{}.{}", stem, suffix, ext))
+                .to_string_lossy()
+                .to_string();
+            let pending = backup::prepare_untracked(Path::new(&target));
+            let query = format!(
+                "COPY {} {}) TO '{}' (FORMAT {})",
+                source,
+                where_clause,
+                pending.temp().to_string_lossy().replace('\'', "''"),
+                format_str
+            );
+            let started = Instant::now();
+            self.connection
+                .execute(&query, [])
+                .map_err(|e| DuckDbError::QueryError(format!("Failed to write {}: {}", target, e)))?;
+            finish_write(pending)?;
+            Self::log_query("split", &query, started);
+            progress(i + 1, total, &target);
+            written.push(target);
+        }
+
+        Ok(written)
+    }
+
+    /// Estimates how many rows fit in the requested size from the input's average bytes per row
+    fn rows_per_size(&self, mode: &SplitMode) -> Result<usize, DuckDbError> {
+        let SplitMode::Size(bytes) = mode else {
+            return Ok(0);
+        };
+        if self.table.is_some() {
+            return Err(DuckDbError::InvalidFileFormat("Splitting by size needs a file, not a table".to_string()));
+        }
+        let file_size = std::fs::metadata(&self.file_path)
+            .map_err(|e| DuckDbError::DatabaseError(e.to_string()))?
+            .len();
+        let rows = self.row_count()?.max(1) as u64;
+        let bytes_per_row = (file_size / rows).max(1);
+        Ok((bytes / bytes_per_row) as usize)
+    }
+}
+
+/// Preview rows with NULLs written as the text "NULL", for plain-text outputs
+pub fn nulls_as_text(rows: &[Vec<Option<String>>]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| row.iter().map(|v| v.clone().unwrap_or_else(|| "NULL".to_string())).collect())
+        .collect()
 }
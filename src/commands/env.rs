@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use super::postgres::redact;
+
+/// Key fragments marking a variable as secret; matched ignoring case
+pub const SECRET_PATTERNS: &[&str] = &[
+    "SECRET", "PASSWORD", "PASSWD", "TOKEN", "API_KEY", "APIKEY", "PRIVATE_KEY", "ACCESS_KEY", "CREDENTIAL",
+    "AUTH", "COOKIE", "SIGNATURE",
+];
+
+/// Shown instead of a secret value
+pub const MASK: &str = "********";
+
+/// One `KEY=value` pair, from the process environment or a `.env` file
+pub type EnvVar = (String, String);
+
+/// The process environment, sorted by key
+pub fn process_env() -> Vec<EnvVar> {
+    let mut vars: Vec<EnvVar> = std::env::vars_os()
+        .map(|(k, v)| (k.to_string_lossy().to_string(), v.to_string_lossy().to_string()))
+        .collect();
+    vars.sort();
+    vars
+}
+
+/// Reads a `.env` file: `KEY=value` lines, optionally prefixed with `export`,
+/// with `#` comments and single- or double-quoted values (`\n`, `\"` and `\\`
+/// are unescaped in double quotes). A key set twice keeps its last value, in
+/// the position of its first.
+pub fn read_env_file(path: &Path) -> anyhow::Result<Vec<EnvVar>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut vars: Vec<EnvVar> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        let Some((key, value)) = line.split_once('=') else {
+            anyhow::bail!("{}:{}: expected KEY=value", path.display(), i + 1);
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            anyhow::bail!("{}:{}: invalid key '{}'", path.display(), i + 1, key);
+        }
+        let value = parse_value(value.trim())
+            .with_context(|| format!("{}:{}: unterminated quote", path.display(), i + 1))?;
+        match vars.iter_mut().find(|(k, _)| k == key) {
+            Some(existing) => existing.1 = value,
+            None => vars.push((key.to_string(), value)),
+        }
+    }
+    Ok(vars)
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.find('\'').map(|end| rest[..end].to_string());
+    }
+    let Some(rest) = value.strip_prefix('"') else {
+        // Unquoted: a ` #` starts a comment
+        let end = value.find(" #").unwrap_or(value.len());
+        return Some(value[..end].trim_end().to_string());
+    };
+    let mut out = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(out),
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                other => out.push(other),
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+/// Whether `key` names a secret (a token, password, API key...). Dashes count
+/// as underscores, so header names like `X-Api-Key` match too.
+pub fn is_secret(key: &str) -> bool {
+    let key = key.to_uppercase().replace('-', "_");
+    key.ends_with("_KEY") || SECRET_PATTERNS.iter().any(|p| key.contains(p))
+}
+
+/// The value as shown: masked for secret keys, with any `user:password@` in a
+/// URL hidden otherwise; unchanged with `reveal`
+pub fn display_value(key: &str, value: &str, reveal: bool) -> String {
+    if reveal || value.is_empty() {
+        value.to_string()
+    } else if is_secret(key) {
+        MASK.to_string()
+    } else {
+        redact(value)
+    }
+}
+
+/// Whether the key or the shown value contains `search`, ignoring case; a
+/// masked secret only matches on its key
+pub fn matches(key: &str, shown: &str, search: &str) -> bool {
+    let search = search.to_lowercase();
+    key.to_lowercase().contains(&search) || shown.to_lowercase().contains(&search)
+}
+
+/// How a key differs between two environments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnvStatus {
+    Same,
+    /// Only in the second
+    Added,
+    /// Only in the first
+    Removed,
+    Changed,
+}
+
+impl EnvStatus {
+    /// `+`, `-`, `~` or a space, as the diff lines start
+    pub fn marker(self) -> char {
+        match self {
+            EnvStatus::Same => ' ',
+            EnvStatus::Added => '+',
+            EnvStatus::Removed => '-',
+            EnvStatus::Changed => '~',
+        }
+    }
+}
+
+/// A key in either environment, with its value in each
+#[derive(Debug, Clone)]
+pub struct EnvDiffLine {
+    pub key: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl EnvDiffLine {
+    pub fn status(&self) -> EnvStatus {
+        match (&self.left, &self.right) {
+            (Some(l), Some(r)) if l == r => EnvStatus::Same,
+            (Some(_), Some(_)) => EnvStatus::Changed,
+            (None, _) => EnvStatus::Added,
+            (_, None) => EnvStatus::Removed,
+        }
+    }
+
+    /// The value as listed: `old -> new` when changed, else the one value,
+    /// shown as `display_value` does
+    pub fn shown_value(&self, reveal: bool) -> String {
+        let shown = |v: &Option<String>| display_value(&self.key, v.as_deref().unwrap_or_default(), reveal);
+        match self.status() {
+            EnvStatus::Changed if is_secret(&self.key) && !reveal => format!("{} (changed)", MASK),
+            EnvStatus::Changed => format!("{} -> {}", shown(&self.left), shown(&self.right)),
+            EnvStatus::Added => shown(&self.right),
+            EnvStatus::Same | EnvStatus::Removed => shown(&self.left),
+        }
+    }
+
+    /// `+ KEY=value`, `- KEY=value`, `~ KEY: old -> new` or `  KEY=value`
+    pub fn describe(&self, reveal: bool) -> String {
+        let status = self.status();
+        let separator = if status == EnvStatus::Changed { ": " } else { "=" };
+        format!("{} {}{}{}", status.marker(), self.key, separator, self.shown_value(reveal))
+    }
+}
+
+/// Every key of `left` and `right`, sorted
+pub fn diff(left: &[EnvVar], right: &[EnvVar]) -> Vec<EnvDiffLine> {
+    let lookup = |vars: &[EnvVar], key: &str| vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+    let mut keys: Vec<&String> = left.iter().chain(right).map(|(k, _)| k).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .map(|key| EnvDiffLine { key: key.clone(), left: lookup(left, key), right: lookup(right, key) })
+        .collect()
+}
+
+/// "2 added, 1 removed, 3 changed" (or "No differences")
+pub fn diff_summary(lines: &[EnvDiffLine]) -> String {
+    let count = |status| lines.iter().filter(|l| l.status() == status).count();
+    let (added, removed, changed) = (count(EnvStatus::Added), count(EnvStatus::Removed), count(EnvStatus::Changed));
+    if added + removed + changed == 0 {
+        return "No differences".to_string();
+    }
+    format!("{} added, {} removed, {} changed", added, removed, changed)
+}
+
+/// Whether a file looks like a dotenv file: `.env`, `.env.local`, `prod.env`...
+pub fn is_env_file(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    name == ".env" || name.starts_with(".env.") || name.ends_with(".env")
+}
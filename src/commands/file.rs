@@ -1,30 +1,71 @@
+use std::collections::VecDeque;
 use std::fs::File as FsFile;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use clap::ValueEnum;
+use serde::Serialize;
+use thiserror::Error;
 
-#[derive(Debug)]
+use super::dir_scan;
+use super::duckdb_inspector::{DuckDbInspector, nulls_as_text};
+use super::table_format::to_markdown;
+use super::units::SizeFormat;
+
+/// Output of `ftool file` over several paths
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum FileFormat {
+    /// A table (or one section per file for --head)
+    Text,
+    /// A JSON array with one object per file
+    Json,
+}
+
+/// What `ftool file` reports for each path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileAction {
+    Info,
+    Lines,
+    Size,
+    Head(usize),
+    Tail(usize),
+}
+
+/// One path's result in a batch `ftool file` run; fields the action
+/// doesn't produce are left out of the JSON
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Files counted in a directory's size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readonly: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tail: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Error)]
 pub enum FileError {
+    #[error("File not found: {0}")]
     NotFound(String),
+    #[error("Permission denied: {0}")]
     PermissionDenied(String),
+    #[error("Invalid path: {0}")]
     InvalidPath(String),
+    #[error("Error reading file: {0}")]
     ReadError(String),
+    #[error("Error: {0}")]
     Other(String),
 }
 
-impl std::fmt::Display for FileError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FileError::NotFound(path) => write!(f, "File not found: {}", path),
-            FileError::PermissionDenied(path) => write!(f, "Permission denied: {}", path),
-            FileError::InvalidPath(path) => write!(f, "Invalid path: {}", path),
-            FileError::ReadError(msg) => write!(f, "Error reading file: {}", msg),
-            FileError::Other(msg) => write!(f, "Error: {}", msg),
-        }
-    }
-}
-
-impl std::error::Error for FileError {}
-
 impl From<std::io::Error> for FileError {
     fn from(error: std::io::Error) -> Self {
         match error.kind() {
@@ -61,7 +102,7 @@ impl File {
     }
 
     ///public method info
-    pub fn info(&self) -> Result<String, FileError> {
+    pub fn info(&self, sizes: SizeFormat) -> Result<String, FileError> {
         self.validate_path()?;
 
         let file = FsFile::open(&self.file_path)
@@ -71,9 +112,9 @@ impl File {
             .map_err(|e| FileError::ReadError(format!("Failed to read metadata: {}", e)))?;
 
         let info = format!(
-            "Path: {}\nSize: {} bytes\nReadonly: {}",
+            "Path: {}\nSize: {}\nReadonly: {}",
             self.file_path,
-            metadata.len(),
+            sizes.format(metadata.len()),
             metadata.permissions().readonly()
         );
 
@@ -93,8 +134,13 @@ impl File {
         Ok(info_line)
     }
 
-    ///public method size
-    pub fn size(&self) -> Result<String, FileError> {
+    ///public method size; for a directory, the total of the files inside it
+    pub fn size(&self, sizes: SizeFormat) -> Result<String, FileError> {
+        let path = Path::new(&self.file_path);
+        if path.is_dir() {
+            let (files, bytes) = dir_size(path)?;
+            return Ok(format!("Directory {} has {} in {} files", self.file_path, sizes.format(bytes), files));
+        }
         self.validate_path()?;
 
         let file = FsFile::open(&self.file_path)
@@ -103,13 +149,53 @@ impl File {
         let metadata = file.metadata()
             .map_err(|e| FileError::ReadError(format!("Failed to read metadata: {}", e)))?;
 
-        let info_size = format!("File {} has {} bytes", self.file_path, metadata.len());
+        let info_size = format!("File {} has {}", self.file_path, sizes.format(metadata.len()));
         Ok(info_size)
     }
 
-    ///public method head
+    /// The action's result as data, with a failure recorded in `error`
+    /// instead of returned, so one bad path doesn't stop a batch
+    pub fn report(&self, action: FileAction) -> FileReport {
+        let mut report = FileReport { path: self.file_path.clone(), ..Default::default() };
+        if let Err(e) = self.fill_report(action, &mut report) {
+            report.error = Some(e.to_string());
+        }
+        report
+    }
+
+    fn fill_report(&self, action: FileAction, report: &mut FileReport) -> Result<(), FileError> {
+        let path = Path::new(&self.file_path);
+        if action == FileAction::Size && path.is_dir() {
+            let (files, bytes) = dir_size(path)?;
+            report.size_bytes = Some(bytes);
+            report.files = Some(files);
+            return Ok(());
+        }
+        self.validate_path()?;
+        let file = FsFile::open(&self.file_path)
+            .map_err(|e| FileError::ReadError(format!("Failed to open {}: {}", self.file_path, e)))?;
+        match action {
+            FileAction::Info | FileAction::Size => {
+                let metadata = file.metadata()
+                    .map_err(|e| FileError::ReadError(format!("Failed to read metadata: {}", e)))?;
+                report.size_bytes = Some(metadata.len());
+                if action == FileAction::Info {
+                    report.readonly = Some(metadata.permissions().readonly());
+                }
+            }
+            FileAction::Lines => report.lines = Some(BufReader::new(file).lines().count()),
+            FileAction::Head(n) => report.head = Some(self.head(n)?.lines().map(str::to_string).collect()),
+            FileAction::Tail(n) => report.tail = Some(self.tail(n)?.lines().map(str::to_string).collect()),
+        }
+        Ok(())
+    }
+
+    ///public method head; a Parquet file's first rows as a table
     pub fn head(&self, lines: usize) -> Result<String, FileError> {
         self.validate_path()?;
+        if self.is_parquet() {
+            return self.parquet_rows(lines, false);
+        }
 
         let file = FsFile::open(&self.file_path)
             .map_err(|e| FileError::ReadError(format!("Failed to open {}: {}", self.file_path, e)))?;
@@ -125,4 +211,144 @@ impl File {
 
         Ok(result)
     }
-}
\ No newline at end of file
+
+    /// The last lines of the file, read through once keeping only `lines` of
+    /// them; a Parquet file's last rows as a table
+    pub fn tail(&self, lines: usize) -> Result<String, FileError> {
+        self.validate_path()?;
+        if self.is_parquet() {
+            return self.parquet_rows(lines, true);
+        }
+
+        let file = FsFile::open(&self.file_path)
+            .map_err(|e| FileError::ReadError(format!("Failed to open {}: {}", self.file_path, e)))?;
+
+        let mut last = VecDeque::with_capacity(lines.min(10_000));
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| FileError::ReadError(format!("Failed to read line: {}", e)))?;
+            if last.len() == lines {
+                last.pop_front();
+            }
+            if lines > 0 {
+                last.push_back(line);
+            }
+        }
+
+        Ok(last.into_iter().map(|line| line + "\n").collect())
+    }
+
+    fn is_parquet(&self) -> bool {
+        Path::new(&self.file_path).extension().is_some_and(|e| e == "parquet")
+    }
+
+    /// The first (or last) `limit` rows of a Parquet file, read with DuckDB and
+    /// printed as a table instead of the file's binary pages
+    fn parquet_rows(&self, limit: usize, from_end: bool) -> Result<String, FileError> {
+        let read_error = |e: super::duckdb_inspector::DuckDbError| FileError::ReadError(e.to_string());
+        let inspector = DuckDbInspector::new(self.file_path.clone()).map_err(read_error)?;
+        let offset = if from_end { inspector.row_count().map_err(read_error)?.saturating_sub(limit) } else { 0 };
+        let (headers, rows) = inspector.preview(limit, offset, "", None, &[]).map_err(read_error)?;
+        Ok(to_markdown(&headers, &nulls_as_text(&rows)))
+    }
+}
+
+/// Number of files under `path` and their total size, scanned in parallel.
+/// Symbolic links are not followed, so a link to a parent can't loop.
+fn dir_size(path: &Path) -> Result<(u64, u64), FileError> {
+    let stats = dir_scan::scan(path, &|| false)?.unwrap_or_default();
+    if stats.unreadable > 0 {
+        tracing::warn!(dir = %path.display(), unreadable = stats.unreadable, "entries left out of the directory size");
+    }
+    Ok((stats.files, stats.bytes))
+}
+
+/// Expands glob patterns (`*`, `?`, `[...]`) into the matching paths, sorted;
+/// other arguments are kept as they are. A pattern matching nothing is an error.
+pub fn expand_paths(patterns: &[String]) -> Result<Vec<String>, FileError> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            paths.push(pattern.clone());
+            continue;
+        }
+        let entries = glob::glob(pattern).map_err(|e| FileError::InvalidPath(format!("{}: {}", pattern, e)))?;
+        let mut matched: Vec<String> =
+            entries.filter_map(Result::ok).map(|p| p.to_string_lossy().to_string()).collect();
+        if matched.is_empty() {
+            return Err(FileError::NotFound(format!("no files match {}", pattern)));
+        }
+        matched.sort();
+        paths.extend(matched);
+    }
+    Ok(paths)
+}
+
+/// Batch results as a table, with a total row for sizes and line counts and an
+/// Error column when a path failed; `--head` prints a `==> path <==` section per file
+pub fn render_reports(reports: &[FileReport], action: FileAction, sizes: SizeFormat) -> String {
+    if let FileAction::Head(_) | FileAction::Tail(_) = action {
+        let sections: Vec<String> = reports
+            .iter()
+            .map(|r| {
+                let body = match (r.head.as_ref().or(r.tail.as_ref()), &r.error) {
+                    (_, Some(e)) => format!("error: {}", e),
+                    (Some(lines), None) => lines.join("\n"),
+                    (None, None) => String::new(),
+                };
+                format!("==> {} <==\n{}\n", r.path, body)
+            })
+            .collect();
+        return sections.join("\n");
+    }
+
+    let size = |r: &FileReport| match (r.size_bytes, r.files) {
+        (Some(bytes), Some(files)) => format!("{} ({} files)", sizes.format(bytes), files),
+        (Some(bytes), None) => sizes.format(bytes),
+        _ => "-".to_string(),
+    };
+    let mut headers = vec!["Path".to_string()];
+    headers.extend(match action {
+        FileAction::Info => vec!["Size".to_string(), "Readonly".to_string()],
+        FileAction::Lines => vec!["Lines".to_string()],
+        _ => vec!["Size".to_string()],
+    });
+    let mut rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|r| {
+            let mut row = vec![r.path.clone()];
+            match action {
+                FileAction::Info => {
+                    row.push(size(r));
+                    row.push(r.readonly.map_or("-".to_string(), |v| v.to_string()));
+                }
+                FileAction::Lines => row.push(r.lines.map_or("-".to_string(), |n| n.to_string())),
+                _ => row.push(size(r)),
+            }
+            row
+        })
+        .collect();
+    if reports.len() > 1 {
+        match action {
+            FileAction::Lines => {
+                let total: usize = reports.iter().filter_map(|r| r.lines).sum();
+                rows.push(vec!["Total".to_string(), total.to_string()]);
+            }
+            FileAction::Size => {
+                let total: u64 = reports.iter().filter_map(|r| r.size_bytes).sum();
+                rows.push(vec!["Total".to_string(), sizes.format(total)]);
+            }
+            _ => {}
+        }
+    }
+    if reports.iter().any(|r| r.error.is_some()) {
+        headers.push("Error".to_string());
+        for (row, report) in rows.iter_mut().zip(reports) {
+            row.push(report.error.clone().unwrap_or_default());
+        }
+        let width = headers.len();
+        for row in &mut rows {
+            row.resize(width, String::new());
+        }
+    }
+    to_markdown(&headers, &rows)
+}
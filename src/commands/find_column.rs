@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use ignore::WalkBuilder;
+use serde::Serialize;
+
+use super::duckdb_inspector::DuckDbInspector;
+use super::table_format::to_markdown;
+
+/// A column name pattern: a glob when it has `*`, `?` or `[`, otherwise a
+/// substring; both ignore case
+pub struct ColumnPattern {
+    glob: Option<glob::Pattern>,
+    text: String,
+}
+
+impl ColumnPattern {
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let text = pattern.to_lowercase();
+        let glob = if text.contains(['*', '?', '[']) {
+            Some(glob::Pattern::new(&text).map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {}", pattern, e))?)
+        } else {
+            None
+        };
+        Ok(Self { glob, text })
+    }
+
+    pub fn matches(&self, column: &str) -> bool {
+        let column = column.to_lowercase();
+        match &self.glob {
+            Some(glob) => glob.matches(&column),
+            None => column.contains(&self.text),
+        }
+    }
+}
+
+/// A matching column and the file it's in
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnMatch {
+    pub path: String,
+    pub column: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+    /// Position of the column in the file, from 1
+    pub position: usize,
+}
+
+/// Matches under a directory, and the files whose schema couldn't be read
+#[derive(Debug, Clone, Default)]
+pub struct ColumnSearch {
+    pub matches: Vec<ColumnMatch>,
+    pub files_scanned: usize,
+    pub errors: Vec<(String, String)>,
+}
+
+impl ColumnSearch {
+    /// One table row per matching column, then a summary line
+    pub fn render(&self) -> String {
+        let headers: Vec<String> = ["File", "Column", "Type", "#"].iter().map(|h| h.to_string()).collect();
+        let rows: Vec<Vec<String>> = self
+            .matches
+            .iter()
+            .map(|m| vec![m.path.clone(), m.column.clone(), m.data_type.clone(), m.position.to_string()])
+            .collect();
+        let files = {
+            let mut paths: Vec<&str> = self.matches.iter().map(|m| m.path.as_str()).collect();
+            paths.dedup();
+            paths.len()
+        };
+        let mut out = if rows.is_empty() { String::new() } else { to_markdown(&headers, &rows) };
+        out.push_str(&format!(
+            "\n{} matching columns in {} of {} files\n",
+            self.matches.len(),
+            files,
+            self.files_scanned
+        ));
+        out
+    }
+}
+
+/// The CSV and Parquet files under `dir`, sorted. Hidden directories are
+/// skipped; .gitignore is not, since data folders are often ignored.
+pub fn data_files(dir: &Path) -> anyhow::Result<Vec<String>> {
+    if !dir.is_dir() {
+        anyhow::bail!("{} is not a directory", dir.display());
+    }
+    let mut paths: Vec<String> = WalkBuilder::new(dir)
+        .standard_filters(false)
+        .hidden(true)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "csv" || e == "parquet"))
+        .map(|entry| entry.path().to_string_lossy().to_string())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads the schema of every file `data_files` lists (Parquet only reads the
+/// footer, CSV a sample of rows) and keeps the columns matching `pattern`
+pub fn find_column(dir: &Path, pattern: &ColumnPattern) -> anyhow::Result<ColumnSearch> {
+    let paths = data_files(dir)?;
+    let mut search = ColumnSearch { files_scanned: paths.len(), ..Default::default() };
+    for path in paths {
+        let schema = match DuckDbInspector::new(path.clone()).and_then(|i| i.schema()) {
+            Ok(schema) => schema,
+            Err(e) => {
+                tracing::warn!(file = %path, error = %e, "could not read schema");
+                search.errors.push((path, e.to_string()));
+                continue;
+            }
+        };
+        for (i, (column, data_type)) in schema.into_iter().enumerate() {
+            if pattern.matches(&column) {
+                search.matches.push(ColumnMatch { path: path.clone(), column, data_type, position: i + 1 });
+            }
+        }
+    }
+    Ok(search)
+}
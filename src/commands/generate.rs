@@ -0,0 +1,204 @@
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Instant;
+
+use super::duckdb_inspector::{DuckDbError, finish_write, open_connection, prepare_write};
+
+const FIRST_NAMES: [&str; 16] = [
+    "Ana", "Ben", "Carla", "David", "Elena", "Felipe", "Grace", "Hugo", "Irene", "Jorge", "Kate",
+    "Lucas", "Maria", "Nico", "Olivia", "Pablo",
+];
+const LAST_NAMES: [&str; 16] = [
+    "Alvarez", "Brown", "Castro", "Diaz", "Evans", "Fernandez", "Garcia", "Hughes", "Ibarra",
+    "Johnson", "Kim", "Lopez", "Martin", "Nguyen", "Ortiz", "Perez",
+];
+
+/// Schema file for `ftool generate`, e.g.
+/// `{"columns": [{"name": "id", "type": "sequence"}, {"name": "region", "type": "enum", "values": ["EU", "US"]}]}`
+#[derive(Debug, Deserialize)]
+pub struct GenerateSchema {
+    pub columns: Vec<ColumnSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: ColumnKind,
+    /// Fraction of rows (0.0–1.0) that are NULL
+    #[serde(default)]
+    pub null_ratio: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ColumnKind {
+    /// 1, 2, 3, ... starting at `start`
+    Sequence {
+        #[serde(default = "default_start")]
+        start: i64,
+    },
+    Int {
+        #[serde(default)]
+        min: i64,
+        #[serde(default = "default_int_max")]
+        max: i64,
+    },
+    Float {
+        #[serde(default)]
+        min: f64,
+        #[serde(default = "default_float_max")]
+        max: f64,
+    },
+    Bool,
+    /// Dates between `start` and `end` (YYYY-MM-DD)
+    Date {
+        start: String,
+        end: String,
+    },
+    Enum {
+        values: Vec<String>,
+    },
+    /// "First Last" from a built-in list of names
+    Name,
+    Email,
+    Uuid,
+}
+
+fn default_start() -> i64 {
+    1
+}
+
+fn default_int_max() -> i64 {
+    1000
+}
+
+fn default_float_max() -> f64 {
+    1.0
+}
+
+impl GenerateSchema {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let schema: Self = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid schema file {}: {}", path.display(), e))?;
+        if schema.columns.is_empty() {
+            anyhow::bail!("Schema file {} has no columns", path.display());
+        }
+        Ok(schema)
+    }
+
+    /// Generates `rows` rows with DuckDB's random functions and writes them to
+    /// `output` (CSV or Parquet by extension). `seed` in [-1, 1] makes runs repeatable.
+    pub fn write(&self, rows: usize, output: &str, seed: Option<f64>) -> Result<(), DuckDbError> {
+        let format_str = match Path::new(output).extension().and_then(|e| e.to_str()) {
+            Some("csv") => "CSV",
+            Some("parquet") => "PARQUET",
+            _ => {
+                return Err(DuckDbError::InvalidFileFormat(format!(
+                    "Output must be a .csv or .parquet file: {}",
+                    output
+                )));
+            }
+        };
+
+        let mut exprs = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let expr = column.kind.expression()?;
+            let expr = if column.null_ratio > 0.0 {
+                format!("CASE WHEN random() < {} THEN NULL ELSE {} END", column.null_ratio, expr)
+            } else {
+                expr
+            };
+            exprs.push(format!("{} AS \"{}\"", expr, column.name.replace('"', "\"\"")));
+        }
+
+        let (pending, temp) = prepare_write(output);
+        let query = format!(
+            "COPY (SELECT {} FROM range({})) TO '{}' (FORMAT {})",
+            exprs.join(", "),
+            rows,
+            temp,
+            format_str
+        );
+
+        let connection = open_connection()?;
+        if let Some(seed) = seed {
+            connection
+                .execute(&format!("SELECT setseed({})", seed), [])
+                .map_err(|e| DuckDbError::QueryError(format!("Failed to set seed: {}", e)))?;
+        }
+
+        let started = Instant::now();
+        connection
+            .execute(&query, [])
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to generate data: {}", e)))?;
+        finish_write(pending)?;
+        tracing::debug!(
+            kind = "generate",
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            query,
+            "duckdb query"
+        );
+        Ok(())
+    }
+}
+
+impl ColumnKind {
+    /// DuckDB expression producing one value per row of `range(n)`
+    fn expression(&self) -> Result<String, DuckDbError> {
+        Ok(match self {
+            ColumnKind::Sequence { start } => format!("range + {}", start),
+            ColumnKind::Int { min, max } => {
+                if max < min {
+                    return Err(DuckDbError::QueryError(format!("int range {}..{} is empty", min, max)));
+                }
+                format!("CAST(floor(random() * ({} - {} + 1)) + {} AS BIGINT)", max, min, min)
+            }
+            ColumnKind::Float { min, max } => format!("{} + random() * ({} - {})", min, max, min),
+            ColumnKind::Bool => "random() < 0.5".to_string(),
+            ColumnKind::Date { start, end } => {
+                let start = date_literal(start)?;
+                let end = date_literal(end)?;
+                format!(
+                    "{0} + CAST(floor(random() * ({1} - {0} + 1)) AS INTEGER)",
+                    start, end
+                )
+            }
+            ColumnKind::Enum { values } => {
+                if values.is_empty() {
+                    return Err(DuckDbError::QueryError("enum column has no values".to_string()));
+                }
+                pick(values.iter().map(|v| v.as_str()))
+            }
+            ColumnKind::Name => format!(
+                "{} || ' ' || {}",
+                pick(FIRST_NAMES.iter().copied()),
+                pick(LAST_NAMES.iter().copied())
+            ),
+            ColumnKind::Email => format!(
+                "lower({}) || '.' || CAST(range AS VARCHAR) || '@example.com'",
+                pick(FIRST_NAMES.iter().copied())
+            ),
+            ColumnKind::Uuid => "CAST(gen_random_uuid() AS VARCHAR)".to_string(),
+        })
+    }
+}
+
+/// Expression picking a random element of `values` (DuckDB lists are 1-based)
+fn pick<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    let quoted: Vec<String> = values.map(|v| format!("'{}'", v.replace('\'', "''"))).collect();
+    format!(
+        "([{}])[1 + CAST(floor(random() * {}) AS INTEGER)]",
+        quoted.join(", "),
+        quoted.len()
+    )
+}
+
+fn date_literal(date: &str) -> Result<String, DuckDbError> {
+    let valid = date.len() == 10 && date.chars().all(|c| c.is_ascii_digit() || c == '-');
+    if !valid {
+        return Err(DuckDbError::QueryError(format!("Invalid date '{}' (expected YYYY-MM-DD)", date)));
+    }
+    Ok(format!("DATE '{}'", date))
+}
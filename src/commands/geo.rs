@@ -0,0 +1,173 @@
+/// How a geo column stores its geometries
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoEncoding {
+    /// Well-known text, e.g. `POINT (2.35 48.85)`
+    Wkt,
+    /// Well-known binary in a BLOB column
+    Wkb,
+    /// GeoJSON geometry objects stored as strings
+    GeoJson,
+    /// DuckDB's own `GEOMETRY` type
+    Geometry,
+    /// A numeric latitude column and a numeric longitude column
+    LatLon,
+}
+
+impl GeoEncoding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GeoEncoding::Wkt => "WKT",
+            GeoEncoding::Wkb => "WKB",
+            GeoEncoding::GeoJson => "GeoJSON",
+            GeoEncoding::Geometry => "GEOMETRY",
+            GeoEncoding::LatLon => "lat/lon",
+        }
+    }
+}
+
+/// A column (or latitude/longitude pair) holding geometries
+#[derive(Debug, Clone)]
+pub struct GeoColumn {
+    pub encoding: GeoEncoding,
+    /// The geometry column, or the latitude then the longitude column
+    pub columns: Vec<String>,
+}
+
+impl GeoColumn {
+    /// "geom (WKT)" or "lat + lon (lat/lon)"
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.columns.join(" + "), self.encoding.label())
+    }
+
+    pub fn contains(&self, column: &str) -> bool {
+        self.columns.iter().any(|c| c == column)
+    }
+
+    /// SQL building a `GEOMETRY` from the column(s); needs the spatial extension
+    pub fn geometry_expr(&self) -> String {
+        let col = |i: usize| quote(&self.columns[i]);
+        match self.encoding {
+            GeoEncoding::Wkt => format!("ST_GeomFromText({})", col(0)),
+            GeoEncoding::Wkb => format!("ST_GeomFromWKB({})", col(0)),
+            GeoEncoding::GeoJson => format!("ST_GeomFromGeoJSON({})", col(0)),
+            GeoEncoding::Geometry => col(0),
+            GeoEncoding::LatLon => format!("ST_Point({}, {})", col(1), col(0)),
+        }
+    }
+
+    /// SQL for the longitude (x) and latitude (y) of a representative point
+    pub fn point_exprs(&self) -> (String, String) {
+        match self.encoding {
+            // No need for the spatial extension when the coordinates are columns
+            GeoEncoding::LatLon => (
+                format!("CAST({} AS DOUBLE)", quote(&self.columns[1])),
+                format!("CAST({} AS DOUBLE)", quote(&self.columns[0])),
+            ),
+            _ => {
+                let centroid = format!("ST_Centroid({})", self.geometry_expr());
+                (format!("ST_X({})", centroid), format!("ST_Y({})", centroid))
+            }
+        }
+    }
+
+    pub fn needs_spatial(&self) -> bool {
+        self.encoding != GeoEncoding::LatLon
+    }
+}
+
+const WKT_TYPES: &[&str] = &[
+    "POINT", "LINESTRING", "POLYGON", "MULTIPOINT", "MULTILINESTRING", "MULTIPOLYGON",
+    "GEOMETRYCOLLECTION",
+];
+
+/// Column names read as a latitude, with the matching longitude names
+const LAT_LON_NAMES: &[(&str, &[&str])] = &[
+    ("lat", &["lon", "lng", "long"]),
+    ("latitude", &["longitude"]),
+];
+
+/// `POINT (1 2)`, `SRID=4326;POLYGON((...))`, `LINESTRING Z (...)`, `POINT EMPTY`…
+pub fn is_wkt(value: &str) -> bool {
+    let value = value.trim().to_ascii_uppercase();
+    let value = match value.strip_prefix("SRID=") {
+        Some(rest) => rest.split_once(';').map(|(_, wkt)| wkt.to_string()).unwrap_or_default(),
+        None => value,
+    };
+    WKT_TYPES.iter().any(|t| {
+        value.strip_prefix(t).is_some_and(|rest| {
+            let rest = rest.trim_start();
+            rest.starts_with('(')
+                || rest.starts_with("EMPTY")
+                || ["Z", "M", "ZM"].iter().any(|dim| {
+                    rest.strip_prefix(dim).is_some_and(|r| r.trim_start().starts_with('('))
+                })
+        })
+    })
+}
+
+/// A JSON object with a geometry `type` and `coordinates` (or `geometries`)
+pub fn is_geojson(value: &str) -> bool {
+    let value = value.trim();
+    value.starts_with('{')
+        && value.contains("\"type\"")
+        && (value.contains("\"coordinates\"") || value.contains("\"geometries\""))
+}
+
+/// Hex of a WKB (or EWKB) value: a byte-order flag, then a geometry type of 1–7
+/// with optional Z/M offsets
+pub fn is_wkb_hex(hex: &str) -> bool {
+    if hex.len() < 10 {
+        return false;
+    }
+    let Ok(bytes) = (0..5).map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)).collect::<Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    let raw = [bytes[1], bytes[2], bytes[3], bytes[4]];
+    let kind = match bytes[0] {
+        0 => u32::from_be_bytes(raw),
+        1 => u32::from_le_bytes(raw),
+        _ => return false,
+    };
+    // EWKB keeps the SRID/Z/M flags in the high bits
+    (1..=7).contains(&((kind & 0x0FFF_FFFF) % 1000))
+}
+
+/// Latitude/longitude column pairs among `numeric` column names, matched on
+/// the whole name or on a shared prefix/suffix (`pickup_lat` + `pickup_lon`)
+pub fn lat_lon_pairs(numeric: &[String]) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for lat in numeric {
+        let lower = lat.to_lowercase();
+        for (lat_name, lon_names) in LAT_LON_NAMES {
+            let Some((prefix, suffix)) = split_around(&lower, lat_name) else {
+                continue;
+            };
+            let lon = lon_names.iter().find_map(|lon_name| {
+                let wanted = format!("{}{}{}", prefix, lon_name, suffix);
+                numeric.iter().find(|c| c.to_lowercase() == wanted)
+            });
+            if let Some(lon) = lon
+                && !pairs.iter().any(|(a, _): &(String, String)| a == lat)
+            {
+                pairs.push((lat.clone(), lon.clone()));
+            }
+        }
+    }
+    pairs
+}
+
+/// Splits `name` around `word` when the word stands on its own, separated
+/// from the rest by `_`, `-`, `.` or a space
+fn split_around<'a>(name: &'a str, word: &str) -> Option<(&'a str, &'a str)> {
+    let is_separator = |c: Option<char>| c.is_none_or(|c| matches!(c, '_' | '-' | '.' | ' '));
+    name.match_indices(word).find_map(|(start, _)| {
+        let (prefix, rest) = name.split_at(start);
+        let suffix = &rest[word.len()..];
+        (is_separator(prefix.chars().last()) && is_separator(suffix.chars().next())).then_some((prefix, suffix))
+    })
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
@@ -0,0 +1,275 @@
+use std::path::Path;
+
+use anyhow::{Context, bail};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use serde_json::{Map, Value, json};
+
+/// GPX tracks and KML placemarks, read into the same FeatureCollection a
+/// `.geojson` file would give
+pub fn is_geo_xml(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("gpx") | Some("kml"))
+}
+
+/// Reads a `.gpx` or `.kml` file as a GeoJSON FeatureCollection
+pub fn read(path: &Path) -> anyhow::Result<Value> {
+    let xml = std::fs::read_to_string(path)?;
+    let features = match path.extension().and_then(|e| e.to_str()) {
+        Some("gpx") => {
+            let mut gpx = Gpx::default();
+            walk(&xml, &mut gpx).context("Invalid GPX")?;
+            gpx.features
+        }
+        Some("kml") => {
+            let mut kml = Kml::default();
+            walk(&xml, &mut kml).context("Invalid KML")?;
+            kml.features
+        }
+        _ => bail!("Not a GPX or KML file: {}", path.display()),
+    };
+    Ok(json!({ "type": "FeatureCollection", "features": features }))
+}
+
+/// Callbacks for `walk`, with element names stripped of their namespace prefix
+trait XmlHandler {
+    fn start(&mut self, name: &str, element: &BytesStart) -> anyhow::Result<()>;
+    /// `text` is what the element directly contains, trimmed
+    fn end(&mut self, name: &str, text: &str);
+}
+
+/// Streams the elements of `xml` to `handler`; self-closing elements get
+/// both calls
+fn walk(xml: &str, handler: &mut impl XmlHandler) -> anyhow::Result<()> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut text = String::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                text.clear();
+                handler.start(&local_name(&e), &e)?;
+            }
+            Event::Empty(e) => {
+                let name = local_name(&e);
+                handler.start(&name, &e)?;
+                handler.end(&name, "");
+            }
+            Event::Text(t) => text.push_str(&t.unescape()?),
+            Event::CData(c) => text.push_str(&String::from_utf8_lossy(&c)),
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                handler.end(&name, text.trim());
+                text.clear();
+            }
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).to_string()
+}
+
+fn attribute(element: &BytesStart, name: &str) -> anyhow::Result<Option<String>> {
+    for attr in element.attributes() {
+        let attr = attr?;
+        if attr.key.local_name().as_ref() == name.as_bytes() {
+            return Ok(Some(attr.unescape_value()?.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+fn feature(geometry: Value, properties: Map<String, Value>) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+/// Numbers stay numbers (elevations, times in seconds…), the rest is text
+fn property_value(text: &str) -> Value {
+    text.parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+/// Waypoints become points, routes line strings, and tracks line strings (or
+/// multi line strings with several segments). Point elevations (`<ele>`) are
+/// kept as a third coordinate.
+#[derive(Default)]
+struct Gpx {
+    features: Vec<Value>,
+    /// Child elements of the waypoint, route or track being read
+    properties: Map<String, Value>,
+    in_feature: bool,
+    /// Inside a route or track point, whose children aren't feature properties
+    in_vertex: bool,
+    waypoint: Option<Vec<f64>>,
+    /// Points of the route or track segment being read
+    line: Vec<Vec<f64>>,
+    segments: Vec<Vec<Vec<f64>>>,
+}
+
+impl Gpx {
+    fn point(element: &BytesStart) -> anyhow::Result<Vec<f64>> {
+        let coordinate = |name: &str| -> anyhow::Result<f64> {
+            let value = attribute(element, name)?.with_context(|| format!("point without {}", name))?;
+            value.trim().parse().with_context(|| format!("invalid {} '{}'", name, value))
+        };
+        Ok(vec![coordinate("lon")?, coordinate("lat")?])
+    }
+}
+
+impl XmlHandler for Gpx {
+    fn start(&mut self, name: &str, element: &BytesStart) -> anyhow::Result<()> {
+        match name {
+            "wpt" => {
+                self.waypoint = Some(Self::point(element)?);
+                self.properties.clear();
+                self.in_feature = true;
+            }
+            "rte" | "trk" => {
+                self.properties.clear();
+                self.line.clear();
+                self.segments.clear();
+                self.in_feature = true;
+            }
+            "rtept" | "trkpt" => {
+                self.line.push(Self::point(element)?);
+                self.in_vertex = true;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, name: &str, text: &str) {
+        match name {
+            "rtept" | "trkpt" => self.in_vertex = false,
+            "trkseg" => self.segments.push(std::mem::take(&mut self.line)),
+            "wpt" => {
+                let coordinates = self.waypoint.take().unwrap_or_default();
+                let geometry = json!({ "type": "Point", "coordinates": coordinates });
+                self.features.push(feature(geometry, std::mem::take(&mut self.properties)));
+                self.in_feature = false;
+            }
+            "rte" => {
+                let geometry = json!({ "type": "LineString", "coordinates": std::mem::take(&mut self.line) });
+                let mut properties = std::mem::take(&mut self.properties);
+                properties.insert("gpx_type".to_string(), json!("route"));
+                self.features.push(feature(geometry, properties));
+                self.in_feature = false;
+            }
+            "trk" => {
+                let mut segments = std::mem::take(&mut self.segments);
+                let geometry = if segments.len() == 1 {
+                    json!({ "type": "LineString", "coordinates": segments.remove(0) })
+                } else {
+                    json!({ "type": "MultiLineString", "coordinates": segments })
+                };
+                let mut properties = std::mem::take(&mut self.properties);
+                properties.insert("gpx_type".to_string(), json!("track"));
+                self.features.push(feature(geometry, properties));
+                self.in_feature = false;
+            }
+            "ele" if self.in_vertex || self.waypoint.is_some() => {
+                let point = if self.in_vertex { self.line.last_mut() } else { self.waypoint.as_mut() };
+                if let (Some(point), Ok(ele)) = (point, text.parse::<f64>()) {
+                    point.truncate(2);
+                    point.push(ele);
+                }
+            }
+            _ if self.in_feature && !self.in_vertex && !text.is_empty() => {
+                self.properties.insert(name.to_string(), property_value(text));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Each placemark becomes a feature with its name, description and extended
+/// data as properties. A placemark with several geometries (`MultiGeometry`)
+/// gets a GeometryCollection.
+#[derive(Default)]
+struct Kml {
+    features: Vec<Value>,
+    in_placemark: bool,
+    properties: Map<String, Value>,
+    /// `name` attribute of the `Data` or `SimpleData` element being read
+    data_name: Option<String>,
+    /// Coordinates of the last `<coordinates>` element
+    coordinates: Vec<Vec<f64>>,
+    /// Rings of the polygon being read, outer ring first
+    rings: Vec<Vec<Vec<f64>>>,
+    geometries: Vec<Value>,
+}
+
+impl Kml {
+    /// `lon,lat[,alt]` tuples separated by whitespace; malformed tuples are skipped
+    fn parse_coordinates(text: &str) -> Vec<Vec<f64>> {
+        text.split_whitespace()
+            .filter_map(|tuple| {
+                let values: Result<Vec<f64>, _> = tuple.split(',').map(|v| v.trim().parse::<f64>()).collect();
+                values.ok().filter(|v| v.len() >= 2)
+            })
+            .collect()
+    }
+}
+
+impl XmlHandler for Kml {
+    fn start(&mut self, name: &str, element: &BytesStart) -> anyhow::Result<()> {
+        match name {
+            "Placemark" => {
+                self.in_placemark = true;
+                self.properties.clear();
+                self.geometries.clear();
+            }
+            "Data" | "SimpleData" => self.data_name = attribute(element, "name")?,
+            "Polygon" => self.rings.clear(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn end(&mut self, name: &str, text: &str) {
+        if !self.in_placemark {
+            return;
+        }
+        match name {
+            "name" | "description" if !text.is_empty() => {
+                self.properties.insert(name.to_string(), Value::String(text.to_string()));
+            }
+            "value" | "SimpleData" => {
+                if let Some(data_name) = self.data_name.take() {
+                    self.properties.insert(data_name, property_value(text));
+                }
+            }
+            "coordinates" => self.coordinates = Self::parse_coordinates(text),
+            "Point" => {
+                if let Some(point) = std::mem::take(&mut self.coordinates).into_iter().next() {
+                    self.geometries.push(json!({ "type": "Point", "coordinates": point }));
+                }
+            }
+            "LineString" => {
+                let line = std::mem::take(&mut self.coordinates);
+                self.geometries.push(json!({ "type": "LineString", "coordinates": line }));
+            }
+            "LinearRing" => self.rings.push(std::mem::take(&mut self.coordinates)),
+            "Polygon" => {
+                let rings = std::mem::take(&mut self.rings);
+                self.geometries.push(json!({ "type": "Polygon", "coordinates": rings }));
+            }
+            "Placemark" => {
+                let geometry = match self.geometries.len() {
+                    0 => Value::Null,
+                    1 => self.geometries.remove(0),
+                    _ => json!({ "type": "GeometryCollection", "geometries": std::mem::take(&mut self.geometries) }),
+                };
+                self.features.push(feature(geometry, std::mem::take(&mut self.properties)));
+                self.in_placemark = false;
+            }
+            _ => {}
+        }
+    }
+}
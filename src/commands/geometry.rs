@@ -0,0 +1,547 @@
+use anyhow::{Context, bail};
+use serde_json::{Value, json};
+
+/// A position: x, y and an optional z (measures are dropped when reading)
+pub type Coord = Vec<f64>;
+
+/// A single geometry, as read from WKT, WKB or GeoJSON
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    /// `None` for `POINT EMPTY`
+    Point(Option<Coord>),
+    LineString(Vec<Coord>),
+    /// Outer ring first, then the holes
+    Polygon(Vec<Vec<Coord>>),
+    MultiPoint(Vec<Coord>),
+    MultiLineString(Vec<Vec<Coord>>),
+    MultiPolygon(Vec<Vec<Vec<Coord>>>),
+    GeometryCollection(Vec<Geometry>),
+}
+
+/// Text encodings a geometry can be read from and written to
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GeometryFormat {
+    #[default]
+    Wkt,
+    /// Hex-encoded WKB (or EWKB when reading)
+    Wkb,
+    GeoJson,
+}
+
+impl GeometryFormat {
+    pub const ALL: [GeometryFormat; 3] = [GeometryFormat::Wkt, GeometryFormat::Wkb, GeometryFormat::GeoJson];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GeometryFormat::Wkt => "WKT",
+            GeometryFormat::Wkb => "WKB (hex)",
+            GeometryFormat::GeoJson => "GeoJSON",
+        }
+    }
+}
+
+/// Reads a geometry, telling the format from the text: GeoJSON starts with
+/// `{`, hex WKB (optionally prefixed with `0x` or `\x`) is all hex digits,
+/// anything else is read as WKT (with an optional `SRID=…;` prefix)
+pub fn parse(input: &str) -> anyhow::Result<(Geometry, GeometryFormat)> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("Nothing to parse");
+    }
+    if input.starts_with('{') {
+        let value: Value = serde_json::from_str(input).context("Invalid JSON")?;
+        return Ok((Geometry::from_geojson(&value)?, GeometryFormat::GeoJson));
+    }
+    let hex = input.strip_prefix("0x").or_else(|| input.strip_prefix("\\x")).unwrap_or(input);
+    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok((Geometry::from_wkb_hex(hex)?, GeometryFormat::Wkb));
+    }
+    Ok((Geometry::from_wkt(input)?, GeometryFormat::Wkt))
+}
+
+impl Geometry {
+    /// The GeoJSON type name
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Geometry::Point(_) => "Point",
+            Geometry::LineString(_) => "LineString",
+            Geometry::Polygon(_) => "Polygon",
+            Geometry::MultiPoint(_) => "MultiPoint",
+            Geometry::MultiLineString(_) => "MultiLineString",
+            Geometry::MultiPolygon(_) => "MultiPolygon",
+            Geometry::GeometryCollection(_) => "GeometryCollection",
+        }
+    }
+
+    fn wkb_code(&self) -> u32 {
+        match self {
+            Geometry::Point(_) => 1,
+            Geometry::LineString(_) => 2,
+            Geometry::Polygon(_) => 3,
+            Geometry::MultiPoint(_) => 4,
+            Geometry::MultiLineString(_) => 5,
+            Geometry::MultiPolygon(_) => 6,
+            Geometry::GeometryCollection(_) => 7,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Geometry::Point(c) => c.is_none(),
+            Geometry::LineString(c) | Geometry::MultiPoint(c) => c.is_empty(),
+            Geometry::Polygon(r) | Geometry::MultiLineString(r) => r.is_empty(),
+            Geometry::MultiPolygon(p) => p.is_empty(),
+            Geometry::GeometryCollection(g) => g.is_empty(),
+        }
+    }
+
+    /// Every position, in order, including those of collection members
+    pub fn vertices(&self) -> Vec<&Coord> {
+        let mut out = Vec::new();
+        self.collect_vertices(&mut out);
+        out
+    }
+
+    fn collect_vertices<'a>(&'a self, out: &mut Vec<&'a Coord>) {
+        match self {
+            Geometry::Point(c) => out.extend(c),
+            Geometry::LineString(c) | Geometry::MultiPoint(c) => out.extend(c),
+            Geometry::Polygon(r) | Geometry::MultiLineString(r) => out.extend(r.iter().flatten()),
+            Geometry::MultiPolygon(p) => out.extend(p.iter().flatten().flatten()),
+            Geometry::GeometryCollection(g) => g.iter().for_each(|g| g.collect_vertices(out)),
+        }
+    }
+
+    /// Whether the positions carry a height
+    pub fn has_z(&self) -> bool {
+        self.vertices().iter().any(|c| c.len() > 2)
+    }
+
+    /// (min x, min y, max x, max y), or `None` when empty
+    pub fn bbox(&self) -> Option<(f64, f64, f64, f64)> {
+        self.vertices().iter().fold(None, |bbox, c| {
+            let (x, y) = (c[0], c[1]);
+            Some(match bbox {
+                None => (x, y, x, y),
+                Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+            })
+        })
+    }
+
+    pub fn to_format(&self, format: GeometryFormat) -> String {
+        match format {
+            GeometryFormat::Wkt => self.to_wkt(),
+            GeometryFormat::Wkb => self.to_wkb_hex(),
+            GeometryFormat::GeoJson => serde_json::to_string_pretty(&self.to_geojson()).unwrap_or_default(),
+        }
+    }
+
+    // --- WKT ---
+
+    pub fn from_wkt(input: &str) -> anyhow::Result<Self> {
+        let input = input.trim();
+        // EWKT: SRID=4326;POINT (…)
+        let input = match input.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("SRID=") => {
+                input.split_once(';').map(|(_, wkt)| wkt).context("SRID without a ';'")?
+            }
+            _ => input,
+        };
+        let mut parser = WktParser { tokens: tokenize(input), pos: 0, measured: false };
+        let geometry = parser.geometry()?;
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected '{}' after the geometry", token);
+        }
+        Ok(geometry)
+    }
+
+    pub fn to_wkt(&self) -> String {
+        let z = self.has_z();
+        let name = self.type_name().to_ascii_uppercase();
+        format!("{}{} {}", name, if z { " Z" } else { "" }, self.wkt_body(z))
+    }
+
+    fn wkt_body(&self, z: bool) -> String {
+        if self.is_empty() {
+            return "EMPTY".to_string();
+        }
+        let coord = |c: &Coord| {
+            let n = if z { 3 } else { 2 };
+            (0..n).map(|i| c.get(i).copied().unwrap_or(0.0).to_string()).collect::<Vec<_>>().join(" ")
+        };
+        let line = |cs: &Vec<Coord>| format!("({})", cs.iter().map(coord).collect::<Vec<_>>().join(", "));
+        let rings = |rs: &Vec<Vec<Coord>>| format!("({})", rs.iter().map(line).collect::<Vec<_>>().join(", "));
+        match self {
+            Geometry::Point(Some(c)) => format!("({})", coord(c)),
+            Geometry::Point(None) => "EMPTY".to_string(),
+            Geometry::LineString(cs) => line(cs),
+            Geometry::MultiPoint(cs) => {
+                format!("({})", cs.iter().map(|c| format!("({})", coord(c))).collect::<Vec<_>>().join(", "))
+            }
+            Geometry::Polygon(rs) | Geometry::MultiLineString(rs) => rings(rs),
+            Geometry::MultiPolygon(ps) => format!("({})", ps.iter().map(rings).collect::<Vec<_>>().join(", ")),
+            Geometry::GeometryCollection(gs) => {
+                format!("({})", gs.iter().map(Geometry::to_wkt).collect::<Vec<_>>().join(", "))
+            }
+        }
+    }
+
+    // --- WKB ---
+
+    pub fn from_wkb_hex(hex: &str) -> anyhow::Result<Self> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Hex WKB can only contain hex digits");
+        }
+        if !hex.len().is_multiple_of(2) {
+            bail!("Hex WKB has an odd number of digits");
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .context("Invalid hex")?;
+        let mut reader = WkbReader { bytes: &bytes, pos: 0 };
+        let geometry = reader.geometry()?;
+        if reader.pos != bytes.len() {
+            bail!("{} bytes left after the geometry", bytes.len() - reader.pos);
+        }
+        Ok(geometry)
+    }
+
+    /// Little-endian ISO WKB (Z types numbered from 1001), as uppercase hex
+    pub fn to_wkb_hex(&self) -> String {
+        let mut bytes = Vec::new();
+        self.write_wkb(&mut bytes, self.has_z());
+        bytes.iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    fn write_wkb(&self, out: &mut Vec<u8>, z: bool) {
+        out.push(1);
+        out.extend((self.wkb_code() + if z { 1000 } else { 0 }).to_le_bytes());
+        let coord = |out: &mut Vec<u8>, c: &Coord| {
+            let n = if z { 3 } else { 2 };
+            for i in 0..n {
+                out.extend(c.get(i).copied().unwrap_or(0.0).to_le_bytes());
+            }
+        };
+        let line = |out: &mut Vec<u8>, cs: &Vec<Coord>| {
+            out.extend((cs.len() as u32).to_le_bytes());
+            cs.iter().for_each(|c| coord(out, c));
+        };
+        let rings = |out: &mut Vec<u8>, rs: &Vec<Vec<Coord>>| {
+            out.extend((rs.len() as u32).to_le_bytes());
+            rs.iter().for_each(|r| line(out, r));
+        };
+        match self {
+            // An empty point is written with NaN coordinates, as PostGIS does
+            Geometry::Point(c) => coord(out, c.as_ref().unwrap_or(&vec![f64::NAN; 3])),
+            Geometry::LineString(cs) => line(out, cs),
+            Geometry::Polygon(rs) => rings(out, rs),
+            Geometry::MultiPoint(cs) => {
+                out.extend((cs.len() as u32).to_le_bytes());
+                cs.iter().for_each(|c| Geometry::Point(Some(c.clone())).write_wkb(out, z));
+            }
+            Geometry::MultiLineString(rs) => {
+                out.extend((rs.len() as u32).to_le_bytes());
+                rs.iter().for_each(|r| Geometry::LineString(r.clone()).write_wkb(out, z));
+            }
+            Geometry::MultiPolygon(ps) => {
+                out.extend((ps.len() as u32).to_le_bytes());
+                ps.iter().for_each(|p| Geometry::Polygon(p.clone()).write_wkb(out, z));
+            }
+            Geometry::GeometryCollection(gs) => {
+                out.extend((gs.len() as u32).to_le_bytes());
+                gs.iter().for_each(|g| g.write_wkb(out, z));
+            }
+        }
+    }
+
+    // --- GeoJSON ---
+
+    /// Reads a GeoJSON geometry, or the geometry of a Feature
+    pub fn from_geojson(value: &Value) -> anyhow::Result<Self> {
+        let kind = value.get("type").and_then(|t| t.as_str()).context("GeoJSON without a \"type\"")?;
+        if kind == "Feature" {
+            return Self::from_geojson(value.get("geometry").context("Feature without a geometry")?);
+        }
+        if kind == "GeometryCollection" {
+            let members = value.get("geometries").and_then(|g| g.as_array()).context("Missing \"geometries\"")?;
+            return Ok(Geometry::GeometryCollection(
+                members.iter().map(Self::from_geojson).collect::<anyhow::Result<_>>()?,
+            ));
+        }
+        let coordinates = value.get("coordinates").cloned().context("Missing \"coordinates\"")?;
+        let invalid = || format!("Invalid coordinates for a {}", kind);
+        let geometry = match kind {
+            "Point" => {
+                let c: Coord = serde_json::from_value(coordinates).with_context(invalid)?;
+                Geometry::Point((!c.is_empty()).then_some(c))
+            }
+            "LineString" => Geometry::LineString(serde_json::from_value(coordinates).with_context(invalid)?),
+            "Polygon" => Geometry::Polygon(serde_json::from_value(coordinates).with_context(invalid)?),
+            "MultiPoint" => Geometry::MultiPoint(serde_json::from_value(coordinates).with_context(invalid)?),
+            "MultiLineString" => Geometry::MultiLineString(serde_json::from_value(coordinates).with_context(invalid)?),
+            "MultiPolygon" => Geometry::MultiPolygon(serde_json::from_value(coordinates).with_context(invalid)?),
+            other => bail!("Not a GeoJSON geometry type: {}", other),
+        };
+        if geometry.vertices().iter().any(|c| c.len() < 2) {
+            bail!("{}", invalid());
+        }
+        Ok(geometry)
+    }
+
+    pub fn to_geojson(&self) -> Value {
+        match self {
+            Geometry::Point(c) => json!({ "type": "Point", "coordinates": c.clone().unwrap_or_default() }),
+            Geometry::LineString(c) | Geometry::MultiPoint(c) => json!({ "type": self.type_name(), "coordinates": c }),
+            Geometry::Polygon(r) | Geometry::MultiLineString(r) => {
+                json!({ "type": self.type_name(), "coordinates": r })
+            }
+            Geometry::MultiPolygon(p) => json!({ "type": "MultiPolygon", "coordinates": p }),
+            Geometry::GeometryCollection(g) => json!({
+                "type": "GeometryCollection",
+                "geometries": g.iter().map(Geometry::to_geojson).collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+/// Words, numbers and the `(`, `)` and `,` punctuation of a WKT string
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in input.chars() {
+        if c.is_whitespace() || matches!(c, '(' | ')' | ',') {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+struct WktParser {
+    tokens: Vec<String>,
+    pos: usize,
+    /// The geometry being read is tagged `M`: a third number is a measure, not a height
+    measured: bool,
+}
+
+impl WktParser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn expect(&mut self, token: &str) -> anyhow::Result<()> {
+        match self.peek() {
+            Some(t) if t == token => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(t) => bail!("Expected '{}', found '{}'", token, t),
+            None => bail!("Expected '{}' at the end", token),
+        }
+    }
+
+    fn geometry(&mut self) -> anyhow::Result<Geometry> {
+        let name = self.peek().context("Expected a geometry type")?.to_ascii_uppercase();
+        self.pos += 1;
+        self.measured = false;
+        match self.peek().map(str::to_ascii_uppercase).as_deref() {
+            Some("Z") | Some("ZM") => self.pos += 1,
+            Some("M") => {
+                self.measured = true;
+                self.pos += 1;
+            }
+            _ => {}
+        }
+        if self.peek().is_some_and(|t| t.eq_ignore_ascii_case("EMPTY")) {
+            self.pos += 1;
+            return Ok(match name.as_str() {
+                "POINT" => Geometry::Point(None),
+                "LINESTRING" => Geometry::LineString(Vec::new()),
+                "POLYGON" => Geometry::Polygon(Vec::new()),
+                "MULTIPOINT" => Geometry::MultiPoint(Vec::new()),
+                "MULTILINESTRING" => Geometry::MultiLineString(Vec::new()),
+                "MULTIPOLYGON" => Geometry::MultiPolygon(Vec::new()),
+                "GEOMETRYCOLLECTION" => Geometry::GeometryCollection(Vec::new()),
+                other => bail!("Unknown geometry type {}", other),
+            });
+        }
+        Ok(match name.as_str() {
+            "POINT" => {
+                self.expect("(")?;
+                let c = self.coord()?;
+                self.expect(")")?;
+                Geometry::Point(Some(c))
+            }
+            "LINESTRING" => Geometry::LineString(self.coords()?),
+            "POLYGON" => Geometry::Polygon(self.rings()?),
+            // Both MULTIPOINT (1 2, 3 4) and MULTIPOINT ((1 2), (3 4))
+            "MULTIPOINT" => Geometry::MultiPoint(self.list(|p| {
+                if p.peek() == Some("(") {
+                    p.expect("(")?;
+                    let c = p.coord()?;
+                    p.expect(")")?;
+                    Ok(c)
+                } else {
+                    p.coord()
+                }
+            })?),
+            "MULTILINESTRING" => Geometry::MultiLineString(self.list(Self::coords)?),
+            "MULTIPOLYGON" => Geometry::MultiPolygon(self.list(Self::rings)?),
+            "GEOMETRYCOLLECTION" => Geometry::GeometryCollection(self.list(Self::geometry)?),
+            other => bail!("Unknown geometry type {}", other),
+        })
+    }
+
+    /// `( item, item, … )`
+    fn list<T>(&mut self, item: impl Fn(&mut Self) -> anyhow::Result<T>) -> anyhow::Result<Vec<T>> {
+        self.expect("(")?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(",") {
+            self.pos += 1;
+            items.push(item(self)?);
+        }
+        self.expect(")")?;
+        Ok(items)
+    }
+
+    fn coords(&mut self) -> anyhow::Result<Vec<Coord>> {
+        self.list(Self::coord)
+    }
+
+    fn rings(&mut self) -> anyhow::Result<Vec<Vec<Coord>>> {
+        self.list(Self::coords)
+    }
+
+    fn coord(&mut self) -> anyhow::Result<Coord> {
+        let mut values = Vec::new();
+        while let Some(value) = self.peek().and_then(|t| t.parse::<f64>().ok()) {
+            values.push(value);
+            self.pos += 1;
+        }
+        if values.len() < 2 {
+            match self.peek() {
+                Some(t) => bail!("Expected coordinates, found '{}'", t),
+                None => bail!("Expected coordinates at the end"),
+            }
+        }
+        values.truncate(if self.measured { 2 } else { 3 });
+        Ok(values)
+    }
+}
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+/// How the positions of one WKB geometry are laid out
+#[derive(Clone, Copy)]
+struct WkbLayout {
+    little_endian: bool,
+    /// Numbers per position, including any measure
+    width: usize,
+    z: bool,
+}
+
+impl WkbReader<'_> {
+    fn take<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + N)
+            .context("WKB ends in the middle of a geometry")?;
+        self.pos += N;
+        Ok(bytes.try_into().expect("slice of N bytes"))
+    }
+
+    fn u32(&mut self, layout: WkbLayout) -> anyhow::Result<u32> {
+        let bytes = self.take::<4>()?;
+        Ok(if layout.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn coord(&mut self, layout: WkbLayout) -> anyhow::Result<Coord> {
+        let mut values = Vec::with_capacity(layout.width);
+        for _ in 0..layout.width {
+            let bytes = self.take::<8>()?;
+            values.push(if layout.little_endian { f64::from_le_bytes(bytes) } else { f64::from_be_bytes(bytes) });
+        }
+        values.truncate(if layout.z { 3 } else { 2 });
+        Ok(values)
+    }
+
+    fn coords(&mut self, layout: WkbLayout) -> anyhow::Result<Vec<Coord>> {
+        (0..self.u32(layout)?).map(|_| self.coord(layout)).collect()
+    }
+
+    fn geometry(&mut self) -> anyhow::Result<Geometry> {
+        let little_endian = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            b => bail!("Invalid WKB byte order {}", b),
+        };
+        let mut layout = WkbLayout { little_endian, width: 2, z: false };
+        let raw = self.u32(layout)?;
+        // EWKB keeps Z, M and SRID flags in the high bits; ISO adds 1000s to the type
+        let code = raw & 0x0FFF_FFFF;
+        let dims = code / 1000;
+        layout.z = raw & 0x8000_0000 != 0 || dims == 1 || dims == 3;
+        let m = raw & 0x4000_0000 != 0 || dims == 2 || dims == 3;
+        layout.width = 2 + usize::from(layout.z) + usize::from(m);
+        if raw & 0x2000_0000 != 0 {
+            self.u32(layout)?; // SRID
+        }
+        Ok(match code % 1000 {
+            1 => {
+                let c = self.coord(layout)?;
+                Geometry::Point((!c.iter().all(|v| v.is_nan())).then_some(c))
+            }
+            2 => Geometry::LineString(self.coords(layout)?),
+            3 => Geometry::Polygon((0..self.u32(layout)?).map(|_| self.coords(layout)).collect::<anyhow::Result<_>>()?),
+            kind @ 4..=7 => {
+                let members = (0..self.u32(layout)?).map(|_| self.geometry()).collect::<anyhow::Result<Vec<_>>>()?;
+                let mismatch = |g: &Geometry| anyhow::anyhow!("{} inside a multi-geometry of type {}", g.type_name(), kind);
+                match kind {
+                    4 => Geometry::MultiPoint(
+                        members
+                            .into_iter()
+                            .filter_map(|g| match g {
+                                Geometry::Point(c) => c.map(Ok),
+                                g => Some(Err(mismatch(&g))),
+                            })
+                            .collect::<anyhow::Result<_>>()?,
+                    ),
+                    5 => Geometry::MultiLineString(
+                        members
+                            .into_iter()
+                            .map(|g| match g {
+                                Geometry::LineString(c) => Ok(c),
+                                g => Err(mismatch(&g)),
+                            })
+                            .collect::<anyhow::Result<_>>()?,
+                    ),
+                    6 => Geometry::MultiPolygon(
+                        members
+                            .into_iter()
+                            .map(|g| match g {
+                                Geometry::Polygon(r) => Ok(r),
+                                g => Err(mismatch(&g)),
+                            })
+                            .collect::<anyhow::Result<_>>()?,
+                    ),
+                    _ => Geometry::GeometryCollection(members),
+                }
+            }
+            other => bail!("Unknown WKB geometry type {}", other),
+        })
+    }
+}
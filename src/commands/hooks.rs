@@ -0,0 +1,73 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+use anyhow::{Context, bail};
+
+use crate::config;
+
+/// Events a `[hooks]` command runs after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// A conversion wrote a CSV, Parquet or Excel file (or a partitioned directory)
+    Convert,
+    /// Rows were appended or upserted into an existing file
+    Export,
+    /// tippecanoe wrote a tileset
+    Tiles,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 3] = [HookEvent::Convert, HookEvent::Export, HookEvent::Tiles];
+
+    /// Key of the event in the `[hooks]` table
+    pub fn key(self) -> &'static str {
+        match self {
+            HookEvent::Convert => "after_convert",
+            HookEvent::Export => "after_export",
+            HookEvent::Tiles => "after_tiles",
+        }
+    }
+}
+
+/// Runs the command configured for `event`, if any, once `output` is in place.
+/// The command goes through `sh -c` (`cmd /C` on Windows) with the output path
+/// as its first argument (`$1`), and `FTOOL_EVENT` and `FTOOL_OUTPUT` set. It
+/// fails when the command can't start or exits non-zero, with the last lines
+/// it printed to stderr.
+pub fn run(event: HookEvent, output: &Path) -> anyhow::Result<()> {
+    let Some(command) = config::get().hooks.get(event.key()) else {
+        return Ok(());
+    };
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        // The name after the script becomes $0, so the path is $1
+        process.arg("-c").arg(command).arg("ftool-hook");
+        process
+    };
+    process
+        .arg(output)
+        .env("FTOOL_EVENT", event.key())
+        .env("FTOOL_OUTPUT", output)
+        .stdin(Stdio::null());
+
+    let started = Instant::now();
+    let result = process.output().with_context(|| format!("Failed to start the {} hook", event.key()))?;
+    tracing::debug!(event = event.key(), stdout = %String::from_utf8_lossy(&result.stdout), "hook output");
+    if !result.status.success() {
+        let log = String::from_utf8_lossy(&result.stderr);
+        let tail: Vec<&str> = log.lines().rev().take(5).collect::<Vec<_>>().into_iter().rev().collect();
+        bail!("the {} hook exited with {}:\n{}", event.key(), result.status, tail.join("\n"));
+    }
+    tracing::info!(
+        event = event.key(),
+        output = %output.display(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "ran hook"
+    );
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::decode::base64_encode;
+
+/// How long a request may take, connecting and reading the body included
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest response body read
+pub const MAX_BODY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// An HTTP request sent by `ftool json <url>` or the TUI's HTTP client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// Status, headers and body of a response, and how long it took
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub elapsed: Duration,
+}
+
+pub fn is_url(text: &str) -> bool {
+    text.starts_with("http://") || text.starts_with("https://")
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        Self { method: "GET".to_string(), url: url.to_string(), headers: Vec::new(), body: None }
+    }
+
+    /// Sets a header, replacing one of the same name (ignoring case)
+    pub fn header(&mut self, name: &str, value: &str) {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    /// Adds the headers configured for the URL's host under `[http_headers."host"]`,
+    /// without replacing headers already set. A value of `$NAME` is read from
+    /// the environment variable NAME, so tokens can stay out of the config file.
+    pub fn with_config_headers(mut self, config: &BTreeMap<String, BTreeMap<String, String>>) -> anyhow::Result<Self> {
+        let Some(headers) = config.get(host(&self.url)) else { return Ok(self) };
+        for (name, value) in headers {
+            if self.headers.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)) {
+                continue;
+            }
+            let value = expand_env(value).with_context(|| format!("Header {} for {}", name, host(&self.url)))?;
+            self.headers.push((name.clone(), value));
+        }
+        Ok(self)
+    }
+
+    /// `Authorization: Basic` from `user:password`
+    pub fn basic_auth(&mut self, credentials: &str) {
+        self.header("Authorization", &format!("Basic {}", base64_encode(credentials.as_bytes())));
+    }
+
+    pub fn bearer(&mut self, token: &str) {
+        self.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    /// Sends the request. Error statuses are returned as responses, not errors,
+    /// since their bodies are often worth inspecting.
+    pub fn send(&self) -> anyhow::Result<HttpResponse> {
+        let agent: ureq::Agent =
+            ureq::Agent::config_builder().http_status_as_error(false).timeout_global(Some(TIMEOUT)).build().into();
+        let mut request = ureq::http::Request::builder().method(self.method.as_str()).uri(&self.url);
+        if !self.headers.iter().any(|(n, _)| n.eq_ignore_ascii_case("accept")) {
+            request = request.header("Accept", "application/json");
+        }
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let invalid = || format!("Invalid request to {}", self.url);
+
+        let started = Instant::now();
+        let response = match &self.body {
+            Some(body) => agent.run(request.body(body.clone()).with_context(invalid)?),
+            None => agent.run(request.body(()).with_context(invalid)?),
+        };
+        let mut response = response.with_context(|| format!("{} {} failed", self.method, self.url))?;
+        let body = response
+            .body_mut()
+            .with_config()
+            .limit(MAX_BODY_BYTES)
+            .read_to_string()
+            .with_context(|| format!("Failed to read the response from {}", self.url))?;
+        let elapsed = started.elapsed();
+        let status = response.status();
+        tracing::info!(method = %self.method, url = %display_url(&self.url), status = status.as_u16(), elapsed_ms = elapsed.as_millis() as u64, "http request");
+
+        Ok(HttpResponse {
+            method: self.method.clone(),
+            url: self.url.clone(),
+            status: status.as_u16(),
+            reason: status.canonical_reason().unwrap_or_default().to_string(),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+                .collect(),
+            body,
+            elapsed,
+        })
+    }
+}
+
+impl HttpResponse {
+    /// `200 OK`
+    pub fn status_line(&self) -> String {
+        format!("{} {}", self.status, self.reason).trim_end().to_string()
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// A `$NAME` value read from the environment variable NAME; other values as they are
+pub fn expand_env(value: &str) -> anyhow::Result<String> {
+    match value.strip_prefix('$') {
+        Some(var) => std::env::var(var).with_context(|| format!("${} isn't set", var)),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Parses a `Name: value` header argument
+pub fn parse_header(text: &str) -> Result<(String, String), String> {
+    match text.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("expected 'Name: value', got '{}'", text)),
+    }
+}
+
+/// `url` without its query string and fragment, which may carry tokens, for logs
+pub fn display_url(url: &str) -> &str {
+    &url[..url.find(['?', '#']).unwrap_or(url.len())]
+}
+
+/// The `host` or `host:port` of a URL
+fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    authority.rsplit_once('@').map_or(authority, |(_, host)| host)
+}
@@ -1,48 +1,94 @@
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde_json::Value;
-use std::path::Path;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 use anyhow::Result;
 
+/// Temp files written by `save_pasted`, deleted by `remove_pasted`
+static PASTED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum FileKind {
     Json,
     GeoJson,
+    /// One JSON value per line (`.jsonl` / `.ndjson`), read as an array of records
+    JsonLines,
 }
 
 pub struct JsonInspector {
     pub root: Value,
     pub kind: FileKind,
+    /// Where reading stopped in a JSON Lines file, to pick up appended records
+    pub tail: Option<JsonLinesTail>,
 }
 
 impl JsonInspector {
     pub fn new(path: &Path) -> Result<Self> {
+        if super::geo_xml::is_geo_xml(path) {
+            return Ok(Self { root: super::geo_xml::read(path)?, kind: FileKind::GeoJson, tail: None });
+        }
+        if is_geojson_seq(path) {
+            let mut tail = JsonLinesTail::default();
+            let features = tail.read(path)?.into_iter().map(as_feature).collect();
+            let root = serde_json::json!({ "type": "FeatureCollection", "features": Value::Array(features) });
+            return Ok(Self { root, kind: FileKind::GeoJson, tail: Some(tail) });
+        }
+        if is_json_lines(path) {
+            let mut tail = JsonLinesTail::default();
+            let records = tail.read(path)?;
+            return Ok(Self { root: Value::Array(records), kind: FileKind::JsonLines, tail: Some(tail) });
+        }
         let content = std::fs::read_to_string(path)?;
         let root: Value = serde_json::from_str(&content)?;
         let kind = detect_kind(path, &root);
-        Ok(Self { root, kind })
+        Ok(Self { root, kind, tail: None })
     }
 
-    pub fn geojson_summary(&self) -> (usize, Vec<String>, Option<(f64, f64, f64, f64)>) {
-        let features = match self.root.get("features").and_then(|f| f.as_array()) {
-            Some(f) => f,
-            None => return (0, vec![], None),
-        };
-        let count = features.len();
-        let mut geom_types: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
-        let mut min_lon = f64::MAX;
-        let mut min_lat = f64::MAX;
-        let mut max_lon = f64::MIN;
-        let mut max_lat = f64::MIN;
-        let mut has_coords = false;
-        for feature in features {
-            if let Some(geom) = feature.get("geometry") {
-                if let Some(t) = geom.get("type").and_then(|t| t.as_str()) {
-                    geom_types.insert(t.to_string());
+    /// Reads at most `limit` records of a JSON Lines or GeoJSONSeq file, or the
+    /// first `limit` items of every array of a JSON document. The document is
+    /// still parsed to its end, but the items past the limit are never kept.
+    pub fn sample(path: &Path, limit: usize) -> Result<Self> {
+        if is_json_lines(path) || is_geojson_seq(path) {
+            let mut tail = JsonLinesTail::default();
+            let mut records = Vec::new();
+            for line in BufReader::new(File::open(path)?).split(b'\n') {
+                if records.len() >= limit {
+                    break;
                 }
-                collect_bbox(geom, &mut min_lon, &mut min_lat, &mut max_lon, &mut max_lat, &mut has_coords);
+                records.extend(tail.parse_line(&line?));
             }
+            return Ok(Self::from_records(path, records, None));
+        }
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(File::open(path)?));
+        let root = Truncated { limit }.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        let kind = detect_kind(path, &root);
+        Ok(Self { root, kind, tail: None })
+    }
+
+    /// Reads only the records in the last `bytes` of a JSON Lines or GeoJSONSeq
+    /// file, with the tail set to follow what gets appended after them
+    pub fn stream(path: &Path, bytes: u64) -> Result<Self> {
+        let mut tail = JsonLinesTail::near_end(path, bytes)?;
+        let records = tail.read(path)?;
+        Ok(Self::from_records(path, records, Some(tail)))
+    }
+
+    /// A JSON Lines root, or a FeatureCollection for GeoJSONSeq
+    fn from_records(path: &Path, records: Vec<Value>, tail: Option<JsonLinesTail>) -> Self {
+        if is_geojson_seq(path) {
+            let features = records.into_iter().map(as_feature).collect();
+            let root = serde_json::json!({ "type": "FeatureCollection", "features": Value::Array(features) });
+            return Self { root, kind: FileKind::GeoJson, tail };
         }
-        let bbox = if has_coords { Some((min_lon, min_lat, max_lon, max_lat)) } else { None };
-        (count, geom_types.into_iter().collect(), bbox)
+        Self { root: Value::Array(records), kind: FileKind::JsonLines, tail }
+    }
+
+    pub fn geojson_summary(&self) -> (usize, Vec<String>, Option<(f64, f64, f64, f64)>) {
+        geojson_summary(&self.root)
     }
 
     pub fn features_table(&self) -> (Vec<String>, Vec<Vec<String>>) {
@@ -77,16 +123,360 @@ impl JsonInspector {
     }
 }
 
+/// Changes applied to a FeatureCollection before writing it out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pruning {
+    /// Properties removed from every feature
+    pub drop: Vec<String>,
+    /// Keep only the first feature of each distinct geometry
+    pub dedupe: bool,
+}
+
+impl Pruning {
+    pub fn is_empty(&self) -> bool {
+        self.drop.is_empty() && !self.dedupe
+    }
+}
+
+/// Feature counts and compact JSON sizes before and after `prune`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneStats {
+    pub features_before: usize,
+    pub features_after: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+impl PruneStats {
+    /// Share of the bytes saved, from 0 to 100
+    pub fn saved_percent(&self) -> f64 {
+        if self.bytes_before == 0 {
+            return 0.0;
+        }
+        (1.0 - self.bytes_after as f64 / self.bytes_before as f64) * 100.0
+    }
+}
+
+/// A copy of a FeatureCollection with `pruning` applied, and what it saved.
+/// Other documents are returned unchanged.
+pub fn prune(root: &Value, pruning: &Pruning) -> (Value, PruneStats) {
+    let size = |v: &Value| serde_json::to_vec(v).map(|b| b.len()).unwrap_or(0);
+    let mut pruned = root.clone();
+    let mut stats = PruneStats { bytes_before: size(root), ..Default::default() };
+    if let Some(features) = pruned.get_mut("features").and_then(|f| f.as_array_mut()) {
+        stats.features_before = features.len();
+        if pruning.dedupe {
+            let mut seen = std::collections::HashSet::new();
+            features.retain(|f| match f.get("geometry") {
+                Some(g) if !g.is_null() => seen.insert(g.to_string()),
+                _ => true,
+            });
+        }
+        for feature in features.iter_mut() {
+            if let Some(props) = feature.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                props.retain(|k, _| !pruning.drop.contains(k));
+            }
+        }
+        stats.features_after = features.len();
+    }
+    stats.bytes_after = size(&pruned);
+    (pruned, stats)
+}
+
+/// Reads the records appended to a JSON Lines file since the last call, so a
+/// log being written by another process can be followed like `tail -f`
+#[derive(Debug, Clone, Default)]
+pub struct JsonLinesTail {
+    /// Bytes of the file already consumed
+    offset: u64,
+    /// A last line still being written, kept until its newline arrives
+    partial: Vec<u8>,
+    /// Non-empty lines that weren't valid JSON
+    pub skipped: usize,
+}
+
+impl JsonLinesTail {
+    /// Parses every complete line added since the last read. Starts over from
+    /// the beginning when the file got shorter (truncated or rotated).
+    pub fn read(&mut self, path: &Path) -> std::io::Result<Vec<Value>> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            *self = Self::default();
+        }
+        if len == self.offset {
+            return Ok(Vec::new());
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = std::mem::take(&mut self.partial);
+        self.offset += file.read_to_end(&mut buf)? as u64;
+
+        let complete = buf.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        self.partial = buf.split_off(complete);
+        // A last object without a newline can't grow into something else,
+        // so it is taken as is (files often don't end in a newline)
+        let finished = matches!(
+            serde_json::from_slice::<Value>(&self.partial),
+            Ok(Value::Object(_) | Value::Array(_))
+        );
+        if finished {
+            buf.append(&mut self.partial);
+        }
+        let mut records = Vec::new();
+        for line in buf.split(|&b| b == b'\n') {
+            records.extend(self.parse_line(line));
+        }
+        Ok(records)
+    }
+
+    /// The record on one line, if it isn't blank; invalid lines are counted in `skipped`
+    fn parse_line(&mut self, line: &[u8]) -> Option<Value> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        // GeoJSONSeq (RFC 8142) starts each record with a record separator
+        let line = line.strip_prefix(b"\x1e").unwrap_or(line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+        match serde_json::from_slice(line) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.skipped += 1;
+                None
+            }
+        }
+    }
+
+    /// Starts at the first whole line within the last `bytes` of the file, so
+    /// only the end of a file too large to read whole is loaded
+    pub fn near_end(path: &Path, bytes: u64) -> std::io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len <= bytes {
+            return Ok(Self::default());
+        }
+        file.seek(SeekFrom::Start(len - bytes))?;
+        // The line the seek landed in is cut; skip to the next one
+        let cut = BufReader::new(file).read_until(b'\n', &mut Vec::new())?;
+        Ok(Self { offset: len - bytes + cut as u64, ..Default::default() })
+    }
+}
+
+/// Builds a JSON value keeping only the first `limit` items of each array;
+/// the rest are skipped as they are parsed instead of being built
+#[derive(Clone, Copy)]
+struct Truncated {
+    limit: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for Truncated {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Truncated {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> std::result::Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> std::result::Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Value, A::Error> {
+        let mut items = Vec::new();
+        while items.len() < self.limit {
+            match seq.next_element_seed(self)? {
+                Some(item) => items.push(item),
+                None => return Ok(Value::Array(items)),
+            }
+        }
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Value, A::Error> {
+        let mut object = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            object.insert(key, map.next_value_seed(self)?);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+pub fn is_json_lines(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("jsonl") | Some("ndjson"))
+}
+
+/// GeoJSONSeq: one feature per line (`.geojsonl` / `.geojsons`), read as a
+/// FeatureCollection
+pub fn is_geojson_seq(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("geojsonl") | Some("geojsons"))
+}
+
+/// A GeoJSONSeq record as a feature; bare geometries get empty properties
+pub fn as_feature(value: Value) -> Value {
+    match value.get("type").and_then(|t| t.as_str()) {
+        Some("Feature") | None => value,
+        Some(_) => serde_json::json!({ "type": "Feature", "geometry": value, "properties": {} }),
+    }
+}
+
+/// The properties of each feature as records for `append_records`
+pub fn feature_properties(features: &[Value]) -> Vec<Value> {
+    features
+        .iter()
+        .map(|f| match f.get("properties") {
+            Some(Value::Object(props)) => Value::Object(props.clone()),
+            _ => Value::Object(Default::default()),
+        })
+        .collect()
+}
+
+/// Columns for a list of JSON Lines records: the keys of object records in
+/// first-seen order, or a single `value` column for other values
+pub fn records_table(records: &[Value]) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    append_records(&mut headers, &mut rows, records);
+    (headers, rows)
+}
+
+/// Adds `records` to a table built by `records_table`; keys not seen before
+/// become new columns, filled with `null` on the earlier rows
+pub fn append_records(headers: &mut Vec<String>, rows: &mut Vec<Vec<String>>, records: &[Value]) {
+    for record in records {
+        match record.as_object() {
+            Some(object) => {
+                for key in object.keys() {
+                    if !headers.contains(key) {
+                        headers.push(key.clone());
+                    }
+                }
+            }
+            None => {
+                if !headers.iter().any(|h| h == "value") {
+                    headers.push("value".to_string());
+                }
+            }
+        }
+    }
+    for row in rows.iter_mut() {
+        row.resize(headers.len(), "null".to_string());
+    }
+    rows.extend(records.iter().map(|record| {
+        headers
+            .iter()
+            .map(|h| match record.as_object() {
+                Some(object) => object.get(h).map(value_to_display),
+                None => (h == "value").then(|| value_to_display(record)),
+            })
+            .map(|v| v.unwrap_or_else(|| "null".to_string()))
+            .collect()
+    }));
+}
+
+/// Feature count, geometry types and bounding box of a FeatureCollection
+pub fn geojson_summary(root: &Value) -> (usize, Vec<String>, Option<(f64, f64, f64, f64)>) {
+    let features = match root.get("features").and_then(|f| f.as_array()) {
+        Some(f) => f,
+        None => return (0, vec![], None),
+    };
+    let count = features.len();
+    let mut geom_types: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+    let mut min_lon = f64::MAX;
+    let mut min_lat = f64::MAX;
+    let mut max_lon = f64::MIN;
+    let mut max_lat = f64::MIN;
+    let mut has_coords = false;
+    for feature in features {
+        if let Some(geom) = feature.get("geometry") {
+            if let Some(t) = geom.get("type").and_then(|t| t.as_str()) {
+                geom_types.insert(t.to_string());
+            }
+            collect_bbox(geom, &mut min_lon, &mut min_lat, &mut max_lon, &mut max_lat, &mut has_coords);
+        }
+    }
+    let bbox = if has_coords { Some((min_lon, min_lat, max_lon, max_lat)) } else { None };
+    (count, geom_types.into_iter().collect(), bbox)
+}
+
 fn detect_kind(path: &Path, root: &Value) -> FileKind {
-    if path.extension().and_then(|e| e.to_str()) == Some("geojson") {
+    if path.extension().and_then(|e| e.to_str()) == Some("geojson") || is_geojson_value(root) {
         return FileKind::GeoJson;
     }
-    if let Some(t) = root.get("type").and_then(|t| t.as_str()) {
-        if t == "FeatureCollection" || t == "Feature" {
-            return FileKind::GeoJson;
+    FileKind::Json
+}
+
+fn is_geojson_value(root: &Value) -> bool {
+    matches!(root.get("type").and_then(|t| t.as_str()), Some("FeatureCollection") | Some("Feature"))
+}
+
+/// Saves JSON text, e.g. from the clipboard or an HTTP response, to a temp file
+/// `ftool-<source>-<time>` with an extension for what it holds (`.json`,
+/// `.geojson` or `.jsonl` for one value per line), so it opens like any other
+/// file. Fails if the text isn't JSON. The file is readable by the user only,
+/// since responses and tokens may be secret, and `remove_pasted` deletes it.
+pub fn save_pasted(text: &str, source: &str) -> Result<PathBuf> {
+    let text = text.trim();
+    let extension = match serde_json::from_str::<Value>(text) {
+        Ok(root) if is_geojson_value(&root) => "geojson",
+        Ok(_) => "json",
+        Err(e) => {
+            let mut lines = text.lines().filter(|l| !l.trim().is_empty()).peekable();
+            if lines.peek().is_none() || lines.any(|l| serde_json::from_str::<Value>(l).is_err()) {
+                anyhow::bail!("Not JSON or JSON Lines: {}", e);
+            }
+            "jsonl"
+        }
+    };
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default();
+    let path = std::env::temp_dir().join(format!("ftool-{}-{}.{}", source, stamp, extension));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(text.as_bytes())?;
+    PASTED.lock().unwrap_or_else(|e| e.into_inner()).push(path.clone());
+    Ok(path)
+}
+
+/// Deletes the temp files `save_pasted` wrote, once the TUI showing them closes
+pub fn remove_pasted() {
+    let paths = std::mem::take(&mut *PASTED.lock().unwrap_or_else(|e| e.into_inner()));
+    for path in paths {
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::debug!(file = %path.display(), error = %e, "could not delete pasted JSON");
         }
     }
-    FileKind::Json
 }
 
 fn collect_bbox(geom: &Value, min_lon: &mut f64, min_lat: &mut f64, max_lon: &mut f64, max_lat: &mut f64, has_coords: &mut bool) {
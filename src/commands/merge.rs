@@ -0,0 +1,130 @@
+use std::path::Path;
+use std::time::Instant;
+
+use super::duckdb_inspector::{DuckDbError, DuckDbInspector, finish_write, open_connection, prepare_write};
+
+/// A column whose type differs between input files
+#[derive(Debug)]
+pub struct TypeConflict {
+    pub column: String,
+    /// (file, type) for every input that has the column
+    pub types: Vec<(String, String)>,
+}
+
+/// Union schema of the inputs, computed before anything is written
+#[derive(Debug)]
+pub struct MergePlan {
+    pub inputs: Vec<String>,
+    /// Output columns in order of first appearance across the inputs
+    pub columns: Vec<String>,
+    pub conflicts: Vec<TypeConflict>,
+}
+
+impl MergePlan {
+    /// Reads every input's schema and works out the union of their columns
+    pub fn new(inputs: &[String]) -> Result<Self, DuckDbError> {
+        let mut columns: Vec<String> = Vec::new();
+        let mut types: Vec<Vec<(String, String)>> = Vec::new();
+
+        for input in inputs {
+            let schema = DuckDbInspector::new(input.clone())?.schema()?;
+            for (name, ty) in schema {
+                let idx = match columns.iter().position(|c| *c == name) {
+                    Some(idx) => idx,
+                    None => {
+                        columns.push(name);
+                        types.push(Vec::new());
+                        columns.len() - 1
+                    }
+                };
+                types[idx].push((input.clone(), ty));
+            }
+        }
+
+        let conflicts = columns
+            .iter()
+            .zip(types)
+            .filter(|(_, types)| types.iter().any(|(_, ty)| *ty != types[0].1))
+            .map(|(column, types)| TypeConflict {
+                column: column.clone(),
+                types,
+            })
+            .collect();
+
+        Ok(Self {
+            inputs: inputs.to_vec(),
+            columns,
+            conflicts,
+        })
+    }
+
+    /// Human-readable list of type conflicts, one line per file and type
+    pub fn conflict_report(&self) -> String {
+        let mut out = String::new();
+        for conflict in &self.conflicts {
+            out.push_str(&format!("Column '{}' has different types:\n", conflict.column));
+            for (file, ty) in &conflict.types {
+                out.push_str(&format!("  {:<12} {}\n", ty, file));
+            }
+        }
+        out
+    }
+
+    /// Writes all inputs into `output` (CSV or Parquet by extension). Columns missing
+    /// from an input are NULL; conflicting types are widened by DuckDB where possible.
+    /// Returns the number of rows written.
+    pub fn write(&self, output: &str) -> Result<usize, DuckDbError> {
+        let format_str = match Path::new(output).extension().and_then(|e| e.to_str()) {
+            Some("csv") => "CSV",
+            Some("parquet") => "PARQUET",
+            _ => {
+                return Err(DuckDbError::InvalidFileFormat(format!(
+                    "Output must be a .csv or .parquet file: {}",
+                    output
+                )));
+            }
+        };
+
+        let sources: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|input| format!("SELECT * FROM {}('{}')", read_function(input), input.replace('\'', "''")))
+            .collect();
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| format!("\"{}\"", c.replace('"', "\"\"")))
+            .collect();
+
+        let (pending, temp) = prepare_write(output);
+        let query = format!(
+            "COPY (SELECT {} FROM ({})) TO '{}' (FORMAT {})",
+            columns.join(", "),
+            sources.join(" UNION ALL BY NAME "),
+            temp,
+            format_str
+        );
+
+        let connection = open_connection()?;
+        let started = Instant::now();
+        let rows = connection
+            .execute(&query, [])
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to merge files: {}", e)))?;
+        finish_write(pending)?;
+        tracing::debug!(
+            kind = "merge",
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            query,
+            "duckdb query"
+        );
+
+        Ok(rows)
+    }
+}
+
+pub fn read_function(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("csv") => "read_csv_auto",
+        _ => "read_parquet",
+    }
+}
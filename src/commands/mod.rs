@@ -1,7 +1,41 @@
 pub mod file;
+pub mod dir_scan;
 pub mod duckdb_inspector;
 
 pub use file::File;
 pub use duckdb_inspector::DuckDbInspector;
 pub mod json_inspector;
 pub use json_inspector::JsonInspector;
+pub mod docs;
+pub mod table_format;
+pub mod report;
+pub mod transform;
+pub mod split;
+pub mod merge;
+pub mod pipeline;
+pub mod convert_dir;
+pub mod generate;
+pub mod schema_diff;
+pub mod row_diff;
+pub mod profile;
+pub mod csv_lint;
+pub mod geo;
+pub mod tile;
+pub mod pmtiles;
+pub mod serve;
+pub mod postgres;
+pub mod env;
+pub mod decode;
+pub mod time;
+pub mod random;
+pub mod units;
+pub mod count;
+pub mod recode;
+pub mod find_column;
+pub mod catalog;
+pub mod notes;
+pub mod backup;
+pub mod deletion;
+pub mod hooks;
+pub mod geo_xml;
+pub mod geometry;
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+
+use super::backup::write_atomic;
+use super::table_format::to_markdown;
+use crate::config;
+
+const NOTES_FILE: &str = "notes.json";
+
+/// Free-text notes about a data file and its columns
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileNotes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Notes by column name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub columns: BTreeMap<String, String>,
+}
+
+impl FileNotes {
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none() && self.columns.is_empty()
+    }
+
+    pub fn column(&self, name: &str) -> Option<&str> {
+        self.columns.get(name).map(String::as_str)
+    }
+
+    /// The file note, then a table of the column notes
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return "No notes\n".to_string();
+        }
+        let mut out = String::new();
+        if let Some(note) = &self.file {
+            out.push_str(&format!("{}\n", note));
+        }
+        if !self.columns.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            let headers = vec!["Column".to_string(), "Note".to_string()];
+            let rows: Vec<Vec<String>> =
+                self.columns.iter().map(|(column, note)| vec![column.clone(), note.clone()]).collect();
+            out.push_str(&to_markdown(&headers, &rows));
+        }
+        out
+    }
+}
+
+/// Notes of every annotated file, keyed by absolute path (or table URL)
+type AllNotes = BTreeMap<String, FileNotes>;
+
+fn notes_path() -> Option<PathBuf> {
+    config::data_dir().map(|d| d.join(NOTES_FILE))
+}
+
+/// Notes follow the file whichever directory it's opened from
+fn key(path: &Path) -> String {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()).to_string_lossy().to_string()
+}
+
+/// Every saved note; a file that can't be read or parsed is an error rather
+/// than no notes, so `set` doesn't write over it
+fn read_all(path: &Path) -> anyhow::Result<AllNotes> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AllNotes::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    serde_json::from_str(&content)
+        .with_context(|| format!("{} is corrupt; fix or move it away to save notes again", path.display()))
+}
+
+fn load_all() -> AllNotes {
+    let Some(path) = notes_path() else { return AllNotes::new() };
+    read_all(&path).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "ignoring saved notes");
+        AllNotes::new()
+    })
+}
+
+/// The notes saved for `path`, empty if it has none
+pub fn for_file(path: &Path) -> FileNotes {
+    load_all().remove(&key(path)).unwrap_or_default()
+}
+
+/// Sets the note of `path` (or of its `column`); `None` removes it
+pub fn set(path: &Path, column: Option<&str>, text: Option<&str>) -> anyhow::Result<()> {
+    let notes_file = notes_path().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    let mut all = read_all(&notes_file)?;
+    let key = key(path);
+    let notes = all.entry(key.clone()).or_default();
+    let text = text.map(str::trim).filter(|t| !t.is_empty()).map(str::to_string);
+    match (column, text) {
+        (Some(column), Some(text)) => {
+            notes.columns.insert(column.to_string(), text);
+        }
+        (Some(column), None) => {
+            notes.columns.remove(column);
+        }
+        (None, text) => notes.file = text,
+    }
+    if notes.is_empty() {
+        all.remove(&key);
+    }
+    if let Some(dir) = notes_file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_atomic(&notes_file, serde_json::to_string_pretty(&all)?)?;
+    Ok(())
+}
+
+/// The command notes are edited with: `editor` from the config, then
+/// `$VISUAL` and `$EDITOR`, and `vi` (`notepad` on Windows) without them
+fn editor() -> String {
+    config::get()
+        .editor
+        .clone()
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Opens the note of `path` (or of its `column`) in the editor and saves the
+/// text it's left with; emptying it removes the note. The editor command goes
+/// through `sh -c` (`cmd /C` on Windows), so it may carry arguments (`code --wait`).
+pub fn edit(path: &Path, column: Option<&str>) -> anyhow::Result<()> {
+    let notes = for_file(path);
+    let current = match column {
+        Some(column) => notes.column(column),
+        None => notes.file.as_deref(),
+    };
+    let temp = std::env::temp_dir().join(format!("ftool-note-{}.txt", std::process::id()));
+    std::fs::write(&temp, current.unwrap_or_default())
+        .with_context(|| format!("Failed to create {}", temp.display()))?;
+
+    let editor = editor();
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(format!("{} \"{}\"", editor, temp.display()));
+        process
+    } else {
+        let mut process = Command::new("sh");
+        // The name after the script becomes $0, so the path is $1
+        process.arg("-c").arg(format!("{} \"$1\"", editor)).arg("ftool-editor").arg(&temp);
+        process
+    };
+    let status = process.status();
+    let text = std::fs::read_to_string(&temp);
+    let _ = std::fs::remove_file(&temp);
+    let status = status.with_context(|| format!("Failed to start the editor ({})", editor))?;
+    if !status.success() {
+        bail!("{} exited with {}; the note was left as it was", editor, status);
+    }
+    set(path, column, Some(&text.context("Failed to read the edited note")?))
+}
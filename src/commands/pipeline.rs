@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Context, bail};
+use serde::Deserialize;
+
+use super::duckdb_inspector::{DuckDbInspector, ExportMode};
+use super::tile::{TilePlan, find_preset};
+use super::transform::{ComputedColumn, Mask, Transform};
+use crate::config;
+
+/// A `ftool run` file: steps run in order, stopping at the first one that fails.
+/// Relative paths are taken from the pipeline file's directory, e.g.
+///
+/// ```toml
+/// [[steps]]
+/// run = "assert"
+/// input = "raw/sales.csv"
+/// min_rows = 1
+/// not_null = ["id"]
+///
+/// [[steps]]
+/// run = "convert"
+/// input = "raw/sales.csv"
+/// output = "clean/sales.parquet"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Pipeline {
+    pub steps: Vec<Step>,
+    /// Directory of the pipeline file
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    /// Shown in the log instead of the step's description
+    #[serde(default)]
+    pub name: Option<String>,
+    /// File (or dataset alias) the step reads
+    pub input: String,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "run", rename_all = "lowercase")]
+pub enum Action {
+    /// Fails unless every given condition holds
+    Assert {
+        /// Exact row count
+        rows: Option<usize>,
+        min_rows: Option<usize>,
+        max_rows: Option<usize>,
+        /// Columns that must exist
+        #[serde(default)]
+        columns: Vec<String>,
+        /// Columns that must exist and hold no NULLs
+        #[serde(default)]
+        not_null: Vec<String>,
+        /// Column types that must match, e.g. `{ id = "BIGINT" }`
+        #[serde(default)]
+        types: BTreeMap<String, String>,
+    },
+    /// Writes the input as CSV or Parquet, with the `inspect -c` transforms
+    Convert {
+        /// csv or parquet; taken from `output` when omitted
+        to: Option<String>,
+        /// Defaults to `<stem>.<to>` next to the input
+        output: Option<String>,
+        #[serde(default)]
+        select: Vec<String>,
+        #[serde(default)]
+        rename: BTreeMap<String, String>,
+        #[serde(default)]
+        cast: BTreeMap<String, String>,
+        #[serde(default)]
+        drop: Vec<String>,
+        /// Column to hash, redact or keep-prefix[:N]
+        #[serde(default)]
+        mask: BTreeMap<String, String>,
+        /// `"price * qty AS total"` expressions
+        #[serde(default)]
+        compute: Vec<String>,
+    },
+    /// Appends the input's rows to an existing file, or upserts them on a key
+    Export {
+        output: String,
+        /// Upsert on this column instead of appending
+        key: Option<String>,
+    },
+    /// Builds vector tiles with tippecanoe
+    Tile {
+        /// Defaults to `<stem>.pmtiles` next to the input
+        output: Option<String>,
+        layer: Option<String>,
+        preset: Option<String>,
+        min_zoom: Option<u8>,
+        max_zoom: Option<u8>,
+    },
+}
+
+impl Pipeline {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut pipeline: Self = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid pipeline file {}: {}", path.display(), e))?;
+        if pipeline.steps.is_empty() {
+            bail!("Pipeline file {} has no steps", path.display());
+        }
+        pipeline.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(pipeline)
+    }
+
+    /// Whether any step writes a file, which read-only mode refuses
+    pub fn writes(&self) -> bool {
+        self.steps.iter().any(|s| !matches!(s.action, Action::Assert { .. }))
+    }
+
+    /// `[n/total] description` of every step, without running them
+    pub fn plan(&self) -> Vec<String> {
+        let total = self.steps.len();
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| format!("[{}/{}] {}", i + 1, total, step.label(&self.dir)))
+            .collect()
+    }
+
+    /// Runs the steps in order, passing a line to `log` as each one starts and
+    /// finishes. The first failure stops the run and is returned with its step.
+    pub fn run(&self, log: impl Fn(String)) -> anyhow::Result<()> {
+        let total = self.steps.len();
+        for (i, step) in self.steps.iter().enumerate() {
+            let label = step.label(&self.dir);
+            log(format!("[{}/{}] {}", i + 1, total, label));
+            let started = Instant::now();
+            let done = step
+                .run(&self.dir)
+                .with_context(|| format!("Step {}/{} failed: {}", i + 1, total, label))?;
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            tracing::info!(step = i + 1, label = %label, elapsed_ms, "pipeline step done");
+            log(format!("      {} ({} ms)", done, elapsed_ms));
+        }
+        Ok(())
+    }
+}
+
+impl Step {
+    /// The step's name, or what it does
+    pub fn label(&self, dir: &Path) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let input = resolve(dir, &self.input);
+        match &self.action {
+            Action::Assert { .. } => format!("assert {}", input),
+            Action::Convert { output, to, .. } => match output {
+                Some(output) => format!("convert {} -> {}", input, resolve(dir, output)),
+                None => format!("convert {} to {}", input, to.as_deref().unwrap_or("?")),
+            },
+            Action::Export { output, key: None } => format!("append {} to {}", input, resolve(dir, output)),
+            Action::Export { output, key: Some(key) } => {
+                format!("upsert {} into {} on {}", input, resolve(dir, output), key)
+            }
+            Action::Tile { output, .. } => match output {
+                Some(output) => format!("tile {} -> {}", input, resolve(dir, output)),
+                None => format!("tile {}", input),
+            },
+        }
+    }
+
+    /// Runs the step; returns a short summary of what it did
+    fn run(&self, dir: &Path) -> anyhow::Result<String> {
+        let input = resolve(dir, &self.input);
+        match &self.action {
+            Action::Assert { rows, min_rows, max_rows, columns, not_null, types } => {
+                let inspector = DuckDbInspector::new(input)?;
+                let schema = inspector.schema()?;
+                let mut failures = Vec::new();
+                let required: BTreeSet<&String> = columns.iter().chain(not_null).chain(types.keys()).collect();
+                for column in required {
+                    if !schema.iter().any(|(name, _)| name == column) {
+                        failures.push(format!("column {} is missing", column));
+                    }
+                }
+                for (column, expected) in types {
+                    if let Some((_, actual)) = schema.iter().find(|(name, _)| name == column)
+                        && !actual.eq_ignore_ascii_case(expected)
+                    {
+                        failures.push(format!("{} is {}, expected {}", column, actual, expected));
+                    }
+                }
+                for column in not_null {
+                    if schema.iter().any(|(name, _)| name == column) {
+                        let nulls = inspector.null_count(column)?;
+                        if nulls > 0 {
+                            failures.push(format!("{} has {} NULL(s)", column, nulls));
+                        }
+                    }
+                }
+                let count = inspector.row_count()?;
+                if let Some(n) = *rows
+                    && count != n
+                {
+                    failures.push(format!("{} rows, expected {}", count, n));
+                }
+                if let Some(n) = *min_rows
+                    && count < n
+                {
+                    failures.push(format!("{} rows, expected at least {}", count, n));
+                }
+                if let Some(n) = *max_rows
+                    && count > n
+                {
+                    failures.push(format!("{} rows, expected at most {}", count, n));
+                }
+                if !failures.is_empty() {
+                    bail!("{}", failures.join("; "));
+                }
+                Ok(format!("ok, {} rows", count))
+            }
+            Action::Convert { to, output, select, rename, cast, drop, mask, compute } => {
+                let output = output.as_deref().map(|o| resolve(dir, o));
+                let extension =
+                    output.as_deref().and_then(|o| Path::new(o).extension()).map(|e| e.to_string_lossy().to_string());
+                if let (Some(to), Some(extension)) = (to, &extension)
+                    && !to.eq_ignore_ascii_case(extension)
+                {
+                    bail!("`to = \"{}\"` conflicts with the .{} output", to, extension);
+                }
+                let format = to
+                    .clone()
+                    .or(extension)
+                    .ok_or_else(|| anyhow::anyhow!("convert needs `to` or an `output` with a .csv or .parquet extension"))?;
+                let transform = Transform {
+                    select: select.clone(),
+                    rename: rename.clone().into_iter().collect(),
+                    cast: cast.clone().into_iter().collect(),
+                    drop: drop.clone(),
+                    mask: mask
+                        .iter()
+                        .map(|(column, m)| Mask::parse(m).map(|m| (column.clone(), m)))
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| anyhow::anyhow!(e))?,
+                    computed: compute
+                        .iter()
+                        .map(|c| ComputedColumn::parse(c))
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| anyhow::anyhow!(e))?,
+                };
+                let inspector = DuckDbInspector::new(input.clone())?;
+                let Some(mut plan) = inspector.convert_query(&format, &transform, &[])? else {
+                    bail!("{} already is a {} file and nothing is transformed", input, format);
+                };
+                if let Some(output) = output {
+                    plan.target = output;
+                }
+                create_parent(&plan.target)?;
+                let written = inspector.run_copy(&plan)?;
+                Ok(format!("wrote {}", written))
+            }
+            Action::Export { output, key } => {
+                let output = resolve(dir, output);
+                let mode = match key {
+                    Some(key) => ExportMode::Upsert { key: key.clone() },
+                    None => ExportMode::Append,
+                };
+                create_parent(&output)?;
+                let inspector = DuckDbInspector::new(input)?;
+                let summary = inspector.export_into(&output, &Transform::default(), &mode, false)?;
+                for conflict in &summary.type_conflicts {
+                    tracing::warn!(output = %output, conflict = %conflict, "export type conflict");
+                }
+                Ok(format!("{} row(s) added, {} replaced in {}", summary.inserted, summary.replaced, output))
+            }
+            Action::Tile { output, layer, preset, min_zoom, max_zoom } => {
+                let mut plan = TilePlan::new(Path::new(&input))?;
+                if let Some(output) = output {
+                    plan.output = PathBuf::from(resolve(dir, output));
+                }
+                plan.layer = layer.clone();
+                if let Some(name) = preset {
+                    plan.apply(&find_preset(name)?);
+                }
+                plan.min_zoom = min_zoom.or(plan.min_zoom);
+                plan.max_zoom = max_zoom.or(plan.max_zoom);
+                create_parent(&plan.output.to_string_lossy())?;
+                tracing::debug!(command = %plan.command_line(), "pipeline tiling");
+                plan.run_captured()?;
+                Ok(format!("wrote {}", plan.output.display()))
+            }
+        }
+    }
+}
+
+/// Creates the directories a step's output goes in; URLs are left to the writer
+fn create_parent(output: &str) -> anyhow::Result<()> {
+    if output.contains("://") {
+        return Ok(());
+    }
+    if let Some(parent) = Path::new(output).parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// A dataset alias's target, or `path` taken relative to the pipeline's
+/// directory. URLs and absolute paths are kept as written.
+fn resolve(dir: &Path, path: &str) -> String {
+    let resolved = config::get().resolve(path);
+    if resolved != path || resolved.contains("://") || Path::new(&resolved).is_absolute() {
+        return resolved;
+    }
+    dir.join(&resolved).to_string_lossy().to_string()
+}
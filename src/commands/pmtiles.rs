@@ -0,0 +1,430 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+use flate2::read::GzDecoder;
+use serde_json::Value;
+
+const HEADER_LEN: usize = 127;
+/// Tiles at the top zoom decoded to count the features that made it into the
+/// tileset; bigger tilesets are only checked against tippecanoe's metadata
+pub const MAX_COUNTED_TILES: usize = 50_000;
+/// Dropped features above this percentage are flagged as a lossy run
+pub const LOSSY_WARNING: f64 = 1.0;
+
+/// Compression of directories, metadata or tiles, from the header
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Unknown,
+    None,
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+impl Compression {
+    fn from_byte(b: u8) -> Self {
+        match b {
+            1 => Compression::None,
+            2 => Compression::Gzip,
+            3 => Compression::Brotli,
+            4 => Compression::Zstd,
+            _ => Compression::Unknown,
+        }
+    }
+
+    fn decompress(self, data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Compression::None | Compression::Unknown => Ok(data),
+            Compression::Gzip => {
+                let mut out = Vec::new();
+                GzDecoder::new(data.as_slice()).read_to_end(&mut out).context("Invalid gzip data")?;
+                Ok(out)
+            }
+            other => bail!("{:?} compression isn't supported", other),
+        }
+    }
+}
+
+/// The parts of a PMTiles v3 header needed to find metadata and tiles
+#[derive(Debug, Clone)]
+struct Header {
+    root_offset: u64,
+    root_length: u64,
+    metadata_offset: u64,
+    metadata_length: u64,
+    leaf_offset: u64,
+    tile_data_offset: u64,
+    internal_compression: Compression,
+    tile_compression: Compression,
+    min_zoom: u8,
+    max_zoom: u8,
+}
+
+/// A directory entry: `run_length` tiles from `tile_id` sharing the same
+/// data, or a leaf directory when `run_length` is 0
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    tile_id: u64,
+    offset: u64,
+    length: u64,
+    run_length: u64,
+}
+
+/// A PMTiles archive, read lazily from disk
+pub struct PmTiles {
+    file: File,
+    header: Header,
+}
+
+impl PmTiles {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut buf = [0u8; HEADER_LEN];
+        file.read_exact(&mut buf).context("Not a PMTiles file (too short)")?;
+        if &buf[0..7] != b"PMTiles" {
+            bail!("Not a PMTiles file: {}", path.display());
+        }
+        if buf[7] != 3 {
+            bail!("Unsupported PMTiles version {}", buf[7]);
+        }
+        let u64_at = |i: usize| u64::from_le_bytes(buf[i..i + 8].try_into().unwrap_or_default());
+        let header = Header {
+            root_offset: u64_at(8),
+            root_length: u64_at(16),
+            metadata_offset: u64_at(24),
+            metadata_length: u64_at(32),
+            leaf_offset: u64_at(40),
+            tile_data_offset: u64_at(56),
+            internal_compression: Compression::from_byte(buf[97]),
+            tile_compression: Compression::from_byte(buf[98]),
+            min_zoom: buf[100],
+            max_zoom: buf[101],
+        };
+        Ok(Self { file, header })
+    }
+
+    pub fn zooms(&self) -> (u8, u8) {
+        (self.header.min_zoom, self.header.max_zoom)
+    }
+
+    /// The JSON metadata; tippecanoe puts `vector_layers` and `tilestats` there
+    pub fn metadata(&mut self) -> anyhow::Result<Value> {
+        let data = self.read_at(self.header.metadata_offset, self.header.metadata_length)?;
+        let data = self.header.internal_compression.decompress(data)?;
+        if data.is_empty() {
+            return Ok(Value::Object(Default::default()));
+        }
+        serde_json::from_slice(&data).context("Invalid PMTiles metadata")
+    }
+
+    /// The (offset, length) of each distinct tile at `zoom`, following leaf
+    /// directories; runs of identical tiles are listed once
+    pub fn tiles_at(&mut self, zoom: u8) -> anyhow::Result<Vec<(u64, u64)>> {
+        if zoom > 30 {
+            bail!("Invalid zoom level {}", zoom);
+        }
+        let range = zoom_first_id(zoom)..zoom_first_id(zoom + 1);
+        let mut tiles = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![(self.header.root_offset, self.header.root_length)];
+        while let Some((offset, length)) = pending.pop() {
+            let data = self.read_at(offset, length)?;
+            let entries = parse_directory(&self.header.internal_compression.decompress(data)?)?;
+            for (i, entry) in entries.iter().enumerate() {
+                if entry.run_length == 0 {
+                    // A leaf holds tile ids up to the next entry's
+                    let end = entries.get(i + 1).map_or(u64::MAX, |e| e.tile_id);
+                    if entry.tile_id < range.end && end > range.start {
+                        pending.push((self.header.leaf_offset.saturating_add(entry.offset), entry.length));
+                    }
+                } else if entry.tile_id < range.end
+                    && entry.tile_id.saturating_add(entry.run_length) > range.start
+                    && seen.insert(entry.offset)
+                {
+                    tiles.push((entry.offset, entry.length));
+                }
+            }
+        }
+        Ok(tiles)
+    }
+
+    /// A tile's decompressed bytes, from `tiles_at`
+    pub fn tile(&mut self, offset: u64, length: u64) -> anyhow::Result<Vec<u8>> {
+        let data = self.read_at(self.header.tile_data_offset.saturating_add(offset), length)?;
+        self.header.tile_compression.decompress(data)
+    }
+
+    fn read_at(&mut self, offset: u64, length: u64) -> anyhow::Result<Vec<u8>> {
+        let mut data = vec![0u8; usize::try_from(length)?];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut data).context("PMTiles file is truncated")?;
+        Ok(data)
+    }
+}
+
+/// Tile ids count tiles zoom by zoom, so zoom z starts after the 4^0 … 4^(z-1) before it
+fn zoom_first_id(zoom: u8) -> u64 {
+    ((1u64 << (2 * u32::from(zoom))) - 1) / 3
+}
+
+fn parse_directory(data: &[u8]) -> anyhow::Result<Vec<Entry>> {
+    let mut reader = Protobuf::new(data);
+    let count = usize::try_from(reader.varint()?)?;
+    // Every entry takes at least four bytes, which bounds a corrupt count
+    if count > data.len() {
+        bail!("Invalid PMTiles directory");
+    }
+    let mut entries = vec![Entry::default(); count];
+    let mut tile_id = 0;
+    for entry in entries.iter_mut() {
+        tile_id = reader.varint()?.checked_add(tile_id).context("Invalid PMTiles directory")?;
+        entry.tile_id = tile_id;
+    }
+    for entry in entries.iter_mut() {
+        entry.run_length = reader.varint()?;
+    }
+    for entry in entries.iter_mut() {
+        entry.length = reader.varint()?;
+    }
+    let mut previous_end = 0;
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let value = reader.varint()?;
+        // 0 means right after the previous entry's data
+        entry.offset = if value == 0 && i > 0 { previous_end } else { value.saturating_sub(1) };
+        previous_end = entry.offset.checked_add(entry.length).context("Invalid PMTiles directory")?;
+    }
+    Ok(entries)
+}
+
+/// Minimal protobuf reading, enough for PMTiles directories and vector tile ids
+struct Protobuf<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Protobuf<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn varint(&mut self) -> anyhow::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let Some(&byte) = self.data.get(self.pos) else { bail!("Truncated varint") };
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("Invalid varint")
+    }
+
+    /// The next field number and wire type
+    fn key(&mut self) -> anyhow::Result<(u64, u8)> {
+        let key = self.varint()?;
+        Ok((key >> 3, (key & 7) as u8))
+    }
+
+    fn bytes(&mut self) -> anyhow::Result<&'a [u8]> {
+        let len = usize::try_from(self.varint()?)?;
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+        let Some(end) = end else { bail!("Truncated field") };
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, wire_type: u8) -> anyhow::Result<()> {
+        match wire_type {
+            0 => {
+                self.varint()?;
+            }
+            1 => self.pos += 8,
+            2 => {
+                self.bytes()?;
+            }
+            5 => self.pos += 4,
+            _ => bail!("Unsupported wire type {}", wire_type),
+        }
+        Ok(())
+    }
+}
+
+/// Features found in one layer of the decoded tiles
+#[derive(Debug, Default)]
+struct LayerFeatures {
+    ids: HashSet<u64>,
+    /// Features without an id can't be told apart across tiles
+    anonymous: u64,
+}
+
+impl LayerFeatures {
+    fn count(&self) -> u64 {
+        self.ids.len() as u64 + self.anonymous
+    }
+}
+
+/// Adds the features of a vector tile (layers = 3, name = 1, features = 2,
+/// id = 1) to `layers`
+fn count_tile_features(tile: &[u8], layers: &mut BTreeMap<String, LayerFeatures>) -> anyhow::Result<()> {
+    let mut reader = Protobuf::new(tile);
+    while !reader.done() {
+        match reader.key()? {
+            (3, 2) => {
+                let mut layer = Protobuf::new(reader.bytes()?);
+                let mut name = String::new();
+                let mut ids = Vec::new();
+                let mut anonymous = 0;
+                while !layer.done() {
+                    match layer.key()? {
+                        (1, 2) => name = String::from_utf8_lossy(layer.bytes()?).to_string(),
+                        (2, 2) => {
+                            let mut feature = Protobuf::new(layer.bytes()?);
+                            let mut id = None;
+                            while !feature.done() {
+                                match feature.key()? {
+                                    (1, 0) => id = Some(feature.varint()?),
+                                    (_, wire_type) => feature.skip(wire_type)?,
+                                }
+                            }
+                            match id {
+                                Some(id) => ids.push(id),
+                                None => anonymous += 1,
+                            }
+                        }
+                        (_, wire_type) => layer.skip(wire_type)?,
+                    }
+                }
+                let counts = layers.entry(name).or_default();
+                counts.ids.extend(ids);
+                counts.anonymous += anonymous;
+            }
+            (_, wire_type) => reader.skip(wire_type)?,
+        }
+    }
+    Ok(())
+}
+
+/// One layer of a tileset compared against the input
+#[derive(Debug, Clone)]
+pub struct LayerCheck {
+    pub layer: String,
+    /// Features in the input for this layer
+    pub input: u64,
+    /// Features tippecanoe read, from the metadata's `tilestats`
+    pub read: Option<u64>,
+    /// Distinct features in the tiles at the top zoom
+    pub kept: Option<u64>,
+}
+
+impl LayerCheck {
+    /// Percentage of the input missing from the top zoom (or, when the tiles
+    /// weren't counted, from what tippecanoe read)
+    pub fn dropped(&self) -> Option<f64> {
+        let kept = self.kept.or(self.read)?;
+        (self.input > 0).then(|| 100.0 * self.input.saturating_sub(kept) as f64 / self.input as f64)
+    }
+
+    pub fn is_lossy(&self) -> bool {
+        self.dropped().is_some_and(|d| d > LOSSY_WARNING)
+    }
+}
+
+/// What a tippecanoe run wrote, compared per layer against the input
+#[derive(Debug, Clone)]
+pub struct TileCheck {
+    pub output: PathBuf,
+    pub bytes: u64,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    pub layers: Vec<LayerCheck>,
+    /// Why some counts are missing
+    pub notes: Vec<String>,
+}
+
+impl TileCheck {
+    pub fn is_lossy(&self) -> bool {
+        self.layers.iter().any(LayerCheck::is_lossy)
+    }
+}
+
+/// Opens the tileset tippecanoe wrote from `input_features` features and
+/// counts, per layer, the features it read and those left at the top zoom.
+///
+/// Features are told apart by id, which `TilePlan` asks tippecanoe to
+/// generate; without ids, features clipped across tiles are counted once per
+/// tile, which hides drops.
+pub fn check(output: &Path, input_features: usize) -> anyhow::Result<TileCheck> {
+    let mut archive = PmTiles::open(output)?;
+    let metadata = archive.metadata()?;
+    let (min_zoom, max_zoom) = archive.zooms();
+    let mut notes = Vec::new();
+
+    // Layers tippecanoe wrote, with how many features it read into each
+    let mut read: BTreeMap<String, Option<u64>> = BTreeMap::new();
+    for layer in metadata.get("vector_layers").and_then(Value::as_array).into_iter().flatten() {
+        if let Some(id) = layer.get("id").and_then(Value::as_str) {
+            read.insert(id.to_string(), None);
+        }
+    }
+    let tilestats = metadata.pointer("/tilestats/layers").and_then(Value::as_array);
+    for layer in tilestats.into_iter().flatten() {
+        if let Some(name) = layer.get("layer").and_then(Value::as_str) {
+            read.insert(name.to_string(), layer.get("count").and_then(Value::as_u64));
+        }
+    }
+
+    let mut kept: BTreeMap<String, LayerFeatures> = BTreeMap::new();
+    let tiles = archive.tiles_at(max_zoom)?;
+    let counted = if tiles.len() > MAX_COUNTED_TILES {
+        notes.push(format!(
+            "{} tiles at zoom {}: too many to count, compared with tippecanoe's own counts",
+            tiles.len(),
+            max_zoom
+        ));
+        false
+    } else {
+        let result = tiles
+            .into_iter()
+            .try_for_each(|(offset, length)| archive.tile(offset, length).and_then(|t| count_tile_features(&t, &mut kept)));
+        if let Err(e) = &result {
+            notes.push(format!("Could not count the tiles' features: {:#}", e));
+        }
+        result.is_ok()
+    };
+    if counted && kept.values().any(|l| l.anonymous > 0) {
+        notes.push("Some features have no id, so clipped copies are counted more than once".to_string());
+    }
+    for name in kept.keys() {
+        read.entry(name.clone()).or_insert(None);
+    }
+
+    // A single layer holds the whole input; with several, tippecanoe's counts
+    // are the only per-layer input figures
+    let single = read.len() == 1;
+    let layers = read
+        .into_iter()
+        .map(|(layer, read)| LayerCheck {
+            input: if single { input_features as u64 } else { read.unwrap_or(0) },
+            kept: counted.then(|| kept.get(&layer).map_or(0, LayerFeatures::count)),
+            read,
+            layer,
+        })
+        .collect();
+    Ok(TileCheck {
+        output: output.to_path_buf(),
+        bytes: std::fs::metadata(output).map_or(0, |m| m.len()),
+        min_zoom,
+        max_zoom,
+        layers,
+        notes,
+    })
+}
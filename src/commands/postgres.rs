@@ -0,0 +1,286 @@
+use clap::ValueEnum;
+
+use super::duckdb_inspector::DuckDbError;
+
+/// What `ftool load` does when the target table may already exist
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum LoadMode {
+    /// Create the table; fails if it exists
+    Create,
+    /// Insert into an existing table, matching columns by name
+    Append,
+    /// Drop the table if it exists and create it again
+    Replace,
+}
+
+/// A Postgres table, optionally schema-qualified (`schema.table`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableName {
+    pub schema: Option<String>,
+    pub table: String,
+}
+
+impl TableName {
+    /// Splits `schema.table` on the first dot; names are taken as written,
+    /// without folding to lower case
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let (schema, table) = match name.split_once('.') {
+            Some((schema, table)) => (Some(schema.to_string()), table.to_string()),
+            None => (None, name.to_string()),
+        };
+        if table.is_empty() || schema.as_deref().is_some_and(str::is_empty) {
+            return Err(format!("Invalid table name: '{}'", name));
+        }
+        Ok(Self { schema, table })
+    }
+
+    /// A table named after a file, dots and all (`sales.2024.csv` loads into
+    /// `"sales.2024"`, not table `2024` of schema `sales`)
+    pub fn from_stem(stem: &str) -> Self {
+        Self { schema: None, table: stem.to_string() }
+    }
+
+    /// Where `LoadMode::Replace` loads the rows before swapping the table out
+    pub fn staging(&self) -> Self {
+        Self { schema: self.schema.clone(), table: format!("{}_ftool_load", self.table) }
+    }
+
+    /// `"schema"."table"` as Postgres expects it
+    pub fn quoted(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", quote(schema), quote(&self.table)),
+            None => quote(&self.table),
+        }
+    }
+
+    /// The table inside a Postgres database attached to DuckDB as `catalog`
+    pub fn in_catalog(&self, catalog: &str) -> String {
+        format!("{}.{}.{}", catalog, quote(self.schema.as_deref().unwrap_or("public")), quote(&self.table))
+    }
+}
+
+impl std::fmt::Display for TableName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.schema {
+            Some(schema) => write!(f, "{}.{}", schema, self.table),
+            None => write!(f, "{}", self.table),
+        }
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// The Postgres type closest to a DuckDB column type. Nested types (STRUCT,
+/// MAP, UNION) become `jsonb`; unsigned integers move up a size.
+pub fn pg_type(duckdb_type: &str) -> String {
+    if let Some(inner) = duckdb_type.strip_suffix("[]") {
+        return format!("{}[]", pg_type(inner));
+    }
+    if duckdb_type.starts_with("DECIMAL") || duckdb_type.starts_with("NUMERIC") {
+        return duckdb_type.replacen("DECIMAL", "numeric", 1).replacen("NUMERIC", "numeric", 1);
+    }
+    if duckdb_type.starts_with("STRUCT") || duckdb_type.starts_with("MAP") || duckdb_type.starts_with("UNION") {
+        return "jsonb".to_string();
+    }
+    match duckdb_type {
+        "BOOLEAN" => "boolean",
+        "TINYINT" | "SMALLINT" | "UTINYINT" => "smallint",
+        "INTEGER" | "USMALLINT" => "integer",
+        "BIGINT" | "UINTEGER" => "bigint",
+        "UBIGINT" | "HUGEINT" | "UHUGEINT" => "numeric",
+        "FLOAT" => "real",
+        "DOUBLE" => "double precision",
+        "DATE" => "date",
+        "TIME" => "time",
+        "TIMESTAMP" | "TIMESTAMP_S" | "TIMESTAMP_MS" | "TIMESTAMP_NS" => "timestamp",
+        "TIMESTAMP WITH TIME ZONE" => "timestamptz",
+        "INTERVAL" => "interval",
+        "BLOB" => "bytea",
+        "UUID" => "uuid",
+        "JSON" => "jsonb",
+        _ => "text",
+    }
+    .to_string()
+}
+
+/// `CREATE TABLE` for `columns` (name and DuckDB type), as `--dry-run` prints it
+pub fn create_table_ddl(table: &TableName, columns: &[(String, String)]) -> String {
+    let columns: Vec<String> = columns
+        .iter()
+        .map(|(name, ty)| format!("    {} {}", quote(name), pg_type(ty)))
+        .collect();
+    format!("CREATE TABLE {} (\n{}\n);", table.quoted(), columns.join(",\n"))
+}
+
+/// The statements `load` runs in Postgres before inserting the rows. Replace
+/// creates a staging table (see `load_target`), so the old table stays until
+/// the rows are in.
+pub fn load_ddl(table: &TableName, columns: &[(String, String)], mode: LoadMode) -> Vec<String> {
+    match mode {
+        LoadMode::Create => vec![create_table_ddl(table, columns)],
+        LoadMode::Append => Vec::new(),
+        LoadMode::Replace => {
+            let staging = table.staging();
+            vec![
+                format!("DROP TABLE IF EXISTS {};", staging.quoted()),
+                create_table_ddl(&staging, columns),
+            ]
+        }
+    }
+}
+
+/// The table `load` inserts the rows into
+pub fn load_target(table: &TableName, mode: LoadMode) -> TableName {
+    match mode {
+        LoadMode::Replace => table.staging(),
+        LoadMode::Create | LoadMode::Append => table.clone(),
+    }
+}
+
+/// The statement `load` runs in Postgres after inserting the rows: for
+/// Replace, one transaction dropping the old table and renaming the staging
+/// table in its place
+pub fn load_finish_ddl(table: &TableName, mode: LoadMode) -> Option<String> {
+    (mode == LoadMode::Replace).then(|| {
+        format!(
+            "BEGIN; DROP TABLE IF EXISTS {}; ALTER TABLE {} RENAME TO {}; COMMIT;",
+            table.quoted(),
+            table.staging().quoted(),
+            quote(&table.table)
+        )
+    })
+}
+
+/// Database whose tables DuckDB can read through a scanner extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatabaseKind {
+    Postgres,
+    MySql,
+}
+
+impl DatabaseKind {
+    /// The DuckDB extension and `ATTACH` type
+    fn extension(self) -> &'static str {
+        match self {
+            DatabaseKind::Postgres => "postgres",
+            DatabaseKind::MySql => "mysql",
+        }
+    }
+}
+
+/// A table named by URL, read in place instead of a file:
+/// `postgres://user@host/db/schema.table` (the schema defaults to `public`)
+/// or `mysql://user@host/db/table`
+#[derive(Debug, Clone)]
+pub struct DatabaseTable {
+    pub kind: DatabaseKind,
+    /// What DuckDB's `ATTACH` gets: a libpq URL for Postgres, key=value
+    /// pairs for MySQL
+    connection: String,
+    pub table: TableName,
+    /// The URL with its password hidden, for titles and logs
+    pub display: String,
+}
+
+impl DatabaseTable {
+    pub fn is_url(text: &str) -> bool {
+        ["postgres://", "postgresql://", "mysql://"].iter().any(|scheme| text.starts_with(scheme))
+    }
+
+    pub fn parse(url: &str) -> Result<Self, DuckDbError> {
+        let display = redact(url);
+        let invalid = |why: &str| DuckDbError::InvalidFileFormat(format!("{}: {}", why, display));
+        let (scheme, rest) = url.split_once("://").ok_or_else(|| invalid("Not a database URL"))?;
+        let kind = match scheme {
+            "postgres" | "postgresql" => DatabaseKind::Postgres,
+            "mysql" => DatabaseKind::MySql,
+            _ => return Err(invalid("Unsupported database")),
+        };
+        let (rest, params) = rest.split_once('?').unwrap_or((rest, ""));
+        let (authority, path) = rest.split_once('/').ok_or_else(|| invalid("No database and table in"))?;
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let (database, mut table) = match segments.as_slice() {
+            [database, schema, table] => {
+                (*database, TableName { schema: Some(schema.to_string()), table: table.to_string() })
+            }
+            [database, table] => (*database, TableName::parse(table).map_err(DuckDbError::InvalidFileFormat)?),
+            _ => return Err(invalid("Expected .../database/schema.table in")),
+        };
+
+        let connection = match kind {
+            DatabaseKind::Postgres => {
+                let mut connection = format!("{}://{}/{}", scheme, authority, database);
+                if !params.is_empty() {
+                    connection.push('?');
+                    connection.push_str(params);
+                }
+                connection
+            }
+            DatabaseKind::MySql => {
+                // MySQL has no schemas within a database: the database is the schema
+                if table.schema.is_some() {
+                    return Err(invalid("MySQL tables are named .../database/table, without a schema"));
+                }
+                table.schema = Some(database.to_string());
+                let (user_info, host_port) = authority.rsplit_once('@').map_or((None, authority), |(u, h)| (Some(u), h));
+                let mut pairs = Vec::new();
+                match host_port.rsplit_once(':') {
+                    Some((host, port)) => pairs.extend([mysql_pair("host", host), mysql_pair("port", port)]),
+                    None if !host_port.is_empty() => pairs.push(mysql_pair("host", host_port)),
+                    None => {}
+                }
+                if let Some(user_info) = user_info {
+                    let (user, password) = user_info.split_once(':').map_or((user_info, None), |(u, p)| (u, Some(p)));
+                    pairs.push(mysql_pair("user", user));
+                    if let Some(password) = password {
+                        pairs.push(mysql_pair("password", password));
+                    }
+                }
+                pairs.push(mysql_pair("database", database));
+                pairs.join(" ")
+            }
+        };
+        Ok(Self { kind, connection, table, display })
+    }
+
+    /// Loads the scanner extension (installed on first use) and attaches the
+    /// database read-only as `remote`
+    pub fn attach_sql(&self) -> String {
+        let extension = self.kind.extension();
+        format!(
+            "INSTALL {0}; LOAD {0}; ATTACH '{1}' AS remote (TYPE {0}, READ_ONLY);",
+            extension,
+            self.connection.replace('\'', "''")
+        )
+    }
+
+    /// The table in the attached database, for `FROM`
+    pub fn source(&self) -> String {
+        self.table.in_catalog("remote")
+    }
+}
+
+/// `key=value` for a MySQL connection string, the value quoted (with `\` and
+/// `'` escaped) when it is empty or holds a space, `=`, `'` or `\`
+fn mysql_pair(key: &str, value: &str) -> String {
+    if value.is_empty() || value.contains([' ', '=', '\'', '\\']) {
+        format!("{}='{}'", key, value.replace('\\', "\\\\").replace('\'', "\\'"))
+    } else {
+        format!("{}={}", key, value)
+    }
+}
+
+/// `url` with the password of `user:password@` replaced by `***`
+pub fn redact(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else { return url.to_string() };
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    match rest[..authority_end].rsplit_once('@') {
+        Some((user_info, _)) if user_info.contains(':') => {
+            let user = user_info.split(':').next().unwrap_or_default();
+            format!("{}://{}:***{}", scheme, user, &rest[user_info.len()..])
+        }
+        _ => url.to_string(),
+    }
+}
@@ -0,0 +1,142 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use super::table_format::to_markdown;
+
+/// Number of most frequent values kept per column
+pub const TOP_VALUES: usize = 5;
+/// Number of equal-width histogram buckets for numeric columns
+pub const HISTOGRAM_BUCKETS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ProfileFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+/// Values in `[lower, upper)`; the last bucket also includes `upper`
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+}
+
+/// Everything known about one column, as returned by `DuckDbInspector::profile`
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub data_type: String,
+    /// Non-NULL values
+    pub count: usize,
+    pub nulls: usize,
+    /// Approximate number of distinct non-NULL values
+    pub distinct: usize,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// Average of the values that can be read as numbers
+    pub mean: Option<f64>,
+    pub top_values: Vec<ValueCount>,
+    /// Empty for non-numeric columns
+    pub histogram: Vec<HistogramBucket>,
+}
+
+/// Profile of a whole file, the shape printed by `ftool inspect --profile --format json`
+#[derive(Debug, Clone, Serialize)]
+pub struct FileProfile {
+    pub file: String,
+    pub row_count: usize,
+    pub columns: Vec<ColumnProfile>,
+}
+
+impl FileProfile {
+    pub fn render(&self, format: ProfileFormat) -> String {
+        match format {
+            ProfileFormat::Json => {
+                // Only plain strings and numbers, so serialization cannot fail
+                serde_json::to_string_pretty(self).unwrap_or_default() + "\n"
+            }
+            ProfileFormat::Text => self.to_text(),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("{}: {} rows, {} columns\n\n", self.file, self.row_count, self.columns.len());
+
+        let headers: Vec<String> = ["Column", "Type", "Count", "Nulls", "Distinct", "Min", "Max", "Avg"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let rows: Vec<Vec<String>> = self
+            .columns
+            .iter()
+            .map(|c| {
+                vec![
+                    c.name.clone(),
+                    c.data_type.clone(),
+                    c.count.to_string(),
+                    c.nulls.to_string(),
+                    c.distinct.to_string(),
+                    c.min.clone().unwrap_or_else(|| "-".to_string()),
+                    c.max.clone().unwrap_or_else(|| "-".to_string()),
+                    format_mean(c.mean),
+                ]
+            })
+            .collect();
+        out.push_str(&to_markdown(&headers, &rows));
+
+        for column in &self.columns {
+            if column.top_values.is_empty() && column.histogram.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{}\n", column.name));
+            if !column.top_values.is_empty() {
+                let top: Vec<String> = column
+                    .top_values
+                    .iter()
+                    .map(|v| format!("{} ({})", v.value, v.count))
+                    .collect();
+                out.push_str(&format!("  top: {}\n", top.join(", ")));
+            }
+            let widest = column.histogram.iter().map(|b| b.count).max().unwrap_or(0);
+            for bucket in &column.histogram {
+                let bar = (bucket.count * 30).checked_div(widest).unwrap_or(0);
+                out.push_str(&format!(
+                    "  {:>12} - {:<12} {:>8} {}\n",
+                    format_bound(bucket.lower),
+                    format_bound(bucket.upper),
+                    bucket.count,
+                    "#".repeat(bar)
+                ));
+            }
+        }
+        out
+    }
+}
+
+pub fn format_mean(mean: Option<f64>) -> String {
+    mean.map(|m| format!("{:.2}", m)).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_bound(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+/// Whether DuckDB can bucket the column's values on a number line
+pub fn is_numeric_type(data_type: &str) -> bool {
+    matches!(
+        data_type,
+        "TINYINT" | "SMALLINT" | "INTEGER" | "BIGINT" | "HUGEINT" | "UTINYINT" | "USMALLINT"
+            | "UINTEGER" | "UBIGINT" | "UHUGEINT" | "FLOAT" | "DOUBLE"
+    ) || data_type.starts_with("DECIMAL")
+}
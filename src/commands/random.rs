@@ -0,0 +1,83 @@
+use chrono::Utc;
+
+/// Characters of a ULID (Crockford's base32: no I, L, O or U)
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const LETTERS_AND_DIGITS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const SYMBOLS: &str = "!#$%&*+-.:;=?@^_~";
+
+/// Length of passwords generated without `--length` (and from the TUI)
+pub const PASSWORD_LENGTH: usize = 20;
+
+/// Values `ftool gen` and the TUI command palette generate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GenKind {
+    Uuid,
+    Ulid,
+    Password,
+}
+
+impl GenKind {
+    pub const ALL: [GenKind; 3] = [GenKind::Uuid, GenKind::Ulid, GenKind::Password];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GenKind::Uuid => "UUID",
+            GenKind::Ulid => "ULID",
+            GenKind::Password => "password",
+        }
+    }
+
+    /// One value, passwords at the default length with symbols
+    pub fn generate(self) -> anyhow::Result<String> {
+        match self {
+            GenKind::Uuid => uuid_v4(),
+            GenKind::Ulid => ulid(),
+            GenKind::Password => password(PASSWORD_LENGTH, true),
+        }
+    }
+}
+
+fn random_bytes<const N: usize>() -> anyhow::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| anyhow::anyhow!("No random data from the OS: {}", e))?;
+    Ok(bytes)
+}
+
+/// Random (version 4) UUID, e.g. `9b2f0c1e-5d4a-4e8b-a3c7-0f6d2e91b845`
+pub fn uuid_v4() -> anyhow::Result<String> {
+    let mut b: [u8; 16] = random_bytes()?;
+    b[6] = (b[6] & 0x0f) | 0x40; // version 4
+    b[8] = (b[8] & 0x3f) | 0x80; // RFC 4122 variant
+    let hex: String = b.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+}
+
+/// ULID: 48 bits of milliseconds since the epoch and 80 random bits in 26
+/// base32 characters, so ULIDs sort by creation time
+pub fn ulid() -> anyhow::Result<String> {
+    let random: [u8; 10] = random_bytes()?;
+    let millis = Utc::now().timestamp_millis().max(0) as u128 & ((1 << 48) - 1);
+    let value = random.iter().fold(millis, |n, b| (n << 8) | *b as u128);
+    // 26 characters hold 130 bits: the first one only uses the top 3
+    Ok((0..26).map(|i| CROCKFORD[((value >> (125 - 5 * i)) & 31) as usize] as char).collect())
+}
+
+/// Password of `length` letters and digits, and symbols unless `symbols` is false
+pub fn password(length: usize, symbols: bool) -> anyhow::Result<String> {
+    let alphabet: Vec<char> =
+        if symbols { LETTERS_AND_DIGITS.chars().chain(SYMBOLS.chars()).collect() } else { LETTERS_AND_DIGITS.chars().collect() };
+    // Bytes past the last multiple of the alphabet size are skipped, so every
+    // character is equally likely
+    let limit = 256 - 256 % alphabet.len();
+    let mut out = String::with_capacity(length);
+    while out.len() < length {
+        let bytes: [u8; 64] = random_bytes()?;
+        for b in bytes.iter().map(|b| *b as usize).filter(|b| *b < limit) {
+            if out.len() == length {
+                break;
+            }
+            out.push(alphabet[b % alphabet.len()]);
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,246 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+use encoding_rs::{Decoder, Encoder, EncoderResult, Encoding, UTF_8, UTF_16BE, UTF_16LE};
+
+use super::backup;
+
+/// Bytes read from the input at a time, so memory use doesn't grow with the file
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Line ending `ftool file --eol` writes
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Eol {
+    /// \n (Unix)
+    Lf,
+    /// \r\n (Windows)
+    Crlf,
+}
+
+impl Eol {
+    fn as_str(self) -> &'static str {
+        match self {
+            Eol::Lf => "\n",
+            Eol::Crlf => "\r\n",
+        }
+    }
+}
+
+/// What a conversion did, for the status line
+#[derive(Debug, Clone)]
+pub struct RecodeSummary {
+    pub from: &'static Encoding,
+    pub to: &'static Encoding,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Line endings rewritten by `--eol`
+    pub line_endings: u64,
+    /// The input had bytes invalid in `from`; they became U+FFFD
+    pub malformed: bool,
+    /// Characters `to` can't represent, written as `?`
+    pub unmappable: u64,
+}
+
+impl RecodeSummary {
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "Converted {} bytes of {} to {} bytes of {}",
+            self.bytes_read,
+            self.from.name(),
+            self.bytes_written,
+            self.to.name()
+        );
+        if self.line_endings > 0 {
+            text.push_str(&format!(", {} line endings normalized", self.line_endings));
+        }
+        if self.malformed {
+            text.push_str(&format!(
+                "\nwarning: the input isn't valid {}; bad bytes were replaced with U+FFFD (pass --from to name its encoding)",
+                self.from.name()
+            ));
+        }
+        if self.unmappable > 0 {
+            text.push_str(&format!(
+                "\nwarning: {} characters have no {} equivalent and were written as '?'",
+                self.unmappable,
+                self.to.name()
+            ));
+        }
+        text
+    }
+}
+
+/// Looks up an encoding by name, accepting the WHATWG labels (`latin1`,
+/// `windows-1252`, `shift_jis`, ...) and `utf16`/`utf16le`/`utf16be`
+pub fn encoding_for(label: &str) -> anyhow::Result<&'static Encoding> {
+    let label = label.trim().to_lowercase();
+    let label = match label.as_str() {
+        "utf16" | "utf16le" | "utf-16" => "utf-16le",
+        "utf16be" => "utf-16be",
+        other => other,
+    };
+    Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| anyhow::anyhow!("Unknown encoding '{}' (try utf8, utf16le, latin1, windows-1252, shift_jis)", label))
+}
+
+/// Streams `input` to `output` (or stdout), decoding it as `from` and encoding
+/// it as `to`. Without `from` the input is read as UTF-8 unless it starts with
+/// a UTF-8 or UTF-16 byte order mark; without `to` it keeps its encoding.
+/// `eol` rewrites every \n, \r\n and lone \r. UTF-16 output gets a byte order mark.
+pub fn recode(
+    input: &Path,
+    output: Option<&Path>,
+    from: Option<&'static Encoding>,
+    to: Option<&'static Encoding>,
+    eol: Option<Eol>,
+) -> anyhow::Result<RecodeSummary> {
+    if let Some(output) = output
+        && output.exists()
+        && std::fs::canonicalize(output)? == std::fs::canonicalize(input)?
+    {
+        anyhow::bail!("The output would overwrite the input; write to a different file");
+    }
+    let mut reader = File::open(input).map_err(|e| anyhow::anyhow!("Failed to open {}: {}", input.display(), e))?;
+    let pending = output.map(backup::prepare);
+    let writer: Box<dyn Write> = match &pending {
+        Some(pending) => {
+            let temp = pending.temp();
+            Box::new(File::create(temp).map_err(|e| anyhow::anyhow!("Failed to create {}: {}", temp.display(), e))?)
+        }
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    // A byte order mark wins over --from (and the UTF-8 default), as in browsers
+    let mut decoder = from.unwrap_or(UTF_8).new_decoder();
+    let mut converter: Option<Converter> = None;
+    let mut summary = RecodeSummary {
+        from: from.unwrap_or(UTF_8),
+        to: to.unwrap_or(UTF_8),
+        bytes_read: 0,
+        bytes_written: 0,
+        line_endings: 0,
+        malformed: false,
+        unmappable: 0,
+    };
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut text = String::new();
+    let mut pending_cr = false;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        let last = read == 0;
+        summary.bytes_read += read as u64;
+
+        text.clear();
+        decode_chunk(&mut decoder, &buffer[..read], &mut text, last, &mut summary.malformed);
+        let converter = converter.get_or_insert_with(|| {
+            // The decoder only knows the real encoding once it has seen the first bytes
+            summary.from = decoder.encoding();
+            summary.to = to.unwrap_or(summary.from);
+            Converter::new(summary.to)
+        });
+        if let Some(eol) = eol {
+            text = normalize_eol(&text, eol, &mut pending_cr, &mut summary.line_endings);
+        }
+        let bytes = converter.encode(&text, last, &mut summary.unmappable);
+        writer.write_all(&bytes)?;
+        summary.bytes_written += bytes.len() as u64;
+        if last {
+            break;
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+    if let Some(pending) = pending {
+        pending.finish()?;
+    }
+    Ok(summary)
+}
+
+fn decode_chunk(decoder: &mut Decoder, mut src: &[u8], out: &mut String, last: bool, malformed: &mut bool) {
+    loop {
+        out.reserve(decoder.max_utf8_buffer_length(src.len()).unwrap_or(src.len() * 3 + 16));
+        let (result, read, had_errors) = decoder.decode_to_string(src, out, last);
+        *malformed |= had_errors;
+        src = &src[read..];
+        if result == encoding_rs::CoderResult::InputEmpty {
+            return;
+        }
+    }
+}
+
+/// Rewrites line endings in one decoded chunk; `pending_cr` carries a \r that
+/// ended the previous chunk, so a \r\n split across chunks counts once
+fn normalize_eol(text: &str, eol: Eol, pending_cr: &mut bool, count: &mut u64) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 16);
+    for c in text.chars() {
+        match c {
+            '\n' if *pending_cr => *pending_cr = false,
+            '\r' | '\n' => {
+                out.push_str(eol.as_str());
+                *count += 1;
+                *pending_cr = c == '\r';
+            }
+            _ => {
+                out.push(c);
+                *pending_cr = false;
+            }
+        }
+    }
+    out
+}
+
+/// UTF-8 text to the output encoding. encoding_rs only encodes UTF-16 as
+/// UTF-8 (as browsers do), so UTF-16 is written here.
+enum Converter {
+    Utf8,
+    Utf16 { big_endian: bool, bom_written: bool },
+    Other(Encoder),
+}
+
+impl Converter {
+    fn new(encoding: &'static Encoding) -> Self {
+        if encoding == UTF_16LE || encoding == UTF_16BE {
+            Converter::Utf16 { big_endian: encoding == UTF_16BE, bom_written: false }
+        } else if encoding == UTF_8 {
+            Converter::Utf8
+        } else {
+            Converter::Other(encoding.new_encoder())
+        }
+    }
+
+    fn encode(&mut self, text: &str, last: bool, unmappable: &mut u64) -> Vec<u8> {
+        match self {
+            Converter::Utf8 => text.as_bytes().to_vec(),
+            Converter::Utf16 { big_endian, bom_written } => {
+                let mut out = Vec::with_capacity(text.len() * 2 + 2);
+                let units = (!*bom_written).then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+                for unit in units {
+                    out.extend(if *big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() });
+                }
+                *bom_written = true;
+                out
+            }
+            Converter::Other(encoder) => {
+                let mut out = Vec::new();
+                let mut rest = text;
+                loop {
+                    let room = encoder.max_buffer_length_from_utf8_without_replacement(rest.len()).unwrap_or(rest.len() * 4);
+                    out.reserve(room.max(16));
+                    let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut out, last);
+                    rest = &rest[read..];
+                    match result {
+                        EncoderResult::InputEmpty => return out,
+                        EncoderResult::OutputFull => {}
+                        EncoderResult::Unmappable(_) => {
+                            out.push(b'?');
+                            *unmappable += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use super::duckdb_inspector::{DuckDbError, DuckDbInspector, nulls_as_text};
+use super::notes::{self, FileNotes};
+use super::profile::{ColumnProfile, TOP_VALUES, format_mean};
+use super::table_format::to_markdown;
+
+/// Number of rows shown in the sample section
+const SAMPLE_ROWS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Picks HTML for .html/.htm outputs and Markdown otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("html") | Some("htm") => ReportFormat::Html,
+            _ => ReportFormat::Markdown,
+        }
+    }
+}
+
+/// Data profile of a CSV or Parquet file, built from the same queries as the inspector
+pub struct Report {
+    pub file: String,
+    pub row_count: usize,
+    pub columns: Vec<ColumnProfile>,
+    pub sample_headers: Vec<String>,
+    pub sample_rows: Vec<Vec<String>>,
+    /// Notes saved with `ftool note`
+    pub notes: FileNotes,
+}
+
+impl Report {
+    pub fn build(file_path: &str) -> Result<Self, DuckDbError> {
+        let inspector = DuckDbInspector::new(file_path.to_string())?;
+        let row_count = inspector.row_count()?;
+        let columns = inspector.profile()?;
+
+        let (sample_headers, sample_rows) = inspector.preview(SAMPLE_ROWS, 0, "", None, &[])?;
+
+        Ok(Self {
+            file: file_path.to_string(),
+            row_count,
+            columns,
+            sample_headers,
+            sample_rows: nulls_as_text(&sample_rows),
+            notes: notes::for_file(Path::new(file_path)),
+        })
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    fn null_pct(&self, nulls: usize) -> String {
+        if self.row_count == 0 {
+            return "-".to_string();
+        }
+        format!("{:.1}%", nulls as f64 * 100.0 / self.row_count as f64)
+    }
+
+    /// Column statistics, with a Notes column when any column has a note
+    fn stats_table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let with_notes = !self.notes.columns.is_empty();
+        let mut headers: Vec<String> = ["Column", "Type", "Nulls", "Null %", "Min", "Max", "Avg"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        if with_notes {
+            headers.push("Notes".to_string());
+        }
+        let rows = self
+            .columns
+            .iter()
+            .map(|c| {
+                let mut row = vec![
+                    c.name.clone(),
+                    c.data_type.clone(),
+                    c.nulls.to_string(),
+                    self.null_pct(c.nulls),
+                    c.min.clone().unwrap_or_else(|| "-".to_string()),
+                    c.max.clone().unwrap_or_else(|| "-".to_string()),
+                    format_mean(c.mean),
+                ];
+                if with_notes {
+                    row.push(self.notes.column(&c.name).unwrap_or_default().to_string());
+                }
+                row
+            })
+            .collect();
+        (headers, rows)
+    }
+
+    fn top_values_table(column: &ColumnProfile) -> (Vec<String>, Vec<Vec<String>>) {
+        let headers = vec!["Value".to_string(), "Count".to_string()];
+        let rows = column
+            .top_values
+            .iter()
+            .map(|v| vec![v.value.clone(), v.count.to_string()])
+            .collect();
+        (headers, rows)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Data profile: {}\n\n", self.file);
+        out.push_str(&format!(
+            "* **Rows:** {}\n* **Columns:** {}\n\n",
+            self.row_count,
+            self.columns.len()
+        ));
+        if let Some(note) = &self.notes.file {
+            out.push_str(&format!("> {}\n\n", note.replace('\n', "\n> ")));
+        }
+
+        out.push_str("## Schema and statistics\n\n");
+        let (headers, rows) = self.stats_table();
+        out.push_str(&to_markdown(&headers, &rows));
+
+        out.push_str(&format!("\n## Top {} values per column\n", TOP_VALUES));
+        for column in &self.columns {
+            out.push_str(&format!("\n### {}\n\n", column.name));
+            if column.top_values.is_empty() {
+                out.push_str("_All values are NULL_\n");
+            } else {
+                let (headers, rows) = Self::top_values_table(column);
+                out.push_str(&to_markdown(&headers, &rows));
+            }
+        }
+
+        out.push_str(&format!("\n## Sample rows (first {})\n\n", SAMPLE_ROWS));
+        out.push_str(&to_markdown(&self.sample_headers, &self.sample_rows));
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        );
+        out.push_str(&format!("<title>Data profile: {}</title>\n", html_escape(&self.file)));
+        out.push_str(
+            "<style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse;margin-bottom:1em}\
+             th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}th{background:#f0f0f0}</style>\n",
+        );
+        out.push_str("</head>\n<body>\n");
+        out.push_str(&format!("<h1>Data profile: {}</h1>\n", html_escape(&self.file)));
+        out.push_str(&format!(
+            "<ul><li><b>Rows:</b> {}</li><li><b>Columns:</b> {}</li></ul>\n",
+            self.row_count,
+            self.columns.len()
+        ));
+        if let Some(note) = &self.notes.file {
+            out.push_str(&format!("<blockquote>{}</blockquote>\n", html_escape(note).replace('\n', "<br>")));
+        }
+
+        out.push_str("<h2>Schema and statistics</h2>\n");
+        let (headers, rows) = self.stats_table();
+        out.push_str(&html_table(&headers, &rows));
+
+        out.push_str(&format!("<h2>Top {} values per column</h2>\n", TOP_VALUES));
+        for column in &self.columns {
+            out.push_str(&format!("<h3>{}</h3>\n", html_escape(&column.name)));
+            if column.top_values.is_empty() {
+                out.push_str("<p><i>All values are NULL</i></p>\n");
+            } else {
+                let (headers, rows) = Self::top_values_table(column);
+                out.push_str(&html_table(&headers, &rows));
+            }
+        }
+
+        out.push_str(&format!("<h2>Sample rows (first {})</h2>\n", SAMPLE_ROWS));
+        out.push_str(&html_table(&self.sample_headers, &self.sample_rows));
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+fn html_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::from("<table>\n<tr>");
+    for h in headers {
+        out.push_str(&format!("<th>{}</th>", html_escape(h)));
+    }
+    out.push_str("</tr>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            out.push_str(&format!("<td>{}</td>", html_escape(cell)));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
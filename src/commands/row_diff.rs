@@ -0,0 +1,210 @@
+use std::time::Instant;
+
+use super::duckdb_inspector::{DuckDbError, DuckDbInspector, finish_write, open_connection, prepare_write};
+use super::merge::read_function;
+
+/// How a key's row differs between the two files
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowChange {
+    /// Only in the new file
+    Added,
+    /// Only in the old file
+    Removed,
+    /// In both, with these columns holding different values
+    Changed(Vec<String>),
+}
+
+/// One differing key, as streamed by `RowDiff::for_each`
+#[derive(Debug, Clone)]
+pub struct RowDiffEntry {
+    pub key: String,
+    pub change: RowChange,
+}
+
+impl RowDiffEntry {
+    /// "+ key", "- key" or "~ key (col, col)"
+    pub fn line(&self) -> String {
+        match &self.change {
+            RowChange::Added => format!("+ {}", self.key),
+            RowChange::Removed => format!("- {}", self.key),
+            RowChange::Changed(columns) => format!("~ {} ({})", self.key, columns.join(", ")),
+        }
+    }
+}
+
+/// Number of added, removed and changed rows found
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RowDiffCounts {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+}
+
+/// Row-level comparison of two files joined on a key column.
+///
+/// Only columns present in both files are compared; a key that appears several
+/// times in a file is compared against every matching row of the other.
+#[derive(Debug)]
+pub struct RowDiff {
+    pub old: String,
+    pub new: String,
+    pub key: String,
+    /// Non-key columns present in both files
+    pub columns: Vec<String>,
+    /// Columns present in only one of the files, which are not compared
+    pub skipped: Vec<String>,
+}
+
+impl RowDiff {
+    /// Reads both schemas and checks that `key` exists in each
+    pub fn new(old: &str, new: &str, key: &str) -> Result<Self, DuckDbError> {
+        let old_schema = DuckDbInspector::new(old.to_string())?.schema()?;
+        let new_schema = DuckDbInspector::new(new.to_string())?.schema()?;
+
+        for (file, schema) in [(old, &old_schema), (new, &new_schema)] {
+            if !schema.iter().any(|(name, _)| name == key) {
+                return Err(DuckDbError::InvalidColumn(format!(
+                    "Key column '{}' not found in {}",
+                    key, file
+                )));
+            }
+        }
+
+        let in_new = |name: &str| new_schema.iter().any(|(n, _)| n == name);
+        let in_old = |name: &str| old_schema.iter().any(|(n, _)| n == name);
+        let columns = old_schema
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| name != key && in_new(name))
+            .collect();
+        let skipped = old_schema
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| !in_new(name))
+            .chain(new_schema.iter().map(|(name, _)| name.clone()).filter(|name| !in_old(name)))
+            .collect();
+
+        Ok(Self {
+            old: old.to_string(),
+            new: new.to_string(),
+            key: key.to_string(),
+            columns,
+            skipped,
+        })
+    }
+
+    /// One row per differing key: `key`, `change` (added/removed/changed) and
+    /// `changed_columns` (comma-separated), ordered by key
+    fn query(&self) -> String {
+        let key = quote(&self.key);
+        let distinct: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| format!("o.{0} IS DISTINCT FROM n.{0}", quote(c)))
+            .collect();
+        let changed_columns = if self.columns.is_empty() {
+            "''".to_string()
+        } else {
+            let names: Vec<String> = self
+                .columns
+                .iter()
+                .zip(&distinct)
+                .map(|(c, cond)| format!("CASE WHEN {} THEN '{}' END", cond, c.replace('\'', "''")))
+                .collect();
+            format!("concat_ws(', ', {})", names.join(", "))
+        };
+        let any_changed = if distinct.is_empty() {
+            "false".to_string()
+        } else {
+            distinct.join(" OR ")
+        };
+
+        format!(
+            "SELECT CAST(COALESCE(o.{key}, n.{key}) AS VARCHAR) AS key, \
+             CASE WHEN o.{key} IS NULL THEN 'added' WHEN n.{key} IS NULL THEN 'removed' ELSE 'changed' END AS change, \
+             CASE WHEN o.{key} IS NULL OR n.{key} IS NULL THEN '' ELSE {changed_columns} END AS changed_columns \
+             FROM {old} o FULL OUTER JOIN {new} n ON o.{key} = n.{key} \
+             WHERE o.{key} IS NULL OR n.{key} IS NULL OR {any_changed} \
+             ORDER BY 1",
+            old = source(&self.old),
+            new = source(&self.new),
+        )
+    }
+
+    /// Runs the comparison, calling `f` for each difference as it is read
+    pub fn for_each(&self, mut f: impl FnMut(RowDiffEntry)) -> Result<RowDiffCounts, DuckDbError> {
+        let query = self.query();
+        let connection = open_connection()?;
+        let started = Instant::now();
+        let mut stmt = connection
+            .prepare(&query)
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to prepare diff query: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to compare files: {}", e)))?;
+
+        let mut counts = RowDiffCounts::default();
+        for row in rows {
+            let (key, change, columns) =
+                row.map_err(|e| DuckDbError::QueryError(format!("Failed to read diff row: {}", e)))?;
+            let change = match change.as_str() {
+                "added" => {
+                    counts.added += 1;
+                    RowChange::Added
+                }
+                "removed" => {
+                    counts.removed += 1;
+                    RowChange::Removed
+                }
+                _ => {
+                    counts.changed += 1;
+                    RowChange::Changed(columns.split(", ").map(str::to_string).collect())
+                }
+            };
+            f(RowDiffEntry {
+                key: key.unwrap_or_else(|| "NULL".to_string()),
+                change,
+            });
+        }
+        log_query(&query, started);
+        Ok(counts)
+    }
+
+    /// Writes the differences to a CSV with `key,change,changed_columns` columns.
+    /// Returns the number of rows written.
+    pub fn write_csv(&self, output: &str) -> Result<usize, DuckDbError> {
+        let (pending, temp) = prepare_write(output);
+        let query = format!("COPY ({}) TO '{}' (FORMAT CSV, HEADER)", self.query(), temp);
+        let connection = open_connection()?;
+        let started = Instant::now();
+        let rows = connection
+            .execute(&query, [])
+            .map_err(|e| DuckDbError::QueryError(format!("Failed to write diff: {}", e)))?;
+        finish_write(pending)?;
+        log_query(&query, started);
+        Ok(rows)
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn source(path: &str) -> String {
+    format!("{}('{}')", read_function(path), path.replace('\'', "''"))
+}
+
+fn log_query(query: &str, started: Instant) {
+    tracing::debug!(
+        kind = "row_diff",
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        query,
+        "duckdb query"
+    );
+}
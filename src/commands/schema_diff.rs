@@ -0,0 +1,134 @@
+/// Integer and float types in widening order: a change to a later type in the same list is safe
+const INTEGER_WIDENING: [&str; 5] = ["TINYINT", "SMALLINT", "INTEGER", "BIGINT", "HUGEINT"];
+const FLOAT_WIDENING: [&str; 2] = ["FLOAT", "DOUBLE"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaChange {
+    Added { name: String, data_type: String },
+    Removed { name: String, data_type: String },
+    /// A column removed and another added at the same position with the same type
+    Renamed { from: String, to: String, data_type: String },
+    TypeChanged { name: String, from: String, to: String },
+}
+
+impl SchemaChange {
+    /// Whether readers of the old schema keep working with the new one
+    pub fn is_compatible(&self) -> bool {
+        match self {
+            SchemaChange::Added { .. } => true,
+            SchemaChange::Removed { .. } | SchemaChange::Renamed { .. } => false,
+            SchemaChange::TypeChanged { from, to, .. } => is_widening(from, to),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            SchemaChange::Added { name, data_type } => format!("+ added    {} ({})", name, data_type),
+            SchemaChange::Removed { name, data_type } => format!("- removed  {} ({})", name, data_type),
+            SchemaChange::Renamed { from, to, data_type } => {
+                format!("~ renamed  {} -> {} ({})", from, to, data_type)
+            }
+            SchemaChange::TypeChanged { name, from, to } => {
+                format!("~ type     {}: {} -> {}", name, from, to)
+            }
+        }
+    }
+}
+
+/// Differences between two (column name, type) schemas
+#[derive(Debug, Clone)]
+pub struct SchemaDiff {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    pub fn compare(old: &[(String, String)], new: &[(String, String)]) -> Self {
+        let mut changes = Vec::new();
+        let mut removed: Vec<(usize, &String, &String)> = Vec::new();
+        let mut added: Vec<(usize, &String, &String)> = Vec::new();
+
+        for (i, (name, ty)) in old.iter().enumerate() {
+            match new.iter().find(|(n, _)| n == name) {
+                Some((_, new_ty)) if new_ty != ty => changes.push(SchemaChange::TypeChanged {
+                    name: name.clone(),
+                    from: ty.clone(),
+                    to: new_ty.clone(),
+                }),
+                Some(_) => {}
+                None => removed.push((i, name, ty)),
+            }
+        }
+        for (i, (name, ty)) in new.iter().enumerate() {
+            if !old.iter().any(|(n, _)| n == name) {
+                added.push((i, name, ty));
+            }
+        }
+
+        // Pair up removed/added columns in the same position with the same type as renames
+        for (i, name, ty) in removed {
+            match added.iter().position(|(j, _, t)| *j == i && *t == ty) {
+                Some(k) => {
+                    let (_, new_name, _) = added.remove(k);
+                    changes.push(SchemaChange::Renamed {
+                        from: name.clone(),
+                        to: new_name.clone(),
+                        data_type: ty.clone(),
+                    });
+                }
+                None => changes.push(SchemaChange::Removed {
+                    name: name.clone(),
+                    data_type: ty.clone(),
+                }),
+            }
+        }
+        for (_, name, ty) in added {
+            changes.push(SchemaChange::Added {
+                name: name.clone(),
+                data_type: ty.clone(),
+            });
+        }
+
+        Self { changes }
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        self.changes.iter().all(|c| c.is_compatible())
+    }
+
+    /// One line per change, incompatible ones flagged, followed by a verdict
+    pub fn report(&self) -> Vec<String> {
+        if self.changes.is_empty() {
+            return vec!["Schemas are identical".to_string()];
+        }
+        let mut lines: Vec<String> = self
+            .changes
+            .iter()
+            .map(|c| {
+                if c.is_compatible() {
+                    format!("  {}", c.describe())
+                } else {
+                    format!("! {}", c.describe())
+                }
+            })
+            .collect();
+        lines.push(String::new());
+        lines.push(if self.is_compatible() {
+            "Compatible: only additions or widened types".to_string()
+        } else {
+            "Incompatible: columns removed, renamed, or narrowed/changed type".to_string()
+        });
+        lines
+    }
+}
+
+fn is_widening(from: &str, to: &str) -> bool {
+    let rank = |list: &[&str], ty: &str| list.iter().position(|t| *t == ty);
+    if let (Some(a), Some(b)) = (rank(&INTEGER_WIDENING, from), rank(&INTEGER_WIDENING, to)) {
+        return b > a;
+    }
+    if let (Some(a), Some(b)) = (rank(&FLOAT_WIDENING, from), rank(&FLOAT_WIDENING, to)) {
+        return b > a;
+    }
+    // Any integer fits in a DOUBLE for the purposes of a schema check
+    INTEGER_WIDENING.contains(&from) && to == "DOUBLE"
+}
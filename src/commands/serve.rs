@@ -0,0 +1,416 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::Context;
+use serde_json::{Map, Value, json};
+
+use super::duckdb_inspector::{DuckDbError, DuckDbInspector};
+use super::profile::is_numeric_type;
+
+/// Rows returned by `/rows` when `limit` isn't given
+pub const DEFAULT_LIMIT: usize = 100;
+/// Most rows one `/rows` request can ask for
+pub const MAX_LIMIT: usize = 10_000;
+/// Connections answered at the same time, each worker with its own DuckDB connection
+pub const WORKERS: usize = 4;
+/// How long a client may take to send its request or read the response
+pub const IO_TIMEOUT: Duration = Duration::from_secs(10);
+/// Comparisons accepted in a `where` filter, longest first so `>=` isn't read as `>`
+const OPERATORS: [&str; 7] = [">=", "<=", "!=", "<>", "=", ">", "<"];
+
+/// A CSV or Parquet file answering JSON requests over HTTP:
+///
+/// - `GET /schema`: columns, their types and the row count
+/// - `GET /rows?limit=&offset=&where=&columns=`: a page of rows, optionally
+///   filtered by `column op value` conditions joined with AND and narrowed to
+///   comma-separated columns
+/// - `GET /stats`: null count, min, max and mean per column
+///
+/// The DuckDB connection can only read the served file, and filters are
+/// rebuilt from their parsed parts rather than passed through as SQL.
+pub struct Server {
+    file: String,
+    inspector: DuckDbInspector,
+    schema: Vec<(String, String)>,
+}
+
+/// An HTTP status and JSON body
+type Response = (u16, Value);
+
+impl Server {
+    pub fn new(file: &str) -> anyhow::Result<Self> {
+        // Absolute, so DuckDB's allowed path matches the one it reads
+        let path = std::fs::canonicalize(file).map_err(|_| DuckDbError::FileNotFound(file.to_string()))?;
+        let inspector = DuckDbInspector::new(path.to_string_lossy().to_string())?;
+        let schema = inspector.schema()?;
+        inspector.restrict_to_file()?;
+        Ok(Self { file: file.to_string(), inspector, schema })
+    }
+
+    /// Accepts connections on `listener` until the process is stopped, calling
+    /// `log` with one line per request. Connections are handed to `WORKERS`
+    /// threads, each with its own copy of the server, so a slow client only
+    /// holds up one of them, and for at most `IO_TIMEOUT`.
+    pub fn run(self, listener: TcpListener, log: impl Fn(String) + Sync) -> anyhow::Result<()> {
+        let mut servers = Vec::with_capacity(WORKERS);
+        for _ in 1..WORKERS {
+            servers.push(Server::new(&self.file)?);
+        }
+        servers.push(self);
+
+        let (tx, rx) = mpsc::channel::<TcpStream>();
+        let rx = Mutex::new(rx);
+        let (rx, log) = (&rx, &log);
+        std::thread::scope(|scope| {
+            for server in servers {
+                scope.spawn(move || {
+                    loop {
+                        let Ok(stream) = rx.lock().map_err(|_| ()).and_then(|r| r.recv().map_err(|_| ())) else {
+                            break;
+                        };
+                        match server.handle(stream) {
+                            Ok(line) => log(line),
+                            Err(e) => tracing::debug!(error = %e, "failed to answer a request"),
+                        }
+                    }
+                });
+            }
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(s) => s,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to accept a connection");
+                        continue;
+                    }
+                };
+                if let Err(e) = stream
+                    .set_read_timeout(Some(IO_TIMEOUT))
+                    .and_then(|_| stream.set_write_timeout(Some(IO_TIMEOUT)))
+                {
+                    tracing::debug!(error = %e, "failed to set connection timeouts");
+                    continue;
+                }
+                if tx.send(stream).is_err() {
+                    break;
+                }
+            }
+            drop(tx);
+        });
+        Ok(())
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> anyhow::Result<String> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).context("Failed to read the request")?;
+        // The headers aren't needed, but must be read before answering
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or("/");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params = parse_query(query);
+
+        let (status, body) = match (method, path) {
+            ("GET", "/") => self.index(),
+            ("GET", "/schema") => self.schema(),
+            ("GET", "/rows") => self.rows(&params),
+            ("GET", "/stats") => self.stats(),
+            ("GET", _) => error(404, format!("No endpoint {}: try /schema, /rows or /stats", path)),
+            _ => error(405, format!("{} isn't supported, only GET", method)),
+        };
+
+        let body = serde_json::to_string_pretty(&body)?;
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason(status),
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+        Ok(format!("{} {} {}", method, target, status))
+    }
+
+    fn index(&self) -> Response {
+        (
+            200,
+            json!({
+                "file": self.file,
+                "endpoints": {
+                    "/schema": "columns, types and row count",
+                    "/rows": "rows; parameters: limit, offset, where (column op value, joined with AND), columns (comma-separated)",
+                    "/stats": "null count, min, max and mean per column",
+                },
+            }),
+        )
+    }
+
+    fn schema(&self) -> Response {
+        let rows = match self.inspector.row_count() {
+            Ok(n) => n,
+            Err(e) => return error(500, e.to_string()),
+        };
+        let columns: Vec<Value> = self.schema.iter().map(|(name, ty)| json!({ "name": name, "type": ty })).collect();
+        (200, json!({ "file": self.file, "rows": rows, "columns": columns }))
+    }
+
+    fn rows(&self, params: &[(String, String)]) -> Response {
+        let param = |name: &str| params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+        let number = |name: &str, default: usize| match param(name) {
+            Some(v) => v.parse::<usize>().map_err(|_| format!("{} must be a non-negative number", name)),
+            None => Ok(default),
+        };
+        let (limit, offset) = match (number("limit", DEFAULT_LIMIT), number("offset", 0)) {
+            (Ok(limit), Ok(offset)) => (limit.min(MAX_LIMIT), offset),
+            (Err(e), _) | (_, Err(e)) => return error(400, e),
+        };
+        let where_clause = match param("where").map(str::trim).filter(|w| !w.is_empty()) {
+            Some(filter) => match self.where_clause(filter) {
+                Ok(clause) => clause,
+                Err(e) => return error(400, e),
+            },
+            None => String::new(),
+        };
+
+        let columns: Vec<String> = match param("columns") {
+            Some(list) => {
+                let names: Vec<String> = list.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+                if let Some(unknown) = names.iter().find(|n| !self.schema.iter().any(|(name, _)| name == *n)) {
+                    return error(400, format!("Unknown column: {}", unknown));
+                }
+                names
+            }
+            None => self.schema.iter().map(|(name, _)| name.clone()).collect(),
+        };
+
+        let total = match self.inspector.row_count_filtered(&where_clause) {
+            Ok(n) => n,
+            Err(e) => return error(400, e.to_string()),
+        };
+        let (headers, rows) = match self.inspector.preview(limit, offset, &where_clause, Some(&columns), &[]) {
+            Ok(page) => page,
+            Err(e) => return error(400, e.to_string()),
+        };
+        let types: Vec<&str> = headers.iter().map(|h| self.column_type(h)).collect();
+        let rows: Vec<Value> = rows
+            .into_iter()
+            .map(|row| {
+                let record: Map<String, Value> = headers
+                    .iter()
+                    .zip(&types)
+                    .zip(row)
+                    .map(|((name, ty), value)| (name.clone(), typed(value, ty)))
+                    .collect();
+                Value::Object(record)
+            })
+            .collect();
+        (200, json!({ "total": total, "offset": offset, "limit": limit, "rows": rows }))
+    }
+
+    fn stats(&self) -> Response {
+        let names: Vec<String> = self.schema.iter().map(|(name, _)| name.clone()).collect();
+        let stats = match self.inspector.column_stats(&names) {
+            Ok(s) => s,
+            Err(e) => return error(500, e.to_string()),
+        };
+        let columns: Vec<Value> = self
+            .schema
+            .iter()
+            .zip(stats)
+            .map(|((name, ty), s)| {
+                let value = |v: String| if v == "-" { Value::Null } else { typed(Some(v), ty) };
+                json!({
+                    "name": name,
+                    "type": ty,
+                    "nulls": s.null_count,
+                    "min": value(s.min),
+                    "max": value(s.max),
+                    "mean": if s.mean == "-" { Value::Null } else { typed(Some(s.mean), "DOUBLE") },
+                })
+            })
+            .collect();
+        (200, json!({ "columns": columns }))
+    }
+
+    /// `WHERE …` for a filter like `amount > 100 AND region = 'EMEA'`. Each
+    /// condition is `column op value`, `column LIKE value` or `column IS [NOT]
+    /// NULL`; columns must exist and values are always quoted as literals, so
+    /// the filter can't add SQL of its own.
+    fn where_clause(&self, filter: &str) -> Result<String, String> {
+        let conditions = split_and(filter)
+            .into_iter()
+            .map(|condition| {
+                let (column, rest) = split_column(condition)?;
+                if !self.schema.iter().any(|(name, _)| *name == column) {
+                    return Err(format!("Unknown column in where: {}", column));
+                }
+                let column = format!("\"{}\"", column.replace('"', "\"\""));
+                let rest = rest.trim();
+                let upper = rest.to_ascii_uppercase();
+                let normalized: Vec<&str> = upper.split_whitespace().collect();
+                if normalized == ["IS", "NULL"] {
+                    return Ok(format!("{} IS NULL", column));
+                }
+                if normalized == ["IS", "NOT", "NULL"] {
+                    return Ok(format!("{} IS NOT NULL", column));
+                }
+                let (op, value) = if let Some(value) = upper.strip_prefix("NOT LIKE ").map(|v| &rest[rest.len() - v.len()..]) {
+                    ("NOT LIKE", value)
+                } else if let Some(value) = upper.strip_prefix("LIKE ").map(|v| &rest[rest.len() - v.len()..]) {
+                    ("LIKE", value)
+                } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+                    (*op, &rest[op.len()..])
+                } else {
+                    return Err(format!("Expected =, !=, <, <=, >, >=, LIKE or IS [NOT] NULL after {}", column));
+                };
+                let value = literal(value.trim())?;
+                Ok(match op {
+                    "LIKE" | "NOT LIKE" => format!("{}::VARCHAR {} {}", column, op, value),
+                    _ => format!("{} {} {}", column, op, value),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("WHERE {}", conditions.join(" AND ")))
+    }
+
+    fn column_type(&self, name: &str) -> &str {
+        self.schema.iter().find(|(n, _)| n == name).map_or("VARCHAR", |(_, ty)| ty.as_str())
+    }
+}
+
+/// Numbers and booleans as JSON numbers and booleans, the rest as text
+fn typed(value: Option<String>, ty: &str) -> Value {
+    let Some(value) = value else { return Value::Null };
+    if is_numeric_type(ty)
+        && let Ok(n) = value.parse::<serde_json::Number>()
+    {
+        return Value::Number(n);
+    }
+    match (ty, value.as_str()) {
+        ("BOOLEAN", "true") => Value::Bool(true),
+        ("BOOLEAN", "false") => Value::Bool(false),
+        _ => Value::String(value),
+    }
+}
+
+/// The conditions of a filter, split on `AND` outside of quotes
+fn split_and(filter: &str) -> Vec<&str> {
+    let bytes = filter.as_bytes();
+    let mut parts = Vec::new();
+    let (mut start, mut quote) = (0, None);
+    let mut i = 0;
+    while i < bytes.len() {
+        match (quote, bytes[i]) {
+            (None, b'\'' | b'"') => quote = Some(bytes[i]),
+            (Some(q), b) if b == q => quote = None,
+            (None, b) if b.is_ascii_whitespace()
+                && bytes.get(i + 1..i + 4).is_some_and(|w| w.eq_ignore_ascii_case(b"and"))
+                && bytes.get(i + 4).is_some_and(u8::is_ascii_whitespace) =>
+            {
+                parts.push(filter[start..i].trim());
+                i += 4;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(filter[start..].trim());
+    parts
+}
+
+/// A condition's column, bare or double-quoted, and what follows it
+fn split_column(condition: &str) -> Result<(String, &str), String> {
+    if let Some(quoted) = condition.strip_prefix('"') {
+        let mut name = String::new();
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' if chars.peek().is_some_and(|(_, next)| *next == '"') => {
+                    chars.next();
+                    name.push('"');
+                }
+                '"' => return Ok((name, &quoted[i + 1..])),
+                c => name.push(c),
+            }
+        }
+        return Err(format!("Unterminated column name in where: {}", condition));
+    }
+    let end = condition
+        .find(|c: char| c.is_whitespace() || "=!<>".contains(c))
+        .ok_or_else(|| format!("Expected a condition like column = value, got {}", condition))?;
+    Ok((condition[..end].to_string(), &condition[end..]))
+}
+
+/// `'it''s'` or a bare `42` as a quoted SQL literal
+fn literal(value: &str) -> Result<String, String> {
+    let text = match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        Some(inner) if value.len() >= 2 => inner.replace("''", "'"),
+        _ if value.is_empty() || value.contains('\'') => {
+            return Err(format!("Expected a number or a 'quoted' value, got {}", value));
+        }
+        _ => value.to_string(),
+    };
+    Ok(format!("'{}'", text.replace('\'', "''")))
+}
+
+fn error(status: u16, message: String) -> Response {
+    (status, json!({ "error": message }))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+/// `a=1&b=x%20y` as decoded pairs, with `+` as a space
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let hex = |i: usize| bytes.get(i).and_then(|b| char::from(*b).to_digit(16));
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(high), Some(low)) => {
+                out.push((high * 16 + low) as u8);
+                i += 3;
+            }
+            (b'+', ..) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (b, ..) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
@@ -0,0 +1,43 @@
+/// How `ftool split` divides a file
+#[derive(Debug, Clone)]
+pub enum SplitMode {
+    /// Fixed number of rows per output file
+    Rows(usize),
+    /// Approximate size per output file in bytes, estimated from the input's bytes per row
+    Size(u64),
+    /// One output file per distinct value of a column
+    Column(String),
+}
+
+/// Parses sizes like `250MB`, `1.5GB`, `800k` or a plain byte count
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", s))?;
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("unknown size unit '{}' (use B, KB, MB or GB)", unit)),
+    };
+    let bytes = (number * multiplier as f64) as u64;
+    if bytes == 0 {
+        return Err("size must be greater than zero".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Turns a column value into something safe to use in a file name
+pub fn file_name_part(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() { "empty".to_string() } else { cleaned }
+}
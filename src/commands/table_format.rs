@@ -0,0 +1,67 @@
+/// Text formats a table of strings can be written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableFormat {
+    Csv,
+    Markdown,
+}
+
+impl TableFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "CSV",
+            TableFormat::Markdown => "Markdown",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Markdown => "md",
+        }
+    }
+
+    pub fn render(&self, headers: &[String], rows: &[Vec<String>]) -> String {
+        match self {
+            TableFormat::Csv => to_csv(headers, rows),
+            TableFormat::Markdown => to_markdown(headers, rows),
+        }
+    }
+}
+
+/// Serializes headers and rows as RFC 4180 CSV
+pub fn to_csv(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    for line in std::iter::once(headers).chain(rows.iter().map(|r| r.as_slice())) {
+        let fields: Vec<String> = line.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Serializes headers and rows as a GitHub-flavored Markdown table
+pub fn to_markdown(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    out.push_str(&markdown_row(headers));
+    out.push_str(&format!("|{}\n", "---|".repeat(headers.len())));
+    for row in rows {
+        out.push_str(&markdown_row(row));
+    }
+    out
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|c| c.replace('|', "\\|").replace('\n', " "))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
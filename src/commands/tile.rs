@@ -0,0 +1,352 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use anyhow::{Context, bail};
+use serde_json::Value;
+
+use super::backup;
+use super::geo_xml::{self, is_geo_xml};
+use super::hooks::{self, HookEvent};
+use super::geometry::Geometry;
+use super::json_inspector::is_geojson_seq;
+use crate::config::{self, TilePreset};
+
+/// A tippecanoe run turning a GeoJSON, GeoJSONSeq, GPX or KML file into vector tiles
+#[derive(Debug, Clone)]
+pub struct TilePlan {
+    pub input: PathBuf,
+    /// `.pmtiles` or `.mbtiles`; tippecanoe picks the format from the extension
+    pub output: PathBuf,
+    pub layer: Option<String>,
+    pub min_zoom: Option<u8>,
+    /// Guessed from the data by tippecanoe (`-zg`) when not set
+    pub max_zoom: Option<u8>,
+    /// Passed to tippecanoe as is, after the options above
+    pub extra: Vec<String>,
+    /// Temp directory holding the GeoJSON converted from a GPX or KML input,
+    /// deleted once the last copy of the plan is dropped
+    _converted: Option<Arc<backup::PendingWrite>>,
+}
+
+impl TilePlan {
+    /// Tiles `input` into `<name>.pmtiles` next to it. GPX and KML files are
+    /// first converted to a GeoJSON file of the same name in a temp directory
+    /// of their own, so tippecanoe can read them and still names the layer
+    /// after the input.
+    pub fn new(input: &Path) -> anyhow::Result<Self> {
+        let output = input.with_extension("pmtiles");
+        let (input, converted) = if is_geo_xml(input) {
+            let collection = geo_xml::read(input)?;
+            let name = input.with_extension("geojson").file_name().map(|n| n.to_os_string()).unwrap_or_default();
+            let dir = backup::prepare_untracked(&std::env::temp_dir().join("ftool-tiles"));
+            std::fs::create_dir(dir.temp()).with_context(|| format!("Failed to create {}", dir.temp().display()))?;
+            let converted = dir.temp().join(name);
+            std::fs::write(&converted, serde_json::to_vec(&collection)?)
+                .with_context(|| format!("Failed to write {}", converted.display()))?;
+            (converted, Some(Arc::new(dir)))
+        } else {
+            (input.to_path_buf(), None)
+        };
+        Ok(Self {
+            input,
+            output,
+            layer: None,
+            min_zoom: None,
+            max_zoom: None,
+            extra: Vec::new(),
+            _converted: converted,
+        })
+    }
+
+    /// The tippecanoe binary: `tippecanoe_path` from the config, or the one on PATH
+    pub fn program() -> PathBuf {
+        config::get().tippecanoe_path.clone().unwrap_or_else(|| PathBuf::from("tippecanoe"))
+    }
+
+    /// Takes the preset's zoom range and puts its flags before any extra options
+    pub fn apply(&mut self, preset: &TilePreset) {
+        self.min_zoom = Some(preset.min_zoom);
+        self.max_zoom = Some(preset.max_zoom);
+        self.extra.splice(0..0, preset.flags.iter().cloned());
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["-o".to_string(), self.output.display().to_string(), "--force".to_string()];
+        match self.max_zoom {
+            Some(z) => args.push(format!("-z{}", z)),
+            None => args.push("-zg".to_string()),
+        }
+        if let Some(z) = self.min_zoom {
+            args.push(format!("-Z{}", z));
+        }
+        if let Some(layer) = &self.layer {
+            args.extend(["-l".to_string(), layer.clone()]);
+        }
+        // Sequential feature ids let the PMTiles check tell clipped copies of
+        // a feature apart from distinct features
+        let has_ids = self.extra.iter().any(|a| a.starts_with("--generate-ids") || a.starts_with("--use-attribute-for-id"));
+        if !has_ids && self.writes_pmtiles() {
+            args.push("--generate-ids".to_string());
+        }
+        // Newline-delimited features can be split between threads, which
+        // tippecanoe only does for line-based input when asked to
+        if is_geojson_seq(&self.input) {
+            args.push("-P".to_string());
+        }
+        args.extend(self.extra.iter().cloned());
+        args.push(self.input.display().to_string());
+        args
+    }
+
+    /// The command line as it would be typed, for logs and dry runs
+    pub fn command_line(&self) -> String {
+        std::iter::once(Self::program().display().to_string())
+            .chain(self.args())
+            .map(|a| if a.contains(' ') { format!("'{}'", a) } else { a })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Runs tippecanoe, letting it print its progress to the terminal
+    pub fn run(&self) -> anyhow::Result<()> {
+        self.run_with(|mut command| {
+            let status = command.status().with_context(Self::not_found)?;
+            if !status.success() {
+                bail!("tippecanoe exited with {}", status);
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs tippecanoe with its output captured, for callers that own the
+    /// terminal; a failure carries the last lines tippecanoe printed
+    pub fn run_captured(&self) -> anyhow::Result<()> {
+        self.run_with(|mut command| {
+            let output = command.output().with_context(Self::not_found)?;
+            if !output.status.success() {
+                let log = String::from_utf8_lossy(&output.stderr);
+                let tail: Vec<&str> = log.lines().rev().take(5).collect::<Vec<_>>().into_iter().rev().collect();
+                bail!("tippecanoe exited with {}:\n{}", output.status, tail.join("\n"));
+            }
+            Ok(())
+        })
+    }
+
+    /// Has tippecanoe write a temp file next to the output, renamed into place
+    /// once it succeeded, so an interrupted run leaves the old tileset alone.
+    /// The `after_tiles` hook runs once the tileset is in place.
+    fn run_with(&self, run: impl FnOnce(Command) -> anyhow::Result<()>) -> anyhow::Result<()> {
+        let pending = backup::prepare(&self.output);
+        let plan = TilePlan { output: pending.temp().to_path_buf(), ..self.clone() };
+        run(plan.command()?)?;
+        pending.finish()?;
+        hooks::run(HookEvent::Tiles, &self.output).map_err(|e| anyhow::anyhow!("Wrote the tiles, but {:#}", e))
+    }
+
+    /// Whether the output is a PMTiles archive `pmtiles::check` can read
+    pub fn writes_pmtiles(&self) -> bool {
+        self.output.extension().is_some_and(|e| e.eq_ignore_ascii_case("pmtiles"))
+    }
+
+    /// Features in the input, to compare with what ends up in the tiles
+    pub fn input_features(&self) -> anyhow::Result<usize> {
+        if is_geojson_seq(&self.input) {
+            let text = std::fs::read_to_string(&self.input)?;
+            return Ok(text.lines().filter(|l| !l.trim_matches(|c: char| c == '\x1e' || c.is_whitespace()).is_empty()).count());
+        }
+        let root: Value = serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&self.input)?))
+            .with_context(|| format!("Invalid GeoJSON in {}", self.input.display()))?;
+        Ok(match root.get("features").and_then(Value::as_array) {
+            Some(features) => features.len(),
+            None => usize::from(root.get("type").and_then(Value::as_str).is_some()),
+        })
+    }
+
+    fn command(&self) -> anyhow::Result<Command> {
+        if !self.input.exists() {
+            bail!("File not found: {}", self.input.display());
+        }
+        tracing::debug!(command = self.command_line(), "running tippecanoe");
+        let mut command = Command::new(Self::program());
+        command.args(self.args());
+        Ok(command)
+    }
+
+    fn not_found() -> String {
+        format!(
+            "Failed to run {} (install tippecanoe or set tippecanoe_path in the config)",
+            Self::program().display()
+        )
+    }
+}
+
+/// Presets every install has; saved presets can't reuse their names
+pub fn builtin_presets() -> Vec<TilePreset> {
+    let preset = |name: &str, min_zoom, max_zoom, flags: &[&str]| TilePreset {
+        name: name.to_string(),
+        min_zoom,
+        max_zoom,
+        flags: flags.iter().map(|f| f.to_string()).collect(),
+    };
+    vec![
+        preset("Generic", 0, 14, &["--drop-densest-as-needed", "--extend-zooms-if-still-dropping"]),
+        // Adjacent polygons keep shared edges and small lots aren't dropped when zoomed in
+        preset("Parcels", 10, 16, &["--detect-shared-borders", "--coalesce-smallest-as-needed", "--no-tiny-polygon-reduction"]),
+        // Every point at low zooms too, clustered instead of thinned
+        preset("Points", 0, 14, &["-r1", "--cluster-distance=10", "--accumulate-attribute=point_count:sum"]),
+    ]
+}
+
+/// The built-in presets followed by the ones saved in the config
+pub fn presets() -> Vec<TilePreset> {
+    let mut presets = builtin_presets();
+    presets.extend(config::get().tile_presets.iter().cloned());
+    presets
+}
+
+/// Looks up a preset by name, ignoring case
+pub fn find_preset(name: &str) -> anyhow::Result<TilePreset> {
+    let presets = presets();
+    match presets.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
+        Some(preset) => Ok(preset.clone()),
+        None => {
+            let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+            bail!("Unknown preset '{}' (available: {})", name, names.join(", "))
+        }
+    }
+}
+
+/// Features sampled when estimating a tile pyramid
+pub const ESTIMATE_SAMPLE: usize = 2000;
+/// Tiles at one zoom level above which the run is likely to take hours and
+/// produce an unwieldy file
+const TILE_WARNING: u64 = 1_000_000;
+const SIZE_WARNING: u64 = 2 * 1024 * 1024 * 1024;
+/// Features spanning more tiles than this at the top zoom are mostly clipped copies
+const SPREAD_WARNING: f64 = 500.0;
+/// Vector tile bytes per byte of GeoJSON (integer deltas in protobuf, gzipped)
+const ENCODED_RATIO: f64 = 0.2;
+/// tippecanoe drops features from tiles above this size (`--maximum-tile-bytes`)
+const MAX_TILE_BYTES: u64 = 500 * 1024;
+/// Per-tile bytes for headers and the PMTiles directory entry
+const TILE_OVERHEAD: u64 = 64;
+/// Tiles a single sampled feature may add to the exact count; bigger
+/// features are counted without deduplication
+const EXACT_TILES: u64 = 256;
+
+/// Predicted tile count and size for one zoom level
+#[derive(Debug, Clone)]
+pub struct ZoomEstimate {
+    pub zoom: u8,
+    pub tiles: u64,
+    pub bytes: u64,
+}
+
+/// Rough tile pyramid size, from a sample of the features
+#[derive(Debug, Clone, Default)]
+pub struct TileEstimate {
+    pub features: usize,
+    pub sampled: usize,
+    pub zooms: Vec<ZoomEstimate>,
+    /// Why the chosen zoom range may blow up the output
+    pub warnings: Vec<String>,
+}
+
+impl TileEstimate {
+    pub fn tiles(&self) -> u64 {
+        self.zooms.iter().map(|z| z.tiles).sum()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.zooms.iter().map(|z| z.bytes).sum()
+    }
+}
+
+/// Estimates the tiles and bytes tippecanoe would write for `features`
+/// (GeoJSON Feature objects) between `min_zoom` and `max_zoom`.
+///
+/// Each sampled feature's bounding box gives the tiles it touches; every zoom
+/// holds roughly all the features, capped by tippecanoe's per-tile size limit.
+/// Clustered data and diagonal lines make this an overestimate.
+pub fn estimate(features: &[Value], min_zoom: u8, max_zoom: u8) -> TileEstimate {
+    let step = features.len().div_ceil(ESTIMATE_SAMPLE).max(1);
+    let sample: Vec<((f64, f64, f64, f64), usize)> = features
+        .iter()
+        .step_by(step)
+        .filter_map(|f| {
+            let bbox = Geometry::from_geojson(f.get("geometry")?).ok()?.bbox()?;
+            Some((bbox, serde_json::to_vec(f).map_or(0, |v| v.len())))
+        })
+        .collect();
+    let mut estimate = TileEstimate { features: features.len(), sampled: sample.len(), ..Default::default() };
+    if sample.is_empty() {
+        return estimate;
+    }
+
+    let scale = features.len() as f64 / sample.len() as f64;
+    let encoded = sample.iter().map(|(_, bytes)| *bytes as f64).sum::<f64>() * scale * ENCODED_RATIO;
+    let extent = sample.iter().skip(1).fold(sample[0].0, |(a, b, c, d), ((x0, y0, x1, y1), _)| {
+        (a.min(*x0), b.min(*y0), c.max(*x1), d.max(*y1))
+    });
+
+    for zoom in min_zoom..=max_zoom {
+        let mut unique = HashSet::new();
+        let mut spread = 0u64;
+        for (bbox, _) in &sample {
+            let (x0, y0, x1, y1) = tile_range(*bbox, zoom);
+            let count = u64::from(x1 - x0 + 1) * u64::from(y1 - y0 + 1);
+            if count <= EXACT_TILES {
+                for x in x0..=x1 {
+                    for y in y0..=y1 {
+                        unique.insert((x, y));
+                    }
+                }
+            } else {
+                spread += count;
+            }
+        }
+        let (x0, y0, x1, y1) = tile_range(extent, zoom);
+        let extent_tiles = u64::from(x1 - x0 + 1) * u64::from(y1 - y0 + 1);
+        let tiles = (((unique.len() as u64 + spread) as f64 * scale) as u64).clamp(1, extent_tiles);
+        let bytes = (encoded as u64).min(tiles.saturating_mul(MAX_TILE_BYTES)) + tiles * TILE_OVERHEAD;
+        estimate.zooms.push(ZoomEstimate { zoom, tiles, bytes });
+    }
+
+    if let Some(top) = estimate.zooms.last() {
+        if top.tiles > TILE_WARNING {
+            estimate.warnings.push(format!(
+                "zoom {} alone needs about {} tiles: lower the max zoom",
+                top.zoom, top.tiles
+            ));
+        }
+        let per_feature = top.tiles as f64 / features.len().max(1) as f64;
+        if per_feature > SPREAD_WARNING {
+            estimate.warnings.push(format!(
+                "features span about {:.0} tiles each at zoom {}: the data is coarser than the max zoom",
+                per_feature, top.zoom
+            ));
+        }
+    }
+    if estimate.bytes() > SIZE_WARNING {
+        estimate.warnings.push("the output is likely to exceed 2 GB".to_string());
+    }
+    estimate
+}
+
+/// Tile columns and rows (x0, y0, x1, y1) covering a lon/lat bounding box
+fn tile_range((min_lon, min_lat, max_lon, max_lat): (f64, f64, f64, f64), zoom: u8) -> (u32, u32, u32, u32) {
+    let (x0, y1) = tile_xy(min_lon, min_lat, zoom);
+    let (x1, y0) = tile_xy(max_lon, max_lat, zoom);
+    (x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1))
+}
+
+/// The Web Mercator tile holding a point
+fn tile_xy(lon: f64, lat: f64, zoom: u8) -> (u32, u32) {
+    let n = 2f64.powi(i32::from(zoom));
+    let lat = lat.clamp(-85.0511, 85.0511).to_radians();
+    let x = (lon.clamp(-180.0, 180.0) + 180.0) / 360.0 * n;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * n;
+    (x.floor().clamp(0.0, n - 1.0) as u32, y.floor().clamp(0.0, n - 1.0) as u32)
+}
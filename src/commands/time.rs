@@ -0,0 +1,203 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use clap::ValueEnum;
+
+/// How a timestamp was written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeFormat {
+    EpochSeconds,
+    EpochMillis,
+    EpochMicros,
+    EpochNanos,
+    /// ISO 8601 / RFC 3339, or a date and time without an offset (`2024-05-01 12:00:00`)
+    Iso8601,
+    /// `Wed, 01 May 2024 12:00:00 +0000` (email and HTTP headers)
+    Rfc2822,
+    /// `01/May/2024:12:00:00 +0000` (Apache / nginx access logs)
+    CommonLog,
+    /// `May  1 12:00:00` (syslog; the year is the current one)
+    Syslog,
+}
+
+impl TimeFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeFormat::EpochSeconds => "epoch seconds",
+            TimeFormat::EpochMillis => "epoch milliseconds",
+            TimeFormat::EpochMicros => "epoch microseconds",
+            TimeFormat::EpochNanos => "epoch nanoseconds",
+            TimeFormat::Iso8601 => "ISO 8601",
+            TimeFormat::Rfc2822 => "RFC 2822",
+            TimeFormat::CommonLog => "common log format",
+            TimeFormat::Syslog => "syslog",
+        }
+    }
+
+    fn is_epoch(self) -> bool {
+        matches!(
+            self,
+            TimeFormat::EpochSeconds | TimeFormat::EpochMillis | TimeFormat::EpochMicros | TimeFormat::EpochNanos
+        )
+    }
+}
+
+/// Forms `ftool time --to` can print
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TimeOutput {
+    /// Epoch seconds
+    Epoch,
+    /// Epoch milliseconds
+    EpochMs,
+    /// ISO 8601 in UTC, e.g. 2024-05-01T12:00:00Z
+    Iso,
+    /// ISO 8601 with the local offset
+    Local,
+    /// RFC 2822, e.g. Wed, 01 May 2024 12:00:00 +0000
+    Rfc2822,
+    /// Common log format, e.g. 01/May/2024:12:00:00 +0000
+    Log,
+}
+
+/// A point in time read from text, and how it was written
+#[derive(Debug, Clone, Copy)]
+pub struct Timestamp {
+    pub instant: DateTime<Utc>,
+    pub format: TimeFormat,
+    /// The text had no offset, so it was read as UTC
+    pub naive: bool,
+}
+
+impl Timestamp {
+    pub fn to(&self, output: TimeOutput) -> String {
+        let t = self.instant;
+        match output {
+            TimeOutput::Epoch => t.timestamp().to_string(),
+            TimeOutput::EpochMs => t.timestamp_millis().to_string(),
+            TimeOutput::Iso => t.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+            TimeOutput::Local => t.with_timezone(&Local).to_rfc3339_opts(chrono::SecondsFormat::AutoSi, false),
+            TimeOutput::Rfc2822 => t.to_rfc2822(),
+            TimeOutput::Log => t.format("%d/%b/%Y:%H:%M:%S %z").to_string(),
+        }
+    }
+
+    /// Every converted form, as (label, value) lines
+    pub fn conversions(&self) -> Vec<(&'static str, String)> {
+        let detected = if self.naive {
+            format!("{} (no offset, read as UTC)", self.format.label())
+        } else {
+            self.format.label().to_string()
+        };
+        vec![
+            ("Detected", detected),
+            ("UTC", self.instant.format("%Y-%m-%d %H:%M:%S%.f UTC").to_string()),
+            ("Local", self.instant.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S%.f %:z").to_string()),
+            ("ISO 8601", self.to(TimeOutput::Iso)),
+            ("Epoch s", self.to(TimeOutput::Epoch)),
+            ("Epoch ms", self.to(TimeOutput::EpochMs)),
+            ("RFC 2822", self.to(TimeOutput::Rfc2822)),
+            ("Log", self.to(TimeOutput::Log)),
+            ("Relative", relative(self.instant, Utc::now())),
+        ]
+    }
+
+    /// `conversions` as aligned lines
+    pub fn describe(&self) -> Vec<String> {
+        self.conversions().into_iter().map(|(label, value)| format!("{:<10}{}", label, value)).collect()
+    }
+}
+
+/// Reads epoch seconds, milliseconds, microseconds or nanoseconds (by
+/// magnitude), ISO 8601, RFC 2822, common log format or syslog timestamps
+pub fn parse(text: &str) -> Option<Timestamp> {
+    let text = text.trim().trim_start_matches('[').trim_end_matches(']');
+    if text.is_empty() {
+        return None;
+    }
+    parse_epoch(text).or_else(|| parse_text(text))
+}
+
+/// Like `parse`, but numbers only count as epochs when they fall between 1990
+/// and 2100, so ids and amounts in a data file aren't taken for times
+pub fn parse_cell(text: &str) -> Option<Timestamp> {
+    let ts = parse(text)?;
+    if ts.format.is_epoch() && !(1990..=2100).contains(&ts.instant.year()) {
+        return None;
+    }
+    Some(ts)
+}
+
+fn parse_epoch(text: &str) -> Option<Timestamp> {
+    let value: f64 = text.parse().ok()?;
+    if !text.bytes().all(|b| b.is_ascii_digit() || b == b'.' || b == b'-') {
+        return None;
+    }
+    // Seconds reach 1e11 in the year 5138, so larger values are finer units
+    let magnitude = value.abs();
+    let (format, nanos_per_unit) = if magnitude < 1e11 {
+        (TimeFormat::EpochSeconds, 1e9)
+    } else if magnitude < 1e14 {
+        (TimeFormat::EpochMillis, 1e6)
+    } else if magnitude < 1e17 {
+        (TimeFormat::EpochMicros, 1e3)
+    } else {
+        (TimeFormat::EpochNanos, 1.0)
+    };
+    let nanos = (value * nanos_per_unit).round();
+    if !nanos.is_finite() || nanos.abs() > i64::MAX as f64 {
+        return None;
+    }
+    Some(Timestamp { instant: DateTime::from_timestamp_nanos(nanos as i64), format, naive: false })
+}
+
+fn parse_text(text: &str) -> Option<Timestamp> {
+    let zoned = |instant: DateTime<Utc>, format| Some(Timestamp { instant, format, naive: false });
+    let naive = |dt: NaiveDateTime, format| Some(Timestamp { instant: Utc.from_utc_datetime(&dt), format, naive: true });
+
+    if let Ok(t) = DateTime::parse_from_rfc3339(text) {
+        return zoned(t.to_utc(), TimeFormat::Iso8601);
+    }
+    // `%#z` also takes an hours-only offset, as DuckDB writes them (`+01`)
+    for pattern in ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%#z", "%Y-%m-%d %H:%M:%S%.f %z"] {
+        if let Ok(t) = DateTime::parse_from_str(text, pattern) {
+            return zoned(t.to_utc(), TimeFormat::Iso8601);
+        }
+    }
+    if let Ok(t) = DateTime::parse_from_rfc2822(text) {
+        return zoned(t.to_utc(), TimeFormat::Rfc2822);
+    }
+    if let Ok(t) = DateTime::parse_from_str(text, "%d/%b/%Y:%H:%M:%S %z") {
+        return zoned(t.to_utc(), TimeFormat::CommonLog);
+    }
+    for pattern in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, pattern) {
+            return naive(dt, TimeFormat::Iso8601);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return naive(date.and_hms_opt(0, 0, 0)?, TimeFormat::Iso8601);
+    }
+    let with_year = format!("{} {}", Utc::now().year(), text);
+    if let Ok(dt) = NaiveDateTime::parse_from_str(&with_year, "%Y %b %e %H:%M:%S") {
+        return naive(dt, TimeFormat::Syslog);
+    }
+    None
+}
+
+/// `3 days ago`, `in 2 hours`
+fn relative(instant: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - instant).num_seconds();
+    let abs = seconds.unsigned_abs();
+    let (n, unit) = match abs {
+        0..60 => return if abs < 5 { "now".to_string() } else { format_relative(abs, "second", seconds) },
+        60..3_600 => (abs / 60, "minute"),
+        3_600..86_400 => (abs / 3_600, "hour"),
+        86_400..2_592_000 => (abs / 86_400, "day"),
+        2_592_000..31_536_000 => (abs / 2_592_000, "month"),
+        _ => (abs / 31_536_000, "year"),
+    };
+    format_relative(n, unit, seconds)
+}
+
+fn format_relative(n: u64, unit: &str, seconds: i64) -> String {
+    let plural = if n == 1 { "" } else { "s" };
+    if seconds >= 0 { format!("{} {}{} ago", n, unit, plural) } else { format!("in {} {}{}", n, unit, plural) }
+}
@@ -0,0 +1,197 @@
+use super::duckdb_inspector::DuckDbError;
+
+/// Characters kept by `keep-prefix` when no length is given
+const DEFAULT_PREFIX_LEN: usize = 4;
+
+/// How a column's values are hidden when exporting data that contains PII
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mask {
+    /// SHA-256 of the value, so equal values still match after masking
+    Hash,
+    /// Replace every non-NULL value with `REDACTED`
+    Redact,
+    /// Keep the first N characters and replace the rest with `***`
+    KeepPrefix(usize),
+}
+
+impl Mask {
+    /// Parses `hash`, `redact`, `keep-prefix` or `keep-prefix:N`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.split_once(':') {
+            None if s == "hash" => Ok(Mask::Hash),
+            None if s == "redact" => Ok(Mask::Redact),
+            None if s == "keep-prefix" => Ok(Mask::KeepPrefix(DEFAULT_PREFIX_LEN)),
+            Some(("keep-prefix", n)) => n
+                .parse()
+                .map(Mask::KeepPrefix)
+                .map_err(|_| format!("invalid prefix length '{}'", n)),
+            _ => Err(format!(
+                "unknown mask '{}' (expected hash, redact, or keep-prefix[:N])",
+                s
+            )),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Mask::Hash => "hash".to_string(),
+            Mask::Redact => "redact".to_string(),
+            Mask::KeepPrefix(n) => format!("keep-prefix:{}", n),
+        }
+    }
+
+    /// Next mask when cycling through them in the TUI (None = unmasked)
+    pub fn cycle(mask: Option<Mask>) -> Option<Mask> {
+        match mask {
+            None => Some(Mask::Hash),
+            Some(Mask::Hash) => Some(Mask::Redact),
+            Some(Mask::Redact) => Some(Mask::KeepPrefix(DEFAULT_PREFIX_LEN)),
+            Some(Mask::KeepPrefix(_)) => None,
+        }
+    }
+
+    /// DuckDB expression masking `expr`; NULLs stay NULL
+    fn apply(&self, expr: &str) -> String {
+        let text = format!("CAST({} AS VARCHAR)", expr);
+        match self {
+            Mask::Hash => format!("sha256({})", text),
+            Mask::Redact => format!("CASE WHEN {} IS NULL THEN NULL ELSE 'REDACTED' END", expr),
+            Mask::KeepPrefix(n) => format!("left({0}, {1}) || '***'", text, n),
+        }
+    }
+}
+
+/// An extra column computed from a SQL expression, written as `expr AS name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedColumn {
+    pub name: String,
+    pub expr: String,
+}
+
+impl ComputedColumn {
+    /// Parses `price * qty AS total`; the last ` AS ` (any case) separates the name
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let upper = s.to_ascii_uppercase();
+        let Some(idx) = upper.rfind(" AS ") else {
+            return Err(format!("expected 'expression AS name', got '{}'", s));
+        };
+        let expr = s[..idx].trim();
+        let name = s[idx + 4..].trim().trim_matches('"');
+        if expr.is_empty() || name.is_empty() {
+            return Err(format!("expected 'expression AS name', got '{}'", s));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            expr: expr.to_string(),
+        })
+    }
+
+    /// `(expr) AS "name"` for a SELECT list
+    pub fn sql(&self) -> String {
+        format!("({}) AS {}", self.expr, quote_identifier(&self.name))
+    }
+
+    pub fn label(&self) -> String {
+        format!("{} AS {}", self.expr, self.name)
+    }
+}
+
+/// Column-level cleanups applied when writing a converted file. They are turned
+/// into the SELECT list of the `COPY (SELECT ...) TO ...` query, so no SQL is needed.
+#[derive(Debug, Clone, Default)]
+pub struct Transform {
+    /// Columns to keep, in output order (empty keeps every column)
+    pub select: Vec<String>,
+    /// (old name, new name)
+    pub rename: Vec<(String, String)>,
+    /// (column, DuckDB type)
+    pub cast: Vec<(String, String)>,
+    pub drop: Vec<String>,
+    /// (column, mask) applied after any cast
+    pub mask: Vec<(String, Mask)>,
+    /// Extra columns appended after the file's own
+    pub computed: Vec<ComputedColumn>,
+}
+
+impl Transform {
+    pub fn is_empty(&self) -> bool {
+        self.select.is_empty()
+            && self.rename.is_empty()
+            && self.cast.is_empty()
+            && self.drop.is_empty()
+            && self.mask.is_empty()
+            && self.computed.is_empty()
+    }
+
+    /// Builds the SELECT list for `columns` (the file's column names in schema order)
+    pub fn select_list(&self, columns: &[String]) -> Result<String, DuckDbError> {
+        if self.is_empty() {
+            return Ok("*".to_string());
+        }
+
+        let referenced = self
+            .select
+            .iter()
+            .chain(self.rename.iter().map(|(c, _)| c))
+            .chain(self.cast.iter().map(|(c, _)| c))
+            .chain(self.drop.iter())
+            .chain(self.mask.iter().map(|(c, _)| c));
+        for name in referenced {
+            if !columns.contains(name) {
+                return Err(DuckDbError::InvalidColumn(name.clone()));
+            }
+        }
+
+        let base: Vec<&String> = if self.select.is_empty() {
+            columns.iter().collect()
+        } else {
+            self.select.iter().collect()
+        };
+
+        let mut parts = Vec::new();
+        for name in base.into_iter().filter(|c| !self.drop.contains(c)) {
+            let quoted = quote_identifier(name);
+            let mut expr = match self.cast.iter().find(|(c, _)| c == name) {
+                Some((_, ty)) => format!("CAST({} AS {})", quoted, sanitize_type(ty)?),
+                None => quoted.clone(),
+            };
+            if let Some((_, mask)) = self.mask.iter().find(|(c, _)| c == name) {
+                expr = mask.apply(&expr);
+            }
+            match self.rename.iter().find(|(c, _)| c == name) {
+                Some((_, new)) => expr = format!("{} AS {}", expr, quote_identifier(new)),
+                // Keep the original name on casted/masked columns instead of DuckDB's generated one
+                None if expr != quoted => expr = format!("{} AS {}", expr, quoted),
+                None => {}
+            }
+            parts.push(expr);
+        }
+        parts.extend(self.computed.iter().map(ComputedColumn::sql));
+
+        if parts.is_empty() {
+            return Err(DuckDbError::InvalidColumn(
+                "Transform drops every column".to_string(),
+            ));
+        }
+        Ok(parts.join(", "))
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Type names are spliced into SQL, so only allow what DuckDB types look like
+/// (e.g. `BIGINT`, `DECIMAL(10, 2)`, `VARCHAR[]`)
+fn sanitize_type(ty: &str) -> Result<&str, DuckDbError> {
+    let ty = ty.trim();
+    let valid = !ty.is_empty()
+        && ty
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '(' | ')' | ',' | ' ' | '[' | ']'));
+    if valid {
+        Ok(ty)
+    } else {
+        Err(DuckDbError::QueryError(format!("Invalid cast type: {}", ty)))
+    }
+}
@@ -0,0 +1,33 @@
+/// How `ftool file` prints sizes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SizeFormat {
+    /// Exact byte count, e.g. `1234567 bytes`
+    #[default]
+    Bytes,
+    /// Binary units with one decimal, e.g. `1.2 MB`
+    Human,
+}
+
+impl SizeFormat {
+    pub fn format(self, bytes: u64) -> String {
+        match self {
+            SizeFormat::Bytes => format!("{} bytes", bytes),
+            SizeFormat::Human => format_size(bytes),
+        }
+    }
+}
+
+/// `bytes` in B, KB, MB, GB or TB (powers of 1024) with one decimal
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
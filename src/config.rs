@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::commands::backup;
+use crate::commands::hooks::HookEvent;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// JSON files above this many megabytes ask before loading when `large_json_mb` isn't set
+pub const DEFAULT_LARGE_JSON_MB: u64 = 100;
+
+/// CSVs above this many rows ask before loading when `large_csv_rows` isn't set
+pub const DEFAULT_LARGE_CSV_ROWS: u64 = 5_000_000;
+
+/// Accent colors accepted by the `theme` key
+pub const THEMES: [&str; 4] = ["cyan", "blue", "green", "magenta"];
+
+/// Keys understood by `ftool config get/set`
+pub const KEYS: [&str; 15] = [
+    "theme",
+    "default_convert_format",
+    "tippecanoe_path",
+    "s3_endpoint",
+    "editor",
+    "memory_limit",
+    "threads",
+    "temp_directory",
+    "readonly",
+    "permanent_delete",
+    "http_history_bodies",
+    "large_json_mb",
+    "large_csv_rows",
+    "timezone",
+    "parquet_key",
+];
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// User defaults read from `<config dir>/ftool/config.toml` (e.g. ~/.config/ftool/config.toml).
+/// Every key is optional; unset keys fall back to the built-in defaults.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: Option<String>,
+    pub default_convert_format: Option<String>,
+    pub tippecanoe_path: Option<PathBuf>,
+    /// Host of an S3-compatible store (e.g. MinIO), set as DuckDB's `s3_endpoint`
+    pub s3_endpoint: Option<String>,
+    /// Command `ftool note --edit` opens notes with, instead of `$VISUAL`/`$EDITOR`
+    pub editor: Option<String>,
+    /// DuckDB `memory_limit`, e.g. `2GB` or `50%`
+    pub memory_limit: Option<String>,
+    /// DuckDB worker threads
+    pub threads: Option<usize>,
+    /// Where DuckDB spills to disk when over the memory limit
+    pub temp_directory: Option<PathBuf>,
+    /// Refuse every action that writes or changes data files (`--readonly`)
+    pub readonly: Option<bool>,
+    /// Delete files in the TUI's file browser for good instead of moving them
+    /// to the trash (`ftool tui --permanent`)
+    pub permanent_delete: Option<bool>,
+    /// Keep request bodies in the TUI HTTP client's history (default true)
+    pub http_history_bodies: Option<bool>,
+    /// JSON size, in megabytes, above which the TUI asks before loading; 0 never asks
+    pub large_json_mb: Option<u64>,
+    /// Estimated CSV rows above which the TUI asks before loading; 0 never asks
+    pub large_csv_rows: Option<u64>,
+    /// Zone timestamps are shown in by the TUI: `utc` (default), `local` or an
+    /// IANA name such as `Europe/Paris`
+    pub timezone: Option<String>,
+    /// Key for encrypted Parquet files: a name from `[parquet_keys]` or the key
+    /// itself (16, 24 or 32 bytes, or their base64)
+    pub parquet_key: Option<String>,
+    /// Named Parquet encryption keys (`[parquet_keys]`), picked with `parquet_key`
+    /// or `--parquet-key`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub parquet_keys: BTreeMap<String, String>,
+    /// Friendly dataset names (`ftool alias add`) and the paths, globs or URLs they stand for
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Zoom ranges and tippecanoe options saved from the PMTiles popup
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tile_presets: Vec<TilePreset>,
+    /// Headers sent with `ftool json <url>` requests, by host (`[http_headers."api.example.com"]`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub http_headers: BTreeMap<String, BTreeMap<String, String>>,
+    /// Shell commands run after a file is written (`[hooks]`), by event:
+    /// `after_convert`, `after_export` or `after_tiles`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub hooks: BTreeMap<String, String>,
+}
+
+/// A named zoom range and tippecanoe options, stored as `[[tile_presets]]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TilePreset {
+    pub name: String,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+/// Directory holding the config file (e.g. ~/.config/ftool)
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("ftool"))
+}
+
+/// Directory where ftool keeps its state between launches (e.g. ~/.local/share/ftool)
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("ftool"))
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|d| d.join(CONFIG_FILE))
+}
+
+/// Makes `config` available to the rest of the program through `get()`
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// The config loaded at startup, or the defaults if none was loaded
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+impl Config {
+    /// Reads the config file; a missing file yields the defaults
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid config file {}: {}", path.display(), e))?;
+        if let Some(event) = config.hooks.keys().find(|k| !HookEvent::ALL.iter().any(|e| e.key() == k.as_str())) {
+            let known: Vec<&str> = HookEvent::ALL.iter().map(|e| e.key()).collect();
+            anyhow::bail!("Invalid config file {}: unknown hook '{}' (expected {})", path.display(), event, known.join(", "));
+        }
+        Ok(config)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let dir = config_dir().ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+        std::fs::create_dir_all(&dir)?;
+        backup::write_atomic(&dir.join(CONFIG_FILE), toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.readonly.unwrap_or(false)
+    }
+
+    pub fn is_permanent_delete(&self) -> bool {
+        self.permanent_delete.unwrap_or(false)
+    }
+
+    pub fn keeps_http_bodies(&self) -> bool {
+        self.http_history_bodies.unwrap_or(true)
+    }
+
+    pub fn large_json_mb(&self) -> u64 {
+        self.large_json_mb.unwrap_or(DEFAULT_LARGE_JSON_MB)
+    }
+
+    pub fn large_csv_rows(&self) -> u64 {
+        self.large_csv_rows.unwrap_or(DEFAULT_LARGE_CSV_ROWS)
+    }
+
+    pub fn timezone(&self) -> &str {
+        self.timezone.as_deref().unwrap_or("utc")
+    }
+
+    /// The Parquet key to decrypt and encrypt with: `parquet_key` looked up in
+    /// `[parquet_keys]`, or taken as the key when it names none
+    pub fn parquet_key(&self) -> Option<&str> {
+        let key = self.parquet_key.as_deref()?;
+        Some(self.parquet_keys.get(key).map_or(key, String::as_str))
+    }
+
+    /// Returns the value of `key` as a string, or None if it isn't set
+    pub fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(match key {
+            "theme" => self.theme.clone(),
+            "default_convert_format" => self.default_convert_format.clone(),
+            "tippecanoe_path" => self.tippecanoe_path.as_ref().map(|p| p.display().to_string()),
+            "s3_endpoint" => self.s3_endpoint.clone(),
+            "editor" => self.editor.clone(),
+            "memory_limit" => self.memory_limit.clone(),
+            "threads" => self.threads.map(|n| n.to_string()),
+            "temp_directory" => self.temp_directory.as_ref().map(|p| p.display().to_string()),
+            "readonly" => self.readonly.map(|b| b.to_string()),
+            "permanent_delete" => self.permanent_delete.map(|b| b.to_string()),
+            "http_history_bodies" => self.http_history_bodies.map(|b| b.to_string()),
+            "large_json_mb" => self.large_json_mb.map(|n| n.to_string()),
+            "large_csv_rows" => self.large_csv_rows.map(|n| n.to_string()),
+            "timezone" => self.timezone.clone(),
+            "parquet_key" => self.parquet_key.clone(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Validates and stores `value` under `key`
+    pub fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        match key {
+            "theme" => {
+                if !THEMES.contains(&value) {
+                    anyhow::bail!("theme must be one of: {}", THEMES.join(", "));
+                }
+                self.theme = Some(value.to_string());
+            }
+            "default_convert_format" => {
+                if value != "csv" && value != "parquet" {
+                    anyhow::bail!("default_convert_format must be csv or parquet");
+                }
+                self.default_convert_format = Some(value.to_string());
+            }
+            "tippecanoe_path" => self.tippecanoe_path = Some(PathBuf::from(value)),
+            "s3_endpoint" => self.s3_endpoint = Some(value.to_string()),
+            "editor" => self.editor = Some(value.to_string()),
+            "memory_limit" => self.memory_limit = Some(parse_memory_limit(value)?),
+            "threads" => self.threads = Some(parse_threads(value)?),
+            "temp_directory" => self.temp_directory = Some(PathBuf::from(value)),
+            "readonly" => {
+                let readonly = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("readonly must be true or false"))?;
+                self.readonly = Some(readonly);
+            }
+            "permanent_delete" => {
+                let permanent = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("permanent_delete must be true or false"))?;
+                self.permanent_delete = Some(permanent);
+            }
+            "http_history_bodies" => {
+                let keep = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("http_history_bodies must be true or false"))?;
+                self.http_history_bodies = Some(keep);
+            }
+            "large_json_mb" => {
+                let mb = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("large_json_mb must be a number of megabytes (0 to never ask)"))?;
+                self.large_json_mb = Some(mb);
+            }
+            "large_csv_rows" => {
+                let rows = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("large_csv_rows must be a number of rows (0 to never ask)"))?;
+                self.large_csv_rows = Some(rows);
+            }
+            "timezone" => self.timezone = Some(parse_timezone(value)?),
+            "parquet_key" => self.parquet_key = Some(parse_parquet_key(value)?),
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// The target of alias `name`, or `name` itself when it isn't an alias
+    pub fn resolve(&self, name: &str) -> String {
+        self.aliases.get(name).cloned().unwrap_or_else(|| name.to_string())
+    }
+
+    /// Adds or replaces an alias. Local paths are stored absolute and must
+    /// exist; globs and URLs are stored as given.
+    pub fn add_alias(&mut self, name: &str, target: &str) -> anyhow::Result<()> {
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+            anyhow::bail!("alias names may only contain letters, digits, '_' and '-'");
+        }
+        let is_pattern = target.contains("://") || target.contains(['*', '?', '[']);
+        let target = if is_pattern {
+            target.to_string()
+        } else {
+            Path::new(target)
+                .canonicalize()
+                .map_err(|e| anyhow::anyhow!("{}: {}", target, e))?
+                .to_string_lossy()
+                .to_string()
+        };
+        self.aliases.insert(name.to_string(), target);
+        Ok(())
+    }
+
+    /// Adds a tile preset, replacing the saved one of the same name
+    pub fn save_tile_preset(&mut self, preset: TilePreset) {
+        match self.tile_presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.tile_presets.push(preset),
+        }
+    }
+
+    pub fn remove_alias(&mut self, name: &str) -> anyhow::Result<()> {
+        if self.aliases.remove(name).is_none() {
+            anyhow::bail!("No alias named '{}'", name);
+        }
+        Ok(())
+    }
+
+    /// All known keys with their current values, in a stable order
+    pub fn entries(&self) -> Vec<(&'static str, Option<String>)> {
+        KEYS.iter()
+            .map(|key| (*key, self.get(key).unwrap_or(None)))
+            .collect()
+    }
+}
+
+/// Accepts DuckDB memory sizes such as `512MB`, `2GiB` or `75%`
+pub fn parse_memory_limit(value: &str) -> anyhow::Result<String> {
+    let value = value.trim();
+    let digits = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits);
+    let unit_ok = ["B", "KB", "MB", "GB", "TB", "KIB", "MIB", "GIB", "TIB", "%"]
+        .contains(&unit.trim().to_ascii_uppercase().as_str());
+    if number.parse::<f64>().is_err() || !unit_ok {
+        anyhow::bail!("memory_limit must be a size like 512MB, 2GB or 50%");
+    }
+    Ok(value.to_string())
+}
+
+pub fn parse_threads(value: &str) -> anyhow::Result<usize> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => anyhow::bail!("threads must be a positive number"),
+    }
+}
+
+/// Accepts `utc`, `local` or something shaped like an IANA zone name
+/// (`Europe/Paris`, `America/Argentina/Buenos_Aires`, `Etc/GMT+3`); DuckDB
+/// checks the name itself when a file is opened
+pub fn parse_timezone(value: &str) -> anyhow::Result<String> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+    if lower == "utc" || lower == "local" {
+        return Ok(lower);
+    }
+    let shaped = !value.is_empty()
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+    if !shaped {
+        anyhow::bail!("timezone must be utc, local or an IANA zone name such as Europe/Paris");
+    }
+    Ok(value.to_string())
+}
+
+/// Accepts a keyring name or a key; DuckDB checks the key's length when it's added
+pub fn parse_parquet_key(value: &str) -> anyhow::Result<String> {
+    if value.is_empty() || value.contains('\0') {
+        anyhow::bail!("parquet_key must be a name from [parquet_keys] or a 16, 24 or 32 byte key");
+    }
+    Ok(value.to_string())
+}
+
+fn unknown_key(key: &str) -> anyhow::Error {
+    anyhow::anyhow!("Unknown config key '{}' (expected one of: {})", key, KEYS.join(", "))
+}
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use thiserror::Error;
+
+use crate::commands::duckdb_inspector::DuckDbError;
+use crate::commands::file::FileError;
+use crate::output::Style;
+
+/// Process exit codes, so scripts can tell failure categories apart
+pub mod exit_code {
+    pub const FAILURE: i32 = 1;
+    pub const NOT_FOUND: i32 = 2;
+    pub const BAD_FORMAT: i32 = 3;
+    pub const QUERY_ERROR: i32 = 4;
+}
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Switches `exit_with` to structured JSON output (`--json-errors`)
+pub fn set_json_errors(enabled: bool) {
+    JSON_ERRORS.store(enabled, Ordering::Relaxed);
+}
+
+#[derive(Debug, Error)]
+pub enum FtoolError {
+    #[error(transparent)]
+    File(#[from] FileError),
+    #[error(transparent)]
+    DuckDb(#[from] DuckDbError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Usage(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl FtoolError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FtoolError::File(FileError::NotFound(_))
+            | FtoolError::DuckDb(DuckDbError::FileNotFound(_)) => exit_code::NOT_FOUND,
+            FtoolError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => exit_code::NOT_FOUND,
+            FtoolError::File(FileError::InvalidPath(_))
+            | FtoolError::DuckDb(DuckDbError::InvalidFileFormat(_)) => exit_code::BAD_FORMAT,
+            FtoolError::DuckDb(
+                DuckDbError::QueryError(_)
+                | DuckDbError::InvalidColumn(_)
+                | DuckDbError::DatabaseError(_),
+            ) => exit_code::QUERY_ERROR,
+            _ => exit_code::FAILURE,
+        }
+    }
+
+    /// Short machine-readable category used in `--json-errors` output
+    pub fn kind(&self) -> &'static str {
+        match self.exit_code() {
+            exit_code::NOT_FOUND => "not_found",
+            exit_code::BAD_FORMAT => "bad_format",
+            exit_code::QUERY_ERROR => "query_error",
+            _ => match self {
+                FtoolError::File(FileError::PermissionDenied(_)) => "permission_denied",
+                FtoolError::Io(_) => "io",
+                FtoolError::Usage(_) => "usage",
+                _ => "error",
+            },
+        }
+    }
+
+    /// The file the error refers to, when the variant carries one
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            FtoolError::File(FileError::NotFound(path) | FileError::PermissionDenied(path))
+            | FtoolError::DuckDb(DuckDbError::FileNotFound(path)) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for FtoolError {
+    fn from(msg: String) -> Self {
+        FtoolError::Usage(msg)
+    }
+}
+
+/// Prints `context: error` to stderr (or a JSON object with `--json-errors`)
+/// and exits with the error's category code
+pub fn exit_with(context: &str, error: impl Into<FtoolError>) -> ! {
+    let error = error.into();
+    let code = error.exit_code();
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        let json = serde_json::json!({
+            "code": code,
+            "kind": error.kind(),
+            "message": format!("{}: {}", context, error),
+            "path": error.path(),
+        });
+        eprintln!("{}", json);
+    } else {
+        let out = crate::output::get();
+        eprintln!("{}: {}", out.paint_err(context, Style::Error), error);
+    }
+    std::process::exit(code);
+}
@@ -0,0 +1,49 @@
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tracing::Level;
+
+use crate::config;
+
+/// Installs the global tracing subscriber.
+///
+/// Nothing is logged unless `--verbose` or `--log-file` is given. Without a log
+/// file, verbose output goes to stderr for CLI commands, but to
+/// `<data dir>/ftool/ftool.log` for the TUI since stderr would draw over the screen.
+/// `color` controls ANSI styling of the stderr output.
+pub fn init(
+    verbose: bool,
+    log_file: Option<PathBuf>,
+    tui: bool,
+    color: bool,
+) -> anyhow::Result<()> {
+    if !verbose && log_file.is_none() {
+        return Ok(());
+    }
+
+    let level = if verbose { Level::DEBUG } else { Level::INFO };
+    let builder = tracing_subscriber::fmt().with_max_level(level);
+
+    let log_file = match log_file {
+        Some(path) => Some(path),
+        None if tui => Some(default_log_file()?),
+        None => None,
+    };
+
+    match log_file {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            builder.with_ansi(false).with_writer(Mutex::new(file)).init();
+        }
+        None => builder.with_ansi(color).with_writer(std::io::stderr).init(),
+    }
+
+    Ok(())
+}
+
+fn default_log_file() -> anyhow::Result<PathBuf> {
+    let dir = config::data_dir().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("ftool.log"))
+}
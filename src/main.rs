@@ -1,114 +1,1037 @@
 mod cli;
 mod commands;
+mod config;
+mod error;
+mod logging;
+mod output;
 mod tui;
 
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{AliasAction, Cli, Commands, ConfigAction, DecodeAction, DecodeArgs, GenAction, IndexAction};
+use commands::report::ReportFormat;
+use commands::duckdb_inspector::ExportMode;
+use commands::file::{FileAction, FileFormat};
+use commands::row_diff::RowChange;
+use config::Config;
+use error::exit_with;
+use output::Style;
 
 fn main() {
     let cli = Cli::parse();
+    error::set_json_errors(cli.json_errors);
+    output::init(cli.quiet, cli.no_color);
+    let out = output::get();
+
+    let is_tui = matches!(
+        cli.command,
+        None | Some(Commands::Tui(_))
+            | Some(Commands::Json(_))
+            | Some(Commands::Decode(DecodeArgs { action: DecodeAction::Jwt { tui: true, .. } }))
+    );
+    if let Err(e) = logging::init(cli.verbose, cli.log_file, is_tui, out.color_stderr()) {
+        exit_with("Error initializing logging", e);
+    }
+
+    // Falling back to the defaults would quietly turn `readonly` off
+    let mut config = match Config::load() {
+        Ok(c) => c,
+        Err(e) => exit_with("Error loading the config", e),
+    };
+    // Command-line DuckDB settings win over the config file for this run only
+    config.memory_limit = cli.memory_limit.or(config.memory_limit);
+    config.threads = cli.threads.or(config.threads);
+    config.temp_directory = cli.temp_dir.or(config.temp_directory);
+    config.parquet_key = cli.parquet_key.or(config.parquet_key);
+    if cli.readonly {
+        config.readonly = Some(true);
+    }
+    if let Some(Commands::Tui(args)) = &cli.command
+        && args.permanent
+    {
+        config.permanent_delete = Some(true);
+    }
+    config::init(config.clone());
+
+    if config.is_readonly()
+        && let Some(action) = cli.command.as_ref().and_then(writing_action)
+    {
+        exit_with("Read-only mode", format!("{} is disabled by --readonly (or `readonly = true` in the config)", action));
+    }
 
     match cli.command {
         None => {
             // No subcommand -> launch TUI
-            if let Err(e) = tui::run(None) {
-                eprintln!("TUI error: {}", e);
-                std::process::exit(1);
+            if let Err(e) = tui::run(None, None) {
+                exit_with("TUI error", e);
             }
         }
         Some(Commands::File(args)) => {
             if let Err(e) = args.validate() {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+                exit_with("Error", e);
             }
 
-            let file = commands::File::new(args.file);
+            let sizes = if args.human && !args.bytes {
+                commands::units::SizeFormat::Human
+            } else {
+                commands::units::SizeFormat::Bytes
+            };
+            let paths = match commands::file::expand_paths(&args.files) {
+                Ok(paths) => paths,
+                Err(e) => exit_with("Error", e),
+            };
+
+            if args.recode.is_some() || args.eol.is_some() {
+                let [path] = paths.as_slice() else {
+                    exit_with("Error", "--recode and --eol convert a single file".to_string());
+                };
+                let encoding = |label: &Option<String>| match label.as_deref().map(commands::recode::encoding_for).transpose() {
+                    Ok(encoding) => encoding,
+                    Err(e) => exit_with("Error", e),
+                };
+                let (from, to) = (encoding(&args.from), encoding(&args.recode));
+                match commands::recode::recode(std::path::Path::new(path), args.output.as_deref(), from, to, args.eol) {
+                    // Status goes to stderr when the converted text is on stdout
+                    Ok(summary) if args.output.is_some() => out.info(summary.describe()),
+                    Ok(summary) => out.progress(summary.describe()),
+                    Err(e) => exit_with("Error converting file", e),
+                }
+                return;
+            }
+
+            // Several files (or JSON output): one row or object per file
+            if paths.len() > 1 || args.format == FileFormat::Json {
+                let action = match (args.head, args.tail) {
+                    (Some(n), _) => FileAction::Head(n),
+                    (None, Some(n)) => FileAction::Tail(n),
+                    _ if args.info => FileAction::Info,
+                    _ if args.lines => FileAction::Lines,
+                    _ => FileAction::Size,
+                };
+                let reports: Vec<_> = paths.into_iter().map(|p| commands::File::new(p).report(action)).collect();
+                match args.format {
+                    FileFormat::Json => out.data(serde_json::to_string_pretty(&reports).unwrap_or_default()),
+                    FileFormat::Text => out.text(&commands::file::render_reports(&reports, action, sizes)),
+                }
+                if reports.iter().any(|r| r.error.is_some()) {
+                    std::process::exit(error::exit_code::FAILURE);
+                }
+                return;
+            }
+
+            let file = commands::File::new(paths.into_iter().next().unwrap_or_default());
 
             if args.info {
-                match file.info() {
-                    Ok(result) => println!("{}", result),
-                    Err(e) => eprintln!("Error: {}", e),
+                match file.info(sizes) {
+                    Ok(result) => out.data(result),
+                    Err(e) => exit_with("Error", e),
                 }
             }
 
             if let Some(n) = args.head {
                 match file.head(n) {
-                    Ok(result) => println!("{}", result),
-                    Err(e) => eprintln!("Error: {}", e),
+                    Ok(result) => out.data(result),
+                    Err(e) => exit_with("Error", e),
+                }
+            }
+
+            if let Some(n) = args.tail {
+                match file.tail(n) {
+                    Ok(result) => out.data(result),
+                    Err(e) => exit_with("Error", e),
                 }
             }
 
             if args.size {
-                match file.size() {
-                    Ok(result) => println!("{}", result),
-                    Err(e) => eprintln!("Error: {}", e),
+                match file.size(sizes) {
+                    Ok(result) => out.data(result),
+                    Err(e) => exit_with("Error", e),
                 }
             }
 
             if args.lines {
                 match file.lines() {
-                    Ok(result) => println!("{}", result),
-                    Err(e) => eprintln!("Error: {}", e),
+                    Ok(result) => out.data(result),
+                    Err(e) => exit_with("Error", e),
                 }
             }
         }
 
-        Some(Commands::Inspect(args)) => {
+        Some(Commands::Inspect(mut args)) => {
+            args.file = config.resolve(&args.file);
             if let Err(e) = args.validate() {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+                exit_with("Error", e);
             }
 
-            let inspector = match commands::DuckDbInspector::new(args.file) {
+            let inspector = match commands::DuckDbInspector::new(args.file.clone())
+                .and_then(|i| i.with_types(&args.types))
+                .and_then(|i| i.with_encrypted_output(args.encrypt))
+            {
                 Ok(i) => i,
-                Err(e) => {
-                    eprintln!("Error initializing DuckDB: {}", e);
-                    return;
-                }
+                Err(e) => exit_with("Error initializing DuckDB", e),
             };
 
             if args.desc {
                 match inspector.schema() {
                     Ok(schema) => {
                         for (name, ty) in schema {
-                            println!("{:<20} {}", name, ty);
+                            out.data(format!("{:<20} {}", name, ty));
                         }
                     }
-                    Err(e) => eprintln!("Error reading schema: {}", e),
+                    Err(e) => exit_with("Error reading schema", e),
                 }
             }
 
             if args.row_count {
-                match inspector.row_count() {
-                    Ok(count) => println!("Row count: {}", count),
-                    Err(e) => eprintln!("Error counting rows: {}", e),
+                let count = if args.estimate {
+                    inspector.row_count_estimate()
+                } else {
+                    inspector.row_count().map(commands::duckdb_inspector::RowCount::Exact)
+                };
+                match count {
+                    Ok(count) => out.data(format!("Row count: {}", count)),
+                    Err(e) => exit_with("Error counting rows", e),
                 }
             }
 
             if let Some(column) = args.null_count {
                 match inspector.null_count(&column) {
-                    Ok(count) => println!("Null values in column '{}': {}", column, count),
-                    Err(e) => eprintln!("Error counting nulls: {}", e),
+                    Ok(count) => {
+                        out.data(format!("Null values in column '{}': {}", column, count))
+                    }
+                    Err(e) => exit_with("Error counting nulls", e),
+                }
+            }
+
+            if args.profile {
+                let profile = inspector.row_count().and_then(|row_count| {
+                    Ok(commands::profile::FileProfile {
+                        file: args.file.clone(),
+                        row_count,
+                        columns: inspector.profile()?,
+                    })
+                });
+                match profile {
+                    Ok(profile) => out.text(&profile.render(args.format)),
+                    Err(e) => exit_with("Error profiling file", e),
                 }
             }
             
-            if let Some(format) = args.convert {
-                match inspector.convert(&format) {
-                    Ok(path) => println!("File converted to {}", path),
-                    Err(e) => eprintln!("Error converting file: {}", e),
+            if let Some(rows) = args.sample {
+                let output = args.output.unwrap_or_else(|| {
+                    let path = std::path::Path::new(&args.file);
+                    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    let ext = path.extension().unwrap_or_default().to_string_lossy();
+                    path.with_file_name(format!("{}_sample.{}", stem, ext))
+                        .to_string_lossy()
+                        .to_string()
+                });
+                match inspector.sample(rows, args.stratify_by.as_deref(), &output) {
+                    Ok(written) => {
+                        out.info(format!("Wrote {} sampled rows to {}", written, output))
+                    }
+                    Err(e) => exit_with("Error sampling file", e),
+                }
+            }
+
+            if args.checksum {
+                match inspector.dataset_digest() {
+                    Ok(digest) => out.data(format!("Digest: {}", digest)),
+                    Err(e) => exit_with("Error computing checksum", e),
+                }
+                if let Some(output) = &args.row_hashes {
+                    match inspector.write_row_hashes(output) {
+                        Ok(rows) => out.info(format!("Wrote {} row hashes to {}", rows, output)),
+                        Err(e) => exit_with("Error writing row hashes", e),
+                    }
+                }
+            }
+
+            if args.lint {
+                if !args.file.ends_with(".csv") {
+                    exit_with("Error", "--lint only applies to CSV files".to_string());
+                }
+                match commands::csv_lint::lint(std::path::Path::new(&args.file)) {
+                    Ok(report) => {
+                        for line in report.report() {
+                            let line = if line.starts_with('!') { out.paint(line, Style::Error) } else { line };
+                            out.data(line);
+                        }
+                        if !report.is_clean() {
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => exit_with("Error linting file", e),
+                }
+            }
+
+            if let Some(other) = args.compare_schema {
+                let schemas = inspector.schema().and_then(|old| {
+                    let new = commands::DuckDbInspector::new(other.clone())?.schema()?;
+                    Ok((old, new))
+                });
+                match schemas {
+                    Ok((old, new)) => {
+                        let diff = commands::schema_diff::SchemaDiff::compare(&old, &new);
+                        for line in diff.report() {
+                            let incompatible =
+                                line.starts_with('!') || line.starts_with("Incompatible");
+                            let line = if incompatible {
+                                out.paint(line, Style::Error)
+                            } else if line.starts_with("Compatible") {
+                                out.paint(line, Style::Success)
+                            } else {
+                                line
+                            };
+                            out.data(line);
+                        }
+                        if !diff.is_compatible() {
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => exit_with("Error reading schema", e),
+                }
+            }
+
+            if args.convert.is_some() || args.convert_default {
+                let Some(format) = args.convert.or_else(|| config.default_convert_format.clone()) else {
+                    exit_with(
+                        "Error",
+                        "no target format given and no default_convert_format set".to_string(),
+                    );
+                };
+                let transform = commands::transform::Transform {
+                    select: args.select,
+                    rename: args.rename,
+                    cast: args.cast,
+                    drop: args.drop,
+                    mask: args.mask,
+                    computed: args.compute,
+                };
+                if format != "xlsx" && (args.sheet_rows.is_some() || args.summary_sheet) {
+                    exit_with("Error", "--sheet-rows and --summary-sheet only apply to xlsx".to_string());
+                }
+                if args.encrypt && format != "parquet" {
+                    exit_with("Error", "--encrypt only applies to parquet".to_string());
+                }
+                let into = match (args.append, args.upsert, args.key) {
+                    (Some(target), _, _) => Some((target, ExportMode::Append)),
+                    (None, Some(target), Some(key)) => Some((target, ExportMode::Upsert { key })),
+                    _ => None,
+                };
+
+                if format == "xlsx" {
+                    if !args.partition_by.is_empty() || into.is_some() || args.verify {
+                        exit_with(
+                            "Error",
+                            "--partition-by, --append, --upsert and --verify don't apply to xlsx".to_string(),
+                        );
+                    }
+                    if args.dry_run {
+                        match inspector.xlsx_plan(&transform) {
+                            Ok((path, query)) => {
+                                out.data(format!("{};", query));
+                                out.data(format!("-- the rows are written to {}", path));
+                            }
+                            Err(e) => exit_with("Error planning conversion", e),
+                        }
+                        return;
+                    }
+                    let options = commands::duckdb_inspector::ExcelOptions {
+                        sheet_rows: args.sheet_rows.unwrap_or(commands::duckdb_inspector::MAX_SHEET_ROWS),
+                        summary: args.summary_sheet,
+                    };
+                    match inspector.export_xlsx(&transform, &options) {
+                        Ok(path) => out.info(format!("File converted to {}", path)),
+                        Err(e) => exit_with("Error converting file", e),
+                    }
+                } else if let Some((target, mode)) = into {
+                    if !commands::duckdb_inspector::is_duckdb_target(&target) && !target.ends_with(&format!(".{}", format)) {
+                        exit_with("Error", format!("{} is not a .{} file", target, format));
+                    }
+                    match inspector.export_into(&target, &transform, &mode, args.dry_run) {
+                        Ok(summary) => {
+                            for conflict in &summary.type_conflicts {
+                                out.progress(format!("{}: {}", out.paint_err("Warning", Style::Warning), conflict));
+                            }
+                            if args.dry_run {
+                                out.data(format!("{};", summary.query));
+                                out.info(format!(
+                                    "Would write {} new rows to {} ({} replaced)",
+                                    summary.inserted, target, summary.replaced
+                                ));
+                                return;
+                            }
+                            out.info(format!(
+                                "Wrote {} new rows to {} ({} replaced)",
+                                summary.inserted, target, summary.replaced
+                            ));
+                        }
+                        Err(e) => exit_with("Error exporting file", e),
+                    }
+                } else if args.dry_run {
+                    match inspector.convert_query(&format, &transform, &args.partition_by) {
+                        Ok(Some(plan)) => {
+                            out.data(format!("{};", plan.statement(&plan.target)));
+                            out.info(format!("Would write {}", plan.target));
+                        }
+                        Ok(None) => out.info(format!("{} is already {}; nothing to convert", args.file, format)),
+                        Err(e) => exit_with("Error planning conversion", e),
+                    }
+                } else {
+                    let path = match inspector.convert_with(&format, &transform, &args.partition_by) {
+                        Ok(path) => path,
+                        Err(e) => exit_with("Error converting file", e),
+                    };
+                    out.info(format!("File converted to {}", path));
+
+                    if args.verify {
+                        let verification = match inspector.verify_conversion(&path) {
+                            Ok(v) => v,
+                            Err(e) => exit_with("Error verifying conversion", e),
+                        };
+                        for line in verification.report() {
+                            let line = if line.starts_with('!') || line == "Verification failed" {
+                                out.paint(line, Style::Error)
+                            } else if line == "Verification passed" {
+                                out.paint(line, Style::Success)
+                            } else {
+                                line
+                            };
+                            out.data(line);
+                        }
+                        if !verification.passed() {
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+        }
+        Some(Commands::Report(args)) => {
+            let report = match commands::report::Report::build(&config.resolve(&args.file)) {
+                Ok(r) => r,
+                Err(e) => exit_with("Error building report", e),
+            };
+            let format = args.format.unwrap_or_else(|| match &args.output {
+                Some(path) => ReportFormat::from_path(path),
+                None => ReportFormat::Markdown,
+            });
+            let text = report.render(format);
+            match args.output {
+                Some(path) => match commands::backup::write(&path, text) {
+                    Ok(()) => out.info(format!("Report written to {}", path.display())),
+                    Err(e) => exit_with("Error writing report", e),
+                },
+                None => out.text(&text),
+            }
+        }
+        Some(Commands::Note(args)) => {
+            let path = config.resolve(&args.file);
+            let path = std::path::Path::new(&path);
+            if args.text.is_none() && !args.clear && !args.edit {
+                out.text(&commands::notes::for_file(path).render());
+                return;
+            }
+            let target = match &args.column {
+                Some(column) => format!("column {} of {}", column, path.display()),
+                None => path.display().to_string(),
+            };
+            let saved = if args.edit {
+                commands::notes::edit(path, args.column.as_deref())
+            } else {
+                commands::notes::set(path, args.column.as_deref(), args.text.as_deref())
+            };
+            match saved {
+                Ok(()) if args.clear => out.info(format!("Removed the note on {}", target)),
+                Ok(()) => out.info(format!("Saved the note on {}", target)),
+                Err(e) => exit_with("Error saving the note", e),
+            }
+        }
+        Some(Commands::Undo(args)) => {
+            if args.list {
+                let history = commands::backup::history();
+                if history.is_empty() {
+                    out.info("No recorded writes");
+                }
+                for record in history.iter().rev() {
+                    out.data(record.describe());
+                }
+                return;
+            }
+            match commands::backup::undo_last() {
+                Ok(Some(record)) if record.backup.is_some() => {
+                    out.info(format!("Restored the previous {}", record.path.display()))
+                }
+                Ok(Some(record)) => out.info(format!("Deleted {}", record.path.display())),
+                Ok(None) => out.info("Nothing to undo"),
+                Err(e) => exit_with("Error undoing the last write", e),
+            }
+        }
+        Some(Commands::Split(args)) => {
+            if let Err(e) = args.validate() {
+                exit_with("Error", e);
+            }
+
+            let out_dir = args.out_dir.clone().unwrap_or_else(|| {
+                let path = std::path::Path::new(&args.file);
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                path.with_file_name(format!("{}_split", stem))
+            });
+            let inspector = match commands::DuckDbInspector::new(args.file.clone()) {
+                Ok(i) => i,
+                Err(e) => exit_with("Error initializing DuckDB", e),
+            };
+
+            let result = inspector.split(&args.mode(), &out_dir, |done, total, path| {
+                out.progress(format!("[{}/{}] {}", done, total, path));
+            });
+            match result {
+                Ok(files) => {
+                    out.info(format!("Wrote {} file(s) to {}", files.len(), out_dir.display()))
+                }
+                Err(e) => exit_with("Error splitting file", e),
+            }
+        }
+        Some(Commands::ConvertDir(args)) => {
+            let jobs = args.jobs.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, |n| n.get()).min(4)
+            });
+            let options = commands::convert_dir::BatchOptions {
+                from: args.from,
+                to: args.to,
+                recursive: args.recursive,
+                jobs,
+                skip_existing: args.skip_existing,
+            };
+            let report = commands::convert_dir::convert_dir(&args.dir, &options, |done, total, result| {
+                out.progress(format!("[{}/{}] {}", done, total, result.describe()));
+            });
+            match report {
+                Ok(report) => {
+                    out.text(&report.render());
+                    if report.failed() > 0 || report.hook_failed() > 0 {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => exit_with("Error converting directory", e),
+            }
+        }
+        Some(Commands::Run(args)) => {
+            let pipeline = match commands::pipeline::Pipeline::from_file(&args.pipeline) {
+                Ok(p) => p,
+                Err(e) => exit_with("Error reading pipeline", e),
+            };
+            if args.dry_run {
+                for line in pipeline.plan() {
+                    out.data(line);
+                }
+                return;
+            }
+            if config.is_readonly() && pipeline.writes() {
+                exit_with(
+                    "Read-only mode",
+                    "Pipelines that convert, export or tile are disabled by --readonly (or `readonly = true` in the config)"
+                        .to_string(),
+                );
+            }
+            match pipeline.run(|line| out.progress(line)) {
+                Ok(()) => out.info(format!("Pipeline finished: {} step(s)", pipeline.steps.len())),
+                Err(e) => exit_with("Error running pipeline", e),
+            }
+        }
+        Some(Commands::Merge(args)) => {
+            let plan = match commands::merge::MergePlan::new(&args.inputs) {
+                Ok(p) => p,
+                Err(e) => exit_with("Error reading inputs", e),
+            };
+
+            if !plan.conflicts.is_empty() {
+                out.progress(plan.conflict_report().trim_end());
+                if args.strict {
+                    exit_with(
+                        "Error",
+                        "type conflicts found (remove --strict to let DuckDB widen the types)"
+                            .to_string(),
+                    );
+                }
+            }
+
+            match plan.write(&args.output) {
+                Ok(rows) => out.info(format!(
+                    "Merged {} files ({} columns, {} rows) into {}",
+                    plan.inputs.len(),
+                    plan.columns.len(),
+                    rows,
+                    args.output
+                )),
+                Err(e) => exit_with("Error merging files", e),
+            }
+        }
+        Some(Commands::Diff(args)) => {
+            let (old, new) = (config.resolve(&args.old), config.resolve(&args.new));
+            let diff = match commands::row_diff::RowDiff::new(&old, &new, &args.key) {
+                Ok(d) => d,
+                Err(e) => exit_with("Error reading inputs", e),
+            };
+            if !diff.skipped.is_empty() {
+                out.progress(format!(
+                    "{}: not comparing columns missing from one file: {}",
+                    out.paint_err("Warning", Style::Warning),
+                    diff.skipped.join(", ")
+                ));
+            }
+
+            if let Some(output) = args.output {
+                match diff.write_csv(&output) {
+                    Ok(rows) => out.info(format!("Wrote {} differences to {}", rows, output)),
+                    Err(e) => exit_with("Error writing diff", e),
+                }
+            } else {
+                // Printed as they are read, so large diffs start showing immediately
+                let result = diff.for_each(|entry| {
+                    let style = match entry.change {
+                        RowChange::Added => Style::Success,
+                        RowChange::Removed => Style::Error,
+                        RowChange::Changed(_) => Style::Warning,
+                    };
+                    out.data(out.paint(entry.line(), style));
+                });
+                match result {
+                    Ok(counts) => out.info(format!(
+                        "{} added, {} removed, {} changed",
+                        counts.added, counts.removed, counts.changed
+                    )),
+                    Err(e) => exit_with("Error comparing files", e),
+                }
+            }
+        }
+        Some(Commands::Generate(args)) => {
+            if let Some(seed) = args.seed
+                && !(-1.0..=1.0).contains(&seed)
+            {
+                exit_with("Error", "--seed must be between -1 and 1".to_string());
+            }
+            let schema = match commands::generate::GenerateSchema::from_file(&args.schema) {
+                Ok(s) => s,
+                Err(e) => exit_with("Error", e),
+            };
+            match schema.write(args.rows, &args.output, args.seed) {
+                Ok(()) => out.info(format!("Generated {} rows into {}", args.rows, args.output)),
+                Err(e) => exit_with("Error generating data", e),
+            }
+        }
+        Some(Commands::Tile(args)) => {
+            let mut plan = match commands::tile::TilePlan::new(&args.input) {
+                Ok(p) => p,
+                Err(e) => exit_with("Error reading input", e),
+            };
+            if let Some(output) = args.output {
+                plan.output = output;
+            }
+            plan.layer = args.layer;
+            plan.extra = args.extra;
+            if let Some(name) = args.preset {
+                match commands::tile::find_preset(&name) {
+                    Ok(preset) => plan.apply(&preset),
+                    Err(e) => exit_with("Error", e),
+                }
+            }
+            plan.min_zoom = args.min_zoom.or(plan.min_zoom);
+            plan.max_zoom = args.max_zoom.or(plan.max_zoom);
+            if args.dry_run {
+                out.data(plan.command_line());
+                return;
+            }
+            out.progress(plan.command_line());
+            if let Err(e) = plan.run() {
+                exit_with("Error tiling file", e);
+            }
+            out.info(format!("Tiles written to {}", plan.output.display()));
+            if plan.writes_pmtiles() {
+                match plan.input_features().and_then(|n| commands::pmtiles::check(&plan.output, n)) {
+                    Ok(check) => {
+                        for layer in &check.layers {
+                            let count = |n: Option<u64>| n.map_or("?".to_string(), |n| n.to_string());
+                            let dropped = layer.dropped().map_or("?".to_string(), |d| format!("{:.1}%", d));
+                            let line = format!(
+                                "  {}: {} input, {} read, {} at z{}, {} dropped",
+                                layer.layer,
+                                layer.input,
+                                count(layer.read),
+                                count(layer.kept),
+                                check.max_zoom,
+                                dropped
+                            );
+                            out.info(if layer.is_lossy() { out.paint(line, Style::Warning) } else { line });
+                        }
+                        for note in &check.notes {
+                            out.progress(format!("{}: {}", out.paint_err("Note", Style::Warning), note));
+                        }
+                        if check.is_lossy() {
+                            out.progress(format!(
+                                "{}: tippecanoe dropped features; raise the max zoom or pass --no-feature-limit --no-tile-size-limit",
+                                out.paint_err("Warning", Style::Warning)
+                            ));
+                        }
+                    }
+                    Err(e) => out.progress(format!("{}: could not check the tiles: {:#}", out.paint_err("Warning", Style::Warning), e)),
+                }
+            }
+        }
+        Some(Commands::Serve(args)) => {
+            let server = match commands::serve::Server::new(&config.resolve(&args.file)) {
+                Ok(s) => s,
+                Err(e) => exit_with("Error opening file", e),
+            };
+            let listener = match std::net::TcpListener::bind((args.host.as_str(), args.port)) {
+                Ok(l) => l,
+                Err(e) => exit_with(&format!("Error listening on {}:{}", args.host, args.port), e),
+            };
+            out.info(format!(
+                "Serving {} on http://{}:{} (/schema, /rows, /stats); Ctrl+C to stop",
+                args.file, args.host, args.port
+            ));
+            if let Err(e) = server.run(listener, |line| out.progress(line)) {
+                exit_with("Server error", e);
+            }
+        }
+        Some(Commands::Load(args)) => {
+            let file = config.resolve(&args.file);
+            // --table may name a schema; a file name is the table name as is
+            let table = match &args.table {
+                Some(name) => match commands::postgres::TableName::parse(name) {
+                    Ok(t) => t,
+                    Err(e) => exit_with("Error", e),
+                },
+                None => commands::postgres::TableName::from_stem(
+                    &std::path::Path::new(&file).file_stem().unwrap_or_default().to_string_lossy(),
+                ),
+            };
+            let inspector = match commands::DuckDbInspector::new(file) {
+                Ok(i) => i,
+                Err(e) => exit_with("Error initializing DuckDB", e),
+            };
+            if args.dry_run {
+                let schema = match inspector.schema() {
+                    Ok(s) => s,
+                    Err(e) => exit_with("Error reading schema", e),
+                };
+                for statement in commands::postgres::load_ddl(&table, &schema, args.mode) {
+                    out.data(statement);
+                }
+                let target = commands::postgres::load_target(&table, args.mode);
+                out.data(format!("-- then the rows of {} are inserted into {} by column name", args.file, target.quoted()));
+                if let Some(statement) = commands::postgres::load_finish_ddl(&table, args.mode) {
+                    out.data(statement);
+                }
+            } else {
+                let Some(url) = args.postgres else {
+                    exit_with("Error", "--postgres is required (or use --dry-run to only print the SQL)".to_string());
+                };
+                out.progress(format!("Loading {} into {}...", args.file, table));
+                match inspector.load_postgres(&url, &table, args.mode) {
+                    Ok(rows) => out.info(format!("Loaded {} rows into {}", rows, table)),
+                    Err(e) => exit_with("Error loading file", e),
+                }
+            }
+        }
+        Some(Commands::Env(args)) => {
+            let read = |path: &std::path::Path| match commands::env::read_env_file(path) {
+                Ok(vars) => vars,
+                Err(e) => exit_with("Error reading env file", e),
+            };
+            let vars = match &args.file {
+                Some(path) => read(path),
+                None => commands::env::process_env(),
+            };
+            let search = args.search.as_deref().unwrap_or_default();
+            if let Some(other) = &args.diff {
+                let lines = commands::env::diff(&vars, &read(other));
+                let mut differ = false;
+                for line in &lines {
+                    if line.status() == commands::env::EnvStatus::Same {
+                        continue;
+                    }
+                    differ = true;
+                    if commands::env::matches(&line.key, "", search) {
+                        out.data(line.describe(args.reveal));
+                    }
+                }
+                out.info(commands::env::diff_summary(&lines));
+                if differ {
+                    std::process::exit(1);
+                }
+            } else {
+                for (key, value) in &vars {
+                    let shown = commands::env::display_value(key, value, args.reveal);
+                    if commands::env::matches(key, &shown, search) {
+                        out.data(format!("{}={}", key, shown));
+                    }
+                }
+            }
+        }
+        Some(Commands::Json(args)) if args.file.as_deref().is_some_and(commands::http::is_url) => {
+            let mut request = commands::http::HttpRequest::get(args.file.as_deref().unwrap_or_default());
+            for (name, value) in &args.headers {
+                request.header(name, value);
+            }
+            if let Some(credentials) = &args.user {
+                request.basic_auth(credentials);
+            }
+            if let Some(token) = &args.bearer {
+                request.bearer(token);
+            }
+            let request = match request.with_config_headers(&config.http_headers) {
+                Ok(r) => r,
+                Err(e) => exit_with("Error", e),
+            };
+            out.progress(format!("GET {}...", commands::http::display_url(&request.url)));
+            let response = match request.send() {
+                Ok(r) => r,
+                Err(e) => exit_with("Request failed", e),
+            };
+            let path = match commands::json_inspector::save_pasted(&response.body, "response") {
+                Ok(path) => path,
+                // An error page says more than "not JSON"; JSON error bodies still open
+                Err(_) if !response.is_success() => {
+                    let body = response.body.trim().lines().take(20).collect::<Vec<_>>().join("\n");
+                    exit_with(&format!("Request failed ({})", response.status_line()), body)
+                }
+                Err(e) => exit_with(&format!("Error reading the response ({})", response.status_line()), e),
+            };
+            if let Err(e) = tui::run_response(&path, response) {
+                exit_with("TUI error", e);
+            }
+        }
+        Some(Commands::Json(args)) => {
+            let path = match args.file {
+                Some(file) => config.resolve(&file),
+                None => {
+                    let text = match arboard::Clipboard::new().and_then(|mut c| c.get_text()) {
+                        Ok(t) => t,
+                        Err(e) => exit_with("Error reading the clipboard", anyhow::Error::from(e)),
+                    };
+                    match commands::json_inspector::save_pasted(&text, "clipboard") {
+                        Ok(path) => path.to_string_lossy().to_string(),
+                        Err(e) => exit_with("Error reading the clipboard", e),
+                    }
+                }
+            };
+            if let Err(e) = tui::run(Some(path), None) {
+                exit_with("TUI error", e);
+            }
+        }
+        Some(Commands::Decode(args)) => {
+            let (encoding, input, open) = match args.action {
+                DecodeAction::Base64 { input } => (commands::decode::Encoding::Base64, input, false),
+                DecodeAction::Url { input } => (commands::decode::Encoding::Url, input, false),
+                DecodeAction::Hex { input } => (commands::decode::Encoding::Hex, input, false),
+                DecodeAction::Jwt { input, tui } => (commands::decode::Encoding::Jwt, input, tui),
+            };
+            let text = match input.filter(|i| i != "-") {
+                Some(text) => text,
+                None => {
+                    let mut text = String::new();
+                    if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut text) {
+                        exit_with("Error reading stdin", e);
+                    }
+                    text
+                }
+            };
+            let decoded = match commands::decode::decode(encoding, &text) {
+                Ok(d) => d,
+                Err(e) => exit_with(&format!("Error decoding {}", encoding.label()), e),
+            };
+            match decoded {
+                commands::decode::Decoded::Jwt(_) if open => {
+                    let json = decoded.json().unwrap_or_default();
+                    let path = match commands::json_inspector::save_pasted(&json, "jwt") {
+                        Ok(path) => path,
+                        Err(e) => exit_with("Error saving the decoded JWT", e),
+                    };
+                    if let Err(e) = tui::run(Some(path.to_string_lossy().to_string()), None) {
+                        exit_with("TUI error", e);
+                    }
+                }
+                commands::decode::Decoded::Jwt(ref jwt) => {
+                    out.data(decoded.display());
+                    for line in jwt.describe_times() {
+                        out.info(line);
+                    }
+                }
+                commands::decode::Decoded::Text(text) => out.data(text),
+                commands::decode::Decoded::Bytes(bytes) => {
+                    // Binary data is written as it is, so it can be redirected to a file
+                    if let Err(e) = std::io::Write::write_all(&mut std::io::stdout(), &bytes) {
+                        exit_with("Error writing output", e);
+                    }
+                }
+            }
+        }
+        Some(Commands::Time(args)) => {
+            let text = args.value.join(" ");
+            let Some(ts) = commands::time::parse(&text) else {
+                exit_with(
+                    "Error",
+                    format!(
+                        "Not a timestamp: '{}' (expected epoch seconds/milliseconds, ISO 8601, RFC 2822, common log or syslog format)",
+                        text
+                    ),
+                );
+            };
+            match args.to {
+                Some(output) => out.data(ts.to(output)),
+                None => {
+                    for line in ts.describe() {
+                        out.data(line);
+                    }
+                }
+            }
+        }
+        Some(Commands::Gen(args)) => {
+            let mut values = Vec::with_capacity(args.count);
+            for _ in 0..args.count {
+                let value = match &args.kind {
+                    GenAction::Uuid => commands::random::uuid_v4(),
+                    GenAction::Ulid => commands::random::ulid(),
+                    GenAction::Password { length, no_symbols } => commands::random::password(*length, !no_symbols),
+                };
+                match value {
+                    Ok(v) => values.push(v),
+                    Err(e) => exit_with("Error", e),
+                }
+            }
+            for value in &values {
+                out.data(value);
+            }
+            if args.copy {
+                // On Linux the text stays available after ftool exits only if a clipboard manager takes it
+                match arboard::Clipboard::new().and_then(|mut c| c.set_text(values.join("\n"))) {
+                    Ok(()) => out.info(format!("Copied {} value(s) to the clipboard", values.len())),
+                    Err(e) => exit_with("Error copying to the clipboard", anyhow::Error::from(e)),
+                }
+            }
+        }
+        Some(Commands::Count(args)) => {
+            out.progress(format!("Counting {}...", args.dir.display()));
+            match commands::count::count(&args.dir, &args.include, &args.exclude, args.no_ignore, args.hidden) {
+                Ok(mut report) => {
+                    report.sort(args.sort);
+                    out.text(&report.render());
+                }
+                Err(e) => exit_with("Error counting files", e),
+            }
+        }
+        Some(Commands::FindColumn(args)) => {
+            let pattern = match commands::find_column::ColumnPattern::new(&args.pattern) {
+                Ok(pattern) => pattern,
+                Err(e) => exit_with("Error", e),
+            };
+            out.progress(format!("Reading schemas under {}...", args.dir.display()));
+            let search = match commands::find_column::find_column(&args.dir, &pattern) {
+                Ok(search) => search,
+                Err(e) => exit_with("Error searching columns", e),
+            };
+            for (path, e) in &search.errors {
+                out.progress(format!("warning: skipped {}: {}", path, e));
+            }
+            match args.format {
+                FileFormat::Json => out.data(serde_json::to_string_pretty(&search.matches).unwrap_or_default()),
+                FileFormat::Text => out.text(&search.render()),
+            }
+        }
+        Some(Commands::Index(args)) => {
+            let mut catalog = match commands::catalog::Catalog::load() {
+                Ok(catalog) => catalog,
+                Err(e) => exit_with("Error reading the catalog", e),
+            };
+            match args.action {
+                Some(IndexAction::Search { term, format }) => {
+                    let found = catalog.search(&term);
+                    match format {
+                        FileFormat::Json => out.data(serde_json::to_string_pretty(&found).unwrap_or_default()),
+                        FileFormat::Text => out.text(&commands::catalog::render_search(&found, &term)),
+                    }
+                }
+                None => {
+                    out.progress(format!("Indexing {}...", args.dir.display()));
+                    let stats = match catalog.refresh(&args.dir, args.full) {
+                        Ok(stats) => stats,
+                        Err(e) => exit_with("Error indexing", e),
+                    };
+                    if let Err(e) = catalog.save() {
+                        exit_with("Error saving the catalog", e);
+                    }
+                    out.info(format!("Indexed {}: {}", args.dir.display(), stats.describe()));
                 }
             }
         }
         Some(Commands::Tui(args)) => {
-            if let Err(e) = tui::run(args.path) {
-                eprintln!("TUI error: {}", e);
-                std::process::exit(1);
+            if let Err(e) = tui::run(args.path.map(|p| config.resolve(&p)), args.error_log) {
+                exit_with("TUI error", e);
+            }
+        }
+        Some(Commands::Completions(args)) => {
+            commands::docs::completions(args.shell);
+        }
+        Some(Commands::Man(args)) => {
+            let result = match args.out_dir {
+                Some(dir) => commands::docs::man_to_dir(&dir)
+                    .map(|_| out.info(format!("Man pages written to {}", dir.display()))),
+                None => commands::docs::man(),
+            };
+            if let Err(e) = result {
+                exit_with("Error generating man page", e);
+            }
+        }
+        Some(Commands::Config(args)) => {
+            // Reloaded without the command-line overrides, which `set` would save
+            let mut config = match Config::load() {
+                Ok(c) => c,
+                Err(e) => exit_with("Error", e),
+            };
+            let result = match args.action {
+                ConfigAction::Get { key } => config.get(&key).map(|value| {
+                    if let Some(value) = value {
+                        out.data(value);
+                    }
+                }),
+                ConfigAction::Set { key, value } => {
+                    config.set(&key, &value).and_then(|_| config.save())
+                }
+                ConfigAction::List => {
+                    for (key, value) in config.entries() {
+                        let value = value.unwrap_or_else(|| "-".to_string());
+                        out.data(format!("{:<24} {}", key, value));
+                    }
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                exit_with("Error", e);
+            }
+        }
+        Some(Commands::Alias(args)) => {
+            let mut config = match Config::load() {
+                Ok(c) => c,
+                Err(e) => exit_with("Error", e),
+            };
+            let result = match args.action {
+                AliasAction::Add { name, target } => config.add_alias(&name, &target).and_then(|_| config.save()),
+                AliasAction::Remove { name } => config.remove_alias(&name).and_then(|_| config.save()),
+                AliasAction::List => {
+                    for (name, target) in &config.aliases {
+                        out.data(format!("{:<16} {}", name, target));
+                    }
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                exit_with("Error", e);
             }
         }
         Some(Commands::Todo(args)) => {
             if let Err(e) = args.validate() {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+                exit_with("Error", e);
             }
 
             if let Some(task) = args.add {
@@ -129,3 +1052,24 @@ fn main() {
         }
     }
 }
+
+/// What `command` would write, for commands that create or change data files
+/// (ftool's own config, catalog and notes are not data files)
+fn writing_action(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::File(args) if args.output.is_some() => Some("Writing a converted file"),
+        Commands::Inspect(args) if (args.convert.is_some() || args.convert_default) && !args.dry_run => Some("Conversion"),
+        Commands::Inspect(args) if args.sample.is_some() => Some("Writing a sample"),
+        Commands::Inspect(args) if args.row_hashes.is_some() => Some("Writing row hashes"),
+        Commands::Report(args) if args.output.is_some() => Some("Writing a report"),
+        Commands::Diff(args) if args.output.is_some() => Some("Writing differences"),
+        Commands::Undo(args) if !args.list => Some("Undoing a write"),
+        Commands::Split(_) => Some("Splitting"),
+        Commands::Merge(_) => Some("Merging"),
+        Commands::ConvertDir(_) => Some("Converting a directory"),
+        Commands::Generate(_) => Some("Generating data"),
+        Commands::Tile(args) if !args.dry_run => Some("Building tiles"),
+        Commands::Load(args) if !args.dry_run => Some("Loading into a database"),
+        _ => None,
+    }
+}
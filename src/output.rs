@@ -0,0 +1,109 @@
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+/// ANSI styles used in CLI output
+#[derive(Debug, Clone, Copy)]
+pub enum Style {
+    Error,
+    Warning,
+    Success,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Error => "1;31",
+            Style::Warning => "33",
+            Style::Success => "32",
+        }
+    }
+}
+
+/// Where every CLI command writes its output.
+///
+/// Results (`data`) are always printed; informational messages and progress are
+/// dropped with `--quiet`, and styling is off with `--no-color`, `NO_COLOR`, or
+/// when the stream isn't a terminal.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    quiet: bool,
+    color_stdout: bool,
+    color_stderr: bool,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self::new(false, false)
+    }
+}
+
+/// Makes the output settings available to the rest of the program through `get()`
+pub fn init(quiet: bool, no_color: bool) {
+    let _ = OUTPUT.set(Output::new(quiet, no_color));
+}
+
+/// The output settings from the command line, or the defaults if none were set
+pub fn get() -> &'static Output {
+    OUTPUT.get_or_init(Output::default)
+}
+
+impl Output {
+    fn new(quiet: bool, no_color: bool) -> Self {
+        let color = !no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+        Self {
+            quiet,
+            color_stdout: color && std::io::stdout().is_terminal(),
+            color_stderr: color && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Whether ANSI styling is written to stderr (also used for log output)
+    pub fn color_stderr(&self) -> bool {
+        self.color_stderr
+    }
+
+    /// A command's result, printed even with `--quiet`
+    pub fn data(&self, text: impl Display) {
+        println!("{}", text);
+    }
+
+    /// A multi-line result that already ends in a newline
+    pub fn text(&self, text: &str) {
+        print!("{}", text);
+    }
+
+    /// A status message such as "Wrote 3 files", hidden with `--quiet`
+    pub fn info(&self, text: impl Display) {
+        if !self.quiet {
+            println!("{}", text);
+        }
+    }
+
+    /// Progress and warnings on stderr, hidden with `--quiet`
+    pub fn progress(&self, text: impl Display) {
+        if !self.quiet {
+            eprintln!("{}", text);
+        }
+    }
+
+    /// Wraps `text` in `style` when stdout is styled
+    pub fn paint(&self, text: impl Display, style: Style) -> String {
+        paint(self.color_stdout, text, style)
+    }
+
+    /// Wraps `text` in `style` when stderr is styled
+    pub fn paint_err(&self, text: impl Display, style: Style) -> String {
+        paint(self.color_stderr, text, style)
+    }
+}
+
+fn paint(enabled: bool, text: impl Display, style: Style) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+    } else {
+        text.to_string()
+    }
+}
@@ -1,50 +1,170 @@
+use anyhow::Context;
 use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::Frame;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
-use crate::commands::DuckDbInspector;
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::commands::csv_lint::{lint, LintReport};
+use crate::commands::geo::GeoColumn;
+use crate::commands::report::{Report, ReportFormat};
+use crate::commands::schema_diff::SchemaDiff;
+use crate::commands::env::is_env_file;
+use crate::commands::http::{HttpRequest, HttpResponse};
+use crate::commands::decode::Decoded;
+use crate::commands::catalog::{Catalog, RefreshStats};
+use crate::commands::random::GenKind;
+use crate::commands::backup;
+use crate::commands::deletion;
+use crate::commands::time::{self, Timestamp};
+use crate::commands::json_inspector::save_pasted;
+use crate::commands::pmtiles::{self, TileCheck};
+use crate::commands::postgres::DatabaseTable;
+use crate::commands::tile::{builtin_presets, estimate, presets, TileEstimate, TilePlan};
+use crate::config::{Config, TilePreset};
+use crate::commands::table_format::TableFormat;
+use crate::commands::transform::{ComputedColumn, Mask, Transform};
+use crate::commands::duckdb_inspector::{
+    normalize_decimal_comma, nulls_as_text, ColumnStats, DecimalFormat, DuckDbError, RowGroupInfo,
+};
+
+use super::completion::{self, Completion};
+use super::error_log::ErrorLog;
+use super::persistence::{self, SavedFile, SavedSession};
+use super::history::{self, HistoryEntry};
+use super::presets::{self, FilterPreset};
+use super::scratchpad::ScratchpadState;
+use super::decode::DecodeState;
+use super::file_state::{self, FileState};
+use super::large_file::{self, LargeFile, OpenMode};
+use super::palette::{PaletteCommand, PaletteState};
+use super::catalog::CatalogState;
+use super::macros::KeyMacros;
+use super::dir_preview::DirPreview;
+use super::env::EnvState;
+use super::http_client::{self, HttpClientState, HttpField};
+use super::session::{
+    build_where_clause, is_numeric_type, DataInspectorState, FilterFailure, GeoMap, InspectorSpec, JsonInspectorState,
+    ScatterPlot, Session, HISTOGRAM_BUCKETS, MAP_SAMPLE,
+};
+use super::task::Tasks;
 use super::views;
+use super::widgets::{spinner, status_bar};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Screen {
     Home,
     FileBrowser,
     DataInspector,
     JsonInspector,
+    ErrorLog,
+    Scratchpad,
+    Env,
+    Http,
+    Decode,
+    Catalog,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum InspectorTab {
     Schema,
     Preview,
+    Chart,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum JsonInspectorTab {
+    /// One row per record, for JSON Lines files
+    Records,
     Tree,
     Raw,
+    /// Status and headers of the HTTP response the JSON came from
+    Response,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GeoJsonTab {
     Summary,
     Features,
     Tree,
+    Map,
 }
 
 pub const FILTER_OPERATORS: &[&str] = &[
-    "=", "!=", ">", "<", ">=", "<=", "LIKE", "IS NULL", "IS NOT NULL",
+    "=", "!=", ">", "<", ">=", "<=", "LIKE", "IS NULL", "IS NOT NULL", "REGEXP", "NOT REGEXP",
 ];
 
-pub const PAGE_SIZE: usize = 25;
 pub const COLUMN_PAGE_SIZE: usize = 10;
 
-#[derive(Debug, Clone)]
+/// Shown instead of writing a file from a partly loaded one, which would
+/// leave a truncated copy under the usual name
+const SAMPLED_WRITE: &str = "Only part of this file is loaded; reopen it in Full mode to convert or export it";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FilterCondition {
     pub column: String,
     pub operator: String,
     pub value: String,
+    /// Where the value must appear for `LIKE`
+    #[serde(default)]
+    pub like_mode: LikeMode,
+    /// Use `ILIKE` instead of `LIKE`
+    #[serde(default)]
+    pub case_insensitive: bool,
+}
+
+impl FilterCondition {
+    /// The operator as shown to the user, including the `LIKE` options
+    pub fn operator_label(&self) -> String {
+        if self.operator != "LIKE" {
+            return self.operator.clone();
+        }
+        let op = if self.case_insensitive { "ILIKE" } else { "LIKE" };
+        format!("{} ({})", op, self.like_mode.label())
+    }
+}
+
+/// How a `LIKE` filter value is matched against the column
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum LikeMode {
+    #[default]
+    Contains,
+    StartsWith,
+    EndsWith,
+    Exact,
+}
+
+impl LikeMode {
+    pub fn next(self) -> Self {
+        match self {
+            LikeMode::Contains => LikeMode::StartsWith,
+            LikeMode::StartsWith => LikeMode::EndsWith,
+            LikeMode::EndsWith => LikeMode::Exact,
+            LikeMode::Exact => LikeMode::Contains,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LikeMode::Contains => "contains",
+            LikeMode::StartsWith => "starts with",
+            LikeMode::EndsWith => "ends with",
+            LikeMode::Exact => "exact",
+        }
+    }
+
+    /// The `LIKE` operand for a value already escaped for a SQL string. Contains
+    /// keeps the `%` and `_` typed in the value as wildcards; the other modes
+    /// match them (and `\`) literally, escaped with `\`.
+    pub fn pattern(self, value: &str) -> String {
+        let literal = || value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        match self {
+            LikeMode::Contains => format!("'%{}%'", value),
+            LikeMode::StartsWith => format!("'{}%' ESCAPE '\\'", literal()),
+            LikeMode::EndsWith => format!("'%{}' ESCAPE '\\'", literal()),
+            LikeMode::Exact => format!("'{}' ESCAPE '\\'", literal()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -61,19 +181,232 @@ pub struct FilterEditorState {
     pub operator_idx: usize,
     pub value_input: String,
     pub active_field: FilterField,
+    pub like_mode: LikeMode,
+    pub case_insensitive: bool,
+    /// Why the last condition couldn't be added (e.g. an invalid regex)
+    pub error: Option<String>,
+    /// Why the filters couldn't be applied, with the condition at fault
+    pub failure: Option<FilterFailure>,
+    /// Text typed to find a column, while searching the Column field
+    pub column_search: Option<String>,
+    /// Columns matching `column_search`
+    pub completion: Completion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformField {
+    Rename,
+    Cast,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransformColumn {
+    pub name: String,
+    pub data_type: String,
+    pub keep: bool,
+    pub rename: String,
+    pub cast: String,
+    pub partition: bool,
+    pub mask: Option<Mask>,
+}
+
+/// State of the transform popup opened from the convert confirmation
+#[derive(Debug, Clone)]
+pub struct TransformEditorState {
+    pub target_format: String,
+    pub columns: Vec<TransformColumn>,
+    pub selected: usize,
+    pub editing: Option<TransformField>,
+    pub input: String,
+}
+
+impl TransformEditorState {
+    pub fn to_transform(&self) -> Transform {
+        let mut transform = Transform::default();
+        for c in &self.columns {
+            if !c.keep {
+                transform.drop.push(c.name.clone());
+                continue;
+            }
+            if !c.rename.is_empty() && c.rename != c.name {
+                transform.rename.push((c.name.clone(), c.rename.clone()));
+            }
+            if !c.cast.is_empty() {
+                transform.cast.push((c.name.clone(), c.cast.clone()));
+            }
+            if let Some(mask) = c.mask {
+                transform.mask.push((c.name.clone(), mask));
+            }
+        }
+        transform
+    }
+
+    /// Output names of the columns marked as partition keys
+    pub fn partition_by(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter(|c| c.keep && c.partition)
+            .map(|c| if c.rename.is_empty() { c.name.clone() } else { c.rename.clone() })
+            .collect()
+    }
+}
+
+/// State of the "copy visible rows" popup
+#[derive(Debug, Clone)]
+pub struct CopyPageState {
+    pub format: TableFormat,
+    pub to_file: bool,
+    pub path_input: String,
+}
+
+/// Name input for saving the active filters as a preset
+#[derive(Debug, Clone)]
+pub struct SavePresetState {
+    pub name: String,
+    /// Available for every file instead of only the current one
+    pub global: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PresetPickerState {
+    pub presets: Vec<FilterPreset>,
+    pub selected: usize,
+}
+
+/// Searchable list of previously applied filter sets
+#[derive(Debug, Clone)]
+pub struct HistoryState {
+    pub entries: Vec<HistoryEntry>,
+    pub query: String,
+    pub selected: usize,
+}
+
+impl HistoryState {
+    /// Entries matching the search query, newest first
+    pub fn visible(&self) -> Vec<&HistoryEntry> {
+        self.entries.iter().filter(|e| e.matches(&self.query)).collect()
+    }
+}
+
+/// Expression input for adding a computed preview column
+#[derive(Debug, Clone)]
+pub struct ComputedColumnState {
+    pub input: String,
+    /// Also write the computed columns when converting from the TUI
+    pub export: bool,
+    /// Columns and keywords matching the word being typed
+    pub completion: Completion,
+}
+
+/// Result of a conversion from the TUI, with actions on the new file
+#[derive(Debug, Clone)]
+pub struct ConvertedState {
+    pub path: PathBuf,
+    /// Whether the verification passed and its report, when the output was verified
+    pub verification: Option<(bool, Vec<String>)>,
+    /// New file name being typed, while renaming
+    pub rename: Option<String>,
+    /// Why the last rename failed
+    pub error: Option<String>,
+}
+
+/// Type override being typed for one column
+#[derive(Debug, Clone)]
+pub struct ColumnTypeState {
+    /// Index of the column in the schema
+    pub column: usize,
+    pub name: String,
+    /// The column's current type, shown for reference
+    pub current: String,
+    pub input: String,
+}
+
+/// A geo column and how to reopen its file
+type GeoJob = (GeoColumn, InspectorSpec);
+
+/// How the active CSV is read, changed from the CSV options popup (`O`)
+#[derive(Debug, Clone)]
+pub struct CsvOptionsState {
+    /// Numbers written `1.234,56` rather than `1234.56`
+    pub decimal_comma: bool,
+}
+
+/// Row groups of a Parquet file, for jumping the preview to one
+#[derive(Debug, Clone)]
+pub struct RowGroupsState {
+    pub groups: Vec<RowGroupInfo>,
+    pub selected: usize,
+}
+
+/// Row selected in the PMTiles popup; `←→` change its value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TilesField {
+    Preset,
+    MinZoom,
+    MaxZoom,
+}
+
+/// Preset and zoom range for building PMTiles from the open GeoJSON, with
+/// its size estimate
+#[derive(Debug, Clone)]
+pub struct TilesState {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// Built-in presets, then the ones saved in the config
+    pub presets: Vec<TilePreset>,
+    pub preset: usize,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    /// tippecanoe options of the selected preset
+    pub flags: Vec<String>,
+    pub field: TilesField,
+    /// Name being typed to save the current settings as a preset
+    pub naming: Option<String>,
+    /// Why the last save failed
+    pub error: Option<String>,
+    pub estimate: TileEstimate,
 }
 
+/// Highest zoom offered in the PMTiles popup
+pub const MAX_TILE_ZOOM: u8 = 22;
+
 #[derive(Debug, Clone)]
 pub enum Popup {
     None,
-    ConvertConfirm { target_format: String },
+    ConvertConfirm { target_format: String, verify: bool },
     Message { title: String, body: String },
     FilterEditor(FilterEditorState),
+    RestoreSession(SavedSession),
+    CopyPage(CopyPageState),
+    TransformEditor(TransformEditorState),
+    SavePreset(SavePresetState),
+    PresetPicker(PresetPickerState),
+    History(HistoryState),
+    ComputedColumn(ComputedColumnState),
+    Converted(ConvertedState),
+    ColumnType(ColumnTypeState),
+    CsvOptions(CsvOptionsState),
+    Scatter(ScatterPlot),
+    RowGroups(RowGroupsState),
+    Map(GeoMap),
+    Tiles(TilesState),
+    /// Layers of a tileset just written, with the features tippecanoe dropped
+    TileCheck(TileCheck),
+    /// Scrollable multi-line result (e.g. a schema comparison)
+    Text { title: String, lines: Vec<String>, scroll: usize },
+    Palette(PaletteState),
+    /// Asks how to open a file too large to load whole
+    LargeFile(LargeFile),
+    /// Asks before deleting these files from the browser
+    ConfirmDelete(Vec<PathBuf>),
 }
 
 #[derive(Debug)]
 pub enum Message {
     Quit,
+    ToggleMacroRecording,
+    PlayMacro,
+    RepeatAction,
     NavigateUp,
     NavigateDown,
     Enter,
@@ -83,16 +416,95 @@ pub enum Message {
     ScrollDown,
     ConvertFile,
     ConfirmConvert,
+    ToggleVerify,
+    OpenConverted,
+    ConvertedRenameStart,
+    ConvertedRenameChar(char),
+    ConvertedRenameBackspace,
+    ConvertedRenameConfirm,
+    ConvertedRenameCancel,
+    /// Revert the last file written (from the Converted popup or the palette)
+    UndoWrite,
+    LargeFileUp,
+    LargeFileDown,
+    /// Open the large file the way selected in its popup
+    LargeFileOpen,
     ClosePopup,
     ToggleTreeNode,
+    OpenRowGroups,
+    /// Plot the focused geo column (or the file's first one) over a world map
+    OpenGeoMap,
+    GeoMapLoaded(Result<GeoMap, DuckDbError>),
+    /// Write the file as GeoJSON with the focused geo column as geometry
+    ExportGeoJson,
+    GeoJsonExported(Result<String, DuckDbError>),
+    RowGroupUp,
+    RowGroupDown,
+    /// Show the preview from the first row of the selected row group
+    RowGroupJump,
+    FeatureColLeft,
+    FeatureColRight,
+    /// Mark the selected GeoJSON property to be dropped on export, or keep it again
+    ToggleDropProperty,
+    /// Toggle dropping features whose geometry repeats an earlier one
+    ToggleDedupe,
+    /// Write the GeoJSON with the dropped properties and duplicates removed
+    WritePruned,
+    PrunedWritten(anyhow::Result<PathBuf>),
+    /// Estimate and build PMTiles from the open GeoJSON with tippecanoe
+    OpenTiles,
+    TilesFieldUp,
+    TilesFieldDown,
+    TilesValueDown,
+    TilesValueUp,
+    /// Save the popup's zooms and options as a preset in the config
+    TilesSaveStart,
+    TilesNameChar(char),
+    TilesNameBackspace,
+    TilesNameCancel,
+    TilesNameConfirm,
+    TilesBuild,
+    /// tippecanoe finished; the tileset compared with the input features
+    TilesBuilt(anyhow::Result<TileCheck>),
+    /// Start or stop following the active file as another process writes it
+    ToggleFollow,
+    /// Row count of a followed data file, recounted after it grew
+    FollowCounted(PathBuf, Result<usize, DuckDbError>),
     SwitchGeoTab,
     Noop,
-    NextPage,
-    PrevPage,
+    /// Scroll the preview one row, a screenful, or to either end
+    RowDown,
+    RowUp,
+    ScreenDown,
+    ScreenUp,
+    FirstRow,
+    LastRow,
     NextColPage,
     PrevColPage,
     ColLeft,
     ColRight,
+    TogglePinColumn,
+    ChartCursorUp,
+    ChartCursorDown,
+    ChartSetX,
+    ChartToggleY,
+    PlotChart,
+    OpenScatter,
+    ToggleExpandColumn,
+    ResizeColumn(bool),
+    UnnestColumn,
+    ClearUnnest,
+    ShowRowDetail,
+    OpenPalette,
+    PaletteChar(char),
+    PaletteBackspace,
+    PaletteNavUp,
+    PaletteNavDown,
+    PaletteRun,
+    /// Show the selected top-row value as a timestamp in other forms (Preview tab)
+    ShowTimestamp,
+    /// Left click at (column, row) of the terminal: select the preview cell there
+    PreviewClick(u16, u16),
     OpenFilterPopup,
     FilterTabNext,
     FilterNavUp,
@@ -102,10 +514,181 @@ pub enum Message {
     FilterAddCondition,
     FilterRemoveLast,
     FilterApplyWithCurrent,
+    FilterCycleLikeMode,
+    FilterToggleCase,
     BrowserSearchActivate,
     BrowserSearchChar(char),
     BrowserSearchBackspace,
     BrowserSearchExit,
+    NextSession,
+    PrevSession,
+    CloseSession,
+    ToggleSplit,
+    SplitFocus,
+    RestoreSession,
+    Tick,
+    OpenErrorLog,
+    ClearErrorLog,
+    ScratchpadChar(char),
+    ScratchpadBackspace,
+    ScratchpadClear,
+    ScratchpadPaste,
+    ScratchpadFormat,
+    ScratchpadCopy,
+    DecodeChar(char),
+    DecodeBackspace,
+    DecodeClear,
+    DecodePaste,
+    DecodeEncoding,
+    DecodeCopy,
+    /// Open the decoded JSON (or JWT header and payload) in the JSON inspector
+    DecodeOpen,
+    /// Open the JSON in the clipboard in the JSON inspector
+    PasteJson,
+    HttpChar(char),
+    HttpBackspace,
+    HttpNextField,
+    HttpPrevField,
+    HttpMethodNext,
+    HttpMethodPrev,
+    HttpHistoryUp,
+    HttpHistoryDown,
+    /// Fill the request form with the selected history entry
+    HttpLoadHistory,
+    HttpSend,
+    /// The HTTP client's request came back (or failed)
+    HttpResponded(HttpRequest, anyhow::Result<HttpResponse>),
+    /// Start typing a search in the environment view
+    EnvSearchStart,
+    EnvSearchChar(char),
+    EnvSearchBackspace,
+    /// Stop typing, keeping the search
+    EnvSearchDone,
+    EnvSearchClear,
+    /// Show or mask secret values
+    EnvToggleReveal,
+    EnvReload,
+    /// Start typing a search in the data catalog
+    CatalogSearchStart,
+    CatalogSearchChar(char),
+    CatalogSearchBackspace,
+    CatalogSearchDone,
+    CatalogSearchClear,
+    /// Index the catalog's directories again, re-reading changed files
+    CatalogRefresh,
+    CatalogRefreshed(anyhow::Result<(Catalog, RefreshStats)>),
+    OpenShell,
+    ShellExited(anyhow::Result<()>),
+    OpenCopyPage,
+    CopyPageFormat,
+    CopyPageTarget,
+    CopyPageChar(char),
+    CopyPageBackspace,
+    CopyPageConfirm,
+    OpenTransformEditor,
+    TransformNavUp,
+    TransformNavDown,
+    TransformToggleKeep,
+    TransformTogglePartition,
+    TransformCycleMask,
+    TransformEdit(TransformField),
+    TransformChar(char),
+    TransformBackspace,
+    TransformCommitEdit,
+    TransformCancelEdit,
+    TransformConfirm,
+    ToggleMark,
+    /// Delete the marked files, or the selected one, after asking
+    DeleteFiles,
+    ConfirmDelete,
+    /// Bring back the files the last delete moved to the trash
+    RestoreDeleted,
+    CompareSchemas,
+    SchemaCompared(PathBuf, PathBuf, Result<SchemaDiff, DuckDbError>),
+    PopupScrollUp,
+    PopupScrollDown,
+    WriteReport,
+    ReportWritten(anyhow::Result<PathBuf>),
+    LintCsv,
+    CsvLinted(PathBuf, std::io::Result<LintReport>),
+    SessionLoaded(PathBuf, anyhow::Result<Session>),
+    LoadAllStats,
+    CancelTasks,
+    TaskCancelled(Box<Message>),
+    CountRows,
+    RowCountLoaded(PathBuf, Result<usize, DuckDbError>),
+    DecimalCommaDetected(PathBuf, Result<DecimalFormat, DuckDbError>),
+    GeoColumnsDetected(PathBuf, Result<Vec<GeoColumn>, DuckDbError>),
+    StatsLoaded(PathBuf, Vec<usize>, Result<(Vec<ColumnStats>, Duration), DuckDbError>),
+    ExplainQuery,
+    OpenSavePreset,
+    PresetChar(char),
+    PresetBackspace,
+    PresetToggleScope,
+    PresetSaveConfirm,
+    OpenPresetPicker,
+    PresetNavUp,
+    PresetNavDown,
+    PresetApply,
+    PresetDelete,
+    OpenHistory,
+    HistoryChar(char),
+    HistoryBackspace,
+    HistoryNavUp,
+    HistoryNavDown,
+    HistoryApply,
+    OpenComputedColumn,
+    ComputedChar(char),
+    ComputedBackspace,
+    ComputedToggleExport,
+    /// Move through the suggestion list of the open popup
+    CompletionUp,
+    CompletionDown,
+    /// Insert the selected suggestion into the open popup's input
+    CompletionAccept,
+    FilterSearchStart,
+    FilterSearchChar(char),
+    FilterSearchBackspace,
+    FilterSearchCancel,
+    ComputedConfirm,
+    RemoveComputedColumn,
+    OpenColumnType,
+    ColumnTypeChar(char),
+    ColumnTypeBackspace,
+    ColumnTypeConfirm,
+    OpenCsvOptions,
+    CsvOptionsToggle,
+    CsvOptionsConfirm,
+}
+
+impl Message {
+    /// Messages that write or rename data files, refused in read-only mode
+    fn writes_files(&self) -> bool {
+        matches!(
+            self,
+            Message::ConvertFile
+                | Message::ConfirmConvert
+                | Message::OpenTransformEditor
+                | Message::TransformConfirm
+                | Message::ConvertedRenameConfirm
+                | Message::WritePruned
+                | Message::OpenTiles
+                | Message::TilesBuild
+                | Message::WriteReport
+                | Message::ExportGeoJson
+                | Message::UndoWrite
+                | Message::DeleteFiles
+                | Message::ConfirmDelete
+                | Message::RestoreDeleted
+        )
+    }
+}
+
+/// Session indices shown in the left and right panes of the split view
+#[derive(Debug, Clone, Copy)]
+pub struct SplitView {
+    pub left: usize,
+    pub right: usize,
 }
 
 pub struct DirEntryInfo {
@@ -128,43 +711,49 @@ pub struct App {
     pub browser_search_active: bool,
     pub browser_search_query: String,
     pub browser_filtered_indices: Vec<usize>,
-    // Data inspector
-    pub inspector: Option<DuckDbInspector>,
-    pub inspector_file: Option<PathBuf>,
-    pub inspector_tab: InspectorTab,
-    pub inspector_schema: Vec<(String, String)>,
-    pub inspector_null_counts: Vec<usize>,
-    pub inspector_mean_values: Vec<String>,
-    pub inspector_min_values: Vec<String>,
-    pub inspector_max_values: Vec<String>,
-    pub inspector_preview_headers: Vec<String>,
-    pub inspector_preview_data: Vec<Vec<String>>,
-    pub inspector_row_count: usize,
-    pub inspector_scroll: usize,
-    pub inspector_page: usize,
-    pub inspector_col_page: usize,
-    pub inspector_selected_col: usize,
-    pub inspector_stats_loaded: bool,
-    pub inspector_filters: Vec<FilterCondition>,
+    /// Set by `!`; the run loop suspends the TUI and spawns a shell in this directory
+    pub pending_shell: Option<PathBuf>,
+    /// Files marked with Space, kept across directory changes
+    pub browser_marked: Vec<PathBuf>,
+    /// What the last delete moved to the trash, for `u`
+    pub last_deleted: Vec<PathBuf>,
+    /// Totals of the selected directory, shown in the preview panel
+    pub dir_preview: Option<DirPreview>,
+    // Open inspector tabs
+    pub sessions: Vec<Session>,
+    pub active_session: usize,
+    pub split: Option<SplitView>,
     // Popup
     pub popup: Popup,
-    // Json inspector
-    pub json_file: Option<PathBuf>,
-    pub json_root: Option<serde_json::Value>,
-    pub json_kind: Option<crate::commands::json_inspector::FileKind>,
-    pub json_tab: JsonInspectorTab,
-    pub geo_tab: GeoJsonTab,
-    pub json_scroll: usize,
-    pub json_tree_nodes: Vec<(String, crate::tui::tree::TreeNode)>,
-    pub json_collapsed: std::collections::HashSet<String>,
-    pub json_features_headers: Vec<String>,
-    pub json_features_data: Vec<Vec<String>>,
-    pub json_geosummary: Option<(usize, Vec<String>, Option<(f64, f64, f64, f64)>)>,
-    pub json_raw: String,
+    // Background work
+    pub tasks: Tasks,
+    pub tick: usize,
+    // Error log
+    pub error_log: ErrorLog,
+    pub error_log_scroll: usize,
+    error_log_return: Screen,
+    // Geometry scratchpad
+    pub scratchpad: ScratchpadState,
+    pub decode: DecodeState,
+    /// The environment view's variables, once it has been opened
+    pub env: Option<EnvState>,
+    /// Screen to return to when leaving the environment view
+    pub env_return: Screen,
+    /// The HTTP client's form and history, once it has been opened
+    pub http: Option<HttpClientState>,
+    /// The data catalog browser, once it has been opened
+    pub catalog: Option<CatalogState>,
+    /// Keys recorded for `@` and `.`
+    pub macros: KeyMacros,
+    /// Tile presets offered by the PMTiles popup, including ones saved this run
+    pub tile_presets: Vec<TilePreset>,
+    clipboard: Option<arboard::Clipboard>,
+    /// Set while applying a cancelled task's result, whose errors are expected
+    cancelling: bool,
 }
 
 impl App {
-    pub fn new(path: Option<PathBuf>) -> anyhow::Result<Self> {
+    pub fn new(path: Option<PathBuf>, error_log: Option<PathBuf>) -> anyhow::Result<Self> {
         let mut app = Self {
             should_quit: false,
             current_screen: Screen::Home,
@@ -175,44 +764,64 @@ impl App {
             browser_search_active: false,
             browser_search_query: String::new(),
             browser_filtered_indices: Vec::new(),
-            inspector: None,
-            inspector_file: None,
-            inspector_tab: InspectorTab::Schema,
-            inspector_schema: Vec::new(),
-            inspector_null_counts: Vec::new(),
-            inspector_mean_values: Vec::new(),
-            inspector_min_values: Vec::new(),
-            inspector_max_values: Vec::new(),
-            inspector_preview_headers: Vec::new(),
-            inspector_preview_data: Vec::new(),
-            inspector_row_count: 0,
-            inspector_scroll: 0,
-            inspector_page: 0,
-            inspector_col_page: 0,
-            inspector_selected_col: 0,
-            inspector_stats_loaded: false,
-            inspector_filters: Vec::new(),
+            pending_shell: None,
+            browser_marked: Vec::new(),
+            last_deleted: Vec::new(),
+            dir_preview: None,
+            sessions: Vec::new(),
+            active_session: 0,
+            split: None,
             popup: Popup::None,
-            json_file: None,
-            json_root: None,
-            json_kind: None,
-            json_tab: JsonInspectorTab::Tree,
-            geo_tab: GeoJsonTab::Summary,
-            json_scroll: 0,
-            json_tree_nodes: Vec::new(),
-            json_collapsed: std::collections::HashSet::new(),
-            json_features_headers: Vec::new(),
-            json_features_data: Vec::new(),
-            json_geosummary: None,
-            json_raw: String::new(),
+            tasks: Tasks::new(),
+            tick: 0,
+            error_log: ErrorLog::new(error_log),
+            error_log_scroll: 0,
+            error_log_return: Screen::Home,
+            scratchpad: ScratchpadState::default(),
+            decode: DecodeState::default(),
+            env: None,
+            env_return: Screen::Home,
+            http: None,
+            catalog: None,
+            macros: KeyMacros::default(),
+            tile_presets: presets(),
+            clipboard: None,
+            cancelling: false,
         };
 
+        // No path given: offer to restore the previous session, if any
+        if path.is_none()
+            && let Some(saved) = persistence::load()
+        {
+            app.popup = Popup::RestoreSession(saved);
+        }
+
         if let Some(p) = path {
             let p = std::fs::canonicalize(&p).unwrap_or(p);
-            if p.is_dir() {
+            if DatabaseTable::is_url(&p.to_string_lossy()) {
+                let mut state = DataInspectorState::open(&p)?;
+                file_state::restore(&mut state);
+                app.open_session(Session::Data(state));
+            } else if p.is_dir() {
                 app.current_dir = p;
                 app.load_dir_entries()?;
                 app.current_screen = Screen::FileBrowser;
+            } else if is_env_file(&p) {
+                // Esc goes back to the file browser in its directory
+                if let Some(parent) = p.parent() {
+                    app.current_dir = parent.to_path_buf();
+                    app.load_dir_entries()?;
+                    app.current_screen = Screen::FileBrowser;
+                }
+                app.show_env(EnvState::open(&p)?);
+            } else if let Some(large) = large_file::check(&p) {
+                // Too big to load without freezing: ask first, over the browser
+                if let Some(parent) = p.parent() {
+                    app.current_dir = parent.to_path_buf();
+                    app.load_dir_entries()?;
+                }
+                app.current_screen = Screen::FileBrowser;
+                app.popup = Popup::LargeFile(large);
             } else {
                 match p.extension().and_then(|e| e.to_str()) {
                     Some("csv") | Some("parquet") => {
@@ -221,17 +830,17 @@ impl App {
                             app.current_dir = parent.to_path_buf();
                             app.load_dir_entries()?;
                         }
-                        app.inspector_file = Some(p.clone());
-                        app.load_inspector_data(&p)?;
-                        app.current_screen = Screen::DataInspector;
+                        let mut state = DataInspectorState::open(&p)?;
+                        file_state::restore(&mut state);
+                        app.open_session(Session::Data(state));
                     }
-                    Some("json") | Some("geojson") => {
+                    Some("json") | Some("geojson") | Some("jsonl") | Some("ndjson") | Some("geojsonl")
+                    | Some("geojsons") | Some("gpx") | Some("kml") => {
                         if let Some(parent) = p.parent() {
                             app.current_dir = parent.to_path_buf();
                             app.load_dir_entries()?;
                         }
-                        app.load_json_data(&p)?;
-                        app.current_screen = Screen::JsonInspector;
+                        app.open_session(Session::Json(JsonInspectorState::open(&p)?));
                     }
                     _ => {
                         // Unknown file type - open browser in parent dir
@@ -248,12 +857,34 @@ impl App {
         Ok(app)
     }
 
+    /// Handles a terminal event, noting keys for `.` and the macro being recorded
+    pub fn handle_input(&mut self, event: Event) {
+        let popup_before = !matches!(self.popup, Popup::None);
+        let message = self.handle_event(event.clone());
+        let control = matches!(
+            message,
+            Message::Noop | Message::ToggleMacroRecording | Message::PlayMacro | Message::RepeatAction
+        );
+        self.update(message);
+        if let Event::Key(key) = event
+            && key.kind == KeyEventKind::Press
+            && !control
+        {
+            self.macros.record(key, popup_before, !matches!(self.popup, Popup::None));
+        }
+    }
+
     pub fn handle_event(&self, event: Event) -> Message {
         match event {
             Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
             Event::Mouse(mouse) => match mouse.kind {
                 crossterm::event::MouseEventKind::ScrollUp => Message::ScrollUp,
                 crossterm::event::MouseEventKind::ScrollDown => Message::ScrollDown,
+                crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left)
+                    if self.current_screen == Screen::DataInspector && matches!(self.popup, Popup::None) =>
+                {
+                    Message::PreviewClick(mouse.column, mouse.row)
+                }
                 _ => Message::Noop,
             },
             _ => Message::Noop,
@@ -263,9 +894,41 @@ impl App {
     fn handle_key(&self, key: crossterm::event::KeyEvent) -> Message {
         // Popup handling takes priority
         match &self.popup {
+            Popup::ConfirmDelete(_) => {
+                return match key.code {
+                    KeyCode::Enter => Message::ConfirmDelete,
+                    KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
             Popup::ConvertConfirm { .. } => {
                 return match key.code {
                     KeyCode::Enter => Message::ConfirmConvert,
+                    KeyCode::Char('t') => Message::OpenTransformEditor,
+                    KeyCode::Char('v') => Message::ToggleVerify,
+                    KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::TransformEditor(state) if state.editing.is_some() => {
+                return match key.code {
+                    KeyCode::Enter => Message::TransformCommitEdit,
+                    KeyCode::Esc => Message::TransformCancelEdit,
+                    KeyCode::Backspace => Message::TransformBackspace,
+                    KeyCode::Char(c) => Message::TransformChar(c),
+                    _ => Message::Noop,
+                };
+            }
+            Popup::TransformEditor(_) => {
+                return match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => Message::TransformNavUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::TransformNavDown,
+                    KeyCode::Char(' ') => Message::TransformToggleKeep,
+                    KeyCode::Char('p') => Message::TransformTogglePartition,
+                    KeyCode::Char('m') => Message::TransformCycleMask,
+                    KeyCode::Char('r') => Message::TransformEdit(TransformField::Rename),
+                    KeyCode::Char('t') => Message::TransformEdit(TransformField::Cast),
+                    KeyCode::Enter => Message::TransformConfirm,
                     KeyCode::Esc => Message::ClosePopup,
                     _ => Message::Noop,
                 };
@@ -276,9 +939,52 @@ impl App {
                     _ => Message::Noop,
                 };
             }
+            Popup::Converted(state) => {
+                return match (key.code, state.rename.is_some()) {
+                    (KeyCode::Esc, true) => Message::ConvertedRenameCancel,
+                    (KeyCode::Enter, true) => Message::ConvertedRenameConfirm,
+                    (KeyCode::Backspace, true) => Message::ConvertedRenameBackspace,
+                    (KeyCode::Char(c), true) => Message::ConvertedRenameChar(c),
+                    (KeyCode::Char('o'), false) => Message::OpenConverted,
+                    (KeyCode::Char('r'), false) => Message::ConvertedRenameStart,
+                    (KeyCode::Char('u'), false) => Message::UndoWrite,
+                    (KeyCode::Enter | KeyCode::Esc, false) => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::LargeFile(_) => {
+                return match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => Message::LargeFileUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::LargeFileDown,
+                    KeyCode::Enter => Message::LargeFileOpen,
+                    KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::RestoreSession(_) => {
+                return match key.code {
+                    KeyCode::Enter => Message::RestoreSession,
+                    KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::FilterEditor(state) if state.column_search.is_some() => {
+                return match key.code {
+                    KeyCode::Esc => Message::FilterSearchCancel,
+                    KeyCode::Up => Message::CompletionUp,
+                    KeyCode::Down => Message::CompletionDown,
+                    KeyCode::Tab | KeyCode::Enter => Message::CompletionAccept,
+                    KeyCode::Backspace => Message::FilterSearchBackspace,
+                    KeyCode::Char(c) => Message::FilterSearchChar(c),
+                    _ => Message::Noop,
+                };
+            }
             Popup::FilterEditor(state) => {
                 return match key.code {
                     KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Char('/') if state.active_field == FilterField::Column => {
+                        Message::FilterSearchStart
+                    }
                     KeyCode::Tab => Message::FilterTabNext,
                     KeyCode::Up => Message::FilterNavUp,
                     KeyCode::Down => Message::FilterNavDown,
@@ -294,6 +1000,18 @@ impl App {
                     KeyCode::Char('d') if state.active_field != FilterField::Value => {
                         Message::FilterRemoveLast
                     }
+                    KeyCode::Char('m')
+                        if state.active_field != FilterField::Value
+                            && FILTER_OPERATORS[state.operator_idx] == "LIKE" =>
+                    {
+                        Message::FilterCycleLikeMode
+                    }
+                    KeyCode::Char('i')
+                        if state.active_field != FilterField::Value
+                            && FILTER_OPERATORS[state.operator_idx] == "LIKE" =>
+                    {
+                        Message::FilterToggleCase
+                    }
                     KeyCode::Char(c) => {
                         if state.active_field == FilterField::Value {
                             Message::FilterChar(c)
@@ -304,9 +1022,154 @@ impl App {
                     _ => Message::Noop,
                 };
             }
+            Popup::RowGroups(_) => {
+                return match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => Message::RowGroupUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::RowGroupDown,
+                    KeyCode::Enter => Message::RowGroupJump,
+                    KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::Tiles(state) => {
+                return match (key.code, state.naming.is_some()) {
+                    (KeyCode::Esc, true) => Message::TilesNameCancel,
+                    (KeyCode::Enter, true) => Message::TilesNameConfirm,
+                    (KeyCode::Backspace, true) => Message::TilesNameBackspace,
+                    (KeyCode::Char(c), true) => Message::TilesNameChar(c),
+                    (KeyCode::Up | KeyCode::BackTab | KeyCode::Char('k'), false) => Message::TilesFieldUp,
+                    (KeyCode::Down | KeyCode::Tab | KeyCode::Char('j'), false) => Message::TilesFieldDown,
+                    (KeyCode::Left | KeyCode::Char('h'), false) => Message::TilesValueDown,
+                    (KeyCode::Right | KeyCode::Char('l'), false) => Message::TilesValueUp,
+                    (KeyCode::Char('s'), false) => Message::TilesSaveStart,
+                    (KeyCode::Enter, false) => Message::TilesBuild,
+                    (KeyCode::Esc, false) => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::TileCheck(_) => {
+                return match key.code {
+                    KeyCode::Enter | KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::Map(_) => {
+                return match key.code {
+                    KeyCode::Enter | KeyCode::Esc | KeyCode::Char('M') => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::Scatter(_) => {
+                return match key.code {
+                    KeyCode::Enter | KeyCode::Esc | KeyCode::Char('s') => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::Text { .. } => {
+                return match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => Message::PopupScrollUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::PopupScrollDown,
+                    KeyCode::Enter | KeyCode::Esc => Message::ClosePopup,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::SavePreset(_) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Tab => Message::PresetToggleScope,
+                    KeyCode::Enter => Message::PresetSaveConfirm,
+                    KeyCode::Backspace => Message::PresetBackspace,
+                    KeyCode::Char(c) => Message::PresetChar(c),
+                    _ => Message::Noop,
+                };
+            }
+            Popup::PresetPicker(_) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Up | KeyCode::Char('k') => Message::PresetNavUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::PresetNavDown,
+                    KeyCode::Enter => Message::PresetApply,
+                    KeyCode::Char('d') => Message::PresetDelete,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::ComputedColumn(state) => {
+                let suggesting = !state.completion.is_empty();
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Up if suggesting => Message::CompletionUp,
+                    KeyCode::Down if suggesting => Message::CompletionDown,
+                    KeyCode::Tab if suggesting => Message::CompletionAccept,
+                    KeyCode::Tab => Message::ComputedToggleExport,
+                    KeyCode::Enter => Message::ComputedConfirm,
+                    KeyCode::Backspace => Message::ComputedBackspace,
+                    KeyCode::Char(c) => Message::ComputedChar(c),
+                    _ => Message::Noop,
+                };
+            }
+            Popup::ColumnType(_) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Enter => Message::ColumnTypeConfirm,
+                    KeyCode::Backspace => Message::ColumnTypeBackspace,
+                    KeyCode::Char(c) => Message::ColumnTypeChar(c),
+                    _ => Message::Noop,
+                };
+            }
+            Popup::CsvOptions(_) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Char(' ') => Message::CsvOptionsToggle,
+                    KeyCode::Enter => Message::CsvOptionsConfirm,
+                    _ => Message::Noop,
+                };
+            }
+            Popup::History(_) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Up => Message::HistoryNavUp,
+                    KeyCode::Down => Message::HistoryNavDown,
+                    KeyCode::Enter => Message::HistoryApply,
+                    KeyCode::Backspace => Message::HistoryBackspace,
+                    KeyCode::Char(c) => Message::HistoryChar(c),
+                    _ => Message::Noop,
+                };
+            }
+            Popup::Palette(_) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Up => Message::PaletteNavUp,
+                    KeyCode::Down => Message::PaletteNavDown,
+                    KeyCode::Enter => Message::PaletteRun,
+                    KeyCode::Backspace => Message::PaletteBackspace,
+                    KeyCode::Char(c) => Message::PaletteChar(c),
+                    _ => Message::Noop,
+                };
+            }
+            Popup::CopyPage(state) => {
+                return match key.code {
+                    KeyCode::Esc => Message::ClosePopup,
+                    KeyCode::Tab => Message::CopyPageFormat,
+                    KeyCode::Up | KeyCode::Down => Message::CopyPageTarget,
+                    KeyCode::Enter => Message::CopyPageConfirm,
+                    KeyCode::Backspace if state.to_file => Message::CopyPageBackspace,
+                    KeyCode::Char(c) if state.to_file => Message::CopyPageChar(c),
+                    _ => Message::Noop,
+                };
+            }
             Popup::None => {}
         }
 
+        // The command palette opens from every screen, text fields included
+        if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Message::OpenPalette;
+        }
+
+        // Esc interrupts running background queries before it navigates
+        if key.code == KeyCode::Esc && self.tasks.current().is_some() {
+            return Message::CancelTasks;
+        }
+
         // Browser search mode intercept
         if self.current_screen == Screen::FileBrowser && self.browser_search_active {
             return match key.code {
@@ -320,13 +1183,113 @@ impl App {
             };
         }
 
+        // The scratchpad is a text field: every character goes into it
+        if self.current_screen == Screen::Scratchpad {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            return match key.code {
+                KeyCode::Esc => Message::Back,
+                KeyCode::Char('c') if ctrl => Message::Quit,
+                KeyCode::Char('v') if ctrl => Message::ScratchpadPaste,
+                KeyCode::Char('y') if ctrl => Message::ScratchpadCopy,
+                KeyCode::Char('u') if ctrl => Message::ScratchpadClear,
+                KeyCode::Tab => Message::ScratchpadFormat,
+                KeyCode::Backspace => Message::ScratchpadBackspace,
+                // Pasted text arrives as keys, with its line breaks as Enter
+                KeyCode::Enter => Message::ScratchpadChar('\n'),
+                KeyCode::Char(c) => Message::ScratchpadChar(c),
+                _ => Message::Noop,
+            };
+        }
+
+        // The decode scratchpad is a text field too
+        if self.current_screen == Screen::Decode {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            return match key.code {
+                KeyCode::Esc => Message::Back,
+                KeyCode::Char('c') if ctrl => Message::Quit,
+                KeyCode::Char('v') if ctrl => Message::DecodePaste,
+                KeyCode::Char('y') if ctrl => Message::DecodeCopy,
+                KeyCode::Char('u') if ctrl => Message::DecodeClear,
+                KeyCode::Char('o') if ctrl => Message::DecodeOpen,
+                KeyCode::Tab => Message::DecodeEncoding,
+                KeyCode::Backspace => Message::DecodeBackspace,
+                KeyCode::Enter => Message::DecodeChar('\n'),
+                KeyCode::Char(c) => Message::DecodeChar(c),
+                _ => Message::Noop,
+            };
+        }
+
+        // The HTTP client is a form: characters go into the focused field
+        if self.current_screen == Screen::Http {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            let field = self.http.as_ref().map(|h| h.field);
+            return match (key.code, field) {
+                (KeyCode::Esc, _) => Message::Back,
+                (KeyCode::Char('c'), _) if ctrl => Message::Quit,
+                (KeyCode::Char('s'), _) if ctrl => Message::HttpSend,
+                (KeyCode::Tab, _) => Message::HttpNextField,
+                (KeyCode::BackTab, _) => Message::HttpPrevField,
+                (KeyCode::Left | KeyCode::Up, Some(HttpField::Method)) => Message::HttpMethodPrev,
+                (KeyCode::Right | KeyCode::Down | KeyCode::Char(' '), Some(HttpField::Method)) => {
+                    Message::HttpMethodNext
+                }
+                (KeyCode::Up | KeyCode::Char('k'), Some(HttpField::History)) => Message::HttpHistoryUp,
+                (KeyCode::Down | KeyCode::Char('j'), Some(HttpField::History)) => Message::HttpHistoryDown,
+                (KeyCode::Enter, Some(HttpField::History)) => Message::HttpLoadHistory,
+                (KeyCode::Enter, Some(HttpField::Url)) => Message::HttpSend,
+                (KeyCode::Enter, _) => Message::HttpChar('\n'),
+                (KeyCode::Backspace, _) => Message::HttpBackspace,
+                (KeyCode::Char(c), _) => Message::HttpChar(c),
+                _ => Message::Noop,
+            };
+        }
+
+        // Typing an environment search
+        if self.current_screen == Screen::Env && self.env.as_ref().is_some_and(|e| e.searching) {
+            return match key.code {
+                KeyCode::Esc => Message::EnvSearchClear,
+                KeyCode::Enter => Message::EnvSearchDone,
+                KeyCode::Backspace => Message::EnvSearchBackspace,
+                KeyCode::Up => Message::ScrollUp,
+                KeyCode::Down => Message::ScrollDown,
+                KeyCode::Char(c) => Message::EnvSearchChar(c),
+                _ => Message::Noop,
+            };
+        }
+
+        // Typing a catalog search
+        if self.current_screen == Screen::Catalog && self.catalog.as_ref().is_some_and(|c| c.searching) {
+            return match key.code {
+                KeyCode::Esc => Message::CatalogSearchClear,
+                KeyCode::Enter => Message::CatalogSearchDone,
+                KeyCode::Backspace => Message::CatalogSearchBackspace,
+                KeyCode::Up => Message::ScrollUp,
+                KeyCode::Down => Message::ScrollDown,
+                KeyCode::Char(c) => Message::CatalogSearchChar(c),
+                _ => Message::Noop,
+            };
+        }
+
         // Global quit
         if key.code == KeyCode::Char('q') {
             return Message::Quit;
         }
+        // Macros: Q records, @ plays back, . repeats the last action
+        match key.code {
+            KeyCode::Char('Q') => return Message::ToggleMacroRecording,
+            KeyCode::Char('@') => return Message::PlayMacro,
+            KeyCode::Char('.') => return Message::RepeatAction,
+            _ => {}
+        }
         if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
             return Message::Quit;
         }
+        if key.code == KeyCode::Char('w') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Message::SplitFocus;
+        }
+        if key.code == KeyCode::Char('E') && self.current_screen != Screen::ErrorLog {
+            return Message::OpenErrorLog;
+        }
 
         // Screen-specific
         match self.current_screen {
@@ -342,24 +1305,103 @@ impl App {
                 KeyCode::Enter => Message::Enter,
                 KeyCode::Esc => Message::Back,
                 KeyCode::Char('/') => Message::BrowserSearchActivate,
+                KeyCode::Char('!') => Message::OpenShell,
+                KeyCode::Char(' ') => Message::ToggleMark,
+                KeyCode::Char('d') => Message::DeleteFiles,
+                KeyCode::Char('u') => Message::RestoreDeleted,
+                KeyCode::Char('=') => Message::CompareSchemas,
+                KeyCode::Char('V') => Message::PasteJson,
                 _ => Message::Noop,
             },
+            Screen::DataInspector if self.active_data().is_some_and(|s| s.tab == InspectorTab::Chart) => {
+                match key.code {
+                    KeyCode::Tab => Message::SwitchTab,
+                    KeyCode::Up | KeyCode::Char('k') => Message::ChartCursorUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::ChartCursorDown,
+                    KeyCode::Char('X') => Message::ChartSetX,
+                    KeyCode::Char(' ') => Message::ChartToggleY,
+                    KeyCode::Enter => Message::PlotChart,
+                    KeyCode::Char('s') => Message::OpenScatter,
+                    KeyCode::Esc => Message::Back,
+                    KeyCode::Char(']') => Message::NextSession,
+                    KeyCode::Char('[') => Message::PrevSession,
+                    KeyCode::Char('x') => Message::CloseSession,
+                    KeyCode::Char('|') => Message::ToggleSplit,
+                    _ => Message::Noop,
+                }
+            }
             Screen::DataInspector => match key.code {
                 KeyCode::Tab => Message::SwitchTab,
-                KeyCode::Up | KeyCode::Char('k') => Message::PrevPage,
-                KeyCode::Down | KeyCode::Char('j') => Message::NextPage,
+                KeyCode::Enter => Message::LoadAllStats,
+                KeyCode::Char('#') => Message::CountRows,
+                KeyCode::Char('P') => Message::ExplainQuery,
+                KeyCode::Char('S') => Message::OpenSavePreset,
+                KeyCode::Char('F') => Message::OpenPresetPicker,
+                KeyCode::Char('H') => Message::OpenHistory,
+                KeyCode::Char('a') => Message::OpenComputedColumn,
+                KeyCode::Char('A') => Message::RemoveComputedColumn,
+                KeyCode::Char('T') => Message::OpenColumnType,
+                KeyCode::Char('O') => Message::OpenCsvOptions,
+                KeyCode::Up | KeyCode::Char('k') => Message::RowUp,
+                KeyCode::Down | KeyCode::Char('j') => Message::RowDown,
+                KeyCode::PageUp => Message::ScreenUp,
+                KeyCode::PageDown => Message::ScreenDown,
+                KeyCode::Home => Message::FirstRow,
+                KeyCode::End => Message::LastRow,
                 KeyCode::Char('c') => Message::ConvertFile,
                 KeyCode::Char('f') => Message::OpenFilterPopup,
+                KeyCode::Char('y') => Message::OpenCopyPage,
+                KeyCode::Char('R') => Message::WriteReport,
+                KeyCode::Char('L') => Message::LintCsv,
+                KeyCode::Char('W') => Message::ToggleFollow,
+                KeyCode::Char('G') => Message::OpenRowGroups,
+                KeyCode::Char('M') => Message::OpenGeoMap,
+                KeyCode::Char('J') => Message::ExportGeoJson,
                 KeyCode::Esc => Message::Back,
                 KeyCode::Right => Message::ColRight,
                 KeyCode::Left => Message::ColLeft,
+                KeyCode::Char('K') => Message::TogglePinColumn,
+                KeyCode::Char('e') => Message::ToggleExpandColumn,
+                KeyCode::Char('<') => Message::ResizeColumn(false),
+                KeyCode::Char('>') => Message::ResizeColumn(true),
+                KeyCode::Char('u') => Message::UnnestColumn,
+                KeyCode::Char('U') => Message::ClearUnnest,
+                KeyCode::Char('v') => Message::ShowRowDetail,
+                KeyCode::Char('t') => Message::ShowTimestamp,
                 KeyCode::Char('l') => Message::NextColPage,
                 KeyCode::Char('h') => Message::PrevColPage,
+                KeyCode::Char(']') => Message::NextSession,
+                KeyCode::Char('[') => Message::PrevSession,
+                KeyCode::Char('x') => Message::CloseSession,
+                KeyCode::Char('|') => Message::ToggleSplit,
                 _ => Message::Noop,
             },
+            Screen::JsonInspector
+                if self.active_json().is_some_and(|s| s.is_geojson() && s.geo_tab == GeoJsonTab::Features) =>
+            {
+                match key.code {
+                    KeyCode::Tab => Message::SwitchGeoTab,
+                    KeyCode::Up | KeyCode::Char('k') => Message::ScrollUp,
+                    KeyCode::Down | KeyCode::Char('j') => Message::ScrollDown,
+                    KeyCode::Left | KeyCode::Char('h') => Message::FeatureColLeft,
+                    KeyCode::Right | KeyCode::Char('l') => Message::FeatureColRight,
+                    KeyCode::Char('d') => Message::ToggleDropProperty,
+                    KeyCode::Char('u') => Message::ToggleDedupe,
+                    KeyCode::Char('w') => Message::WritePruned,
+                    KeyCode::Char('f') => Message::ToggleFollow,
+                    KeyCode::Char('J') => Message::ExportGeoJson,
+                    KeyCode::Char('T') => Message::OpenTiles,
+                    KeyCode::Esc => Message::Back,
+                    KeyCode::Char(']') => Message::NextSession,
+                    KeyCode::Char('[') => Message::PrevSession,
+                    KeyCode::Char('x') => Message::CloseSession,
+                    KeyCode::Char('|') => Message::ToggleSplit,
+                    _ => Message::Noop,
+                }
+            }
             Screen::JsonInspector => match key.code {
                 KeyCode::Tab => {
-                    if self.json_kind == Some(crate::commands::json_inspector::FileKind::GeoJson) {
+                    if self.active_json().is_some_and(|s| s.is_geojson()) {
                         Message::SwitchGeoTab
                     } else {
                         Message::SwitchTab
@@ -368,6 +1410,40 @@ impl App {
                 KeyCode::Up | KeyCode::Char('k') => Message::ScrollUp,
                 KeyCode::Down | KeyCode::Char('j') => Message::ScrollDown,
                 KeyCode::Enter => Message::ToggleTreeNode,
+                KeyCode::Char('f') => Message::ToggleFollow,
+                KeyCode::Char('J') => Message::ExportGeoJson,
+                KeyCode::Char('T') => Message::OpenTiles,
+                KeyCode::Esc => Message::Back,
+                KeyCode::Char(']') => Message::NextSession,
+                KeyCode::Char('[') => Message::PrevSession,
+                KeyCode::Char('x') => Message::CloseSession,
+                KeyCode::Char('|') => Message::ToggleSplit,
+                _ => Message::Noop,
+            },
+            Screen::ErrorLog => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => Message::ScrollUp,
+                KeyCode::Down | KeyCode::Char('j') => Message::ScrollDown,
+                KeyCode::Char('c') => Message::ClearErrorLog,
+                KeyCode::Esc => Message::Back,
+                _ => Message::Noop,
+            },
+            Screen::Scratchpad => Message::Noop,
+            Screen::Http | Screen::Decode => Message::Noop,
+            Screen::Env => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => Message::ScrollUp,
+                KeyCode::Down | KeyCode::Char('j') => Message::ScrollDown,
+                KeyCode::Char('/') => Message::EnvSearchStart,
+                KeyCode::Char('m') => Message::EnvToggleReveal,
+                KeyCode::Char('r') => Message::EnvReload,
+                KeyCode::Esc => Message::Back,
+                _ => Message::Noop,
+            },
+            Screen::Catalog => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => Message::ScrollUp,
+                KeyCode::Down | KeyCode::Char('j') => Message::ScrollDown,
+                KeyCode::Enter => Message::Enter,
+                KeyCode::Char('/') => Message::CatalogSearchStart,
+                KeyCode::Char('r') => Message::CatalogRefresh,
                 KeyCode::Esc => Message::Back,
                 _ => Message::Noop,
             },
@@ -375,8 +1451,26 @@ impl App {
     }
 
     pub fn update(&mut self, message: Message) {
+        if message.writes_files() && crate::config::get().is_readonly() {
+            self.show_error("ftool is in read-only mode (--readonly): files can't be written");
+            return;
+        }
         match message {
             Message::Quit => self.should_quit = true,
+            Message::ToggleMacroRecording => {
+                let recording = self.macros.toggle_recording();
+                tracing::debug!(recording, "macro recording");
+            }
+            Message::PlayMacro => {
+                if !self.macros.play() {
+                    self.show_macro_hint("No macro recorded yet: press Q, type the keys, then Q again");
+                }
+            }
+            Message::RepeatAction => {
+                if !self.macros.repeat() {
+                    self.show_macro_hint("Nothing to repeat yet: . repeats the last action that opened a popup");
+                }
+            }
             Message::NavigateUp => self.navigate_up(),
             Message::NavigateDown => self.navigate_down(),
             Message::Enter => self.enter(),
@@ -386,15 +1480,136 @@ impl App {
             Message::ScrollDown => self.scroll_down(),
             Message::ConvertFile => self.convert_file(),
             Message::ConfirmConvert => self.confirm_convert(),
+            Message::ToggleVerify => {
+                if let Popup::ConvertConfirm { ref mut verify, .. } = self.popup {
+                    *verify = !*verify;
+                }
+            }
             Message::ClosePopup => self.popup = Popup::None,
             Message::ToggleTreeNode => self.toggle_tree_node(),
+            Message::ToggleFollow => {
+                if let Some(s) = self.active_json_mut() {
+                    s.toggle_follow();
+                } else {
+                    self.with_data(|s| s.toggle_follow());
+                }
+            }
+            Message::FollowCounted(path, result) => self.follow_counted(path, result),
+            Message::FeatureColLeft => {
+                if let Some(s) = self.active_json_mut() {
+                    s.features_col_left();
+                }
+            }
+            Message::FeatureColRight => {
+                if let Some(s) = self.active_json_mut() {
+                    s.features_col_right();
+                }
+            }
+            Message::ToggleDropProperty => {
+                if let Some(s) = self.active_json_mut() {
+                    s.toggle_drop_property();
+                }
+            }
+            Message::ToggleDedupe => {
+                if let Some(s) = self.active_json_mut() {
+                    s.toggle_dedupe();
+                }
+            }
+            Message::WritePruned => self.write_pruned(),
+            Message::PrunedWritten(result) => match result {
+                Ok(path) => {
+                    self.popup = Popup::Converted(ConvertedState {
+                        path,
+                        verification: None,
+                        rename: None,
+                        error: None,
+                    });
+                }
+                Err(e) => self.show_error(e),
+            },
+            Message::OpenTiles => self.open_tiles(),
+            Message::TilesFieldUp => self.move_tiles_field(false),
+            Message::TilesFieldDown => self.move_tiles_field(true),
+            Message::TilesValueDown => self.change_tiles_value(false),
+            Message::TilesValueUp => self.change_tiles_value(true),
+            Message::TilesSaveStart => {
+                if let Popup::Tiles(state) = &mut self.popup {
+                    state.naming = Some(String::new());
+                    state.error = None;
+                }
+            }
+            Message::TilesNameChar(c) => {
+                if let Popup::Tiles(TilesState { naming: Some(name), .. }) = &mut self.popup {
+                    name.push(c);
+                }
+            }
+            Message::TilesNameBackspace => {
+                if let Popup::Tiles(TilesState { naming: Some(name), .. }) = &mut self.popup {
+                    name.pop();
+                }
+            }
+            Message::TilesNameCancel => {
+                if let Popup::Tiles(state) = &mut self.popup {
+                    state.naming = None;
+                    state.error = None;
+                }
+            }
+            Message::TilesNameConfirm => self.save_tile_preset(),
+            Message::TilesBuild => self.build_tiles(),
+            Message::TilesBuilt(result) => match result {
+                Ok(check) => self.popup = Popup::TileCheck(check),
+                Err(e) => self.show_error(format!("{:#}", e)),
+            },
             Message::SwitchGeoTab => self.switch_geo_tab(),
-            Message::NextPage => self.next_page(),
-            Message::PrevPage => self.prev_page(),
+            Message::RowDown => self.with_data(|s| s.scroll_rows(1)),
+            Message::RowUp => self.with_data(|s| s.scroll_rows(-1)),
+            Message::ScreenDown => self.with_data(|s| s.scroll_screen(true)),
+            Message::ScreenUp => self.with_data(|s| s.scroll_screen(false)),
+            Message::FirstRow => self.with_data(|s| s.scroll_rows(isize::MIN)),
+            Message::LastRow => {
+                // Where the file really ends is only known once it's counted
+                self.count_rows_in_background();
+                self.with_data(|s| s.scroll_rows(isize::MAX))
+            }
             Message::NextColPage => self.next_col_page(),
             Message::PrevColPage => self.prev_col_page(),
             Message::ColLeft => self.col_left(),
             Message::ColRight => self.col_right(),
+            Message::TogglePinColumn => self.with_data(|s| s.toggle_pin()),
+            Message::ChartCursorUp => {
+                if let Some(s) = self.active_data_mut() {
+                    s.chart_cursor_up();
+                }
+            }
+            Message::ChartCursorDown => {
+                if let Some(s) = self.active_data_mut() {
+                    s.chart_cursor_down();
+                }
+            }
+            Message::ChartSetX => self.with_data(|s| s.chart_set_x()),
+            Message::ChartToggleY => self.with_data(|s| s.chart_toggle_y()),
+            Message::PlotChart => self.with_data(|s| s.plot_chart()),
+            Message::OpenScatter => self.open_scatter(),
+            Message::ToggleExpandColumn => {
+                if let Some(s) = self.active_data_mut() {
+                    s.expand_selected = !s.expand_selected;
+                }
+            }
+            Message::ResizeColumn(grow) => self.resize_column(grow),
+            Message::UnnestColumn => self.with_data(|s| match s.focused_column() {
+                Some(column) => s.unnest_column(&column),
+                None => Ok(()),
+            }),
+            Message::ClearUnnest => self.with_data(|s| s.clear_unnest()),
+            Message::ShowRowDetail => {
+                if self.active_data().is_some_and(|s| s.tab == InspectorTab::Schema) {
+                    self.show_column_detail();
+                } else {
+                    self.show_row_detail();
+                }
+            }
+            Message::ShowTimestamp => self.show_selected_timestamp(),
+            Message::PreviewClick(x, y) => self.preview_click(x, y),
             Message::OpenFilterPopup => self.open_filter_popup(),
             Message::FilterTabNext => self.filter_tab_next(),
             Message::FilterNavUp => self.filter_nav_up(),
@@ -403,11 +1618,542 @@ impl App {
             Message::FilterBackspace => self.filter_backspace(),
             Message::FilterAddCondition => self.filter_add_condition(),
             Message::FilterRemoveLast => self.filter_remove_last(),
+            Message::FilterCycleLikeMode => self.filter_cycle_like_mode(),
+            Message::FilterToggleCase => self.filter_toggle_case(),
             Message::FilterApplyWithCurrent => self.filter_apply_with_current(),
             Message::BrowserSearchActivate => self.browser_search_activate(),
             Message::BrowserSearchChar(c) => self.browser_search_char(c),
             Message::BrowserSearchBackspace => self.browser_search_backspace(),
             Message::BrowserSearchExit => self.browser_search_exit(),
+            Message::NextSession => self.next_session(),
+            Message::PrevSession => self.prev_session(),
+            Message::CloseSession => self.close_session(),
+            Message::ToggleSplit => self.toggle_split(),
+            Message::SplitFocus => self.split_focus(),
+            Message::RestoreSession => self.restore_session(),
+            Message::LargeFileUp => {
+                if let Popup::LargeFile(state) = &mut self.popup {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+            }
+            Message::LargeFileDown => {
+                if let Popup::LargeFile(state) = &mut self.popup
+                    && state.selected + 1 < state.modes.len()
+                {
+                    state.selected += 1;
+                }
+            }
+            Message::LargeFileOpen => {
+                if let Popup::LargeFile(state) = std::mem::replace(&mut self.popup, Popup::None) {
+                    let mode = state.mode();
+                    self.open_file_as(state.path, mode);
+                }
+            }
+            Message::Tick => self.on_tick(),
+            Message::CancelTasks => {
+                self.tasks.cancel_all();
+            }
+            Message::TaskCancelled(msg) => {
+                // Apply the result so loading state is cleared, without an error popup
+                self.cancelling = true;
+                self.update(*msg);
+                self.cancelling = false;
+            }
+            Message::PasteJson => self.paste_json(),
+            Message::HttpChar(c) => self.with_http(|h| h.push(c)),
+            Message::HttpBackspace => self.with_http(HttpClientState::backspace),
+            Message::HttpNextField => self.with_http(|h| h.next_field(true)),
+            Message::HttpPrevField => self.with_http(|h| h.next_field(false)),
+            Message::HttpMethodNext => self.with_http(|h| h.cycle_method(true)),
+            Message::HttpMethodPrev => self.with_http(|h| h.cycle_method(false)),
+            Message::HttpHistoryUp => self.with_http(HttpClientState::history_up),
+            Message::HttpHistoryDown => self.with_http(HttpClientState::history_down),
+            Message::HttpLoadHistory => self.with_http(HttpClientState::load_selected),
+            Message::HttpSend => self.send_http(),
+            Message::HttpResponded(request, result) => self.http_responded(request, result),
+            Message::EnvSearchStart => self.with_env(|e| e.searching = true),
+            Message::EnvSearchChar(c) => self.with_env(|e| e.push_search(c)),
+            Message::EnvSearchBackspace => self.with_env(|e| e.pop_search()),
+            Message::EnvSearchDone => self.with_env(|e| e.searching = false),
+            Message::EnvSearchClear => self.with_env(|e| {
+                e.searching = false;
+                e.search.clear();
+                e.scroll = 0;
+            }),
+            Message::EnvToggleReveal => self.with_env(|e| e.reveal = !e.reveal),
+            Message::EnvReload => {
+                if let Some(Err(e)) = self.env.as_mut().map(EnvState::reload) {
+                    self.show_error(e);
+                }
+            }
+            Message::CatalogSearchStart => self.with_catalog(|c| c.searching = true),
+            Message::CatalogSearchChar(c) => self.with_catalog(|s| s.push_search(c)),
+            Message::CatalogSearchBackspace => self.with_catalog(CatalogState::pop_search),
+            Message::CatalogSearchDone => self.with_catalog(|c| c.searching = false),
+            Message::CatalogSearchClear => self.with_catalog(|c| {
+                c.searching = false;
+                c.search.clear();
+                c.selected = 0;
+            }),
+            Message::CatalogRefresh => self.refresh_catalog(),
+            Message::CatalogRefreshed(result) => match result {
+                Ok((catalog, stats)) => self.with_catalog(|c| c.set_catalog(catalog, stats.describe())),
+                Err(e) => self.show_error(format!("Could not refresh the catalog: {:#}", e)),
+            },
+            Message::OpenErrorLog => {
+                self.error_log_return = self.current_screen;
+                self.error_log_scroll = 0;
+                self.current_screen = Screen::ErrorLog;
+            }
+            Message::ClearErrorLog => {
+                self.error_log.clear();
+                self.error_log_scroll = 0;
+            }
+            Message::ScratchpadChar(c) => self.scratchpad.push(c),
+            Message::ScratchpadBackspace => self.scratchpad.backspace(),
+            Message::ScratchpadClear => self.scratchpad.set_input(String::new()),
+            Message::ScratchpadFormat => self.scratchpad.next_format(),
+            Message::ScratchpadPaste => match self.paste_from_clipboard() {
+                Ok(text) => self.scratchpad.set_input(text),
+                Err(e) => self.show_error(format!("Could not read the clipboard: {}", e)),
+            },
+            Message::ScratchpadCopy => {
+                self.scratchpad.refresh();
+                if let Some(text) = self.scratchpad.output_text() {
+                    let label = self.scratchpad.output.label();
+                    match self.copy_to_clipboard(text) {
+                        Ok(()) => {
+                            self.popup = Popup::Message {
+                                title: "Copied".to_string(),
+                                body: format!("{} copied to clipboard", label),
+                            }
+                        }
+                        Err(e) => self.show_error(format!("Could not copy to the clipboard: {}", e)),
+                    }
+                }
+            }
+            Message::DecodeChar(c) => self.decode.push(c),
+            Message::DecodeBackspace => self.decode.backspace(),
+            Message::DecodeClear => self.decode.set_input(String::new()),
+            Message::DecodeEncoding => self.decode.next_encoding(),
+            Message::DecodePaste => match self.paste_from_clipboard() {
+                Ok(text) => self.decode.set_input(text),
+                Err(e) => self.show_error(format!("Could not read the clipboard: {}", e)),
+            },
+            Message::DecodeCopy => {
+                self.decode.refresh();
+                if let Some(text) = self.decode.output_text() {
+                    match self.copy_to_clipboard(text) {
+                        Ok(()) => {
+                            self.popup = Popup::Message {
+                                title: "Copied".to_string(),
+                                body: "Decoded text copied to clipboard".to_string(),
+                            }
+                        }
+                        Err(e) => self.show_error(format!("Could not copy to the clipboard: {}", e)),
+                    }
+                }
+            }
+            Message::DecodeOpen => self.open_decoded(),
+            Message::OpenShell => self.pending_shell = Some(self.current_dir.clone()),
+            Message::ShellExited(result) => self.shell_exited(result),
+            Message::OpenCopyPage => self.open_copy_page(),
+            Message::CopyPageFormat => self.copy_page_format(),
+            Message::CopyPageTarget => {
+                if let Popup::CopyPage(state) = &mut self.popup {
+                    state.to_file = !state.to_file;
+                }
+            }
+            Message::CopyPageChar(c) => {
+                if let Popup::CopyPage(state) = &mut self.popup {
+                    state.path_input.push(c);
+                }
+            }
+            Message::CopyPageBackspace => {
+                if let Popup::CopyPage(state) = &mut self.popup {
+                    state.path_input.pop();
+                }
+            }
+            Message::CopyPageConfirm => self.copy_page_confirm(),
+            Message::OpenTransformEditor => self.open_transform_editor(),
+            Message::TransformNavUp => {
+                if let Popup::TransformEditor(ref mut state) = self.popup {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+            }
+            Message::TransformNavDown => {
+                if let Popup::TransformEditor(ref mut state) = self.popup
+                    && state.selected + 1 < state.columns.len()
+                {
+                    state.selected += 1;
+                }
+            }
+            Message::TransformToggleKeep => {
+                if let Popup::TransformEditor(ref mut state) = self.popup
+                    && let Some(c) = state.columns.get_mut(state.selected)
+                {
+                    c.keep = !c.keep;
+                }
+            }
+            Message::TransformTogglePartition => {
+                if let Popup::TransformEditor(ref mut state) = self.popup
+                    && let Some(c) = state.columns.get_mut(state.selected)
+                {
+                    c.partition = !c.partition;
+                }
+            }
+            Message::TransformCycleMask => {
+                if let Popup::TransformEditor(ref mut state) = self.popup
+                    && let Some(c) = state.columns.get_mut(state.selected)
+                {
+                    c.mask = Mask::cycle(c.mask);
+                }
+            }
+            Message::TransformEdit(field) => self.transform_edit(field),
+            Message::TransformChar(c) => {
+                if let Popup::TransformEditor(ref mut state) = self.popup {
+                    state.input.push(c);
+                }
+            }
+            Message::TransformBackspace => {
+                if let Popup::TransformEditor(ref mut state) = self.popup {
+                    state.input.pop();
+                }
+            }
+            Message::TransformCommitEdit => self.transform_commit_edit(),
+            Message::TransformCancelEdit => {
+                if let Popup::TransformEditor(ref mut state) = self.popup {
+                    state.editing = None;
+                    state.input.clear();
+                }
+            }
+            Message::TransformConfirm => self.transform_confirm(),
+            Message::ToggleMark => self.toggle_mark(),
+            Message::DeleteFiles => self.ask_delete(),
+            Message::ConfirmDelete => self.delete_files(),
+            Message::RestoreDeleted => self.restore_deleted(),
+            Message::CompareSchemas => self.compare_schemas(),
+            Message::SchemaCompared(left, right, result) => match result {
+                Ok(diff) => {
+                    let mut lines = vec![
+                        format!("old: {}", left.display()),
+                        format!("new: {}", right.display()),
+                        String::new(),
+                    ];
+                    lines.extend(diff.report());
+                    self.popup = Popup::Text {
+                        title: "Schema comparison".to_string(),
+                        lines,
+                        scroll: 0,
+                    };
+                }
+                Err(e) => self.show_error(e),
+            },
+            Message::PopupScrollUp => {
+                if let Popup::Text { ref mut scroll, .. } = self.popup {
+                    *scroll = scroll.saturating_sub(1);
+                }
+            }
+            Message::PopupScrollDown => {
+                if let Popup::Text { ref lines, ref mut scroll, .. } = self.popup
+                    && *scroll + 1 < lines.len()
+                {
+                    *scroll += 1;
+                }
+            }
+            Message::WriteReport => self.write_report(),
+            Message::ReportWritten(result) => match result {
+                Ok(path) => {
+                    self.popup = Popup::Message {
+                        title: "Report".to_string(),
+                        body: format!("Report written to {}", path.display()),
+                    };
+                }
+                Err(e) => self.show_error(e),
+            },
+            Message::LintCsv => self.lint_csv(),
+            Message::CsvLinted(path, result) => match result {
+                Ok(report) => {
+                    self.popup = Popup::Text {
+                        title: format!("Lint {}", path.file_name().unwrap_or_default().to_string_lossy()),
+                        lines: report.report(),
+                        scroll: 0,
+                    };
+                }
+                Err(e) => self.show_error(e),
+            },
+            Message::SessionLoaded(path, result) => self.session_loaded(path, result),
+            Message::LoadAllStats => {
+                let columns = self.active_data().map(|s| s.all_missing_stats());
+                self.load_stats_in_background(columns.unwrap_or_default());
+            }
+            Message::CountRows => self.count_rows_in_background(),
+            Message::ExplainQuery => self.explain_query(),
+            Message::OpenRowGroups => self.open_row_groups(),
+            Message::OpenGeoMap => self.open_geo_map(),
+            Message::GeoMapLoaded(result) => match result {
+                Ok(map) => self.popup = Popup::Map(map),
+                Err(e) => self.show_error(e),
+            },
+            Message::ExportGeoJson => self.export_geojson(),
+            Message::GeoJsonExported(result) => match result {
+                Ok(path) => {
+                    self.popup = Popup::Converted(ConvertedState {
+                        path: PathBuf::from(path),
+                        verification: None,
+                        rename: None,
+                        error: None,
+                    });
+                }
+                Err(e) => self.show_error(e),
+            },
+            Message::RowGroupUp => {
+                if let Popup::RowGroups(state) = &mut self.popup {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+            }
+            Message::RowGroupDown => {
+                if let Popup::RowGroups(state) = &mut self.popup
+                    && state.selected + 1 < state.groups.len()
+                {
+                    state.selected += 1;
+                }
+            }
+            Message::RowGroupJump => self.jump_to_row_group(),
+            Message::OpenSavePreset => self.open_save_preset(),
+            Message::PresetChar(c) => {
+                if let Popup::SavePreset(state) = &mut self.popup {
+                    state.name.push(c);
+                }
+            }
+            Message::PresetBackspace => {
+                if let Popup::SavePreset(state) = &mut self.popup {
+                    state.name.pop();
+                }
+            }
+            Message::PresetToggleScope => {
+                if let Popup::SavePreset(state) = &mut self.popup {
+                    state.global = !state.global;
+                }
+            }
+            Message::PresetSaveConfirm => self.save_preset(),
+            Message::OpenPresetPicker => self.open_preset_picker(),
+            Message::PresetNavUp => {
+                if let Popup::PresetPicker(state) = &mut self.popup {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+            }
+            Message::PresetNavDown => {
+                if let Popup::PresetPicker(state) = &mut self.popup
+                    && state.selected + 1 < state.presets.len()
+                {
+                    state.selected += 1;
+                }
+            }
+            Message::PresetApply => self.apply_preset(),
+            Message::PresetDelete => self.delete_preset(),
+            Message::OpenHistory => {
+                self.popup = Popup::History(HistoryState {
+                    entries: history::load(),
+                    query: String::new(),
+                    selected: 0,
+                });
+            }
+            Message::HistoryChar(c) => {
+                if let Popup::History(state) = &mut self.popup {
+                    state.query.push(c);
+                    state.selected = 0;
+                }
+            }
+            Message::HistoryBackspace => {
+                if let Popup::History(state) = &mut self.popup {
+                    state.query.pop();
+                    state.selected = 0;
+                }
+            }
+            Message::HistoryNavUp => {
+                if let Popup::History(state) = &mut self.popup {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+            }
+            Message::HistoryNavDown => {
+                if let Popup::History(state) = &mut self.popup
+                    && state.selected + 1 < state.visible().len()
+                {
+                    state.selected += 1;
+                }
+            }
+            Message::HistoryApply => self.apply_history(),
+            Message::OpenPalette => self.popup = Popup::Palette(PaletteState::default()),
+            Message::PaletteChar(c) => {
+                if let Popup::Palette(state) = &mut self.popup {
+                    state.query.push(c);
+                    state.selected = 0;
+                }
+            }
+            Message::PaletteBackspace => {
+                if let Popup::Palette(state) = &mut self.popup {
+                    state.query.pop();
+                    state.selected = 0;
+                }
+            }
+            Message::PaletteNavUp => {
+                if let Popup::Palette(state) = &mut self.popup {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+            }
+            Message::PaletteNavDown => {
+                if let Popup::Palette(state) = &mut self.popup
+                    && state.selected + 1 < state.visible().len()
+                {
+                    state.selected += 1;
+                }
+            }
+            Message::PaletteRun => self.run_palette(),
+            Message::OpenComputedColumn => {
+                if let Some(s) = self.active_data() {
+                    self.popup = Popup::ComputedColumn(ComputedColumnState {
+                        input: String::new(),
+                        export: s.computed_in_export,
+                        completion: Completion::default(),
+                    });
+                }
+            }
+            Message::ComputedChar(c) => {
+                if let Popup::ComputedColumn(state) = &mut self.popup {
+                    state.input.push(c);
+                }
+                self.update_computed_completion();
+            }
+            Message::ComputedBackspace => {
+                if let Popup::ComputedColumn(state) = &mut self.popup {
+                    state.input.pop();
+                }
+                self.update_computed_completion();
+            }
+            Message::ComputedToggleExport => {
+                if let Popup::ComputedColumn(state) = &mut self.popup {
+                    state.export = !state.export;
+                }
+            }
+            Message::ComputedConfirm => self.add_computed_column(),
+            Message::CompletionUp => {
+                if let Some(completion) = self.popup_completion() {
+                    completion.up();
+                }
+            }
+            Message::CompletionDown => {
+                if let Some(completion) = self.popup_completion() {
+                    completion.down();
+                }
+            }
+            Message::CompletionAccept => self.accept_completion(),
+            Message::FilterSearchStart => {
+                if let Popup::FilterEditor(state) = &mut self.popup {
+                    state.column_search = Some(String::new());
+                }
+                self.update_filter_completion();
+            }
+            Message::FilterSearchChar(c) => {
+                if let Popup::FilterEditor(FilterEditorState { column_search: Some(query), .. }) = &mut self.popup {
+                    query.push(c);
+                }
+                self.update_filter_completion();
+            }
+            Message::FilterSearchBackspace => {
+                if let Popup::FilterEditor(FilterEditorState { column_search: Some(query), .. }) = &mut self.popup {
+                    query.pop();
+                }
+                self.update_filter_completion();
+            }
+            Message::FilterSearchCancel => {
+                if let Popup::FilterEditor(state) = &mut self.popup {
+                    state.column_search = None;
+                    state.completion = Completion::default();
+                }
+            }
+            Message::RemoveComputedColumn => self.with_data(|s| s.remove_last_computed()),
+            Message::OpenColumnType => self.open_column_type(),
+            Message::UndoWrite => self.undo_write(),
+            Message::OpenConverted => {
+                if let Popup::Converted(state) = &self.popup {
+                    let path = state.path.clone();
+                    self.popup = Popup::None;
+                    if path.is_dir() {
+                        // Partitioned output: browse the partitions
+                        self.current_dir = path;
+                        self.browser_selected = 0;
+                        match self.load_dir_entries() {
+                            Ok(()) => self.current_screen = Screen::FileBrowser,
+                            Err(e) => self.show_error(e),
+                        }
+                    } else {
+                        self.open_file(&path);
+                    }
+                }
+            }
+            Message::ConvertedRenameStart => {
+                if let Popup::Converted(state) = &mut self.popup {
+                    let name = state.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    state.rename = Some(name);
+                }
+            }
+            Message::ConvertedRenameChar(c) => {
+                if let Popup::Converted(ConvertedState { rename: Some(name), .. }) = &mut self.popup {
+                    name.push(c);
+                }
+            }
+            Message::ConvertedRenameBackspace => {
+                if let Popup::Converted(ConvertedState { rename: Some(name), .. }) = &mut self.popup {
+                    name.pop();
+                }
+            }
+            Message::ConvertedRenameCancel => {
+                if let Popup::Converted(state) = &mut self.popup {
+                    state.rename = None;
+                }
+            }
+            Message::ConvertedRenameConfirm => self.rename_converted(),
+            Message::ColumnTypeChar(c) => {
+                if let Popup::ColumnType(state) = &mut self.popup {
+                    state.input.push(c);
+                }
+            }
+            Message::ColumnTypeBackspace => {
+                if let Popup::ColumnType(state) = &mut self.popup {
+                    state.input.pop();
+                }
+            }
+            Message::ColumnTypeConfirm => {
+                let Popup::ColumnType(state) = self.popup.clone() else { return };
+                self.popup = Popup::None;
+                self.with_data(|s| s.set_column_type(state.column, &state.input));
+            }
+            Message::OpenCsvOptions => self.open_csv_options(),
+            Message::CsvOptionsToggle => {
+                if let Popup::CsvOptions(state) = &mut self.popup {
+                    state.decimal_comma = !state.decimal_comma;
+                }
+            }
+            Message::CsvOptionsConfirm => {
+                let Popup::CsvOptions(state) = self.popup.clone() else { return };
+                self.popup = Popup::None;
+                if !state.decimal_comma {
+                    self.with_data(|s| {
+                        s.decimal_comma_pending = false;
+                        s.set_decimal_format(DecimalFormat::Point)
+                    });
+                } else if let Some(s) = self.active_data_mut() {
+                    s.decimal_comma_pending = true;
+                    self.detect_decimal_commas();
+                }
+            }
+            Message::RowCountLoaded(path, result) => self.row_count_loaded(path, result),
+            Message::DecimalCommaDetected(path, result) => self.decimal_comma_detected(path, result),
+            Message::GeoColumnsDetected(path, result) => self.geo_columns_detected(path, result),
+            Message::StatsLoaded(path, columns, result) => {
+                self.stats_loaded(path, columns, result)
+            }
             Message::Noop => {}
         }
     }
@@ -431,7 +2177,8 @@ impl App {
     fn navigate_down(&mut self) {
         match self.current_screen {
             Screen::Home => {
-                if self.home_selected < 1 {
+                // The menu items, then the dataset aliases
+                if self.home_selected + 1 < views::home::MENU.len() + crate::config::get().aliases.len() {
                     self.home_selected += 1;
                 }
             }
@@ -449,19 +2196,61 @@ impl App {
         }
     }
 
-    fn enter(&mut self) {
-        match self.current_screen {
-            Screen::Home => {
-                // Both options go to file browser
+    /// Runs the Home menu entry at `index` (dataset aliases follow the menu)
+    fn open_menu_entry(&mut self, index: usize) {
+        match index {
+            i if i >= views::home::MENU.len() => {
+                let target =
+                    crate::config::get().aliases.values().nth(i - views::home::MENU.len()).cloned();
+                if let Some(target) = target.map(PathBuf::from) {
+                    if target.is_dir() {
+                        self.current_dir = target;
+                        self.browser_selected = 0;
+                        if let Err(e) = self.load_dir_entries() {
+                            self.show_error(e);
+                            return;
+                        }
+                        self.current_screen = Screen::FileBrowser;
+                    } else {
+                        self.open_file(&target);
+                    }
+                }
+            }
+            views::home::SCRATCHPAD => {
+                self.current_screen = Screen::Scratchpad;
+            }
+            views::home::ENV => {
+                self.show_env(EnvState::process());
+            }
+            views::home::PASTE_JSON => self.paste_json(),
+            views::home::DECODE => {
+                self.current_screen = Screen::Decode;
+            }
+            views::home::HTTP_CLIENT => {
+                self.http.get_or_insert_with(HttpClientState::default);
+                self.current_screen = Screen::Http;
+            }
+            views::home::CATALOG => match CatalogState::load() {
+                Ok(state) => {
+                    self.catalog = Some(state);
+                    self.current_screen = Screen::Catalog;
+                }
+                Err(e) => self.show_error(e),
+            },
+            _ => {
+                // The other options go to file browser
                 if let Err(e) = self.load_dir_entries() {
-                    self.popup = Popup::Message {
-                        title: "Error".to_string(),
-                        body: e.to_string(),
-                    };
+                    self.show_error(e);
                     return;
                 }
                 self.current_screen = Screen::FileBrowser;
             }
+        }
+    }
+
+    fn enter(&mut self) {
+        match self.current_screen {
+            Screen::Home => self.open_menu_entry(self.home_selected),
             Screen::FileBrowser => {
                 let entry_path;
                 let entry_is_dir;
@@ -484,442 +2273,2084 @@ impl App {
                     self.current_dir = entry_path;
                     self.browser_selected = 0;
                     if let Err(e) = self.load_dir_entries() {
-                        self.popup = Popup::Message {
-                            title: "Error".to_string(),
-                            body: e.to_string(),
-                        };
+                        self.show_error(e);
                     }
                 } else {
-                    // Check if data file
-                    match entry_path.extension().and_then(|e| e.to_str()) {
-                        Some("csv") | Some("parquet") => {
-                            self.inspector_file = Some(entry_path.clone());
-                            match self.load_inspector_data(&entry_path) {
-                                Ok(()) => self.current_screen = Screen::DataInspector,
-                                Err(e) => {
-                                    self.popup = Popup::Message {
-                                        title: "Error".to_string(),
-                                        body: e.to_string(),
-                                    };
-                                }
-                            }
-                        }
-                        Some("json") | Some("geojson") => match self.load_json_data(&entry_path) {
-                            Ok(()) => self.current_screen = Screen::JsonInspector,
-                            Err(e) => {
-                                self.popup = Popup::Message {
-                                    title: "Error".to_string(),
-                                    body: e.to_string(),
-                                };
-                            }
-                        },
-                        _ => {} // Can't open non-data files
-                    }
+                    self.open_file(&entry_path);
                 }
             }
             Screen::DataInspector => {}
             Screen::JsonInspector => {}
+            Screen::ErrorLog => {}
+            Screen::Scratchpad => {}
+            Screen::Env => {}
+            Screen::Http => {}
+            Screen::Decode => {}
+            Screen::Catalog => {
+                let path = self.catalog.as_ref().and_then(|c| c.selected_entry()).map(|e| e.path.clone());
+                if let Some(path) = path {
+                    self.open_file(&path);
+                }
+            }
         }
     }
 
-    fn back(&mut self) {
-        match self.current_screen {
-            Screen::JsonInspector => {
-                self.current_screen = Screen::FileBrowser;
-            }
-            Screen::DataInspector => {
-                self.inspector = None;
-                // Go back to file browser
-                if self.dir_entries.is_empty() {
-                    if let Some(ref file) = self.inspector_file {
-                        if let Some(parent) = file.parent() {
-                            self.current_dir = parent.to_path_buf();
-                            let _ = self.load_dir_entries();
-                        }
-                    }
-                }
-                self.current_screen = Screen::FileBrowser;
-            }
-            Screen::FileBrowser => {
-                self.current_screen = Screen::Home;
+    /// Opens a data or JSON file in an inspector tab, reusing an existing tab
+    /// for the same path so its state is preserved.
+    fn open_file(&mut self, path: &Path) {
+        if let Some(idx) = self.sessions.iter().position(|s| s.path() == path) {
+            self.switch_session(idx);
+            return;
+        }
+
+        // .env files open in the environment view
+        if is_env_file(path) {
+            match EnvState::open(path) {
+                Ok(state) => self.show_env(state),
+                Err(e) => self.show_error(e),
             }
-            Screen::Home => {}
+            return;
+        }
+
+        // Too big to load without freezing: ask first
+        if let Some(large) = large_file::check(path) {
+            self.popup = Popup::LargeFile(large);
+            return;
         }
+        self.open_file_as(path.to_path_buf(), OpenMode::Full);
     }
 
-    fn switch_tab(&mut self) {
-        match self.current_screen {
-            Screen::JsonInspector => {
-                self.json_scroll = 0;
-                self.json_tab = match self.json_tab {
-                    JsonInspectorTab::Tree => JsonInspectorTab::Raw,
-                    JsonInspectorTab::Raw => JsonInspectorTab::Tree,
-                };
-            }
-            _ => {
-                self.inspector_scroll = 0;
-                self.inspector_tab = match self.inspector_tab {
-                    InspectorTab::Schema => InspectorTab::Preview,
-                    InspectorTab::Preview => {
-                        self.load_stats_if_needed();
-                        InspectorTab::Schema
-                    }
-                };
+    fn open_file_as(&mut self, path: PathBuf, mode: OpenMode) {
+        let name = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let label = format!("Loading {}", name);
+
+        // Check if data file
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") | Some("parquet") => self.tasks.spawn_cancellable(label, move |cancel| {
+                let result = DataInspectorState::open_with(&path, cancel, mode).map(|mut state| {
+                    file_state::restore(&mut state);
+                    Session::Data(state)
+                });
+                Message::SessionLoaded(path, result)
+            }),
+            Some("json") | Some("geojson") | Some("jsonl") | Some("ndjson") | Some("geojsonl")
+            | Some("geojsons") | Some("gpx") | Some("kml") => self.tasks.spawn(label, move || {
+                let result = JsonInspectorState::open_with(&path, mode).map(Session::Json);
+                Message::SessionLoaded(path, result)
+            }),
+            _ => {} // Can't open non-data files
+        }
+    }
+
+    fn session_loaded(&mut self, path: PathBuf, result: anyhow::Result<Session>) {
+        match result {
+            Ok(session) => match self.sessions.iter().position(|s| s.path() == path) {
+                // Opened twice while loading: keep the existing tab
+                Some(idx) => self.switch_session(idx),
+                None => self.open_session(session),
+            },
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    /// Back from the `!` shell: the directory may have changed, so rescan it
+    fn shell_exited(&mut self, result: anyhow::Result<()>) {
+        if let Err(e) = result {
+            self.show_error(e);
+            return;
+        }
+        // Keep the cursor on the same entry if it still exists
+        let selected = self.dir_entries.get(self.browser_selected).map(|e| e.name.clone());
+        if let Err(e) = self.load_dir_entries() {
+            self.show_error(e);
+            return;
+        }
+        if let Some(name) = selected
+            && let Some(idx) = self.dir_entries.iter().position(|e| e.name == name)
+        {
+            self.browser_selected = idx;
+        }
+    }
+
+    fn on_tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+        self.play_pending_keys();
+        self.update_dir_preview();
+        self.load_visible_stats();
+        self.poll_followed_files();
+        self.scratchpad.refresh();
+        self.decode.refresh();
+    }
+
+    /// Plays back queued macro keys while no background task runs, so a key
+    /// meant for a conversion's result waits for the conversion. A key that
+    /// ends in an error drops the rest.
+    fn play_pending_keys(&mut self) {
+        while self.tasks.current().is_none() {
+            let Some(key) = self.macros.next_pending() else { break };
+            let errors = self.error_log.entries.len();
+            let message = self.handle_key(key);
+            self.update(message);
+            if self.error_log.entries.len() > errors {
+                self.macros.stop();
             }
         }
     }
 
-    fn scroll_up(&mut self) {
+    /// Scans the directory selected in the browser for the preview panel,
+    /// dropping (and so cancelling) the scan of the one selected before
+    fn update_dir_preview(&mut self) {
+        let idx = if self.browser_search_active {
+            self.browser_filtered_indices.get(self.browser_selected).copied()
+        } else {
+            Some(self.browser_selected)
+        };
+        let selected = idx
+            .and_then(|i| self.dir_entries.get(i))
+            .filter(|entry| self.current_screen == Screen::FileBrowser && entry.is_dir && entry.name != "..")
+            .map(|entry| entry.path.clone());
+        if let Some(preview) = &mut self.dir_preview
+            && selected.as_ref() == Some(&preview.path)
+        {
+            preview.poll();
+        } else {
+            self.dir_preview = selected.map(DirPreview::start);
+        }
+    }
+
+    fn show_macro_hint(&mut self, body: &str) {
+        self.popup = Popup::Message { title: "Macro".to_string(), body: body.to_string() };
+    }
+
+    /// Reads records appended to followed JSON Lines files and recounts followed
+    /// data files that grew, in every session so background tabs stay current;
+    /// a JSON Lines file that can't be read stops being followed
+    fn poll_followed_files(&mut self) {
+        let mut errors = Vec::new();
+        let mut recounts = Vec::new();
+        for session in &mut self.sessions {
+            match session {
+                Session::Json(s) => {
+                    if let Err(e) = s.poll_follow() {
+                        s.following = false;
+                        errors.push(format!("Stopped following {}: {}", s.file.display(), e));
+                    }
+                }
+                Session::Data(s) => {
+                    if s.follow_due() {
+                        recounts.push((s.reopen_spec(), build_where_clause(&s.filters)));
+                    }
+                }
+            }
+        }
+        for error in errors {
+            self.show_error(error);
+        }
+        for (spec, where_clause) in recounts {
+            self.tasks.spawn_cancellable("Counting new rows", move |cancel| {
+                let result = spec.open(cancel).and_then(|i| i.row_count_filtered(&where_clause));
+                Message::FollowCounted(spec.path, result)
+            });
+        }
+    }
+
+    fn follow_counted(&mut self, path: PathBuf, result: Result<usize, DuckDbError>) {
+        let Some(state) = self.sessions.iter_mut().find_map(|s| match s {
+            Session::Data(d) if d.file == path => Some(d),
+            _ => None,
+        }) else {
+            return;
+        };
+        match result {
+            Ok(count) => {
+                if let Err(e) = state.follow_counted(count) {
+                    self.show_error(e);
+                }
+            }
+            Err(e) => {
+                // Usually a row still being written; counted again once the file grows
+                tracing::debug!(file = %path.display(), error = %e, "follow recount failed");
+                if let Some(follow) = state.follow.as_mut() {
+                    follow.counting = false;
+                }
+            }
+        }
+    }
+
+    /// Requests stats for the Schema tab rows currently in view
+    fn load_visible_stats(&mut self) {
+        let columns = self.active_data().map(|s| s.visible_missing_stats()).unwrap_or_default();
+        self.load_stats_in_background(columns);
+    }
+
+    /// Computes stats for `columns` of the active data session on a worker thread
+    fn load_stats_in_background(&mut self, columns: Vec<usize>) {
+        if columns.is_empty() {
+            return;
+        }
+        let Some(state) = self.active_data_mut() else {
+            return;
+        };
+        let (names, numeric): (Vec<String>, Vec<bool>) = state.start_stats(&columns).into_iter().unzip();
+        let spec = state.reopen_spec();
+        self.tasks.spawn_cancellable("Computing column stats", move |cancel| {
+            let result = spec.open(cancel).and_then(|i| {
+                let started = Instant::now();
+                let mut stats = i.column_stats(&names)?;
+                let numeric_names: Vec<String> = names
+                    .iter()
+                    .zip(&numeric)
+                    .filter(|(_, n)| **n)
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                let mut histograms = i.histograms(&numeric_names, HISTOGRAM_BUCKETS)?.into_iter();
+                for (stat, _) in stats.iter_mut().zip(&numeric).filter(|(_, n)| **n) {
+                    stat.histogram = histograms.next().unwrap_or_default();
+                }
+                Ok((stats, started.elapsed()))
+            });
+            Message::StatsLoaded(spec.path, columns, result)
+        });
+    }
+
+    fn stats_loaded(
+        &mut self,
+        path: PathBuf,
+        columns: Vec<usize>,
+        result: Result<(Vec<ColumnStats>, Duration), DuckDbError>,
+    ) {
+        let Some(state) = self.sessions.iter_mut().find_map(|s| match s {
+            Session::Data(d) if d.file == path => Some(d),
+            _ => None,
+        }) else {
+            return;
+        };
+        match result {
+            Ok((stats, elapsed)) => state.set_stats(&columns, stats, elapsed),
+            Err(e) => {
+                state.fail_stats(&columns);
+                self.show_error(e);
+            }
+        }
+    }
+
+    /// Shows every value of the top visible preview row untruncated
+    fn show_row_detail(&mut self) {
+        let Some(state) = self.active_data() else {
+            return;
+        };
+        if state.tab != InspectorTab::Preview {
+            return;
+        }
+        if let Some((number, lines)) = state.row_detail() {
+            self.popup = Popup::Text {
+                title: format!("Row {}", number),
+                lines,
+                scroll: 0,
+            };
+        }
+    }
+
+    /// Shows the selected column's top-row value converted to other time forms
+    fn show_selected_timestamp(&mut self) {
+        let Some(state) = self.active_data() else {
+            return;
+        };
+        if state.tab != InspectorTab::Preview {
+            return;
+        }
+        let col = state.preview_offset + state.selected_col;
+        let Some(header) = state.preview_headers.get(col).cloned() else {
+            return;
+        };
+        let value = state.preview_data.get(state.preview_skip()).and_then(|row| row.get(col)).cloned().flatten();
+        match value.as_deref().and_then(time::parse) {
+            Some(ts) => self.show_timestamp(&header, value.as_deref().unwrap_or_default(), &ts),
+            None => self.show_error(format!("{} is not a timestamp", value.as_deref().unwrap_or("NULL"))),
+        }
+    }
+
+    /// Narrows or widens the selected preview column (`<`/`>`) from the width
+    /// it is drawn at; a column expanded with `e` goes back to its own width first
+    fn resize_column(&mut self, grow: bool) {
+        let Some(s) = self.active_data_mut() else {
+            return;
+        };
+        if s.tab != InspectorTab::Preview {
+            return;
+        }
+        s.expand_selected = false;
+        let col = s.preview_offset + s.selected_col;
+        let Some(current) = views::data_inspector::preview_table(s).column_width(s.preview_area.get(), col) else {
+            return;
+        };
+        s.resize_selected_col(current, grow);
+    }
+
+    /// Selects the clicked preview cell's column and, if the cell holds a
+    /// timestamp, shows it converted to other forms
+    fn preview_click(&mut self, x: u16, y: u16) {
+        let Some(state) = self.active_data() else {
+            return;
+        };
+        if state.tab != InspectorTab::Preview {
+            return;
+        }
+        let Some((row, col)) = views::data_inspector::preview_table(state).cell_at(state.preview_area.get(), x, y) else {
+            return;
+        };
+        let header = state.preview_headers.get(col).cloned().unwrap_or_default();
+        let value = state.preview_data.get(row).and_then(|r| r.get(col)).cloned().flatten();
+        if let Some(s) = self.active_data_mut()
+            && col >= s.preview_offset
+        {
+            s.selected_col = col - s.preview_offset;
+        }
+        if let Some(value) = value
+            && let Some(ts) = time::parse_cell(&value)
+        {
+            self.show_timestamp(&header, &value, &ts);
+        }
+    }
+
+    fn show_timestamp(&mut self, column: &str, value: &str, ts: &Timestamp) {
+        let mut lines = vec![format!("{:<10}{}", "Value", value), String::new()];
+        lines.extend(ts.describe());
+        self.popup = Popup::Text {
+            title: format!("Timestamp: {}", column),
+            lines,
+            scroll: 0,
+        };
+    }
+
+    /// Shows the Parquet metadata of the top visible column on the Schema tab
+    fn show_column_detail(&mut self) {
+        let Some(state) = self.active_data() else {
+            return;
+        };
+        let Some((name, dtype)) = state.schema.get(state.scroll).cloned() else {
+            return;
+        };
+        match state.inspector.parquet_column_info(&name) {
+            Ok(info) => {
+                let mut lines = vec![format!("Type: {}", dtype), String::new()];
+                lines.extend(info.report());
+                self.popup = Popup::Text {
+                    title: format!("Column {}", name),
+                    lines,
+                    scroll: 0,
+                };
+            }
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    /// Lists the row groups of the active Parquet file
+    fn open_row_groups(&mut self) {
+        let Some(state) = self.active_data() else {
+            return;
+        };
+        match state.inspector.parquet_row_groups() {
+            Ok(groups) if groups.is_empty() => self.popup = Popup::Message {
+                title: "Row groups".to_string(),
+                body: "The file has no row groups".to_string(),
+            },
+            Ok(groups) => self.popup = Popup::RowGroups(RowGroupsState { groups, selected: 0 }),
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    /// The geo column under the cursor, or the file's first one, with what a
+    /// background job needs to reopen the file
+    fn focused_geo(&mut self) -> Option<GeoJob> {
+        let s = self.active_data()?;
+        if s.geo_pending {
+            self.detect_geo_columns();
+            self.popup = Popup::Message {
+                title: "Geo columns".to_string(),
+                body: "Still looking for geo columns; try again in a moment".to_string(),
+            };
+            return None;
+        }
+        let geo = s
+            .focused_column()
+            .and_then(|c| s.geo_column(&c))
+            .or_else(|| s.geo.first())
+            .cloned();
+        let found = geo.map(|g| (g, s.reopen_spec()));
+        if found.is_none() {
+            self.show_error("No geometry, WKT, WKB, GeoJSON or latitude/longitude columns found");
+        }
+        found
+    }
+
+    fn open_geo_map(&mut self) {
+        let Some((geo, spec)) = self.focused_geo() else { return };
+        self.tasks.spawn_cancellable(format!("Mapping {}", geo.label()), move |cancel| {
+            let result = spec
+                .open(cancel)
+                .and_then(|i| i.geo_points(&geo, MAP_SAMPLE))
+                .map(|(points, total)| GeoMap { label: geo.label(), points, total });
+            Message::GeoMapLoaded(result)
+        });
+    }
+
+    fn export_geojson(&mut self) {
+        if let Some(s) = self.active_json() {
+            if !s.is_geojson() {
+                return;
+            }
+            if s.partial {
+                self.show_error(SAMPLED_WRITE);
+                return;
+            }
+            match s.write_geojson() {
+                Ok(path) => {
+                    self.popup = Popup::Converted(ConvertedState {
+                        path,
+                        verification: None,
+                        rename: None,
+                        error: None,
+                    });
+                }
+                Err(e) => self.show_error(e),
+            }
+            return;
+        }
+        let Some((geo, spec)) = self.focused_geo() else { return };
+        if spec.row_limit().is_some() {
+            self.show_error(SAMPLED_WRITE);
+            return;
+        }
+        self.tasks.spawn_cancellable(format!("Exporting {} as GeoJSON", geo.label()), move |cancel| {
+            let result = spec.open(cancel).and_then(|i| i.export_geojson(&geo));
+            Message::GeoJsonExported(result)
+        });
+    }
+
+    /// Opens the PMTiles popup for the GeoJSON in the inspector, with the
+    /// first (Generic) preset selected and estimated
+    fn open_tiles(&mut self) {
+        let Some(s) = self.active_json().filter(|s| s.is_geojson()) else { return };
+        let input = s.file.clone();
+        let output = input.with_extension("pmtiles");
+        let presets = self.tile_presets.clone();
+        let first = presets[0].clone();
+        self.popup = Popup::Tiles(TilesState {
+            input,
+            output,
+            presets,
+            preset: 0,
+            min_zoom: first.min_zoom,
+            max_zoom: first.max_zoom,
+            flags: first.flags,
+            field: TilesField::Preset,
+            naming: None,
+            error: None,
+            estimate: TileEstimate::default(),
+        });
+        self.estimate_tiles();
+    }
+
+    fn move_tiles_field(&mut self, down: bool) {
+        let Popup::Tiles(state) = &mut self.popup else { return };
+        let fields = [TilesField::Preset, TilesField::MinZoom, TilesField::MaxZoom];
+        let i = fields.iter().position(|f| *f == state.field).unwrap_or(0);
+        let i = if down { i + 1 } else { i + fields.len() - 1 };
+        state.field = fields[i % fields.len()];
+    }
+
+    /// Cycles the preset or moves the selected zoom bound (keeping min ≤ max),
+    /// then estimates again
+    fn change_tiles_value(&mut self, up: bool) {
+        let Popup::Tiles(state) = &mut self.popup else { return };
+        match state.field {
+            TilesField::Preset => {
+                let count = state.presets.len();
+                state.preset = if up { (state.preset + 1) % count } else { (state.preset + count - 1) % count };
+                let preset = &state.presets[state.preset];
+                state.min_zoom = preset.min_zoom;
+                state.max_zoom = preset.max_zoom;
+                state.flags = preset.flags.clone();
+            }
+            field => {
+                let zoom = if field == TilesField::MaxZoom { &mut state.max_zoom } else { &mut state.min_zoom };
+                *zoom = if up { (*zoom + 1).min(MAX_TILE_ZOOM) } else { zoom.saturating_sub(1) };
+                if field == TilesField::MaxZoom {
+                    state.min_zoom = state.min_zoom.min(state.max_zoom);
+                } else {
+                    state.max_zoom = state.max_zoom.max(state.min_zoom);
+                }
+            }
+        }
+        self.estimate_tiles();
+    }
+
+    /// Estimates the PMTiles popup's zoom range from the inspector's features
+    fn estimate_tiles(&mut self) {
+        let Popup::Tiles(state) = &self.popup else { return };
+        let (min_zoom, max_zoom) = (state.min_zoom, state.max_zoom);
+        let Some(s) = self.active_json() else { return };
+        let features = s.root.get("features").and_then(|f| f.as_array()).map(Vec::as_slice).unwrap_or_default();
+        let estimated = estimate(features, min_zoom, max_zoom);
+        if let Popup::Tiles(state) = &mut self.popup {
+            state.estimate = estimated;
+        }
+    }
+
+    /// Saves the popup's zoom range and options under the typed name in the
+    /// config file and selects the new preset. Built-in names are refused.
+    fn save_tile_preset(&mut self) {
+        let Popup::Tiles(state) = &mut self.popup else { return };
+        let Some(name) = state.naming.as_deref().map(str::trim) else { return };
+        let preset = TilePreset {
+            name: name.to_string(),
+            min_zoom: state.min_zoom,
+            max_zoom: state.max_zoom,
+            flags: state.flags.clone(),
+        };
+        let result = if name.is_empty() {
+            Err(anyhow::anyhow!("Enter a name for the preset"))
+        } else if builtin_presets().iter().any(|p| p.name.eq_ignore_ascii_case(name)) {
+            Err(anyhow::anyhow!("{} is a built-in preset", name))
+        } else if preset.min_zoom > preset.max_zoom {
+            Err(anyhow::anyhow!("The min zoom ({}) is above the max zoom ({})", preset.min_zoom, preset.max_zoom))
+        } else {
+            Config::load().and_then(|mut config| {
+                config.save_tile_preset(preset.clone());
+                config.save()
+            })
+        };
+        if let Err(e) = result {
+            state.error = Some(format!("{:#}", e));
+            return;
+        }
+        state.preset = match state.presets.iter().position(|p| p.name == preset.name) {
+            Some(i) => {
+                state.presets[i] = preset;
+                i
+            }
+            None => {
+                state.presets.push(preset);
+                state.presets.len() - 1
+            }
+        };
+        state.naming = None;
+        state.error = None;
+        // Offered again the next time the popup opens
+        self.tile_presets = state.presets.clone();
+    }
+
+    /// Runs tippecanoe in the background with the popup's zoom range, then
+    /// checks the tileset for dropped features
+    fn build_tiles(&mut self) {
+        let Popup::Tiles(state) = std::mem::replace(&mut self.popup, Popup::None) else { return };
+        let label = format!("Building {}", state.output.file_name().unwrap_or_default().to_string_lossy());
+        self.tasks.spawn(label, move || {
+            let result = TilePlan::new(&state.input).and_then(|mut plan| {
+                plan.output = state.output.clone();
+                plan.min_zoom = Some(state.min_zoom);
+                plan.max_zoom = Some(state.max_zoom);
+                plan.extra = state.flags.clone();
+                plan.run_captured()?;
+                pmtiles::check(&plan.output, state.estimate.features)
+                    .context("The tiles were written but could not be checked")
+            });
+            Message::TilesBuilt(result)
+        });
+    }
+
+    /// Writes the pruned GeoJSON in the background and offers to open or rename it
+    fn write_pruned(&mut self) {
+        let Some(s) = self.active_json() else { return };
+        if s.pruning.is_empty() {
+            self.show_error("Nothing to prune: mark properties with d or deduplicate with u first");
+            return;
+        }
+        let (output, pruned) = s.pruned();
+        self.tasks.spawn("Writing the pruned GeoJSON", move || {
+            let result = serde_json::to_vec(&pruned).map_err(anyhow::Error::from).and_then(|json| {
+                backup::write(&output, json)?;
+                Ok(output)
+            });
+            Message::PrunedWritten(result)
+        });
+    }
+
+    /// Moves the preview to the first row of the selected row group
+    fn jump_to_row_group(&mut self) {
+        let Popup::RowGroups(state) = &self.popup else { return };
+        let Some(first_row) = state.groups.get(state.selected).map(|g| g.first_row) else {
+            return;
+        };
+        // Row groups number the file's rows; with filters the pages hold other rows
+        if self.active_data().is_some_and(|s| !s.filters.is_empty()) {
+            self.show_error("Clear the filters to jump to a row group");
+            return;
+        }
+        self.popup = Popup::None;
+        self.with_data(|s| s.jump_to_row(first_row));
+    }
+
+    /// Plots the Chart tab's x column against its y column in a popup
+    fn open_scatter(&mut self) {
+        let Some(state) = self.active_data_mut() else {
+            return;
+        };
+        match state.scatter() {
+            Ok(plot) => self.popup = Popup::Scatter(plot),
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    /// Shows DuckDB's plan for the current preview query (with filters) in a popup
+    fn explain_query(&mut self) {
+        let Some(state) = self.active_data() else {
+            return;
+        };
+        match state.explain_preview() {
+            Ok(plan) => {
+                self.popup = Popup::Text {
+                    title: "Query plan".to_string(),
+                    lines: plan.lines().map(str::to_string).collect(),
+                    scroll: 0,
+                };
+            }
+            Err(e) => self.show_error(e),
+        }
+    }
+
+    /// Replaces an estimated row count with an exact one from a full scan
+    fn count_rows_in_background(&mut self) {
+        let Some(state) = self.active_data_mut() else {
+            return;
+        };
+        if !state.row_count_estimated || state.counting_rows {
+            return;
+        }
+        state.counting_rows = true;
+        let spec = state.reopen_spec();
+        self.tasks.spawn_cancellable("Counting rows", move |cancel| {
+            let result = spec.open(cancel).and_then(|i| i.row_count());
+            Message::RowCountLoaded(spec.path, result)
+        });
+    }
+
+    fn row_count_loaded(&mut self, path: PathBuf, result: Result<usize, DuckDbError>) {
+        let state = self.sessions.iter_mut().find_map(|s| match s {
+            Session::Data(d) if d.file == path => Some(d),
+            _ => None,
+        });
+        let mut scrolled = Ok(());
+        if let Some(state) = state {
+            state.counting_rows = false;
+            // A filter applied in the meantime already set an exact (filtered) count
+            if let Ok(count) = result.as_ref()
+                && state.row_count_estimated
+            {
+                state.row_count = *count;
+                state.row_count_estimated = false;
+                // `End` may have gone past an estimate above the real count
+                if state.top_row > state.last_top_row() {
+                    scrolled = state.show_row(state.last_top_row());
+                }
+            }
+        }
+        if let Err(e) = result.and(scrolled) {
+            self.show_error(e);
+        }
+    }
+
+    /// Opens an HTTP response's JSON body, saved to `path`, with its status and
+    /// headers in a Response tab
+    pub fn open_response(&mut self, path: &Path, mut response: HttpResponse) -> anyhow::Result<()> {
+        let mut state = JsonInspectorState::open(path)?;
+        // The inspector holds the body already
+        response.body.clear();
+        state.response = Some(response);
+        self.open_session(Session::Json(state));
+        Ok(())
+    }
+
+    /// Shows a new tab. A data file's preview is up before it is fully
+    /// scanned: its exact row count and first column stats follow in the background.
+    fn open_session(&mut self, session: Session) {
+        self.sessions.push(session);
+        self.switch_session(self.sessions.len() - 1);
+        if let Some(state) = self.active_data()
+            && !state.lazy
+        {
+            let columns = state.prefetch_stats();
+            self.count_rows_in_background();
+            self.load_stats_in_background(columns);
+        }
+        self.detect_decimal_commas();
+        self.detect_geo_columns();
+    }
+
+    /// Looks for the thousands-grouped columns of every session waiting for
+    /// them on worker threads, then reads its numbers with a decimal comma
+    fn detect_decimal_commas(&mut self) {
+        let specs: Vec<InspectorSpec> = self
+            .sessions
+            .iter_mut()
+            .filter_map(|s| match s {
+                Session::Data(d) if d.decimal_comma_pending && !d.detecting_decimal_comma => {
+                    d.detecting_decimal_comma = true;
+                    Some(d.reopen_spec())
+                }
+                _ => None,
+            })
+            .collect();
+        for spec in specs {
+            self.tasks.spawn_cancellable("Reading decimal commas", move |cancel| {
+                let result = spec.detect_decimal_comma(cancel);
+                Message::DecimalCommaDetected(spec.path, result)
+            });
+        }
+    }
+
+    /// A cancelled detection stays pending, to run again when another file
+    /// opens (or on the file's next open); one switched back to a decimal
+    /// point meanwhile is dropped
+    fn decimal_comma_detected(&mut self, path: PathBuf, result: Result<DecimalFormat, DuckDbError>) {
+        let cancelled = self.cancelling;
+        let Some(state) = self.sessions.iter_mut().find_map(|s| match s {
+            Session::Data(d) if d.file == path => Some(d),
+            _ => None,
+        }) else {
+            return;
+        };
+        state.detecting_decimal_comma = false;
+        if cancelled || !state.decimal_comma_pending {
+            return;
+        }
+        state.decimal_comma_pending = false;
+        if let Err(e) = result.and_then(|format| state.set_decimal_format(format)) {
+            self.show_error(e);
+        }
+    }
+
+    /// Samples the rows of every session still waiting for its geometry and
+    /// coordinate columns, on worker threads
+    fn detect_geo_columns(&mut self) {
+        let specs: Vec<InspectorSpec> = self
+            .sessions
+            .iter_mut()
+            .filter_map(|s| match s {
+                Session::Data(d) if d.geo_pending && !d.detecting_geo => {
+                    d.detecting_geo = true;
+                    Some(d.reopen_spec())
+                }
+                _ => None,
+            })
+            .collect();
+        for spec in specs {
+            self.tasks.spawn_cancellable("Looking for geo columns", move |cancel| {
+                let result = spec.geo_columns(cancel);
+                Message::GeoColumnsDetected(spec.path, result)
+            });
+        }
+    }
+
+    /// A cancelled or failed detection stays pending, to run again when `M`
+    /// or `J` asks for the geo columns or another file opens
+    fn geo_columns_detected(&mut self, path: PathBuf, result: Result<Vec<GeoColumn>, DuckDbError>) {
+        let Some(state) = self.sessions.iter_mut().find_map(|s| match s {
+            Session::Data(d) if d.file == path => Some(d),
+            _ => None,
+        }) else {
+            return;
+        };
+        state.detecting_geo = false;
+        match result {
+            Ok(geo) => {
+                state.geo = geo;
+                state.geo_pending = false;
+            }
+            Err(e) => tracing::warn!(file = %path.display(), error = %e, "geo column detection failed"),
+        }
+    }
+
+    fn switch_session(&mut self, idx: usize) {
+        if let Some(session) = self.sessions.get(idx) {
+            // In split view, a session outside the pair replaces the focused pane
+            if let Some(ref mut split) = self.split {
+                if split.left == self.active_session && split.right != idx {
+                    split.left = idx;
+                } else if split.right == self.active_session && split.left != idx {
+                    split.right = idx;
+                }
+            }
+            self.active_session = idx;
+            self.current_screen = match session {
+                Session::Data(_) => Screen::DataInspector,
+                Session::Json(_) => Screen::JsonInspector,
+            };
+        }
+    }
+
+    fn next_session(&mut self) {
+        if !self.sessions.is_empty() {
+            self.switch_session((self.active_session + 1) % self.sessions.len());
+        }
+    }
+
+    fn prev_session(&mut self) {
+        if !self.sessions.is_empty() {
+            let len = self.sessions.len();
+            self.switch_session((self.active_session + len - 1) % len);
+        }
+    }
+
+    fn close_session(&mut self) {
+        if self.active_session >= self.sessions.len() {
+            return;
+        }
+        if let Session::Data(s) = self.sessions.remove(self.active_session) {
+            let states = [(s.file.as_path(), FileState::from_data(&s))];
+            if let Err(e) = file_state::save(&states) {
+                tracing::warn!(error = %e, "could not save file state");
+            }
+        }
+        self.split = None;
+        if self.sessions.is_empty() {
+            self.active_session = 0;
+            self.back_to_browser();
+        } else {
+            self.switch_session(self.active_session.min(self.sessions.len() - 1));
+        }
+    }
+
+    /// Remembers where each open data file was left, for the next time it's opened
+    pub fn remember_files(&self) {
+        let states: Vec<(&Path, FileState)> = self
+            .sessions
+            .iter()
+            .filter_map(|session| match session {
+                Session::Data(s) => Some((s.file.as_path(), FileState::from_data(s))),
+                Session::Json(_) => None,
+            })
+            .collect();
+        if let Err(e) = file_state::save(&states) {
+            tracing::warn!(error = %e, "could not save file state");
+        }
+    }
+
+    /// Shows the active session next to the following one, or closes the split view
+    fn toggle_split(&mut self) {
+        if self.split.is_some() {
+            self.split = None;
+        } else if self.sessions.len() > 1 {
+            self.split = Some(SplitView {
+                left: self.active_session,
+                right: (self.active_session + 1) % self.sessions.len(),
+            });
+        }
+    }
+
+    fn split_focus(&mut self) {
+        if let Some(split) = self.split {
+            let other = if self.active_session == split.left { split.right } else { split.left };
+            self.switch_session(other);
+        }
+    }
+
+    /// Snapshot of the current screen, directory and open files for persistence
+    pub fn saved_session(&self) -> SavedSession {
+        // Restore to the screen underneath the error log rather than the log itself
+        let screen = match self.current_screen {
+            Screen::ErrorLog => self.error_log_return,
+            Screen::Scratchpad | Screen::Env | Screen::Http | Screen::Decode | Screen::Catalog => Screen::Home,
+            screen => screen,
+        };
+        // Database tables aren't saved: their URL may carry a password
+        let saved: Vec<(usize, SavedFile)> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !DatabaseTable::is_url(&s.path().to_string_lossy()))
+            .map(|(i, s)| (i, SavedFile::from_session(s)))
+            .collect();
+        SavedSession {
+            screen,
+            current_dir: self.current_dir.clone(),
+            active_file: saved.iter().position(|(i, _)| *i == self.active_session).unwrap_or(0),
+            files: saved.into_iter().map(|(_, file)| file).collect(),
+        }
+    }
+
+    fn restore_session(&mut self) {
+        let saved = match std::mem::replace(&mut self.popup, Popup::None) {
+            Popup::RestoreSession(saved) => saved,
+            other => {
+                self.popup = other;
+                return;
+            }
+        };
+
+        if saved.current_dir.is_dir() {
+            self.current_dir = saved.current_dir;
+        }
+        if let Err(e) = self.load_dir_entries() {
+            self.show_error(e);
+            return;
+        }
+
+        // Files that were moved or deleted since the last run are skipped
+        let mut failed = Vec::new();
+        for file in &saved.files {
+            match file.open() {
+                Ok(session) => self.sessions.push(session),
+                Err(_) => failed.push(file),
+            }
+        }
+        self.detect_decimal_commas();
+        self.detect_geo_columns();
+
+        self.current_screen = match saved.screen {
+            Screen::DataInspector | Screen::JsonInspector if !self.sessions.is_empty() => {
+                self.switch_session(saved.active_file.min(self.sessions.len() - 1));
+                self.current_screen
+            }
+            Screen::Home => Screen::Home,
+            _ => Screen::FileBrowser,
+        };
+
+        if !failed.is_empty() {
+            self.show_error(format!("Could not reopen {} file(s) from the previous session", failed.len()));
+        }
+    }
+
+    pub fn active(&self) -> Option<&Session> {
+        self.sessions.get(self.active_session)
+    }
+
+    pub fn active_data(&self) -> Option<&DataInspectorState> {
+        match self.sessions.get(self.active_session) {
+            Some(Session::Data(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn active_data_mut(&mut self) -> Option<&mut DataInspectorState> {
+        match self.sessions.get_mut(self.active_session) {
+            Some(Session::Data(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn active_json(&self) -> Option<&JsonInspectorState> {
+        match self.sessions.get(self.active_session) {
+            Some(Session::Json(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn active_json_mut(&mut self) -> Option<&mut JsonInspectorState> {
+        match self.sessions.get_mut(self.active_session) {
+            Some(Session::Json(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Runs a fallible action against the active data inspector, surfacing errors as a popup
+    fn with_data(&mut self, f: impl FnOnce(&mut DataInspectorState) -> Result<(), DuckDbError>) {
+        let result = self.active_data_mut().map(f);
+        if let Some(Err(e)) = result {
+            self.show_error(e);
+        }
+    }
+
+    /// Saves the clipboard's JSON to a temp file and opens it in the JSON inspector
+    fn paste_json(&mut self) {
+        match self.paste_from_clipboard().and_then(|text| save_pasted(&text, "clipboard")) {
+            Ok(path) => self.open_file(&path),
+            Err(e) => self.show_error(format!("Clipboard: {:#}", e)),
+        }
+    }
+
+    /// Opens the decode scratchpad's JSON result (a JWT's header and payload,
+    /// or decoded text that is JSON) in the JSON inspector
+    fn open_decoded(&mut self) {
+        self.decode.refresh();
+        let Some(decoded) = self.decode.result() else { return };
+        let Some(json) = decoded.json() else {
+            self.show_error("The decoded text isn't JSON");
+            return;
+        };
+        let source = if matches!(decoded, Decoded::Jwt(_)) { "jwt" } else { "decoded" };
+        match save_pasted(&json, source) {
+            Ok(path) => self.open_file(&path),
+            Err(e) => self.show_error(format!("{:#}", e)),
+        }
+    }
+
+    fn with_http(&mut self, f: impl FnOnce(&mut HttpClientState)) {
+        if let Some(h) = self.http.as_mut() {
+            f(h);
+        }
+    }
+
+    /// Sends the HTTP client's request on a worker thread
+    fn send_http(&mut self) {
+        let Some(state) = &self.http else { return };
+        let request = match state.request() {
+            Ok(r) => r,
+            Err(e) => {
+                self.show_error(e);
+                return;
+            }
+        };
+        self.tasks.spawn(format!("{} {}", request.method, request.url), move || {
+            let result = http_client::expand(&request).and_then(|r| r.send());
+            Message::HttpResponded(request, result)
+        });
+    }
+
+    /// Records the request in the history and opens a JSON response in the inspector
+    fn http_responded(&mut self, request: HttpRequest, result: anyhow::Result<HttpResponse>) {
+        let status = result.as_ref().ok().map(|r| r.status);
+        if let Some(state) = self.http.as_mut()
+            && let Err(e) = state.remember(&request, status)
+        {
+            tracing::warn!(error = %e, "failed to save the request history");
+        }
+        let response = match result {
+            Ok(r) => r,
+            Err(e) => {
+                self.show_error(format!("{:#}", e));
+                return;
+            }
+        };
+        let status = response.status_line();
+        let opened = save_pasted(&response.body, "response").and_then(|path| self.open_response(&path, response));
+        if let Err(e) = opened {
+            self.show_error(format!("{}: {:#}", status, e));
+        }
+    }
+
+    fn with_catalog(&mut self, f: impl FnOnce(&mut CatalogState)) {
+        if let Some(c) = self.catalog.as_mut() {
+            f(c);
+        }
+    }
+
+    /// Re-indexes the catalog's directories on a worker thread; only changed
+    /// files are read again
+    fn refresh_catalog(&mut self) {
+        let Some(state) = &self.catalog else { return };
+        let mut catalog = state.catalog.clone();
+        if catalog.roots.is_empty() {
+            self.show_error("Nothing indexed yet: run `ftool index <dir>` first");
+            return;
+        }
+        self.tasks.spawn("Refreshing the data catalog", move || {
+            let result = catalog.refresh_all().and_then(|stats| {
+                catalog.save()?;
+                Ok((catalog, stats))
+            });
+            Message::CatalogRefreshed(result)
+        });
+    }
+
+    fn with_env(&mut self, f: impl FnOnce(&mut EnvState)) {
+        if let Some(e) = self.env.as_mut() {
+            f(e);
+        }
+    }
+
+    /// Shows `state` in the environment view; Esc returns to the current screen
+    fn show_env(&mut self, state: EnvState) {
+        self.env_return = self.current_screen;
+        self.env = Some(state);
+        self.current_screen = Screen::Env;
+    }
+
+    fn back(&mut self) {
+        match self.current_screen {
+            Screen::JsonInspector | Screen::DataInspector => self.back_to_browser(),
+            Screen::FileBrowser => {
+                self.current_screen = Screen::Home;
+            }
+            Screen::ErrorLog => {
+                self.current_screen = self.error_log_return;
+            }
+            Screen::Scratchpad | Screen::Http | Screen::Decode | Screen::Catalog => self.current_screen = Screen::Home,
+            Screen::Env => self.current_screen = self.env_return,
+            Screen::Home => {}
+        }
+    }
+
+    fn back_to_browser(&mut self) {
+        // Go back to file browser
+        if self.dir_entries.is_empty() {
+            let parent = self.active().and_then(|s| s.path().parent()).map(Path::to_path_buf);
+            if let Some(parent) = parent {
+                self.current_dir = parent;
+                let _ = self.load_dir_entries();
+            }
+        }
+        self.current_screen = Screen::FileBrowser;
+    }
+
+    fn switch_tab(&mut self) {
         match self.current_screen {
             Screen::JsonInspector => {
-                if self.json_scroll > 0 {
-                    self.json_scroll -= 1;
+                if let Some(s) = self.active_json_mut() {
+                    s.switch_tab();
                 }
             }
             _ => {
-                if self.inspector_scroll > 0 {
-                    self.inspector_scroll -= 1;
+                if let Some(s) = self.active_data_mut() {
+                    s.switch_tab();
+                }
+                self.load_visible_stats();
+            }
+        }
+    }
+
+    fn scroll_up(&mut self) {
+        match self.current_screen {
+            Screen::Env => self.with_env(EnvState::scroll_up),
+            Screen::Catalog => self.with_catalog(CatalogState::select_up),
+            Screen::ErrorLog => {
+                self.error_log_scroll = self.error_log_scroll.saturating_sub(1);
+            }
+            Screen::JsonInspector => {
+                if let Some(s) = self.active_json_mut() {
+                    s.scroll_up();
+                }
+            }
+            _ => self.with_data(|s| match s.tab {
+                InspectorTab::Preview => s.scroll_rows(-1),
+                _ => {
+                    s.scroll_up();
+                    Ok(())
+                }
+            }),
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        match self.current_screen {
+            Screen::Env => self.with_env(EnvState::scroll_down),
+            Screen::Catalog => self.with_catalog(CatalogState::select_down),
+            Screen::ErrorLog => {
+                if self.error_log_scroll + 1 < self.error_log.entries.len() {
+                    self.error_log_scroll += 1;
+                }
+            }
+            Screen::JsonInspector => {
+                if let Some(s) = self.active_json_mut() {
+                    s.scroll_down();
+                }
+            }
+            _ => self.with_data(|s| match s.tab {
+                InspectorTab::Preview => s.scroll_rows(1),
+                _ => {
+                    s.scroll_down();
+                    Ok(())
+                }
+            }),
+        }
+    }
+
+    fn show_error(&mut self, e: impl std::fmt::Display) {
+        if self.cancelling {
+            tracing::debug!(error = %e, "cancelled task error");
+            return;
+        }
+        let body = e.to_string();
+        let context = self.error_context();
+        tracing::warn!(%context, error = %body, "tui error");
+        self.error_log.push(context, body.clone());
+        self.popup = Popup::Message {
+            title: "Error".to_string(),
+            body,
+        };
+    }
+
+    /// Where the user was when an error happened, recorded alongside it in the error log
+    fn error_context(&self) -> String {
+        match self.current_screen {
+            Screen::Home => "Home".to_string(),
+            Screen::FileBrowser => format!("Browser: {}", self.current_dir.display()),
+            Screen::DataInspector | Screen::JsonInspector => match self.active() {
+                Some(s) => format!("Inspector: {}", s.path().display()),
+                None => "Inspector".to_string(),
+            },
+            Screen::ErrorLog => "Error log".to_string(),
+            Screen::Scratchpad => "Geometry scratchpad".to_string(),
+            Screen::Env => match &self.env {
+                Some(e) => format!("Environment: {}", e.title()),
+                None => "Environment".to_string(),
+            },
+            Screen::Http => "HTTP client".to_string(),
+            Screen::Decode => "Decode scratchpad".to_string(),
+            Screen::Catalog => "Data catalog".to_string(),
+        }
+    }
+
+    fn next_col_page(&mut self) {
+        self.with_data(|s| s.next_col_page());
+    }
+
+    fn prev_col_page(&mut self) {
+        self.with_data(|s| s.prev_col_page());
+    }
+
+    fn col_left(&mut self) {
+        self.with_data(|s| s.col_left());
+    }
+
+    fn col_right(&mut self) {
+        self.with_data(|s| s.col_right());
+    }
+
+    /// Number of columns in the active data inspector's schema
+    fn schema_len(&self) -> usize {
+        self.active_data().map(|s| s.schema.len()).unwrap_or(0)
+    }
+
+    fn open_filter_popup(&mut self) {
+        let conditions = match self.active_data() {
+            Some(s) if s.tab == InspectorTab::Preview => s.filters.clone(),
+            _ => return,
+        };
+        self.popup = Popup::FilterEditor(FilterEditorState {
+            conditions,
+            column_idx: 0,
+            operator_idx: 0,
+            value_input: String::new(),
+            active_field: FilterField::Column,
+            like_mode: LikeMode::default(),
+            case_insensitive: false,
+            error: None,
+            failure: None,
+            column_search: None,
+            completion: Completion::default(),
+        });
+    }
+
+    fn filter_tab_next(&mut self) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            let op = FILTER_OPERATORS[state.operator_idx];
+            state.active_field = match state.active_field {
+                FilterField::Column => FilterField::Operator,
+                FilterField::Operator => {
+                    if op == "IS NULL" || op == "IS NOT NULL" {
+                        FilterField::Column
+                    } else {
+                        FilterField::Value
+                    }
+                }
+                FilterField::Value => FilterField::Column,
+            };
+        }
+    }
+
+    fn filter_nav_up(&mut self) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            match state.active_field {
+                FilterField::Column => {
+                    if state.column_idx > 0 {
+                        state.column_idx -= 1;
+                    }
+                }
+                FilterField::Operator => {
+                    if state.operator_idx > 0 {
+                        state.operator_idx -= 1;
+                    }
+                }
+                FilterField::Value => {}
+            }
+        }
+    }
+
+    fn filter_nav_down(&mut self) {
+        let schema_len = self.schema_len();
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            match state.active_field {
+                FilterField::Column => {
+                    if state.column_idx + 1 < schema_len {
+                        state.column_idx += 1;
+                    }
+                }
+                FilterField::Operator => {
+                    if state.operator_idx + 1 < FILTER_OPERATORS.len() {
+                        state.operator_idx += 1;
+                    }
+                }
+                FilterField::Value => {}
+            }
+        }
+    }
+
+    fn filter_char(&mut self, c: char) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            state.value_input.push(c);
+        }
+    }
+
+    fn filter_backspace(&mut self) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            state.value_input.pop();
+        }
+    }
+
+    fn filter_add_condition(&mut self) {
+        let col_name = match (&self.popup, self.active_data()) {
+            (Popup::FilterEditor(state), Some(s)) => s.schema.get(state.column_idx).map(|(n, _)| n.clone()),
+            _ => None,
+        };
+        // A number typed as `1.234,5` in a file read with a decimal comma
+        let decimal_comma = match (&self.popup, self.active_data()) {
+            (Popup::FilterEditor(state), Some(s)) => {
+                s.inspector.is_decimal_comma()
+                    && s.schema.get(state.column_idx).is_some_and(|(_, ty)| is_numeric_type(ty))
+            }
+            _ => false,
+        };
+        // Check regex patterns with DuckDB so a typo is reported here instead of on apply
+        let regex_error = match (&self.popup, self.active_data()) {
+            (Popup::FilterEditor(state), Some(s))
+                if FILTER_OPERATORS[state.operator_idx].ends_with("REGEXP") =>
+            {
+                s.inspector.validate_regex(&state.value_input).err()
+            }
+            _ => None,
+        };
+        if let (Popup::FilterEditor(state), Some(col_name)) = (&mut self.popup, col_name) {
+            if let Some(e) = regex_error {
+                state.error = Some(e.to_string());
+                return;
+            }
+            state.error = None;
+            state.failure = None;
+            let op = FILTER_OPERATORS[state.operator_idx];
+            let is_null_op = op == "IS NULL" || op == "IS NOT NULL";
+            let is_comparison = !is_null_op && op != "LIKE" && !op.ends_with("REGEXP");
+            let value = if is_null_op {
+                String::new()
+            } else if decimal_comma
+                && is_comparison
+                && let Some(number) = normalize_decimal_comma(&state.value_input)
+            {
+                number
+            } else {
+                state.value_input.clone()
+            };
+            state.conditions.push(FilterCondition {
+                column: col_name,
+                operator: op.to_string(),
+                value,
+                like_mode: state.like_mode,
+                case_insensitive: state.case_insensitive,
+            });
+            state.value_input.clear();
+            state.active_field = FilterField::Column;
+        }
+    }
+
+    fn filter_cycle_like_mode(&mut self) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            state.like_mode = state.like_mode.next();
+        }
+    }
+
+    fn filter_toggle_case(&mut self) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            state.case_insensitive = !state.case_insensitive;
+        }
+    }
+
+    fn filter_remove_last(&mut self) {
+        if let Popup::FilterEditor(ref mut state) = self.popup {
+            state.conditions.pop();
+            state.failure = None;
+        }
+    }
+
+    fn filter_apply_with_current(&mut self) {
+        let should_add = if let Popup::FilterEditor(ref state) = self.popup {
+            let op = FILTER_OPERATORS[state.operator_idx];
+            let is_null_op = op == "IS NULL" || op == "IS NOT NULL";
+            is_null_op || !state.value_input.is_empty()
+        } else {
+            false
+        };
+
+        if should_add {
+            self.filter_add_condition();
+            // Keep the popup open when the condition was rejected
+            if matches!(&self.popup, Popup::FilterEditor(state) if state.error.is_some()) {
+                return;
+            }
+        }
+
+        self.filter_apply();
+    }
+
+    fn filter_apply(&mut self) {
+        let conditions = if let Popup::FilterEditor(ref state) = self.popup {
+            state.conditions.clone()
+        } else {
+            return;
+        };
+        let result = self.active_data_mut().map(|s| {
+            s.apply_filters(conditions.clone()).map_err(|e| s.explain_filter_error(&conditions, &e))
+        });
+        match result {
+            // Stay in the editor with the conditions as typed, pointing at the failing one
+            Some(Err(failure)) => {
+                if let Popup::FilterEditor(ref mut state) = self.popup {
+                    state.failure = Some(failure);
                 }
             }
+            Some(Ok(())) => {
+                self.popup = Popup::None;
+                self.record_history(&conditions);
+            }
+            None => self.popup = Popup::None,
+        }
+    }
+
+    /// Parses the typed `expr AS name` and adds it as a preview column
+    fn add_computed_column(&mut self) {
+        let Popup::ComputedColumn(state) = self.popup.clone() else { return };
+        if state.input.trim().is_empty() {
+            self.popup = Popup::None;
+            return;
+        }
+        let column = match ComputedColumn::parse(&state.input) {
+            Ok(c) => c,
+            Err(e) => {
+                self.show_error(e);
+                return;
+            }
+        };
+        self.popup = Popup::None;
+        self.with_data(|s| {
+            s.computed_in_export = state.export;
+            s.add_computed(column)
+        });
+    }
+
+    /// Suggestion list of the open popup, if it has one
+    fn popup_completion(&mut self) -> Option<&mut Completion> {
+        match &mut self.popup {
+            Popup::ComputedColumn(state) => Some(&mut state.completion),
+            Popup::FilterEditor(state) => Some(&mut state.completion),
+            _ => None,
+        }
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        self.active_data()
+            .map(|s| s.schema.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    fn update_computed_completion(&mut self) {
+        let columns = self.column_names();
+        if let Popup::ComputedColumn(state) = &mut self.popup {
+            state.completion = Completion::sql(&state.input, &columns);
         }
     }
 
-    fn scroll_down(&mut self) {
-        match self.current_screen {
-            Screen::JsonInspector => {
-                let max = match self.geo_tab {
-                    GeoJsonTab::Features => self.json_features_data.len(),
-                    _ => self.json_tree_nodes.len(),
-                };
-                if self.json_scroll + 1 < max {
-                    self.json_scroll += 1;
+    fn update_filter_completion(&mut self) {
+        let columns = self.column_names();
+        if let Popup::FilterEditor(FilterEditorState { column_search: Some(query), completion, .. }) =
+            &mut self.popup
+        {
+            *completion = Completion::columns(query, &columns);
+        }
+    }
+
+    /// Inserts the selected suggestion: completes the word in the computed
+    /// column expression, or selects the column found in the filter editor
+    fn accept_completion(&mut self) {
+        let columns = self.column_names();
+        match &mut self.popup {
+            Popup::ComputedColumn(state) => {
+                if let Some(suggestion) = state.completion.selected() {
+                    state.input = completion::complete(&state.input, suggestion);
                 }
+                state.completion = Completion::default();
             }
-            _ => {
-                let max = match self.inspector_tab {
-                    InspectorTab::Schema => self.inspector_schema.len(),
-                    InspectorTab::Preview => self.inspector_preview_data.len(),
-                };
-                if self.inspector_scroll + 1 < max {
-                    self.inspector_scroll += 1;
+            Popup::FilterEditor(state) => {
+                if let Some(idx) = state.completion.selected().and_then(|c| columns.iter().position(|n| n == c)) {
+                    state.column_idx = idx;
+                    state.active_field = FilterField::Operator;
                 }
+                state.column_search = None;
+                state.completion = Completion::default();
             }
+            _ => {}
         }
     }
 
-    fn show_error(&mut self, e: impl std::fmt::Display) {
-        self.popup = Popup::Message {
-            title: "Error".to_string(),
-            body: e.to_string(),
+    /// Opens the type override popup for the top Schema row, or the selected
+    /// Preview column, prefilled with its override if it has one
+    fn open_column_type(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        let Some(column) = s.focused_column().and_then(|n| s.schema.iter().position(|(c, _)| *c == n)) else {
+            return;
         };
+        let (name, current) = s.schema[column].clone();
+        let input = s
+            .type_overrides
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, t)| t.clone())
+            .unwrap_or_default();
+        self.popup = Popup::ColumnType(ColumnTypeState { column, name, current, input });
     }
 
-    fn next_page(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
+    /// Opens the CSV options popup with the active file's current settings
+    fn open_csv_options(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        if s.file.extension().is_none_or(|e| e != "csv") {
+            self.show_error("CSV options only apply to CSV files");
             return;
         }
-        let total_pages = (self.inspector_row_count + PAGE_SIZE - 1) / PAGE_SIZE;
-        if self.inspector_page + 1 < total_pages {
-            self.inspector_page += 1;
-            self.load_preview_page();
-        }
+        self.popup = Popup::CsvOptions(CsvOptionsState { decimal_comma: s.inspector.is_decimal_comma() });
     }
 
-    fn prev_page(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
+    /// Appends applied filters to the history file; failures only get logged
+    fn record_history(&self, filters: &[FilterCondition]) {
+        let Some(s) = self.active_data() else { return };
+        if filters.is_empty() {
             return;
         }
-        if self.inspector_page > 0 {
-            self.inspector_page -= 1;
-            self.load_preview_page();
+        if let Err(e) = history::append(s.file.clone(), filters.to_vec()) {
+            tracing::warn!(error = %e, "failed to write filter history");
         }
     }
 
-    fn next_col_page(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
+    /// Re-applies the selected history entry's filters to the active file
+    fn apply_history(&mut self) {
+        let Popup::History(state) = &self.popup else { return };
+        let Some(entry) = state.visible().get(state.selected).map(|e| (*e).clone()) else {
+            return;
+        };
+        self.popup = Popup::None;
+        self.with_data(|s| s.apply_filters(entry.filters));
+    }
+
+    fn run_palette(&mut self) {
+        let Popup::Palette(state) = &self.popup else { return };
+        let Some(command) = state.visible().get(state.selected).copied() else {
             return;
+        };
+        self.popup = Popup::None;
+        match command {
+            PaletteCommand::Menu(index) => self.open_menu_entry(index),
+            PaletteCommand::ErrorLog => self.update(Message::OpenErrorLog),
+            PaletteCommand::UndoWrite => self.update(Message::UndoWrite),
+            PaletteCommand::RestoreDeleted => self.update(Message::RestoreDeleted),
+            PaletteCommand::Generate(kind) => self.copy_generated(kind),
         }
-        let total_cols = self.inspector_schema.len();
-        let total_col_pages = (total_cols + COLUMN_PAGE_SIZE - 1) / COLUMN_PAGE_SIZE;
-        if self.inspector_col_page + 1 < total_col_pages {
-            self.inspector_col_page += 1;
-            self.inspector_selected_col = 0;
-            self.load_preview_page();
+    }
+
+    /// Generates a UUID, ULID or password and copies it to the clipboard
+    fn copy_generated(&mut self, kind: GenKind) {
+        let result = kind.generate().and_then(|value| self.copy_to_clipboard(value.clone()).map(|_| value));
+        match result {
+            Ok(value) => {
+                // A password stays off the screen
+                let body = match kind {
+                    GenKind::Password => "Password copied to clipboard".to_string(),
+                    _ => format!("{} copied to clipboard", value),
+                };
+                self.popup = Popup::Message { title: "Copied".to_string(), body };
+            }
+            Err(e) => self.show_error(format!("Could not generate a {}: {:#}", kind.label(), e)),
         }
     }
 
-    fn prev_col_page(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
+    fn convert_file(&mut self) {
+        if self.active_data().is_some_and(|s| s.inspector.row_limit().is_some()) {
+            self.show_error(SAMPLED_WRITE);
             return;
         }
-        if self.inspector_col_page > 0 {
-            self.inspector_col_page -= 1;
-            self.inspector_selected_col = 0;
-            self.load_preview_page();
+        if let Some(s) = self.active_data() {
+            let ext = s.file.extension().and_then(|e| e.to_str()).unwrap_or("");
+            let target = if ext == "csv" { "parquet" } else { "csv" };
+            self.popup = Popup::ConvertConfirm {
+                target_format: target.to_string(),
+                verify: false,
+            };
         }
     }
 
-    fn col_left(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
-            return;
+    fn confirm_convert(&mut self) {
+        let (target_format, verify) = match &self.popup {
+            Popup::ConvertConfirm { target_format, verify } => (target_format.clone(), *verify),
+            _ => return,
+        };
+        let result = self.active_data().map(|s| {
+            let transform = Transform {
+                computed: s.export_computed(),
+                ..Transform::default()
+            };
+            let path = s.inspector.convert_with(&target_format, &transform, &[])?;
+            // Computed columns change the output, so there is nothing to compare against
+            let verification = if verify && transform.is_empty() {
+                Some(s.inspector.verify_conversion(&path)?)
+            } else {
+                None
+            };
+            Ok::<_, DuckDbError>((path, verification))
+        });
+        match result {
+            Some(Ok((path, verification))) => {
+                self.popup = Popup::Converted(ConvertedState {
+                    path: PathBuf::from(path),
+                    verification: verification.map(|v| (v.passed(), v.report())),
+                    rename: None,
+                    error: None,
+                });
+            }
+            Some(Err(e)) => self.show_error(e),
+            None => {}
         }
-        if self.inspector_selected_col > 0 {
-            self.inspector_selected_col -= 1;
-        } else if self.inspector_col_page > 0 {
-            self.inspector_col_page -= 1;
-            let visible_len = self.visible_columns().len();
-            self.inspector_selected_col = visible_len.saturating_sub(1);
-            self.load_preview_page();
+    }
+
+    /// Reverts the last recorded write, putting back the file it replaced
+    fn undo_write(&mut self) {
+        let body = match backup::undo_last() {
+            Ok(Some(record)) if record.backup.is_some() => format!("Restored the previous {}", record.path.display()),
+            Ok(Some(record)) => format!("Deleted {}", record.path.display()),
+            Ok(None) => "Nothing to undo".to_string(),
+            Err(e) => {
+                self.show_error(format!("{:#}", e));
+                return;
+            }
+        };
+        self.popup = Popup::Message { title: "Undo".to_string(), body };
+        if let Err(e) = self.load_dir_entries() {
+            tracing::debug!(error = %e, "could not refresh the file list after an undo");
         }
     }
 
-    fn col_right(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
+    /// Renames the converted file to the typed name, in the same directory.
+    /// Failures are shown in the popup so the file can still be opened.
+    fn rename_converted(&mut self) {
+        let Popup::Converted(state) = &mut self.popup else { return };
+        let Some(name) = state.rename.as_deref().map(str::trim) else { return };
+        let target = state.path.with_file_name(name);
+        let result = if name.is_empty() || name.contains(std::path::MAIN_SEPARATOR) {
+            Err("Enter a file name without a directory".to_string())
+        } else if target.exists() {
+            Err(format!("{} already exists", target.display()))
+        } else {
+            std::fs::rename(&state.path, &target).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(()) => {
+                state.path = target;
+                state.rename = None;
+                state.error = None;
+            }
+            Err(e) => {
+                state.error = Some(e);
+                return;
+            }
+        }
+        // The browser may be showing the directory the file was renamed in
+        if let Err(e) = self.load_dir_entries() {
+            tracing::debug!(error = %e, "could not refresh the file list after a rename");
+        }
+    }
+
+    /// Asks before deleting the marked files, or the selected entry when none are marked
+    fn ask_delete(&mut self) {
+        let paths = if self.browser_marked.is_empty() {
+            let idx = if self.browser_search_active {
+                self.browser_filtered_indices.get(self.browser_selected).copied()
+            } else {
+                Some(self.browser_selected)
+            };
+            let Some(entry) = idx.and_then(|i| self.dir_entries.get(i)).filter(|e| e.name != "..") else {
+                return;
+            };
+            vec![entry.path.clone()]
+        } else {
+            self.browser_marked.clone()
+        };
+        self.popup = Popup::ConfirmDelete(paths);
+    }
+
+    /// Moves the files the popup asked about to the trash, or deletes them for
+    /// good with `--permanent`
+    fn delete_files(&mut self) {
+        let Popup::ConfirmDelete(paths) = std::mem::replace(&mut self.popup, Popup::None) else { return };
+        let permanent = crate::config::get().is_permanent_delete();
+        let result = deletion::remove(&paths, permanent);
+        let selected = self.browser_selected;
+        if let Err(e) = self.load_dir_entries() {
+            tracing::debug!(error = %e, "could not refresh the file list after deleting");
+        }
+        self.browser_selected = selected.min(self.dir_entries.len().saturating_sub(1));
+        if let Err(e) = result {
+            self.show_error(format!("{:#}", e));
             return;
         }
-        let visible_count = self.visible_columns().len();
-        if self.inspector_selected_col + 1 < visible_count {
-            self.inspector_selected_col += 1;
+        self.browser_marked.retain(|p| !paths.contains(p));
+        let body = if permanent {
+            format!("Deleted {}", deletion::describe(&paths))
         } else {
-            let total_col_pages = (self.inspector_schema.len() + COLUMN_PAGE_SIZE - 1) / COLUMN_PAGE_SIZE;
-            if self.inspector_col_page + 1 < total_col_pages {
-                self.inspector_col_page += 1;
-                self.inspector_selected_col = 0;
-                self.load_preview_page();
-            }
+            format!("Moved {} to the trash (u restores it)", deletion::describe(&paths))
+        };
+        if !permanent {
+            self.last_deleted = paths;
         }
+        self.popup = Popup::Message { title: "Delete".to_string(), body };
     }
 
-    /// Compute visible column names for the current column page
-    fn visible_columns(&self) -> Vec<String> {
-        let all_cols: Vec<String> = self.inspector_schema.iter().map(|(n, _)| n.clone()).collect();
-        let start = self.inspector_col_page * COLUMN_PAGE_SIZE;
-        let end = (start + COLUMN_PAGE_SIZE).min(all_cols.len());
-        if start >= all_cols.len() {
-            return all_cols; // fallback: show all if page is out of range
+    /// Puts the files the last delete moved to the trash back in place
+    fn restore_deleted(&mut self) {
+        if self.last_deleted.is_empty() {
+            self.popup = Popup::Message { title: "Restore".to_string(), body: "Nothing to restore".to_string() };
+            return;
+        }
+        if let Err(e) = deletion::restore(&self.last_deleted) {
+            self.show_error(format!("{:#}", e));
+            return;
+        }
+        let paths = std::mem::take(&mut self.last_deleted);
+        self.popup = Popup::Message { title: "Restore".to_string(), body: format!("Restored {}", deletion::describe(&paths)) };
+        if let Err(e) = self.load_dir_entries() {
+            tracing::debug!(error = %e, "could not refresh the file list after restoring");
         }
-        all_cols[start..end].to_vec()
     }
 
-    fn load_stats_if_needed(&mut self) {
-        if self.inspector_stats_loaded {
+    /// Marks or unmarks the selected file in the browser
+    fn toggle_mark(&mut self) {
+        let idx = if self.browser_search_active {
+            match self.browser_filtered_indices.get(self.browser_selected) {
+                Some(&idx) => idx,
+                None => return,
+            }
+        } else {
+            self.browser_selected
+        };
+        let Some(entry) = self.dir_entries.get(idx) else { return };
+        if entry.is_dir {
             return;
         }
-        let schema = self.inspector_schema.clone();
-        let result = self.inspector.as_ref().map(|i| i.column_stats(&schema));
-        match result {
-            Some(Ok((nulls, mins, maxs, means))) => {
-                self.inspector_null_counts = nulls;
-                self.inspector_min_values = mins;
-                self.inspector_max_values = maxs;
-                self.inspector_mean_values = means;
-                self.inspector_stats_loaded = true;
+        match self.browser_marked.iter().position(|p| *p == entry.path) {
+            Some(pos) => {
+                self.browser_marked.remove(pos);
             }
-            Some(Err(e)) => self.show_error(e),
-            None => {}
+            None => self.browser_marked.push(entry.path.clone()),
         }
     }
 
-    fn load_preview_page(&mut self) {
-        let where_clause = Self::build_where_clause(&self.inspector_filters);
-        let cols = self.visible_columns();
-        let offset = self.inspector_page * PAGE_SIZE;
-        let result = self.inspector.as_ref().map(|i| {
-            i.preview(PAGE_SIZE, offset, &where_clause, Some(&cols))
-        });
-        match result {
-            Some(Ok((headers, data))) => {
-                self.inspector_preview_headers = headers;
-                self.inspector_preview_data = data;
-                self.inspector_scroll = 0;
+    /// Compares the schemas of the two marked files on a worker thread
+    fn compare_schemas(&mut self) {
+        let [left, right] = self.browser_marked.as_slice() else {
+            self.popup = Popup::Message {
+                title: "Compare schemas".to_string(),
+                body: "Mark exactly two CSV/Parquet files (or two .env files) with Space".to_string(),
+            };
+            return;
+        };
+        if is_env_file(left) && is_env_file(right) {
+            match EnvState::compare(left, right) {
+                Ok(state) => self.show_env(state),
+                Err(e) => self.show_error(e),
             }
-            Some(Err(e)) => self.show_error(e),
-            None => {}
+            return;
         }
+        // Read as an open session reads the file, else with its saved settings
+        let spec = |path: &PathBuf| {
+            self.sessions
+                .iter()
+                .find_map(|s| match s {
+                    Session::Data(d) if d.file == *path => Some(d.reopen_spec()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| InspectorSpec::for_file(path))
+        };
+        let (left, right) = (spec(left), spec(right));
+        self.tasks.spawn_cancellable("Comparing schemas", move |cancel| {
+            let result = left
+                .open(cancel)
+                .and_then(|i| i.schema())
+                .and_then(|old| {
+                    let new = right.open(cancel)?.schema()?;
+                    Ok(SchemaDiff::compare(&old, &new))
+                });
+            Message::SchemaCompared(left.path, right.path, result)
+        });
     }
 
-    fn open_filter_popup(&mut self) {
-        if self.inspector_tab != InspectorTab::Preview {
+    /// Checks the active CSV's raw lines for structural problems on a worker thread
+    fn lint_csv(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        let file = s.file.clone();
+        if file.extension().is_none_or(|e| e != "csv") {
+            self.show_error("Lint only applies to CSV files");
             return;
         }
-        self.popup = Popup::FilterEditor(FilterEditorState {
-            conditions: self.inspector_filters.clone(),
-            column_idx: 0,
-            operator_idx: 0,
-            value_input: String::new(),
-            active_field: FilterField::Column,
+        self.tasks.spawn("Linting CSV", move || {
+            let result = lint(&file);
+            Message::CsvLinted(file, result)
         });
     }
 
-    fn filter_tab_next(&mut self) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            let op = FILTER_OPERATORS[state.operator_idx];
-            state.active_field = match state.active_field {
-                FilterField::Column => FilterField::Operator,
-                FilterField::Operator => {
-                    if op == "IS NULL" || op == "IS NOT NULL" {
-                        FilterField::Column
-                    } else {
-                        FilterField::Value
-                    }
-                }
-                FilterField::Value => FilterField::Column,
-            };
-        }
+    /// Profiles the active data file on a worker thread and writes `<stem>_report.md` next to it
+    fn write_report(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        let file = s.file.clone();
+        let stem = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let output = file.with_file_name(format!("{}_report.md", stem));
+        self.tasks.spawn("Building report", move || {
+            let result = Report::build(&file.to_string_lossy())
+                .map_err(anyhow::Error::from)
+                .and_then(|report| {
+                    backup::write(&output, report.render(ReportFormat::Markdown))?;
+                    Ok(output)
+                });
+            Message::ReportWritten(result)
+        });
     }
 
-    fn filter_nav_up(&mut self) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            match state.active_field {
-                FilterField::Column => {
-                    if state.column_idx > 0 {
-                        state.column_idx -= 1;
-                    }
-                }
-                FilterField::Operator => {
-                    if state.operator_idx > 0 {
-                        state.operator_idx -= 1;
-                    }
-                }
-                FilterField::Value => {}
-            }
+    fn open_save_preset(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        if s.filters.is_empty() {
+            self.popup = Popup::Message {
+                title: "Filter presets".to_string(),
+                body: "Add filters with f before saving a preset".to_string(),
+            };
+            return;
         }
+        self.popup = Popup::SavePreset(SavePresetState {
+            name: String::new(),
+            global: false,
+        });
     }
 
-    fn filter_nav_down(&mut self) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            match state.active_field {
-                FilterField::Column => {
-                    if state.column_idx + 1 < self.inspector_schema.len() {
-                        state.column_idx += 1;
-                    }
-                }
-                FilterField::Operator => {
-                    if state.operator_idx + 1 < FILTER_OPERATORS.len() {
-                        state.operator_idx += 1;
-                    }
-                }
-                FilterField::Value => {}
+    /// Saves the active filters under the typed name, for this file or globally
+    fn save_preset(&mut self) {
+        let Popup::SavePreset(state) = self.popup.clone() else { return };
+        let Some(s) = self.active_data() else { return };
+        let name = state.name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let preset = FilterPreset {
+            name: name.clone(),
+            file: if state.global { None } else { Some(s.file.clone()) },
+            filters: s.filters.clone(),
+        };
+        match presets::add(preset) {
+            Ok(()) => {
+                self.popup = Popup::Message {
+                    title: "Preset saved".to_string(),
+                    body: format!("Saved filters as '{}'", name),
+                };
             }
+            Err(e) => self.show_error(e),
         }
     }
 
-    fn filter_char(&mut self, c: char) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            state.value_input.push(c);
+    fn open_preset_picker(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        let presets = presets::for_file(&s.file);
+        if presets.is_empty() {
+            self.popup = Popup::Message {
+                title: "Filter presets".to_string(),
+                body: "No saved presets for this file (S saves the active filters)".to_string(),
+            };
+            return;
         }
+        self.popup = Popup::PresetPicker(PresetPickerState { presets, selected: 0 });
     }
 
-    fn filter_backspace(&mut self) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            state.value_input.pop();
-        }
+    /// Replaces the active filters with the selected preset's
+    fn apply_preset(&mut self) {
+        let Popup::PresetPicker(state) = &self.popup else { return };
+        let Some(preset) = state.presets.get(state.selected) else { return };
+        let filters = preset.filters.clone();
+        self.popup = Popup::None;
+        self.with_data(|s| s.apply_filters(filters));
     }
 
-    fn filter_add_condition(&mut self) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            if let Some((col_name, _)) = self.inspector_schema.get(state.column_idx) {
-                let op = FILTER_OPERATORS[state.operator_idx];
-                let is_null_op = op == "IS NULL" || op == "IS NOT NULL";
-                state.conditions.push(FilterCondition {
-                    column: col_name.clone(),
-                    operator: op.to_string(),
-                    value: if is_null_op { String::new() } else { state.value_input.clone() },
-                });
-                state.value_input.clear();
-                state.active_field = FilterField::Column;
-            }
+    fn delete_preset(&mut self) {
+        let Popup::PresetPicker(state) = &mut self.popup else { return };
+        if state.selected >= state.presets.len() {
+            return;
+        }
+        let preset = state.presets.remove(state.selected);
+        state.selected = state.selected.min(state.presets.len().saturating_sub(1));
+        if state.presets.is_empty() {
+            self.popup = Popup::None;
+        }
+        if let Err(e) = presets::remove(&preset) {
+            self.show_error(e);
         }
     }
 
-    fn filter_remove_last(&mut self) {
-        if let Popup::FilterEditor(ref mut state) = self.popup {
-            state.conditions.pop();
+    fn open_copy_page(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        if s.tab != InspectorTab::Preview {
+            return;
         }
+        let format = TableFormat::Csv;
+        self.popup = Popup::CopyPage(CopyPageState {
+            path_input: copy_page_path(s, format),
+            format,
+            to_file: false,
+        });
     }
 
-    fn filter_apply_with_current(&mut self) {
-        let should_add = if let Popup::FilterEditor(ref state) = self.popup {
-            let op = FILTER_OPERATORS[state.operator_idx];
-            let is_null_op = op == "IS NULL" || op == "IS NOT NULL";
-            is_null_op || !state.value_input.is_empty()
+    fn copy_page_format(&mut self) {
+        let Some(s) = self.active_data() else { return };
+        let Popup::CopyPage(state) = &self.popup else { return };
+        let format = match state.format {
+            TableFormat::Csv => TableFormat::Markdown,
+            TableFormat::Markdown => TableFormat::Csv,
+        };
+        // Only replace the path if the user hasn't edited the suggested one
+        let path_input = if state.path_input == copy_page_path(s, state.format) {
+            copy_page_path(s, format)
         } else {
-            false
+            state.path_input.clone()
         };
-
-        if should_add {
-            self.filter_add_condition();
-        }
-
-        self.filter_apply();
+        self.popup = Popup::CopyPage(CopyPageState {
+            format,
+            path_input,
+            to_file: state.to_file,
+        });
     }
 
-    fn filter_apply(&mut self) {
-        let conditions = if let Popup::FilterEditor(ref state) = self.popup {
-            state.conditions.clone()
+    /// Writes the rows on screen (with headers) to the clipboard or a file
+    fn copy_page_confirm(&mut self) {
+        let Popup::CopyPage(state) = self.popup.clone() else { return };
+        let Some(s) = self.active_data() else { return };
+        let text = state.format.render(&s.preview_headers, &nulls_as_text(s.visible_rows()));
+        let rows = s.visible_rows().len();
+
+        let result = if state.to_file && crate::config::get().is_readonly() {
+            Err(anyhow::anyhow!("ftool is in read-only mode (--readonly): files can't be written"))
+        } else if state.to_file {
+            backup::write(Path::new(&state.path_input), text)
+                .map(|_| format!("{} rows written to {}", rows, state.path_input))
         } else {
-            return;
+            self.copy_to_clipboard(text)
+                .map(|_| format!("{} rows copied to clipboard as {}", rows, state.format.name()))
         };
-        self.inspector_filters = conditions;
-        self.inspector_page = 0;
-        self.inspector_scroll = 0;
-        self.popup = Popup::None;
+        match result {
+            Ok(body) => {
+                self.popup = Popup::Message {
+                    title: "Copied".to_string(),
+                    body,
+                };
+            }
+            Err(e) => self.show_error(e),
+        }
+    }
 
-        let where_clause = Self::build_where_clause(&self.inspector_filters);
-        let cols = self.visible_columns();
-        match self.inspector.as_ref().map(|i| i.row_count_filtered(&where_clause)) {
-            Some(Ok(count)) => self.inspector_row_count = count,
-            Some(Err(e)) => { self.show_error(e); return; }
-            None => return,
+    /// The clipboard is kept open for the app's lifetime since on X11/Wayland
+    /// the copied text is served by this process and disappears when it's dropped
+    fn copy_to_clipboard(&mut self, text: String) -> anyhow::Result<()> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new()?);
         }
-        match self.inspector.as_ref().map(|i| i.preview(PAGE_SIZE, 0, &where_clause, Some(&cols))) {
-            Some(Ok((headers, data))) => {
-                self.inspector_preview_headers = headers;
-                self.inspector_preview_data = data;
-            }
-            Some(Err(e)) => self.show_error(e),
-            None => {}
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            clipboard.set_text(text)?;
         }
+        Ok(())
     }
 
-    fn build_where_clause(filters: &[FilterCondition]) -> String {
-        if filters.is_empty() {
-            return String::new();
+    fn paste_from_clipboard(&mut self) -> anyhow::Result<String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new()?);
+        }
+        match self.clipboard.as_mut() {
+            Some(clipboard) => Ok(clipboard.get_text()?),
+            None => Ok(String::new()),
         }
-        let parts: Vec<String> = filters.iter().map(|f| {
-            let col = f.column.replace('"', "\"\"");
-            let v = f.value.replace('\'', "''");
-            match f.operator.as_str() {
-                "IS NULL"     => format!("\"{}\" IS NULL", col),
-                "IS NOT NULL" => format!("\"{}\" IS NOT NULL", col),
-                "LIKE"        => format!("\"{}\"::VARCHAR LIKE '%{}%'", col, v),
-                op            => format!("\"{}\" {} '{}'", col, op, v),
-            }
-        }).collect();
-        format!("WHERE {}", parts.join(" AND "))
     }
 
-    fn convert_file(&mut self) {
-        if let Some(ref file) = self.inspector_file {
-            let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
-            let target = if ext == "csv" { "parquet" } else { "csv" };
-            self.popup = Popup::ConvertConfirm {
-                target_format: target.to_string(),
+    fn open_transform_editor(&mut self) {
+        let Popup::ConvertConfirm { target_format, .. } = &self.popup else { return };
+        let Some(s) = self.active_data() else { return };
+        let columns = s
+            .schema
+            .iter()
+            .map(|(name, data_type)| TransformColumn {
+                name: name.clone(),
+                data_type: data_type.clone(),
+                keep: true,
+                rename: String::new(),
+                cast: String::new(),
+                partition: false,
+                mask: None,
+            })
+            .collect();
+        self.popup = Popup::TransformEditor(TransformEditorState {
+            target_format: target_format.clone(),
+            columns,
+            selected: 0,
+            editing: None,
+            input: String::new(),
+        });
+    }
+
+    fn transform_edit(&mut self, field: TransformField) {
+        if let Popup::TransformEditor(ref mut state) = self.popup
+            && let Some(c) = state.columns.get(state.selected)
+        {
+            state.input = match field {
+                TransformField::Rename => c.rename.clone(),
+                TransformField::Cast => c.cast.clone(),
             };
+            state.editing = Some(field);
         }
     }
 
-    fn confirm_convert(&mut self) {
-        let target_format = match &self.popup {
-            Popup::ConvertConfirm { target_format } => target_format.clone(),
-            _ => return,
-        };
-        match self.inspector.as_ref().map(|i| i.convert(&target_format)) {
+    fn transform_commit_edit(&mut self) {
+        if let Popup::TransformEditor(ref mut state) = self.popup {
+            let input = std::mem::take(&mut state.input).trim().to_string();
+            let field = state.editing.take();
+            if let Some(c) = state.columns.get_mut(state.selected) {
+                match field {
+                    Some(TransformField::Rename) => c.rename = input,
+                    Some(TransformField::Cast) => c.cast = input.to_uppercase(),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    fn transform_confirm(&mut self) {
+        let Popup::TransformEditor(state) = &self.popup else { return };
+        let mut transform = state.to_transform();
+        let partition_by = state.partition_by();
+        let target_format = state.target_format.clone();
+        let result = self.active_data().map(|s| {
+            transform.computed = s.export_computed();
+            s.inspector.convert_with(&target_format, &transform, &partition_by)
+        });
+        match result {
             Some(Ok(path)) => {
-                self.popup = Popup::Message {
-                    title: "Success".to_string(),
-                    body: format!("Converted to {}", path),
-                };
+                self.popup = Popup::Converted(ConvertedState {
+                    path: PathBuf::from(path),
+                    verification: None,
+                    rename: None,
+                    error: None,
+                });
             }
             Some(Err(e)) => self.show_error(e),
             None => {}
@@ -927,72 +4358,44 @@ impl App {
     }
 
     pub fn view(&self, frame: &mut Frame) {
-        match self.current_screen {
-            Screen::Home => views::home::render(frame, self),
-            Screen::FileBrowser => views::file_browser::render(frame, self),
-            Screen::DataInspector => views::data_inspector::render(frame, self),
-            Screen::JsonInspector => views::json_inspector::render(frame, self),
-        }
-    }
-
-    pub fn load_json_data(&mut self, path: &Path) -> anyhow::Result<()> {
-        use crate::commands::JsonInspector;
-        use crate::tui::tree::build_tree;
-
-        let inspector = JsonInspector::new(path)?;
-        self.json_raw = serde_json::to_string_pretty(&inspector.root)?;
-        self.json_kind = Some(inspector.kind.clone());
-        self.json_collapsed = std::collections::HashSet::new();
-        self.json_tree_nodes = build_tree(&inspector.root, &self.json_collapsed);
-
-        if inspector.kind == crate::commands::json_inspector::FileKind::GeoJson {
-            let (count, types, bbox) = inspector.geojson_summary();
-            self.json_geosummary = Some((count, types, bbox));
-            let (headers, rows) = inspector.features_table();
-            self.json_features_headers = headers;
-            self.json_features_data = rows;
-            self.geo_tab = GeoJsonTab::Summary;
+        let in_inspector = matches!(self.current_screen, Screen::DataInspector | Screen::JsonInspector);
+        if in_inspector && self.split.is_some() {
+            views::split::render(frame, self);
         } else {
-            self.json_tab = JsonInspectorTab::Tree;
-            self.json_geosummary = None;
-            self.json_features_headers = vec![];
-            self.json_features_data = vec![];
+            match self.current_screen {
+                Screen::Home => views::home::render(frame, self),
+                Screen::FileBrowser => views::file_browser::render(frame, self),
+                Screen::DataInspector => views::data_inspector::render(frame, self),
+                Screen::JsonInspector => views::json_inspector::render(frame, self),
+                Screen::ErrorLog => views::error_log::render(frame, self),
+                Screen::Scratchpad => views::scratchpad::render(frame, self),
+                Screen::Env => views::env::render(frame, self),
+                Screen::Http => views::http_client::render(frame, self),
+                Screen::Decode => views::decode::render(frame, self),
+                Screen::Catalog => views::catalog::render(frame, self),
+            }
+        }
+        // Keys played back wait for background tasks, so playback can span frames
+        if self.macros.is_playing() {
+            status_bar::render_indicator(frame, "playing macro");
+        } else if self.macros.is_recording() {
+            status_bar::render_indicator(frame, "recording (Q to stop)");
+        }
+        if let Some(label) = self.tasks.current() {
+            spinner::render(frame, &format!("{} (Esc to cancel)", label), self.tick);
         }
-
-        self.json_root = Some(inspector.root);
-        self.json_scroll = 0;
-        self.json_file = Some(path.to_path_buf());
-        Ok(())
     }
 
     fn toggle_tree_node(&mut self) {
-        if let Some((path, node)) = self.json_tree_nodes.get(self.json_scroll) {
-            use crate::tui::tree::NodeKind;
-            match &node.kind {
-                NodeKind::Object | NodeKind::Array => {
-                    let path = path.clone();
-                    if self.json_collapsed.contains(&path) {
-                        self.json_collapsed.remove(&path);
-                    } else {
-                        self.json_collapsed.insert(path);
-                    }
-                    if let Some(ref root) = self.json_root.clone() {
-                        self.json_tree_nodes =
-                            crate::tui::tree::build_tree(root, &self.json_collapsed);
-                    }
-                }
-                _ => {}
-            }
+        if let Some(s) = self.active_json_mut() {
+            s.toggle_tree_node();
         }
     }
 
     fn switch_geo_tab(&mut self) {
-        self.json_scroll = 0;
-        self.geo_tab = match self.geo_tab {
-            GeoJsonTab::Summary => GeoJsonTab::Features,
-            GeoJsonTab::Features => GeoJsonTab::Tree,
-            GeoJsonTab::Tree => GeoJsonTab::Summary,
-        };
+        if let Some(s) = self.active_json_mut() {
+            s.switch_geo_tab();
+        }
     }
 
     fn apply_browser_search_filter(&mut self) {
@@ -1076,36 +4479,24 @@ impl App {
         self.browser_filtered_indices.clear();
         Ok(())
     }
+}
 
-    fn load_inspector_data(&mut self, path: &Path) -> anyhow::Result<()> {
-        let inspector = DuckDbInspector::new(path.to_string_lossy().to_string())?;
-
-        self.inspector_schema = inspector.schema()?;
-        self.inspector_row_count = inspector.row_count()?;
-
-        // Reset stats — will be loaded lazily when Schema tab is viewed
-        self.inspector_null_counts = Vec::new();
-        self.inspector_mean_values = Vec::new();
-        self.inspector_min_values = Vec::new();
-        self.inspector_max_values = Vec::new();
-        self.inspector_stats_loaded = false;
-
-        // Column pagination
-        self.inspector_col_page = 0;
-        self.inspector_selected_col = 0;
-        let cols = self.visible_columns();
-
-        // Preview data (only visible columns)
-        let (headers, data) = inspector.preview(PAGE_SIZE, 0, "", Some(&cols))?;
-        self.inspector_preview_headers = headers;
-        self.inspector_preview_data = data;
-
-        self.inspector_scroll = 0;
-        self.inspector_page = 0;
-        self.inspector_filters = Vec::new();
-        self.inspector_tab = InspectorTab::Preview;
-
-        self.inspector = Some(inspector);
-        Ok(())
-    }
+/// Suggested file for "copy visible rows": `<stem>_rows<first>-<last>.<ext>` next to the data file
+fn copy_page_path(state: &DataInspectorState, format: TableFormat) -> String {
+    let stem = state
+        .file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    state
+        .file
+        .with_file_name(format!(
+            "{}_rows{}-{}.{}",
+            stem,
+            state.top_row + 1,
+            state.top_row + state.visible_rows().len(),
+            format.extension()
+        ))
+        .to_string_lossy()
+        .to_string()
 }
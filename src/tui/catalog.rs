@@ -0,0 +1,54 @@
+use crate::commands::catalog::{Catalog, CatalogEntry};
+
+/// Catalog browser: the files indexed by `ftool index`, filtered by a search
+#[derive(Debug, Default)]
+pub struct CatalogState {
+    pub catalog: Catalog,
+    pub search: String,
+    pub searching: bool,
+    pub selected: usize,
+    /// Summary of the last refresh, shown in the title
+    pub status: Option<String>,
+}
+
+impl CatalogState {
+    pub fn load() -> anyhow::Result<Self> {
+        Ok(Self { catalog: Catalog::load()?, ..Default::default() })
+    }
+
+    /// Entries whose path or a column name contains the search
+    pub fn rows(&self) -> Vec<&CatalogEntry> {
+        self.catalog.search(&self.search)
+    }
+
+    pub fn selected_entry(&self) -> Option<&CatalogEntry> {
+        self.rows().get(self.selected).copied()
+    }
+
+    /// Replaces the catalog after a refresh, keeping the selection in range
+    pub fn set_catalog(&mut self, catalog: Catalog, status: String) {
+        self.catalog = catalog;
+        self.status = Some(status);
+        self.selected = self.selected.min(self.rows().len().saturating_sub(1));
+    }
+
+    pub fn select_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_down(&mut self) {
+        if self.selected + 1 < self.rows().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn push_search(&mut self, c: char) {
+        self.search.push(c);
+        self.selected = 0;
+    }
+
+    pub fn pop_search(&mut self) {
+        self.search.pop();
+        self.selected = 0;
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::commands::backup;
+use crate::config;
+
+const COLUMN_TYPES_FILE: &str = "column_types.json";
+
+/// Overridden column types per CSV file, keyed by path
+type ColumnTypes = BTreeMap<String, Vec<(String, String)>>;
+
+fn load() -> ColumnTypes {
+    let Some(path) = config::data_dir().map(|d| d.join(COLUMN_TYPES_FILE)) else {
+        return ColumnTypes::new();
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The (column, type) overrides saved for `path`, if any
+pub fn for_file(path: &Path) -> Vec<(String, String)> {
+    load().remove(path.to_string_lossy().as_ref()).unwrap_or_default()
+}
+
+/// Saves `overrides` for `path`; an empty list forgets the file
+pub fn save(path: &Path, overrides: &[(String, String)]) -> anyhow::Result<()> {
+    let dir = config::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let mut all = load();
+    let key = path.to_string_lossy().to_string();
+    if overrides.is_empty() {
+        all.remove(&key);
+    } else {
+        all.insert(key, overrides.to_vec());
+    }
+    backup::write_atomic(&dir.join(COLUMN_TYPES_FILE), serde_json::to_string_pretty(&all)?)?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+/// Keywords and functions offered while typing a SQL expression
+const SQL_KEYWORDS: &[&str] = &[
+    "ABS", "AND", "AS", "BETWEEN", "BIGINT", "BOOLEAN", "CASE", "CAST", "COALESCE", "CONCAT", "DATE",
+    "DATE_TRUNC", "DECIMAL", "DOUBLE", "ELSE", "END", "EXTRACT", "FALSE", "ILIKE", "IN", "INTEGER",
+    "IS", "LENGTH", "LIKE", "LOWER", "NOT", "NULL", "NULLIF", "OR", "REGEXP_MATCHES", "ROUND",
+    "STRFTIME", "SUBSTRING", "THEN", "TIMESTAMP", "TRIM", "TRUE", "TRY_CAST", "UPPER", "VARCHAR", "WHEN",
+];
+
+/// Suggestions shown in the list at most; the rest are reached by typing more
+const MAX_SUGGESTIONS: usize = 50;
+
+/// Suggestion list for the word being typed, with one entry selected
+#[derive(Debug, Clone, Default)]
+pub struct Completion {
+    pub items: Vec<String>,
+    pub selected: usize,
+}
+
+impl Completion {
+    /// Column names, then SQL keywords, starting with the last word of `input`
+    /// (ignoring case); empty while no word is being typed
+    pub fn sql(input: &str, columns: &[String]) -> Self {
+        let word = current_word(input).to_lowercase();
+        if word.is_empty() {
+            return Self::default();
+        }
+        let items = columns
+            .iter()
+            .map(String::as_str)
+            .chain(SQL_KEYWORDS.iter().copied())
+            .filter(|c| c.to_lowercase().starts_with(&word))
+            .take(MAX_SUGGESTIONS)
+            .map(str::to_string)
+            .collect();
+        Self { items, selected: 0 }
+    }
+
+    /// Column names containing `query` (ignoring case), in schema order
+    pub fn columns(query: &str, columns: &[String]) -> Self {
+        let query = query.to_lowercase();
+        let items = columns
+            .iter()
+            .filter(|c| c.to_lowercase().contains(&query))
+            .take(MAX_SUGGESTIONS)
+            .cloned()
+            .collect();
+        Self { items, selected: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.items.get(self.selected).map(String::as_str)
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.items.len() {
+            self.selected += 1;
+        }
+    }
+}
+
+/// The identifier being typed at the end of `input`
+pub fn current_word(input: &str) -> &str {
+    let start = input
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(input.len());
+    &input[start..]
+}
+
+/// `input` with its last word replaced by `suggestion`, quoted when it isn't
+/// a plain identifier
+pub fn complete(input: &str, suggestion: &str) -> String {
+    let word = current_word(input);
+    let prefix = &input[..input.len() - word.len()];
+    let plain = suggestion.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !suggestion.starts_with(|c: char| c.is_ascii_digit());
+    if plain {
+        format!("{}{}", prefix, suggestion)
+    } else {
+        format!("{}\"{}\"", prefix, suggestion.replace('"', "\"\""))
+    }
+}
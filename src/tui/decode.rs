@@ -0,0 +1,76 @@
+use crate::commands::decode::{self, Decoded, Encoding};
+
+/// Decode scratchpad: pasted base64, percent-encoded, hex or JWT text, decoded
+/// as it is typed
+#[derive(Debug, Default)]
+pub struct DecodeState {
+    pub input: String,
+    /// Decode as this encoding, or detect it when `None`
+    pub encoding: Option<Encoding>,
+    /// The decoded input and the encoding used, or why it couldn't be decoded;
+    /// `None` while the input is empty
+    pub decoded: Option<Result<(Decoded, Encoding), String>>,
+    /// The input changed since it was last decoded; typed (or pasted) keys are
+    /// decoded together on the next idle tick
+    dirty: bool,
+}
+
+impl DecodeState {
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.dirty = true;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.dirty = true;
+    }
+
+    /// Decodes the input again if it changed
+    pub fn refresh(&mut self) {
+        if self.dirty {
+            self.redecode();
+        }
+    }
+
+    pub fn set_input(&mut self, text: String) {
+        self.input = text;
+        self.redecode();
+    }
+
+    /// Cycles Auto → Base64 → URL → Hex → JWT → Auto
+    pub fn next_encoding(&mut self) {
+        self.encoding = match self.encoding {
+            None => Some(Encoding::ALL[0]),
+            Some(current) => {
+                let i = Encoding::ALL.iter().position(|e| *e == current).unwrap_or(0);
+                Encoding::ALL.get(i + 1).copied()
+            }
+        };
+        self.redecode();
+    }
+
+    pub fn result(&self) -> Option<&Decoded> {
+        self.decoded.as_ref().and_then(|d| d.as_ref().ok()).map(|(d, _)| d)
+    }
+
+    /// The decoded text (binary data as a hex dump)
+    pub fn output_text(&self) -> Option<String> {
+        self.result().map(Decoded::display)
+    }
+
+    fn redecode(&mut self) {
+        self.dirty = false;
+        if self.input.trim().is_empty() {
+            self.decoded = None;
+            return;
+        }
+        let decoded = match self.encoding.or_else(|| Encoding::detect(&self.input)) {
+            Some(encoding) => decode::decode(encoding, &self.input)
+                .map(|d| (d, encoding))
+                .map_err(|e| format!("{:#}", e)),
+            None => Err("Not base64, hex, percent-encoded text or a JWT".to_string()),
+        };
+        self.decoded = Some(decoded);
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+
+use crate::commands::dir_scan::{self, DirStats};
+
+/// Totals of the directory selected in the file browser, scanned on a worker
+/// thread. Dropping the preview (the selection moved on) cancels the scan.
+pub struct DirPreview {
+    pub path: PathBuf,
+    /// `None` while scanning
+    pub stats: Option<Result<DirStats, String>>,
+    cancelled: Arc<AtomicBool>,
+    rx: Receiver<Result<DirStats, String>>,
+}
+
+impl DirPreview {
+    pub fn start(path: PathBuf) -> Self {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let flag = cancelled.clone();
+        let dir = path.clone();
+        std::thread::spawn(move || {
+            let result = dir_scan::scan(&dir, &|| flag.load(Ordering::Relaxed));
+            let result = match result {
+                Ok(Some(stats)) => Ok(stats),
+                Ok(None) => return,
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(result);
+        });
+        Self { path, stats: None, cancelled, rx }
+    }
+
+    /// Picks up the scan's result once it's done
+    pub fn poll(&mut self) {
+        if self.stats.is_none()
+            && let Ok(result) = self.rx.try_recv()
+        {
+            self.stats = Some(result);
+        }
+    }
+}
+
+impl Drop for DirPreview {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
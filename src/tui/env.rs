@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::env::{self, EnvDiffLine, EnvStatus, EnvVar};
+
+/// Environment view: the process environment or a `.env` file, optionally
+/// compared with a second file
+#[derive(Debug)]
+pub struct EnvState {
+    /// The `.env` file listed, or `None` for the process environment
+    pub source: Option<PathBuf>,
+    pub vars: Vec<EnvVar>,
+    /// The file compared with, and every key of both
+    pub diff: Option<(PathBuf, Vec<EnvDiffLine>)>,
+    pub search: String,
+    pub searching: bool,
+    /// Show secret values instead of masking them
+    pub reveal: bool,
+    pub scroll: usize,
+}
+
+/// One listed variable: its key and the value as shown
+pub struct EnvRow {
+    pub status: EnvStatus,
+    pub key: String,
+    pub value: String,
+    pub secret: bool,
+}
+
+impl EnvState {
+    pub fn process() -> Self {
+        Self::new(None, env::process_env())
+    }
+
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self::new(Some(path.to_path_buf()), env::read_env_file(path)?))
+    }
+
+    /// `left` listed with each key's value in `right` alongside
+    pub fn compare(left: &Path, right: &Path) -> anyhow::Result<Self> {
+        let mut state = Self::open(left)?;
+        let lines = env::diff(&state.vars, &env::read_env_file(right)?);
+        state.diff = Some((right.to_path_buf(), lines));
+        Ok(state)
+    }
+
+    fn new(source: Option<PathBuf>, vars: Vec<EnvVar>) -> Self {
+        Self { source, vars, diff: None, search: String::new(), searching: false, reveal: false, scroll: 0 }
+    }
+
+    /// Reads the file (or the environment) again, and the compared file
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let reloaded = match (&self.source, &self.diff) {
+            (Some(left), Some((right, _))) => Self::compare(left, right)?,
+            (Some(path), None) => Self::open(path)?,
+            (None, _) => Self::process(),
+        };
+        self.vars = reloaded.vars;
+        self.diff = reloaded.diff;
+        self.scroll = self.scroll.min(self.rows().len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// The variables matching the search, with secrets masked unless revealed
+    pub fn rows(&self) -> Vec<EnvRow> {
+        let rows: Vec<EnvRow> = match &self.diff {
+            Some((_, lines)) => lines
+                .iter()
+                .map(|line| EnvRow {
+                    status: line.status(),
+                    key: line.key.clone(),
+                    value: line.shown_value(self.reveal),
+                    secret: env::is_secret(&line.key),
+                })
+                .collect(),
+            None => self
+                .vars
+                .iter()
+                .map(|(key, value)| EnvRow {
+                    status: EnvStatus::Same,
+                    key: key.clone(),
+                    value: env::display_value(key, value, self.reveal),
+                    secret: env::is_secret(key),
+                })
+                .collect(),
+        };
+        rows.into_iter().filter(|row| env::matches(&row.key, &row.value, &self.search)).collect()
+    }
+
+    pub fn title(&self) -> String {
+        let name = |p: &Path| p.file_name().map_or_else(|| p.display().to_string(), |n| n.to_string_lossy().to_string());
+        match (&self.source, &self.diff) {
+            (Some(left), Some((right, _))) => format!("{} vs {}", name(left), name(right)),
+            (Some(path), None) => name(path),
+            (None, _) => "Process environment".to_string(),
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll + 1 < self.rows().len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn push_search(&mut self, c: char) {
+        self.search.push(c);
+        self.scroll = 0;
+    }
+
+    pub fn pop_search(&mut self) {
+        self.search.pop();
+        self.scroll = 0;
+    }
+}
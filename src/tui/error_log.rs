@@ -0,0 +1,52 @@
+use chrono::{DateTime, Local};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub struct ErrorLogEntry {
+    pub time: DateTime<Local>,
+    pub context: String,
+    pub message: String,
+}
+
+/// Every error shown during the session, kept so popups dismissed with Esc aren't lost.
+/// Entries are optionally appended to a file as well.
+pub struct ErrorLog {
+    pub entries: Vec<ErrorLogEntry>,
+    file: Option<PathBuf>,
+}
+
+impl ErrorLog {
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            entries: Vec::new(),
+            file,
+        }
+    }
+
+    pub fn push(&mut self, context: String, message: String) {
+        let entry = ErrorLogEntry {
+            time: Local::now(),
+            context,
+            message,
+        };
+
+        if let Some(ref path) = self.file {
+            // Logging must never take the TUI down, so write failures are ignored
+            if let Ok(mut f) = std::fs::OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(
+                    f,
+                    "{}\t{}\t{}",
+                    entry.time.to_rfc3339(),
+                    entry.context,
+                    entry.message
+                );
+            }
+        }
+
+        self.entries.push(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -1,8 +1,11 @@
 use crossterm::event::{self, Event};
 use std::time::Duration;
 
+/// How often the loop wakes up without input; each idle wake-up emits a `Message::Tick`
+pub const TICK_RATE: Duration = Duration::from_millis(100);
+
 pub fn poll_event() -> anyhow::Result<Option<Event>> {
-    if event::poll(Duration::from_millis(250))? {
+    if event::poll(TICK_RATE)? {
         Ok(Some(event::read()?))
     } else {
         Ok(None)
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::backup;
+use crate::commands::postgres::DatabaseTable;
+use crate::config;
+
+use super::app::{FilterCondition, InspectorTab};
+use super::session::DataInspectorState;
+
+const FILE_STATE_FILE: &str = "file_state.json";
+
+/// Files remembered; the ones closed longest ago are forgotten first
+const MAX_FILES: usize = 200;
+
+/// Where a data file was left when it was last closed, so reopening it lands
+/// on the same rows, filters, columns and column widths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileState {
+    pub tab: InspectorTab,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<FilterCondition>,
+    /// Row at the top of the preview
+    #[serde(default)]
+    pub row: usize,
+    pub col_page: usize,
+    pub selected_col: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_col: Option<String>,
+    /// Column widths set with `<`/`>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub col_widths: BTreeMap<String, u16>,
+    /// Numbers read with a decimal comma, set in the CSV options popup
+    #[serde(default)]
+    pub decimal_comma: bool,
+    /// Seconds since the epoch
+    pub closed: i64,
+}
+
+impl FileState {
+    pub fn from_data(s: &DataInspectorState) -> Self {
+        Self {
+            tab: s.tab.clone(),
+            filters: s.filters.clone(),
+            row: s.top_row,
+            col_page: s.col_page,
+            selected_col: s.selected_col,
+            pinned_col: s.pinned_col.clone(),
+            col_widths: s.col_widths.clone(),
+            // Chosen but not detected yet: detected again on the next open
+            decimal_comma: s.inspector.is_decimal_comma() || s.decimal_comma_pending,
+            closed: Utc::now().timestamp(),
+        }
+    }
+
+    /// Reapplies the state to a freshly opened file. Filters the file no longer
+    /// accepts (e.g. a dropped column) are skipped; pages are clamped.
+    pub fn apply(&self, s: &mut DataInspectorState) -> anyhow::Result<()> {
+        if !self.filters.is_empty()
+            && let Err(e) = s.apply_filters(self.filters.clone())
+        {
+            tracing::warn!(file = %s.file.display(), error = %e, "ignoring saved filters");
+            s.apply_filters(Vec::new())?;
+        }
+        s.pinned_col = self.pinned_col.clone().filter(|c| s.schema.iter().any(|(name, _)| name == c));
+        s.col_widths = self
+            .col_widths
+            .iter()
+            .filter(|(c, _)| s.schema.iter().any(|(name, _)| name == *c))
+            .map(|(c, &width)| (c.clone(), width))
+            .collect();
+        s.top_row = self.row.min(s.row_count.saturating_sub(1));
+        s.col_page = self.col_page.min(s.total_col_pages().saturating_sub(1));
+        s.load_preview()?;
+        s.selected_col = self.selected_col.min(s.visible_columns().len().saturating_sub(1));
+        s.tab = self.tab.clone();
+        Ok(())
+    }
+}
+
+/// Puts a freshly opened file back where it was left, if it was inspected before
+pub fn restore(s: &mut DataInspectorState) {
+    let Some(state) = for_file(&s.file) else { return };
+    if let Err(e) = state.apply(s) {
+        tracing::warn!(file = %s.file.display(), error = %e, "could not restore file state");
+    }
+}
+
+/// Saved states, keyed by absolute path
+type FileStates = BTreeMap<String, FileState>;
+
+fn key(path: &Path) -> String {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()).to_string_lossy().to_string()
+}
+
+fn load() -> FileStates {
+    let Some(path) = config::data_dir().map(|d| d.join(FILE_STATE_FILE)) else {
+        return FileStates::new();
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The state `path` was left in, if it was inspected before
+pub fn for_file(path: &Path) -> Option<FileState> {
+    load().remove(&key(path))
+}
+
+/// Remembers the state of each inspected file, forgetting the oldest ones
+/// past `MAX_FILES`. Database tables are skipped, as their URL may hold a password.
+pub fn save(states: &[(&Path, FileState)]) -> anyhow::Result<()> {
+    let states: Vec<&(&Path, FileState)> =
+        states.iter().filter(|(path, _)| !DatabaseTable::is_url(&path.to_string_lossy())).collect();
+    if states.is_empty() {
+        return Ok(());
+    }
+    let dir = config::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let mut all = load();
+    for (path, state) in states {
+        all.insert(key(path), state.clone());
+    }
+    while all.len() > MAX_FILES {
+        let Some(oldest) = all.iter().min_by_key(|(_, s)| s.closed).map(|(k, _)| k.clone()) else { break };
+        all.remove(&oldest);
+    }
+    backup::write_atomic(&dir.join(FILE_STATE_FILE), serde_json::to_string_pretty(&all)?)?;
+    Ok(())
+}
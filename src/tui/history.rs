@@ -0,0 +1,73 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config;
+
+use super::app::FilterCondition;
+use super::session::build_where_clause;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Entries kept when the history file is read; older ones are ignored
+const MAX_ENTRIES: usize = 500;
+
+/// A filter set applied in the data inspector, appended to the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Local time the filters were applied (`YYYY-MM-DD HH:MM:SS`)
+    pub time: String,
+    pub file: PathBuf,
+    pub filters: Vec<FilterCondition>,
+}
+
+impl HistoryEntry {
+    /// The filters as the WHERE clause they produce, without the keyword
+    pub fn summary(&self) -> String {
+        build_where_clause(&self.filters)
+            .trim_start_matches("WHERE ")
+            .to_string()
+    }
+
+    /// Case-insensitive match against the file name and the filters
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.file.to_string_lossy().to_lowercase().contains(&query)
+            || self.summary().to_lowercase().contains(&query)
+    }
+}
+
+/// Appends one line to `<data dir>/ftool/history.jsonl`
+pub fn append(file: PathBuf, filters: Vec<FilterCondition>) -> anyhow::Result<()> {
+    let dir = config::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let entry = HistoryEntry {
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        file,
+        filters,
+    };
+    let mut f = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(HISTORY_FILE))?;
+    writeln!(f, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+/// Reads the history, newest first; unparsable lines are skipped
+pub fn load() -> Vec<HistoryEntry> {
+    let Some(path) = config::data_dir().map(|d| d.join(HISTORY_FILE)) else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(MAX_ENTRIES)
+        .collect()
+}
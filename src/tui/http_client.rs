@@ -0,0 +1,244 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+use crate::commands::backup;
+use crate::commands::env::is_secret;
+use crate::commands::http::{HttpRequest, expand_env, parse_header};
+use crate::config;
+
+const HISTORY_FILE: &str = "requests.jsonl";
+
+/// Entries kept in the history file; older ones are dropped when it grows past this
+const MAX_ENTRIES: usize = 200;
+
+pub const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Focused part of the HTTP client screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HttpField {
+    Method,
+    Url,
+    Headers,
+    Body,
+    History,
+}
+
+impl HttpField {
+    const ORDER: [HttpField; 5] = [HttpField::Method, HttpField::Url, HttpField::Headers, HttpField::Body, HttpField::History];
+
+    fn step(self, forward: bool) -> Self {
+        let i = Self::ORDER.iter().position(|f| *f == self).unwrap_or(0);
+        let len = Self::ORDER.len();
+        Self::ORDER[if forward { (i + 1) % len } else { (i + len - 1) % len }]
+    }
+}
+
+/// A request sent from the HTTP client, appended to the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestEntry {
+    /// Local time the request was sent (`YYYY-MM-DD HH:MM:SS`)
+    pub time: String,
+    pub request: HttpRequest,
+    /// Response status, if one came back
+    pub status: Option<u16>,
+}
+
+/// HTTP client screen: a request form whose JSON responses open in the JSON
+/// inspector, and the requests sent before
+#[derive(Debug)]
+pub struct HttpClientState {
+    /// Index into `METHODS`
+    pub method: usize,
+    pub url: String,
+    /// One `Name: value` per line
+    pub headers: String,
+    pub body: String,
+    pub field: HttpField,
+    /// Newest first
+    pub history: Vec<RequestEntry>,
+    pub history_selected: usize,
+}
+
+impl Default for HttpClientState {
+    fn default() -> Self {
+        Self {
+            method: 0,
+            url: String::new(),
+            headers: String::new(),
+            body: String::new(),
+            field: HttpField::Url,
+            history: load(),
+            history_selected: 0,
+        }
+    }
+}
+
+impl HttpClientState {
+    pub fn method(&self) -> &'static str {
+        METHODS[self.method]
+    }
+
+    pub fn next_field(&mut self, forward: bool) {
+        self.field = self.field.step(forward);
+    }
+
+    pub fn cycle_method(&mut self, forward: bool) {
+        let len = METHODS.len();
+        self.method = if forward { (self.method + 1) % len } else { (self.method + len - 1) % len };
+    }
+
+    /// The text field being typed in, if the focus is on one
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            HttpField::Url => Some(&mut self.url),
+            HttpField::Headers => Some(&mut self.headers),
+            HttpField::Body => Some(&mut self.body),
+            HttpField::Method | HttpField::History => None,
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(text) = self.text_mut() {
+            text.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(text) = self.text_mut() {
+            text.pop();
+        }
+    }
+
+    pub fn history_up(&mut self) {
+        self.history_selected = self.history_selected.saturating_sub(1);
+    }
+
+    pub fn history_down(&mut self) {
+        if self.history_selected + 1 < self.history.len() {
+            self.history_selected += 1;
+        }
+    }
+
+    /// Fills the form with the selected history entry
+    pub fn load_selected(&mut self) {
+        let Some(entry) = self.history.get(self.history_selected) else { return };
+        let request = entry.request.clone();
+        self.method = METHODS.iter().position(|m| *m == request.method).unwrap_or(0);
+        self.url = request.url;
+        self.headers = request.headers.iter().map(|(n, v)| format!("{}: {}", n, v)).collect::<Vec<_>>().join("\n");
+        self.body = request.body.unwrap_or_default();
+        self.field = HttpField::Url;
+    }
+
+    /// The form as a request, as written (`$NAME` header values unexpanded)
+    pub fn request(&self) -> Result<HttpRequest, String> {
+        let url = self.url.trim();
+        if !crate::commands::http::is_url(url) {
+            return Err("The URL must start with http:// or https://".to_string());
+        }
+        let headers = self
+            .headers
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(parse_header)
+            .collect::<Result<Vec<_>, _>>()?;
+        let body = (!self.body.trim().is_empty()).then(|| self.body.clone());
+        Ok(HttpRequest { method: self.method().to_string(), url: url.to_string(), headers, body })
+    }
+
+    /// Records a sent request at the top of the history and in the history file
+    pub fn remember(&mut self, request: &HttpRequest, status: Option<u16>) -> anyhow::Result<()> {
+        let entry = RequestEntry {
+            time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            request: without_secrets(request),
+            status,
+        };
+        self.history.insert(0, entry.clone());
+        self.history.truncate(MAX_ENTRIES);
+        self.history_selected = 0;
+        append(&entry)
+    }
+}
+
+/// `request` with `$NAME` header values read from the environment
+pub fn expand(request: &HttpRequest) -> anyhow::Result<HttpRequest> {
+    let mut expanded = request.clone();
+    for (name, value) in &mut expanded.headers {
+        *value = expand_env(value).map_err(|e| anyhow::anyhow!("Header {}: {}", name, e))?;
+    }
+    Ok(expanded)
+}
+
+/// Secret header values (`Authorization`, `X-Api-Key`...) are left out of the
+/// history unless they are `$NAME` references, and so are the URL's
+/// `user:password@` and secret query parameters. The body is dropped too when
+/// `http_history_bodies` is off.
+fn without_secrets(request: &HttpRequest) -> HttpRequest {
+    let mut request = request.clone();
+    for (name, value) in &mut request.headers {
+        if is_secret(name) && !value.starts_with('$') {
+            value.clear();
+        }
+    }
+    request.url = without_credentials(&request.url);
+    if !config::get().keeps_http_bodies() {
+        request.body = None;
+    }
+    request
+}
+
+/// `url` without its `user:password@`, and with the values of query
+/// parameters that look secret (`token`, `api_key`, `sig`...) emptied
+fn without_credentials(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else { return url.to_string() };
+    let (authority, rest) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let Some((path, query)) = rest.split_once('?') else {
+        return format!("{}://{}{}", scheme, host, rest);
+    };
+    let (query, fragment) = match query.split_once('#') {
+        Some((query, fragment)) => (query, format!("#{}", fragment)),
+        None => (query, String::new()),
+    };
+    let params: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if is_secret(name) || ["key", "sig", "code"].contains(&name.to_ascii_lowercase().as_str()) => {
+                format!("{}=", name)
+            }
+            _ => pair.to_string(),
+        })
+        .collect();
+    format!("{}://{}{}?{}{}", scheme, host, path, params.join("&"), fragment)
+}
+
+/// Appends one line to `<data dir>/ftool/requests.jsonl`. Past `MAX_ENTRIES`
+/// lines, the file is rewritten with the newest ones.
+fn append(entry: &RequestEntry) -> anyhow::Result<()> {
+    let dir = config::data_dir().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(HISTORY_FILE);
+    let mut f = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(f, "{}", serde_json::to_string(entry)?)?;
+    drop(f);
+
+    let content = std::fs::read_to_string(&path)?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() > MAX_ENTRIES {
+        let kept = lines[lines.len() - MAX_ENTRIES..].join("\n");
+        backup::write_atomic(&path, format!("{}\n", kept))?;
+    }
+    Ok(())
+}
+
+/// Reads the request history, newest first; unparsable lines are skipped
+fn load() -> Vec<RequestEntry> {
+    let Some(path) = config::data_dir().map(|d| d.join(HISTORY_FILE)) else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content.lines().rev().filter_map(|line| serde_json::from_str(line).ok()).take(MAX_ENTRIES).collect()
+}
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::duckdb_inspector::{estimate_csv_rows, RowCount};
+use crate::commands::json_inspector::{is_geojson_seq, is_json_lines};
+use crate::commands::units::format_size;
+use crate::config;
+
+/// Rows of a sampled CSV or JSON Lines file, and items kept of each array of a
+/// sampled JSON document
+pub const SAMPLE_ROWS: usize = 10_000;
+
+/// How much of a JSON Lines file is read when streaming it from its end
+pub const STREAM_BYTES: u64 = 4 * 1024 * 1024;
+
+/// How a file is loaded into an inspector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenMode {
+    /// The whole file
+    Full,
+    /// Its first `SAMPLE_ROWS` rows (or items of each array)
+    Sample,
+    /// CSV: read as it scrolls with an estimated row count, never counted in full.
+    /// JSON Lines: its last records, followed as the file grows.
+    Stream,
+}
+
+impl OpenMode {
+    pub fn label(self, csv: bool) -> String {
+        match (self, csv) {
+            (OpenMode::Full, _) => "Load anyway".to_string(),
+            (OpenMode::Sample, true) => format!("Sample the first {} rows", SAMPLE_ROWS),
+            (OpenMode::Sample, false) => format!("Sample the first {} items of each array", SAMPLE_ROWS),
+            (OpenMode::Stream, true) => "Stream: scroll through it without counting rows".to_string(),
+            (OpenMode::Stream, false) => "Stream: read the last records and follow the file".to_string(),
+        }
+    }
+}
+
+/// A file over the `large_json_mb` or `large_csv_rows` threshold, waiting for
+/// the user to pick how to open it
+#[derive(Debug, Clone)]
+pub struct LargeFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Estimated rows, for CSVs
+    pub rows: Option<RowCount>,
+    pub modes: Vec<OpenMode>,
+    pub selected: usize,
+}
+
+impl LargeFile {
+    pub fn is_csv(&self) -> bool {
+        self.rows.is_some()
+    }
+
+    pub fn mode(&self) -> OpenMode {
+        self.modes.get(self.selected).copied().unwrap_or(OpenMode::Full)
+    }
+
+    /// `<size>, ≈<rows> rows` for the popup
+    pub fn describe(&self) -> String {
+        match self.rows {
+            Some(rows) => format!("{}, {} rows", format_size(self.size), rows),
+            None => format_size(self.size),
+        }
+    }
+}
+
+/// Whether `path` is large enough to ask before loading it: a JSON file over
+/// `large_json_mb`, or a CSV whose estimated rows exceed `large_csv_rows`.
+/// CSVs are estimated from their first lines, so the check stays quick.
+pub fn check(path: &Path) -> Option<LargeFile> {
+    let size = std::fs::metadata(path).ok()?.len();
+    let ext = path.extension().and_then(|e| e.to_str())?;
+    let config = config::get();
+    let (rows, modes) = match ext {
+        "csv" => {
+            let limit = config.large_csv_rows();
+            let rows = estimate_csv_rows(path).ok()?;
+            if limit == 0 || rows.value() as u64 <= limit {
+                return None;
+            }
+            (Some(rows), vec![OpenMode::Full, OpenMode::Sample, OpenMode::Stream])
+        }
+        "json" | "geojson" | "jsonl" | "ndjson" | "geojsonl" | "geojsons" => {
+            let limit = config.large_json_mb();
+            if limit == 0 || size <= limit * 1024 * 1024 {
+                return None;
+            }
+            let mut modes = vec![OpenMode::Full, OpenMode::Sample];
+            if is_json_lines(path) || is_geojson_seq(path) {
+                modes.push(OpenMode::Stream);
+            }
+            (None, modes)
+        }
+        _ => return None,
+    };
+    tracing::debug!(file = %path.display(), size, "large file, asking how to open it");
+    Some(LargeFile { path: path.to_path_buf(), size, rows, modes, selected: 0 })
+}
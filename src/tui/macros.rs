@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+
+use crossterm::event::KeyEvent;
+
+/// Keys recorded with `Q` and played back with `@`, and the last action `.`
+/// repeats. An action is the keys from one pressed with no popup open until
+/// no popup is left open, such as `c` `Enter` for a conversion; single keys
+/// that never open a popup (moving around) don't count.
+#[derive(Debug, Default)]
+pub struct KeyMacros {
+    /// Keys typed since `Q` started recording
+    recording: Option<Vec<KeyEvent>>,
+    /// The macro recorded last
+    recorded: Vec<KeyEvent>,
+    /// Keys of the action in progress
+    action: Vec<KeyEvent>,
+    last_action: Vec<KeyEvent>,
+    /// Keys waiting to be played back
+    pending: VecDeque<KeyEvent>,
+}
+
+impl KeyMacros {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Starts recording, or stops and keeps what was recorded; returns
+    /// whether it is now recording
+    pub fn toggle_recording(&mut self) -> bool {
+        match self.recording.take() {
+            Some(keys) => {
+                self.recorded = keys;
+                false
+            }
+            None => {
+                self.recording = Some(Vec::new());
+                true
+            }
+        }
+    }
+
+    /// Notes a key that was just handled. `popup_before` and `popup_after`
+    /// tell whether a popup was open before and after it.
+    pub fn record(&mut self, key: KeyEvent, popup_before: bool, popup_after: bool) {
+        if let Some(keys) = &mut self.recording {
+            keys.push(key);
+        }
+        if !popup_before {
+            self.action = vec![key];
+        } else if !self.action.is_empty() {
+            self.action.push(key);
+        } else {
+            // Closing a popup a background task opened
+            return;
+        }
+        if popup_after {
+            return;
+        }
+        let action = std::mem::take(&mut self.action);
+        if action.len() > 1 {
+            self.last_action = action;
+        }
+    }
+
+    /// Queues the recorded macro; false when nothing was recorded
+    pub fn play(&mut self) -> bool {
+        self.pending.extend(self.recorded.iter().copied());
+        !self.recorded.is_empty()
+    }
+
+    /// Queues the last action; false when there is none yet
+    pub fn repeat(&mut self) -> bool {
+        self.pending.extend(self.last_action.iter().copied());
+        !self.last_action.is_empty()
+    }
+
+    /// The next key to play back
+    pub fn next_pending(&mut self) -> Option<KeyEvent> {
+        self.pending.pop_front()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Drops the keys left to play, e.g. after one of them failed
+    pub fn stop(&mut self) {
+        self.pending.clear();
+    }
+}
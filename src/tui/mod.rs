@@ -4,30 +4,109 @@ mod terminal;
 mod views;
 mod widgets;
 pub mod tree;
+pub mod session;
+mod error_log;
+mod history;
+mod persistence;
+mod presets;
+mod column_types;
+mod file_state;
+mod large_file;
+mod dir_preview;
+mod macros;
+mod completion;
+mod scratchpad;
+mod decode;
+mod catalog;
+mod env;
+mod palette;
+mod http_client;
+mod task;
+mod text;
+mod theme;
 
-use app::App;
-use std::path::PathBuf;
+use app::{App, Message, Popup};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-pub fn run(path: Option<String>) -> anyhow::Result<()> {
-    terminal::install_panic_hook();
+use crate::commands::http::HttpResponse;
 
+pub fn run(path: Option<String>, error_log: Option<PathBuf>) -> anyhow::Result<()> {
     let path = path.map(PathBuf::from);
-    let mut app = App::new(path)?;
+    run_app(App::new(path, error_log)?)
+}
+
+/// Opens the JSON body of an HTTP response, saved to `path`, in the JSON
+/// inspector with the status and headers in a Response tab
+pub fn run_response(path: &Path, response: HttpResponse) -> anyhow::Result<()> {
+    let mut app = App::new(None, None)?;
+    // Not the time to offer restoring the last session
+    app.popup = Popup::None;
+    app.open_response(path, response)?;
+    run_app(app)
+}
+
+/// Deletes the pasted JSON temp files however the TUI exits
+struct PastedFiles;
+
+impl Drop for PastedFiles {
+    fn drop(&mut self) {
+        crate::commands::json_inspector::remove_pasted();
+    }
+}
+
+fn run_app(mut app: App) -> anyhow::Result<()> {
+    let _pasted = PastedFiles;
+    terminal::install_panic_hook();
     let mut terminal = terminal::init()?;
 
     loop {
         terminal.draw(|frame| app.view(frame))?;
 
-        if let Some(ev) = event::poll_event()? {
-            let msg = app.handle_event(ev);
+        // Apply results from background tasks before handling new input
+        while let Some(msg) = app.tasks.try_recv() {
             app.update(msg);
         }
 
+        match event::poll_event()? {
+            Some(ev) => app.handle_input(ev),
+            None => app.update(Message::Tick),
+        }
+
+        if let Some(dir) = app.pending_shell.take() {
+            let result = run_shell(&mut terminal, &dir);
+            app.update(Message::ShellExited(result));
+        }
+
         if app.should_quit {
             break;
         }
     }
 
     terminal::restore()?;
+
+    app.remember_files();
+    if let Err(e) = persistence::save(&app.saved_session()) {
+        eprintln!("Failed to save session: {}", e);
+    }
+    Ok(())
+}
+
+/// Suspends the TUI and runs the user's shell in `dir` until it exits
+fn run_shell(terminal: &mut terminal::Tui, dir: &Path) -> anyhow::Result<()> {
+    let shell = if cfg!(windows) {
+        std::env::var("COMSPEC").unwrap_or_else(|_| "cmd".to_string())
+    } else {
+        std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string())
+    };
+
+    terminal::restore()?;
+    println!("ftool: type `exit` to return");
+    tracing::info!(%shell, dir = %dir.display(), "spawning shell");
+    let status = Command::new(&shell).current_dir(dir).status();
+    terminal::resume(terminal)?;
+
+    let status = status.map_err(|e| anyhow::anyhow!("Failed to start {}: {}", shell, e))?;
+    tracing::debug!(%status, "shell exited");
     Ok(())
 }
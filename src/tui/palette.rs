@@ -0,0 +1,58 @@
+use crate::commands::random::GenKind;
+
+use super::views::home::MENU;
+
+/// An action run from the command palette (`Ctrl-P`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteCommand {
+    /// A Home menu entry, by index into `MENU`
+    Menu(usize),
+    ErrorLog,
+    /// Revert the last file ftool wrote
+    UndoWrite,
+    /// Bring back the files the browser last moved to the trash
+    RestoreDeleted,
+    /// Generate a value and copy it to the clipboard
+    Generate(GenKind),
+}
+
+impl PaletteCommand {
+    pub fn all() -> Vec<PaletteCommand> {
+        (0..MENU.len())
+            .map(PaletteCommand::Menu)
+            .chain([PaletteCommand::ErrorLog, PaletteCommand::UndoWrite, PaletteCommand::RestoreDeleted])
+            .chain(GenKind::ALL.map(PaletteCommand::Generate))
+            .collect()
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            PaletteCommand::Menu(i) => MENU[i].to_string(),
+            PaletteCommand::ErrorLog => "Error Log".to_string(),
+            PaletteCommand::UndoWrite => "Undo last write".to_string(),
+            PaletteCommand::RestoreDeleted => "Restore last deleted".to_string(),
+            PaletteCommand::Generate(kind) => format!("Generate {} to clipboard", kind.label()),
+        }
+    }
+}
+
+/// Searchable list of palette commands
+#[derive(Debug, Clone, Default)]
+pub struct PaletteState {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl PaletteState {
+    /// Commands whose label contains every word of the query (ignoring case)
+    pub fn visible(&self) -> Vec<PaletteCommand> {
+        let query = self.query.to_lowercase();
+        PaletteCommand::all()
+            .into_iter()
+            .filter(|c| {
+                let label = c.label().to_lowercase();
+                query.split_whitespace().all(|word| label.contains(word))
+            })
+            .collect()
+    }
+}
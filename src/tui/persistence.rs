@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::commands::backup;
+use crate::config;
+
+use super::app::{FilterCondition, GeoJsonTab, InspectorTab, JsonInspectorTab, Screen};
+use super::session::{DataInspectorState, JsonInspectorState, Session};
+
+const SESSION_FILE: &str = "session.json";
+
+/// Snapshot of the TUI written on quit so the next launch can pick up where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub screen: Screen,
+    pub current_dir: PathBuf,
+    pub files: Vec<SavedFile>,
+    pub active_file: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SavedFile {
+    Data {
+        path: PathBuf,
+        tab: InspectorTab,
+        filters: Vec<FilterCondition>,
+        /// Row at the top of the preview
+        #[serde(default)]
+        row: usize,
+        col_page: usize,
+        selected_col: usize,
+        scroll: usize,
+    },
+    Json {
+        path: PathBuf,
+        tab: JsonInspectorTab,
+        geo_tab: GeoJsonTab,
+        scroll: usize,
+    },
+}
+
+pub fn save(session: &SavedSession) -> anyhow::Result<()> {
+    let dir = config::data_dir().ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let json = serde_json::to_string_pretty(session)?;
+    backup::write_atomic(&dir.join(SESSION_FILE), json)?;
+    Ok(())
+}
+
+/// Loads the last saved session, if one exists and can be parsed
+pub fn load() -> Option<SavedSession> {
+    let path = config::data_dir()?.join(SESSION_FILE);
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+impl SavedFile {
+    pub fn from_session(session: &Session) -> Self {
+        match session {
+            Session::Data(s) => SavedFile::Data {
+                path: s.file.clone(),
+                tab: s.tab.clone(),
+                filters: s.filters.clone(),
+                row: s.top_row,
+                col_page: s.col_page,
+                selected_col: s.selected_col,
+                scroll: s.scroll,
+            },
+            Session::Json(s) => SavedFile::Json {
+                path: s.file.clone(),
+                tab: s.tab.clone(),
+                geo_tab: s.geo_tab.clone(),
+                scroll: s.scroll,
+            },
+        }
+    }
+
+    /// Reopens the file and reapplies the saved filters, rows, column page and scroll position
+    pub fn open(&self) -> anyhow::Result<Session> {
+        match self {
+            SavedFile::Data { path, tab, filters, row, col_page, selected_col, scroll } => {
+                let mut state = DataInspectorState::open(path)?;
+                if !filters.is_empty() {
+                    state.apply_filters(filters.clone())?;
+                }
+                state.top_row = (*row).min(state.row_count.saturating_sub(1));
+                state.col_page = (*col_page).min(state.total_col_pages().saturating_sub(1));
+                state.load_preview()?;
+                state.selected_col = (*selected_col).min(state.preview_headers.len().saturating_sub(1));
+                state.tab = tab.clone();
+                state.scroll = *scroll;
+                Ok(Session::Data(state))
+            }
+            SavedFile::Json { path, tab, geo_tab, scroll } => {
+                let mut state = JsonInspectorState::open(path)?;
+                // The response isn't saved, only the body it was read from
+                state.tab = if *tab == JsonInspectorTab::Response { JsonInspectorTab::Tree } else { tab.clone() };
+                state.geo_tab = geo_tab.clone();
+                state.scroll = *scroll;
+                Ok(Session::Json(state))
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::commands::backup;
+use crate::config;
+
+use super::app::FilterCondition;
+
+const PRESETS_FILE: &str = "filter_presets.json";
+
+/// A named set of filters, saved either for one file or for every file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    /// The file the preset belongs to; `None` makes it available everywhere
+    pub file: Option<PathBuf>,
+    pub filters: Vec<FilterCondition>,
+}
+
+impl FilterPreset {
+    pub fn applies_to(&self, path: &Path) -> bool {
+        self.file.as_deref().is_none_or(|f| f == path)
+    }
+}
+
+/// Reads every saved preset; a missing or unreadable file yields none
+pub fn load() -> Vec<FilterPreset> {
+    let Some(path) = config::config_dir().map(|d| d.join(PRESETS_FILE)) else {
+        return Vec::new();
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(presets: &[FilterPreset]) -> anyhow::Result<()> {
+    let dir = config::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    backup::write_atomic(&dir.join(PRESETS_FILE), serde_json::to_string_pretty(presets)?)?;
+    Ok(())
+}
+
+/// Presets usable on `path`, in the order they were saved
+pub fn for_file(path: &Path) -> Vec<FilterPreset> {
+    load().into_iter().filter(|p| p.applies_to(path)).collect()
+}
+
+/// Adds `preset`, replacing one with the same name and scope
+pub fn add(preset: FilterPreset) -> anyhow::Result<()> {
+    let mut presets = load();
+    presets.retain(|p| !(p.name == preset.name && p.file == preset.file));
+    presets.push(preset);
+    save(&presets)
+}
+
+pub fn remove(preset: &FilterPreset) -> anyhow::Result<()> {
+    let mut presets = load();
+    presets.retain(|p| !(p.name == preset.name && p.file == preset.file));
+    save(&presets)
+}
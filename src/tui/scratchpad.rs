@@ -0,0 +1,87 @@
+use crate::commands::geometry::{self, Geometry, GeometryFormat};
+use crate::tui::session::{GeoMap, MAP_SAMPLE};
+
+/// Geometry scratchpad: pasted WKT, hex WKB or GeoJSON, parsed as it is typed
+/// and shown again in the chosen output format
+#[derive(Debug, Default)]
+pub struct ScratchpadState {
+    pub input: String,
+    /// The geometry and the format it was read from, or why it couldn't be
+    /// read; `None` while the input is empty
+    pub parsed: Option<Result<(Geometry, GeometryFormat), String>>,
+    pub output: GeometryFormat,
+    /// The geometry's vertices for the map
+    pub map: Option<GeoMap>,
+    /// The input changed since it was last parsed. Typed (or pasted) keys
+    /// are parsed together on the next idle tick rather than one by one.
+    dirty: bool,
+}
+
+impl ScratchpadState {
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.dirty = true;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.dirty = true;
+    }
+
+    /// Parses the input again if it changed
+    pub fn refresh(&mut self) {
+        if self.dirty {
+            self.reparse();
+        }
+    }
+
+    pub fn set_input(&mut self, text: String) {
+        self.input = text;
+        self.reparse();
+    }
+
+    /// Shows the next output format, skipping the one the input is already in
+    pub fn next_format(&mut self) {
+        let position = |f: GeometryFormat| GeometryFormat::ALL.iter().position(|g| *g == f).unwrap_or(0);
+        let mut next = GeometryFormat::ALL[(position(self.output) + 1) % GeometryFormat::ALL.len()];
+        if let Some(Ok((_, source))) = &self.parsed
+            && next == *source
+        {
+            next = GeometryFormat::ALL[(position(next) + 1) % GeometryFormat::ALL.len()];
+        }
+        self.output = next;
+    }
+
+    pub fn geometry(&self) -> Option<&Geometry> {
+        self.parsed.as_ref().and_then(|p| p.as_ref().ok()).map(|(g, _)| g)
+    }
+
+    /// The geometry converted to the output format
+    pub fn output_text(&self) -> Option<String> {
+        self.geometry().map(|g| g.to_format(self.output))
+    }
+
+    fn reparse(&mut self) {
+        self.dirty = false;
+        self.map = None;
+        if self.input.trim().is_empty() {
+            self.parsed = None;
+            return;
+        }
+        let parsed = geometry::parse(&self.input).map_err(|e| format!("{:#}", e));
+        if let Ok((geometry, source)) = &parsed {
+            // Converting to the format just pasted isn't useful: show another one
+            if self.output == *source {
+                self.output = GeometryFormat::ALL.into_iter().find(|f| f != source).unwrap_or_default();
+            }
+            let vertices = geometry.vertices();
+            let step = vertices.len().div_ceil(MAP_SAMPLE).max(1);
+            self.map = Some(GeoMap {
+                label: geometry.type_name().to_string(),
+                points: vertices.iter().step_by(step).map(|c| (c[0], c[1])).collect(),
+                total: vertices.len(),
+            });
+        }
+        self.parsed = Some(parsed);
+    }
+}
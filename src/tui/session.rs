@@ -0,0 +1,1490 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Rect;
+
+use crate::commands::duckdb_inspector::{ColumnStats, DecimalFormat, DuckDbError, RowCount};
+use crate::commands::geo::GeoColumn;
+use crate::commands::http::HttpResponse;
+use crate::commands::notes::{self, FileNotes};
+use crate::commands::json_inspector::{
+    append_records, as_feature, feature_properties, geojson_summary, prune, records_table, FileKind,
+    JsonLinesTail, PruneStats, Pruning,
+};
+use crate::commands::transform::ComputedColumn;
+use crate::commands::backup;
+use crate::config;
+use crate::commands::{DuckDbInspector, JsonInspector};
+use crate::tui::task::CancelToken;
+use crate::tui::tree::{build_tree, NodeKind, TreeNode};
+
+use super::column_types;
+use super::file_state;
+use super::text;
+use super::widgets::frozen_table::MIN_COL_WIDTH;
+use super::large_file::{OpenMode, SAMPLE_ROWS, STREAM_BYTES};
+use super::app::{FilterCondition, GeoJsonTab, InspectorTab, JsonInspectorTab, COLUMN_PAGE_SIZE};
+
+pub use crate::commands::profile::is_numeric_type;
+
+/// An open inspector tab. Each session owns the full state of its inspector so
+/// switching between tabs preserves filters, pages and scroll positions.
+pub enum Session {
+    Data(DataInspectorState),
+    Json(JsonInspectorState),
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Session").field(&self.path()).finish()
+    }
+}
+
+impl Session {
+    pub fn path(&self) -> &Path {
+        match self {
+            Session::Data(s) => &s.file,
+            Session::Json(s) => &s.file,
+        }
+    }
+
+    /// Short label used in the session tab bar
+    pub fn label(&self) -> String {
+        self.path()
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Rows fetched above and below the preview's viewport, so scrolling only
+/// goes back to DuckDB once it leaves them
+pub const PREVIEW_MARGIN: usize = 100;
+
+/// Preview rows assumed before the first frame gives the real viewport height
+const DEFAULT_VIEWPORT_ROWS: usize = 40;
+
+/// Cells a preview column grows or shrinks by with `>`/`<`
+const COL_WIDTH_STEP: u16 = 4;
+
+/// Widest a column can be made with `>`, before the preview is first drawn
+const MAX_SET_COL_WIDTH: u16 = 200;
+
+/// Rows of the Schema tab, counted from the scroll position, whose stats are
+/// computed as soon as they are shown
+pub const STATS_WINDOW: usize = 50;
+
+/// Load state of one column's stats in the Schema tab
+#[derive(Debug, Clone)]
+pub enum StatsCell {
+    NotLoaded,
+    Loading,
+    Loaded(ColumnStats),
+    Failed,
+}
+
+/// Buckets the x range is split into when a chart is downsampled
+pub const CHART_POINTS: usize = 200;
+
+/// Buckets in the Schema tab's distribution sparklines
+pub const HISTOGRAM_BUCKETS: usize = 8;
+
+/// Most points drawn in a scatter plot; larger files are sampled by DuckDB
+pub const SCATTER_SAMPLE: usize = 2000;
+
+/// Points drawn on the map preview of a geo column
+pub const MAP_SAMPLE: usize = 5000;
+
+/// Two numeric columns plotted against each other, shown in a popup
+#[derive(Debug, Clone)]
+pub struct ScatterPlot {
+    pub x: String,
+    pub y: String,
+    pub points: Vec<(f64, f64)>,
+    /// Points beyond 1.5 × IQR from the quartiles on either axis
+    pub outliers: Vec<(f64, f64)>,
+    /// Rows with both values set, of which `points` and `outliers` are a sample
+    pub total: usize,
+}
+
+impl ScatterPlot {
+    fn new(x: String, y: String, sample: Vec<(f64, f64)>, total: usize) -> Self {
+        let (x_lo, x_hi) = fences(sample.iter().map(|p| p.0).collect());
+        let (y_lo, y_hi) = fences(sample.iter().map(|p| p.1).collect());
+        let (outliers, points) = sample
+            .into_iter()
+            .partition(|&(px, py)| px < x_lo || px > x_hi || py < y_lo || py > y_hi);
+        Self { x, y, points, outliers, total }
+    }
+
+    pub fn sampled(&self) -> usize {
+        self.points.len() + self.outliers.len()
+    }
+}
+
+/// Sampled points of a geo column, drawn over a world map in a popup
+#[derive(Debug, Clone)]
+pub struct GeoMap {
+    pub label: String,
+    /// (x, y) pairs: longitude and latitude for geographic data
+    pub points: Vec<(f64, f64)>,
+    /// Rows with a point, of which `points` is a sample
+    pub total: usize,
+}
+
+/// Tukey fences: values outside them count as outliers
+fn fences(mut values: Vec<f64>) -> (f64, f64) {
+    if values.is_empty() {
+        return (f64::NEG_INFINITY, f64::INFINITY);
+    }
+    values.sort_by(f64::total_cmp);
+    let quartile = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+    let (q1, q3) = (quartile(0.25), quartile(0.75));
+    let iqr = q3 - q1;
+    (q1 - 1.5 * iqr, q3 + 1.5 * iqr)
+}
+
+/// Column choices and the last plotted data of the Chart tab
+#[derive(Debug, Clone, Default)]
+pub struct ChartState {
+    /// Schema index highlighted in the column list
+    pub cursor: usize,
+    pub x: Option<usize>,
+    pub ys: Vec<usize>,
+    /// Name of the plotted x column, as of the last plot
+    pub x_label: String,
+    /// (column name, (x, y) points) per plotted y column, as of the last plot
+    pub series: Vec<(String, Vec<(f64, f64)>)>,
+    /// Whether the plotted x values are seconds since the epoch
+    pub x_is_temporal: bool,
+}
+
+pub fn is_temporal_type(ty: &str) -> bool {
+    ty == "DATE" || ty.starts_with("TIMESTAMP")
+}
+
+/// Rows each filter condition is tried on alone to find the one that failed
+const FILTER_CHECK_ROWS: usize = 1000;
+
+/// Why applying filters failed, put in terms of the condition at fault
+#[derive(Debug, Clone)]
+pub struct FilterFailure {
+    /// Index of the failing condition, when one could be singled out
+    pub condition: Option<usize>,
+    pub message: String,
+    /// A way around it, such as the format the column expects or a cast
+    pub hint: Option<String>,
+}
+
+/// How often a followed file is checked for new rows
+pub const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watching a data file that another process is still writing
+#[derive(Debug, Clone)]
+pub struct FollowState {
+    /// File size at the last check; `None` until the first recount
+    pub size: Option<u64>,
+    pub checked: Instant,
+    /// A recount is running in the background
+    pub counting: bool,
+}
+
+/// What a background task needs to open a data file again the way its
+/// session reads it: number format, type overrides, unnested columns and the
+/// sample's row limit
+#[derive(Debug, Clone)]
+pub struct InspectorSpec {
+    pub path: PathBuf,
+    /// `None` for the decimal comma saved for the file, whose grouped columns
+    /// are looked for again on open
+    decimal_format: Option<DecimalFormat>,
+    types: Vec<(String, String)>,
+    unnested: Vec<String>,
+    row_limit: Option<usize>,
+}
+
+impl InspectorSpec {
+    /// A file with no open session, read with the types and decimal comma
+    /// saved on an earlier visit
+    pub fn for_file(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            decimal_format: None,
+            types: column_types::for_file(path),
+            unnested: Vec::new(),
+            row_limit: None,
+        }
+    }
+
+    pub fn row_limit(&self) -> Option<usize> {
+        self.row_limit
+    }
+
+    /// Opens the file with its connection registered on `cancel`
+    pub fn open(&self, cancel: &CancelToken) -> Result<DuckDbInspector, DuckDbError> {
+        let mut inspector =
+            DuckDbInspector::new(self.path.to_string_lossy().to_string())?.with_row_limit(self.row_limit);
+        cancel.register(&inspector);
+        match &self.decimal_format {
+            Some(format) => inspector.set_decimal_format(format.clone()),
+            None if file_state::for_file(&self.path).is_some_and(|f| f.decimal_comma) => {
+                inspector.set_decimal_comma(true)?
+            }
+            None => {}
+        }
+        inspector.with_types(&self.types)?.with_unnested(&self.unnested)
+    }
+
+    /// The file's number format with a decimal comma, along with the columns
+    /// that also group thousands, which takes a scan of every row
+    pub fn detect_decimal_comma(&self, cancel: &CancelToken) -> Result<DecimalFormat, DuckDbError> {
+        let mut inspector =
+            DuckDbInspector::new(self.path.to_string_lossy().to_string())?.with_row_limit(self.row_limit);
+        cancel.register(&inspector);
+        inspector.set_decimal_comma(true)?;
+        Ok(inspector.decimal_format().clone())
+    }
+
+    /// The file's geometry and coordinate columns, which takes reading a
+    /// sample of its rows
+    pub fn geo_columns(&self, cancel: &CancelToken) -> Result<Vec<GeoColumn>, DuckDbError> {
+        let inspector = self.open(cancel)?;
+        let schema = inspector.schema()?;
+        inspector.geo_columns(&schema)
+    }
+}
+
+pub struct DataInspectorState {
+    pub inspector: DuckDbInspector,
+    pub file: PathBuf,
+    pub tab: InspectorTab,
+    pub schema: Vec<(String, String)>,
+    /// Per-column stats, filled in lazily as columns scroll into view on the Schema tab
+    pub stats: Vec<StatsCell>,
+    pub preview_headers: Vec<String>,
+    /// Preview cells as text, `None` for SQL NULL
+    pub preview_data: Vec<Vec<Option<String>>>,
+    pub row_count: usize,
+    /// Set while `row_count` is extrapolated from the first lines of a CSV
+    pub row_count_estimated: bool,
+    /// An exact count is running in the background
+    pub counting_rows: bool,
+    /// Numbers are read with a decimal comma whose thousands-grouped columns
+    /// are still to be looked for: set until a detection completes, so a
+    /// cancelled one runs again
+    pub decimal_comma_pending: bool,
+    /// A decimal comma detection is running in the background
+    pub detecting_decimal_comma: bool,
+    /// Opened in the large-file Stream mode: nothing scans the whole file
+    /// (row count, stats) until asked for with `#` or the Schema tab
+    pub lazy: bool,
+    pub scroll: usize,
+    /// Row (0-based, in filtered order) at the top of the preview; the one
+    /// `v` and `t` show
+    pub top_row: usize,
+    /// Row of `preview_data[0]`: the preview holds a window of rows around the
+    /// viewport, fetched again only when scrolling leaves it
+    pub preview_start: usize,
+    /// Rows the preview had room for when last drawn
+    pub viewport_rows: Cell<usize>,
+    pub col_page: usize,
+    pub selected_col: usize,
+    /// Key column kept visible at the left of the preview on every column page
+    pub pinned_col: Option<String>,
+    /// 1 when the pinned column was prepended to `preview_headers` because it
+    /// isn't on the current column page, so `selected_col` is offset by it
+    pub preview_offset: usize,
+    /// Show the selected column at its full content width (`e`)
+    pub expand_selected: bool,
+    /// Preview column widths set with `<`/`>`, by column name
+    pub col_widths: BTreeMap<String, u16>,
+    pub filters: Vec<FilterCondition>,
+    /// SQL expressions shown as extra preview columns after the file's own
+    pub computed: Vec<ComputedColumn>,
+    /// Whether conversions from the TUI also write the computed columns
+    pub computed_in_export: bool,
+    /// Column types chosen instead of DuckDB's inferred ones, as (column, type)
+    pub type_overrides: Vec<(String, String)>,
+    pub chart: ChartState,
+    /// Kind and elapsed time of the last preview/filter/stats query, shown in the info bar
+    pub last_query: Option<(&'static str, Duration)>,
+    /// Set while the file is followed for rows appended to it (`W`)
+    pub follow: Option<FollowState>,
+    /// Columns recognised as geometries or coordinates when the file was opened
+    pub geo: Vec<GeoColumn>,
+    /// `geo` is still to be looked for: set until a detection succeeds, so
+    /// one that was cancelled or failed runs again
+    pub geo_pending: bool,
+    /// A geo column detection is running in the background
+    pub detecting_geo: bool,
+    /// Where the preview table was last drawn, to map mouse clicks to cells
+    pub preview_area: Cell<Rect>,
+    /// Notes on the file and its columns, from `ftool note`
+    pub notes: FileNotes,
+}
+
+impl DataInspectorState {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Self::open_cancellable(path, &CancelToken::default())
+    }
+
+    /// Opens `path` with its connection registered on `cancel`, so a slow first
+    /// scan (e.g. counting a huge CSV) can be interrupted
+    pub fn open_cancellable(path: &Path, cancel: &CancelToken) -> anyhow::Result<Self> {
+        Self::open_with(path, cancel, OpenMode::Full)
+    }
+
+    /// Opens `path` whole, as a sample of its first rows, or (for a CSV too big
+    /// to count) with its row count estimated instead of scanned
+    pub fn open_with(path: &Path, cancel: &CancelToken, mode: OpenMode) -> anyhow::Result<Self> {
+        let mut inspector = DuckDbInspector::new(path.to_string_lossy().to_string())?
+            .with_row_limit((mode == OpenMode::Sample).then_some(SAMPLE_ROWS));
+        cancel.register(&inspector);
+
+        // A decimal comma chosen on an earlier visit, before the types computed
+        // for it; its grouped columns are found once the preview is up
+        let decimal_comma_pending = file_state::for_file(path).is_some_and(|f| f.decimal_comma);
+        if decimal_comma_pending {
+            inspector.set_decimal_format(DecimalFormat::Comma { grouped: Vec::new() });
+        }
+
+        if let Err(e) = inspector.set_display_timezone(config::get().timezone()) {
+            tracing::warn!(file = %path.display(), error = %e, "ignoring timezone setting");
+        }
+
+        // Types pinned on an earlier visit; dropped if the file no longer fits them
+        let mut type_overrides = column_types::for_file(path);
+        if let Err(e) = inspector.set_types(&type_overrides) {
+            tracing::warn!(file = %path.display(), error = %e, "ignoring saved column types");
+            type_overrides.clear();
+        }
+
+        let schema = inspector.schema()?;
+        // Counted exactly in the background once the preview is up
+        let row_count = inspector.quick_row_count()?;
+
+        let mut state = Self {
+            inspector,
+            file: path.to_path_buf(),
+            tab: InspectorTab::Preview,
+            // Stats are loaded lazily, per column, when the Schema tab shows them
+            stats: vec![StatsCell::NotLoaded; schema.len()],
+            schema,
+            preview_headers: Vec::new(),
+            preview_data: Vec::new(),
+            row_count: row_count.value(),
+            row_count_estimated: row_count.is_estimated(),
+            counting_rows: false,
+            decimal_comma_pending,
+            detecting_decimal_comma: false,
+            lazy: mode == OpenMode::Stream,
+            scroll: 0,
+            top_row: 0,
+            preview_start: 0,
+            viewport_rows: Cell::new(DEFAULT_VIEWPORT_ROWS),
+            col_page: 0,
+            selected_col: 0,
+            pinned_col: None,
+            preview_offset: 0,
+            expand_selected: false,
+            col_widths: BTreeMap::new(),
+            filters: Vec::new(),
+            computed: Vec::new(),
+            computed_in_export: false,
+            type_overrides,
+            chart: ChartState::default(),
+            last_query: None,
+            follow: None,
+            // Sampled in the background once the preview is up
+            geo: Vec::new(),
+            geo_pending: true,
+            detecting_geo: false,
+            preview_area: Cell::default(),
+            notes: notes::for_file(path),
+        };
+
+        // Preview data (only visible columns)
+        state.load_preview()?;
+
+        Ok(state)
+    }
+
+    /// How a background task opens the file to read it as this session does
+    pub fn reopen_spec(&self) -> InspectorSpec {
+        InspectorSpec {
+            path: self.file.clone(),
+            decimal_format: Some(self.inspector.decimal_format().clone()),
+            types: self.type_overrides.clone(),
+            unnested: self.inspector.unnested(),
+            row_limit: self.inspector.row_limit(),
+        }
+    }
+
+    /// Row count for display, prefixed with `≈` when estimated
+    pub fn row_count_label(&self) -> String {
+        if self.row_count_estimated {
+            RowCount::Estimated(self.row_count).to_string()
+        } else {
+            self.row_count.to_string()
+        }
+    }
+
+    /// Rows drawn in the preview, as of the last frame
+    pub fn viewport(&self) -> usize {
+        self.viewport_rows.get().max(1)
+    }
+
+    /// The top row that shows the last rows at the bottom of the preview
+    pub fn last_top_row(&self) -> usize {
+        self.row_count.saturating_sub(self.viewport())
+    }
+
+    /// Index in `preview_data` of the row at the top of the preview
+    pub fn preview_skip(&self) -> usize {
+        self.top_row.saturating_sub(self.preview_start)
+    }
+
+    /// The rows on screen
+    pub fn visible_rows(&self) -> &[Vec<Option<String>>] {
+        let start = self.preview_skip().min(self.preview_data.len());
+        let end = (start + self.viewport()).min(self.preview_data.len());
+        &self.preview_data[start..end]
+    }
+
+    pub fn total_col_pages(&self) -> usize {
+        self.schema.len().div_ceil(COLUMN_PAGE_SIZE)
+    }
+
+    /// Compute visible column names for the current column page
+    pub fn visible_columns(&self) -> Vec<String> {
+        let all_cols: Vec<String> = self.schema.iter().map(|(n, _)| n.clone()).collect();
+        let start = self.col_page * COLUMN_PAGE_SIZE;
+        let end = (start + COLUMN_PAGE_SIZE).min(all_cols.len());
+        if start >= all_cols.len() {
+            return all_cols; // fallback: show all if page is out of range
+        }
+        all_cols[start..end].to_vec()
+    }
+
+    pub fn switch_tab(&mut self) {
+        self.scroll = 0;
+        self.tab = match self.tab {
+            InspectorTab::Schema => InspectorTab::Preview,
+            InspectorTab::Preview => InspectorTab::Chart,
+            InspectorTab::Chart => InspectorTab::Schema,
+        };
+    }
+
+    /// Scrolls the Schema tab; the preview scrolls with `scroll_rows`
+    pub fn scroll_up(&mut self) {
+        if self.scroll > 0 {
+            self.scroll -= 1;
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        let max = match self.tab {
+            InspectorTab::Schema => self.schema.len(),
+            InspectorTab::Preview | InspectorTab::Chart => 0,
+        };
+        if self.scroll + 1 < max {
+            self.scroll += 1;
+        }
+    }
+
+    /// Moves the preview `delta` rows down (up when negative), stopping once
+    /// the last row is at the bottom
+    pub fn scroll_rows(&mut self, delta: isize) -> Result<(), DuckDbError> {
+        if self.tab != InspectorTab::Preview {
+            return Ok(());
+        }
+        let last = if self.row_count_estimated {
+            // The estimate may fall short of the real count: scroll on past it
+            // until a page comes back short, which gives the exact count
+            self.last_top_row().max(self.preview_start + self.preview_data.len())
+        } else {
+            self.last_top_row()
+        };
+        self.show_row(self.top_row.saturating_add_signed(delta).min(last))?;
+        if !self.row_count_estimated && self.top_row > self.last_top_row() {
+            self.show_row(self.last_top_row())?;
+        }
+        Ok(())
+    }
+
+    /// Scrolls the preview by a screenful
+    pub fn scroll_screen(&mut self, down: bool) -> Result<(), DuckDbError> {
+        let screen = self.viewport() as isize;
+        self.scroll_rows(if down { screen } else { -screen })
+    }
+
+    /// Puts `row` at the top of the preview, querying DuckDB only when the
+    /// rows now on screen aren't all in the loaded window
+    pub fn show_row(&mut self, row: usize) -> Result<(), DuckDbError> {
+        self.top_row = row;
+        let loaded_end = self.preview_start + self.preview_data.len();
+        let wanted_end = if self.row_count_estimated {
+            row + self.viewport()
+        } else {
+            (row + self.viewport()).min(self.row_count)
+        };
+        if row < self.preview_start || wanted_end > loaded_end {
+            self.load_preview()?;
+        }
+        Ok(())
+    }
+
+    pub fn next_col_page(&mut self) -> Result<(), DuckDbError> {
+        if self.tab != InspectorTab::Preview {
+            return Ok(());
+        }
+        if self.col_page + 1 < self.total_col_pages() {
+            self.col_page += 1;
+            self.selected_col = 0;
+            self.load_preview()?;
+        }
+        Ok(())
+    }
+
+    pub fn prev_col_page(&mut self) -> Result<(), DuckDbError> {
+        if self.tab != InspectorTab::Preview {
+            return Ok(());
+        }
+        if self.col_page > 0 {
+            self.col_page -= 1;
+            self.selected_col = 0;
+            self.load_preview()?;
+        }
+        Ok(())
+    }
+
+    pub fn col_left(&mut self) -> Result<(), DuckDbError> {
+        if self.tab != InspectorTab::Preview {
+            return Ok(());
+        }
+        if self.selected_col > 0 {
+            self.selected_col -= 1;
+        } else if self.col_page > 0 {
+            self.col_page -= 1;
+            let visible_len = self.visible_columns().len();
+            self.selected_col = visible_len.saturating_sub(1);
+            self.load_preview()?;
+        }
+        Ok(())
+    }
+
+    pub fn col_right(&mut self) -> Result<(), DuckDbError> {
+        if self.tab != InspectorTab::Preview {
+            return Ok(());
+        }
+        let visible_count = self.visible_columns().len();
+        if self.selected_col + 1 < visible_count {
+            self.selected_col += 1;
+        } else if self.col_page + 1 < self.total_col_pages() {
+            self.col_page += 1;
+            self.selected_col = 0;
+            self.load_preview()?;
+        }
+        Ok(())
+    }
+
+    /// Columns shown on the Schema tab whose stats haven't been requested yet
+    pub fn visible_missing_stats(&self) -> Vec<usize> {
+        if self.tab != InspectorTab::Schema {
+            return Vec::new();
+        }
+        let end = (self.scroll + STATS_WINDOW).min(self.stats.len());
+        (self.scroll..end)
+            .filter(|&i| matches!(self.stats[i], StatsCell::NotLoaded))
+            .collect()
+    }
+
+    /// The first Schema tab rows, computed in the background as soon as the
+    /// file opens so their stats are there when the tab is shown
+    pub fn prefetch_stats(&self) -> Vec<usize> {
+        if self.lazy {
+            return Vec::new();
+        }
+        (0..STATS_WINDOW.min(self.stats.len()))
+            .filter(|&i| matches!(self.stats[i], StatsCell::NotLoaded))
+            .collect()
+    }
+
+    /// Every column without stats, including failed or cancelled ones (`Enter` on the Schema tab)
+    pub fn all_missing_stats(&self) -> Vec<usize> {
+        if self.tab != InspectorTab::Schema {
+            return Vec::new();
+        }
+        (0..self.stats.len())
+            .filter(|&i| matches!(self.stats[i], StatsCell::NotLoaded | StatsCell::Failed))
+            .collect()
+    }
+
+    /// Marks `columns` as loading and returns their names, with whether each is
+    /// numeric (and so gets a histogram)
+    pub fn start_stats(&mut self, columns: &[usize]) -> Vec<(String, bool)> {
+        columns
+            .iter()
+            .map(|&i| {
+                self.stats[i] = StatsCell::Loading;
+                let (name, ty) = &self.schema[i];
+                (name.clone(), is_numeric_type(ty))
+            })
+            .collect()
+    }
+
+    pub fn set_stats(&mut self, columns: &[usize], stats: Vec<ColumnStats>, elapsed: Duration) {
+        self.last_query = Some(("stats", elapsed));
+        for (&i, stat) in columns.iter().zip(stats) {
+            self.stats[i] = StatsCell::Loaded(stat);
+        }
+    }
+
+    /// Marks `columns` as failed (or cancelled) so they aren't retried on every tick
+    pub fn fail_stats(&mut self, columns: &[usize]) {
+        for &i in columns {
+            self.stats[i] = StatsCell::Failed;
+        }
+    }
+
+    pub fn chart_cursor_up(&mut self) {
+        self.chart.cursor = self.chart.cursor.saturating_sub(1);
+    }
+
+    pub fn chart_cursor_down(&mut self) {
+        if self.chart.cursor + 1 < self.schema.len() {
+            self.chart.cursor += 1;
+        }
+    }
+
+    /// Uses the highlighted column as the x axis; it must be numeric or a date/time
+    pub fn chart_set_x(&mut self) -> Result<(), DuckDbError> {
+        let (name, ty) = &self.schema[self.chart.cursor];
+        if !is_numeric_type(ty) && !is_temporal_type(ty) {
+            return Err(DuckDbError::InvalidColumn(format!(
+                "{} ({}) can't be an x axis: pick a numeric or date/time column",
+                name, ty
+            )));
+        }
+        self.chart.x = Some(self.chart.cursor);
+        self.chart.ys.retain(|&y| y != self.chart.cursor);
+        Ok(())
+    }
+
+    /// Adds or removes the highlighted column as a plotted series; it must be numeric
+    pub fn chart_toggle_y(&mut self) -> Result<(), DuckDbError> {
+        let cursor = self.chart.cursor;
+        if let Some(pos) = self.chart.ys.iter().position(|&y| y == cursor) {
+            self.chart.ys.remove(pos);
+            return Ok(());
+        }
+        let (name, ty) = &self.schema[cursor];
+        if !is_numeric_type(ty) || self.chart.x == Some(cursor) {
+            return Err(DuckDbError::InvalidColumn(format!(
+                "{} ({}) can't be plotted: pick a numeric column other than the x axis",
+                name, ty
+            )));
+        }
+        self.chart.ys.push(cursor);
+        Ok(())
+    }
+
+    /// Loads the chart data for the chosen columns, downsampled by DuckDB and
+    /// respecting the active filters
+    pub fn plot_chart(&mut self) -> Result<(), DuckDbError> {
+        let Some(x) = self.chart.x else {
+            return Err(DuckDbError::InvalidColumn("Pick an x axis column first (X)".to_string()));
+        };
+        if self.chart.ys.is_empty() {
+            return Err(DuckDbError::InvalidColumn("Pick at least one y column (Space)".to_string()));
+        }
+        let (x_name, x_type) = &self.schema[x];
+        let x_is_temporal = is_temporal_type(x_type);
+        let ys: Vec<String> = self.chart.ys.iter().map(|&i| self.schema[i].0.clone()).collect();
+        let where_clause = build_where_clause(&self.filters);
+
+        let started = Instant::now();
+        let series = self.inspector.chart_series(x_name, x_is_temporal, &ys, &where_clause, CHART_POINTS)?;
+        self.chart.x_label = x_name.clone();
+        self.chart.series = ys.into_iter().zip(series).collect();
+        self.chart.x_is_temporal = x_is_temporal;
+        self.last_query = Some(("chart", started.elapsed()));
+        Ok(())
+    }
+
+    /// Samples the chart's x column against its single y column, respecting the
+    /// active filters
+    pub fn scatter(&mut self) -> Result<ScatterPlot, DuckDbError> {
+        let (Some(x), [y]) = (self.chart.x, self.chart.ys.as_slice()) else {
+            return Err(DuckDbError::InvalidColumn(
+                "A scatter plot needs an x column (X) and exactly one y column (Space)".to_string(),
+            ));
+        };
+        let (x_name, x_type) = &self.schema[x];
+        if !is_numeric_type(x_type) {
+            return Err(DuckDbError::InvalidColumn(format!(
+                "{} ({}) can't be a scatter plot axis: pick a numeric column",
+                x_name, x_type
+            )));
+        }
+        let y_name = &self.schema[*y].0;
+        let where_clause = build_where_clause(&self.filters);
+
+        let started = Instant::now();
+        let (sample, total) = self.inspector.scatter_sample(x_name, y_name, &where_clause, SCATTER_SAMPLE)?;
+        let plot = ScatterPlot::new(x_name.clone(), y_name.clone(), sample, total);
+        self.last_query = Some(("scatter", started.elapsed()));
+        Ok(plot)
+    }
+
+    /// Index in `preview_headers` of the pinned column, if one is set
+    pub fn pinned_preview_col(&self) -> Option<usize> {
+        let pinned = self.pinned_col.as_ref()?;
+        if self.preview_offset > 0 {
+            return Some(0);
+        }
+        self.preview_headers.iter().position(|h| h == pinned)
+    }
+
+    /// Every column of the row at the top of the preview, with values in full
+    /// (long ones wrapped), for the row detail popup
+    pub fn row_detail(&self) -> Option<(usize, Vec<String>)> {
+        const WRAP: usize = 72;
+        let row = self.preview_data.get(self.preview_skip())?;
+        let number = self.top_row + 1;
+        let mut lines = Vec::new();
+        for (i, (header, value)) in self.preview_headers.iter().zip(row).enumerate() {
+            if i < self.preview_offset {
+                continue; // pinned column borrowed from another column page
+            }
+            lines.push(format!("{}:", header));
+            let Some(value) = value else {
+                lines.push("  \u{2205} (NULL)".to_string());
+                continue;
+            };
+            if value.is_empty() {
+                lines.push("  \"\" (empty string)".to_string());
+            }
+            // Wrapped by display width, so CJK and emoji lines don't overflow
+            for line in text::wrap(value, WRAP) {
+                lines.push(format!("  {}", line));
+            }
+        }
+        Some((number, lines))
+    }
+
+    /// Pins the selected column, or unpins it if it is the pinned one
+    pub fn toggle_pin(&mut self) -> Result<(), DuckDbError> {
+        if self.tab != InspectorTab::Preview {
+            return Ok(());
+        }
+        let Some(selected) = self.visible_columns().get(self.selected_col).cloned() else {
+            return Ok(());
+        };
+        self.pinned_col = if self.pinned_col.as_ref() == Some(&selected) { None } else { Some(selected) };
+        self.load_preview()
+    }
+
+    /// Widens (or narrows) the selected column by `COL_WIDTH_STEP` from
+    /// `current`, the width it is drawn at, up to the width of the preview
+    pub fn resize_selected_col(&mut self, current: u16, grow: bool) {
+        let Some(name) = self.preview_headers.get(self.preview_offset + self.selected_col) else {
+            return;
+        };
+        let widest = match self.preview_area.get().width {
+            0 => MAX_SET_COL_WIDTH,
+            pane => pane.max(MIN_COL_WIDTH),
+        };
+        let width = if grow {
+            current.saturating_add(COL_WIDTH_STEP).min(widest.max(current))
+        } else {
+            current.saturating_sub(COL_WIDTH_STEP).max(MIN_COL_WIDTH)
+        };
+        self.col_widths.insert(name.clone(), width);
+    }
+
+    /// Offset and row limit of the preview query: the viewport with
+    /// `PREVIEW_MARGIN` rows above and below
+    fn preview_window(&self) -> (usize, usize) {
+        let start = self.top_row.saturating_sub(PREVIEW_MARGIN);
+        (start, self.top_row - start + self.viewport() + PREVIEW_MARGIN)
+    }
+
+    /// Fetches the rows around the viewport for the visible columns
+    pub fn load_preview(&mut self) -> Result<(), DuckDbError> {
+        let where_clause = build_where_clause(&self.filters);
+        let mut cols = self.visible_columns();
+        self.preview_offset = 0;
+        if let Some(pinned) = &self.pinned_col
+            && !cols.contains(pinned)
+        {
+            cols.insert(0, pinned.clone());
+            self.preview_offset = 1;
+        }
+        let (offset, limit) = self.preview_window();
+        let started = Instant::now();
+        let (headers, data) = self
+            .inspector
+            .preview(limit, offset, &where_clause, Some(&cols), &self.computed)?;
+        self.last_query = Some(("preview", started.elapsed()));
+        // A short page that still holds rows (or starts the file) ends it: the
+        // estimate gives way to the exact count
+        if self.row_count_estimated && data.len() < limit && (offset == 0 || !data.is_empty()) {
+            self.row_count = offset + data.len();
+            self.row_count_estimated = false;
+        }
+        self.preview_headers = headers;
+        self.preview_data = data;
+        self.preview_start = offset;
+        Ok(())
+    }
+
+    /// Column under the cursor: the top Schema row or the selected Preview column
+    pub fn focused_column(&self) -> Option<String> {
+        match self.tab {
+            InspectorTab::Schema => self.schema.get(self.scroll).map(|(n, _)| n.clone()),
+            InspectorTab::Preview => self.preview_headers.get(self.preview_offset + self.selected_col).cloned(),
+            InspectorTab::Chart => None,
+        }
+    }
+
+    /// The geo column (or coordinate pair) `column` belongs to
+    pub fn geo_column(&self, column: &str) -> Option<&GeoColumn> {
+        self.geo.iter().find(|g| g.contains(column))
+    }
+
+    /// Starts following the file from its last rows, or stops following it
+    pub fn toggle_follow(&mut self) -> Result<(), DuckDbError> {
+        if self.follow.take().is_some() {
+            return Ok(());
+        }
+        self.follow = Some(FollowState { size: None, checked: Instant::now(), counting: false });
+        self.show_row(self.last_top_row())
+    }
+
+    /// Whether a followed file changed size since the last check, looking at
+    /// most once per `FOLLOW_INTERVAL`. Marks the recount as started.
+    pub fn follow_due(&mut self) -> bool {
+        let Some(follow) = self
+            .follow
+            .as_mut()
+            .filter(|f| !f.counting && (f.size.is_none() || f.checked.elapsed() >= FOLLOW_INTERVAL))
+        else {
+            return false;
+        };
+        follow.checked = Instant::now();
+        let Ok(size) = std::fs::metadata(&self.file).map(|m| m.len()) else {
+            return false;
+        };
+        if follow.size == Some(size) {
+            return false;
+        }
+        follow.size = Some(size);
+        follow.counting = true;
+        true
+    }
+
+    /// Applies the row count of a followed file: scrolls to the new last rows
+    /// if the last rows were showing, and reloads the preview either way
+    pub fn follow_counted(&mut self, count: usize) -> Result<(), DuckDbError> {
+        let Some(follow) = self.follow.as_mut() else {
+            return Ok(());
+        };
+        follow.counting = false;
+        let at_end = self.top_row >= self.last_top_row();
+        self.row_count = count;
+        self.row_count_estimated = false;
+        if at_end {
+            self.top_row = self.last_top_row();
+        }
+        self.load_preview()
+    }
+
+    /// Shows the Preview tab with `row` (0-based, in file order) on top
+    pub fn jump_to_row(&mut self, row: usize) -> Result<(), DuckDbError> {
+        self.tab = InspectorTab::Preview;
+        self.top_row = row.min(self.row_count.saturating_sub(1));
+        self.load_preview()
+    }
+
+    /// Replaces the active filters and reloads the row count and first page;
+    /// if DuckDB rejects them, the previous filters stay in effect
+    pub fn apply_filters(&mut self, filters: Vec<FilterCondition>) -> Result<(), DuckDbError> {
+        let previous = std::mem::replace(&mut self.filters, filters);
+        if let Err(e) = self.load_filtered() {
+            self.filters = previous;
+            self.load_filtered()?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn load_filtered(&mut self) -> Result<(), DuckDbError> {
+        self.top_row = 0;
+        self.scroll = 0;
+
+        let where_clause = build_where_clause(&self.filters);
+        let started = Instant::now();
+        self.row_count = self.inspector.row_count_filtered(&where_clause)?;
+        self.row_count_estimated = false;
+        self.load_preview()?;
+        self.last_query = Some(("filter", started.elapsed()));
+        Ok(())
+    }
+
+    /// Explains `error`, returned when applying `filters`: each condition is
+    /// tried alone on the first rows to find the one DuckDB rejects, and its
+    /// error is read for what went wrong
+    pub fn explain_filter_error(&self, filters: &[FilterCondition], error: &DuckDbError) -> FilterFailure {
+        let failed = filters.iter().enumerate().find_map(|(i, filter)| {
+            let where_clause = build_where_clause(std::slice::from_ref(filter));
+            self.inspector.check_filter(&where_clause, FILTER_CHECK_ROWS).err().map(|e| (i, filter, e))
+        });
+        let Some((index, filter, e)) = failed else {
+            let message = error.to_string().lines().next().unwrap_or_default().to_string();
+            return FilterFailure { condition: None, message, hint: None };
+        };
+        let ty = self.schema.iter().find(|(name, _)| *name == filter.column).map_or("", |(_, ty)| ty.as_str());
+        let csv = self.file.extension().is_some_and(|ext| ext == "csv");
+        let (message, hint) = describe_filter_error(filter, ty, &e.to_string(), csv);
+        FilterFailure { condition: Some(index), message, hint }
+    }
+
+    /// DuckDB's plan for the current preview query, filters included
+    pub fn explain_preview(&self) -> Result<String, DuckDbError> {
+        let where_clause = build_where_clause(&self.filters);
+        let (offset, limit) = self.preview_window();
+        self.inspector.explain_preview(
+            limit,
+            offset,
+            &where_clause,
+            &self.visible_columns(),
+            &self.computed,
+        )
+    }
+
+    /// Adds a computed column, dropping it again if the preview query rejects it
+    pub fn add_computed(&mut self, column: ComputedColumn) -> Result<(), DuckDbError> {
+        self.computed.retain(|c| c.name != column.name);
+        self.computed.push(column);
+        if let Err(e) = self.load_preview() {
+            self.computed.pop();
+            self.load_preview()?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn remove_last_computed(&mut self) -> Result<(), DuckDbError> {
+        if self.computed.pop().is_some() {
+            self.load_preview()?;
+        }
+        Ok(())
+    }
+
+    /// Reads schema column `column` as `ty` instead of its inferred type (an
+    /// empty `ty` restores the inferred one) and saves the choice for the next
+    /// time the file is opened
+    pub fn set_column_type(&mut self, column: usize, ty: &str) -> Result<(), DuckDbError> {
+        let name = self.schema[column].0.clone();
+        let previous = self.type_overrides.clone();
+        let mut overrides = previous.clone();
+        overrides.retain(|(n, _)| *n != name);
+        if !ty.trim().is_empty() {
+            overrides.push((name, ty.trim().to_uppercase()));
+        }
+
+        self.inspector.set_types(&overrides)?;
+        // A type the values can't be cast to only fails once rows are read
+        if let Err(e) = self.load_preview() {
+            self.inspector.set_types(&previous)?;
+            self.load_preview()?;
+            return Err(e);
+        }
+        self.type_overrides = overrides;
+        self.schema = self.inspector.schema()?;
+        self.stats = vec![StatsCell::NotLoaded; self.schema.len()];
+        if let Err(e) = column_types::save(&self.file, &self.type_overrides) {
+            tracing::warn!(error = %e, "failed to save column types");
+        }
+        Ok(())
+    }
+
+    /// Reads the file's numbers in `format`: with a decimal comma (`1.234,56`)
+    /// as found by `InspectorSpec::detect_decimal_comma`, or back with a
+    /// decimal point, keeping the type overrides
+    pub fn set_decimal_format(&mut self, format: DecimalFormat) -> Result<(), DuckDbError> {
+        let previous = self.inspector.decimal_format().clone();
+        if let Err(e) = self.read_decimal_format(format) {
+            self.inspector.set_types(&[])?;
+            self.inspector.set_decimal_format(previous);
+            self.inspector.set_types(&self.type_overrides)?;
+            self.load_preview()?;
+            return Err(e);
+        }
+        self.schema = self.inspector.schema()?;
+        self.stats = vec![StatsCell::NotLoaded; self.schema.len()];
+        Ok(())
+    }
+
+    fn read_decimal_format(&mut self, format: DecimalFormat) -> Result<(), DuckDbError> {
+        // The overrides were computed for the previous format
+        self.inspector.set_types(&[])?;
+        self.inspector.set_decimal_format(format);
+        self.inspector.set_types(&self.type_overrides)?;
+        self.load_preview()
+    }
+
+    /// Explodes the LIST column `column` into a row per element, or flattens the
+    /// STRUCT column into a column per field, then recounts the rows; on error
+    /// the file is read as before
+    pub fn unnest_column(&mut self, column: &str) -> Result<(), DuckDbError> {
+        self.inspector.unnest_column(column)?;
+        if let Err(e) = self.reload_schema() {
+            self.inspector.pop_unnest();
+            self.reload_schema()?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Reads every unnested column nested again
+    pub fn clear_unnest(&mut self) -> Result<(), DuckDbError> {
+        if self.inspector.unnested().is_empty() {
+            return Ok(());
+        }
+        self.inspector.clear_unnest();
+        self.reload_schema()
+    }
+
+    /// Rereads the schema and row count after the columns changed, keeping
+    /// the filters
+    fn reload_schema(&mut self) -> Result<(), DuckDbError> {
+        self.schema = self.inspector.schema()?;
+        self.stats = vec![StatsCell::NotLoaded; self.schema.len()];
+        self.pinned_col = self.pinned_col.take().filter(|c| self.schema.iter().any(|(name, _)| name == c));
+        self.col_page = self.col_page.min(self.total_col_pages().saturating_sub(1));
+        self.apply_filters(self.filters.clone())?;
+        self.selected_col = self.selected_col.min(self.visible_columns().len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// The computed columns to write on export, if exporting them is enabled
+    pub fn export_computed(&self) -> Vec<ComputedColumn> {
+        if self.computed_in_export {
+            self.computed.clone()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// What DuckDB's `error` means for `filter` on a column of type `ty`, and a
+/// suggestion: the value format the type expects, or a cast to compare as text
+fn describe_filter_error(filter: &FilterCondition, ty: &str, error: &str, csv: bool) -> (String, Option<String>) {
+    let lower = error.to_lowercase();
+    let column = &filter.column;
+    let retype = if csv { ", or read the column as another type with T" } else { "" };
+    if lower.contains("conversion error") || lower.contains("could not convert") || lower.contains("invalid input") {
+        let expected = if ty == "DATE" {
+            "a date like 2024-03-01"
+        } else if ty.starts_with("TIMESTAMP") {
+            "a timestamp like 2024-03-01 10:00:00"
+        } else if ty == "TIME" {
+            "a time like 10:00:00"
+        } else if ty == "BOOLEAN" {
+            "true or false"
+        } else if is_numeric_type(ty) {
+            "a number like 1234.5"
+        } else {
+            "a value of that type"
+        };
+        return (
+            format!("Condition on \"{}\": '{}' is not a valid {}", column, filter.value, ty),
+            Some(format!("Type {}; to match the text instead use LIKE{}", expected, retype)),
+        );
+    }
+    if lower.contains("cannot compare") || lower.contains("no function matches") || lower.contains("explicit cast") {
+        return (
+            format!("Condition on \"{}\": {} values can't be compared with '{}'", column, ty, filter.value),
+            Some(format!(
+                "Add a computed column (a) like CAST(\"{0}\" AS VARCHAR) AS {0}_text and filter on it{1}",
+                column, retype
+            )),
+        );
+    }
+    let detail = error.lines().next().unwrap_or_default();
+    (format!("Condition on \"{}\": {}", column, detail), None)
+}
+
+pub fn build_where_clause(filters: &[FilterCondition]) -> String {
+    if filters.is_empty() {
+        return String::new();
+    }
+    let parts: Vec<String> = filters.iter().map(|f| {
+        let col = f.column.replace('"', "\"\"");
+        let v = f.value.replace('\'', "''");
+        match f.operator.as_str() {
+            "IS NULL"     => format!("\"{}\" IS NULL", col),
+            "IS NOT NULL" => format!("\"{}\" IS NOT NULL", col),
+            "LIKE"        => format!(
+                "\"{}\"::VARCHAR {} {}",
+                col,
+                if f.case_insensitive { "ILIKE" } else { "LIKE" },
+                f.like_mode.pattern(&v),
+            ),
+            "REGEXP"      => format!("regexp_matches(\"{}\"::VARCHAR, '{}')", col, v),
+            "NOT REGEXP"  => format!("NOT regexp_matches(\"{}\"::VARCHAR, '{}')", col, v),
+            op            => format!("\"{}\" {} '{}'", col, op, v),
+        }
+    }).collect();
+    format!("WHERE {}", parts.join(" AND "))
+}
+
+/// Every `[x, y, …]` position in a geometry's coordinates, or in the members
+/// of a GeometryCollection
+fn collect_vertices(value: &serde_json::Value, out: &mut Vec<(f64, f64)>) {
+    if let Some(geometries) = value.get("geometries").and_then(|g| g.as_array()) {
+        for geometry in geometries {
+            collect_vertices(geometry, out);
+        }
+        return;
+    }
+    let coordinates = value.get("coordinates").unwrap_or(value);
+    let Some(array) = coordinates.as_array() else { return };
+    match (array.first().and_then(|v| v.as_f64()), array.get(1).and_then(|v| v.as_f64())) {
+        (Some(x), Some(y)) => out.push((x, y)),
+        _ => {
+            for item in array {
+                collect_vertices(item, out);
+            }
+        }
+    }
+}
+
+pub struct JsonInspectorState {
+    pub file: PathBuf,
+    pub root: serde_json::Value,
+    pub kind: FileKind,
+    pub tab: JsonInspectorTab,
+    pub geo_tab: GeoJsonTab,
+    pub scroll: usize,
+    pub tree_nodes: Vec<(String, TreeNode)>,
+    pub collapsed: HashSet<String>,
+    pub features_headers: Vec<String>,
+    pub features_data: Vec<Vec<String>>,
+    pub geosummary: Option<(usize, Vec<String>, Option<(f64, f64, f64, f64)>)>,
+    pub raw: String,
+    /// Where reading stopped, for JSON Lines and GeoJSONSeq files
+    pub tail: Option<JsonLinesTail>,
+    /// Appending records written to a line-based file and keeping the last one in view
+    pub following: bool,
+    /// `tree_nodes` and `raw` are missing records appended while following
+    stale: bool,
+    /// Selected property column in the Features tab
+    pub features_col: usize,
+    /// Properties to drop and whether to deduplicate geometries on export
+    pub pruning: Pruning,
+    /// What `pruning` would save, recomputed when it changes
+    pub prune_stats: Option<PruneStats>,
+    /// Feature vertices for the Map tab, gathered when it is shown
+    pub geo_map: Option<GeoMap>,
+    /// The HTTP response the file was fetched from (its body cleared), shown in
+    /// the Response tab
+    pub response: Option<HttpResponse>,
+    /// Only a sample or the end of the file was loaded
+    pub partial: bool,
+}
+
+impl JsonInspectorState {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Self::open_with(path, OpenMode::Full)
+    }
+
+    /// Opens `path` whole, as a sample, or (JSON Lines) streamed from its end
+    /// and followed
+    pub fn open_with(path: &Path, mode: OpenMode) -> anyhow::Result<Self> {
+        let inspector = match mode {
+            OpenMode::Full => JsonInspector::new(path)?,
+            OpenMode::Sample => JsonInspector::sample(path, SAMPLE_ROWS)?,
+            OpenMode::Stream => JsonInspector::stream(path, STREAM_BYTES)?,
+        };
+        let raw = serde_json::to_string_pretty(&inspector.root)?;
+        let collapsed = HashSet::new();
+        let tree_nodes = build_tree(&inspector.root, &collapsed);
+
+        let (geosummary, features_headers, features_data) = match inspector.kind {
+            FileKind::GeoJson => {
+                let (headers, rows) = inspector.features_table();
+                (Some(inspector.geojson_summary()), headers, rows)
+            }
+            FileKind::JsonLines => {
+                let records = inspector.root.as_array().map(Vec::as_slice).unwrap_or_default();
+                let (headers, rows) = records_table(records);
+                (None, headers, rows)
+            }
+            FileKind::Json => (None, vec![], vec![]),
+        };
+        let tab = if inspector.kind == FileKind::JsonLines {
+            JsonInspectorTab::Records
+        } else {
+            JsonInspectorTab::Tree
+        };
+
+        let mut state = Self {
+            file: path.to_path_buf(),
+            kind: inspector.kind.clone(),
+            root: inspector.root,
+            tab,
+            geo_tab: GeoJsonTab::Summary,
+            scroll: 0,
+            tree_nodes,
+            collapsed,
+            features_headers,
+            features_data,
+            geosummary,
+            raw,
+            tail: inspector.tail,
+            following: false,
+            stale: false,
+            features_col: 0,
+            pruning: Pruning::default(),
+            prune_stats: None,
+            geo_map: None,
+            response: None,
+            partial: mode != OpenMode::Full,
+        };
+        if mode == OpenMode::Stream {
+            state.toggle_follow();
+        }
+        Ok(state)
+    }
+
+    pub fn is_geojson(&self) -> bool {
+        self.kind == FileKind::GeoJson
+    }
+
+    pub fn is_json_lines(&self) -> bool {
+        self.kind == FileKind::JsonLines
+    }
+
+    /// Whether the records table is on screen, rather than the tree or raw text
+    pub fn showing_table(&self) -> bool {
+        if self.is_geojson() {
+            self.geo_tab == GeoJsonTab::Features
+        } else {
+            self.tab == JsonInspectorTab::Records
+        }
+    }
+
+    pub fn switch_tab(&mut self) {
+        self.scroll = 0;
+        self.following = false;
+        self.tab = match self.tab {
+            JsonInspectorTab::Records => JsonInspectorTab::Tree,
+            JsonInspectorTab::Tree => JsonInspectorTab::Raw,
+            JsonInspectorTab::Raw if self.response.is_some() => JsonInspectorTab::Response,
+            JsonInspectorTab::Raw | JsonInspectorTab::Response if self.is_json_lines() => JsonInspectorTab::Records,
+            JsonInspectorTab::Raw | JsonInspectorTab::Response => JsonInspectorTab::Tree,
+        };
+        if self.tab != JsonInspectorTab::Records {
+            self.refresh_stale();
+        }
+    }
+
+    /// Rebuilds the tree and raw views once records were appended to `root`
+    fn refresh_stale(&mut self) {
+        if self.stale {
+            self.tree_nodes = build_tree(&self.root, &self.collapsed);
+            self.raw = serde_json::to_string_pretty(&self.root).unwrap_or_default();
+            self.stale = false;
+        }
+    }
+
+    pub fn features_col_left(&mut self) {
+        self.features_col = self.features_col.saturating_sub(1);
+    }
+
+    pub fn features_col_right(&mut self) {
+        if self.features_col + 1 < self.features_headers.len() {
+            self.features_col += 1;
+        }
+    }
+
+    /// Marks the selected property to be dropped from every feature, or keeps it again
+    pub fn toggle_drop_property(&mut self) {
+        let Some(name) = self.features_headers.get(self.features_col).cloned() else {
+            return;
+        };
+        if let Some(i) = self.pruning.drop.iter().position(|p| *p == name) {
+            self.pruning.drop.remove(i);
+        } else {
+            self.pruning.drop.push(name);
+        }
+        self.update_prune_stats();
+    }
+
+    pub fn toggle_dedupe(&mut self) {
+        self.pruning.dedupe = !self.pruning.dedupe;
+        self.update_prune_stats();
+    }
+
+    fn update_prune_stats(&mut self) {
+        self.prune_stats = (!self.pruning.is_empty()).then(|| prune(&self.root, &self.pruning).1);
+    }
+
+    /// The collection with the pruning applied, and `<name>.pruned.geojson`
+    /// next to the file where it's written (as compact JSON, like the size
+    /// preview assumes)
+    pub fn pruned(&self) -> (PathBuf, serde_json::Value) {
+        let (pruned, _) = prune(&self.root, &self.pruning);
+        let stem = self.file.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        (self.file.with_file_name(format!("{}.pruned.geojson", stem)), pruned)
+    }
+
+    /// Whether records are read line by line and can be followed
+    pub fn can_follow(&self) -> bool {
+        self.tail.is_some()
+    }
+
+    /// Starts or stops following a JSON Lines or GeoJSONSeq file, switching to
+    /// the records (or features) table and jumping to its last row
+    pub fn toggle_follow(&mut self) {
+        if !self.can_follow() {
+            return;
+        }
+        self.following = !self.following;
+        if self.following {
+            if self.is_geojson() {
+                self.geo_tab = GeoJsonTab::Features;
+            } else {
+                self.tab = JsonInspectorTab::Records;
+            }
+            self.scroll = self.features_data.len().saturating_sub(1);
+        }
+    }
+
+    /// Appends the records written since the last poll while following.
+    /// Returns how many were added.
+    pub fn poll_follow(&mut self) -> std::io::Result<usize> {
+        let Some(tail) = self.tail.as_mut().filter(|_| self.following) else {
+            return Ok(0);
+        };
+        let records = tail.read(&self.file)?;
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let added = records.len();
+        if self.is_geojson() {
+            let features: Vec<_> = records.into_iter().map(as_feature).collect();
+            append_records(&mut self.features_headers, &mut self.features_data, &feature_properties(&features));
+            if let Some(array) = self.root.get_mut("features").and_then(|f| f.as_array_mut()) {
+                array.extend(features);
+            }
+            self.geosummary = Some(geojson_summary(&self.root));
+            self.update_prune_stats();
+            if self.geo_map.is_some() {
+                self.geo_map = Some(self.feature_map());
+            }
+        } else {
+            append_records(&mut self.features_headers, &mut self.features_data, &records);
+            if let Some(array) = self.root.as_array_mut() {
+                array.extend(records.iter().cloned());
+            }
+        }
+        self.stale = true;
+        self.scroll = self.features_data.len().saturating_sub(1);
+        Ok(added)
+    }
+
+    pub fn switch_geo_tab(&mut self) {
+        self.scroll = 0;
+        self.following = false;
+        self.geo_tab = match self.geo_tab {
+            GeoJsonTab::Summary => GeoJsonTab::Features,
+            GeoJsonTab::Features => GeoJsonTab::Tree,
+            GeoJsonTab::Tree => GeoJsonTab::Map,
+            GeoJsonTab::Map => GeoJsonTab::Summary,
+        };
+        match self.geo_tab {
+            GeoJsonTab::Tree => self.refresh_stale(),
+            GeoJsonTab::Map => self.geo_map = Some(self.feature_map()),
+            _ => {}
+        }
+    }
+
+    /// Up to `MAP_SAMPLE` vertices spread evenly over all feature geometries
+    fn feature_map(&self) -> GeoMap {
+        let mut vertices = Vec::new();
+        if let Some(features) = self.root.get("features").and_then(|f| f.as_array()) {
+            for feature in features {
+                if let Some(geometry) = feature.get("geometry") {
+                    collect_vertices(geometry, &mut vertices);
+                }
+            }
+        }
+        let total = vertices.len();
+        let step = total.div_ceil(MAP_SAMPLE).max(1);
+        GeoMap {
+            label: self.file.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default(),
+            points: vertices.into_iter().step_by(step).collect(),
+            total,
+        }
+    }
+
+    /// Writes the collection to `<name>.geojson` next to a GPX, KML or GeoJSONSeq file
+    pub fn write_geojson(&self) -> anyhow::Result<PathBuf> {
+        if self.file.extension().is_some_and(|e| e == "geojson") {
+            anyhow::bail!("{} is already GeoJSON", self.file.display());
+        }
+        let output = self.file.with_extension("geojson");
+        backup::write(&output, serde_json::to_vec(&self.root)?)?;
+        Ok(output)
+    }
+
+    pub fn scroll_up(&mut self) {
+        // Scrolling back through the records stops following, like `less +F`
+        self.following = false;
+        if self.scroll > 0 {
+            self.scroll -= 1;
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        let max = if self.showing_table() {
+            self.features_data.len()
+        } else if !self.is_geojson() && self.tab == JsonInspectorTab::Response {
+            self.response.as_ref().map_or(0, |r| r.headers.len())
+        } else {
+            self.tree_nodes.len()
+        };
+        if self.scroll + 1 < max {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn toggle_tree_node(&mut self) {
+        if self.showing_table()
+            || (self.is_geojson() && self.geo_tab == GeoJsonTab::Map)
+            || (!self.is_geojson() && self.tab == JsonInspectorTab::Response)
+        {
+            return;
+        }
+        if let Some((path, node)) = self.tree_nodes.get(self.scroll) {
+            match &node.kind {
+                NodeKind::Object | NodeKind::Array => {
+                    let path = path.clone();
+                    if self.collapsed.contains(&path) {
+                        self.collapsed.remove(&path);
+                    } else {
+                        self.collapsed.insert(path);
+                    }
+                    self.tree_nodes = build_tree(&self.root, &self.collapsed);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use duckdb::InterruptHandle;
+
+use crate::commands::DuckDbInspector;
+
+use super::app::Message;
+
+/// Handed to a cancellable job so it can register the DuckDB connections it
+/// queries; cancelling interrupts whatever query they are running.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    handles: Arc<Mutex<Vec<Arc<InterruptHandle>>>>,
+}
+
+impl CancelToken {
+    /// Makes `inspector`'s queries interruptible; interrupts at once if already cancelled
+    pub fn register(&self, inspector: &DuckDbInspector) {
+        let handle = inspector.interrupt_handle();
+        if self.is_cancelled() {
+            handle.interrupt();
+        }
+        if let Ok(mut handles) = self.handles.lock() {
+            handles.push(handle);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        if let Ok(handles) = self.handles.lock() {
+            for handle in handles.iter() {
+                handle.interrupt();
+            }
+        }
+    }
+}
+
+/// Runs long operations (DuckDB scans, file loads) on worker threads and hands
+/// their results back to the event loop as messages, so rendering never blocks.
+pub struct Tasks {
+    tx: Sender<(u64, Message)>,
+    rx: Receiver<(u64, Message)>,
+    next_id: u64,
+    running: Vec<(u64, String, CancelToken)>,
+    cancelled: HashSet<u64>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            next_id: 0,
+            running: Vec::new(),
+            cancelled: HashSet::new(),
+        }
+    }
+
+    /// Spawns `job` on a worker thread; the message it returns is delivered by `try_recv`
+    pub fn spawn(&mut self, label: impl Into<String>, job: impl FnOnce() -> Message + Send + 'static) {
+        self.spawn_cancellable(label, move |_| job());
+    }
+
+    /// Like `spawn`, but `job` gets a token to register the connections `cancel_all` interrupts
+    pub fn spawn_cancellable(
+        &mut self,
+        label: impl Into<String>,
+        job: impl FnOnce(&CancelToken) -> Message + Send + 'static,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let label = label.into();
+        let token = CancelToken::default();
+        self.running.push((id, label.clone(), token.clone()));
+
+        let tx = self.tx.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            let msg = job(&token);
+            tracing::debug!(
+                task = %label,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "background task finished"
+            );
+            let _ = tx.send((id, msg));
+        });
+    }
+
+    /// Returns the next finished task's message, if any. Messages from cancelled
+    /// tasks come wrapped in `Message::TaskCancelled`.
+    pub fn try_recv(&mut self) -> Option<Message> {
+        let (id, msg) = self.rx.try_recv().ok()?;
+        self.running.retain(|(task_id, _, _)| *task_id != id);
+        if self.cancelled.remove(&id) {
+            return Some(Message::TaskCancelled(Box::new(msg)));
+        }
+        Some(msg)
+    }
+
+    /// Interrupts every running task; returns how many were cancelled
+    pub fn cancel_all(&mut self) -> usize {
+        let count = self.running.len();
+        for (id, label, token) in self.running.drain(..) {
+            tracing::debug!(task = %label, "background task cancelled");
+            token.cancel();
+            self.cancelled.insert(id);
+        }
+        count
+    }
+
+    /// Label of the oldest task still running
+    pub fn current(&self) -> Option<&str> {
+        self.running.first().map(|(_, label, _)| label.as_str())
+    }
+}
@@ -23,6 +23,14 @@ pub fn restore() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Re-enters the TUI after `restore` (e.g. when a child process exits) and forces a full redraw
+pub fn resume(terminal: &mut Tui) -> anyhow::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(())
+}
+
 pub fn install_panic_hook() {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
@@ -0,0 +1,53 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Terminal cells `value` takes: 2 for each CJK character or emoji, 0 for
+/// combining marks
+pub fn width(value: &str) -> usize {
+    value.width()
+}
+
+/// The longest start of `value` that fits in `max` cells, never splitting a
+/// wide character
+pub fn fit(value: &str, max: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in value.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > max {
+            return &value[..i];
+        }
+    }
+    value
+}
+
+/// `value` cut to `max` cells, ending in `…` when it doesn't fit
+pub fn truncate(value: &str, max: usize) -> String {
+    if width(value) <= max {
+        return value.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    format!("{}\u{2026}", fit(value, max - 1))
+}
+
+/// `value` padded with spaces to at least `cells` cells: `{:<cells}`, counting
+/// display width instead of chars
+pub fn pad(value: &str, cells: usize) -> String {
+    format!("{}{}", value, " ".repeat(cells.saturating_sub(width(value))))
+}
+
+/// `value` split into lines of at most `max` cells
+pub fn wrap(value: &str, max: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut rest = value;
+    while !rest.is_empty() {
+        let mut line = fit(rest, max);
+        // A single character wider than `max` still goes on its own line
+        if line.is_empty() {
+            line = &rest[..rest.chars().next().map_or(rest.len(), char::len_utf8)];
+        }
+        lines.push(line.to_string());
+        rest = &rest[line.len()..];
+    }
+    lines
+}
@@ -0,0 +1,13 @@
+use ratatui::style::Color;
+
+use crate::config;
+
+/// Accent color for borders, titles, and key hints, chosen by the `theme` config key
+pub fn accent() -> Color {
+    match config::get().theme.as_deref() {
+        Some("blue") => Color::Blue,
+        Some("green") => Color::Green,
+        Some("magenta") => Color::Magenta,
+        _ => Color::Cyan,
+    }
+}
@@ -0,0 +1,127 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+
+use crate::commands::units::format_size;
+use crate::tui::app::App;
+use crate::tui::catalog::CatalogState;
+use crate::tui::views::popup;
+use crate::tui::widgets::status_bar;
+use crate::tui::theme;
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.area());
+
+    let Some(state) = &app.catalog else { return };
+
+    let search_style = if state.searching { Style::default().fg(Color::Yellow) } else { Style::default().fg(Color::Gray) };
+    let cursor = if state.searching { "\u{2588}" } else { "" };
+    let search = Paragraph::new(Span::styled(format!("/{}{}", state.search, cursor), search_style)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .title(" Search paths and column names "),
+    );
+    frame.render_widget(search, chunks[0]);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(62), Constraint::Percentage(38)])
+        .split(chunks[1]);
+    render_files(frame, state, middle[0]);
+    render_columns(frame, state, middle[1]);
+
+    let bindings: &[(&str, &str)] = if state.searching {
+        &[("Enter", "done"), ("Esc", "clear search")]
+    } else {
+        &[
+            ("\u{2191}\u{2193}", "select"),
+            ("Enter", "open"),
+            ("/", "search"),
+            ("r", "refresh"),
+            ("Esc", "back"),
+            ("q", "quit"),
+        ]
+    };
+    status_bar::render(frame, chunks[2], bindings);
+
+    popup::render(frame, app);
+}
+
+fn render_files(frame: &mut Frame, state: &CatalogState, area: Rect) {
+    let rows = state.rows();
+    let mut title = format!(" Data catalog ({} of {} files) ", rows.len(), state.catalog.entries.len());
+    if let Some(status) = &state.status {
+        title.push_str(&format!("- {} ", status));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(title)
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+    if rows.is_empty() {
+        let text = if state.catalog.entries.is_empty() {
+            "Nothing indexed yet: run `ftool index <dir>`"
+        } else {
+            "No files match the search"
+        };
+        frame.render_widget(Paragraph::new(text).style(Style::default().fg(Color::Gray)).block(block), area);
+        return;
+    }
+
+    let table_rows: Vec<Row> = rows
+        .iter()
+        .map(|entry| {
+            let style = if entry.error.is_some() { Style::default().fg(Color::Red) } else { Style::default() };
+            Row::new(vec![
+                entry.path.display().to_string(),
+                entry.rows.map_or("-".to_string(), |n| n.to_string()),
+                format_size(entry.size),
+                entry.modified_label(),
+            ])
+            .style(style)
+        })
+        .collect();
+    let header = Row::new(vec!["Path", "Rows", "Size", "Modified"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let table = Table::new(
+        table_rows,
+        [Constraint::Min(20), Constraint::Length(10), Constraint::Length(10), Constraint::Length(16)],
+    )
+    .header(header)
+    .block(block)
+    .row_highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+    let mut table_state = TableState::default().with_selected(Some(state.selected));
+    frame.render_stateful_widget(table, area, &mut table_state);
+}
+
+fn render_columns(frame: &mut Frame, state: &CatalogState, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)).title(" Columns ");
+    let Some(entry) = state.selected_entry() else {
+        frame.render_widget(block, area);
+        return;
+    };
+    let term = state.search.to_lowercase();
+    let lines: Vec<Line> = match &entry.error {
+        Some(e) => vec![Line::styled(e.clone(), Style::default().fg(Color::Red))],
+        None => entry
+            .columns
+            .iter()
+            .map(|c| {
+                let style = if !term.is_empty() && c.name.to_lowercase().contains(&term) {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Line::from(vec![Span::styled(c.name.clone(), style), Span::styled(format!("  {}", c.data_type), Style::default().fg(Color::Gray))])
+            })
+            .collect(),
+    };
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
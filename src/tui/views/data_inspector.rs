@@ -1,43 +1,90 @@
 use ratatui::Frame;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
 use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Tabs};
+use ratatui::widgets::canvas::{Canvas, Map, MapResolution, Points};
+use ratatui::widgets::{
+    Axis, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Row, Table,
+    Tabs,
+};
 
-use crate::tui::app::{App, FilterEditorState, FilterField, InspectorTab, PAGE_SIZE, COLUMN_PAGE_SIZE, Popup, FILTER_OPERATORS};
-use crate::tui::views::centered_rect;
-use crate::tui::widgets::status_bar;
+use crate::commands::units::format_size;
+use crate::tui::app::{
+    App, FilterEditorState, FilterField, InspectorTab, RowGroupsState, TransformEditorState, TransformField,
+    FILTER_OPERATORS,
+};
+use crate::tui::text;
+use crate::tui::session::{DataInspectorState, GeoMap, ScatterPlot, StatsCell};
+use crate::tui::views::{centered_rect, popup};
+use crate::tui::widgets::frozen_table::FrozenTable;
+use crate::tui::widgets::{completion_list, session_bar, status_bar};
+use crate::tui::theme;
 
 pub fn render(frame: &mut Frame, app: &App) {
+    let Some(state) = app.active_data() else {
+        return;
+    };
     let area = frame.area();
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)])
+        .constraints([
+            Constraint::Length(if app.sessions.len() > 1 { 1 } else { 0 }),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    if app.sessions.len() > 1 {
+        session_bar::render(frame, chunks[0], app);
+    }
+
+    render_pane(frame, state, chunks[1], true);
+    status_bar::render(frame, chunks[2], &hints(app, state));
+
+    // Render popup on top if active
+    popup::render(frame, app);
+}
+
+/// Renders the inspector block, tabs, content and info bar for one data session.
+/// Unfocused panes (in split view) get a dimmed border.
+pub fn render_pane(frame: &mut Frame, state: &DataInspectorState, area: Rect, focused: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
         .split(area);
 
     let main_area = chunks[0];
     let info_area = chunks[1];
-    let status_area = chunks[2];
+
+    let border_color = if focused { theme::accent() } else { Color::DarkGray };
 
     // Title with file name and row count
-    let title = if let Some(ref file) = app.inspector_file {
-        let name = file
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())
-            .unwrap_or_default();
-        format!(" Inspector: {} ({} rows) ", name, app.inspector_row_count)
+    let name = state
+        .file
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut title = if state.counting_rows {
+        format!(" Inspector: {} ({} rows, counting\u{2026}) ", name, state.row_count_label())
     } else {
-        " Inspector ".to_string()
+        format!(" Inspector: {} ({} rows) ", name, state.row_count_label())
     };
+    if state.inspector.row_limit().is_some() {
+        title.push_str("\u{25cf} sample ");
+    }
+    if state.follow.is_some() {
+        title.push_str("\u{25cf} following ");
+    }
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(border_color))
         .title(title)
         .title_style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(border_color)
                 .add_modifier(Modifier::BOLD),
         );
 
@@ -51,11 +98,12 @@ pub fn render(frame: &mut Frame, app: &App) {
         .split(inner);
 
     // Tab bar
-    let tab_index = match app.inspector_tab {
+    let tab_index = match state.tab {
         InspectorTab::Schema => 0,
         InspectorTab::Preview => 1,
+        InspectorTab::Chart => 2,
     };
-    let tabs = Tabs::new(vec!["Schema", "Preview"])
+    let tabs = Tabs::new(vec!["Schema", "Preview", "Chart"])
         .select(tab_index)
         .style(Style::default().fg(Color::Gray))
         .highlight_style(
@@ -67,280 +115,491 @@ pub fn render(frame: &mut Frame, app: &App) {
     frame.render_widget(tabs, inner_chunks[0]);
 
     // Content area
-    match app.inspector_tab {
-        InspectorTab::Schema => render_schema(frame, app, inner_chunks[1]),
-        InspectorTab::Preview => render_preview(frame, app, inner_chunks[1]),
+    match state.tab {
+        InspectorTab::Schema => render_schema(frame, state, inner_chunks[1]),
+        InspectorTab::Preview => render_preview(frame, state, inner_chunks[1]),
+        InspectorTab::Chart => render_chart(frame, state, inner_chunks[1]),
     }
 
-    // Info bar (only in Preview tab)
-    if app.inspector_tab == InspectorTab::Preview && app.inspector_row_count > 0 {
-        let from = app.inspector_page * PAGE_SIZE + 1;
-        let to = ((app.inspector_page + 1) * PAGE_SIZE).min(app.inspector_row_count);
-        let total_pages = (app.inspector_row_count + PAGE_SIZE - 1) / PAGE_SIZE;
-        let total_cols = app.inspector_schema.len();
-        let total_col_pages = (total_cols + COLUMN_PAGE_SIZE - 1) / COLUMN_PAGE_SIZE;
+    // Info bar (rows on screen in Preview tab, last query time in both)
+    if state.tab == InspectorTab::Preview && state.row_count > 0 {
+        let from = state.top_row + 1;
+        let to = state.top_row + state.visible_rows().len();
+        let total_col_pages = state.total_col_pages();
 
         let info_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(33), Constraint::Percentage(34), Constraint::Percentage(33)])
             .split(info_area);
 
-        let left = Paragraph::new(format!(" rows {} to {} of {} ", from, to, app.inspector_row_count))
+        let mut rows = format!(" rows {} to {} of {} ", from, to, state.row_count_label());
+        if let Some(timing) = query_timing(state) {
+            rows.push_str(&format!("| {} ", timing));
+        }
+        let left = Paragraph::new(rows)
             .style(Style::default().fg(Color::DarkGray));
-        let right = Paragraph::new(format!(
-            " page {} of {} | cols {} of {} ",
-            app.inspector_page + 1, total_pages,
-            app.inspector_col_page + 1, total_col_pages,
-        ))
+        let mut cols = format!(" cols {} of {} ", state.col_page + 1, total_col_pages);
+        // The zone timestamps are shown in, when the file has any
+        if state.schema.iter().any(|(_, ty)| ty.starts_with("TIMESTAMP")) {
+            cols.insert_str(0, &format!(" {} |", state.inspector.display_timezone()));
+        }
+        let unnested = state.inspector.unnested();
+        if !unnested.is_empty() {
+            cols.insert_str(0, &format!(" unnested {} |", unnested.join(", ")));
+        }
+        let right = Paragraph::new(cols)
             .style(Style::default().fg(Color::DarkGray))
             .alignment(Alignment::Right);
 
         frame.render_widget(left, info_chunks[0]);
         frame.render_widget(right, info_chunks[2]);
 
-        if !app.inspector_filters.is_empty() {
-            let n = app.inspector_filters.len();
+        if !state.filters.is_empty() {
+            let n = state.filters.len();
             let label = if n == 1 { "1 filter".to_string() } else { format!("{} filters", n) };
             let center = Paragraph::new(format!(" {} active ", label))
                 .style(Style::default().fg(Color::Yellow))
                 .alignment(Alignment::Center);
             frame.render_widget(center, info_chunks[1]);
         }
+    } else if state.tab != InspectorTab::Preview
+        && let Some(timing) = query_timing(state)
+    {
+        let info = Paragraph::new(format!(" {} ", timing)).style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(info, info_area);
     }
+}
+
+/// "preview 12 ms" for the last query the session ran
+fn query_timing(state: &DataInspectorState) -> Option<String> {
+    let (kind, elapsed) = state.last_query?;
+    Some(format!("{} {} ms", kind, elapsed.as_millis()))
+}
 
-    // Status bar
+/// Status bar key hints for the data inspector
+pub fn hints(app: &App, state: &DataInspectorState) -> Vec<(&'static str, &'static str)> {
     let mut hints: Vec<(&str, &str)> = vec![
         ("Tab", "Switch"),
     ];
-    if app.inspector_tab == InspectorTab::Preview {
-        hints.push(("\u{2191}\u{2193}", "Row page"));
+    if state.tab == InspectorTab::Preview {
+        hints.push(("\u{2191}\u{2193}", "Scroll"));
         hints.push(("\u{2190}\u{2192}", "Col cursor"));
         hints.push(("h/l", "Col page"));
+        hints.push(("K", "Pin col"));
+        hints.push(("e", "Expand col"));
+        hints.push(("</>", "Col width"));
+        hints.push(("v", "Row detail"));
         hints.push(("f", "Filter"));
+        hints.push(("S/F", "Presets"));
+        hints.push(("H", "History"));
+        hints.push(("a/A", "Computed col"));
+        hints.push(("T", "Column type"));
+        if state.file.extension().is_some_and(|e| e == "csv") {
+            hints.push(("O", "CSV options"));
+        }
+        hints.push(("y", "Copy rows"));
+        hints.push(("P", "Plan"));
+        if state.file.extension().is_some_and(|e| e == "parquet") {
+            hints.push(("G", "Row groups"));
+        }
+        hints.push(("W", if state.follow.is_some() { "Stop following" } else { "Follow" }));
+        if state.row_count_estimated && !state.counting_rows {
+            hints.push(("#", "Exact count"));
+        }
+    } else if state.tab == InspectorTab::Chart {
+        hints.push(("\u{2191}\u{2193}", "Column"));
+        hints.push(("X", "Set x"));
+        hints.push(("Space", "Toggle y"));
+        hints.push(("Enter", "Plot"));
+        hints.push(("s", "Scatter"));
     } else {
         hints.push(("scroll", "Scroll"));
+        hints.push(("Enter", "All stats"));
+        hints.push(("v", "Column detail"));
+        hints.push(("T", "Column type"));
+    }
+    if !state.geo.is_empty() && state.tab != InspectorTab::Chart {
+        hints.push(("M", "Map"));
+        hints.push(("J", "GeoJSON"));
+    }
+    if state.tab != InspectorTab::Chart {
+        if state.schema.iter().any(|(_, ty)| ty.ends_with(']') || ty.starts_with("STRUCT(")) {
+            hints.push(("u", "Unnest col"));
+        }
+        if !state.inspector.unnested().is_empty() {
+            hints.push(("U", "Nest again"));
+        }
+    }
+    if app.sessions.len() > 1 {
+        hints.push(("[/]", "Tabs"));
+        hints.push(("|", "Split"));
+    }
+    if state.file.extension().is_some_and(|e| e == "csv") {
+        hints.push(("L", "Lint"));
     }
     hints.extend_from_slice(&[
         ("c", "Convert"),
+        ("R", "Report"),
         ("Esc", "Back"),
         ("q", "Quit")
     ]);
-
-    status_bar::render(frame, status_area, &hints);
-
-    // Render popup on top if active
-    render_popup(frame, app, frame.area());
+    hints
 }
 
-fn render_schema(frame: &mut Frame, app: &App, area: Rect) {
-    let header = Row::new(vec!["Column Name", "Type", "Nulls", "Min", "Max", "Avg"])
+fn render_schema(frame: &mut Frame, state: &DataInspectorState, area: Rect) {
+    // The file's note goes above the table
+    let area = match &state.notes.file {
+        Some(note) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(2), Constraint::Min(0)])
+                .split(area);
+            let line = Line::from(vec![
+                Span::styled("Note: ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(note.replace('\n', " "), Style::default().fg(Color::Gray)),
+            ]);
+            frame.render_widget(Paragraph::new(line), chunks[0]);
+            chunks[1]
+        }
+        None => area,
+    };
+    let with_notes = !state.notes.columns.is_empty();
+    let mut headers = vec!["Column Name", "Type", "Nulls", "Min", "Max", "Avg", "Distribution"];
+    if with_notes {
+        headers.push("Note");
+    }
+    let header = Row::new(headers)
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme::accent())
                 .add_modifier(Modifier::BOLD),
         )
         .bottom_margin(1);
 
-    let rows: Vec<Row> = app
-        .inspector_schema
+    let rows: Vec<Row> = state
+        .schema
         .iter()
         .enumerate()
-        .skip(app.inspector_scroll)
+        .skip(state.scroll)
         .map(|(i, (name, dtype))| {
-            let null_count = app
-                .inspector_null_counts
-                .get(i)
-                .map(|c| c.to_string())
-                .unwrap_or_else(|| "-".to_string());
-            let min = app
-                .inspector_min_values
-                .get(i)
-                .cloned()
-                .unwrap_or_else(|| "-".to_string());
-            let max = app
-                .inspector_max_values
-                .get(i)
-                .cloned()
-                .unwrap_or_else(|| "-".to_string());
-            let mean = app
-                .inspector_mean_values
-                .get(i)
-                .cloned()
-                .unwrap_or_else(|| "-".to_string());
-            Row::new(vec![
-                name.clone(),
-                dtype.clone(),
-                null_count,
-                min,
-                max,
-                mean,
-            ])
+            let (null_count, min, max, mean, distribution) = match state.stats.get(i) {
+                Some(StatsCell::Loaded(s)) => (
+                    s.null_count.to_string(),
+                    s.min.clone(),
+                    s.max.clone(),
+                    s.mean.clone(),
+                    sparkline(&s.histogram),
+                ),
+                Some(StatsCell::Failed) | None => {
+                    let dash = "-".to_string();
+                    (dash.clone(), dash.clone(), dash.clone(), dash, String::new())
+                }
+                Some(StatsCell::NotLoaded | StatsCell::Loading) => {
+                    let pending = "\u{2026}".to_string();
+                    (pending.clone(), pending.clone(), pending.clone(), pending, String::new())
+                }
+            };
+            // Geo columns get a badge, with the encoding they were recognised by
+            let dtype = match state.geo_column(name) {
+                Some(geo) => Cell::from(Line::from(vec![
+                    Span::raw(format!("{} ", dtype)),
+                    Span::styled(
+                        "Geo",
+                        Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(format!(" {}", geo.encoding.label()), Style::default().fg(Color::Green)),
+                ])),
+                None => Cell::from(dtype.clone()),
+            };
+            let mut cells = vec![
+                Cell::from(name.clone()),
+                dtype,
+                Cell::from(null_count),
+                Cell::from(min),
+                Cell::from(max),
+                Cell::from(mean),
+                Cell::from(distribution).style(Style::default().fg(Color::Cyan)),
+            ];
+            if with_notes {
+                let note = state.notes.column(name).unwrap_or_default().replace('\n', " ");
+                cells.push(Cell::from(note).style(Style::default().fg(Color::Yellow)));
+            }
+            Row::new(cells)
         })
         .collect();
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Min(15),
-            Constraint::Length(12),
-            Constraint::Length(7),
-            Constraint::Length(12),
-            Constraint::Length(12),
-            Constraint::Length(10),
-        ],
-    )
-    .header(header);
+    let mut widths = vec![
+        Constraint::Min(15),
+        Constraint::Length(if state.geo.is_empty() { 12 } else { 22 }),
+        Constraint::Length(7),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(12),
+    ];
+    if with_notes {
+        widths.push(Constraint::Min(20));
+    }
+    let table = Table::new(rows, widths).header(header);
 
     frame.render_widget(table, area);
 }
 
-fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
-    if app.inspector_preview_headers.is_empty() {
+fn render_preview(frame: &mut Frame, state: &DataInspectorState, area: Rect) {
+    if state.preview_headers.is_empty() {
         let msg =
             Paragraph::new("No preview data available").style(Style::default().fg(Color::Gray));
         frame.render_widget(msg, area);
         return;
     }
 
-    let selected = app.inspector_selected_col;
+    state.preview_area.set(area);
+    // The header and the blank line under it take two lines
+    state.viewport_rows.set(area.height.saturating_sub(2) as usize);
+    frame.render_widget(preview_table(state), area);
+}
+
+/// The preview table as drawn, also used to find the cell under a mouse click.
+/// The header stays on top; the pinned key column stays on the left while the
+/// rest scroll to keep the selected column (highlighted) in view.
+pub fn preview_table(state: &DataInspectorState) -> FrozenTable<'_> {
+    FrozenTable::new(&state.preview_headers, &state.preview_data)
+        .skip(state.preview_skip())
+        .frozen(state.pinned_preview_col())
+        .selected(state.preview_offset + state.selected_col)
+        .expand_selected(state.expand_selected)
+        .col_widths(&state.col_widths)
+        .header_style(
+            Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )
+        .selected_style(Style::default().bg(Color::DarkGray).fg(Color::White))
+}
+
+/// Block characters from lowest to highest, one per histogram bucket
+fn sparkline(histogram: &[usize]) -> String {
+    const BARS: [char; 8] = ['\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}'];
+    let max = histogram.iter().copied().max().unwrap_or(0);
+    histogram
+        .iter()
+        .map(|&n| match n {
+            0 => ' ',
+            // Any non-empty bucket gets at least the lowest bar
+            _ => BARS[((n * BARS.len()).div_ceil(max) - 1).min(BARS.len() - 1)],
+        })
+        .collect()
+}
+
+/// Colours given to the plotted series, in order
+const SERIES_COLORS: [Color; 6] = [Color::Cyan, Color::Yellow, Color::Magenta, Color::Green, Color::Red, Color::Blue];
 
-    // Header row with selected column highlighted in yellow
-    let header_cells: Vec<Cell> = app.inspector_preview_headers.iter().enumerate()
-        .map(|(i, h)| {
-            let style = if i == selected {
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+fn render_chart(frame: &mut Frame, state: &DataInspectorState, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(28), Constraint::Min(0)])
+        .split(area);
+
+    // Column list: the x column and the y columns are marked
+    let items: Vec<ListItem> = state
+        .schema
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| {
+            let mark = if state.chart.x == Some(i) {
+                "X "
+            } else if state.chart.ys.contains(&i) {
+                "Y "
             } else {
-                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                "  "
             };
-            Cell::from(h.as_str()).style(style)
+            let mut style = Style::default();
+            if i == state.chart.cursor {
+                style = style.bg(Color::DarkGray).fg(Color::White);
+            }
+            ListItem::new(Line::from(vec![
+                Span::styled(mark, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::raw(name.as_str()),
+            ]))
+            .style(style)
         })
         .collect();
-    let header = Row::new(header_cells).bottom_margin(1);
+    // Keep the cursor in view in a long schema
+    let visible = chunks[0].height.saturating_sub(2) as usize;
+    let skip = state.chart.cursor.saturating_sub(visible.saturating_sub(1));
+    let list = List::new(items.into_iter().skip(skip).collect::<Vec<_>>()).block(
+        Block::default()
+            .borders(Borders::RIGHT)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(list, chunks[0]);
 
-    // Data rows with selected column highlighted
-    let rows: Vec<Row> = app
-        .inspector_preview_data
+    let points: Vec<&(f64, f64)> = state.chart.series.iter().flat_map(|(_, p)| p).collect();
+    if points.is_empty() {
+        let msg = if state.chart.series.is_empty() {
+            "Pick an x column (X) and y columns (Space), then press Enter"
+        } else {
+            "No data to plot"
+        };
+        let msg = Paragraph::new(msg).style(Style::default().fg(Color::Gray));
+        frame.render_widget(msg, chunks[1].inner(Margin::new(1, 0)));
+        return;
+    }
+
+    let (x_min, x_max) = bounds(points.iter().map(|p| p.0));
+    let (y_min, y_max) = bounds(points.iter().map(|p| p.1));
+
+    let datasets: Vec<Dataset> = state
+        .chart
+        .series
         .iter()
-        .skip(app.inspector_scroll)
-        .map(|row_data| {
-            let cells: Vec<Cell> = row_data.iter().enumerate()
-                .map(|(i, val)| {
-                    let style = if i == selected {
-                        Style::default().bg(Color::DarkGray).fg(Color::White)
-                    } else {
-                        Style::default()
-                    };
-                    Cell::from(val.as_str()).style(style)
-                })
-                .collect();
-            Row::new(cells)
+        .enumerate()
+        .map(|(i, (name, data))| {
+            Dataset::default()
+                .name(name.as_str())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(SERIES_COLORS[i % SERIES_COLORS.len()]))
+                .data(data)
         })
         .collect();
 
-    // Column widths - distribute evenly
-    let col_count = app.inspector_preview_headers.len();
-    let widths: Vec<Constraint> = (0..col_count).map(|_| Constraint::Min(10)).collect();
-
-    let table = Table::new(rows, widths).header(header);
-    frame.render_widget(table, area);
+    let x_label = |v: f64| {
+        if state.chart.x_is_temporal {
+            chrono::DateTime::from_timestamp(v as i64, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default()
+        } else {
+            format_number(v)
+        }
+    };
+    let axis_style = Style::default().fg(Color::DarkGray);
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .title(state.chart.x_label.as_str())
+                .style(axis_style)
+                .bounds([x_min, x_max])
+                .labels([x_label(x_min), x_label((x_min + x_max) / 2.0), x_label(x_max)]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([y_min, y_max])
+                .labels([format_number(y_min), format_number((y_min + y_max) / 2.0), format_number(y_max)]),
+        );
+    frame.render_widget(chart, chunks[1]);
 }
 
-fn render_popup(frame: &mut Frame, app: &App, area: Rect) {
-    match &app.popup {
-        Popup::None => {}
-        Popup::ConvertConfirm { target_format } => {
-            let popup_area = centered_rect(44, 7, area);
-            frame.render_widget(Clear, popup_area);
-
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .title(" Convert ")
-                .title_style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                );
-
-            let inner = block.inner(popup_area);
-            frame.render_widget(block, popup_area);
-
-            let text = vec![
-                Line::from(""),
-                Line::from(format!("  Convert to {}?", target_format)),
-                Line::from(""),
-                Line::from(vec![
-                    Span::styled(
-                        " Enter ",
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw("confirm  "),
-                    Span::styled(
-                        " Esc ",
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw("cancel"),
-                ]),
-            ];
-            frame.render_widget(Paragraph::new(text), inner);
-        }
-        Popup::Message { title, body } => {
-            let width = (body.len() as u16 + 6)
-                .max(30)
-                .min(area.width.saturating_sub(4));
-            let popup_area = centered_rect(width, 7, area);
-            frame.render_widget(Clear, popup_area);
-
-            let color = if title.contains("Error") {
-                Color::Red
-            } else {
-                Color::Green
-            };
+pub fn render_scatter_popup(frame: &mut Frame, plot: &ScatterPlot, area: Rect) {
+    let popup_area = centered_rect(area.width.saturating_sub(8).min(100), area.height.saturating_sub(4).min(32), area);
+    frame.render_widget(Clear, popup_area);
 
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(color))
-                .title(format!(" {} ", title))
-                .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(format!(" {} vs {} ", plot.y, plot.x))
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
 
-            let inner = block.inner(popup_area);
-            frame.render_widget(block, popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
 
-            let text = vec![
-                Line::from(""),
-                Line::from(format!("  {}", body)),
-                Line::from(""),
-                Line::from(vec![
-                    Span::styled(
-                        " Enter/Esc ",
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw("close"),
-                ]),
-            ];
-            frame.render_widget(Paragraph::new(text), inner);
-        }
-        Popup::FilterEditor(state) => render_filter_popup(frame, app, state, area),
+    let all = plot.points.iter().chain(&plot.outliers);
+    let (x_min, x_max) = bounds(all.clone().map(|p| p.0));
+    let (y_min, y_max) = bounds(all.map(|p| p.1));
+
+    let outliers_label = format!("outliers ({})", plot.outliers.len());
+    let datasets = vec![
+        Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&plot.points),
+        Dataset::default()
+            .name(outliers_label)
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Red))
+            .data(&plot.outliers),
+    ];
+    let axis_style = Style::default().fg(Color::DarkGray);
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .title(plot.x.as_str())
+                .style(axis_style)
+                .bounds([x_min, x_max])
+                .labels([format_number(x_min), format_number((x_min + x_max) / 2.0), format_number(x_max)]),
+        )
+        .y_axis(
+            Axis::default()
+                .title(plot.y.as_str())
+                .style(axis_style)
+                .bounds([y_min, y_max])
+                .labels([format_number(y_min), format_number((y_min + y_max) / 2.0), format_number(y_max)]),
+        );
+    if plot.sampled() == 0 {
+        let msg = Paragraph::new("No rows where both columns are numbers").style(Style::default().fg(Color::Gray));
+        frame.render_widget(msg, chunks[0]);
+    } else {
+        frame.render_widget(chart, chunks[0]);
     }
+
+    let shown = if plot.sampled() < plot.total {
+        format!(" sample of {} from {} rows ", plot.sampled(), plot.total)
+    } else {
+        format!(" {} rows ", plot.total)
+    };
+    let footer = Line::from(vec![
+        Span::styled(shown, Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            format!(
+                "| x {} to {} | y {} to {} ",
+                format_number(x_min),
+                format_number(x_max),
+                format_number(y_min),
+                format_number(y_max)
+            ),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(" Esc ", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
+        Span::raw("close"),
+    ]);
+    frame.render_widget(Paragraph::new(footer), chunks[1]);
+}
+
+/// Lowest and highest value, widened when they are equal so the axis has a range
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if min == max { (min - 1.0, max + 1.0) } else { (min, max) }
 }
 
-fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState, area: Rect) {
+fn format_number(v: f64) -> String {
+    if v.abs() >= 1e6 || (v != 0.0 && v.abs() < 1e-2) {
+        format!("{:.2e}", v)
+    } else if v.fract() == 0.0 {
+        format!("{}", v as i64)
+    } else {
+        format!("{:.2}", v)
+    }
+}
+
+pub fn render_filter_popup(frame: &mut Frame, data: &DataInspectorState, state: &FilterEditorState, area: Rect) {
+    // A failed apply adds a line for its hint
+    let hint = state.failure.as_ref().and_then(|f| f.hint.as_deref());
     let width = 72_u16.min(area.width.saturating_sub(4));
-    let height = 16_u16.min(area.height.saturating_sub(2));
+    let height = (16 + u16::from(hint.is_some())).min(area.height.saturating_sub(2));
     let popup_area = centered_rect(width, height, area);
     frame.render_widget(Clear, popup_area);
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(theme::accent()))
         .title(" Filters ")
-        .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
 
     let inner = block.inner(popup_area);
     frame.render_widget(block, popup_area);
@@ -350,6 +609,7 @@ fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState,
         .constraints([
             Constraint::Min(3),      // conditions list
             Constraint::Length(1),   // separator
+            Constraint::Length(u16::from(hint.is_some())), // failure hint
             Constraint::Length(3),   // editor fields
             Constraint::Length(1),   // help text
         ])
@@ -362,13 +622,20 @@ fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState,
             Style::default().fg(Color::DarkGray),
         ))]
     } else {
+        let failed = state.failure.as_ref().and_then(|f| f.condition);
         state.conditions.iter().enumerate().map(|(i, c)| {
-            let text = if c.operator == "IS NULL" || c.operator == "IS NOT NULL" {
+            let mut text = if c.operator == "IS NULL" || c.operator == "IS NOT NULL" {
                 format!("  {}. \"{}\" {}", i + 1, c.column, c.operator)
             } else {
-                format!("  {}. \"{}\" {} '{}'", i + 1, c.column, c.operator, c.value)
+                format!("  {}. \"{}\" {} '{}'", i + 1, c.column, c.operator_label(), c.value)
+            };
+            let color = if failed == Some(i) {
+                text.push_str("  \u{2190} failed");
+                Color::Red
+            } else {
+                Color::White
             };
-            Line::from(Span::styled(text, Style::default().fg(Color::White)))
+            Line::from(Span::styled(text, Style::default().fg(color)))
         }).collect()
     };
     condition_lines.insert(0, Line::from(Span::styled(
@@ -377,39 +644,66 @@ fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState,
     )));
     frame.render_widget(Paragraph::new(condition_lines), chunks[0]);
 
-    // --- Separator ---
-    frame.render_widget(
-        Paragraph::new(Span::styled(
-            "─".repeat(inner.width as usize),
-            Style::default().fg(Color::DarkGray),
-        )),
-        chunks[1],
-    );
+    // --- Separator, or the reason the last condition or the apply was rejected ---
+    let error = state.error.as_deref().or(state.failure.as_ref().map(|f| f.message.as_str()));
+    let separator = match error {
+        Some(error) => Span::styled(format!(" {}", error), Style::default().fg(Color::Red)),
+        None => Span::styled("─".repeat(inner.width as usize), Style::default().fg(Color::DarkGray)),
+    };
+    frame.render_widget(Paragraph::new(separator), chunks[1]);
+    if let Some(hint) = hint {
+        frame.render_widget(
+            Paragraph::new(Span::styled(format!(" {}", hint), Style::default().fg(Color::Yellow))),
+            chunks[2],
+        );
+    }
 
     // --- Editor fields ---
     let active_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
     let inactive_style = Style::default().fg(Color::Gray);
 
-    let col_name = app.inspector_schema
-        .get(state.column_idx)
-        .map(|(name, _)| name.as_str())
-        .unwrap_or("-");
+    let col_name = match &state.column_search {
+        Some(query) => format!("/{}_", query),
+        None => data.schema
+            .get(state.column_idx)
+            .map(|(name, _)| name.clone())
+            .unwrap_or_else(|| "-".to_string()),
+    };
     let op_name = FILTER_OPERATORS.get(state.operator_idx).copied().unwrap_or("=");
+    let op_hint = if op_name == "LIKE" {
+        format!(
+            "  {}, {} (m/i to change)",
+            state.like_mode.label(),
+            if state.case_insensitive { "ignore case" } else { "match case" },
+        )
+    } else {
+        "  ↑↓ to change".to_string()
+    };
     let value_display = format!("{}_", state.value_input);
+    // The preview shows TIMESTAMP columns in the display zone, but they are stored in UTC
+    let value_hint = match data.schema.get(state.column_idx) {
+        Some((name, _)) if data.inspector.is_zoned(name) => {
+            format!("  in UTC, not {}", data.inspector.display_timezone())
+        }
+        _ => "  type to input".to_string(),
+    };
 
     let field_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1); 3])
-        .split(chunks[2]);
+        .split(chunks[3]);
 
     frame.render_widget(
         Paragraph::new(Line::from(vec![
             Span::raw("  Column:   "),
             Span::styled(
-                format!("[ {:<20} ]", col_name),
+                format!("[ {} ]", text::pad(&col_name, 20)),
                 if state.active_field == FilterField::Column { active_style } else { inactive_style },
             ),
-            Span::styled("  ↑↓ to change", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                if state.column_search.is_some() { "  ↑↓ Tab to pick" } else { "  ↑↓ to change, / to search" },
+                Style::default().fg(Color::DarkGray),
+            ),
         ])),
         field_chunks[0],
     );
@@ -421,7 +715,7 @@ fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState,
                 format!("[ {:<20} ]", op_name),
                 if state.active_field == FilterField::Operator { active_style } else { inactive_style },
             ),
-            Span::styled("  ↑↓ to change", Style::default().fg(Color::DarkGray)),
+            Span::styled(op_hint, Style::default().fg(Color::DarkGray)),
         ])),
         field_chunks[1],
     );
@@ -430,10 +724,10 @@ fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState,
         Paragraph::new(Line::from(vec![
             Span::raw("  Value:    "),
             Span::styled(
-                format!("[ {:<20} ]", value_display),
+                format!("[ {} ]", text::pad(&value_display, 20)),
                 if state.active_field == FilterField::Value { active_style } else { inactive_style },
             ),
-            Span::styled("  type to input", Style::default().fg(Color::DarkGray)),
+            Span::styled(value_hint, Style::default().fg(Color::DarkGray)),
         ])),
         field_chunks[2],
     );
@@ -441,17 +735,293 @@ fn render_filter_popup(frame: &mut Frame, app: &App, state: &FilterEditorState,
     // --- Help text ---
     frame.render_widget(
         Paragraph::new(Line::from(vec![
-            Span::styled(" Tab", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(" Tab", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
             Span::raw(":next  "),
-            Span::styled("Enter", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled("Enter", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
             Span::raw(":add  "),
-            Span::styled("r", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled("r", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
             Span::raw(":apply  "),
-            Span::styled("d", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled("d", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
             Span::raw(":remove last  "),
-            Span::styled("Esc", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled("Esc", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
             Span::raw(":cancel"),
         ])),
-        chunks[3],
+        chunks[4],
+    );
+
+    // Column search results open under the Column field
+    completion_list::render(frame, &state.completion, field_chunks[0].x + 13, field_chunks[0].y + 1, area);
+}
+
+pub fn render_transform_popup(frame: &mut Frame, state: &TransformEditorState, area: Rect) {
+    let width = 80_u16.min(area.width.saturating_sub(4));
+    let height = 20_u16.min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(width, height, area);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(format!(" Transform → {} ", state.target_format))
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),      // columns table
+            Constraint::Length(1),   // edit line
+            Constraint::Length(1),   // help text
+        ])
+        .split(inner);
+
+    // --- Columns table ---
+    let header = Row::new(vec!["Keep", "Column", "Type", "Rename to", "Cast to", "Mask", "Partition"])
+        .style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+    // Keep the selected column in view
+    let visible = chunks[0].height.saturating_sub(1) as usize;
+    let offset = (state.selected + 1).saturating_sub(visible);
+
+    let rows: Vec<Row> = state
+        .columns
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(i, c)| {
+            let style = if i == state.selected {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else if !c.keep {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Row::new(vec![
+                Cell::from(if c.keep { "[x]" } else { "[ ]" }),
+                Cell::from(c.name.clone()),
+                Cell::from(c.data_type.clone()),
+                Cell::from(c.rename.clone()),
+                Cell::from(c.cast.clone()),
+                Cell::from(c.mask.map(|m| m.label()).unwrap_or_default()),
+                Cell::from(if c.partition { "\u{2713}" } else { "" }),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Percentage(22),
+            Constraint::Percentage(14),
+            Constraint::Percentage(18),
+            Constraint::Percentage(14),
+            Constraint::Length(14),
+            Constraint::Length(9),
+        ],
+    )
+    .header(header);
+    frame.render_widget(table, chunks[0]);
+
+    // --- Edit line ---
+    if let Some(field) = state.editing {
+        let label = match field {
+            TransformField::Rename => "  Rename to: ",
+            TransformField::Cast => "  Cast to:   ",
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(label),
+                Span::styled(
+                    format!("{}_", state.input),
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ),
+            ])),
+            chunks[1],
+        );
+    }
+
+    // --- Help text ---
+    let key = |k: &'static str| {
+        Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+    };
+    let help = if state.editing.is_some() {
+        vec![key(" Enter"), Span::raw(":set  "), key("Esc"), Span::raw(":cancel")]
+    } else {
+        vec![
+            key(" Space"),
+            Span::raw(":keep/drop  "),
+            key("r"),
+            Span::raw(":rename  "),
+            key("t"),
+            Span::raw(":cast  "),
+            key("m"),
+            Span::raw(":mask  "),
+            key("p"),
+            Span::raw(":partition  "),
+            key("Enter"),
+            Span::raw(":convert  "),
+            key("Esc"),
+            Span::raw(":cancel"),
+        ]
+    };
+    frame.render_widget(Paragraph::new(Line::from(help)), chunks[2]);
+}
+
+/// Row groups with their position and sizes; the selected one stays in view
+pub fn render_row_groups_popup(frame: &mut Frame, state: &RowGroupsState, area: Rect) {
+    let height = (state.groups.len() as u16 + 6).clamp(8, area.height.saturating_sub(2));
+    let popup_area = centered_rect(76.min(area.width.saturating_sub(4)), height, area);
+    frame.render_widget(Clear, popup_area);
+
+    let total: usize = state.groups.iter().map(|g| g.rows).sum();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(format!(" Row groups ({}, {} rows) ", state.groups.len(), total))
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    // Only name the file when the dataset spans several
+    let several_files = state.groups.iter().any(|g| g.file != state.groups[0].file);
+    let visible = chunks[0].height.saturating_sub(2) as usize;
+    let first = (state.selected + 1).saturating_sub(visible);
+    let rows: Vec<Row> = state
+        .groups
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(i, g)| {
+            let id = if several_files {
+                let file = std::path::Path::new(&g.file)
+                    .file_name()
+                    .map(|f| f.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                format!("{} #{}", file, g.id)
+            } else {
+                format!("#{}", g.id)
+            };
+            let ratio = if g.compressed_bytes > 0 {
+                format!("{:.1}x", g.uncompressed_bytes as f64 / g.compressed_bytes as f64)
+            } else {
+                "-".to_string()
+            };
+            let style = if i == state.selected {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                id,
+                (g.first_row + 1).to_string(),
+                g.rows.to_string(),
+                format_size(g.compressed_bytes),
+                format_size(g.uncompressed_bytes),
+                ratio,
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec!["Group", "First row", "Rows", "Compressed", "Uncompressed", "Ratio"])
+        .style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+    let widths = [
+        Constraint::Min(10),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(11),
+        Constraint::Length(13),
+        Constraint::Length(6),
+    ];
+    frame.render_widget(Table::new(rows, widths).header(header), chunks[0]);
+
+    let key = |k: &'static str| Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            key(" \u{2191}\u{2193} "),
+            Span::raw("select  "),
+            key(" Enter "),
+            Span::raw("jump to first row  "),
+            key(" Esc "),
+            Span::raw("close"),
+        ])),
+        chunks[1],
+    );
+}
+
+/// Sampled points over a world map; the map is left out when the points
+/// aren't longitude/latitude (e.g. projected coordinates)
+pub fn render_map_popup(frame: &mut Frame, map: &GeoMap, area: Rect) {
+    let popup_area = area.inner(Margin::new(4, 2));
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(format!(" Map: {} ", map.label))
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    if map.points.is_empty() {
+        frame.render_widget(Paragraph::new(" No rows with a point").style(Style::default().fg(Color::Gray)), inner);
+        return;
+    }
+    render_map(frame, map, inner);
+}
+
+/// The points over a world map (for lon/lat data), with their extent below
+pub fn render_map(frame: &mut Frame, map: &GeoMap, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let (x_min, x_max) = bounds(map.points.iter().map(|p| p.0));
+    let (y_min, y_max) = bounds(map.points.iter().map(|p| p.1));
+    let geographic = x_min >= -180.0 && x_max <= 180.0 && y_min >= -90.0 && y_max <= 90.0;
+    // Some margin around the points, clamped to the globe for lon/lat
+    let pad = |lo: f64, hi: f64| ((hi - lo) * 0.1).max(if geographic { 1.0 } else { f64::EPSILON });
+    let (x_pad, y_pad) = (pad(x_min, x_max), pad(y_min, y_max));
+    let (mut x_bounds, mut y_bounds) = ([x_min - x_pad, x_max + x_pad], [y_min - y_pad, y_max + y_pad]);
+    if geographic {
+        x_bounds = [x_bounds[0].max(-180.0), x_bounds[1].min(180.0)];
+        y_bounds = [y_bounds[0].max(-90.0), y_bounds[1].min(90.0)];
+    }
+
+    let canvas = Canvas::default()
+        .marker(Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            if geographic {
+                ctx.draw(&Map { color: Color::DarkGray, resolution: MapResolution::High });
+                ctx.layer();
+            }
+            ctx.draw(&Points { coords: &map.points, color: Color::Yellow });
+        });
+    frame.render_widget(canvas, chunks[0]);
+
+    let footer = format!(
+        " {} of {} points  x {} to {}  y {} to {}{} ",
+        map.points.len(),
+        map.total,
+        format_number(x_min),
+        format_number(x_max),
+        format_number(y_min),
+        format_number(y_max),
+        if geographic { "" } else { "  (not lon/lat: no map)" },
     );
+    frame.render_widget(Paragraph::new(footer).style(Style::default().fg(Color::DarkGray)), chunks[1]);
 }
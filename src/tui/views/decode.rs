@@ -0,0 +1,130 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+use crate::commands::decode::{Decoded, Encoding};
+use crate::tui::app::App;
+use crate::tui::decode::DecodeState;
+use crate::tui::text;
+use crate::tui::views::popup;
+use crate::tui::widgets::status_bar;
+use crate::tui::theme;
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let state = &app.decode;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(8), Constraint::Length(1)])
+        .split(frame.area());
+
+    render_input(frame, state, chunks[0]);
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(42), Constraint::Min(0)])
+        .split(chunks[1]);
+    render_info(frame, state, middle[0]);
+    render_output(frame, state, middle[1]);
+
+    let mut bindings = vec![("Ctrl+V", "paste"), ("Tab", "encoding"), ("Ctrl+Y", "copy output")];
+    if state.result().and_then(Decoded::json).is_some() {
+        bindings.push(("Ctrl+O", "open as JSON"));
+    }
+    bindings.extend([("Ctrl+U", "clear"), ("Esc", "back")]);
+    status_bar::render(frame, chunks[2], &bindings);
+    popup::render(frame, app);
+}
+
+fn block(title: String, color: Color) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(title)
+        .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+}
+
+fn render_input(frame: &mut Frame, state: &DecodeState, area: Rect) {
+    let block = block(" Decode: paste or type base64, percent-encoded, hex or a JWT ".to_string(), theme::accent());
+    let inner_width = area.width.saturating_sub(2).max(1) as usize;
+    let input = format!("{}\u{2588}", state.input);
+    // Keep the end of a long input (where the cursor is) in view
+    let lines = input.lines().map(|l| text::width(l) / inner_width + 1).sum::<usize>();
+    let scroll = lines.saturating_sub(area.height.saturating_sub(2) as usize) as u16;
+    let paragraph = Paragraph::new(input).block(block).wrap(Wrap { trim: false }).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_info(frame: &mut Frame, state: &DecodeState, area: Rect) {
+    let label = |text: &str| Span::styled(format!("{:<11}", text), Style::default().fg(Color::Gray));
+    let lines: Vec<Line> = match &state.decoded {
+        None => vec![Line::styled("Nothing to decode yet", Style::default().fg(Color::Gray))],
+        Some(Err(e)) => vec![
+            Line::styled("Could not decode the input", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Line::from(""),
+            Line::styled(e.clone(), Style::default().fg(Color::Red)),
+        ],
+        Some(Ok((decoded, encoding))) => {
+            let how = if state.encoding.is_some() { "" } else { " (detected)" };
+            let mut lines = vec![Line::from(vec![
+                label("Read as"),
+                Span::styled(encoding.label(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::raw(how),
+            ])];
+            match decoded {
+                Decoded::Text(text) => {
+                    lines.push(Line::from(vec![label("Result"), Span::raw(format!("{} characters", text.chars().count()))]));
+                    if decoded.json().is_some() {
+                        lines.push(Line::from(vec![label(""), Span::raw("valid JSON")]));
+                    }
+                }
+                Decoded::Bytes(bytes) => {
+                    lines.push(Line::from(vec![label("Result"), Span::raw(format!("{} bytes, not UTF-8", bytes.len()))]));
+                }
+                Decoded::Jwt(jwt) => {
+                    if let Some(alg) = jwt.header.get("alg").and_then(|a| a.as_str()) {
+                        lines.push(Line::from(vec![label("Algorithm"), Span::raw(alg.to_string())]));
+                    }
+                    let expired = jwt.is_expired();
+                    for line in jwt.describe_times() {
+                        let style = if expired && line.starts_with("Expires") {
+                            Style::default().fg(Color::Red)
+                        } else {
+                            Style::default()
+                        };
+                        lines.push(Line::styled(line, style));
+                    }
+                    lines.push(Line::from(vec![
+                        label("Signature"),
+                        Span::raw(format!("{} chars, not verified", jwt.signature.len())),
+                    ]));
+                }
+            }
+            lines
+        }
+    };
+    let paragraph = Paragraph::new(lines).block(block(" Decoded ".to_string(), Color::DarkGray)).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_output(frame: &mut Frame, state: &DecodeState, area: Rect) {
+    let mut title = vec![Span::raw(" Output:")];
+    let modes = std::iter::once(None).chain(Encoding::ALL.into_iter().map(Some));
+    for (i, mode) in modes.enumerate() {
+        let style = if mode == state.encoding {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        if i > 0 {
+            title.push(Span::raw("|"));
+        }
+        title.push(Span::styled(format!(" {} ", mode.map_or("Auto", Encoding::label)), style));
+    }
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(Line::from(title));
+    let text = state.output_text().unwrap_or_default();
+    frame.render_widget(Paragraph::new(text).block(outer).wrap(Wrap { trim: false }), area);
+}
@@ -0,0 +1,82 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table};
+
+use crate::commands::env::{EnvStatus, diff_summary};
+use crate::tui::app::App;
+use crate::tui::text;
+use crate::tui::views::popup;
+use crate::tui::widgets::status_bar;
+use crate::tui::theme;
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.area());
+
+    let Some(state) = &app.env else { return };
+    let rows = state.rows();
+
+    let search_style = if state.searching { Style::default().fg(Color::Yellow) } else { Style::default().fg(Color::Gray) };
+    let cursor = if state.searching { "\u{2588}" } else { "" };
+    let mut search = vec![Span::styled(format!("/{}{}", state.search, cursor), search_style)];
+    if let Some((_, lines)) = &state.diff {
+        search.push(Span::styled(format!("   {}", diff_summary(lines)), Style::default().fg(Color::Gray)));
+    }
+    let search_block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)).title(" Search ");
+    frame.render_widget(Paragraph::new(Line::from(search)).block(search_block), chunks[0]);
+
+    let title = format!(" {} ({} of {}) ", state.title(), rows.len(), state.diff.as_ref().map_or(state.vars.len(), |(_, l)| l.len()));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(title)
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let inner = block.inner(chunks[1]);
+    frame.render_widget(block, chunks[1]);
+
+    if rows.is_empty() {
+        let text = if state.search.is_empty() { "No variables" } else { "No variables match the search" };
+        frame.render_widget(Paragraph::new(text).style(Style::default().fg(Color::Gray)), inner);
+    } else {
+        let key_width = rows.iter().map(|r| text::width(&r.key)).max().unwrap_or(0).min(40) as u16;
+        let table_rows: Vec<Row> = rows
+            .iter()
+            .skip(state.scroll)
+            .map(|row| {
+                let color = match row.status {
+                    EnvStatus::Same if row.secret => Color::Magenta,
+                    EnvStatus::Same => Color::White,
+                    EnvStatus::Added => Color::Green,
+                    EnvStatus::Removed => Color::Red,
+                    EnvStatus::Changed => Color::Yellow,
+                };
+                Row::new(vec![row.status.marker().to_string(), row.key.clone(), row.value.clone()])
+                    .style(Style::default().fg(color))
+            })
+            .collect();
+        let table = Table::new(table_rows, [Constraint::Length(1), Constraint::Length(key_width), Constraint::Min(10)])
+            .column_spacing(1);
+        frame.render_widget(table, inner);
+    }
+
+    let reveal = if state.reveal { "mask secrets" } else { "show secrets" };
+    let bindings: &[(&str, &str)] = if state.searching {
+        &[("Enter", "done"), ("Esc", "clear search")]
+    } else {
+        &[
+            ("\u{2191}\u{2193}", "scroll"),
+            ("/", "search"),
+            ("m", reveal),
+            ("r", "reload"),
+            ("Esc", "back"),
+            ("q", "quit"),
+        ]
+    };
+    status_bar::render(frame, chunks[2], bindings);
+
+    popup::render(frame, app);
+}
@@ -0,0 +1,79 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table};
+
+use crate::tui::app::App;
+use crate::tui::views::popup;
+use crate::tui::widgets::status_bar;
+use crate::tui::theme;
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    let title = format!(" Error Log ({}) ", app.error_log.entries.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(title)
+        .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+
+    let inner = block.inner(chunks[0]);
+    frame.render_widget(block, chunks[0]);
+
+    if app.error_log.entries.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No errors this session").style(Style::default().fg(Color::Gray)),
+            inner,
+        );
+    } else {
+        let header = Row::new(vec!["Time", "Context", "Message"])
+            .style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            .bottom_margin(1);
+
+        // Newest first
+        let rows: Vec<Row> = app
+            .error_log
+            .entries
+            .iter()
+            .rev()
+            .skip(app.error_log_scroll)
+            .map(|e| {
+                Row::new(vec![
+                    e.time.format("%H:%M:%S").to_string(),
+                    e.context.clone(),
+                    e.message.clone(),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(10),
+                Constraint::Percentage(30),
+                Constraint::Min(20),
+            ],
+        )
+        .header(header);
+        frame.render_widget(table, inner);
+    }
+
+    status_bar::render(
+        frame,
+        chunks[1],
+        &[
+            ("\u{2191}\u{2193}", "scroll"),
+            ("c", "clear"),
+            ("Esc", "back"),
+            ("q", "quit"),
+        ],
+    );
+
+    popup::render(frame, app);
+}
@@ -4,8 +4,11 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
 
+use crate::commands::units::format_size;
 use crate::tui::app::App;
+use crate::tui::views::popup;
 use crate::tui::widgets::status_bar;
+use crate::tui::theme;
 
 pub fn render(frame: &mut Frame, app: &App) {
     let area = frame.area();
@@ -36,9 +39,9 @@ pub fn render(frame: &mut Frame, app: &App) {
     let title = format!(" File Browser: {} ", app.current_dir.display());
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(theme::accent()))
         .title(title)
-        .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
 
     let inner = block.inner(main_area);
     frame.render_widget(block, main_area);
@@ -58,7 +61,7 @@ pub fn render(frame: &mut Frame, app: &App) {
     }
 
     // Status bar
-    let hints: Vec<(&str, &str)> = if app.browser_search_active {
+    let mut hints: Vec<(&str, &str)> = if app.browser_search_active {
         vec![
             ("\u{2191}\u{2193}", "navigate"),
             ("Enter", "open"),
@@ -70,15 +73,25 @@ pub fn render(frame: &mut Frame, app: &App) {
             ("Enter", "open"),
             ("Esc", "back"),
             ("/", "search"),
+            ("!", "shell"),
+            ("Space", "mark"),
+            ("d", "delete"),
             ("q", "quit"),
         ]
     };
+    if !app.browser_search_active && !app.last_deleted.is_empty() {
+        hints.insert(hints.len() - 1, ("u", "restore"));
+    }
+    if app.browser_marked.len() == 2 {
+        hints.insert(hints.len() - 1, ("=", "compare schemas"));
+    }
     status_bar::render(frame, status_area, &hints);
+    popup::render(frame, app);
 }
 
 fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
     let line = Line::from(vec![
-        Span::styled("/ ", Style::default().fg(Color::Cyan)),
+        Span::styled("/ ", Style::default().fg(theme::accent())),
         Span::raw(&app.browser_search_query),
         Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
     ]);
@@ -90,7 +103,7 @@ fn render_file_list(frame: &mut Frame, app: &App, area: Rect) {
     let header = Row::new(vec!["Name", "Size", "Modified"])
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme::accent())
                 .add_modifier(Modifier::BOLD),
         )
         .bottom_margin(1);
@@ -107,8 +120,11 @@ fn render_file_list(frame: &mut Frame, app: &App, area: Rect) {
     let rows: Vec<Row> = entries
         .iter()
         .map(|entry| {
+            let marked = app.browser_marked.contains(&entry.path);
             let name = if entry.is_dir && entry.name != ".." {
                 format!("{}/", entry.name)
+            } else if marked {
+                format!("* {}", entry.name)
             } else {
                 entry.name.clone()
             };
@@ -121,7 +137,9 @@ fn render_file_list(frame: &mut Frame, app: &App, area: Rect) {
 
             let modified = format_modified(entry.modified);
 
-            let style = if entry.is_dir {
+            let style = if marked {
+                Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)
+            } else if entry.is_dir {
                 Style::default().fg(Color::Blue)
             } else {
                 match entry
@@ -129,7 +147,8 @@ fn render_file_list(frame: &mut Frame, app: &App, area: Rect) {
                     .extension()
                     .and_then(|e| e.to_str())
                 {
-                    Some("csv") | Some("parquet") | Some("json") | Some("geojson") => Style::default().fg(Color::Green),
+                    Some("csv") | Some("parquet") | Some("json") | Some("geojson") | Some("jsonl") | Some("ndjson") | Some("geojsonl")
+                    | Some("geojsons") | Some("gpx") | Some("kml") => Style::default().fg(Color::Green),
                     _ => Style::default(),
                 }
             };
@@ -186,17 +205,46 @@ fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
                 )),
             ]
         } else if entry.is_dir {
-            vec![
+            let mut lines = vec![
                 Line::from(vec![
                     Span::styled("Type: ", Style::default().fg(Color::Gray)),
                     Span::raw("Directory"),
                 ]),
-                Line::from(""),
-                Line::from(Span::styled(
-                    entry.path.display().to_string(),
-                    Style::default().fg(Color::DarkGray),
-                )),
-            ]
+            ];
+            let preview = app.dir_preview.as_ref().filter(|p| p.path == entry.path);
+            match preview.and_then(|p| p.stats.as_ref()) {
+                Some(Ok(stats)) => {
+                    lines.push(Line::from(vec![
+                        Span::styled("Size: ", Style::default().fg(Color::Gray)),
+                        Span::raw(format_size(stats.bytes)),
+                    ]));
+                    lines.push(Line::from(vec![
+                        Span::styled("Files: ", Style::default().fg(Color::Gray)),
+                        Span::raw(format!("{} in {} folders", stats.files, stats.dirs + 1)),
+                    ]));
+                    if let Some((path, size)) = &stats.largest {
+                        let name = path.strip_prefix(&entry.path).unwrap_or(path);
+                        lines.push(Line::from(vec![
+                            Span::styled("Largest: ", Style::default().fg(Color::Gray)),
+                            Span::raw(format!("{} ({})", name.display(), format_size(*size))),
+                        ]));
+                    }
+                    if stats.unreadable > 0 {
+                        lines.push(Line::from(Span::styled(
+                            format!("{} entries unreadable", stats.unreadable),
+                            Style::default().fg(Color::Yellow),
+                        )));
+                    }
+                }
+                Some(Err(e)) => lines.push(Line::from(Span::styled(e.clone(), Style::default().fg(Color::Red)))),
+                None => lines.push(Line::from(Span::styled("Scanning…", Style::default().fg(Color::DarkGray)))),
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                entry.path.display().to_string(),
+                Style::default().fg(Color::DarkGray),
+            )));
+            lines
         } else {
             let ext = entry
                 .path
@@ -219,7 +267,9 @@ fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
                 ]),
             ];
 
-            if ext == "csv" || ext == "parquet" || ext == "json" || ext == "geojson" {
+            if ext == "csv" || ext == "parquet" || ext == "json" || ext == "geojson" || ext == "jsonl" || ext == "ndjson"
+                || ext == "geojsonl" || ext == "geojsons" || ext == "gpx" || ext == "kml"
+            {
                 lines.push(Line::from(""));
                 lines.push(Line::from(Span::styled(
                     "Press Enter to inspect",
@@ -240,18 +290,6 @@ fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, inner);
 }
 
-fn format_size(bytes: u64) -> String {
-    if bytes < 1024 {
-        format!("{} B", bytes)
-    } else if bytes < 1024 * 1024 {
-        format!("{:.1} KB", bytes as f64 / 1024.0)
-    } else if bytes < 1024 * 1024 * 1024 {
-        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
-    } else {
-        format!("{:.1} GB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
-    }
-}
-
 fn format_modified(time: Option<std::time::SystemTime>) -> String {
     match time {
         Some(t) => {
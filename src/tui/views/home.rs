@@ -4,8 +4,11 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
 
+use crate::config;
 use crate::tui::app::App;
+use crate::tui::views::popup;
 use crate::tui::widgets::status_bar;
+use crate::tui::theme;
 
 const LOGO: &[&str] = &[
     "███████╗████████╗ ██████╗  ██████╗ ██╗     ",
@@ -18,6 +21,30 @@ const LOGO: &[&str] = &[
 
 const LOGO_WIDTH: u16 = 43;
 
+/// Home menu entries; the dataset aliases are listed after them
+pub const MENU: &[&str] = &[
+    "Browse Files",
+    "Inspect Data File",
+    "Geometry Scratchpad",
+    "Environment Variables",
+    "Paste JSON",
+    "HTTP Client",
+    "Decode Scratchpad",
+    "Data Catalog",
+];
+/// Index of the scratchpad entry; it and the ones after it don't open the file browser
+pub const SCRATCHPAD: usize = 2;
+/// Index of the environment variables entry
+pub const ENV: usize = 3;
+/// Index of the entry opening the clipboard's JSON in the JSON inspector
+pub const PASTE_JSON: usize = 4;
+/// Index of the HTTP client entry
+pub const HTTP_CLIENT: usize = 5;
+/// Index of the base64 / URL / hex / JWT decode scratchpad entry
+pub const DECODE: usize = 6;
+/// Index of the browser of files indexed with `ftool index`
+pub const CATALOG: usize = 7;
+
 pub fn render(frame: &mut Frame, app: &App) {
     let area = frame.area();
 
@@ -31,19 +58,24 @@ pub fn render(frame: &mut Frame, app: &App) {
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
-        .title(" ftool ")
+        .border_style(Style::default().fg(theme::accent()))
+        .title(if config::get().is_readonly() { " ftool (read-only) " } else { " ftool " })
         .title_style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme::accent())
                 .add_modifier(Modifier::BOLD),
         );
 
     let inner = block.inner(main_area);
     frame.render_widget(block, main_area);
 
-    // content: 6 (logo) + 1 spacer + 1 subtitle + 1 spacer + 2 menu = 11
-    let content_height = 11u16;
+    // Dataset aliases from the config, listed under the menu
+    let aliases = &config::get().aliases;
+    let datasets_height = if aliases.is_empty() { 0 } else { 2 + aliases.len() as u16 };
+
+    // content: 6 (logo) + 1 spacer + 1 subtitle + 1 spacer + menu, plus datasets
+    let menu_height = MENU.len() as u16;
+    let content_height = 9 + menu_height + datasets_height;
     let v_pad = inner.height.saturating_sub(content_height) / 2;
 
     let inner_chunks = Layout::default()
@@ -54,7 +86,8 @@ pub fn render(frame: &mut Frame, app: &App) {
             Constraint::Length(1), // spacer
             Constraint::Length(1), // subtitle
             Constraint::Length(1), // spacer
-            Constraint::Length(2), // menu
+            Constraint::Length(menu_height),
+            Constraint::Length(datasets_height),
             Constraint::Min(0),
         ])
         .split(inner);
@@ -78,7 +111,7 @@ pub fn render(frame: &mut Frame, app: &App) {
             Line::from(Span::styled(
                 l,
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme::accent())
                     .add_modifier(Modifier::BOLD),
             ))
         })
@@ -94,10 +127,7 @@ pub fn render(frame: &mut Frame, app: &App) {
     );
 
     // Menu
-    let items = vec![
-        ListItem::new("  Browse Files"),
-        ListItem::new("  Inspect Data File"),
-    ];
+    let items: Vec<ListItem> = MENU.iter().map(|item| ListItem::new(format!("  {}", item))).collect();
     let list = List::new(items)
         .highlight_style(
             Style::default()
@@ -106,7 +136,7 @@ pub fn render(frame: &mut Frame, app: &App) {
         )
         .highlight_symbol("> ");
     let mut state = ListState::default();
-    state.select(Some(app.home_selected));
+    state.select((app.home_selected < MENU.len()).then_some(app.home_selected));
 
     let list_width = 30u16.min(inner_chunks[5].width);
     let list_h_pad = inner_chunks[5].width.saturating_sub(list_width) / 2;
@@ -118,6 +148,30 @@ pub fn render(frame: &mut Frame, app: &App) {
     );
     frame.render_stateful_widget(list, list_area, &mut state);
 
+    if !aliases.is_empty() {
+        let area = inner_chunks[6];
+        let width = 50u16.min(area.width);
+        let datasets_area = Rect::new(area.x + area.width.saturating_sub(width) / 2, area.y + 1, width, area.height.saturating_sub(1));
+        let items: Vec<ListItem> = std::iter::once(ListItem::new(Span::styled(
+            "  Datasets",
+            Style::default().fg(Color::Gray).add_modifier(Modifier::BOLD),
+        )))
+        .chain(aliases.iter().map(|(name, target)| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("  {:<14}", name)),
+                Span::styled(target.as_str(), Style::default().fg(Color::DarkGray)),
+            ]))
+        }))
+        .collect();
+        let list = List::new(items)
+            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        let mut state = ListState::default();
+        // Row 0 is the heading
+        state.select(app.home_selected.checked_sub(MENU.len() - 1).filter(|&i| i >= 1));
+        frame.render_stateful_widget(list, datasets_area, &mut state);
+    }
+
     // Status bar
     status_bar::render(
         frame,
@@ -128,4 +182,5 @@ pub fn render(frame: &mut Frame, app: &App) {
             ("q", "quit"),
         ],
     );
+    popup::render(frame, app);
 }
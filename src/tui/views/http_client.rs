@@ -0,0 +1,113 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+
+use crate::tui::app::App;
+use crate::tui::http_client::{HttpClientState, HttpField, METHODS};
+use crate::tui::views::popup;
+use crate::tui::widgets::status_bar;
+use crate::tui::theme;
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let Some(state) = &app.http else { return };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(10),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(10), Constraint::Min(0)])
+        .split(chunks[0]);
+    render_method(frame, state, top[0]);
+    render_text(frame, state, HttpField::Url, " URL ", &state.url, top[1]);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(chunks[1]);
+    render_text(frame, state, HttpField::Headers, " Headers (Name: value per line) ", &state.headers, middle[0]);
+    render_text(frame, state, HttpField::Body, " Body ", &state.body, middle[1]);
+    render_history(frame, state, chunks[2]);
+
+    let mut bindings = vec![("Tab", "next field"), ("Ctrl+S", "send")];
+    match state.field {
+        HttpField::Method => bindings.push(("\u{2190}\u{2192}", "method")),
+        HttpField::History => bindings.extend([("\u{2191}\u{2193}", "select"), ("Enter", "load")]),
+        HttpField::Url => bindings.push(("Enter", "send")),
+        HttpField::Headers | HttpField::Body => {}
+    }
+    bindings.push(("Esc", "back"));
+    status_bar::render(frame, chunks[3], &bindings);
+    popup::render(frame, app);
+}
+
+fn block(title: &str, focused: bool) -> Block<'_> {
+    let color = if focused { theme::accent() } else { Color::DarkGray };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(title)
+        .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+}
+
+fn render_method(frame: &mut Frame, state: &HttpClientState, area: Rect) {
+    let focused = state.field == HttpField::Method;
+    let color = match state.method() {
+        "GET" => Color::Green,
+        "DELETE" => Color::Red,
+        _ => Color::Yellow,
+    };
+    let text = Span::styled(METHODS[state.method], Style::default().fg(color).add_modifier(Modifier::BOLD));
+    frame.render_widget(Paragraph::new(Line::from(text)).block(block(" Method ", focused)), area);
+}
+
+fn render_text(frame: &mut Frame, state: &HttpClientState, field: HttpField, title: &str, text: &str, area: Rect) {
+    let focused = state.field == field;
+    let text = if focused { format!("{}\u{2588}", text) } else { text.to_string() };
+    // Keep the end (where the cursor is) in view
+    let scroll = (text.lines().count() as u16).saturating_sub(area.height.saturating_sub(2));
+    let paragraph = Paragraph::new(text).block(block(title, focused)).wrap(Wrap { trim: false }).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_history(frame: &mut Frame, state: &HttpClientState, area: Rect) {
+    let focused = state.field == HttpField::History;
+    let outer = block(" History ", focused);
+    if state.history.is_empty() {
+        let empty = Paragraph::new("Requests you send are listed here").style(Style::default().fg(Color::Gray));
+        frame.render_widget(empty.block(outer), area);
+        return;
+    }
+    let items: Vec<ListItem> = state
+        .history
+        .iter()
+        .map(|entry| {
+            let status = match entry.status {
+                Some(s) if (200..300).contains(&s) => Span::styled(format!("{:>4}", s), Style::default().fg(Color::Green)),
+                Some(s) => Span::styled(format!("{:>4}", s), Style::default().fg(Color::Red)),
+                None => Span::styled("   -", Style::default().fg(Color::DarkGray)),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}  ", entry.time), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:<7}", entry.request.method), Style::default().add_modifier(Modifier::BOLD)),
+                status,
+                Span::raw(format!("  {}", entry.request.url)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(outer)
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    let mut list_state = ListState::default();
+    list_state.select(focused.then_some(state.history_selected));
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
@@ -2,38 +2,71 @@ use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, Tabs};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Tabs};
 
-use crate::commands::json_inspector::FileKind;
-use crate::tui::app::{App, GeoJsonTab, JsonInspectorTab};
+use crate::commands::pmtiles::TileCheck;
+use crate::commands::units::format_size;
+use crate::tui::app::{App, GeoJsonTab, JsonInspectorTab, TilesField, TilesState};
+use crate::tui::session::JsonInspectorState;
 use crate::tui::tree::{NodeKind, ScalarType};
-use crate::tui::widgets::status_bar;
+use crate::tui::views::{centered_rect, data_inspector, popup};
+use crate::tui::text;
+use crate::tui::widgets::frozen_table::{MAX_COL_WIDTH, MIN_COL_WIDTH};
+use crate::tui::widgets::{session_bar, status_bar};
+use crate::tui::theme;
 
 pub fn render(frame: &mut Frame, app: &App) {
+    let Some(state) = app.active_json() else {
+        return;
+    };
     let area = frame.area();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .constraints([
+            Constraint::Length(if app.sessions.len() > 1 { 1 } else { 0 }),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
         .split(area);
 
-    let main_area = chunks[0];
-    let status_area = chunks[1];
+    if app.sessions.len() > 1 {
+        session_bar::render(frame, chunks[0], app);
+    }
+
+    render_pane(frame, state, chunks[1], true);
+    status_bar::render(frame, chunks[2], &hints(app, state));
+    popup::render(frame, app);
+}
 
-    let filename = app
-        .json_file
-        .as_ref()
-        .and_then(|p| p.file_name())
+/// Renders the JSON/GeoJSON block, tabs and content for one session.
+/// Unfocused panes (in split view) get a dimmed border.
+pub fn render_pane(frame: &mut Frame, state: &JsonInspectorState, main_area: Rect, focused: bool) {
+    let filename = state
+        .file
+        .file_name()
         .map(|f| f.to_string_lossy().to_string())
         .unwrap_or_default();
 
-    let is_geojson = app.json_kind == Some(FileKind::GeoJson);
+    let filename = if state.partial { format!("{} \u{25cf} partial", filename) } else { filename };
+    let border_color = if focused { theme::accent() } else { Color::DarkGray };
 
-    let title = format!(" {} ", filename);
+    let skipped = state.tail.as_ref().map_or(0, |t| t.skipped);
+    let title = match (state.following, skipped) {
+        (true, 0) => format!(" {} \u{25cf} following ({} records) ", filename, state.features_data.len()),
+        (true, n) => format!(
+            " {} \u{25cf} following ({} records, {} invalid lines skipped) ",
+            filename,
+            state.features_data.len(),
+            n
+        ),
+        (false, 0) => format!(" {} ", filename),
+        (false, n) => format!(" {} ({} invalid lines skipped) ", filename, n),
+    };
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(border_color))
         .title(title)
-        .title_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+        .title_style(Style::default().fg(border_color).add_modifier(Modifier::BOLD));
 
     let inner = block.inner(main_area);
     frame.render_widget(block, main_area);
@@ -43,42 +76,72 @@ pub fn render(frame: &mut Frame, app: &App) {
         .constraints([Constraint::Length(2), Constraint::Min(0)])
         .split(inner);
 
-    if is_geojson {
-        render_geo_tabs(frame, app, inner_chunks[0]);
-        match app.geo_tab {
-            GeoJsonTab::Summary => render_geo_summary(frame, app, inner_chunks[1]),
-            GeoJsonTab::Features => render_features_table(frame, app, inner_chunks[1]),
-            GeoJsonTab::Tree => render_tree(frame, app, inner_chunks[1]),
+    if state.is_geojson() {
+        render_geo_tabs(frame, state, inner_chunks[0]);
+        match state.geo_tab {
+            GeoJsonTab::Summary => render_geo_summary(frame, state, inner_chunks[1]),
+            GeoJsonTab::Features => render_features_table(frame, state, inner_chunks[1]),
+            GeoJsonTab::Tree => render_tree(frame, state, inner_chunks[1]),
+            GeoJsonTab::Map => render_geo_map(frame, state, inner_chunks[1]),
         }
-        status_bar::render(frame, status_area, &[
-            ("Tab", "next tab"),
-            ("\u{2191}\u{2193}", "scroll"),
-            ("Enter", "expand/collapse"),
-            ("Esc", "back"),
-            ("q", "quit"),
-        ]);
     } else {
-        render_json_tabs(frame, app, inner_chunks[0]);
-        match app.json_tab {
-            JsonInspectorTab::Tree => render_tree(frame, app, inner_chunks[1]),
-            JsonInspectorTab::Raw => render_raw(frame, app, inner_chunks[1]),
+        render_json_tabs(frame, state, inner_chunks[0]);
+        match state.tab {
+            JsonInspectorTab::Records => render_features_table(frame, state, inner_chunks[1]),
+            JsonInspectorTab::Tree => render_tree(frame, state, inner_chunks[1]),
+            JsonInspectorTab::Raw => render_raw(frame, state, inner_chunks[1]),
+            JsonInspectorTab::Response => render_response(frame, state, inner_chunks[1]),
         }
-        status_bar::render(frame, status_area, &[
-            ("Tab", "switch"),
-            ("\u{2191}\u{2193}", "scroll"),
-            ("Enter", "expand/collapse"),
-            ("Esc", "back"),
-            ("q", "quit"),
-        ]);
     }
 }
 
-fn render_json_tabs(frame: &mut Frame, app: &App, area: Rect) {
-    let idx = match app.json_tab {
-        JsonInspectorTab::Tree => 0,
-        JsonInspectorTab::Raw => 1,
+/// Status bar key hints for the JSON inspector
+pub fn hints(app: &App, state: &JsonInspectorState) -> Vec<(&'static str, &'static str)> {
+    let mut hints: Vec<(&str, &str)> = if state.is_geojson() {
+        vec![("Tab", "next tab")]
+    } else {
+        vec![("Tab", "switch")]
     };
-    let tabs = Tabs::new(vec!["Tree", "Raw"])
+    hints.extend_from_slice(&[
+        ("\u{2191}\u{2193}", "scroll"),
+        ("Enter", "expand/collapse"),
+    ]);
+    if state.can_follow() {
+        hints.push(("f", if state.following { "stop following" } else { "follow" }));
+    }
+    if state.is_geojson() && state.geo_tab == GeoJsonTab::Features {
+        hints.extend_from_slice(&[("\u{2190}\u{2192}", "property"), ("d", "drop"), ("u", "dedupe"), ("w", "write")]);
+    }
+    if state.is_geojson() && state.file.extension().is_some_and(|e| e != "geojson") {
+        hints.push(("J", "to GeoJSON"));
+    }
+    if state.is_geojson() {
+        hints.push(("T", "tiles"));
+    }
+    if app.sessions.len() > 1 {
+        hints.push(("[/]", "tabs"));
+        hints.push(("|", "split"));
+    }
+    hints.extend_from_slice(&[
+        ("Esc", "back"),
+        ("q", "quit"),
+    ]);
+    hints
+}
+
+fn render_json_tabs(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let mut titles = if state.is_json_lines() { vec!["Records", "Tree", "Raw"] } else { vec!["Tree", "Raw"] };
+    if state.response.is_some() {
+        titles.push("Response");
+    }
+    let selected = match state.tab {
+        JsonInspectorTab::Records => "Records",
+        JsonInspectorTab::Tree => "Tree",
+        JsonInspectorTab::Raw => "Raw",
+        JsonInspectorTab::Response => "Response",
+    };
+    let idx = titles.iter().position(|t| *t == selected).unwrap_or(0);
+    let tabs = Tabs::new(titles)
         .select(idx)
         .style(Style::default().fg(Color::Gray))
         .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -86,13 +149,14 @@ fn render_json_tabs(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(tabs, area);
 }
 
-fn render_geo_tabs(frame: &mut Frame, app: &App, area: Rect) {
-    let idx = match app.geo_tab {
+fn render_geo_tabs(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let idx = match state.geo_tab {
         GeoJsonTab::Summary => 0,
         GeoJsonTab::Features => 1,
         GeoJsonTab::Tree => 2,
+        GeoJsonTab::Map => 3,
     };
-    let tabs = Tabs::new(vec!["Summary", "Features", "Tree"])
+    let tabs = Tabs::new(vec!["Summary", "Features", "Tree", "Map"])
         .select(idx)
         .style(Style::default().fg(Color::Gray))
         .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -100,9 +164,19 @@ fn render_geo_tabs(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(tabs, area);
 }
 
-fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
-    let nodes = &app.json_tree_nodes;
-    let scroll = app.json_scroll;
+fn render_geo_map(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    match &state.geo_map {
+        Some(map) if !map.points.is_empty() => data_inspector::render_map(frame, map, area),
+        _ => frame.render_widget(
+            Paragraph::new("No features with coordinates").style(Style::default().fg(Color::Gray)),
+            area,
+        ),
+    }
+}
+
+fn render_tree(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let nodes = &state.tree_nodes;
+    let scroll = state.scroll;
 
     let lines: Vec<Line> = nodes
         .iter()
@@ -149,10 +223,13 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                         ScalarType::Bool => Color::Green,
                         ScalarType::Null => Color::DarkGray,
                     };
+                    // Long values end in `…` at the edge, cut by display width
+                    let used = text::width(&indent) + 2 + text::width(&key_part);
+                    let val = text::truncate(val, (area.width as usize).saturating_sub(used));
                     Line::from(vec![
                         Span::raw(format!("{}  ", indent)).style(Style::default().bg(bg)),
                         Span::styled(key_part, Style::default().fg(Color::White).bg(bg)),
-                        Span::styled(val.clone(), Style::default().fg(val_color).bg(bg)),
+                        Span::styled(val, Style::default().fg(val_color).bg(bg)),
                     ])
                 }
             }
@@ -163,40 +240,78 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(para, area);
 }
 
-fn render_raw(frame: &mut Frame, app: &App, area: Rect) {
-    let lines: Vec<Line> = app
-        .json_raw
+fn render_raw(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let lines: Vec<Line> = state
+        .raw
         .lines()
-        .skip(app.json_scroll)
+        .skip(state.scroll)
         .map(|l| Line::from(l.to_string()))
         .collect();
     let para = Paragraph::new(lines).style(Style::default().fg(Color::Gray));
     frame.render_widget(para, area);
 }
 
-fn render_geo_summary(frame: &mut Frame, app: &App, area: Rect) {
-    let text = match &app.json_geosummary {
+/// Request line, status, timing and headers of the response the JSON came from
+fn render_response(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let Some(response) = &state.response else {
+        frame.render_widget(Paragraph::new("Not fetched over HTTP").style(Style::default().fg(Color::Gray)), area);
+        return;
+    };
+    let label = |text: &str| Span::styled(format!("  {:<9}", text), Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let status_color = if response.is_success() { Color::Green } else { Color::Red };
+    let size = std::fs::metadata(&state.file).map(|m| format_size(m.len())).unwrap_or_default();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![label("Request"), Span::raw(format!("{} {}", response.method, response.url))]),
+        Line::from(vec![
+            label("Status"),
+            Span::styled(response.status_line(), Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(vec![label("Time"), Span::raw(format!("{} ms", response.elapsed.as_millis()))]),
+        Line::from(vec![label("Body"), Span::raw(size)]),
+        Line::from(""),
+        Line::from(label("Headers")),
+    ];
+    let width = response.headers.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    lines.extend(response.headers.iter().skip(state.scroll).map(|(name, value)| {
+        Line::from(vec![
+            Span::styled(format!("    {:<width$}  ", name, width = width), Style::default().fg(Color::Gray)),
+            Span::raw(value.clone()),
+        ])
+    }));
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn render_geo_summary(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let text = match &state.geosummary {
         None => vec![Line::from("No GeoJSON summary available")],
         Some((count, types, bbox)) => {
             let mut lines = vec![
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("  Features:  ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                    Span::styled("  Features:  ", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
                     Span::raw(count.to_string()),
                 ]),
                 Line::from(vec![
-                    Span::styled("  Geometry:  ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                    Span::styled("  Geometry:  ", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
                     Span::raw(types.join(", ")),
                 ]),
             ];
             if let Some((min_lon, min_lat, max_lon, max_lat)) = bbox {
                 lines.push(Line::from(""));
                 lines.push(Line::from(
-                    Span::styled("  Bounding Box:", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                    Span::styled("  Bounding Box:", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
                 ));
                 lines.push(Line::from(format!("    Min lon/lat: {:.6}, {:.6}", min_lon, min_lat)));
                 lines.push(Line::from(format!("    Max lon/lat: {:.6}, {:.6}", max_lon, max_lat)));
             }
+            if let Some(response) = &state.response {
+                lines.push(Line::from(""));
+                lines.push(Line::from(vec![
+                    Span::styled("  Fetched:   ", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{} {} ({}, {} ms)", response.method, response.url, response.status_line(), response.elapsed.as_millis())),
+                ]));
+            }
             lines
         }
     };
@@ -204,27 +319,298 @@ fn render_geo_summary(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(para, area);
 }
 
-fn render_features_table(frame: &mut Frame, app: &App, area: Rect) {
-    if app.json_features_headers.is_empty() {
-        let msg = Paragraph::new("No features or no properties").style(Style::default().fg(Color::Gray));
+fn render_features_table(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    if state.features_headers.is_empty() {
+        let empty = if state.is_json_lines() { "No records yet" } else { "No features or no properties" };
+        let msg = Paragraph::new(empty).style(Style::default().fg(Color::Gray));
         frame.render_widget(msg, area);
         return;
     }
 
-    let header = Row::new(app.json_features_headers.clone())
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .bottom_margin(1);
+    // GeoJSON properties can be selected and marked to be dropped on export
+    let (area, summary) = if state.is_geojson() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        render_prune_summary(frame, state, chunks[1]);
+        (chunks[0], true)
+    } else {
+        (area, false)
+    };
 
-    let rows: Vec<Row> = app
-        .json_features_data
+    // While following, the newest record sits on the last line
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = if state.following {
+        state.features_data.len().saturating_sub(visible)
+    } else {
+        state.scroll
+    };
+    let shown = || state.features_data.iter().skip(skip).take(visible);
+    // Each column as wide as its widest value on screen, measured in terminal
+    // cells so CJK and emoji values stay aligned
+    let widths: Vec<usize> = state
+        .features_headers
         .iter()
-        .skip(app.json_scroll)
-        .map(|row| Row::new(row.clone()))
+        .enumerate()
+        .map(|(i, name)| {
+            shown()
+                .filter_map(|row| row.get(i))
+                .map(|v| text::width(v))
+                .chain([text::width(name)])
+                .max()
+                .unwrap_or(0)
+                .clamp(MIN_COL_WIDTH as usize, MAX_COL_WIDTH as usize)
+        })
         .collect();
 
-    let col_count = app.json_features_headers.len();
-    let widths: Vec<Constraint> = (0..col_count).map(|_| Constraint::Min(12)).collect();
+    let header = if summary {
+        let cells: Vec<Cell> = state
+            .features_headers
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut style = Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD);
+                if state.pruning.drop.contains(name) {
+                    style = style.fg(Color::Red).add_modifier(Modifier::CROSSED_OUT);
+                }
+                if i == state.features_col {
+                    style = style.bg(Color::DarkGray);
+                }
+                Cell::from(text::truncate(name, widths[i])).style(style)
+            })
+            .collect();
+        Row::new(cells).bottom_margin(1)
+    } else {
+        Row::new(state.features_headers.iter().zip(&widths).map(|(name, &width)| text::truncate(name, width)))
+            .style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            .bottom_margin(1)
+    };
+
+    let rows: Vec<Row> = shown()
+        .map(|row| Row::new(row.iter().zip(&widths).map(|(value, &width)| text::truncate(value, width))))
+        .collect();
 
-    let table = Table::new(rows, widths).header(header);
+    let constraints: Vec<Constraint> = widths.iter().map(|&w| Constraint::Length(w as u16)).collect();
+    let table = Table::new(rows, constraints).header(header);
     frame.render_widget(table, area);
 }
+
+/// Pruning options and the size they would save, under the Features table
+fn render_prune_summary(frame: &mut Frame, state: &JsonInspectorState, area: Rect) {
+    let line = match &state.prune_stats {
+        None => Line::from(Span::styled(
+            " d: drop property  u: dedupe geometries  w: write pruned copy",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Some(stats) => {
+            let mut parts = Vec::new();
+            if !state.pruning.drop.is_empty() {
+                parts.push(format!("drop {}", state.pruning.drop.join(", ")));
+            }
+            if state.pruning.dedupe {
+                parts.push("dedupe geometries".to_string());
+            }
+            Line::from(vec![
+                Span::styled(format!(" {} ", parts.join(" + ")), Style::default().fg(Color::Yellow)),
+                Span::raw(format!(
+                    "\u{2192} {} of {} features, {} \u{2192} {} (-{:.1}%)  ",
+                    stats.features_after,
+                    stats.features_before,
+                    format_size(stats.bytes_before as u64),
+                    format_size(stats.bytes_after as u64),
+                    stats.saved_percent(),
+                )),
+                Span::styled("w: write", Style::default().fg(Color::DarkGray)),
+            ])
+        }
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Preset and zoom range for tippecanoe, with the estimated tiles and size per zoom
+pub fn render_tiles_popup(frame: &mut Frame, state: &TilesState, area: Rect) {
+    let estimate = &state.estimate;
+    let saving = u16::from(state.naming.is_some() || state.error.is_some());
+    let height = (estimate.zooms.len() as u16 + estimate.warnings.len() as u16 + saving + 14)
+        .min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(72.min(area.width.saturating_sub(4)), height, area);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme::accent()))
+        .title(" PMTiles ")
+        .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6 + saving),
+            Constraint::Min(3),
+            Constraint::Length(estimate.warnings.len() as u16 + 2),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let label = |text: &str| Span::styled(format!(" {:<10}", text), Style::default().fg(Color::Gray));
+    let field = |value: String, field: TilesField| {
+        let style = if state.field == field {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        };
+        Span::styled(format!(" \u{25c2} {} \u{25b8} ", value), style)
+    };
+    let name = |p: &std::path::Path| p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let preset = &state.presets[state.preset];
+    let modified = preset.min_zoom != state.min_zoom || preset.max_zoom != state.max_zoom;
+    let options = if state.flags.is_empty() { "none".to_string() } else { state.flags.join(" ") };
+    let mut header = vec![
+        Line::from(vec![label("Input"), Span::raw(name(&state.input))]),
+        Line::from(vec![label("Output"), Span::raw(name(&state.output))]),
+        Line::from(vec![
+            label("Preset"),
+            field(preset.name.clone(), TilesField::Preset),
+            Span::styled(if modified { " (zooms changed)" } else { "" }, Style::default().fg(Color::Gray)),
+        ]),
+        Line::from(vec![label("Options"), Span::styled(options, Style::default().fg(Color::Gray))]),
+        Line::from(vec![label("Min zoom"), field(format!("{:>2}", state.min_zoom), TilesField::MinZoom)]),
+        Line::from(vec![label("Max zoom"), field(format!("{:>2}", state.max_zoom), TilesField::MaxZoom)]),
+    ];
+    if let Some(error) = &state.error {
+        header.push(Line::styled(format!(" {}", error), Style::default().fg(Color::Red)));
+    } else if let Some(typed) = &state.naming {
+        header.push(Line::from(vec![label("Save as"), Span::raw(format!("{}\u{2588}", typed))]));
+    }
+    frame.render_widget(Paragraph::new(header), chunks[0]);
+
+    let rows: Vec<Row> = estimate
+        .zooms
+        .iter()
+        .map(|z| Row::new(vec![z.zoom.to_string(), z.tiles.to_string(), format_size(z.bytes)]))
+        .chain(std::iter::once(
+            Row::new(vec!["Total".to_string(), estimate.tiles().to_string(), format_size(estimate.bytes())])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        ))
+        .collect();
+    let table_header = Row::new(vec!["Zoom", "Tiles", "Size"])
+        .style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let widths = [Constraint::Length(6), Constraint::Length(14), Constraint::Length(12)];
+    let table = Table::new(rows, widths).header(table_header).block(Block::default().borders(Borders::TOP).title(" Estimate "));
+    frame.render_widget(table, chunks[1]);
+
+    let mut notes = vec![Line::styled(
+        format!(" Sampled {} of {} features", estimate.sampled, estimate.features),
+        Style::default().fg(Color::Gray),
+    )];
+    if estimate.sampled == 0 {
+        notes.push(Line::styled(" No features with a geometry to estimate from", Style::default().fg(Color::Yellow)));
+    }
+    for warning in &estimate.warnings {
+        notes.push(Line::styled(format!(" \u{26a0} {}", warning), Style::default().fg(Color::Red)));
+    }
+    frame.render_widget(Paragraph::new(notes), chunks[2]);
+
+    let key = |k: &'static str| Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let keys = if state.naming.is_some() {
+        vec![key(" Enter "), Span::raw("save preset  "), key(" Esc "), Span::raw("cancel")]
+    } else {
+        vec![
+            key(" \u{2191}\u{2193} "),
+            Span::raw("field  "),
+            key(" \u{2190}\u{2192} "),
+            Span::raw("change  "),
+            key(" s "),
+            Span::raw("save preset  "),
+            key(" Enter "),
+            Span::raw("run tippecanoe  "),
+            key(" Esc "),
+            Span::raw("close"),
+        ]
+    };
+    frame.render_widget(Paragraph::new(Line::from(keys)), chunks[3]);
+}
+
+/// The tileset tippecanoe wrote, with the features dropped per layer
+pub fn render_tile_check_popup(frame: &mut Frame, check: &TileCheck, area: Rect) {
+    let height = (check.layers.len() as u16 + check.notes.len() as u16 + 10).min(area.height.saturating_sub(2));
+    let popup_area = centered_rect(76.min(area.width.saturating_sub(4)), height, area);
+    frame.render_widget(Clear, popup_area);
+
+    let color = if check.is_lossy() { Color::Yellow } else { Color::Green };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(" Tiles written ")
+        .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(2),
+            Constraint::Length(check.notes.len() as u16 + 1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let name = check.output.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let summary = vec![
+        Line::from(format!(" {} ({})", name, format_size(check.bytes))),
+        Line::styled(format!(" Zooms {}\u{2013}{}", check.min_zoom, check.max_zoom), Style::default().fg(Color::Gray)),
+    ];
+    frame.render_widget(Paragraph::new(summary), chunks[0]);
+
+    let count = |n: Option<u64>| n.map_or("?".to_string(), |n| n.to_string());
+    let rows: Vec<Row> = check
+        .layers
+        .iter()
+        .map(|layer| {
+            let dropped = match layer.dropped() {
+                Some(d) if layer.is_lossy() => Cell::from(format!("{:.1}%", d)).style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Some(d) if d > 0.0 => Cell::from(format!("{:.1}%", d)).style(Style::default().fg(Color::Yellow)),
+                Some(_) => Cell::from("none").style(Style::default().fg(Color::Green)),
+                None => Cell::from("?"),
+            };
+            Row::new(vec![
+                Cell::from(layer.layer.clone()),
+                Cell::from(layer.input.to_string()),
+                Cell::from(count(layer.read)),
+                Cell::from(count(layer.kept)),
+                dropped,
+            ])
+        })
+        .collect();
+    let kept = format!("At z{}", check.max_zoom);
+    let header = Row::new(vec!["Layer", "Input", "Read", kept.as_str(), "Dropped"])
+        .style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Min(12),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(9),
+    ];
+    frame.render_widget(Table::new(rows, widths).header(header), chunks[1]);
+
+    let mut notes: Vec<Line> = check
+        .notes
+        .iter()
+        .map(|n| Line::styled(format!(" {}", n), Style::default().fg(Color::Gray)))
+        .collect();
+    if check.is_lossy() {
+        notes.push(Line::styled(
+            " \u{26a0} Features were dropped: raise the max zoom or use a preset without dropping",
+            Style::default().fg(Color::Red),
+        ));
+    }
+    frame.render_widget(Paragraph::new(notes), chunks[2]);
+
+    let key = Span::styled(" Enter/Esc ", Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+    frame.render_widget(Paragraph::new(Line::from(vec![key, Span::raw("close")])), chunks[3]);
+}
@@ -1,7 +1,15 @@
+pub mod catalog;
 pub mod data_inspector;
+pub mod decode;
+pub mod env;
+pub mod error_log;
 pub mod file_browser;
 pub mod home;
+pub mod http_client;
 pub mod json_inspector;
+pub mod popup;
+pub mod scratchpad;
+pub mod split;
 
 use ratatui::layout::Rect;
 
@@ -0,0 +1,836 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::commands::table_format::TableFormat;
+use crate::tui::app::{App, Popup};
+use crate::tui::completion;
+use crate::tui::views::{centered_rect, data_inspector, json_inspector};
+use crate::tui::theme;
+use crate::tui::widgets::completion_list;
+
+/// Renders the active popup, if any, on top of the current screen
+pub fn render(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+    match &app.popup {
+        Popup::None => {}
+        Popup::ConvertConfirm { target_format, verify } => {
+            let popup_area = centered_rect(58, 8, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Convert ")
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let text = vec![
+                Line::from(""),
+                Line::from(format!("  Convert to {}?", target_format)),
+                Line::from(Span::styled(
+                    format!("  Verify output afterwards: {}", if *verify { "yes" } else { "no" }),
+                    Style::default().fg(Color::Gray),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(
+                        " t ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("transform  "),
+                    Span::styled(
+                        " v ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("verify  "),
+                    Span::styled(
+                        " Enter ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("confirm  "),
+                    Span::styled(
+                        " Esc ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("cancel"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::Message { title, body } => {
+            let width = (body.len() as u16 + 6)
+                .max(30)
+                .min(area.width.saturating_sub(4));
+            let popup_area = centered_rect(width, 7, area);
+            frame.render_widget(Clear, popup_area);
+
+            let color = if title.contains("Error") {
+                Color::Red
+            } else {
+                Color::Green
+            };
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color))
+                .title(format!(" {} ", title))
+                .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let text = vec![
+                Line::from(""),
+                Line::from(format!("  {}", body)),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(
+                        " Enter/Esc ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("close"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::Converted(state) => {
+            let verification = state.verification.as_ref();
+            let extra = verification.map_or(0, |(_, lines)| lines.len() as u16 + 1);
+            let body = format!("Converted to {}", state.path.display());
+            let width = (body.len() as u16 + 6).clamp(50, area.width.saturating_sub(4).max(50));
+            let popup_area = centered_rect(width, 9 + extra, area);
+            frame.render_widget(Clear, popup_area);
+
+            let (title, color) = match verification {
+                Some((true, _)) => ("Verification passed", Color::Green),
+                Some((false, _)) => ("Verification failed", Color::Red),
+                None => ("Success", Color::Green),
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color))
+                .title(format!(" {} ", title))
+                .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            let mut text = vec![Line::from(""), Line::from(format!("  {}", body))];
+            if let Some((_, lines)) = verification {
+                text.push(Line::from(""));
+                text.extend(lines.iter().map(|l| {
+                    let style = if l.starts_with('!') { Style::default().fg(Color::Red) } else { Style::default() };
+                    Line::from(Span::styled(format!("  {}", l), style))
+                }));
+            }
+            text.push(Line::from(""));
+            text.push(match &state.rename {
+                Some(name) => Line::from(vec![
+                    Span::styled("  Rename to: ", Style::default().fg(Color::Gray)),
+                    Span::raw(name.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ]),
+                None => Line::from(""),
+            });
+            if let Some(error) = &state.error {
+                text.push(Line::from(Span::styled(format!("  {}", error), Style::default().fg(Color::Red))));
+            } else {
+                text.push(Line::from(""));
+            }
+            text.push(if state.rename.is_some() {
+                Line::from(vec![key(" Enter "), Span::raw("rename  "), key(" Esc "), Span::raw("cancel")])
+            } else {
+                Line::from(vec![
+                    key(" o "),
+                    Span::raw("open result  "),
+                    key(" r "),
+                    Span::raw("rename  "),
+                    key(" u "),
+                    Span::raw("undo  "),
+                    key(" Enter/Esc "),
+                    Span::raw("close"),
+                ])
+            });
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::RestoreSession(saved) => {
+            let popup_area = centered_rect(50, 8, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Restore Session ")
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let text = vec![
+                Line::from(""),
+                Line::from("  Restore the previous session?"),
+                Line::from(Span::styled(
+                    format!("  {} open file(s) in {}", saved.files.len(), saved.current_dir.display()),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(
+                        " Enter ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("restore  "),
+                    Span::styled(
+                        " Esc ",
+                        Style::default()
+                            .fg(theme::accent())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("start fresh"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::CopyPage(state) => {
+            let popup_area = centered_rect(60, 10, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Copy Visible Rows ")
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let selected = Style::default().fg(Color::Black).bg(Color::Yellow);
+            let option = |label: &'static str, on: bool| {
+                Span::styled(format!(" {} ", label), if on { selected } else { Style::default() })
+            };
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+
+            let path_line = if state.to_file {
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::raw(state.path_input.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ])
+            } else {
+                Line::from("")
+            };
+
+            let text = vec![
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled("  Format:  ", Style::default().fg(Color::Gray)),
+                    option("CSV", state.format == TableFormat::Csv),
+                    Span::raw(" "),
+                    option("Markdown", state.format == TableFormat::Markdown),
+                ]),
+                Line::from(vec![
+                    Span::styled("  To:      ", Style::default().fg(Color::Gray)),
+                    option("Clipboard", !state.to_file),
+                    Span::raw(" "),
+                    option("File", state.to_file),
+                ]),
+                path_line,
+                Line::from(""),
+                Line::from(vec![
+                    key(" Tab "),
+                    Span::raw("format  "),
+                    key(" \u{2191}\u{2193} "),
+                    Span::raw("destination  "),
+                    key(" Enter "),
+                    Span::raw("copy  "),
+                    key(" Esc "),
+                    Span::raw("cancel"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::Scatter(plot) => data_inspector::render_scatter_popup(frame, plot, area),
+        Popup::RowGroups(state) => data_inspector::render_row_groups_popup(frame, state, area),
+        Popup::Map(map) => data_inspector::render_map_popup(frame, map, area),
+        Popup::Tiles(state) => json_inspector::render_tiles_popup(frame, state, area),
+        Popup::TileCheck(check) => json_inspector::render_tile_check_popup(frame, check, area),
+        Popup::Text { title, lines, scroll } => {
+            let width = 80_u16.min(area.width.saturating_sub(4));
+            let height = (lines.len() as u16 + 4).clamp(7, area.height.saturating_sub(2));
+            let popup_area = centered_rect(width, height, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::accent()))
+                .title(format!(" {} ", title))
+                .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(inner);
+
+            let text: Vec<Line> = lines
+                .iter()
+                .skip(*scroll)
+                .map(|l| {
+                    let style = if l.starts_with('!') || l.starts_with("Incompatible") {
+                        Style::default().fg(Color::Red)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(Span::styled(format!(" {}", l), style))
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(text), chunks[0]);
+
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled(
+                        " \u{2191}\u{2193} ",
+                        Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("scroll  "),
+                    Span::styled(
+                        " Enter/Esc ",
+                        Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("close"),
+                ])),
+                chunks[1],
+            );
+        }
+        Popup::SavePreset(state) => {
+            let popup_area = centered_rect(56, 9, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Save Filter Preset ")
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let selected = Style::default().fg(Color::Black).bg(Color::Yellow);
+            let option = |label: &'static str, on: bool| {
+                Span::styled(format!(" {} ", label), if on { selected } else { Style::default() })
+            };
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+
+            let text = vec![
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled("  Name:   ", Style::default().fg(Color::Gray)),
+                    Span::raw(state.name.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ]),
+                Line::from(vec![
+                    Span::styled("  Scope:  ", Style::default().fg(Color::Gray)),
+                    option("This file", !state.global),
+                    Span::raw(" "),
+                    option("All files", state.global),
+                ]),
+                Line::from(""),
+                Line::from(vec![
+                    key(" Tab "),
+                    Span::raw("scope  "),
+                    key(" Enter "),
+                    Span::raw("save  "),
+                    key(" Esc "),
+                    Span::raw("cancel"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::PresetPicker(state) => {
+            let height = (state.presets.len() as u16 + 5).clamp(7, area.height.saturating_sub(2));
+            let popup_area = centered_rect(60, height, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::accent()))
+                .title(" Filter Presets ")
+                .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(inner);
+
+            let lines: Vec<Line> = state
+                .presets
+                .iter()
+                .enumerate()
+                .map(|(i, preset)| {
+                    let scope = if preset.file.is_some() { "file" } else { "all files" };
+                    let style = if i == state.selected {
+                        Style::default().fg(Color::Black).bg(Color::Yellow)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(vec![
+                        Span::styled(format!(" {} ", preset.name), style),
+                        Span::styled(
+                            format!("  {} filter(s), {}", preset.filters.len(), scope),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ])
+                })
+                .collect();
+            frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    key(" Enter "),
+                    Span::raw("apply  "),
+                    key(" d "),
+                    Span::raw("delete  "),
+                    key(" Esc "),
+                    Span::raw("close"),
+                ])),
+                chunks[1],
+            );
+        }
+        Popup::ComputedColumn(state) => {
+            let popup_area = centered_rect(64, 10, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Add Computed Column ")
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let selected = Style::default().fg(Color::Black).bg(Color::Yellow);
+            let option = |label: &'static str, on: bool| {
+                Span::styled(format!(" {} ", label), if on { selected } else { Style::default() })
+            };
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+
+            let text = vec![
+                Line::from(Span::styled(
+                    "  SQL expression, e.g. price * qty AS total",
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::raw(state.input.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ]),
+                Line::from(vec![
+                    Span::styled("  Include in exports:  ", Style::default().fg(Color::Gray)),
+                    option("No", !state.export),
+                    Span::raw(" "),
+                    option("Yes", state.export),
+                ]),
+                Line::from(""),
+                Line::from(vec![
+                    key(" Tab "),
+                    Span::raw(if state.completion.is_empty() { "exports  " } else { "complete  " }),
+                    key(" Enter "),
+                    Span::raw("add  "),
+                    key(" Esc "),
+                    Span::raw("cancel"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+
+            // Suggestions open under the word being typed
+            let typed = state.input.len() - completion::current_word(&state.input).len();
+            let x = inner.x + 2 + Span::raw(&state.input[..typed]).width() as u16;
+            completion_list::render(frame, &state.completion, x, inner.y + 3, area);
+        }
+        Popup::ColumnType(state) => {
+            let popup_area = centered_rect(60, 9, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(format!(" Column type: {} ", state.name))
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            let text = vec![
+                Line::from(Span::styled(
+                    format!("  Currently {}; e.g. VARCHAR, BIGINT, DATE", state.current),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(Span::styled(
+                    "  Leave empty to use the inferred type",
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::raw(state.input.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ]),
+                Line::from(""),
+                Line::from(vec![
+                    key(" Enter "),
+                    Span::raw("apply  "),
+                    key(" Esc "),
+                    Span::raw("cancel"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::CsvOptions(state) => {
+            let popup_area = centered_rect(60, 8, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" CSV Options ")
+                .title_style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            let check = if state.decimal_comma { "[x]" } else { "[ ]" };
+            let text = vec![
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(format!("  {} ", check), Style::default().fg(Color::Yellow)),
+                    Span::raw("Decimal comma"),
+                ]),
+                Line::from(Span::styled(
+                    "      1.234,56 is read as 1234.56, in the file and in filters",
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    key(" Space "),
+                    Span::raw("toggle  "),
+                    key(" Enter "),
+                    Span::raw("apply  "),
+                    key(" Esc "),
+                    Span::raw("cancel"),
+                ]),
+            ];
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::History(state) => {
+            let width = 90_u16.min(area.width.saturating_sub(4));
+            let height = 20_u16.min(area.height.saturating_sub(2));
+            let popup_area = centered_rect(width, height, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::accent()))
+                .title(" Filter History ")
+                .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(2), Constraint::Min(1), Constraint::Length(1)])
+                .split(inner);
+
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled(" Search: ", Style::default().fg(Color::Gray)),
+                    Span::raw(state.query.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ])),
+                chunks[0],
+            );
+
+            let visible = state.visible();
+            let rows = chunks[1].height as usize;
+            // Keep the selection on screen
+            let skip = state.selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = if visible.is_empty() {
+                vec![Line::from(Span::styled(
+                    " No matching filters",
+                    Style::default().fg(Color::DarkGray),
+                ))]
+            } else {
+                visible
+                    .iter()
+                    .enumerate()
+                    .skip(skip)
+                    .map(|(i, entry)| {
+                        let name = entry
+                            .file
+                            .file_name()
+                            .map(|f| f.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        let style = if i == state.selected {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default()
+                        };
+                        Line::from(vec![
+                            Span::styled(format!(" {} ", entry.time), Style::default().fg(Color::DarkGray)),
+                            Span::styled(format!(" {} ", name), Style::default().fg(theme::accent())),
+                            Span::styled(format!(" {} ", entry.summary()), style),
+                        ])
+                    })
+                    .collect()
+            };
+            frame.render_widget(Paragraph::new(lines), chunks[1]);
+
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    key(" type "),
+                    Span::raw("search  "),
+                    key(" \u{2191}\u{2193} "),
+                    Span::raw("select  "),
+                    key(" Enter "),
+                    Span::raw("apply to this file  "),
+                    key(" Esc "),
+                    Span::raw("close"),
+                ])),
+                chunks[2],
+            );
+        }
+        Popup::Palette(state) => {
+            let width = 60_u16.min(area.width.saturating_sub(4));
+            let height = 18_u16.min(area.height.saturating_sub(2));
+            let popup_area = centered_rect(width, height, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme::accent()))
+                .title(" Commands ")
+                .title_style(Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(2), Constraint::Min(1), Constraint::Length(1)])
+                .split(inner);
+
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled(" > ", Style::default().fg(Color::Gray)),
+                    Span::raw(state.query.clone()),
+                    Span::styled("\u{2588}", Style::default().fg(Color::Gray)),
+                ])),
+                chunks[0],
+            );
+
+            let visible = state.visible();
+            let rows = chunks[1].height as usize;
+            // Keep the selection on screen
+            let skip = state.selected.saturating_sub(rows.saturating_sub(1));
+            let lines: Vec<Line> = if visible.is_empty() {
+                vec![Line::from(Span::styled(" No matching commands", Style::default().fg(Color::DarkGray)))]
+            } else {
+                visible
+                    .iter()
+                    .enumerate()
+                    .skip(skip)
+                    .map(|(i, command)| {
+                        let style = if i == state.selected {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default()
+                        };
+                        Line::from(Span::styled(format!(" {} ", command.label()), style))
+                    })
+                    .collect()
+            };
+            frame.render_widget(Paragraph::new(lines), chunks[1]);
+
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    key(" type "),
+                    Span::raw("search  "),
+                    key(" \u{2191}\u{2193} "),
+                    Span::raw("select  "),
+                    key(" Enter "),
+                    Span::raw("run  "),
+                    key(" Esc "),
+                    Span::raw("close"),
+                ])),
+                chunks[2],
+            );
+        }
+        Popup::LargeFile(state) => {
+            let popup_area = centered_rect(64, state.modes.len() as u16 + 7, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title(" Large File ")
+                .title_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let name = state.path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+            let mut text = vec![
+                Line::from(format!("  {} is large: {}", name, state.describe())),
+                Line::from(Span::styled(
+                    "  Loading it whole may freeze ftool or run out of memory.",
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(""),
+            ];
+            text.extend(state.modes.iter().enumerate().map(|(i, mode)| {
+                let style = if i == state.selected {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                Line::from(Span::styled(format!(" {} ", mode.label(state.is_csv())), style))
+            }));
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            text.push(Line::from(""));
+            text.push(Line::from(vec![
+                key(" \u{2191}\u{2193} "),
+                Span::raw("select  "),
+                key(" Enter "),
+                Span::raw("open  "),
+                key(" Esc "),
+                Span::raw("cancel"),
+            ]));
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::ConfirmDelete(paths) => {
+            let shown = paths.len().min(5);
+            let more = paths.len() > shown;
+            let popup_area = centered_rect(64, shown as u16 + more as u16 + 7, area);
+            frame.render_widget(Clear, popup_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(" Delete ")
+                .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let question = if crate::config::get().is_permanent_delete() {
+                "  Delete for good? This can't be undone."
+            } else {
+                "  Move to the trash? u in the browser brings it back."
+            };
+            let mut text = vec![Line::from(question), Line::from("")];
+            text.extend(paths.iter().take(shown).map(|path| {
+                let name = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+                let name = if path.is_dir() { format!("{}/", name) } else { name };
+                Line::from(Span::styled(format!("  {}", name), Style::default().fg(Color::Gray)))
+            }));
+            if more {
+                text.push(Line::from(Span::styled(
+                    format!("  …and {} more", paths.len() - shown),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            let key = |k: &'static str| {
+                Span::styled(k, Style::default().fg(theme::accent()).add_modifier(Modifier::BOLD))
+            };
+            text.push(Line::from(""));
+            text.push(Line::from(vec![key(" Enter "), Span::raw("delete  "), key(" Esc "), Span::raw("cancel")]));
+            frame.render_widget(Paragraph::new(text), inner);
+        }
+        Popup::TransformEditor(state) => {
+            data_inspector::render_transform_popup(frame, state, area);
+        }
+        Popup::FilterEditor(editor) => {
+            if let Some(state) = app.active_data() {
+                data_inspector::render_filter_popup(frame, state, editor, area);
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+
+use crate::commands::geometry::{Geometry, GeometryFormat};
+use crate::tui::app::App;
+use crate::tui::scratchpad::ScratchpadState;
+use crate::tui::text;
+use crate::tui::views::{data_inspector, popup};
+use crate::tui::widgets::status_bar;
+use crate::tui::theme;
+
+pub fn render(frame: &mut Frame, app: &App) {
+    let state = &app.scratchpad;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),
+            Constraint::Min(8),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    render_input(frame, state, chunks[0]);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(42), Constraint::Min(0)])
+        .split(chunks[1]);
+    render_info(frame, state, middle[0]);
+    render_map(frame, state, middle[1]);
+    render_output(frame, state, chunks[2]);
+
+    status_bar::render(
+        frame,
+        chunks[3],
+        &[
+            ("Ctrl+V", "paste"),
+            ("Tab", "output format"),
+            ("Ctrl+Y", "copy output"),
+            ("Ctrl+U", "clear"),
+            ("Esc", "back"),
+        ],
+    );
+    popup::render(frame, app);
+}
+
+fn block(title: String, color: Color) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .title(title)
+        .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+}
+
+fn render_input(frame: &mut Frame, state: &ScratchpadState, area: Rect) {
+    let block = block(" Geometry: paste or type WKT, hex WKB or GeoJSON ".to_string(), theme::accent());
+    let inner_width = area.width.saturating_sub(2).max(1) as usize;
+    let input = format!("{}\u{2588}", state.input);
+    // Keep the end of a long input (where the cursor is) in view
+    let lines = input.lines().map(|l| text::width(l) / inner_width + 1).sum::<usize>();
+    let scroll = lines.saturating_sub(area.height.saturating_sub(2) as usize) as u16;
+    let paragraph = Paragraph::new(input).block(block).wrap(Wrap { trim: false }).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn render_info(frame: &mut Frame, state: &ScratchpadState, area: Rect) {
+    let label = |text: &str| Span::styled(format!("{:<10}", text), Style::default().fg(Color::Gray));
+    let lines: Vec<Line> = match &state.parsed {
+        None => vec![Line::styled("Nothing to parse yet", Style::default().fg(Color::Gray))],
+        Some(Err(e)) => vec![
+            Line::styled("Could not parse the input", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Line::from(""),
+            Line::styled(e.clone(), Style::default().fg(Color::Red)),
+        ],
+        Some(Ok((geometry, source))) => {
+            let vertices = geometry.vertices().len();
+            let mut lines = vec![
+                Line::from(vec![label("Read as"), Span::raw(source.label())]),
+                Line::from(vec![
+                    label("Type"),
+                    Span::styled(geometry.type_name(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                    Span::raw(if geometry.has_z() { " (Z)" } else { "" }),
+                ]),
+                Line::from(vec![label("Vertices"), Span::raw(vertices.to_string())]),
+            ];
+            if let Geometry::GeometryCollection(members) = geometry {
+                lines.push(Line::from(vec![label("Members"), Span::raw(members.len().to_string())]));
+            }
+            match geometry.bbox() {
+                Some((min_x, min_y, max_x, max_y)) => {
+                    lines.push(Line::from(vec![label("Min x/y"), Span::raw(format!("{} {}", min_x, min_y))]));
+                    lines.push(Line::from(vec![label("Max x/y"), Span::raw(format!("{} {}", max_x, max_y))]));
+                }
+                None => lines.push(Line::from(vec![label("Bbox"), Span::raw("empty")])),
+            }
+            lines
+        }
+    };
+    let paragraph = Paragraph::new(lines).block(block(" Parsed ".to_string(), Color::DarkGray)).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_map(frame: &mut Frame, state: &ScratchpadState, area: Rect) {
+    let outer = block(" Map ".to_string(), Color::DarkGray);
+    let inner = outer.inner(area);
+    frame.render_widget(outer, area);
+    match &state.map {
+        Some(map) if !map.points.is_empty() => data_inspector::render_map(frame, map, inner),
+        _ => frame.render_widget(Paragraph::new("No vertices to draw").style(Style::default().fg(Color::Gray)), inner),
+    }
+}
+
+fn render_output(frame: &mut Frame, state: &ScratchpadState, area: Rect) {
+    let mut title = vec![Span::raw(" Output:")];
+    for (i, format) in GeometryFormat::ALL.iter().enumerate() {
+        let style = if *format == state.output {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        if i > 0 {
+            title.push(Span::raw("|"));
+        }
+        title.push(Span::styled(format!(" {} ", format.label()), style));
+    }
+    let title = Line::from(title);
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(title);
+    let text = state.output_text().unwrap_or_default();
+    frame.render_widget(Paragraph::new(text).block(outer).wrap(Wrap { trim: false }), area);
+}
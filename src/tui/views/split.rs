@@ -0,0 +1,50 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+use crate::tui::app::App;
+use crate::tui::session::Session;
+use crate::tui::views::{data_inspector, json_inspector, popup};
+use crate::tui::widgets::{session_bar, status_bar};
+
+/// Side-by-side view of two inspector sessions. Key input goes to the focused
+/// (active) session; `Ctrl-W` moves focus to the other pane.
+pub fn render(frame: &mut Frame, app: &App) {
+    let Some(split) = app.split else {
+        return;
+    };
+    let area = frame.area();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    session_bar::render(frame, chunks[0], app);
+
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    render_session(frame, app, split.left, panes[0]);
+    render_session(frame, app, split.right, panes[1]);
+
+    let mut hints = match app.active() {
+        Some(Session::Data(s)) => data_inspector::hints(app, s),
+        Some(Session::Json(s)) => json_inspector::hints(app, s),
+        None => Vec::new(),
+    };
+    hints.insert(0, ("^W", "Focus"));
+    status_bar::render(frame, chunks[2], &hints);
+
+    popup::render(frame, app);
+}
+
+fn render_session(frame: &mut Frame, app: &App, idx: usize, area: Rect) {
+    let focused = idx == app.active_session;
+    match app.sessions.get(idx) {
+        Some(Session::Data(s)) => data_inspector::render_pane(frame, s, area, focused),
+        Some(Session::Json(s)) => json_inspector::render_pane(frame, s, area, focused),
+        None => {}
+    }
+}
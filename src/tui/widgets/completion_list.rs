@@ -0,0 +1,49 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::tui::completion::Completion;
+use crate::tui::text;
+
+/// Suggestions drawn at once; the list scrolls to keep the selection visible
+const VISIBLE: usize = 6;
+const WIDTH: u16 = 32;
+
+/// Draws the suggestion list with its top-left corner at (`x`, `y`), kept
+/// inside `bounds`; draws nothing when there are no suggestions
+pub fn render(frame: &mut Frame, completion: &Completion, x: u16, y: u16, bounds: Rect) {
+    if completion.is_empty() {
+        return;
+    }
+    let height = completion.items.len().min(VISIBLE) as u16 + 2;
+    let width = WIDTH.min(bounds.width);
+    let x = x.min(bounds.right().saturating_sub(width));
+    let y = y.min(bounds.bottom().saturating_sub(height));
+    let area = Rect::new(x, y, width, height).intersection(bounds);
+    frame.render_widget(Clear, area);
+
+    let first = (completion.selected + 1).saturating_sub(VISIBLE);
+    let lines: Vec<Line> = completion
+        .items
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(VISIBLE)
+        .map(|(i, item)| {
+            let style = if i == completion.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::styled(format!(" {}", text::pad(item, width as usize - 3)), style)
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(format!(" {}/{} ", completion.selected + 1, completion.items.len()));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
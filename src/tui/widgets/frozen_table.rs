@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::tui::text;
+
+/// Widest a column is drawn, so one long value can't push the rest off screen
+pub const MAX_COL_WIDTH: u16 = 40;
+pub const MIN_COL_WIDTH: u16 = 4;
+
+/// A table whose header row stays on top and whose frozen column stays on the
+/// left; the other columns scroll horizontally so the selected one is visible.
+pub struct FrozenTable<'a> {
+    headers: &'a [String],
+    rows: &'a [Vec<Option<String>>],
+    /// Rows skipped from the top (vertical scroll)
+    skip: usize,
+    frozen: Option<usize>,
+    selected: usize,
+    /// Draw the selected column at its full content width instead of `MAX_COL_WIDTH`
+    expand_selected: bool,
+    /// Widths set by hand, by header, used instead of the content width
+    col_widths: Option<&'a BTreeMap<String, u16>>,
+    header_style: Style,
+    selected_header_style: Style,
+    selected_style: Style,
+}
+
+impl<'a> FrozenTable<'a> {
+    pub fn new(headers: &'a [String], rows: &'a [Vec<Option<String>>]) -> Self {
+        Self {
+            headers,
+            rows,
+            skip: 0,
+            frozen: None,
+            selected: 0,
+            expand_selected: false,
+            col_widths: None,
+            header_style: Style::default(),
+            selected_header_style: Style::default(),
+            selected_style: Style::default(),
+        }
+    }
+
+    pub fn skip(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    /// Keeps column `frozen` drawn at the left edge
+    pub fn frozen(mut self, frozen: Option<usize>) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    pub fn selected(mut self, selected: usize) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    pub fn expand_selected(mut self, expand: bool) -> Self {
+        self.expand_selected = expand;
+        self
+    }
+
+    pub fn col_widths(mut self, widths: &'a BTreeMap<String, u16>) -> Self {
+        self.col_widths = Some(widths);
+        self
+    }
+
+    pub fn header_style(mut self, normal: Style, selected: Style) -> Self {
+        self.header_style = normal;
+        self.selected_header_style = selected;
+        self
+    }
+
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    /// Width of each column: its content width over the header and the rows
+    /// visible in `area`, unless it was set by hand. An expanded column is at
+    /// most as wide as `area`.
+    fn widths(&self, area: Rect) -> Vec<u16> {
+        let height = area.height as usize;
+        self.headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                let expanded = self.expand_selected && i == self.selected;
+                if !expanded && let Some(&width) = self.col_widths.and_then(|w| w.get(header)) {
+                    return width;
+                }
+                let cells = self
+                    .rows
+                    .iter()
+                    .skip(self.skip)
+                    .take(height)
+                    .filter_map(|row| row.get(i))
+                    .map(|v| text::width(display(v.as_deref())));
+                let widest = cells.chain([text::width(header)]).max().unwrap_or(0);
+                let max = if expanded { area.width.max(MIN_COL_WIDTH) } else { MAX_COL_WIDTH };
+                (widest.min(u16::MAX as usize) as u16).clamp(MIN_COL_WIDTH, max)
+            })
+            .collect()
+    }
+
+    /// The scrolling columns to draw: from the first one that still leaves the
+    /// selected column inside `available` columns of space
+    fn scrolled_columns(&self, widths: &[u16], available: u16) -> Vec<usize> {
+        let columns: Vec<usize> = (0..self.headers.len()).filter(|&i| Some(i) != self.frozen).collect();
+        let selected_pos = columns.iter().position(|&i| i == self.selected).unwrap_or(0);
+        let mut first = 0;
+        while first < selected_pos
+            && columns[first..=selected_pos].iter().fold(0u16, |sum, &i| sum.saturating_add(widths[i].saturating_add(1)))
+                > available
+        {
+            first += 1;
+        }
+        columns[first..].to_vec()
+    }
+
+    fn draw_column(&self, buf: &mut Buffer, area: Rect, x: u16, width: u16, col: usize) {
+        let header_style = if col == self.selected { self.selected_header_style } else { self.header_style };
+        set_cell(buf, x, area.y, &self.headers[col], width, header_style);
+
+        // One blank line between the header and the data, like the Schema tab
+        let body_top = area.y + 2;
+        for (line, row) in self.rows.iter().skip(self.skip).enumerate() {
+            let y = body_top + line as u16;
+            if y >= area.bottom() {
+                break;
+            }
+            let Some(value) = row.get(col) else {
+                continue;
+            };
+            let mut style = Style::default();
+            if col == self.selected {
+                buf.set_style(Rect::new(x, y, width, 1), self.selected_style);
+                style = self.selected_style;
+            }
+            // NULLs and empty strings are placeholders, not data: draw them dimmed
+            if value.as_deref().is_none_or(str::is_empty) {
+                style = style.fg(Color::DarkGray).add_modifier(Modifier::DIM);
+            }
+            set_cell(buf, x, y, display(value.as_deref()), width, style);
+        }
+    }
+}
+
+/// Text drawn for a cell: `∅` for NULL and `""` for an empty string, so neither
+/// is confused with the text "NULL" or a blank value
+fn display(value: Option<&str>) -> &str {
+    match value {
+        None => "\u{2205}",
+        Some("") => "\"\"",
+        Some(v) => v,
+    }
+}
+
+/// Writes `value` in `width` cells, ending in a dimmed `…` when it doesn't fit.
+/// Cut by display width, so CJK and emoji cells stay aligned.
+fn set_cell(buf: &mut Buffer, x: u16, y: u16, value: &str, width: u16, style: Style) {
+    if width == 0 {
+        return;
+    }
+    if text::width(value) <= width as usize {
+        buf.set_string(x, y, value, style);
+        return;
+    }
+    // Padded, as a wide character that didn't fit leaves a cell before the `…`
+    buf.set_string(x, y, text::pad(text::fit(value, width as usize - 1), width as usize - 1), style);
+    buf.set_string(x + width - 1, y, "\u{2026}", style.fg(Color::DarkGray));
+}
+
+impl FrozenTable<'_> {
+    /// The columns drawn in `area` as (column, x, width), the frozen one first;
+    /// the frozen column's separator sits one cell after it
+    fn layout(&self, area: Rect) -> Vec<(usize, u16, u16)> {
+        let mut columns = Vec::new();
+        if area.height == 0 || self.headers.is_empty() {
+            return columns;
+        }
+        let widths = self.widths(area);
+        let mut x = area.x;
+
+        if let Some(col) = self.frozen.filter(|&c| c < self.headers.len() && area.width > 3) {
+            let width = widths[col].min(area.width - 3);
+            columns.push((col, x, width));
+            x += width + 3;
+        }
+
+        let available = area.right().saturating_sub(x);
+        for col in self.scrolled_columns(&widths, available) {
+            if x >= area.right() {
+                break;
+            }
+            let width = widths[col].min(area.right() - x);
+            columns.push((col, x, width));
+            x += width + 1;
+        }
+        columns
+    }
+
+    /// Width column `col` is drawn at in `area`, before it is cut to fit
+    pub fn column_width(&self, area: Rect, col: usize) -> Option<u16> {
+        self.widths(area).get(col).copied()
+    }
+
+    /// The (row, column) of the data cell drawn at `x`, `y` when the table is
+    /// rendered in `area`, for mouse clicks
+    pub fn cell_at(&self, area: Rect, x: u16, y: u16) -> Option<(usize, usize)> {
+        // Data starts two lines down: the header, then a blank line
+        let line = y.checked_sub(area.y + 2)? as usize;
+        if y >= area.bottom() {
+            return None;
+        }
+        let row = self.skip + line;
+        if row >= self.rows.len() {
+            return None;
+        }
+        let (col, _, _) = self.layout(area).into_iter().find(|(_, left, width)| x >= *left && x < left + width)?;
+        Some((row, col))
+    }
+}
+
+impl Widget for FrozenTable<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (i, (col, x, width)) in self.layout(area).into_iter().enumerate() {
+            self.draw_column(buf, area, x, width, col);
+            if i == 0 && self.frozen == Some(col) {
+                for y in area.y..area.bottom() {
+                    buf.set_string(x + width + 1, y, "\u{2502}", Style::default().fg(Color::DarkGray));
+                }
+            }
+        }
+    }
+}
@@ -1 +1,5 @@
+pub mod completion_list;
+pub mod frozen_table;
+pub mod session_bar;
+pub mod spinner;
 pub mod status_bar;
@@ -0,0 +1,26 @@
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+
+use crate::tui::app::App;
+
+/// One-line bar listing open inspector tabs, with the active one highlighted
+pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    let mut spans: Vec<Span> = Vec::new();
+    for (i, session) in app.sessions.iter().enumerate() {
+        let style = if i == app.active_session {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        spans.push(Span::styled(format!(" {}:{} ", i + 1, session.label()), style));
+        spans.push(Span::raw(" "));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
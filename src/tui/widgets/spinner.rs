@@ -0,0 +1,23 @@
+use ratatui::Frame;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Paragraph;
+
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Right-aligned "⠋ label" indicator drawn over the status bar while a background task runs
+pub fn render(frame: &mut Frame, label: &str, tick: usize) {
+    let area = frame.area();
+    if area.height == 0 {
+        return;
+    }
+    let text = format!(" {} {} ", FRAMES[tick % FRAMES.len()], label);
+    let width = (text.chars().count() as u16).min(area.width);
+    let spinner_area = Rect::new(area.x + area.width - width, area.y + area.height - 1, width, 1);
+    frame.render_widget(
+        Paragraph::new(text)
+            .style(Style::default().fg(Color::Yellow).bg(Color::DarkGray))
+            .alignment(Alignment::Right),
+        spinner_area,
+    );
+}
@@ -1,5 +1,5 @@
 use ratatui::Frame;
-use ratatui::layout::Rect;
+use ratatui::layout::{Alignment, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
@@ -25,3 +25,21 @@ pub fn render(frame: &mut Frame, area: Rect, bindings: &[(&str, &str)]) {
         .style(Style::default().bg(Color::DarkGray).fg(Color::White));
     frame.render_widget(bar, area);
 }
+
+/// Right-aligned label drawn over the end of the status bar, such as the
+/// macro recording mark
+pub fn render_indicator(frame: &mut Frame, label: &str) {
+    let area = frame.area();
+    if area.height == 0 {
+        return;
+    }
+    let text = format!(" \u{25cf} {} ", label);
+    let width = (text.chars().count() as u16).min(area.width);
+    let indicator_area = Rect::new(area.x + area.width - width, area.y + area.height - 1, width, 1);
+    frame.render_widget(
+        Paragraph::new(text)
+            .style(Style::default().fg(Color::Red).bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Right),
+        indicator_area,
+    );
+}